@@ -0,0 +1,84 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Genesis configuration of the bridge hub template runtime.
+//!
+//! The node that is built around this runtime may wrap [`development_genesis`] or
+//! [`testnet_genesis`] into its own `ChainSpec` type.
+
+use crate::{
+	AccountId, BalancesConfig, BridgeMillauGrandpaConfig, GenesisConfig, Signature, SystemConfig, WASM_BINARY,
+};
+
+use bp_header_chain::InitializationData;
+use sp_core::{sr25519, Pair, Public};
+use sp_runtime::traits::{IdentifyAccount, Verify};
+
+type AccountPublic = <Signature as Verify>::Signer;
+
+/// Helper function to generate a crypto pair from seed
+pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
+	TPublic::Pair::from_string(&format!("//{}", seed), None)
+		.expect("static values are valid; qed")
+		.public()
+}
+
+/// Helper function to generate an account ID from seed
+pub fn get_account_id_from_seed<TPublic: Public>(seed: &str) -> AccountId
+where
+	AccountPublic: From<<TPublic::Pair as Pair>::Public>,
+{
+	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
+}
+
+/// Genesis configuration of the development chain.
+///
+/// Alice and Bob are endowed, Alice owns the Millau bridge. The bridge is not initialized at
+/// genesis, so the owner must call `initialize` before any Millau header may be imported.
+pub fn development_genesis() -> GenesisConfig {
+	testnet_genesis(
+		vec![
+			get_account_id_from_seed::<sr25519::Public>("Alice"),
+			get_account_id_from_seed::<sr25519::Public>("Bob"),
+			pallet_bridge_messages::Pallet::<crate::Runtime, crate::WithMillauMessagesInstance>::relayer_fund_account_id(
+			),
+		],
+		Some(get_account_id_from_seed::<sr25519::Public>("Alice")),
+		None,
+	)
+}
+
+/// Genesis configuration with given endowed accounts, Millau bridge owner and (optional)
+/// Millau bridge initialization data.
+pub fn testnet_genesis(
+	endowed_accounts: Vec<AccountId>,
+	bridge_owner: Option<AccountId>,
+	millau_init_data: Option<InitializationData<bp_millau::Header>>,
+) -> GenesisConfig {
+	GenesisConfig {
+		frame_system: SystemConfig {
+			code: WASM_BINARY.expect("Bridge Hub development WASM not available").to_vec(),
+			changes_trie_config: Default::default(),
+		},
+		pallet_balances: BalancesConfig {
+			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 50)).collect(),
+		},
+		pallet_bridge_grandpa: BridgeMillauGrandpaConfig {
+			owner: bridge_owner,
+			init_data: millau_init_data,
+		},
+	}
+}
@@ -0,0 +1,267 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Everything required to serve Bridge Hub <-> Millau messages.
+
+use crate::Runtime;
+
+use bp_messages::{
+	source_chain::TargetHeaderChain,
+	target_chain::{ProvedMessages, SourceHeaderChain},
+	InboundLaneData, LaneId, Message, MessageNonce, Parameter as MessagesParameter,
+};
+use bp_runtime::{ChainId, MILLAU_CHAIN_ID};
+use bridge_runtime_common::messages::{self, MessageBridge, MessageTransaction};
+use codec::{Decode, Encode};
+use frame_support::{
+	parameter_types,
+	weights::{DispatchClass, Weight},
+	RuntimeDebug,
+};
+use sp_runtime::{traits::Zero, FixedPointNumber, FixedU128};
+use sp_std::{convert::TryFrom, ops::RangeInclusive};
+
+/// Initial value of `MillauToBridgeHubConversionRate` parameter.
+pub const INITIAL_MILLAU_TO_BRIDGE_HUB_CONVERSION_RATE: FixedU128 = FixedU128::from_inner(FixedU128::DIV);
+
+parameter_types! {
+	/// Millau to Bridge Hub conversion rate. Initially we treat both tokens as equal.
+	pub storage MillauToBridgeHubConversionRate: FixedU128 = INITIAL_MILLAU_TO_BRIDGE_HUB_CONVERSION_RATE;
+	/// Maximal size of the message that may be sent to Millau.
+	pub MaximalMessageSizeToMillau: u32 = messages::source::maximal_message_size::<WithMillauMessageBridge>();
+	/// Maximal dispatch weight of the message that may be sent to Millau.
+	pub MaximalMessageDispatchWeightToMillau: Weight =
+		messages::target::maximal_incoming_message_dispatch_weight(bp_millau::max_extrinsic_weight());
+}
+
+/// Message payload for Bridge Hub -> Millau messages.
+pub type ToMillauMessagePayload = messages::source::FromThisChainMessagePayload<WithMillauMessageBridge>;
+
+/// Message verifier for Bridge Hub -> Millau messages.
+pub type ToMillauMessageVerifier = messages::source::FromThisChainMessageVerifier<WithMillauMessageBridge>;
+
+/// Message payload for Millau -> Bridge Hub messages.
+pub type FromMillauMessagePayload = messages::target::FromBridgedChainMessagePayload<WithMillauMessageBridge>;
+
+/// Encoded Bridge Hub Call as it comes from Millau.
+pub type FromMillauEncodedCall = messages::target::FromBridgedChainEncodedMessageCall<WithMillauMessageBridge>;
+
+/// Messages proof for Millau -> Bridge Hub messages.
+pub type FromMillauMessagesProof = messages::target::FromBridgedChainMessagesProof<bp_millau::Hash>;
+
+/// Messages delivery proof for Bridge Hub -> Millau messages.
+pub type ToMillauMessagesDeliveryProof = messages::source::FromBridgedChainMessagesDeliveryProof<bp_millau::Hash>;
+
+/// Call-dispatch based message dispatch for Millau -> Bridge Hub messages.
+pub type FromMillauMessageDispatch = messages::target::FromBridgedChainMessageDispatch<
+	WithMillauMessageBridge,
+	crate::Runtime,
+	pallet_balances::Pallet<crate::Runtime>,
+	pallet_bridge_dispatch::DefaultInstance,
+>;
+
+/// Bridge Hub <-> Millau message bridge.
+#[derive(RuntimeDebug, Clone, Copy)]
+pub struct WithMillauMessageBridge;
+
+impl MessageBridge for WithMillauMessageBridge {
+	const RELAYER_FEE_PERCENT: u32 = 10;
+
+	type ThisChain = BridgeHub;
+	type BridgedChain = Millau;
+
+	fn bridged_balance_to_this_balance(bridged_balance: bp_millau::Balance) -> crate::Balance {
+		MillauToBridgeHubConversionRate::get().saturating_mul_int(crate::Balance::from(bridged_balance))
+	}
+}
+
+/// Bridge Hub chain from message lane point of view.
+#[derive(RuntimeDebug, Clone, Copy)]
+pub struct BridgeHub;
+
+impl messages::ChainWithMessages for BridgeHub {
+	const ID: ChainId = crate::BRIDGE_HUB_CHAIN_ID;
+
+	type Hash = crate::Hash;
+	type AccountId = crate::AccountId;
+	type Signer = crate::AccountSigner;
+	type Signature = crate::Signature;
+	type Weight = Weight;
+	type Balance = crate::Balance;
+
+	type MessagesInstance = crate::WithMillauMessagesInstance;
+}
+
+impl messages::ThisChainWithMessages for BridgeHub {
+	type Call = crate::Call;
+
+	fn is_outbound_lane_enabled(lane: &LaneId) -> bool {
+		*lane == LaneId([0, 0, 0, 0])
+	}
+
+	fn maximal_pending_messages_at_outbound_lane() -> MessageNonce {
+		MessageNonce::MAX
+	}
+
+	fn estimate_delivery_confirmation_transaction() -> MessageTransaction<Weight> {
+		let inbound_data_size =
+			InboundLaneData::<crate::AccountId>::encoded_size_hint(crate::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE, 1, 1, 1)
+				.unwrap_or(u32::MAX);
+
+		MessageTransaction {
+			dispatch_weight: crate::MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT,
+			size: inbound_data_size
+				.saturating_add(bp_millau::EXTRA_STORAGE_PROOF_SIZE)
+				.saturating_add(crate::TX_EXTRA_BYTES),
+		}
+	}
+
+	fn transaction_payment(transaction: MessageTransaction<Weight>) -> crate::Balance {
+		// the template has no transaction payment pallet, so we're using 1:1 per-byte fee and weight-to-fee
+		messages::transaction_payment(
+			crate::RuntimeBlockWeights::get()
+				.get(DispatchClass::Normal)
+				.base_extrinsic,
+			1,
+			FixedU128::zero(),
+			|weight| weight as _,
+			transaction,
+		)
+	}
+}
+
+/// Millau chain from message lane point of view.
+#[derive(RuntimeDebug, Clone, Copy)]
+pub struct Millau;
+
+impl messages::ChainWithMessages for Millau {
+	const ID: ChainId = MILLAU_CHAIN_ID;
+
+	type Hash = bp_millau::Hash;
+	type AccountId = bp_millau::AccountId;
+	type Signer = bp_millau::AccountSigner;
+	type Signature = bp_millau::Signature;
+	type Weight = Weight;
+	type Balance = bp_millau::Balance;
+
+	type MessagesInstance = pallet_bridge_messages::DefaultInstance;
+}
+
+impl messages::BridgedChainWithMessages for Millau {
+	fn maximal_extrinsic_size() -> u32 {
+		bp_millau::max_extrinsic_size()
+	}
+
+	fn message_weight_limits(_message_payload: &[u8]) -> RangeInclusive<Weight> {
+		// we don't want to relay too large messages + keep reserve for future upgrades
+		let upper_limit = messages::target::maximal_incoming_message_dispatch_weight(bp_millau::max_extrinsic_weight());
+
+		// we're charging for payload bytes in `WithMillauMessageBridge::transaction_payment` function
+		//
+		// this bridge may be used to deliver all kind of messages, so we're not making any assumptions about
+		// minimal dispatch weight here
+
+		0..=upper_limit
+	}
+
+	fn estimate_delivery_transaction(
+		message_payload: &[u8],
+		message_dispatch_weight: Weight,
+	) -> MessageTransaction<Weight> {
+		let message_payload_len = u32::try_from(message_payload.len()).unwrap_or(u32::MAX);
+		let extra_bytes_in_payload = Weight::from(message_payload_len)
+			.saturating_sub(pallet_bridge_messages::EXPECTED_DEFAULT_MESSAGE_LENGTH.into());
+
+		MessageTransaction {
+			dispatch_weight: extra_bytes_in_payload
+				.saturating_mul(bp_millau::ADDITIONAL_MESSAGE_BYTE_DELIVERY_WEIGHT)
+				.saturating_add(bp_millau::DEFAULT_MESSAGE_DELIVERY_TX_WEIGHT)
+				.saturating_add(message_dispatch_weight),
+			size: message_payload_len
+				.saturating_add(crate::EXTRA_STORAGE_PROOF_SIZE)
+				.saturating_add(bp_millau::TX_EXTRA_BYTES),
+		}
+	}
+
+	fn transaction_payment(transaction: MessageTransaction<Weight>) -> bp_millau::Balance {
+		// in our testnets, both per-byte fee and weight-to-fee are 1:1
+		messages::transaction_payment(
+			bp_millau::BlockWeights::get().get(DispatchClass::Normal).base_extrinsic,
+			1,
+			FixedU128::zero(),
+			|weight| weight as _,
+			transaction,
+		)
+	}
+}
+
+impl TargetHeaderChain<ToMillauMessagePayload, bp_millau::AccountId> for Millau {
+	type Error = &'static str;
+	// The proof is:
+	// - hash of the header this proof has been created with;
+	// - the storage proof or one or several keys;
+	// - id of the lane we prove state of.
+	type MessagesDeliveryProof = ToMillauMessagesDeliveryProof;
+
+	fn verify_message(payload: &ToMillauMessagePayload) -> Result<(), Self::Error> {
+		messages::source::verify_chain_message::<WithMillauMessageBridge>(payload)
+	}
+
+	fn verify_messages_delivery_proof(
+		proof: Self::MessagesDeliveryProof,
+	) -> Result<(LaneId, InboundLaneData<crate::AccountId>), Self::Error> {
+		messages::source::verify_messages_delivery_proof::<WithMillauMessageBridge, Runtime, crate::MillauGrandpaInstance>(
+			proof,
+		)
+	}
+}
+
+impl SourceHeaderChain<bp_millau::Balance> for Millau {
+	type Error = &'static str;
+	// The proof is:
+	// - hash of the header this proof has been created with;
+	// - the storage proof or one or several keys;
+	// - id of the lane we prove messages for;
+	// - inclusive range of messages nonces that are proved.
+	type MessagesProof = FromMillauMessagesProof;
+
+	fn verify_messages_proof(
+		proof: Self::MessagesProof,
+		messages_count: u32,
+	) -> Result<ProvedMessages<Message<bp_millau::Balance>>, Self::Error> {
+		messages::target::verify_messages_proof::<WithMillauMessageBridge, Runtime, crate::MillauGrandpaInstance>(
+			proof,
+			messages_count,
+		)
+	}
+}
+
+/// Bridge Hub -> Millau message lane pallet parameters.
+#[derive(RuntimeDebug, Clone, Encode, Decode, PartialEq, Eq)]
+pub enum BridgeHubToMillauMessagesParameter {
+	/// The conversion formula we use is: `BridgeHubTokens = MillauTokens * conversion_rate`.
+	MillauToBridgeHubConversionRate(FixedU128),
+}
+
+impl MessagesParameter for BridgeHubToMillauMessagesParameter {
+	fn save(&self) {
+		match *self {
+			BridgeHubToMillauMessagesParameter::MillauToBridgeHubConversionRate(ref conversion_rate) => {
+				MillauToBridgeHubConversionRate::set(conversion_rate)
+			}
+		}
+	}
+}
@@ -0,0 +1,222 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests proving that the bridge hub template runtime may serve as both source and target
+//! of the bridge with Millau.
+//!
+//! Millau storage proofs are built from the storage keys of the actual Millau runtime and
+//! with the actual Millau hasher. Headers with these storage roots are then imported into the
+//! bridge hub, so the same proofs would be accepted if they were generated by a live Millau node.
+
+use bp_message_dispatch::{CallOrigin, DispatchFeePayment};
+use bp_messages::{InboundLaneData, LaneId, MessageData, UnrewardedRelayersState};
+use bridge_hub_template_runtime::{
+	millau_messages::{ToMillauMessagesDeliveryProof, WithMillauMessageBridge},
+	AccountId, AccountIdConverter, Balance, BuildStorage, Call, GenesisConfig, Origin, Runtime,
+};
+use bridge_runtime_common::messages::{self, MessageBridge};
+use codec::{Decode, Encode};
+use frame_support::weights::GetDispatchInfo;
+use pallet_bridge_messages::DefaultInstance;
+use sp_runtime::traits::{Convert, Header as HeaderT};
+use sp_trie::{record_all_keys, trie_types::TrieDBMut, Layout, MemoryDB, Recorder, TrieMut};
+
+const LANE: LaneId = LaneId([0, 0, 0, 0]);
+const ENDOWMENT: Balance = 1 << 60;
+
+fn account(seed: u8) -> AccountId {
+	[seed; 32].into()
+}
+
+/// Account that is used to dispatch calls with `CallOrigin::SourceRoot` origin from Millau.
+fn millau_root_account() -> AccountId {
+	AccountIdConverter::convert(bp_runtime::derive_account_id::<bp_millau::AccountId>(
+		bp_runtime::MILLAU_CHAIN_ID,
+		bp_runtime::SourceAccount::Root,
+	))
+}
+
+fn run_test(test: impl FnOnce()) {
+	let storage = GenesisConfig {
+		frame_system: Default::default(),
+		pallet_balances: pallet_balances::GenesisConfig {
+			balances: vec![
+				(account(1), ENDOWMENT),
+				(millau_root_account(), ENDOWMENT),
+				(
+					pallet_bridge_messages::Pallet::<Runtime, DefaultInstance>::relayer_fund_account_id(),
+					ENDOWMENT,
+				),
+			],
+		},
+		pallet_bridge_grandpa: Default::default(),
+	}
+	.build_storage()
+	.unwrap();
+	sp_io::TestExternalities::new(storage).execute_with(test)
+}
+
+/// Build Millau storage with given entries, import Millau header with this storage root into the
+/// bridge hub and return its hash and the storage proof of all entries.
+fn import_millau_state(entries: Vec<(Vec<u8>, Vec<u8>)>) -> (bp_millau::Hash, Vec<Vec<u8>>) {
+	let mut root = Default::default();
+	let mut mdb = MemoryDB::<bp_millau::Hasher>::default();
+	{
+		let mut trie = TrieDBMut::<bp_millau::Hasher>::new(&mut mdb, &mut root);
+		for (key, value) in entries {
+			trie.insert(&key, &value).unwrap();
+		}
+	}
+
+	let mut proof_recorder = Recorder::<bp_millau::Hash>::new();
+	record_all_keys::<Layout<bp_millau::Hasher>, _>(&mdb, &root, &mut proof_recorder).unwrap();
+	let storage_proof = proof_recorder.drain().into_iter().map(|n| n.data.to_vec()).collect();
+
+	let header = bp_millau::Header::new(1, Default::default(), root, Default::default(), Default::default());
+	let header_hash = header.hash();
+	pallet_bridge_grandpa::Pallet::<Runtime>::initialize(
+		Origin::root(),
+		bp_header_chain::InitializationData {
+			header,
+			authority_list: Vec::new(),
+			set_id: 0,
+			is_halted: false,
+		},
+	)
+	.unwrap();
+
+	(header_hash, storage_proof)
+}
+
+#[test]
+fn bridge_hub_receives_and_dispatches_messages_from_millau() {
+	run_test(|| {
+		let recipient = account(2);
+		let transfer_amount: Balance = 1_000_000;
+
+		// this is what Millau would store in its outbound lane when `send_message` is called
+		let hub_call = Call::Balances(pallet_balances::Call::transfer(recipient.clone(), transfer_amount));
+		let payload = millau_runtime::rialto_messages::ToRialtoMessagePayload {
+			spec_version: bridge_hub_template_runtime::VERSION.spec_version,
+			weight: hub_call.get_dispatch_info().weight,
+			delivery_deadline: None,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+			origin: CallOrigin::SourceRoot,
+			call: hub_call.encode(),
+		};
+		let message_key =
+			pallet_bridge_messages::storage_keys::message_key::<millau_runtime::Runtime, DefaultInstance>(&LANE, 1);
+		let message_data = MessageData::<bp_millau::Balance> {
+			payload: payload.encode(),
+			fee: 0,
+		};
+		let (bridged_header_hash, storage_proof) = import_millau_state(vec![(message_key.0, message_data.encode())]);
+
+		// and this is how relayer delivers the message to the bridge hub
+		pallet_bridge_messages::Pallet::<Runtime, DefaultInstance>::receive_messages_proof(
+			Origin::signed(account(1)),
+			account(3),
+			messages::target::FromBridgedChainMessagesProof {
+				bridged_header_hash,
+				storage_proof,
+				lane: LANE,
+				nonces_start: 1,
+				nonces_end: 1,
+			},
+			1,
+			payload.weight,
+		)
+		.unwrap();
+
+		assert_eq!(
+			pallet_bridge_messages::Pallet::<Runtime, DefaultInstance>::inbound_latest_received_nonce(LANE),
+			1,
+		);
+		assert_eq!(
+			pallet_balances::Pallet::<Runtime>::free_balance(&recipient),
+			transfer_amount,
+		);
+	});
+}
+
+#[test]
+fn bridge_hub_sends_messages_to_millau_and_accepts_delivery_confirmations() {
+	run_test(|| {
+		// send message that is dispatched at Millau
+		let millau_call = millau_runtime::Call::System(millau_runtime::SystemCall::remark(vec![42]));
+		let payload = bridge_hub_template_runtime::millau_messages::ToMillauMessagePayload {
+			spec_version: millau_runtime::VERSION.spec_version,
+			weight: millau_call.get_dispatch_info().weight,
+			delivery_deadline: None,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+			origin: CallOrigin::SourceAccount(account(1)),
+			call: millau_call.encode(),
+		};
+		let fee = messages::source::estimate_message_dispatch_and_delivery_fee::<WithMillauMessageBridge>(
+			&payload,
+			WithMillauMessageBridge::RELAYER_FEE_PERCENT,
+		)
+		.unwrap();
+		pallet_bridge_messages::Pallet::<Runtime, DefaultInstance>::send_message(
+			Origin::signed(account(1)),
+			LANE,
+			payload,
+			fee,
+		)
+		.unwrap();
+
+		// Millau is able to decode both the message and the call
+		let sent_payload =
+			pallet_bridge_messages::Pallet::<Runtime, DefaultInstance>::outbound_message_payload(LANE, 1).unwrap();
+		let millau_payload =
+			millau_runtime::rialto_messages::FromRialtoMessagePayload::decode(&mut &sent_payload[..]).unwrap();
+		let decoded_millau_call: Result<millau_runtime::Call, ()> = millau_payload.call.into();
+		assert_eq!(decoded_millau_call, Ok(millau_call));
+
+		// this is what Millau would store in its inbound lane once message is delivered
+		let inbound_lane_data_key = pallet_bridge_messages::storage_keys::inbound_lane_data_key::<
+			millau_runtime::Runtime,
+			DefaultInstance,
+		>(&LANE);
+		let inbound_lane_data = InboundLaneData::<AccountId> {
+			relayers: vec![(1, 1, account(3))].into_iter().collect(),
+			last_confirmed_nonce: 0,
+		};
+		let (bridged_header_hash, storage_proof) =
+			import_millau_state(vec![(inbound_lane_data_key.0, inbound_lane_data.encode())]);
+
+		// and this is how relayer delivers confirmation to the bridge hub
+		pallet_bridge_messages::Pallet::<Runtime, DefaultInstance>::receive_messages_delivery_proof(
+			Origin::signed(account(3)),
+			ToMillauMessagesDeliveryProof {
+				bridged_header_hash,
+				storage_proof,
+				lane: LANE,
+			},
+			UnrewardedRelayersState {
+				unrewarded_relayer_entries: 1,
+				messages_in_oldest_entry: 1,
+				total_messages: 1,
+			},
+		)
+		.unwrap();
+
+		assert_eq!(
+			pallet_bridge_messages::Pallet::<Runtime, DefaultInstance>::outbound_latest_received_nonce(LANE),
+			1,
+		);
+	});
+}
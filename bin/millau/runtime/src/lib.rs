@@ -31,21 +31,26 @@
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 pub mod rialto_messages;
+pub mod rialto_parachain_messages;
 
 use crate::rialto_messages::{ToRialtoMessagePayload, WithRialtoMessageBridge};
+use crate::rialto_parachain_messages::{ToRialtoParachainMessagePayload, WithRialtoParachainMessageBridge};
 
+use beefy_primitives::{crypto::AuthorityId as BeefyId, mmr::MmrLeafVersion, ValidatorSet};
 use bridge_runtime_common::messages::{source::estimate_message_dispatch_and_delivery_fee, MessageBridge};
 use codec::Decode;
+use frame_support::PalletId;
 use pallet_grandpa::{fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
+use pallet_mmr_primitives::{DataOrHash, EncodableOpaqueLeaf, Error as MmrError, LeafDataProvider, Proof as MmrProof};
 use pallet_transaction_payment::{FeeDetails, RuntimeDispatchInfo};
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
-use sp_runtime::traits::{Block as BlockT, IdentityLookup, NumberFor, OpaqueKeys};
+use sp_runtime::traits::{AccountIdConversion, Block as BlockT, IdentityLookup, Keccak256, NumberFor, OpaqueKeys};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature, MultiSigner,
+	ApplyExtrinsicResult, FixedU128, MultiSignature, MultiSigner,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -62,11 +67,16 @@ pub use frame_support::{
 
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_bridge_conversion_rate::Call as ConversionRateCall;
 pub use pallet_bridge_grandpa::Call as BridgeGrandpaRialtoCall;
 pub use pallet_bridge_grandpa::Call as BridgeGrandpaWestendCall;
 pub use pallet_bridge_messages::Call as MessagesCall;
+pub use pallet_bridge_relayers::Call as RelayersCall;
+pub use pallet_bridge_token_swap::Call as TokenSwapCall;
+pub use pallet_proxy::Call as ProxyCall;
 pub use pallet_sudo::Call as SudoCall;
 pub use pallet_timestamp::Call as TimestampCall;
+pub use pallet_utility::Call as UtilityCall;
 
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
@@ -121,6 +131,7 @@ pub mod opaque {
 impl_opaque_keys! {
 	pub struct SessionKeys {
 		pub aura: Aura,
+		pub beefy: Beefy,
 		pub grandpa: Grandpa,
 	}
 }
@@ -208,6 +219,12 @@ impl frame_system::Config for Runtime {
 impl pallet_aura::Config for Runtime {
 	type AuthorityId = AuraId;
 }
+parameter_types! {
+	/// Number of blocks after runtime upgrade, during which bridged messages that have been
+	/// encoded using the previous runtime version are still dispatched.
+	pub const DispatchSpecVersionGracePeriod: BlockNumber = bp_millau::HOURS;
+}
+
 impl pallet_bridge_dispatch::Config for Runtime {
 	type Event = Event;
 	type MessageId = (bp_messages::LaneId, bp_messages::MessageNonce);
@@ -218,6 +235,25 @@ impl pallet_bridge_dispatch::Config for Runtime {
 	type TargetChainAccountPublic = MultiSigner;
 	type TargetChainSignature = MultiSignature;
 	type AccountIdConverter = bp_millau::AccountIdConverter;
+	type ProxyCheck = BridgeProxyCheck;
+	type SpecVersionGracePeriod = DispatchSpecVersionGracePeriod;
+}
+
+/// Instance of the dispatch pallet used to dispatch messages from Rialto parachain.
+pub type WithRialtoParachainDispatchInstance = pallet_bridge_dispatch::Instance1;
+
+impl pallet_bridge_dispatch::Config<WithRialtoParachainDispatchInstance> for Runtime {
+	type Event = Event;
+	type MessageId = (bp_messages::LaneId, bp_messages::MessageNonce);
+	type Call = Call;
+	type CallFilter = ();
+	type EncodedCall = crate::rialto_parachain_messages::FromRialtoParachainEncodedCall;
+	type SourceChainAccountId = bp_rialto_parachain::AccountId;
+	type TargetChainAccountPublic = MultiSigner;
+	type TargetChainSignature = MultiSignature;
+	type AccountIdConverter = bp_millau::AccountIdConverter;
+	type ProxyCheck = BridgeProxyCheck;
+	type SpecVersionGracePeriod = DispatchSpecVersionGracePeriod;
 }
 
 impl pallet_grandpa::Config for Runtime {
@@ -282,6 +318,98 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+impl pallet_utility::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const ProxyDepositBase: Balance = 1_000;
+	pub const ProxyDepositFactor: Balance = 100;
+	pub const MaxProxies: u16 = 32;
+	pub const MaxPending: u16 = 32;
+	pub const AnnouncementDepositBase: Balance = 1_000;
+	pub const AnnouncementDepositFactor: Balance = 100;
+}
+
+/// The type used to represent the kinds of proxying allowed.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, codec::Encode, codec::Decode, sp_runtime::RuntimeDebug)]
+pub enum ProxyType {
+	/// Proxy may dispatch any call on behalf of the proxied account.
+	Any,
+	/// Proxy may only dispatch bridge calls that are submitted by the relayers.
+	BridgeRelay,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl frame_support::traits::InstanceFilter<Call> for ProxyType {
+	fn filter(&self, c: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::BridgeRelay => matches!(
+				c,
+				Call::BridgeRialtoGrandpa(..)
+					| Call::BridgeWestendGrandpa(..)
+					| Call::BridgeRialtoParachains(..)
+					| Call::BridgeRialtoMessages(..)
+					| Call::BridgeRialtoRelayers(..)
+					| Call::BridgeRialtoParachainMessages(..)
+					| Call::Utility(..)
+			),
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			(ProxyType::BridgeRelay, ProxyType::BridgeRelay) => true,
+		}
+	}
+}
+
+impl pallet_proxy::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
+	type WeightInfo = ();
+	type MaxPending = MaxPending;
+	type CallHasher = Hashing;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+/// Proxy relationship check, backed by the proxy pallet.
+///
+/// Used by the dispatch pallet to verify that the spawner of the pure proxy account is still
+/// its proxy.
+pub struct BridgeProxyCheck;
+
+impl pallet_bridge_dispatch::ProxyCheck<AccountId, BlockNumber> for BridgeProxyCheck {
+	type ProxyType = ProxyType;
+
+	fn any_proxy_type() -> ProxyType {
+		ProxyType::Any
+	}
+
+	fn is_proxy(proxied: &AccountId, delegate: &AccountId, proxy_type: &ProxyType, delay: &BlockNumber) -> bool {
+		Proxy::proxies(proxied)
+			.0
+			.iter()
+			.any(|proxy| proxy.delegate == *delegate && proxy.proxy_type == *proxy_type && proxy.delay == *delay)
+	}
+}
+
 parameter_types! {
 	/// Authorities are changing every 5 minutes.
 	pub const Period: BlockNumber = bp_millau::SESSION_LENGTH;
@@ -302,6 +430,33 @@ impl pallet_session::Config for Runtime {
 	type WeightInfo = ();
 }
 
+impl pallet_beefy::Config for Runtime {
+	type AuthorityId = BeefyId;
+}
+
+impl pallet_mmr::Config for Runtime {
+	const INDEXING_PREFIX: &'static [u8] = b"mmr";
+	type Hashing = Keccak256;
+	type Hash = <Keccak256 as sp_runtime::traits::Hash>::Output;
+	type OnNewRoot = pallet_beefy_mmr::DepositBeefyDigest<Runtime>;
+	type WeightInfo = ();
+	type LeafData = pallet_beefy_mmr::Pallet<Runtime>;
+}
+
+parameter_types! {
+	/// Version of the produced MMR leaf.
+	///
+	/// The `major` part should only be updated if the previous MMR leaf format can't be decoded
+	/// from the payload. The `minor` part should be updated if fields are added to the leaf.
+	pub LeafVersion: MmrLeafVersion = MmrLeafVersion::new(0, 0);
+}
+
+impl pallet_beefy_mmr::Config for Runtime {
+	type LeafVersion = LeafVersion;
+	type BeefyAuthorityToMerkleLeaf = pallet_beefy_mmr::BeefyEcdsaToEthereum;
+	type ParachainHeads = ();
+}
+
 parameter_types! {
 	// This is a pretty unscientific cap.
 	//
@@ -314,6 +469,11 @@ parameter_types! {
 	// Assuming the worst case of every header being finalized, we will keep headers for at least a
 	// week.
 	pub const HeadersToKeep: u32 = 7 * bp_millau::DAYS as u32;
+
+	// Maximal number of headers in the `submit_finality_proof_with_ancestry` call.
+	//
+	// The pallet verifies all headers of the ancestry, so the ancestry needs to be bounded.
+	pub const MaxAncestryLength: u32 = 1024;
 }
 
 pub type RialtoGrandpaInstance = ();
@@ -321,6 +481,7 @@ impl pallet_bridge_grandpa::Config for Runtime {
 	type BridgedChain = bp_rialto::Rialto;
 	type MaxRequests = MaxRequests;
 	type HeadersToKeep = HeadersToKeep;
+	type MaxAncestryLength = MaxAncestryLength;
 
 	// TODO [#391]: Use weights generated for the Millau runtime instead of Rialto ones.
 	type WeightInfo = pallet_bridge_grandpa::weights::RialtoWeight<Runtime>;
@@ -331,11 +492,25 @@ impl pallet_bridge_grandpa::Config<WestendGrandpaInstance> for Runtime {
 	type BridgedChain = bp_westend::Westend;
 	type MaxRequests = MaxRequests;
 	type HeadersToKeep = HeadersToKeep;
+	type MaxAncestryLength = MaxAncestryLength;
 
 	// TODO [#391]: Use weights generated for the Millau runtime instead of Rialto ones.
 	type WeightInfo = pallet_bridge_grandpa::weights::RialtoWeight<Runtime>;
 }
 
+parameter_types! {
+	pub const RialtoParasPalletName: &'static str = "Paras";
+	pub const MaxRialtoParaHeadsToKeep: u32 = 1024;
+}
+
+/// Instance of the parachains pallet that is tracking heads of Rialto parachains.
+pub type RialtoParachainsInstance = ();
+impl pallet_bridge_parachains::Config<RialtoParachainsInstance> for Runtime {
+	type BridgesGrandpaPalletInstance = RialtoGrandpaInstance;
+	type ParasPalletName = RialtoParasPalletName;
+	type HeadsToKeep = MaxRialtoParaHeadsToKeep;
+}
+
 impl pallet_shift_session_manager::Config for Runtime {}
 
 parameter_types! {
@@ -362,6 +537,8 @@ impl pallet_bridge_messages::Config<WithRialtoMessagesInstance> for Runtime {
 	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
 
+	type MaximalOutboundPayloadSize = rialto_messages::MaximalMessageSizeToRialto;
+	type MaximalOutboundDispatchWeight = rialto_messages::MaximalMessageDispatchWeightToRialto;
 	type OutboundPayload = crate::rialto_messages::ToRialtoMessagePayload;
 	type OutboundMessageFee = Balance;
 
@@ -382,6 +559,152 @@ impl pallet_bridge_messages::Config<WithRialtoMessagesInstance> for Runtime {
 
 	type SourceHeaderChain = crate::rialto_messages::Rialto;
 	type MessageDispatch = crate::rialto_messages::FromRialtoMessageDispatch;
+	type OnMessagesDelivered = BridgeRialtoRelayers;
+}
+
+parameter_types! {
+	pub const RelayersPalletId: PalletId = PalletId(*b"brl/rlrs");
+	pub const RelayerRegistrationBond: Balance = 1_000_000;
+	pub const MaxRelayers: u32 = 16;
+	pub const RelayersSlotLength: BlockNumber = bp_millau::MINUTES;
+	pub const DeliveryReward: Balance = 1_000;
+	pub const OutOfSlotRewardRatio: Perbill = Perbill::from_percent(10);
+	pub const RegisteredRelayerRewardBoost: Perbill = Perbill::from_percent(20);
+	pub const MaxAbandonedSlots: u32 = 3;
+	pub const SlotAbandonmentSlash: Balance = 100_000;
+	pub const InvalidBridgeTransactionSlash: Balance = 100_000;
+	pub const RelayerRewardsRetentionPeriod: BlockNumber = 30 * bp_millau::DAYS;
+	pub const MaxFinishedRelayerSlotsPerBlock: u32 = 16;
+}
+
+/// Bridge transactions of registered relayers that are checked by the
+/// `SlashInvalidBridgeTransactions` signed extension.
+pub struct BridgeTransactionsFilter;
+
+impl frame_support::traits::Filter<Call> for BridgeTransactionsFilter {
+	fn filter(call: &Call) -> bool {
+		matches!(
+			call,
+			Call::BridgeRialtoMessages(MessagesCall::receive_messages_proof(..))
+				| Call::BridgeRialtoMessages(MessagesCall::receive_messages_delivery_proof(..))
+		)
+	}
+}
+
+/// Errors of bridge transactions that mean that the registered relayer has submitted invalid proof.
+///
+/// Other errors (e.g. when the relayer has lost the race to deliver the same messages) are not
+/// slashed by the `SlashInvalidBridgeTransactions` signed extension.
+pub struct InvalidBridgeProofErrors;
+
+impl frame_support::traits::Filter<sp_runtime::DispatchError> for InvalidBridgeProofErrors {
+	fn filter(error: &sp_runtime::DispatchError) -> bool {
+		type MessagesError = pallet_bridge_messages::Error<Runtime, WithRialtoMessagesInstance>;
+
+		*error == MessagesError::InvalidMessagesProof.into()
+			|| *error == MessagesError::InvalidMessagesDeliveryProof.into()
+	}
+}
+
+impl pallet_bridge_relayers::Config for Runtime {
+	type Event = Event;
+	type PalletId = RelayersPalletId;
+	type Currency = pallet_balances::Pallet<Runtime>;
+	type RegistrationBond = RelayerRegistrationBond;
+	type MaxRelayers = MaxRelayers;
+	type SlotLength = RelayersSlotLength;
+	type DeliveryReward = DeliveryReward;
+	type OutOfSlotRewardRatio = OutOfSlotRewardRatio;
+	type RegisteredRewardBoost = RegisteredRelayerRewardBoost;
+	type MaxAbandonedSlots = MaxAbandonedSlots;
+	type SlotAbandonmentSlash = SlotAbandonmentSlash;
+	type InvalidTransactionSlash = InvalidBridgeTransactionSlash;
+	type BridgeTransactionsFilter = BridgeTransactionsFilter;
+	type InvalidProofErrors = InvalidBridgeProofErrors;
+	type RewardsRetentionPeriod = RelayerRewardsRetentionPeriod;
+	type MaxFinishedSlotsPerBlock = MaxFinishedRelayerSlotsPerBlock;
+}
+
+parameter_types! {
+	pub const InitialRialtoToMillauConversionRate: FixedU128 = rialto_messages::INITIAL_RIALTO_TO_MILLAU_CONVERSION_RATE;
+}
+
+impl pallet_bridge_conversion_rate::Config for Runtime {
+	type Event = Event;
+	// the conversion rate oracle (relay) is using sudo key to update the rate
+	type OracleOrigin = frame_system::EnsureRoot<AccountId>;
+	type InitialConversionRate = InitialRialtoToMillauConversionRate;
+}
+
+/// Instance of the messages pallet used to relay messages to/from Rialto parachain.
+pub type WithRialtoParachainMessagesInstance = pallet_bridge_messages::Instance1;
+
+impl pallet_bridge_messages::Config<WithRialtoParachainMessagesInstance> for Runtime {
+	type Event = Event;
+	// TODO: https://github.com/paritytech/parity-bridges-common/issues/390
+	type WeightInfo = pallet_bridge_messages::weights::RialtoWeight<Runtime>;
+	type Parameter = rialto_parachain_messages::MillauToRialtoParachainMessagesParameter;
+	type MaxMessagesToPruneAtOnce = MaxMessagesToPruneAtOnce;
+	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
+	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+
+	type MaximalOutboundPayloadSize = rialto_parachain_messages::MaximalMessageSizeToRialtoParachain;
+	type MaximalOutboundDispatchWeight = rialto_parachain_messages::MaximalMessageDispatchWeightToRialtoParachain;
+	type OutboundPayload = crate::rialto_parachain_messages::ToRialtoParachainMessagePayload;
+	type OutboundMessageFee = Balance;
+
+	type InboundPayload = crate::rialto_parachain_messages::FromRialtoParachainMessagePayload;
+	type InboundMessageFee = bp_rialto_parachain::Balance;
+	type InboundRelayer = bp_rialto_parachain::AccountId;
+
+	type AccountIdConverter = bp_millau::AccountIdConverter;
+
+	type TargetHeaderChain = crate::rialto_parachain_messages::RialtoParachain;
+	type LaneMessageVerifier = crate::rialto_parachain_messages::ToRialtoParachainMessageVerifier;
+	type MessageDeliveryAndDispatchPayment = pallet_bridge_messages::instant_payments::InstantCurrencyPayments<
+		Runtime,
+		pallet_balances::Pallet<Runtime>,
+		GetDeliveryConfirmationTransactionFee,
+		RootAccountForPayments,
+	>;
+
+	type SourceHeaderChain = crate::rialto_parachain_messages::RialtoParachain;
+	type MessageDispatch = crate::rialto_parachain_messages::FromRialtoParachainMessageDispatch;
+	type OnMessagesDelivered = ();
+}
+
+parameter_types! {
+	pub const TokenSwapPalletId: PalletId = PalletId(*b"bsw/swap");
+	pub const RialtoChainId: bp_runtime::ChainId = bp_runtime::RIALTO_CHAIN_ID;
+	pub const TokenSwapMessagesLane: bp_messages::LaneId = bp_messages::LaneId([0, 0, 0, 0]);
+	pub RialtoTokenSwapPalletAccountId: bp_rialto::AccountId = TokenSwapPalletId::get().into_account();
+	pub const RialtoTokenSwapPalletIndex: u8 = bp_rialto::TOKEN_SWAP_PALLET_INDEX;
+	pub const RialtoMaxClaimSwapWeight: Weight = bp_rialto::MAX_CLAIM_SWAP_WEIGHT;
+	pub const RialtoMaxSettleSwapWeight: Weight = bp_rialto::MAX_SETTLE_SWAP_WEIGHT;
+}
+
+impl pallet_bridge_token_swap::Config for Runtime {
+	type Event = Event;
+
+	type PalletId = TokenSwapPalletId;
+	type BridgedChainId = RialtoChainId;
+	type OutboundMessageLaneId = TokenSwapMessagesLane;
+
+	type MessagesBridge = pallet_bridge_messages::Pallet<Runtime, WithRialtoMessagesInstance>;
+	type ThisCurrency = pallet_balances::Pallet<Runtime>;
+	type AccountIdConverter = bp_millau::AccountIdConverter;
+
+	type BridgedChain = bp_rialto::Rialto;
+	type BridgedBalance = bp_rialto::Balance;
+	type BridgedAccountId = bp_rialto::AccountId;
+	type BridgedAccountPublic = bp_rialto::AccountSigner;
+	type BridgedAccountSignature = bp_rialto::Signature;
+	type BridgedPalletAccountId = RialtoTokenSwapPalletAccountId;
+	type BridgedTokenSwapCalls = pallet_bridge_token_swap::BridgedTokenSwapPalletCalls<
+		RialtoTokenSwapPalletIndex,
+		RialtoMaxClaimSwapWeight,
+		RialtoMaxSettleSwapWeight,
+	>;
 }
 
 construct_runtime!(
@@ -392,8 +715,8 @@ construct_runtime!(
 	{
 		BridgeRialtoMessages: pallet_bridge_messages::{Pallet, Call, Storage, Event<T>},
 		BridgeDispatch: pallet_bridge_dispatch::{Pallet, Event<T>},
-		BridgeRialtoGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage},
-		BridgeWestendGrandpa: pallet_bridge_grandpa::<Instance1>::{Pallet, Call, Config<T>, Storage},
+		BridgeRialtoGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage, ValidateUnsigned},
+		BridgeWestendGrandpa: pallet_bridge_grandpa::<Instance1>::{Pallet, Call, Config<T>, Storage, ValidateUnsigned},
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Call, Storage},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
@@ -404,6 +727,19 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
 		ShiftSessionManager: pallet_shift_session_manager::{Pallet},
+		BridgeRialtoTokenSwap: pallet_bridge_token_swap::{Pallet, Call, Storage, Event<T>},
+		BridgeRialtoParachains: pallet_bridge_parachains::{Pallet, Call, Storage},
+		BridgeRialtoParachainMessages: pallet_bridge_messages::<Instance1>::{Pallet, Call, Storage, Event<T>},
+		BridgeRialtoParachainDispatch: pallet_bridge_dispatch::<Instance1>::{Pallet, Event<T>},
+		Utility: pallet_utility::{Pallet, Call, Event},
+		Proxy: pallet_proxy::{Pallet, Call, Storage, Event<T>},
+		BridgeRialtoRelayers: pallet_bridge_relayers::{Pallet, Call, Storage, Event<T>},
+		BridgeRialtoConversionRate: pallet_bridge_conversion_rate::{Pallet, Call, Storage, Event<T>},
+
+		// BEEFY bridges support.
+		Beefy: pallet_beefy::{Pallet, Storage, Config<T>},
+		Mmr: pallet_mmr::{Pallet, Storage},
+		MmrLeaf: pallet_beefy_mmr::{Pallet, Storage},
 	}
 );
 
@@ -426,6 +762,7 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_bridge_relayers::SlashInvalidBridgeTransactions<Runtime>,
 );
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
@@ -522,6 +859,43 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl beefy_primitives::BeefyApi<Block> for Runtime {
+		fn validator_set() -> ValidatorSet<BeefyId> {
+			Beefy::validator_set()
+		}
+	}
+
+	impl pallet_mmr_primitives::MmrApi<Block, Hash> for Runtime {
+		fn generate_proof(leaf_index: u64)
+			-> Result<(EncodableOpaqueLeaf, MmrProof<Hash>), MmrError>
+		{
+			Mmr::generate_proof(leaf_index)
+				.map(|(leaf, proof)| (EncodableOpaqueLeaf::from_leaf(&leaf), proof))
+		}
+
+		fn verify_proof(leaf: EncodableOpaqueLeaf, proof: MmrProof<Hash>)
+			-> Result<(), MmrError>
+		{
+			type Leaf = <<Runtime as pallet_mmr::Config>::LeafData as LeafDataProvider>::LeafData;
+
+			let leaf: Leaf = leaf
+				.into_opaque_leaf()
+				.try_decode()
+				.ok_or(MmrError::Verify)?;
+			Mmr::verify_leaf(leaf, proof)
+		}
+
+		fn verify_proof_stateless(
+			root: Hash,
+			leaf: EncodableOpaqueLeaf,
+			proof: MmrProof<Hash>
+		) -> Result<(), MmrError> {
+			type MmrHashing = <Runtime as pallet_mmr::Config>::Hashing;
+			let node = DataOrHash::Data(leaf.into_opaque_leaf());
+			pallet_mmr::verify_leaf_proof::<MmrHashing, _>(root, node, proof)
+		}
+	}
+
 	impl sp_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			SessionKeys::generate(seed)
@@ -598,17 +972,22 @@ impl_runtime_apis! {
 			).ok()
 		}
 
-		fn messages_dispatch_weight(
+		fn message_details(
 			lane: bp_messages::LaneId,
 			begin: bp_messages::MessageNonce,
 			end: bp_messages::MessageNonce,
-		) -> Vec<(bp_messages::MessageNonce, Weight, u32)> {
+		) -> Vec<bp_messages::MessageDetails<Balance>> {
 			(begin..=end).filter_map(|nonce| {
-				let encoded_payload = BridgeRialtoMessages::outbound_message_payload(lane, nonce)?;
+				let message_data = BridgeRialtoMessages::outbound_message_data(lane, nonce)?;
 				let decoded_payload = rialto_messages::ToRialtoMessagePayload::decode(
-					&mut &encoded_payload[..]
+					&mut &message_data.payload[..]
 				).ok()?;
-				Some((nonce, decoded_payload.weight, encoded_payload.len() as _))
+				Some(bp_messages::MessageDetails {
+					nonce,
+					dispatch_weight: bp_runtime::DeclaredWeight::declared_weight(&decoded_payload),
+					size: message_data.payload.len() as _,
+					delivery_and_dispatch_fee: message_data.fee,
+				})
 			})
 			.collect()
 		}
@@ -634,6 +1013,76 @@ impl_runtime_apis! {
 		fn unrewarded_relayers_state(lane: bp_messages::LaneId) -> bp_messages::UnrewardedRelayersState {
 			BridgeRialtoMessages::inbound_unrewarded_relayers_state(lane)
 		}
+
+		fn inbound_lane_data(lane: bp_messages::LaneId) -> bp_messages::InboundLaneData<bp_rialto::AccountId> {
+			BridgeRialtoMessages::inbound_lane_data(lane)
+		}
+
+		fn delivery_limits() -> bp_messages::MessagesDeliveryLimits {
+			BridgeRialtoMessages::inbound_delivery_limits()
+		}
+	}
+
+	impl bp_rialto_parachain::ToRialtoParachainOutboundLaneApi<Block, Balance, ToRialtoParachainMessagePayload> for Runtime {
+		fn estimate_message_delivery_and_dispatch_fee(
+			_lane_id: bp_messages::LaneId,
+			payload: ToRialtoParachainMessagePayload,
+		) -> Option<Balance> {
+			estimate_message_dispatch_and_delivery_fee::<WithRialtoParachainMessageBridge>(
+				&payload,
+				WithRialtoParachainMessageBridge::RELAYER_FEE_PERCENT,
+			).ok()
+		}
+
+		fn message_details(
+			lane: bp_messages::LaneId,
+			begin: bp_messages::MessageNonce,
+			end: bp_messages::MessageNonce,
+		) -> Vec<bp_messages::MessageDetails<Balance>> {
+			(begin..=end).filter_map(|nonce| {
+				let message_data = BridgeRialtoParachainMessages::outbound_message_data(lane, nonce)?;
+				let decoded_payload = rialto_parachain_messages::ToRialtoParachainMessagePayload::decode(
+					&mut &message_data.payload[..]
+				).ok()?;
+				Some(bp_messages::MessageDetails {
+					nonce,
+					dispatch_weight: bp_runtime::DeclaredWeight::declared_weight(&decoded_payload),
+					size: message_data.payload.len() as _,
+					delivery_and_dispatch_fee: message_data.fee,
+				})
+			})
+			.collect()
+		}
+
+		fn latest_received_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
+			BridgeRialtoParachainMessages::outbound_latest_received_nonce(lane)
+		}
+
+		fn latest_generated_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
+			BridgeRialtoParachainMessages::outbound_latest_generated_nonce(lane)
+		}
+	}
+
+	impl bp_rialto_parachain::FromRialtoParachainInboundLaneApi<Block> for Runtime {
+		fn latest_received_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
+			BridgeRialtoParachainMessages::inbound_latest_received_nonce(lane)
+		}
+
+		fn latest_confirmed_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
+			BridgeRialtoParachainMessages::inbound_latest_confirmed_nonce(lane)
+		}
+
+		fn unrewarded_relayers_state(lane: bp_messages::LaneId) -> bp_messages::UnrewardedRelayersState {
+			BridgeRialtoParachainMessages::inbound_unrewarded_relayers_state(lane)
+		}
+
+		fn inbound_lane_data(lane: bp_messages::LaneId) -> bp_messages::InboundLaneData<bp_rialto_parachain::AccountId> {
+			BridgeRialtoParachainMessages::inbound_lane_data(lane)
+		}
+
+		fn delivery_limits() -> bp_messages::MessagesDeliveryLimits {
+			BridgeRialtoParachainMessages::inbound_delivery_limits()
+		}
 	}
 }
 
@@ -664,8 +1113,21 @@ where
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use bp_runtime::integrity::{ensure_chain_constants, ChainConstants};
 	use bridge_runtime_common::messages;
 
+	#[test]
+	fn ensure_millau_chain_constants_are_correct() {
+		let declared = ChainConstants {
+			max_extrinsic_size: bp_millau::max_extrinsic_size(),
+			max_extrinsic_weight: bp_millau::max_extrinsic_weight(),
+			session_length: Some(bp_millau::SESSION_LENGTH.into()),
+			ss58_prefix: bp_millau::SS58_PREFIX,
+		};
+		let actual = ChainConstants::from_runtime::<Runtime>(Some(Period::get().into()));
+		assert_eq!(ensure_chain_constants(&declared, &actual), Ok(()));
+	}
+
 	#[test]
 	fn ensure_millau_message_lane_weights_are_correct() {
 		// TODO: https://github.com/paritytech/parity-bridges-common/issues/390
@@ -690,6 +1152,8 @@ mod tests {
 		let max_incoming_inbound_lane_data_proof_size = bp_messages::InboundLaneData::<()>::encoded_size_hint(
 			bp_millau::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE,
 			bp_rialto::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE as _,
+			bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE as _,
+			bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE as _,
 		)
 		.unwrap_or(u32::MAX);
 		pallet_bridge_messages::ensure_able_to_receive_confirmation::<Weights>(
@@ -700,4 +1164,65 @@ mod tests {
 			bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
 		);
 	}
+
+	#[test]
+	fn ensure_token_swap_claim_call_is_correct() {
+		use codec::Encode;
+		use frame_support::weights::GetDispatchInfo;
+
+		let claim_call = Call::BridgeRialtoTokenSwap(pallet_bridge_token_swap::Call::claim_swap(
+			pallet_bridge_token_swap::TokenSwap {
+				cancel_after: 0,
+				source_account: Default::default(),
+				source_balance: 0,
+				target_account: Default::default(),
+				target_balance: 0,
+			},
+			0,
+		));
+
+		assert_eq!(
+			claim_call.encode()[..2],
+			[
+				bp_millau::TOKEN_SWAP_PALLET_INDEX,
+				pallet_bridge_token_swap::CLAIM_SWAP_CALL_INDEX
+			],
+		);
+		assert!(claim_call.get_dispatch_info().weight <= bp_millau::MAX_CLAIM_SWAP_WEIGHT);
+	}
+
+	#[test]
+	fn ensure_token_swap_settle_call_is_correct() {
+		use codec::Encode;
+		use frame_support::weights::GetDispatchInfo;
+
+		let settle_call =
+			Call::BridgeRialtoTokenSwap(pallet_bridge_token_swap::Call::settle_swap(Default::default(), true));
+
+		assert_eq!(
+			settle_call.encode()[..2],
+			[
+				bp_millau::TOKEN_SWAP_PALLET_INDEX,
+				pallet_bridge_token_swap::SETTLE_SWAP_CALL_INDEX
+			],
+		);
+		assert!(settle_call.get_dispatch_info().weight <= bp_millau::MAX_SETTLE_SWAP_WEIGHT);
+	}
+
+	#[test]
+	fn bridge_relay_proxy_only_allows_bridge_calls() {
+		use frame_support::traits::InstanceFilter;
+
+		let remark_call = Call::System(frame_system::Call::remark(vec![]));
+		let transfer_call = Call::Balances(pallet_balances::Call::transfer(Default::default(), 1));
+		let batch_call = Call::Utility(pallet_utility::Call::batch_all(vec![]));
+
+		assert!(ProxyType::Any.filter(&remark_call));
+		assert!(ProxyType::Any.filter(&transfer_call));
+		assert!(!ProxyType::BridgeRelay.filter(&remark_call));
+		assert!(!ProxyType::BridgeRelay.filter(&transfer_call));
+		assert!(ProxyType::BridgeRelay.filter(&batch_call));
+		assert!(ProxyType::Any.is_superset(&ProxyType::BridgeRelay));
+		assert!(!ProxyType::BridgeRelay.is_superset(&ProxyType::Any));
+	}
 }
@@ -34,12 +34,15 @@ use frame_support::{
 use sp_runtime::{traits::Zero, FixedPointNumber, FixedU128};
 use sp_std::{convert::TryFrom, ops::RangeInclusive};
 
-/// Initial value of `RialtoToMillauConversionRate` parameter.
+/// Initial value of the Rialto to Millau conversion rate, stored by the `BridgeRialtoConversionRate` pallet.
 pub const INITIAL_RIALTO_TO_MILLAU_CONVERSION_RATE: FixedU128 = FixedU128::from_inner(FixedU128::DIV);
 
 parameter_types! {
-	/// Rialto to Millau conversion rate. Initially we treat both tokens as equal.
-	pub storage RialtoToMillauConversionRate: FixedU128 = INITIAL_RIALTO_TO_MILLAU_CONVERSION_RATE;
+	/// Maximal size of the message that may be sent to Rialto.
+	pub MaximalMessageSizeToRialto: u32 = messages::source::maximal_message_size::<WithRialtoMessageBridge>();
+	/// Maximal dispatch weight of the message that may be sent to Rialto.
+	pub MaximalMessageDispatchWeightToRialto: Weight =
+		messages::target::maximal_incoming_message_dispatch_weight(bp_rialto::max_extrinsic_weight());
 }
 
 /// Message payload for Millau -> Rialto messages.
@@ -64,6 +67,7 @@ type ToRialtoMessagesDeliveryProof = messages::source::FromBridgedChainMessagesD
 pub type FromRialtoMessageDispatch = messages::target::FromBridgedChainMessageDispatch<
 	WithRialtoMessageBridge,
 	crate::Runtime,
+	pallet_balances::Pallet<crate::Runtime>,
 	pallet_bridge_dispatch::DefaultInstance,
 >;
 
@@ -78,8 +82,10 @@ impl MessageBridge for WithRialtoMessageBridge {
 	type BridgedChain = Rialto;
 
 	fn bridged_balance_to_this_balance(bridged_balance: bp_rialto::Balance) -> bp_millau::Balance {
-		bp_millau::Balance::try_from(RialtoToMillauConversionRate::get().saturating_mul_int(bridged_balance))
-			.unwrap_or(bp_millau::Balance::MAX)
+		bp_millau::Balance::try_from(
+			crate::BridgeRialtoConversionRate::conversion_rate().saturating_mul_int(bridged_balance),
+		)
+		.unwrap_or(bp_millau::Balance::MAX)
 	}
 }
 
@@ -104,7 +110,7 @@ impl messages::ThisChainWithMessages for Millau {
 	type Call = crate::Call;
 
 	fn is_outbound_lane_enabled(lane: &LaneId) -> bool {
-		*lane == [0, 0, 0, 0] || *lane == [0, 0, 0, 1]
+		*lane == LaneId([0, 0, 0, 0]) || *lane == LaneId([0, 0, 0, 1])
 	}
 
 	fn maximal_pending_messages_at_outbound_lane() -> MessageNonce {
@@ -112,9 +118,13 @@ impl messages::ThisChainWithMessages for Millau {
 	}
 
 	fn estimate_delivery_confirmation_transaction() -> MessageTransaction<Weight> {
-		let inbound_data_size =
-			InboundLaneData::<bp_millau::AccountId>::encoded_size_hint(bp_millau::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE, 1)
-				.unwrap_or(u32::MAX);
+		let inbound_data_size = InboundLaneData::<bp_millau::AccountId>::encoded_size_hint(
+			bp_millau::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE,
+			1,
+			1,
+			1,
+		)
+		.unwrap_or(u32::MAX);
 
 		MessageTransaction {
 			dispatch_weight: bp_millau::MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT,
@@ -252,8 +262,10 @@ pub enum MillauToRialtoMessagesParameter {
 impl MessagesParameter for MillauToRialtoMessagesParameter {
 	fn save(&self) {
 		match *self {
+			// the conversion rate is stored by the conversion rate pallet, so that it may be updated
+			// either by the messages pallet owner, or by the conversion rate oracle
 			MillauToRialtoMessagesParameter::RialtoToMillauConversionRate(ref conversion_rate) => {
-				RialtoToMillauConversionRate::set(conversion_rate)
+				pallet_bridge_conversion_rate::ConversionRate::<Runtime>::put(conversion_rate)
 			}
 		}
 	}
@@ -14,10 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
+use beefy_primitives::crypto::AuthorityId as BeefyId;
 use bp_rialto::derive_account_from_millau_id;
 use rialto_runtime::{
-	AccountId, AuraConfig, BalancesConfig, BridgeKovanConfig, BridgeRialtoPoAConfig, GenesisConfig, GrandpaConfig,
-	SessionConfig, SessionKeys, Signature, SudoConfig, SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, BalancesConfig, BeefyConfig, BridgeKovanConfig, BridgeRialtoPoAConfig, GenesisConfig,
+	GrandpaConfig, SessionConfig, SessionKeys, Signature, SudoConfig, SystemConfig, WASM_BINARY,
 };
 use serde_json::json;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -57,10 +58,11 @@ where
 }
 
 /// Helper function to generate an authority key for Aura
-pub fn get_authority_keys_from_seed(s: &str) -> (AccountId, AuraId, GrandpaId) {
+pub fn get_authority_keys_from_seed(s: &str) -> (AccountId, AuraId, BeefyId, GrandpaId) {
 	(
 		get_account_id_from_seed::<sr25519::Public>(s),
 		get_from_seed::<AuraId>(s),
+		get_from_seed::<BeefyId>(s),
 		get_from_seed::<GrandpaId>(s),
 	)
 }
@@ -142,6 +144,8 @@ impl Alternative {
 								rialto_runtime::Runtime,
 								pallet_bridge_messages::DefaultInstance,
 							>::relayer_fund_account_id(),
+							pallet_bridge_relayers::relayers_fund_account_id::<rialto_runtime::Runtime, ()>(),
+							rialto_runtime::PoAHeadersRewardsPot::get(),
 							derive_account_from_millau_id(bp_runtime::SourceAccount::Account(
 								get_account_id_from_seed::<sr25519::Public>("Bob"),
 							)),
@@ -165,12 +169,12 @@ impl Alternative {
 	}
 }
 
-fn session_keys(aura: AuraId, grandpa: GrandpaId) -> SessionKeys {
-	SessionKeys { aura, grandpa }
+fn session_keys(aura: AuraId, beefy: BeefyId, grandpa: GrandpaId) -> SessionKeys {
+	SessionKeys { aura, beefy, grandpa }
 }
 
 fn testnet_genesis(
-	initial_authorities: Vec<(AccountId, AuraId, GrandpaId)>,
+	initial_authorities: Vec<(AccountId, AuraId, BeefyId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
 	_enable_println: bool,
@@ -188,6 +192,9 @@ fn testnet_genesis(
 		},
 		pallet_bridge_eth_poa_Instance1: load_rialto_poa_bridge_config(),
 		pallet_bridge_eth_poa_Instance2: load_kovan_bridge_config(),
+		pallet_beefy: BeefyConfig {
+			authorities: Vec::new(),
+		},
 		pallet_grandpa: GrandpaConfig {
 			authorities: Vec::new(),
 		},
@@ -195,7 +202,13 @@ fn testnet_genesis(
 		pallet_session: SessionConfig {
 			keys: initial_authorities
 				.iter()
-				.map(|x| (x.0.clone(), x.0.clone(), session_keys(x.1.clone(), x.2.clone())))
+				.map(|x| {
+					(
+						x.0.clone(),
+						x.0.clone(),
+						session_keys(x.1.clone(), x.2.clone(), x.3.clone()),
+					)
+				})
 				.collect::<Vec<_>>(),
 		},
 	}
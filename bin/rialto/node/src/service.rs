@@ -194,6 +194,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		.network
 		.extra_sets
 		.push(sc_finality_grandpa::grandpa_peers_set_config());
+	config.network.extra_sets.push(beefy_gadget::beefy_peers_set_config());
 
 	let (network, network_status_sinks, system_rpc_tx, network_starter) =
 		sc_service::build_network(sc_service::BuildNetworkParams {
@@ -217,9 +218,16 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	let (beefy_commitment_link, beefy_commitment_stream) =
+		beefy_gadget::notification::BeefySignedCommitmentStream::channel();
+
 	let rpc_extensions_builder = {
 		use sc_finality_grandpa::FinalityProofProvider as GrandpaFinalityProofProvider;
 
+		use beefy_gadget_rpc::{BeefyApi, BeefyRpcHandler};
+		use pallet_bridge_messages_rpc::{Messages, MessagesApi, MessagesInstance};
+		use pallet_contracts_rpc::{Contracts, ContractsApi};
+		use pallet_mmr_rpc::{Mmr, MmrApi};
 		use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 		use sc_finality_grandpa_rpc::{GrandpaApi, GrandpaRpcHandler};
 		use sc_rpc::DenyUnsafe;
@@ -246,13 +254,34 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 			io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
 				client.clone(),
 			)));
+			io.extend_with(ContractsApi::to_delegate(Contracts::new(client.clone())));
 			io.extend_with(GrandpaApi::to_delegate(GrandpaRpcHandler::new(
 				shared_authority_set.clone(),
 				shared_voter_state.clone(),
 				justification_stream.clone(),
-				subscription_executor,
+				subscription_executor.clone(),
 				finality_proof_provider.clone(),
 			)));
+			io.extend_with(BeefyApi::to_delegate(BeefyRpcHandler::new(
+				beefy_commitment_stream.clone(),
+				subscription_executor,
+			)));
+			io.extend_with(MmrApi::to_delegate(Mmr::new(client.clone())));
+			io.extend_with(MessagesApi::to_delegate(Messages::<
+				_,
+				_,
+				FullBackend,
+				<rialto_runtime::Runtime as pallet_bridge_messages::Config<
+					rialto_runtime::WithMillauMessagesInstance,
+				>>::InboundRelayer,
+				rialto_runtime::Balance,
+			>::new(
+				client.clone(),
+				vec![(
+					"Millau".into(),
+					MessagesInstance::new::<rialto_runtime::Runtime, rialto_runtime::WithMillauMessagesInstance>(),
+				)],
+			)));
 
 			io
 		})
@@ -267,7 +296,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		rpc_extensions_builder,
 		on_demand: None,
 		remote_blockchain: None,
-		backend,
+		backend: backend.clone(),
 		network_status_sinks,
 		system_rpc_tx,
 		config,
@@ -289,7 +318,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		let raw_slot_duration = slot_duration.slot_duration();
 		let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _>(StartAuraParams {
 			slot_duration,
-			client,
+			client: client.clone(),
 			select_chain,
 			block_import,
 			proposer_factory,
@@ -325,6 +354,22 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		None
 	};
 
+	let beefy_params = beefy_gadget::BeefyParams {
+		client,
+		backend,
+		key_store: keystore.clone(),
+		network: network.clone(),
+		signed_commitment_sender: beefy_commitment_link,
+		min_block_delta: 4,
+		prometheus_registry: prometheus_registry.clone(),
+	};
+
+	// Start the BEEFY bridge gadget.
+	task_manager.spawn_essential_handle().spawn_blocking(
+		"beefy-gadget",
+		beefy_gadget::start_beefy_gadget::<_, _, _, _>(beefy_params),
+	);
+
 	let grandpa_config = sc_finality_grandpa::Config {
 		// FIXME #1578 make this available through chainspec
 		gossip_duration: Duration::from_millis(333),
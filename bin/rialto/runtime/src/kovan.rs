@@ -32,6 +32,9 @@ frame_support::parameter_types! {
 		kovan_aura_configuration();
 	pub BridgeValidatorsConfiguration: ValidatorsConfiguration =
 		kovan_validators_configuration();
+	/// Number of blocks (after full headers are pruned) for which we remember hashes of finalized
+	/// headers. It is equivalent of ~7 days of finalized blocks on current Kovan chain.
+	pub const AncientHeadersToKeep: u64 = 7 * FINALIZED_HEADERS_TO_KEEP;
 }
 
 /// Max number of finalized headers to keep. It is equivalent of ~24 hours of
@@ -40,14 +40,18 @@ pub mod rialto_poa;
 
 use crate::millau_messages::{ToMillauMessagePayload, WithMillauMessageBridge};
 
+use beefy_primitives::{crypto::AuthorityId as BeefyId, mmr::MmrLeafVersion, ValidatorSet};
 use bridge_runtime_common::messages::{source::estimate_message_dispatch_and_delivery_fee, MessageBridge};
 use codec::Decode;
+use frame_support::PalletId;
+use pallet_contracts::weights::WeightInfo as _;
 use pallet_grandpa::{fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
+use pallet_mmr_primitives::{DataOrHash, EncodableOpaqueLeaf, Error as MmrError, LeafDataProvider, Proof as MmrProof};
 use pallet_transaction_payment::{FeeDetails, RuntimeDispatchInfo};
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
-use sp_runtime::traits::{Block as BlockT, IdentityLookup, NumberFor, OpaqueKeys};
+use sp_runtime::traits::{AccountIdConversion, Block as BlockT, IdentityLookup, Keccak256, NumberFor, OpaqueKeys};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	transaction_validity::{TransactionSource, TransactionValidity},
@@ -72,8 +76,13 @@ pub use pallet_bridge_currency_exchange::Call as BridgeCurrencyExchangeCall;
 pub use pallet_bridge_eth_poa::Call as BridgeEthPoACall;
 pub use pallet_bridge_grandpa::Call as BridgeGrandpaMillauCall;
 pub use pallet_bridge_messages::Call as MessagesCall;
+pub use pallet_bridge_relayers::Call as RelayersCall;
+pub use pallet_bridge_token_swap::Call as TokenSwapCall;
+pub use pallet_contracts::Call as ContractsCall;
+pub use pallet_proxy::Call as ProxyCall;
 pub use pallet_sudo::Call as SudoCall;
 pub use pallet_timestamp::Call as TimestampCall;
+pub use pallet_utility::Call as UtilityCall;
 
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
@@ -128,6 +137,7 @@ pub mod opaque {
 impl_opaque_keys! {
 	pub struct SessionKeys {
 		pub aura: Aura,
+		pub beefy: Beefy,
 		pub grandpa: Grandpa,
 	}
 }
@@ -216,6 +226,17 @@ impl pallet_aura::Config for Runtime {
 	type AuthorityId = AuraId;
 }
 
+parameter_types! {
+	/// Reward that is paid to the submitter of every finalized PoA header.
+	pub const PoAHeaderSubmitterReward: Balance = 1_000;
+	/// Account that pays rewards to PoA headers submitters.
+	pub PoAHeadersRewardsPot: AccountId = sp_io::hashing::blake2_256(b"pallet-bridge-eth-poa/rewards-pot").into();
+}
+
+/// Rewards for submitting PoA headers that are paid from the `PoAHeadersRewardsPot` account.
+pub type PoAHeadersRewards =
+	pallet_bridge_eth_poa::FinalityRewardsFromPot<Runtime, Balances, PoAHeadersRewardsPot, PoAHeaderSubmitterReward>;
+
 type RialtoPoA = pallet_bridge_eth_poa::Instance1;
 impl pallet_bridge_eth_poa::Config<RialtoPoA> for Runtime {
 	type AuraConfiguration = rialto_poa::BridgeAuraConfiguration;
@@ -223,7 +244,8 @@ impl pallet_bridge_eth_poa::Config<RialtoPoA> for Runtime {
 	type ValidatorsConfiguration = rialto_poa::BridgeValidatorsConfiguration;
 	type PruningStrategy = rialto_poa::PruningStrategy;
 	type ChainTime = rialto_poa::ChainTime;
-	type OnHeadersSubmitted = ();
+	type OnHeadersSubmitted = PoAHeadersRewards;
+	type AncientHeadersToKeep = rialto_poa::AncientHeadersToKeep;
 }
 
 type Kovan = pallet_bridge_eth_poa::Instance2;
@@ -233,7 +255,8 @@ impl pallet_bridge_eth_poa::Config<Kovan> for Runtime {
 	type ValidatorsConfiguration = kovan::BridgeValidatorsConfiguration;
 	type PruningStrategy = kovan::PruningStrategy;
 	type ChainTime = kovan::ChainTime;
-	type OnHeadersSubmitted = ();
+	type OnHeadersSubmitted = PoAHeadersRewards;
+	type AncientHeadersToKeep = kovan::AncientHeadersToKeep;
 }
 
 type RialtoCurrencyExchange = pallet_bridge_currency_exchange::Instance1;
@@ -258,6 +281,12 @@ impl pallet_bridge_currency_exchange::Config<KovanCurrencyExchange> for Runtime
 	type DepositInto = DepositInto;
 }
 
+parameter_types! {
+	/// Number of blocks after runtime upgrade, during which bridged messages that have been
+	/// encoded using the previous runtime version are still dispatched.
+	pub const DispatchSpecVersionGracePeriod: BlockNumber = bp_rialto::HOURS;
+}
+
 impl pallet_bridge_dispatch::Config for Runtime {
 	type Event = Event;
 	type MessageId = (bp_messages::LaneId, bp_messages::MessageNonce);
@@ -268,6 +297,8 @@ impl pallet_bridge_dispatch::Config for Runtime {
 	type TargetChainAccountPublic = MultiSigner;
 	type TargetChainSignature = MultiSignature;
 	type AccountIdConverter = bp_rialto::AccountIdConverter;
+	type ProxyCheck = BridgeProxyCheck;
+	type SpecVersionGracePeriod = DispatchSpecVersionGracePeriod;
 }
 
 pub struct DepositInto;
@@ -384,11 +415,142 @@ impl pallet_transaction_payment::Config for Runtime {
 	type FeeMultiplierUpdate = ();
 }
 
+parameter_types! {
+	pub const TombstoneDeposit: Balance = 16 * ExistentialDeposit::get();
+	pub const DepositPerContract: Balance = TombstoneDeposit::get();
+	pub const DepositPerStorageByte: Balance = 1;
+	pub const DepositPerStorageItem: Balance = 64;
+	pub RentFraction: Perbill = Perbill::from_rational(1u32, 30 * bp_rialto::time_units::DAYS);
+	pub const SurchargeReward: Balance = 150 * ExistentialDeposit::get();
+	pub const SignedClaimHandicap: BlockNumber = 2;
+	// The lazy deletion runs inside on_initialize.
+	pub DeletionWeightLimit: Weight = bp_rialto::AVERAGE_ON_INITIALIZE_RATIO
+		* bp_rialto::BlockWeights::get().max_block;
+	// The weight needed for decoding the queue should be less or equal than a fifth
+	// of the overall weight dedicated to the lazy deletion.
+	pub DeletionQueueDepth: u32 = ((DeletionWeightLimit::get() / (
+		<Runtime as pallet_contracts::Config>::WeightInfo::on_initialize_per_queue_item(1)
+			- <Runtime as pallet_contracts::Config>::WeightInfo::on_initialize_per_queue_item(0)
+	)) / 5) as u32;
+	pub ContractsSchedule: pallet_contracts::Schedule<Runtime> = Default::default();
+}
+
+impl pallet_contracts::Config for Runtime {
+	type Time = Timestamp;
+	type Randomness = RandomnessCollectiveFlip;
+	type Currency = Balances;
+	type Event = Event;
+	type RentPayment = ();
+	type SignedClaimHandicap = SignedClaimHandicap;
+	type TombstoneDeposit = TombstoneDeposit;
+	type DepositPerContract = DepositPerContract;
+	type DepositPerStorageByte = DepositPerStorageByte;
+	type DepositPerStorageItem = DepositPerStorageItem;
+	type RentFraction = RentFraction;
+	type SurchargeReward = SurchargeReward;
+	type CallStack = [pallet_contracts::Frame<Self>; 31];
+	type WeightPrice = pallet_transaction_payment::Pallet<Self>;
+	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Self>;
+	type ChainExtension = ();
+	type DeletionQueueDepth = DeletionQueueDepth;
+	type DeletionWeightLimit = DeletionWeightLimit;
+	type Schedule = ContractsSchedule;
+}
+
 impl pallet_sudo::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
 }
 
+impl pallet_utility::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const ProxyDepositBase: Balance = 1_000;
+	pub const ProxyDepositFactor: Balance = 100;
+	pub const MaxProxies: u16 = 32;
+	pub const MaxPending: u16 = 32;
+	pub const AnnouncementDepositBase: Balance = 1_000;
+	pub const AnnouncementDepositFactor: Balance = 100;
+}
+
+/// The type used to represent the kinds of proxying allowed.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, codec::Encode, codec::Decode, sp_runtime::RuntimeDebug)]
+pub enum ProxyType {
+	/// Proxy may dispatch any call on behalf of the proxied account.
+	Any,
+	/// Proxy may only dispatch bridge calls that are submitted by the relayers.
+	BridgeRelay,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl frame_support::traits::InstanceFilter<Call> for ProxyType {
+	fn filter(&self, c: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::BridgeRelay => matches!(
+				c,
+				Call::BridgeMillauGrandpa(..)
+					| Call::BridgeMillauMessages(..)
+					| Call::BridgeMillauRelayers(..)
+					| Call::Utility(..)
+			),
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			(ProxyType::BridgeRelay, ProxyType::BridgeRelay) => true,
+		}
+	}
+}
+
+impl pallet_proxy::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
+	type WeightInfo = ();
+	type MaxPending = MaxPending;
+	type CallHasher = Hashing;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+/// Proxy relationship check, backed by the proxy pallet.
+///
+/// Used by the dispatch pallet to verify that the spawner of the pure proxy account is still
+/// its proxy.
+pub struct BridgeProxyCheck;
+
+impl pallet_bridge_dispatch::ProxyCheck<AccountId, BlockNumber> for BridgeProxyCheck {
+	type ProxyType = ProxyType;
+
+	fn any_proxy_type() -> ProxyType {
+		ProxyType::Any
+	}
+
+	fn is_proxy(proxied: &AccountId, delegate: &AccountId, proxy_type: &ProxyType, delay: &BlockNumber) -> bool {
+		Proxy::proxies(proxied)
+			.0
+			.iter()
+			.any(|proxy| proxy.delegate == *delegate && proxy.proxy_type == *proxy_type && proxy.delay == *delay)
+	}
+}
+
 parameter_types! {
 	pub const Period: BlockNumber = bp_rialto::SESSION_LENGTH;
 	pub const Offset: BlockNumber = 0;
@@ -408,12 +570,44 @@ impl pallet_session::Config for Runtime {
 	type WeightInfo = ();
 }
 
+impl pallet_beefy::Config for Runtime {
+	type AuthorityId = BeefyId;
+}
+
+impl pallet_mmr::Config for Runtime {
+	const INDEXING_PREFIX: &'static [u8] = b"mmr";
+	type Hashing = Keccak256;
+	type Hash = <Keccak256 as sp_runtime::traits::Hash>::Output;
+	type OnNewRoot = pallet_beefy_mmr::DepositBeefyDigest<Runtime>;
+	type WeightInfo = ();
+	type LeafData = pallet_beefy_mmr::Pallet<Runtime>;
+}
+
+parameter_types! {
+	/// Version of the produced MMR leaf.
+	///
+	/// The `major` part should only be updated if the previous MMR leaf format can't be decoded
+	/// from the payload. The `minor` part should be updated if fields are added to the leaf.
+	pub LeafVersion: MmrLeafVersion = MmrLeafVersion::new(0, 0);
+}
+
+impl pallet_beefy_mmr::Config for Runtime {
+	type LeafVersion = LeafVersion;
+	type BeefyAuthorityToMerkleLeaf = pallet_beefy_mmr::BeefyEcdsaToEthereum;
+	type ParachainHeads = ();
+}
+
 parameter_types! {
 	/// This is a pretty unscientific cap.
 	///
 	/// Note that once this is hit the pallet will essentially throttle incoming requests down to one
 	/// call per block.
 	pub const MaxRequests: u32 = 50;
+
+	/// Maximal number of headers in the `submit_finality_proof_with_ancestry` call.
+	///
+	/// The pallet verifies all headers of the ancestry, so the ancestry needs to be bounded.
+	pub const MaxAncestryLength: u32 = 1024;
 }
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -441,6 +635,7 @@ impl pallet_bridge_grandpa::Config for Runtime {
 	type BridgedChain = bp_millau::Millau;
 	type MaxRequests = MaxRequests;
 	type HeadersToKeep = HeadersToKeep;
+	type MaxAncestryLength = MaxAncestryLength;
 	type WeightInfo = pallet_bridge_grandpa::weights::RialtoWeight<Runtime>;
 }
 
@@ -469,6 +664,8 @@ impl pallet_bridge_messages::Config<WithMillauMessagesInstance> for Runtime {
 	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
 
+	type MaximalOutboundPayloadSize = millau_messages::MaximalMessageSizeToMillau;
+	type MaximalOutboundDispatchWeight = millau_messages::MaximalMessageDispatchWeightToMillau;
 	type OutboundPayload = crate::millau_messages::ToMillauMessagePayload;
 	type OutboundMessageFee = Balance;
 
@@ -489,6 +686,104 @@ impl pallet_bridge_messages::Config<WithMillauMessagesInstance> for Runtime {
 
 	type SourceHeaderChain = crate::millau_messages::Millau;
 	type MessageDispatch = crate::millau_messages::FromMillauMessageDispatch;
+	type OnMessagesDelivered = BridgeMillauRelayers;
+}
+
+parameter_types! {
+	pub const RelayersPalletId: PalletId = PalletId(*b"brl/rlrs");
+	pub const RelayerRegistrationBond: Balance = 1_000_000;
+	pub const MaxRelayers: u32 = 16;
+	pub const RelayersSlotLength: BlockNumber = bp_rialto::MINUTES;
+	pub const DeliveryReward: Balance = 1_000;
+	pub const OutOfSlotRewardRatio: Perbill = Perbill::from_percent(10);
+	pub const RegisteredRelayerRewardBoost: Perbill = Perbill::from_percent(20);
+	pub const MaxAbandonedSlots: u32 = 3;
+	pub const SlotAbandonmentSlash: Balance = 100_000;
+	pub const InvalidBridgeTransactionSlash: Balance = 100_000;
+	pub const RelayerRewardsRetentionPeriod: BlockNumber = 30 * bp_rialto::DAYS;
+	pub const MaxFinishedRelayerSlotsPerBlock: u32 = 16;
+}
+
+/// Bridge transactions of registered relayers that are checked by the
+/// `SlashInvalidBridgeTransactions` signed extension.
+pub struct BridgeTransactionsFilter;
+
+impl frame_support::traits::Filter<Call> for BridgeTransactionsFilter {
+	fn filter(call: &Call) -> bool {
+		matches!(
+			call,
+			Call::BridgeMillauMessages(MessagesCall::receive_messages_proof(..))
+				| Call::BridgeMillauMessages(MessagesCall::receive_messages_delivery_proof(..))
+		)
+	}
+}
+
+/// Errors of bridge transactions that mean that the registered relayer has submitted invalid proof.
+///
+/// Other errors (e.g. when the relayer has lost the race to deliver the same messages) are not
+/// slashed by the `SlashInvalidBridgeTransactions` signed extension.
+pub struct InvalidBridgeProofErrors;
+
+impl frame_support::traits::Filter<sp_runtime::DispatchError> for InvalidBridgeProofErrors {
+	fn filter(error: &sp_runtime::DispatchError) -> bool {
+		type MessagesError = pallet_bridge_messages::Error<Runtime, WithMillauMessagesInstance>;
+
+		*error == MessagesError::InvalidMessagesProof.into()
+			|| *error == MessagesError::InvalidMessagesDeliveryProof.into()
+	}
+}
+
+impl pallet_bridge_relayers::Config for Runtime {
+	type Event = Event;
+	type PalletId = RelayersPalletId;
+	type Currency = pallet_balances::Pallet<Runtime>;
+	type RegistrationBond = RelayerRegistrationBond;
+	type MaxRelayers = MaxRelayers;
+	type SlotLength = RelayersSlotLength;
+	type DeliveryReward = DeliveryReward;
+	type OutOfSlotRewardRatio = OutOfSlotRewardRatio;
+	type RegisteredRewardBoost = RegisteredRelayerRewardBoost;
+	type MaxAbandonedSlots = MaxAbandonedSlots;
+	type SlotAbandonmentSlash = SlotAbandonmentSlash;
+	type InvalidTransactionSlash = InvalidBridgeTransactionSlash;
+	type BridgeTransactionsFilter = BridgeTransactionsFilter;
+	type InvalidProofErrors = InvalidBridgeProofErrors;
+	type RewardsRetentionPeriod = RelayerRewardsRetentionPeriod;
+	type MaxFinishedSlotsPerBlock = MaxFinishedRelayerSlotsPerBlock;
+}
+
+parameter_types! {
+	pub const TokenSwapPalletId: PalletId = PalletId(*b"bsw/swap");
+	pub const MillauChainId: bp_runtime::ChainId = bp_runtime::MILLAU_CHAIN_ID;
+	pub const TokenSwapMessagesLane: bp_messages::LaneId = bp_messages::LaneId([0, 0, 0, 0]);
+	pub MillauTokenSwapPalletAccountId: bp_millau::AccountId = TokenSwapPalletId::get().into_account();
+	pub const MillauTokenSwapPalletIndex: u8 = bp_millau::TOKEN_SWAP_PALLET_INDEX;
+	pub const MillauMaxClaimSwapWeight: Weight = bp_millau::MAX_CLAIM_SWAP_WEIGHT;
+	pub const MillauMaxSettleSwapWeight: Weight = bp_millau::MAX_SETTLE_SWAP_WEIGHT;
+}
+
+impl pallet_bridge_token_swap::Config for Runtime {
+	type Event = Event;
+
+	type PalletId = TokenSwapPalletId;
+	type BridgedChainId = MillauChainId;
+	type OutboundMessageLaneId = TokenSwapMessagesLane;
+
+	type MessagesBridge = pallet_bridge_messages::Pallet<Runtime, WithMillauMessagesInstance>;
+	type ThisCurrency = pallet_balances::Pallet<Runtime>;
+	type AccountIdConverter = bp_rialto::AccountIdConverter;
+
+	type BridgedChain = bp_millau::Millau;
+	type BridgedBalance = bp_millau::Balance;
+	type BridgedAccountId = bp_millau::AccountId;
+	type BridgedAccountPublic = bp_millau::AccountSigner;
+	type BridgedAccountSignature = bp_millau::Signature;
+	type BridgedPalletAccountId = MillauTokenSwapPalletAccountId;
+	type BridgedTokenSwapCalls = pallet_bridge_token_swap::BridgedTokenSwapPalletCalls<
+		MillauTokenSwapPalletIndex,
+		MillauMaxClaimSwapWeight,
+		MillauMaxSettleSwapWeight,
+	>;
 }
 
 construct_runtime!(
@@ -501,7 +796,7 @@ construct_runtime!(
 		BridgeKovan: pallet_bridge_eth_poa::<Instance2>::{Pallet, Call, Config, Storage, ValidateUnsigned},
 		BridgeRialtoCurrencyExchange: pallet_bridge_currency_exchange::<Instance1>::{Pallet, Call},
 		BridgeKovanCurrencyExchange: pallet_bridge_currency_exchange::<Instance2>::{Pallet, Call},
-		BridgeMillauGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage},
+		BridgeMillauGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage, ValidateUnsigned},
 		BridgeDispatch: pallet_bridge_dispatch::{Pallet, Event<T>},
 		BridgeMillauMessages: pallet_bridge_messages::{Pallet, Call, Storage, Event<T>},
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
@@ -514,6 +809,16 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
 		ShiftSessionManager: pallet_shift_session_manager::{Pallet},
+		Contracts: pallet_contracts::{Pallet, Call, Storage, Event<T>},
+		BridgeMillauTokenSwap: pallet_bridge_token_swap::{Pallet, Call, Storage, Event<T>},
+		Utility: pallet_utility::{Pallet, Call, Event},
+		Proxy: pallet_proxy::{Pallet, Call, Storage, Event<T>},
+		BridgeMillauRelayers: pallet_bridge_relayers::{Pallet, Call, Storage, Event<T>},
+
+		// BEEFY bridges support.
+		Beefy: pallet_beefy::{Pallet, Storage, Config<T>},
+		Mmr: pallet_mmr::{Pallet, Storage},
+		MmrLeaf: pallet_beefy_mmr::{Pallet, Storage},
 	}
 );
 
@@ -536,6 +841,7 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_bridge_relayers::SlashInvalidBridgeTransactions<Runtime>,
 );
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
@@ -695,6 +1001,79 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_contracts_rpc_runtime_api::ContractsApi<Block, AccountId, Balance, BlockNumber, Hash> for Runtime {
+		fn call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractExecResult {
+			Contracts::bare_call(origin, dest, value, gas_limit, input_data, true)
+		}
+
+		fn instantiate(
+			origin: AccountId,
+			endowment: Balance,
+			gas_limit: u64,
+			code: pallet_contracts_primitives::Code<Hash>,
+			data: Vec<u8>,
+			salt: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId, BlockNumber> {
+			Contracts::bare_instantiate(origin, endowment, gas_limit, code, data, salt, true, true)
+		}
+
+		fn get_storage(
+			address: AccountId,
+			key: [u8; 32],
+		) -> pallet_contracts_primitives::GetStorageResult {
+			Contracts::get_storage(address, key)
+		}
+
+		fn rent_projection(
+			address: AccountId,
+		) -> pallet_contracts_primitives::RentProjectionResult<BlockNumber> {
+			Contracts::rent_projection(address)
+		}
+	}
+
+	impl beefy_primitives::BeefyApi<Block> for Runtime {
+		fn validator_set() -> ValidatorSet<BeefyId> {
+			Beefy::validator_set()
+		}
+	}
+
+	impl pallet_mmr_primitives::MmrApi<Block, Hash> for Runtime {
+		fn generate_proof(leaf_index: u64)
+			-> Result<(EncodableOpaqueLeaf, MmrProof<Hash>), MmrError>
+		{
+			Mmr::generate_proof(leaf_index)
+				.map(|(leaf, proof)| (EncodableOpaqueLeaf::from_leaf(&leaf), proof))
+		}
+
+		fn verify_proof(leaf: EncodableOpaqueLeaf, proof: MmrProof<Hash>)
+			-> Result<(), MmrError>
+		{
+			type Leaf = <<Runtime as pallet_mmr::Config>::LeafData as LeafDataProvider>::LeafData;
+
+			let leaf: Leaf = leaf
+				.into_opaque_leaf()
+				.try_decode()
+				.ok_or(MmrError::Verify)?;
+			Mmr::verify_leaf(leaf, proof)
+		}
+
+		fn verify_proof_stateless(
+			root: Hash,
+			leaf: EncodableOpaqueLeaf,
+			proof: MmrProof<Hash>
+		) -> Result<(), MmrError> {
+			type MmrHashing = <Runtime as pallet_mmr::Config>::Hashing;
+			let node = DataOrHash::Data(leaf.into_opaque_leaf());
+			pallet_mmr::verify_leaf_proof::<MmrHashing, _>(root, node, proof)
+		}
+	}
+
 	impl sp_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			SessionKeys::generate(seed)
@@ -749,17 +1128,22 @@ impl_runtime_apis! {
 			).ok()
 		}
 
-		fn messages_dispatch_weight(
+		fn message_details(
 			lane: bp_messages::LaneId,
 			begin: bp_messages::MessageNonce,
 			end: bp_messages::MessageNonce,
-		) -> Vec<(bp_messages::MessageNonce, Weight, u32)> {
+		) -> Vec<bp_messages::MessageDetails<Balance>> {
 			(begin..=end).filter_map(|nonce| {
-				let encoded_payload = BridgeMillauMessages::outbound_message_payload(lane, nonce)?;
+				let message_data = BridgeMillauMessages::outbound_message_data(lane, nonce)?;
 				let decoded_payload = millau_messages::ToMillauMessagePayload::decode(
-					&mut &encoded_payload[..]
+					&mut &message_data.payload[..]
 				).ok()?;
-				Some((nonce, decoded_payload.weight, encoded_payload.len() as _))
+				Some(bp_messages::MessageDetails {
+					nonce,
+					dispatch_weight: bp_runtime::DeclaredWeight::declared_weight(&decoded_payload),
+					size: message_data.payload.len() as _,
+					delivery_and_dispatch_fee: message_data.fee,
+				})
 			})
 			.collect()
 		}
@@ -785,6 +1169,14 @@ impl_runtime_apis! {
 		fn unrewarded_relayers_state(lane: bp_messages::LaneId) -> bp_messages::UnrewardedRelayersState {
 			BridgeMillauMessages::inbound_unrewarded_relayers_state(lane)
 		}
+
+		fn inbound_lane_data(lane: bp_messages::LaneId) -> bp_messages::InboundLaneData<bp_millau::AccountId> {
+			BridgeMillauMessages::inbound_lane_data(lane)
+		}
+
+		fn delivery_limits() -> bp_messages::MessagesDeliveryLimits {
+			BridgeMillauMessages::inbound_delivery_limits()
+		}
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
@@ -892,6 +1284,8 @@ impl_runtime_apis! {
 					let message = ToMillauMessagePayload {
 						spec_version: 0,
 						weight: params.size as _,
+						delivery_deadline: None,
+						dispatch_fee_payment: bp_message_dispatch::DispatchFeePayment::AtSourceChain,
 						origin: dispatch_origin,
 						call: message_payload,
 					};
@@ -958,6 +1352,8 @@ impl_runtime_apis! {
 						bp_message_dispatch::MessagePayload {
 							spec_version: VERSION.spec_version,
 							weight: call_weight,
+							delivery_deadline: None,
+							dispatch_fee_payment: bp_message_dispatch::DispatchFeePayment::AtSourceChain,
 							origin: bp_message_dispatch::CallOrigin::<
 								bp_millau::AccountId,
 								MultiSigner,
@@ -1049,7 +1445,9 @@ where
 mod tests {
 	use super::*;
 	use bp_currency_exchange::DepositInto;
+	use bp_runtime::integrity::{ensure_chain_constants, ChainConstants};
 	use bridge_runtime_common::messages;
+	use frame_support::traits::Filter;
 
 	fn run_deposit_into_test(test: impl Fn(AccountId) -> Balance) {
 		let mut ext: sp_io::TestExternalities = SystemConfig::default().build_storage::<Runtime>().unwrap().into();
@@ -1086,6 +1484,18 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn ensure_rialto_chain_constants_are_correct() {
+		let declared = ChainConstants {
+			max_extrinsic_size: bp_rialto::max_extrinsic_size(),
+			max_extrinsic_weight: bp_rialto::max_extrinsic_weight(),
+			session_length: Some(bp_rialto::SESSION_LENGTH.into()),
+			ss58_prefix: bp_rialto::SS58_PREFIX,
+		};
+		let actual = ChainConstants::from_runtime::<Runtime>(Some(Period::get().into()));
+		assert_eq!(ensure_chain_constants(&declared, &actual), Ok(()));
+	}
+
 	#[test]
 	fn ensure_rialto_message_lane_weights_are_correct() {
 		type Weights = pallet_bridge_messages::weights::RialtoWeight<Runtime>;
@@ -1109,6 +1519,8 @@ mod tests {
 		let max_incoming_inbound_lane_data_proof_size = bp_messages::InboundLaneData::<()>::encoded_size_hint(
 			bp_rialto::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE,
 			bp_millau::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE as _,
+			bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE as _,
+			bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE as _,
 		)
 		.unwrap_or(u32::MAX);
 		pallet_bridge_messages::ensure_able_to_receive_confirmation::<Weights>(
@@ -1120,6 +1532,22 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn only_invalid_proof_errors_are_slashed() {
+		type MessagesError = pallet_bridge_messages::Error<Runtime, WithMillauMessagesInstance>;
+
+		assert!(InvalidBridgeProofErrors::filter(
+			&MessagesError::InvalidMessagesProof.into()
+		));
+		assert!(InvalidBridgeProofErrors::filter(
+			&MessagesError::InvalidMessagesDeliveryProof.into()
+		));
+		assert!(!InvalidBridgeProofErrors::filter(
+			&MessagesError::InvalidMessagesDispatchWeight.into()
+		));
+		assert!(!InvalidBridgeProofErrors::filter(&MessagesError::Halted.into()));
+	}
+
 	#[test]
 	fn deposit_into_existing_account_works() {
 		run_deposit_into_test(|existing_account| {
@@ -1157,4 +1585,77 @@ mod tests {
 			additional_amount
 		});
 	}
+
+	#[test]
+	fn ensure_token_swap_claim_call_is_correct() {
+		use codec::Encode;
+		use frame_support::weights::GetDispatchInfo;
+
+		let claim_call = Call::BridgeMillauTokenSwap(pallet_bridge_token_swap::Call::claim_swap(
+			pallet_bridge_token_swap::TokenSwap {
+				cancel_after: 0,
+				source_account: Default::default(),
+				source_balance: 0,
+				target_account: Default::default(),
+				target_balance: 0,
+			},
+			0,
+		));
+
+		assert_eq!(
+			claim_call.encode()[..2],
+			[
+				bp_rialto::TOKEN_SWAP_PALLET_INDEX,
+				pallet_bridge_token_swap::CLAIM_SWAP_CALL_INDEX
+			],
+		);
+		assert!(claim_call.get_dispatch_info().weight <= bp_rialto::MAX_CLAIM_SWAP_WEIGHT);
+	}
+
+	#[test]
+	fn ensure_token_swap_settle_call_is_correct() {
+		use codec::Encode;
+		use frame_support::weights::GetDispatchInfo;
+
+		let settle_call =
+			Call::BridgeMillauTokenSwap(pallet_bridge_token_swap::Call::settle_swap(Default::default(), true));
+
+		assert_eq!(
+			settle_call.encode()[..2],
+			[
+				bp_rialto::TOKEN_SWAP_PALLET_INDEX,
+				pallet_bridge_token_swap::SETTLE_SWAP_CALL_INDEX
+			],
+		);
+		assert!(settle_call.get_dispatch_info().weight <= bp_rialto::MAX_SETTLE_SWAP_WEIGHT);
+	}
+
+	#[test]
+	fn bridge_proxy_check_uses_proxy_pallet() {
+		use pallet_bridge_dispatch::ProxyCheck;
+
+		let mut ext: sp_io::TestExternalities = SystemConfig::default().build_storage::<Runtime>().unwrap().into();
+		ext.execute_with(|| {
+			let proxied = AccountId::from([1u8; 32]);
+			let any_proxy = AccountId::from([2u8; 32]);
+			let relay_proxy = AccountId::from([3u8; 32]);
+			Balances::make_free_balance_be(&proxied, 1_000_000);
+
+			assert!(!BridgeProxyCheck::is_proxy(&proxied, &any_proxy, &ProxyType::Any, &0));
+
+			Proxy::add_proxy(Origin::signed(proxied.clone()), any_proxy.clone(), ProxyType::Any, 0).unwrap();
+			Proxy::add_proxy(
+				Origin::signed(proxied.clone()),
+				relay_proxy.clone(),
+				ProxyType::BridgeRelay,
+				0,
+			)
+			.unwrap();
+
+			assert!(BridgeProxyCheck::is_proxy(&proxied, &any_proxy, &ProxyType::Any, &0));
+			assert!(!BridgeProxyCheck::is_proxy(&proxied, &any_proxy, &ProxyType::Any, &10));
+			assert!(!BridgeProxyCheck::is_proxy(&proxied, &relay_proxy, &ProxyType::Any, &0));
+			assert!(!BridgeProxyCheck::is_proxy(&any_proxy, &proxied, &ProxyType::Any, &0));
+		});
+	}
 }
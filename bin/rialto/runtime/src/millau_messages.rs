@@ -24,7 +24,10 @@ use bp_messages::{
 	InboundLaneData, LaneId, Message, MessageNonce, Parameter as MessagesParameter,
 };
 use bp_runtime::{ChainId, MILLAU_CHAIN_ID, RIALTO_CHAIN_ID};
-use bridge_runtime_common::messages::{self, MessageBridge, MessageTransaction};
+use bridge_runtime_common::{
+	contracts_dispatch,
+	messages::{self, MessageBridge, MessageTransaction},
+};
 use codec::{Decode, Encode};
 use frame_support::{
 	parameter_types,
@@ -40,6 +43,11 @@ pub const INITIAL_MILLAU_TO_RIALTO_CONVERSION_RATE: FixedU128 = FixedU128::from_
 parameter_types! {
 	/// Millau to Rialto conversion rate. Initially we treat both tokens as equal.
 	pub storage MillauToRialtoConversionRate: FixedU128 = INITIAL_MILLAU_TO_RIALTO_CONVERSION_RATE;
+	/// Maximal size of the message that may be sent to Millau.
+	pub MaximalMessageSizeToMillau: u32 = messages::source::maximal_message_size::<WithMillauMessageBridge>();
+	/// Maximal dispatch weight of the message that may be sent to Millau.
+	pub MaximalMessageDispatchWeightToMillau: Weight =
+		messages::target::maximal_incoming_message_dispatch_weight(bp_millau::max_extrinsic_weight());
 }
 
 /// Message payload for Rialto -> Millau messages.
@@ -49,16 +57,24 @@ pub type ToMillauMessagePayload = messages::source::FromThisChainMessagePayload<
 pub type ToMillauMessageVerifier = messages::source::FromThisChainMessageVerifier<WithMillauMessageBridge>;
 
 /// Message payload for Millau -> Rialto messages.
-pub type FromMillauMessagePayload = messages::target::FromBridgedChainMessagePayload<WithMillauMessageBridge>;
+pub type FromMillauMessagePayload =
+	messages::target::FromBridgedChainMessagePayload<WithMillauMessageBridge, FromMillauEncodedCall>;
+
+/// Rialto call (either regular call, or contract call) as it comes from Millau.
+pub type FromMillauMessageCall =
+	contracts_dispatch::ContractMessageCall<crate::Call, bp_rialto::AccountId, bp_rialto::Balance>;
 
-/// Encoded Rialto Call as it comes from Millau.
-pub type FromMillauEncodedCall = messages::target::FromBridgedChainEncodedMessageCall<WithMillauMessageBridge>;
+/// Encoded Rialto call as it comes from Millau.
+pub type FromMillauEncodedCall =
+	contracts_dispatch::FromBridgedChainContractMessageCall<WithMillauMessageBridge, crate::Runtime>;
 
 /// Call-dispatch based message dispatch for Millau -> Rialto messages.
 pub type FromMillauMessageDispatch = messages::target::FromBridgedChainMessageDispatch<
 	WithMillauMessageBridge,
 	crate::Runtime,
+	pallet_balances::Pallet<crate::Runtime>,
 	pallet_bridge_dispatch::DefaultInstance,
+	FromMillauEncodedCall,
 >;
 
 /// Messages proof for Millau -> Rialto messages.
@@ -104,7 +120,7 @@ impl messages::ThisChainWithMessages for Rialto {
 	type Call = crate::Call;
 
 	fn is_outbound_lane_enabled(lane: &LaneId) -> bool {
-		*lane == [0, 0, 0, 0] || *lane == [0, 0, 0, 1]
+		*lane == LaneId([0, 0, 0, 0]) || *lane == LaneId([0, 0, 0, 1])
 	}
 
 	fn maximal_pending_messages_at_outbound_lane() -> MessageNonce {
@@ -112,9 +128,13 @@ impl messages::ThisChainWithMessages for Rialto {
 	}
 
 	fn estimate_delivery_confirmation_transaction() -> MessageTransaction<Weight> {
-		let inbound_data_size =
-			InboundLaneData::<bp_rialto::AccountId>::encoded_size_hint(bp_rialto::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE, 1)
-				.unwrap_or(u32::MAX);
+		let inbound_data_size = InboundLaneData::<bp_rialto::AccountId>::encoded_size_hint(
+			bp_rialto::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE,
+			1,
+			1,
+			1,
+		)
+		.unwrap_or(u32::MAX);
 
 		MessageTransaction {
 			dispatch_weight: bp_rialto::MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT,
@@ -258,3 +278,78 @@ impl MessagesParameter for RialtoToMillauMessagesParameter {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Call, ContractsCall, SystemCall};
+	use bridge_runtime_common::contracts_dispatch::{ContractCall, CONTRACT_CALL_MARKER};
+	use frame_support::weights::GetDispatchInfo;
+	use sp_runtime::traits::StaticLookup;
+
+	fn dispatch_call(encoded_call: Vec<u8>) -> Result<Call, ()> {
+		FromMillauEncodedCall::new(encoded_call).into()
+	}
+
+	#[test]
+	fn rialto_has_no_pallet_at_contract_call_marker_index() {
+		// `MmrLeaf` is the last pallet of the runtime
+		assert!(
+			<crate::PalletInfo as frame_support::traits::PalletInfo>::index::<crate::MmrLeaf>().unwrap()
+				< CONTRACT_CALL_MARKER as usize
+		);
+	}
+
+	#[test]
+	fn regular_call_from_millau_is_dispatched_as_is() {
+		let call = Call::System(SystemCall::remark(vec![42]));
+		assert_eq!(dispatch_call(call.encode()), Ok(call.clone()));
+		assert_eq!(
+			dispatch_call(FromMillauMessageCall::Call(call.clone()).encode()),
+			Ok(call)
+		);
+	}
+
+	#[test]
+	fn contract_call_from_millau_is_dispatched_as_contracts_call() {
+		let dest: bp_rialto::AccountId = [1u8; 32].into();
+		assert_eq!(
+			dispatch_call(
+				FromMillauMessageCall::Contract(ContractCall {
+					dest: dest.clone(),
+					value: 100,
+					gas_limit: 1_000_000,
+					data: vec![1, 2, 3],
+				})
+				.encode()
+			),
+			Ok(Call::Contracts(ContractsCall::call(
+				<Runtime as frame_system::Config>::Lookup::unlookup(dest),
+				100,
+				1_000_000,
+				vec![1, 2, 3],
+			))),
+		);
+	}
+
+	#[test]
+	fn contract_call_spends_declared_dispatch_weight() {
+		let dispatch_weight = 1_000_000_000;
+		let call = dispatch_call(
+			FromMillauMessageCall::Contract(ContractCall::with_dispatch_weight::<Runtime>(
+				[1u8; 32].into(),
+				0,
+				vec![],
+				dispatch_weight,
+			))
+			.encode(),
+		)
+		.unwrap();
+		assert_eq!(call.get_dispatch_info().weight, dispatch_weight);
+	}
+
+	#[test]
+	fn malformed_call_from_millau_is_rejected() {
+		assert_eq!(dispatch_call(vec![CONTRACT_CALL_MARKER, 1, 2, 3]), Err(()));
+	}
+}
@@ -34,6 +34,9 @@ frame_support::parameter_types! {
 		aura_configuration();
 	pub BridgeValidatorsConfiguration: ValidatorsConfiguration =
 		validators_configuration();
+	/// Number of blocks (after full headers are pruned) for which we remember hashes of finalized
+	/// headers.
+	pub const AncientHeadersToKeep: u64 = 10 * FINALIZED_HEADERS_TO_KEEP;
 }
 
 /// Max number of finalized headers to keep.
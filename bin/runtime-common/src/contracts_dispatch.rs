@@ -0,0 +1,238 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dispatching Bridged -> This chain messages as `pallet-contracts` calls.
+//!
+//! The message payload may carry either encoded This chain `Call`, or the call of wasm (e.g. ink!)
+//! contract. The contract call is converted into `pallet_contracts::Call::call` right before
+//! dispatch.
+//!
+//! The call is opaque (`Vec<u8>`) for the Bridged chain, so the payload stays compatible with
+//! `FromThisChainMessagePayload` of the Bridged chain. Regular calls are encoded as is, so senders
+//! that are not aware of contracts keep working. Contract calls are prefixed with the
+//! `CONTRACT_CALL_MARKER`, so This chain must not have a pallet (with calls) at this index.
+//!
+//! The weight of `pallet_contracts::Call::call` is the weight of the call itself plus its gas limit.
+//! Use `ContractCall::with_dispatch_weight` to spend all declared dispatch weight (except the call
+//! overhead) on the contract execution.
+
+use crate::messages::{AccountIdOf, BalanceOf, CallOf, MessageBridge, ThisChain};
+
+use codec::{Decode, DecodeAll, Encode};
+use frame_support::{
+	traits::Currency,
+	weights::{GetDispatchInfo, Weight},
+	RuntimeDebug,
+};
+use sp_runtime::traits::{Saturating, StaticLookup};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Prefix of the encoded contract call.
+///
+/// Encoded This chain `Call` starts with the index of the pallet, so This chain must not have a
+/// pallet with calls at this index.
+pub const CONTRACT_CALL_MARKER: u8 = 0xFF;
+
+/// Balance type used by the `pallet-contracts`.
+type ContractsBalanceOf<T> =
+	<<T as pallet_contracts::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Contract call that is sent over the bridge.
+#[derive(Decode, Encode, Clone, RuntimeDebug, PartialEq)]
+pub struct ContractCall<AccountId, Balance> {
+	/// Address of the contract to call.
+	pub dest: AccountId,
+	/// Value that is transferred to the contract.
+	pub value: Balance,
+	/// Maximal weight the contract execution may use.
+	pub gas_limit: Weight,
+	/// Call input data.
+	pub data: Vec<u8>,
+}
+
+impl<AccountId, Balance> ContractCall<AccountId, Balance> {
+	/// Create contract call that spends all declared dispatch weight (except the weight of
+	/// `pallet_contracts::Call::call` itself) on the contract execution.
+	pub fn with_dispatch_weight<ThisRuntime>(
+		dest: AccountId,
+		value: Balance,
+		data: Vec<u8>,
+		dispatch_weight: Weight,
+	) -> Self
+	where
+		ThisRuntime: pallet_contracts::Config<AccountId = AccountId>,
+		AccountId: Clone,
+		Balance: Clone + Into<ContractsBalanceOf<ThisRuntime>>,
+	{
+		let mut call = ContractCall {
+			dest,
+			value,
+			gas_limit: 0,
+			data,
+		};
+		let call_overhead = call.clone().into_call::<ThisRuntime>().get_dispatch_info().weight;
+		call.gas_limit = dispatch_weight.saturating_sub(call_overhead);
+		call
+	}
+
+	/// Convert into `pallet-contracts` call.
+	pub fn into_call<ThisRuntime>(self) -> pallet_contracts::Call<ThisRuntime>
+	where
+		ThisRuntime: pallet_contracts::Config<AccountId = AccountId>,
+		Balance: Into<ContractsBalanceOf<ThisRuntime>>,
+	{
+		pallet_contracts::Call::call(
+			ThisRuntime::Lookup::unlookup(self.dest),
+			self.value.into(),
+			self.gas_limit,
+			self.data,
+		)
+	}
+}
+
+/// Call that is sent over the bridge to the chain with contracts dispatch adapter - either This
+/// chain `Call`, or contract call.
+#[derive(Clone, RuntimeDebug, PartialEq)]
+pub enum ContractMessageCall<Call, AccountId, Balance> {
+	/// This chain `Call`.
+	Call(Call),
+	/// Contract call.
+	Contract(ContractCall<AccountId, Balance>),
+}
+
+impl<Call: Decode, AccountId: Decode, Balance: Decode> ContractMessageCall<Call, AccountId, Balance> {
+	/// Decode call from the encoded `ContractMessageCall`. All bytes must be consumed.
+	pub fn decode_all(encoded_call: &[u8]) -> Result<Self, codec::Error> {
+		match encoded_call.split_first() {
+			Some((&CONTRACT_CALL_MARKER, encoded_contract_call)) => {
+				DecodeAll::decode_all(encoded_contract_call).map(ContractMessageCall::Contract)
+			}
+			_ => DecodeAll::decode_all(encoded_call).map(ContractMessageCall::Call),
+		}
+	}
+}
+
+impl<Call: Encode, AccountId: Encode, Balance: Encode> Encode for ContractMessageCall<Call, AccountId, Balance> {
+	fn encode_to<T: codec::Output + ?Sized>(&self, dest: &mut T) {
+		match *self {
+			ContractMessageCall::Call(ref call) => call.encode_to(dest),
+			ContractMessageCall::Contract(ref contract_call) => {
+				dest.push_byte(CONTRACT_CALL_MARKER);
+				contract_call.encode_to(dest);
+			}
+		}
+	}
+}
+
+/// Encoded `ContractMessageCall` as it is transferred over bridge.
+///
+/// The call is decoded only when all other message checks (like `spec_version`) have passed.
+#[derive(Decode, Encode, RuntimeDebug, PartialEq)]
+pub struct FromBridgedChainContractMessageCall<B, ThisRuntime> {
+	encoded_call: Vec<u8>,
+	_marker: PhantomData<(B, ThisRuntime)>,
+}
+
+impl<B, ThisRuntime> FromBridgedChainContractMessageCall<B, ThisRuntime> {
+	/// Create encoded call from the encoded `ContractMessageCall`.
+	pub fn new(encoded_call: Vec<u8>) -> Self {
+		FromBridgedChainContractMessageCall {
+			encoded_call,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, ThisRuntime> From<FromBridgedChainContractMessageCall<B, ThisRuntime>> for Result<CallOf<ThisChain<B>>, ()>
+where
+	B: MessageBridge,
+	ThisRuntime: pallet_contracts::Config<AccountId = AccountIdOf<ThisChain<B>>>,
+	BalanceOf<ThisChain<B>>: Into<ContractsBalanceOf<ThisRuntime>>,
+	CallOf<ThisChain<B>>: From<pallet_contracts::Call<ThisRuntime>>,
+{
+	fn from(call: FromBridgedChainContractMessageCall<B, ThisRuntime>) -> Self {
+		match ContractMessageCall::decode_all(&call.encoded_call).map_err(drop)? {
+			ContractMessageCall::Call(call) => Ok(call),
+			ContractMessageCall::Contract(contract_call) => Ok(contract_call.into_call::<ThisRuntime>().into()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bp_message_dispatch::{CallOrigin, DispatchFeePayment, MessagePayload};
+
+	#[derive(Decode, Encode, Clone, RuntimeDebug, PartialEq)]
+	enum TestCall {
+		Remark(Vec<u8>),
+	}
+
+	type TestMessageCall = ContractMessageCall<TestCall, u64, u128>;
+
+	fn contract_call() -> TestMessageCall {
+		ContractMessageCall::Contract(ContractCall {
+			dest: 42,
+			value: 100,
+			gas_limit: 1_000_000,
+			data: vec![1, 2, 3],
+		})
+	}
+
+	fn message_payload(call: Vec<u8>) -> MessagePayload<u64, u64, u64, Vec<u8>> {
+		MessagePayload {
+			spec_version: 1,
+			weight: 100,
+			delivery_deadline: None,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+			origin: CallOrigin::SourceRoot,
+			call,
+		}
+	}
+
+	#[test]
+	fn contract_message_call_is_compatible_with_opaque_bridged_chain_payload() {
+		for call in vec![ContractMessageCall::Call(TestCall::Remark(vec![42])), contract_call()] {
+			// the Bridged chain sees the call as opaque bytes
+			let encoded_payload = message_payload(call.encode()).encode();
+
+			// and This chain decodes it as `FromBridgedChainContractMessageCall`
+			let payload = MessagePayload::<u64, u64, u64, FromBridgedChainContractMessageCall<(), ()>>::decode(
+				&mut &encoded_payload[..],
+			)
+			.unwrap();
+			assert_eq!(TestMessageCall::decode_all(&payload.call.encoded_call).ok(), Some(call));
+		}
+	}
+
+	#[test]
+	fn regular_call_is_encoded_as_is() {
+		let call = TestCall::Remark(vec![42]);
+		assert_eq!(TestMessageCall::Call(call.clone()).encode(), call.encode());
+		assert_eq!(contract_call().encode()[0], CONTRACT_CALL_MARKER);
+	}
+
+	#[test]
+	fn call_with_trailing_bytes_is_rejected() {
+		let mut encoded_call = TestMessageCall::Call(TestCall::Remark(vec![42])).encode();
+		encoded_call.push(0);
+		assert!(TestMessageCall::decode_all(&encoded_call).is_err());
+
+		let mut encoded_call = contract_call().encode();
+		encoded_call.push(0);
+		assert!(TestMessageCall::decode_all(&encoded_call).is_err());
+	}
+}
@@ -0,0 +1,233 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dispatching Bridged -> This chain messages as `pallet-evm` calls.
+//!
+//! The message payload may carry either encoded This chain `Call`, or the EVM call. The EVM call
+//! is converted into `pallet_evm::Call::call` right before dispatch.
+//!
+//! The call is opaque (`Vec<u8>`) for the Bridged chain, so the payload stays compatible with
+//! `FromThisChainMessagePayload` of the Bridged chain. Regular calls are encoded as is, so senders
+//! that are not aware of EVM keep working. EVM calls are prefixed with the `EVM_CALL_MARKER`, so
+//! This chain must not have a pallet (with calls) at this index.
+//!
+//! The weight of `pallet_evm::Call::call` is computed from its gas limit. The dispatch pallet rejects
+//! messages with declared dispatch weight lower than the call weight, so the EVM execution is always
+//! metered against the declared dispatch weight. Use `EvmCall::with_dispatch_weight` to spend all
+//! declared dispatch weight on the EVM execution.
+//!
+//! The dispatch origin (derived from the message origin) must be allowed to act on behalf of the
+//! `EvmCall::source` address by the `pallet_evm::Config::CallOrigin`.
+
+use crate::messages::{CallOf, MessageBridge, ThisChain};
+
+use codec::{Decode, DecodeAll, Encode};
+use frame_support::{weights::Weight, RuntimeDebug};
+use pallet_evm::{FeeCalculator, GasWeightMapping};
+use sp_core::{H160, U256};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Prefix of the encoded EVM call.
+///
+/// Encoded This chain `Call` starts with the index of the pallet, so This chain must not have a
+/// pallet with calls at this index.
+pub const EVM_CALL_MARKER: u8 = 0xFE;
+
+/// EVM call that is sent over the bridge.
+#[derive(Decode, Encode, Clone, RuntimeDebug, PartialEq)]
+pub struct EvmCall {
+	/// Address of the call sender.
+	pub source: H160,
+	/// Address of the contract to call.
+	pub target: H160,
+	/// Call input data.
+	pub input: Vec<u8>,
+	/// Value that is transferred to the contract.
+	pub value: U256,
+	/// Maximal gas the call may use.
+	pub gas_limit: u64,
+}
+
+impl EvmCall {
+	/// Create EVM call that spends all declared dispatch weight on the EVM execution.
+	pub fn with_dispatch_weight<ThisRuntime: pallet_evm::Config>(
+		source: H160,
+		target: H160,
+		input: Vec<u8>,
+		value: U256,
+		dispatch_weight: Weight,
+	) -> Self {
+		EvmCall {
+			source,
+			target,
+			input,
+			value,
+			gas_limit: ThisRuntime::GasWeightMapping::weight_to_gas(dispatch_weight),
+		}
+	}
+
+	/// Returns minimal dispatch weight that must be declared by the message carrying this call.
+	pub fn dispatch_weight<ThisRuntime: pallet_evm::Config>(&self) -> Weight {
+		ThisRuntime::GasWeightMapping::gas_to_weight(self.gas_limit)
+	}
+
+	/// Convert into `pallet-evm` call, paying minimal gas price.
+	pub fn into_call<ThisRuntime: pallet_evm::Config>(self) -> pallet_evm::Call<ThisRuntime> {
+		pallet_evm::Call::call(
+			self.source,
+			self.target,
+			self.input,
+			self.value,
+			self.gas_limit,
+			ThisRuntime::FeeCalculator::min_gas_price(),
+			None,
+		)
+	}
+}
+
+/// Call that is sent over the bridge to the chain with EVM dispatch adapter - either This chain
+/// `Call`, or EVM call.
+#[derive(Clone, RuntimeDebug, PartialEq)]
+pub enum EvmMessageCall<Call> {
+	/// This chain `Call`.
+	Call(Call),
+	/// EVM call.
+	Evm(EvmCall),
+}
+
+impl<Call: Decode> EvmMessageCall<Call> {
+	/// Decode call from the encoded `EvmMessageCall`. All bytes must be consumed.
+	pub fn decode_all(encoded_call: &[u8]) -> Result<Self, codec::Error> {
+		match encoded_call.split_first() {
+			Some((&EVM_CALL_MARKER, encoded_evm_call)) => {
+				DecodeAll::decode_all(encoded_evm_call).map(EvmMessageCall::Evm)
+			}
+			_ => DecodeAll::decode_all(encoded_call).map(EvmMessageCall::Call),
+		}
+	}
+}
+
+impl<Call: Encode> Encode for EvmMessageCall<Call> {
+	fn encode_to<T: codec::Output + ?Sized>(&self, dest: &mut T) {
+		match *self {
+			EvmMessageCall::Call(ref call) => call.encode_to(dest),
+			EvmMessageCall::Evm(ref evm_call) => {
+				dest.push_byte(EVM_CALL_MARKER);
+				evm_call.encode_to(dest);
+			}
+		}
+	}
+}
+
+/// Encoded `EvmMessageCall` as it is transferred over bridge.
+///
+/// The call is decoded only when all other message checks (like `spec_version`) have passed.
+#[derive(Decode, Encode, RuntimeDebug, PartialEq)]
+pub struct FromBridgedChainEvmMessageCall<B, ThisRuntime> {
+	encoded_call: Vec<u8>,
+	_marker: PhantomData<(B, ThisRuntime)>,
+}
+
+impl<B, ThisRuntime> FromBridgedChainEvmMessageCall<B, ThisRuntime> {
+	/// Create encoded call from the encoded `EvmMessageCall`.
+	pub fn new(encoded_call: Vec<u8>) -> Self {
+		FromBridgedChainEvmMessageCall {
+			encoded_call,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, ThisRuntime> From<FromBridgedChainEvmMessageCall<B, ThisRuntime>> for Result<CallOf<ThisChain<B>>, ()>
+where
+	B: MessageBridge,
+	ThisRuntime: pallet_evm::Config,
+	CallOf<ThisChain<B>>: From<pallet_evm::Call<ThisRuntime>>,
+{
+	fn from(call: FromBridgedChainEvmMessageCall<B, ThisRuntime>) -> Self {
+		match EvmMessageCall::decode_all(&call.encoded_call).map_err(drop)? {
+			EvmMessageCall::Call(call) => Ok(call),
+			EvmMessageCall::Evm(evm_call) => Ok(evm_call.into_call::<ThisRuntime>().into()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bp_message_dispatch::{CallOrigin, DispatchFeePayment, MessagePayload};
+
+	#[derive(Decode, Encode, Clone, RuntimeDebug, PartialEq)]
+	enum TestCall {
+		Remark(Vec<u8>),
+	}
+
+	type TestMessageCall = EvmMessageCall<TestCall>;
+
+	fn evm_call() -> TestMessageCall {
+		EvmMessageCall::Evm(EvmCall {
+			source: H160::from_low_u64_be(1),
+			target: H160::from_low_u64_be(42),
+			input: vec![1, 2, 3],
+			value: 100.into(),
+			gas_limit: 1_000_000,
+		})
+	}
+
+	fn message_payload(call: Vec<u8>) -> MessagePayload<u64, u64, u64, Vec<u8>> {
+		MessagePayload {
+			spec_version: 1,
+			weight: 100,
+			delivery_deadline: None,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+			origin: CallOrigin::SourceRoot,
+			call,
+		}
+	}
+
+	#[test]
+	fn evm_message_call_is_compatible_with_opaque_bridged_chain_payload() {
+		for call in vec![EvmMessageCall::Call(TestCall::Remark(vec![42])), evm_call()] {
+			// the Bridged chain sees the call as opaque bytes
+			let encoded_payload = message_payload(call.encode()).encode();
+
+			// and This chain decodes it as `FromBridgedChainEvmMessageCall`
+			let payload = MessagePayload::<u64, u64, u64, FromBridgedChainEvmMessageCall<(), ()>>::decode(
+				&mut &encoded_payload[..],
+			)
+			.unwrap();
+			assert_eq!(TestMessageCall::decode_all(&payload.call.encoded_call).ok(), Some(call));
+		}
+	}
+
+	#[test]
+	fn regular_call_is_encoded_as_is() {
+		let call = TestCall::Remark(vec![42]);
+		assert_eq!(TestMessageCall::Call(call.clone()).encode(), call.encode());
+		assert_eq!(evm_call().encode()[0], EVM_CALL_MARKER);
+	}
+
+	#[test]
+	fn call_with_trailing_bytes_is_rejected() {
+		let mut encoded_call = TestMessageCall::Call(TestCall::Remark(vec![42])).encode();
+		encoded_call.push(0);
+		assert!(TestMessageCall::decode_all(&encoded_call).is_err());
+
+		let mut encoded_call = evm_call().encode();
+		encoded_call.push(0);
+		assert!(TestMessageCall::decode_all(&encoded_call).is_err());
+	}
+}
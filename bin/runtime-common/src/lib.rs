@@ -18,5 +18,9 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "contracts")]
+pub mod contracts_dispatch;
+#[cfg(feature = "evm")]
+pub mod evm_dispatch;
 pub mod messages;
 pub mod messages_benchmarking;
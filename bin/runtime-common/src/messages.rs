@@ -20,18 +20,23 @@
 //! pallet is used to dispatch incoming messages. Message identified by a tuple
 //! of to elements - message lane id and message nonce.
 
-use bp_message_dispatch::MessageDispatch as _;
+use bp_message_dispatch::{DispatchFeePayment, MessageDispatch as _};
 use bp_messages::{
 	source_chain::{LaneMessageVerifier, Sender},
 	target_chain::{DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages},
 	InboundLaneData, LaneId, Message, MessageData, MessageKey, MessageNonce, OutboundLaneData,
 };
-use bp_runtime::{ChainId, Size, StorageProofChecker};
+use bp_parachains::{ParaHash, ParaId};
+use bp_runtime::{messages::MessageDispatchResult, ChainId, DeclaredWeight, Size, StorageProofChecker};
 use codec::{Decode, Encode};
-use frame_support::{traits::Instance, weights::Weight, RuntimeDebug};
+use frame_support::{
+	traits::{Currency, ExistenceRequirement, Instance},
+	weights::Weight,
+	RuntimeDebug,
+};
 use hash_db::Hasher;
 use sp_runtime::{
-	traits::{AtLeast32BitUnsigned, CheckedAdd, CheckedDiv, CheckedMul},
+	traits::{AtLeast32BitUnsigned, CheckedAdd, CheckedDiv, CheckedMul, Saturating, UniqueSaturatedInto, Zero},
 	FixedPointNumber, FixedPointOperand, FixedU128,
 };
 use sp_std::{cmp::PartialOrd, convert::TryFrom, fmt::Debug, marker::PhantomData, ops::RangeInclusive, vec::Vec};
@@ -320,14 +325,19 @@ pub mod source {
 	/// Estimate delivery and dispatch fee that must be paid for delivering a message to the Bridged chain.
 	///
 	/// The fee is paid in This chain Balance, but we use Bridged chain balance to avoid additional conversions.
+	/// If the message pays dispatch fee at the Bridged chain, the dispatch fee is not included.
 	/// Returns `None` if overflow has happened.
 	pub fn estimate_message_dispatch_and_delivery_fee<B: MessageBridge>(
 		payload: &FromThisChainMessagePayload<B>,
 		relayer_fee_percent: u32,
 	) -> Result<BalanceOf<ThisChain<B>>, &'static str> {
 		// the fee (in Bridged tokens) of all transactions that are made on the Bridged chain
+		let paid_dispatch_weight = match payload.dispatch_fee_payment {
+			DispatchFeePayment::AtSourceChain => payload.declared_weight(),
+			DispatchFeePayment::AtTargetChain => 0,
+		};
 		let delivery_transaction =
-			BridgedChain::<B>::estimate_delivery_transaction(&payload.call, payload.weight.into());
+			BridgedChain::<B>::estimate_delivery_transaction(&payload.call, paid_dispatch_weight.into());
 		let delivery_transaction_fee = BridgedChain::<B>::transaction_payment(delivery_transaction);
 
 		// the fee (in This tokens) of all transactions that are made on This chain
@@ -388,6 +398,56 @@ pub mod source {
 		)
 		.map_err(<&'static str>::from)?
 	}
+
+	/// Verify proof of This -> Bridged chain messages delivery, where the Bridged chain is a parachain.
+	///
+	/// The proof must be crafted at the parachain header, which has already been imported by the
+	/// `ParachainsInstance` of the parachains finality pallet.
+	pub fn verify_messages_delivery_proof_from_parachain<
+		B: MessageBridge,
+		BridgedParachain: bp_runtime::Chain,
+		ThisRuntime,
+		ParachainsInstance: 'static,
+	>(
+		bridged_parachain: ParaId,
+		proof: FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<B>>>,
+	) -> Result<ParsedMessagesDeliveryProofFromBridgedChain<B>, &'static str>
+	where
+		ThisRuntime: pallet_bridge_parachains::Config<ParachainsInstance>,
+		ThisRuntime: pallet_bridge_messages::Config<MessagesInstanceOf<BridgedChain<B>>>,
+		HashOf<BridgedChain<B>>: Into<ParaHash>,
+	{
+		let FromBridgedChainMessagesDeliveryProof {
+			bridged_header_hash,
+			storage_proof,
+			lane,
+		} = proof;
+		pallet_bridge_parachains::Pallet::<ThisRuntime, ParachainsInstance>::parse_finalized_storage_proof::<
+			BridgedParachain,
+			_,
+		>(
+			bridged_parachain,
+			bridged_header_hash.into(),
+			StorageProof::new(storage_proof),
+			|storage| {
+				// Messages delivery proof is just proof of single storage key read => any error
+				// is fatal.
+				let storage_inbound_lane_data_key = pallet_bridge_messages::storage_keys::inbound_lane_data_key::<
+					ThisRuntime,
+					MessagesInstanceOf<BridgedChain<B>>,
+				>(&lane);
+				let raw_inbound_lane_data = storage
+					.read_value(storage_inbound_lane_data_key.0.as_ref())
+					.map_err(|_| "Failed to read inbound lane state from storage proof")?
+					.ok_or("Inbound lane state is missing from the messages proof")?;
+				let inbound_lane_data = InboundLaneData::decode(&mut &raw_inbound_lane_data[..])
+					.map_err(|_| "Failed to decode inbound lane state from the proof")?;
+
+				Ok((lane, inbound_lane_data))
+			},
+		)
+		.map_err(<&'static str>::from)?
+	}
 }
 
 /// Sub-module that is declaring types required for processing Bridged -> This chain messages.
@@ -402,12 +462,16 @@ pub mod target {
 	>;
 
 	/// Decoded Bridged -> This message payload.
-	pub type FromBridgedChainMessagePayload<B> = bp_message_dispatch::MessagePayload<
-		AccountIdOf<BridgedChain<B>>,
-		SignerOf<ThisChain<B>>,
-		SignatureOf<ThisChain<B>>,
-		FromBridgedChainEncodedMessageCall<B>,
-	>;
+	///
+	/// By default, the message carries encoded This chain `Call`. Runtimes may use other
+	/// adapters (e.g. EVM or contract calls) as long as they're convertible into This chain `Call`.
+	pub type FromBridgedChainMessagePayload<B, EncodedCall = FromBridgedChainEncodedMessageCall<B>> =
+		bp_message_dispatch::MessagePayload<
+			AccountIdOf<BridgedChain<B>>,
+			SignerOf<ThisChain<B>>,
+			SignatureOf<ThisChain<B>>,
+			EncodedCall,
+		>;
 
 	/// Messages proof from bridged chain:
 	///
@@ -456,41 +520,113 @@ pub mod target {
 	}
 
 	/// Dispatching Bridged -> This chain messages.
+	///
+	/// If the message pays dispatch fee at This chain, the fee is transferred from the dispatch
+	/// origin account to the relayer account, using `ThisCurrency`.
 	#[derive(RuntimeDebug, Clone, Copy)]
-	pub struct FromBridgedChainMessageDispatch<B, ThisRuntime, ThisDispatchInstance> {
-		_marker: PhantomData<(B, ThisRuntime, ThisDispatchInstance)>,
+	pub struct FromBridgedChainMessageDispatch<
+		B,
+		ThisRuntime,
+		ThisCurrency,
+		ThisDispatchInstance,
+		EncodedCall = FromBridgedChainEncodedMessageCall<B>,
+	> {
+		_marker: PhantomData<(B, ThisRuntime, ThisCurrency, ThisDispatchInstance, EncodedCall)>,
 	}
 
-	impl<B: MessageBridge, ThisRuntime, ThisDispatchInstance>
-		MessageDispatch<<BridgedChain<B> as ChainWithMessages>::Balance>
-		for FromBridgedChainMessageDispatch<B, ThisRuntime, ThisDispatchInstance>
+	impl<B: MessageBridge, ThisRuntime, ThisCurrency, ThisDispatchInstance, EncodedCall>
+		MessageDispatch<ThisRuntime::AccountId, <BridgedChain<B> as ChainWithMessages>::Balance>
+		for FromBridgedChainMessageDispatch<B, ThisRuntime, ThisCurrency, ThisDispatchInstance, EncodedCall>
 	where
+		EncodedCall: Decode,
+		BalanceOf<ThisChain<B>>: Saturating + Zero,
+		ThisCurrency: Currency<ThisRuntime::AccountId, Balance = BalanceOf<ThisChain<B>>>,
 		ThisDispatchInstance: frame_support::traits::Instance,
 		ThisRuntime: pallet_bridge_dispatch::Config<ThisDispatchInstance, MessageId = (LaneId, MessageNonce)>,
-		<ThisRuntime as pallet_bridge_dispatch::Config<ThisDispatchInstance>>::Event:
-			From<pallet_bridge_dispatch::RawEvent<(LaneId, MessageNonce), ThisDispatchInstance>>,
-		pallet_bridge_dispatch::Pallet<ThisRuntime, ThisDispatchInstance>:
-			bp_message_dispatch::MessageDispatch<(LaneId, MessageNonce), Message = FromBridgedChainMessagePayload<B>>,
+		<ThisRuntime as pallet_bridge_dispatch::Config<ThisDispatchInstance>>::Event: From<
+			pallet_bridge_dispatch::RawEvent<(LaneId, MessageNonce), ThisRuntime::AccountId, ThisDispatchInstance>,
+		>,
+		pallet_bridge_dispatch::Pallet<ThisRuntime, ThisDispatchInstance>: bp_message_dispatch::MessageDispatch<
+			ThisRuntime::AccountId,
+			(LaneId, MessageNonce),
+			Message = FromBridgedChainMessagePayload<B, EncodedCall>,
+		>,
 	{
-		type DispatchPayload = FromBridgedChainMessagePayload<B>;
+		type DispatchPayload = FromBridgedChainMessagePayload<B, EncodedCall>;
 
 		fn dispatch_weight(
 			message: &DispatchMessage<Self::DispatchPayload, BalanceOf<BridgedChain<B>>>,
 		) -> frame_support::weights::Weight {
-			message.data.payload.as_ref().map(|payload| payload.weight).unwrap_or(0)
+			message
+				.data
+				.payload
+				.as_ref()
+				.map(|payload| payload.declared_weight())
+				.unwrap_or(0)
 		}
 
-		fn dispatch(message: DispatchMessage<Self::DispatchPayload, BalanceOf<BridgedChain<B>>>) {
+		fn is_expired(message: &DispatchMessage<Self::DispatchPayload, BalanceOf<BridgedChain<B>>>) -> bool {
+			let delivery_deadline = match message.data.payload {
+				Ok(ref payload) => payload.delivery_deadline,
+				Err(_) => None,
+			};
+			match delivery_deadline {
+				Some(delivery_deadline) => {
+					let current_block_number: u64 =
+						frame_system::Pallet::<ThisRuntime>::block_number().unique_saturated_into();
+					current_block_number >= delivery_deadline
+				}
+				None => false,
+			}
+		}
+
+		fn dispatch(
+			relayer_account: &ThisRuntime::AccountId,
+			message: DispatchMessage<Self::DispatchPayload, BalanceOf<BridgedChain<B>>>,
+		) -> MessageDispatchResult {
 			let message_id = (message.key.lane_id, message.key.nonce);
 			pallet_bridge_dispatch::Pallet::<ThisRuntime, ThisDispatchInstance>::dispatch(
 				B::BridgedChain::ID,
 				B::ThisChain::ID,
 				message_id,
 				message.data.payload.map_err(drop),
-			);
+				|dispatch_origin, dispatch_weight| {
+					let dispatch_fee = message_dispatch_fee_at_this_chain::<B>(dispatch_weight);
+					if dispatch_fee.is_zero() {
+						return Ok(());
+					}
+
+					ThisCurrency::transfer(
+						dispatch_origin,
+						relayer_account,
+						dispatch_fee,
+						ExistenceRequirement::AllowDeath,
+					)
+					.map_err(drop)
+				},
+			)
 		}
 	}
 
+	/// Return fee that the message with given dispatch weight pays for its dispatch at This chain.
+	///
+	/// This is the difference between the fee of the transaction with given dispatch weight and
+	/// the fee of the same transaction with zero weight.
+	pub fn message_dispatch_fee_at_this_chain<B: MessageBridge>(dispatch_weight: Weight) -> BalanceOf<ThisChain<B>>
+	where
+		BalanceOf<ThisChain<B>>: Saturating,
+	{
+		let transaction_fee = ThisChain::<B>::transaction_payment(MessageTransaction {
+			dispatch_weight: dispatch_weight.into(),
+			size: 0,
+		});
+		let base_transaction_fee = ThisChain::<B>::transaction_payment(MessageTransaction {
+			dispatch_weight: Weight::zero().into(),
+			size: 0,
+		});
+		transaction_fee.saturating_sub(base_transaction_fee)
+	}
+
 	/// Return maximal dispatch weight of the message we're able to receive.
 	pub fn maximal_incoming_message_dispatch_weight(maximal_extrinsic_weight: Weight) -> Weight {
 		maximal_extrinsic_weight / 2
@@ -535,6 +671,49 @@ pub mod target {
 		.map_err(Into::into)
 	}
 
+	/// Verify proof of Bridged -> This chain messages, where the Bridged chain is a parachain.
+	///
+	/// The proof must be crafted at the parachain header, which has already been imported by the
+	/// `ParachainsInstance` of the parachains finality pallet. The `messages_count` argument is
+	/// treated the same way as in the `verify_messages_proof`.
+	pub fn verify_messages_proof_from_parachain<
+		B: MessageBridge,
+		BridgedParachain: bp_runtime::Chain,
+		ThisRuntime,
+		ParachainsInstance: 'static,
+	>(
+		bridged_parachain: ParaId,
+		proof: FromBridgedChainMessagesProof<HashOf<BridgedChain<B>>>,
+		messages_count: u32,
+	) -> Result<ProvedMessages<Message<BalanceOf<BridgedChain<B>>>>, &'static str>
+	where
+		ThisRuntime: pallet_bridge_parachains::Config<ParachainsInstance>,
+		ThisRuntime: pallet_bridge_messages::Config<MessagesInstanceOf<BridgedChain<B>>>,
+		HashOf<BridgedChain<B>>: Into<ParaHash>,
+	{
+		verify_messages_proof_with_parser::<B, _, _>(
+			proof,
+			messages_count,
+			|bridged_header_hash, bridged_storage_proof| {
+				pallet_bridge_parachains::Pallet::<ThisRuntime, ParachainsInstance>::parse_finalized_storage_proof::<
+					BridgedParachain,
+					_,
+				>(
+					bridged_parachain,
+					bridged_header_hash.into(),
+					StorageProof::new(bridged_storage_proof),
+					|storage_adapter| storage_adapter,
+				)
+				.map(|storage| StorageProofCheckerAdapter::<_, B, ThisRuntime> {
+					storage,
+					_dummy: Default::default(),
+				})
+				.map_err(|err| MessageProofError::Custom(err.into()))
+			},
+		)
+		.map_err(Into::into)
+	}
+
 	#[derive(Debug, PartialEq)]
 	pub(crate) enum MessageProofError {
 		Empty,
@@ -934,6 +1113,8 @@ mod tests {
 		let message_on_bridged_chain = source::FromThisChainMessagePayload::<OnBridgedChainBridge> {
 			spec_version: 1,
 			weight: 100,
+			delivery_deadline: None,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 			origin: bp_message_dispatch::CallOrigin::SourceRoot,
 			call: ThisChainCall::Transfer.encode(),
 		}
@@ -948,6 +1129,8 @@ mod tests {
 			target::FromBridgedChainMessagePayload::<OnThisChainBridge> {
 				spec_version: 1,
 				weight: 100,
+				delivery_deadline: None,
+				dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 				origin: bp_message_dispatch::CallOrigin::SourceRoot,
 				call: target::FromBridgedChainEncodedMessageCall::<OnThisChainBridge> {
 					encoded_call: ThisChainCall::Transfer.encode(),
@@ -958,13 +1141,15 @@ mod tests {
 		assert_eq!(Ok(ThisChainCall::Transfer), message_on_this_chain.call.into());
 	}
 
-	const TEST_LANE_ID: &LaneId = b"test";
+	const TEST_LANE_ID: &LaneId = &LaneId(*b"test");
 	const MAXIMAL_PENDING_MESSAGES_AT_TEST_LANE: MessageNonce = 32;
 
 	fn regular_outbound_message_payload() -> source::FromThisChainMessagePayload<OnThisChainBridge> {
 		source::FromThisChainMessagePayload::<OnThisChainBridge> {
 			spec_version: 1,
 			weight: 100,
+			delivery_deadline: None,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 			origin: bp_message_dispatch::CallOrigin::SourceRoot,
 			call: vec![42],
 		}
@@ -1009,12 +1194,31 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn message_fee_excludes_dispatch_fee_paid_at_target_chain() {
+		const EXPECTED_MINIMAL_FEE: u32 = 2860;
+
+		// payload of the This -> Bridged chain message that pays dispatch fee at the Bridged chain
+		let mut payload = regular_outbound_message_payload();
+		payload.dispatch_fee_payment = DispatchFeePayment::AtTargetChain;
+
+		assert_eq!(
+			source::estimate_message_dispatch_and_delivery_fee::<OnThisChainBridge>(
+				&payload,
+				OnThisChainBridge::RELAYER_FEE_PERCENT,
+			),
+			Ok(ThisChainBalance(EXPECTED_MINIMAL_FEE)),
+		);
+	}
+
 	#[test]
 	fn should_disallow_root_calls_from_regular_accounts() {
 		// payload of the This -> Bridged chain message
 		let payload = source::FromThisChainMessagePayload::<OnThisChainBridge> {
 			spec_version: 1,
 			weight: 100,
+			delivery_deadline: None,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 			origin: bp_message_dispatch::CallOrigin::SourceRoot,
 			call: vec![42],
 		};
@@ -1058,6 +1262,8 @@ mod tests {
 		let payload = source::FromThisChainMessagePayload::<OnThisChainBridge> {
 			spec_version: 1,
 			weight: 100,
+			delivery_deadline: None,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 			origin: bp_message_dispatch::CallOrigin::SourceAccount(ThisChainAccountId(1)),
 			call: vec![42],
 		};
@@ -1125,6 +1331,8 @@ mod tests {
 			> {
 				spec_version: 1,
 				weight: 5,
+				delivery_deadline: None,
+				dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 				origin: bp_message_dispatch::CallOrigin::SourceRoot,
 				call: vec![1, 2, 3, 4, 5, 6],
 			},)
@@ -1140,6 +1348,8 @@ mod tests {
 			> {
 				spec_version: 1,
 				weight: BRIDGED_CHAIN_MAX_EXTRINSIC_WEIGHT + 1,
+				delivery_deadline: None,
+				dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 				origin: bp_message_dispatch::CallOrigin::SourceRoot,
 				call: vec![1, 2, 3, 4, 5, 6],
 			},)
@@ -1155,6 +1365,8 @@ mod tests {
 			> {
 				spec_version: 1,
 				weight: BRIDGED_CHAIN_MAX_EXTRINSIC_WEIGHT,
+				delivery_deadline: None,
+				dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 				origin: bp_message_dispatch::CallOrigin::SourceRoot,
 				call: vec![0; source::maximal_message_size::<OnThisChainBridge>() as usize + 1],
 			},)
@@ -1170,6 +1382,8 @@ mod tests {
 			> {
 				spec_version: 1,
 				weight: BRIDGED_CHAIN_MAX_EXTRINSIC_WEIGHT,
+				delivery_deadline: None,
+				dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 				origin: bp_message_dispatch::CallOrigin::SourceRoot,
 				call: vec![0; source::maximal_message_size::<OnThisChainBridge>() as _],
 			},),
@@ -13,8 +13,8 @@ RUST_LOG=bridge=debug \
 	--lane 00000000 \
 	--source-host localhost \
 	--source-port $RIALTO_PORT \
-	--source-signer //Bob \
+	--source-signer //Charlie \
 	--target-host localhost \
 	--target-port $MILLAU_PORT \
-	--target-signer //Bob \
+	--target-signer //Charlie \
 	--prometheus-host=0.0.0.0
@@ -0,0 +1,189 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bridge Conversion Rate Pallet
+//!
+//! This pallet stores the rate that is used to convert bridged chain tokens into this chain
+//! tokens. The rate is used by the messages fee computation: fee for delivering and dispatching
+//! the message at the bridged chain is paid in this chain tokens.
+//!
+//! The rate may only be updated by the `OracleOrigin` (e.g. the relayer, that follows the
+//! external prices, or the root). Until the first update, the `InitialConversionRate` is used.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Runtime-generated enums
+#![allow(clippy::large_enum_variant)]
+
+use frame_support::traits::Get;
+use sp_runtime::{FixedPointNumber, FixedU128};
+
+#[cfg(test)]
+mod mock;
+
+// Re-export in crate namespace for `construct_runtime!`
+pub use pallet::*;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config<I: 'static = ()>: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self, I>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Origin that is allowed to update the conversion rate.
+		type OracleOrigin: EnsureOrigin<Self::Origin>;
+		/// Conversion rate that is used until the first update.
+		#[pallet::constant]
+		type InitialConversionRate: Get<FixedU128>;
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
+
+	#[pallet::hooks]
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {}
+
+	#[pallet::call]
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
+		/// Update the bridged to this chain tokens conversion rate.
+		///
+		/// May only be called by the `OracleOrigin`. The rate must be positive.
+		#[pallet::weight(T::DbWeight::get().writes(2))]
+		pub fn update_conversion_rate(origin: OriginFor<T>, conversion_rate: FixedU128) -> DispatchResultWithPostInfo {
+			T::OracleOrigin::ensure_origin(origin)?;
+			ensure!(!conversion_rate.is_zero(), Error::<T, I>::ZeroConversionRate);
+
+			ConversionRate::<T, I>::put(conversion_rate);
+			UpdatedAt::<T, I>::put(frame_system::Pallet::<T>::block_number());
+
+			log::trace!(
+				target: "runtime::bridge-conversion-rate",
+				"Updated conversion rate: {:?}",
+				conversion_rate,
+			);
+
+			Self::deposit_event(Event::ConversionRateUpdated(conversion_rate));
+
+			Ok(().into())
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	#[pallet::metadata(FixedU128 = "FixedU128")]
+	pub enum Event<T: Config<I>, I: 'static = ()> {
+		/// Conversion rate has been updated. [conversion_rate]
+		ConversionRateUpdated(FixedU128),
+	}
+
+	#[pallet::error]
+	pub enum Error<T, I = ()> {
+		/// Conversion rate must be positive.
+		ZeroConversionRate,
+	}
+
+	#[pallet::type_value]
+	pub fn DefaultConversionRate<T: Config<I>, I: 'static>() -> FixedU128 {
+		T::InitialConversionRate::get()
+	}
+
+	/// Current rate that is used to convert bridged chain tokens into this chain tokens.
+	///
+	/// The conversion formula is: `ThisChainTokens = BridgedChainTokens * conversion_rate`.
+	#[pallet::storage]
+	#[pallet::getter(fn conversion_rate)]
+	pub type ConversionRate<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, FixedU128, ValueQuery, DefaultConversionRate<T, I>>;
+
+	/// Number of the block where the conversion rate has been updated last time.
+	#[pallet::storage]
+	#[pallet::getter(fn updated_at)]
+	pub type UpdatedAt<T: Config<I>, I: 'static = ()> = StorageValue<_, T::BlockNumber, OptionQuery>;
+}
+
+impl<T: Config<I>, I: 'static> Get<FixedU128> for Pallet<T, I> {
+	fn get() -> FixedU128 {
+		ConversionRate::<T, I>::get()
+	}
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Convert bridged chain tokens into this chain tokens, using the current conversion rate.
+	pub fn convert(bridged_tokens: u128) -> u128 {
+		ConversionRate::<T, I>::get().saturating_mul_int(bridged_tokens)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::*;
+	use frame_support::{assert_noop, assert_ok};
+	use sp_runtime::DispatchError;
+
+	#[test]
+	fn initial_conversion_rate_is_used_before_first_update() {
+		run_test(|| {
+			assert_eq!(Pallet::<TestRuntime>::conversion_rate(), InitialConversionRate::get());
+			assert_eq!(Pallet::<TestRuntime>::updated_at(), None);
+			assert_eq!(Pallet::<TestRuntime>::convert(100), 200);
+		});
+	}
+
+	#[test]
+	fn oracle_updates_conversion_rate() {
+		run_test(|| {
+			System::set_block_number(10);
+			let conversion_rate = FixedU128::saturating_from_rational(25, 100);
+			assert_ok!(Pallet::<TestRuntime>::update_conversion_rate(
+				Origin::signed(ORACLE),
+				conversion_rate,
+			));
+
+			assert_eq!(<Pallet<TestRuntime> as Get<FixedU128>>::get(), conversion_rate);
+			assert_eq!(Pallet::<TestRuntime>::updated_at(), Some(10));
+			assert_eq!(Pallet::<TestRuntime>::convert(100), 25);
+		});
+	}
+
+	#[test]
+	fn only_oracle_updates_conversion_rate() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::update_conversion_rate(Origin::signed(ORACLE + 1), 10.into()),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::update_conversion_rate(Origin::root(), 10.into()),
+				DispatchError::BadOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn zero_conversion_rate_is_rejected() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::update_conversion_rate(Origin::signed(ORACLE), FixedU128::from_inner(0)),
+				Error::<TestRuntime>::ZeroConversionRate,
+			);
+		});
+	}
+}
@@ -24,8 +24,11 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
-use bp_message_dispatch::{CallOrigin, MessageDispatch, MessagePayload, SpecVersion, Weight};
-use bp_runtime::{derive_account_id, ChainId, SourceAccount};
+use bp_message_dispatch::{
+	CallOrigin, CallOriginKind, DispatchFeePayment, MessageDispatch, MessagePayload, SpecVersion, TargetAccountProof,
+	Weight, MAX_MULTISIG_SIGNATORIES, MULTISIG_ACCOUNT_DERIVATION_PREFIX, PURE_PROXY_ACCOUNT_DERIVATION_PREFIX,
+};
+use bp_runtime::{messages::MessageDispatchResult, AccountDerivation, ChainId, DeclaredWeight, SourceAccount};
 use codec::{Decode, Encode};
 use frame_support::{
 	decl_event, decl_module, decl_storage,
@@ -36,11 +39,22 @@ use frame_support::{
 };
 use frame_system::RawOrigin;
 use sp_runtime::{
-	traits::{BadOrigin, Convert, IdentifyAccount, MaybeDisplay, MaybeSerializeDeserialize, Member, Verify},
+	traits::{
+		BadOrigin, BlakeTwo256, Convert, Hash, IdentifyAccount, MaybeDisplay, MaybeSerializeDeserialize, Member,
+		Saturating, Verify, Zero,
+	},
 	DispatchResult,
 };
 use sp_std::{fmt::Debug, marker::PhantomData, prelude::*};
 
+/// Scheme that is used to derive target chain accounts for the `CallOrigin::SourceRoot` and
+/// `CallOrigin::SourceAccount` origins.
+///
+/// Changing it would change addresses of all bridged accounts. Other components (e.g. the token
+/// swap pallet and relays) are deriving accounts with `bp_runtime::derive_account_id`, so it must
+/// be kept in sync with this scheme.
+pub const ACCOUNT_DERIVATION: AccountDerivation = AccountDerivation::V0;
+
 /// The module configuration trait.
 pub trait Config<I = DefaultInstance>: frame_system::Config {
 	/// The overarching event type.
@@ -79,15 +93,60 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 	///
 	/// Used when deriving target chain AccountIds from source chain AccountIds.
 	type AccountIdConverter: sp_runtime::traits::Convert<sp_core::hash::H256, Self::AccountId>;
+	/// Checks proxy relationship between target chain accounts.
+	///
+	/// Used to verify `TargetAccountProof::PureProxy` proofs. Use `()` if the runtime doesn't
+	/// support proxies - all pure proxy proofs are rejected then.
+	type ProxyCheck: ProxyCheck<Self::AccountId, Self::BlockNumber>;
+	/// Number of blocks after runtime upgrade, during which messages that have been encoded
+	/// using the previous runtime `spec_version` are still accepted.
+	///
+	/// Calls of such messages are decoded using the current runtime `Call` type, so they're only
+	/// dispatched if they're still decodable. Use zero to reject all messages with previous
+	/// `spec_version` immediately after the upgrade.
+	type SpecVersionGracePeriod: Get<Self::BlockNumber>;
+}
+
+/// Proxy relationship between target chain accounts.
+pub trait ProxyCheck<AccountId, BlockNumber> {
+	/// Type of the proxy relationship.
+	type ProxyType;
+
+	/// Returns proxy type that allows the delegate to dispatch any call on behalf of the proxied account.
+	fn any_proxy_type() -> Self::ProxyType;
+
+	/// Returns true if the `delegate` account is registered as a proxy of the `proxied` account,
+	/// with given proxy type and announcement delay.
+	fn is_proxy(proxied: &AccountId, delegate: &AccountId, proxy_type: &Self::ProxyType, delay: &BlockNumber) -> bool;
+}
+
+impl<AccountId, BlockNumber> ProxyCheck<AccountId, BlockNumber> for () {
+	type ProxyType = ();
+
+	fn any_proxy_type() -> Self::ProxyType {}
+
+	fn is_proxy(_proxied: &AccountId, _delegate: &AccountId, _proxy_type: &(), _delay: &BlockNumber) -> bool {
+		false
+	}
 }
 
 decl_storage! {
-	trait Store for Pallet<T: Config<I>, I: Instance = DefaultInstance> as Dispatch {}
+	trait Store for Pallet<T: Config<I>, I: Instance = DefaultInstance> as Dispatch {
+		/// Runtime `spec_version`, observed by the pallet at the last runtime upgrade.
+		ObservedSpecVersion: Option<SpecVersion>;
+		/// Previous runtime `spec_version` and the number of the last block that has been built
+		/// by the previous runtime.
+		///
+		/// Messages that have been encoded using this version are accepted until the
+		/// `SpecVersionGracePeriod` is over.
+		pub PreviousSpecVersion get(fn previous_spec_version): Option<(SpecVersion, T::BlockNumber)>;
+	}
 }
 
 decl_event!(
 	pub enum Event<T, I = DefaultInstance> where
-		<T as Config<I>>::MessageId
+		<T as Config<I>>::MessageId,
+		AccountId = <T as frame_system::Config>::AccountId,
 	{
 		/// Message has been rejected before reaching dispatch.
 		MessageRejected(ChainId, MessageId),
@@ -100,11 +159,17 @@ decl_event!(
 		/// Message signature mismatch.
 		MessageSignatureMismatch(ChainId, MessageId),
 		/// Message has been dispatched with given result.
-		MessageDispatched(ChainId, MessageId, DispatchResult),
+		/// Arguments after message id are: kind of the call origin, actual weight of the call,
+		/// dispatch result and human-readable description of the dispatch error (empty if the call
+		/// has succeeded).
+		MessageDispatched(ChainId, MessageId, CallOriginKind, Weight, DispatchResult, Vec<u8>),
 		/// We have failed to decode Call from the message.
 		MessageCallDecodeFailed(ChainId, MessageId),
 		/// The call from the message has been rejected by the call filter.
 		MessageCallRejected(ChainId, MessageId),
+		/// The dispatch origin account has failed to pay the dispatch fee at this chain.
+		/// Last two arguments are: the dispatch origin account and the declared call weight.
+		MessageDispatchPaymentFailed(ChainId, MessageId, AccountId, Weight),
 		/// Phantom member, never used. Needed to handle multiple pallet instances.
 		_Dummy(PhantomData<I>),
 	}
@@ -115,18 +180,50 @@ decl_module! {
 	pub struct Module<T: Config<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
 		/// Deposit one of this module's events by using the default implementation.
 		fn deposit_event() = default;
+
+		/// Start the grace period for messages that have been encoded using previous `spec_version`.
+		fn on_runtime_upgrade() -> Weight {
+			let current_version = <T as frame_system::Config>::Version::get().spec_version;
+			let observed_version = ObservedSpecVersion::<I>::get();
+			if observed_version == Some(current_version) {
+				return T::DbWeight::get().reads(1);
+			}
+
+			// the very first upgrade after the pallet has been deployed doesn't start the grace
+			// period, because we don't know the previous version
+			let mut writes = 1;
+			if let Some(previous_version) = observed_version {
+				PreviousSpecVersion::<T, I>::put((previous_version, frame_system::Pallet::<T>::block_number()));
+				writes += 1;
+			}
+			ObservedSpecVersion::<I>::put(current_version);
+
+			T::DbWeight::get().reads_writes(1, writes)
+		}
 	}
 }
 
-impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
+impl<T: Config<I>, I: Instance> MessageDispatch<T::AccountId, T::MessageId> for Pallet<T, I> {
 	type Message =
 		MessagePayload<T::SourceChainAccountId, T::TargetChainAccountPublic, T::TargetChainSignature, T::EncodedCall>;
 
 	fn dispatch_weight(message: &Self::Message) -> Weight {
-		message.weight
+		message.declared_weight()
 	}
 
-	fn dispatch(source_chain: ChainId, target_chain: ChainId, id: T::MessageId, message: Result<Self::Message, ()>) {
+	fn dispatch<P: FnOnce(&T::AccountId, Weight) -> Result<(), ()>>(
+		source_chain: ChainId,
+		target_chain: ChainId,
+		id: T::MessageId,
+		message: Result<Self::Message, ()>,
+		pay_dispatch_fee: P,
+	) -> MessageDispatchResult {
+		// the message is considered failed until the call is actually dispatched
+		let mut result = MessageDispatchResult {
+			dispatch_result: false,
+			dispatch_fee_paid: true,
+		};
+
 		// emit special even if message has been rejected by external component
 		let message = match message {
 			Ok(message) => message,
@@ -138,14 +235,16 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 					id,
 				);
 				Self::deposit_event(RawEvent::MessageRejected(source_chain, id));
-				return;
+				return result;
 			}
 		};
 
 		// verify spec version
 		// (we want it to be the same, because otherwise we may decode Call improperly)
+		// (the only exception is the previous version during the grace period after the upgrade
+		// - the call is then re-validated by decoding it using the current `Call` type)
 		let expected_version = <T as frame_system::Config>::Version::get().spec_version;
-		if message.spec_version != expected_version {
+		if message.spec_version != expected_version && !is_within_grace_period::<T, I>(message.spec_version) {
 			log::trace!(
 				"Message {:?}/{:?}: spec_version mismatch. Expected {:?}, got {:?}",
 				source_chain,
@@ -159,7 +258,7 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 				expected_version,
 				message.spec_version,
 			));
-			return;
+			return result;
 		}
 
 		// now that we have spec version checked, let's decode the call
@@ -168,20 +267,22 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 			Err(_) => {
 				log::trace!(
 					target: "runtime::bridge-dispatch",
-					"Failed to decode Call from message {:?}/{:?}",
+					"Failed to decode Call from message {:?}/{:?} (spec_version: {:?})",
 					source_chain,
 					id,
+					message.spec_version,
 				);
 				Self::deposit_event(RawEvent::MessageCallDecodeFailed(source_chain, id));
-				return;
+				return result;
 			}
 		};
 
 		// prepare dispatch origin
+		let origin_kind = message.origin.kind();
+		let dispatch_fee_weight = message.declared_weight();
 		let origin_account = match message.origin {
 			CallOrigin::SourceRoot => {
-				let hex_id = derive_account_id::<T::SourceChainAccountId>(source_chain, SourceAccount::Root);
-				let target_id = T::AccountIdConverter::convert(hex_id);
+				let target_id = derive_origin_account::<T, I>(source_chain, SourceAccount::Root);
 				log::trace!(target: "runtime::bridge-dispatch", "Root Account: {:?}", &target_id);
 				target_id
 			}
@@ -205,18 +306,43 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 						target_signature,
 					);
 					Self::deposit_event(RawEvent::MessageSignatureMismatch(source_chain, id));
-					return;
+					return result;
 				}
 
 				log::trace!(target: "runtime::bridge-dispatch", "Target Account: {:?}", &target_account);
 				target_account
 			}
 			CallOrigin::SourceAccount(source_account_id) => {
-				let hex_id = derive_account_id(source_chain, SourceAccount::Account(source_account_id));
-				let target_id = T::AccountIdConverter::convert(hex_id);
+				let target_id = derive_origin_account::<T, I>(source_chain, SourceAccount::Account(source_account_id));
 				log::trace!(target: "runtime::bridge-dispatch", "Source Account: {:?}", &target_id);
 				target_id
 			}
+			CallOrigin::TargetAccountWithProof(source_account_id, proof) => {
+				let digest = account_ownership_digest(
+					&call,
+					source_account_id,
+					message.spec_version,
+					source_chain,
+					target_chain,
+				);
+
+				let target_account = match verify_target_account_proof::<T, I>(&digest, proof) {
+					Some(target_account) => target_account,
+					None => {
+						log::trace!(
+							target: "runtime::bridge-dispatch",
+							"Message {:?}/{:?}: multisig or proxy origin proof is invalid",
+							source_chain,
+							id,
+						);
+						Self::deposit_event(RawEvent::MessageSignatureMismatch(source_chain, id));
+						return result;
+					}
+				};
+
+				log::trace!(target: "runtime::bridge-dispatch", "Target Account: {:?}", &target_account);
+				target_account
+			}
 		};
 
 		// filter the call
@@ -229,7 +355,7 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 				call,
 			);
 			Self::deposit_event(RawEvent::MessageCallRejected(source_chain, id));
-			return;
+			return result;
 		}
 
 		// verify weight
@@ -252,7 +378,29 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 				expected_weight,
 				message.weight,
 			));
-			return;
+			return result;
+		}
+
+		// pay dispatch fee right before dispatch
+		if message.dispatch_fee_payment == DispatchFeePayment::AtTargetChain
+			&& pay_dispatch_fee(&origin_account, dispatch_fee_weight).is_err()
+		{
+			log::trace!(
+				target: "runtime::bridge-dispatch",
+				"Message {:?}/{:?}: failed to pay dispatch fee for dispatching {} weight from {:?}",
+				source_chain,
+				id,
+				dispatch_fee_weight,
+				origin_account,
+			);
+			Self::deposit_event(RawEvent::MessageDispatchPaymentFailed(
+				source_chain,
+				id,
+				origin_account,
+				dispatch_fee_weight,
+			));
+			result.dispatch_fee_paid = false;
+			return result;
 		}
 
 		// finally dispatch message
@@ -271,11 +419,119 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 			dispatch_result,
 		);
 
+		let dispatch_result = dispatch_result.map(drop).map_err(|e| e.error);
+		result.dispatch_result = dispatch_result.is_ok();
+		let error_description = match dispatch_result {
+			Ok(_) => Vec::new(),
+			Err(error) => <&'static str>::from(error).as_bytes().to_vec(),
+		};
 		Self::deposit_event(RawEvent::MessageDispatched(
 			source_chain,
 			id,
-			dispatch_result.map(drop).map_err(|e| e.error),
+			origin_kind,
+			actual_call_weight,
+			dispatch_result,
+			error_description,
 		));
+
+		result
+	}
+}
+
+/// Returns true if message with given `spec_version` has been encoded using the previous runtime
+/// version and the grace period after the runtime upgrade is not yet over.
+fn is_within_grace_period<T: Config<I>, I: Instance>(spec_version: SpecVersion) -> bool {
+	match PreviousSpecVersion::<T, I>::get() {
+		Some((previous_version, upgrade_block)) if previous_version == spec_version => {
+			frame_system::Pallet::<T>::block_number() <= upgrade_block.saturating_add(T::SpecVersionGracePeriod::get())
+		}
+		_ => false,
+	}
+}
+
+/// Derive target chain account for the source chain origin, using the `ACCOUNT_DERIVATION` scheme.
+fn derive_origin_account<T: Config<I>, I: Instance>(
+	source_chain: ChainId,
+	source_account: SourceAccount<T::SourceChainAccountId>,
+) -> T::AccountId {
+	T::AccountIdConverter::convert(ACCOUNT_DERIVATION.derive(source_chain, source_account))
+}
+
+/// Verify proof of control over the target chain account and return this account.
+///
+/// Returns `None` if the proof is invalid.
+fn verify_target_account_proof<T: Config<I>, I: Instance>(
+	digest: &[u8],
+	proof: TargetAccountProof<T::TargetChainAccountPublic, T::TargetChainSignature>,
+) -> Option<T::AccountId> {
+	match proof {
+		TargetAccountProof::Multisig {
+			signatories,
+			threshold,
+			signatures,
+		} => {
+			// the number of signature verifications must be bounded, because their cost is
+			// accounted by `TargetAccountProof::verification_weight`
+			if threshold == 0
+				|| signatories.len() > MAX_MULTISIG_SIGNATORIES as usize
+				|| threshold as usize > signatories.len()
+				|| signatures.len() > signatories.len()
+			{
+				return None;
+			}
+
+			// every signature must be valid and there must be at least `threshold` signatures of
+			// distinct signatories
+			let signatories = signatories
+				.into_iter()
+				.map(IdentifyAccount::into_account)
+				.collect::<Vec<_>>();
+			let mut signed = Vec::with_capacity(signatures.len());
+			for (index, signature) in signatures {
+				let signatory = signatories.get(index as usize)?;
+				if signed.contains(&index) || !signature.verify(digest, signatory) {
+					return None;
+				}
+				signed.push(index);
+			}
+			if signed.len() < threshold as usize {
+				return None;
+			}
+
+			// `pallet-multisig` derives account from the sorted list of unique signatories
+			let mut signatories = signatories;
+			signatories.sort();
+			if signatories.windows(2).any(|pair| pair[0] == pair[1]) {
+				return None;
+			}
+
+			let entropy = (MULTISIG_ACCOUNT_DERIVATION_PREFIX, signatories, threshold).using_encoded(BlakeTwo256::hash);
+			Some(T::AccountIdConverter::convert(entropy))
+		}
+		TargetAccountProof::PureProxy {
+			spawner,
+			spawn_data,
+			signature,
+		} => {
+			let spawner = spawner.into_account();
+			if !signature.verify(digest, &spawner) {
+				return None;
+			}
+
+			let mut entropy = (PURE_PROXY_ACCOUNT_DERIVATION_PREFIX, &spawner).encode();
+			entropy.extend_from_slice(&spawn_data);
+			let pure_proxy = T::AccountIdConverter::convert(BlakeTwo256::hash(&entropy));
+
+			// the spawner may have been removed from proxies of the pure proxy account, or it may
+			// only be allowed to dispatch some calls (or after announcement) - we only accept
+			// proxies that may immediately dispatch any call
+			let any_proxy_type = T::ProxyCheck::any_proxy_type();
+			if !T::ProxyCheck::is_proxy(&pure_proxy, &spawner, &any_proxy_type, &Zero::zero()) {
+				return None;
+			}
+
+			Some(pure_proxy)
+		}
 	}
 }
 
@@ -311,6 +567,13 @@ where
 			);
 			Ok(Some(source_account_id.clone()))
 		}
+		CallOrigin::TargetAccountWithProof(ref source_account_id, _) => {
+			ensure!(
+				sender_origin == &RawOrigin::Signed(source_account_id.clone()),
+				BadOrigin
+			);
+			Ok(Some(source_account_id.clone()))
+		}
 	}
 }
 
@@ -347,6 +610,7 @@ mod tests {
 	#![allow(clippy::from_over_into)]
 
 	use super::*;
+	use bp_message_dispatch::{PROXY_CHECK_WEIGHT, SIGNATURE_VERIFICATION_WEIGHT};
 	use frame_support::{parameter_types, weights::Weight};
 	use frame_system::{EventRecord, Phase};
 	use sp_core::H256;
@@ -413,6 +677,7 @@ mod tests {
 		pub const MaximumBlockWeight: Weight = 1024;
 		pub const MaximumBlockLength: u32 = 2 * 1024;
 		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const SpecVersionGracePeriod: u64 = 10;
 	}
 
 	impl frame_system::Config for TestRuntime {
@@ -451,6 +716,38 @@ mod tests {
 		type CallFilter = TestCallFilter;
 		type EncodedCall = EncodedCall;
 		type AccountIdConverter = AccountIdConverter;
+		type ProxyCheck = TestProxyCheck;
+		type SpecVersionGracePeriod = SpecVersionGracePeriod;
+	}
+
+	const PURE_PROXY_SPAWNER: AccountId = 42;
+	const RESTRICTED_PURE_PROXY_SPAWNER: AccountId = 43;
+	const DELAYED_PURE_PROXY_SPAWNER: AccountId = 44;
+
+	#[derive(Debug, PartialEq)]
+	pub enum TestProxyType {
+		Any,
+		Restricted,
+	}
+
+	pub struct TestProxyCheck;
+
+	impl ProxyCheck<AccountId, u64> for TestProxyCheck {
+		type ProxyType = TestProxyType;
+
+		fn any_proxy_type() -> TestProxyType {
+			TestProxyType::Any
+		}
+
+		fn is_proxy(_proxied: &AccountId, delegate: &AccountId, proxy_type: &TestProxyType, delay: &u64) -> bool {
+			let (registered_proxy_type, registered_delay) = match *delegate {
+				PURE_PROXY_SPAWNER => (TestProxyType::Any, 0),
+				RESTRICTED_PURE_PROXY_SPAWNER => (TestProxyType::Restricted, 0),
+				DELAYED_PURE_PROXY_SPAWNER => (TestProxyType::Any, 10),
+				_ => return false,
+			};
+			registered_proxy_type == *proxy_type && registered_delay == *delay
+		}
 	}
 
 	#[derive(Decode, Encode)]
@@ -483,10 +780,12 @@ mod tests {
 	fn prepare_message(
 		origin: CallOrigin<AccountId, TestAccountPublic, TestSignature>,
 		call: Call,
-	) -> <Pallet<TestRuntime> as MessageDispatch<<TestRuntime as Config>::MessageId>>::Message {
+	) -> <Pallet<TestRuntime> as MessageDispatch<AccountId, <TestRuntime as Config>::MessageId>>::Message {
 		MessagePayload {
 			spec_version: TEST_SPEC_VERSION,
 			weight: TEST_WEIGHT,
+			delivery_deadline: None,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 			origin,
 			call: EncodedCall(call.encode()),
 		}
@@ -494,24 +793,43 @@ mod tests {
 
 	fn prepare_root_message(
 		call: Call,
-	) -> <Pallet<TestRuntime> as MessageDispatch<<TestRuntime as Config>::MessageId>>::Message {
+	) -> <Pallet<TestRuntime> as MessageDispatch<AccountId, <TestRuntime as Config>::MessageId>>::Message {
 		prepare_message(CallOrigin::SourceRoot, call)
 	}
 
 	fn prepare_target_message(
 		call: Call,
-	) -> <Pallet<TestRuntime> as MessageDispatch<<TestRuntime as Config>::MessageId>>::Message {
+	) -> <Pallet<TestRuntime> as MessageDispatch<AccountId, <TestRuntime as Config>::MessageId>>::Message {
 		let origin = CallOrigin::TargetAccount(1, TestAccountPublic(1), TestSignature(1));
 		prepare_message(origin, call)
 	}
 
 	fn prepare_source_message(
 		call: Call,
-	) -> <Pallet<TestRuntime> as MessageDispatch<<TestRuntime as Config>::MessageId>>::Message {
+	) -> <Pallet<TestRuntime> as MessageDispatch<AccountId, <TestRuntime as Config>::MessageId>>::Message {
 		let origin = CallOrigin::SourceAccount(1);
 		prepare_message(origin, call)
 	}
 
+	#[test]
+	fn origin_accounts_are_derived_using_v0_scheme() {
+		assert_eq!(ACCOUNT_DERIVATION, AccountDerivation::V0);
+		assert_eq!(
+			derive_origin_account::<TestRuntime, DefaultInstance>(SOURCE_CHAIN_ID, SourceAccount::Account(1)),
+			AccountIdConverter::convert(bp_runtime::derive_account_id(
+				SOURCE_CHAIN_ID,
+				SourceAccount::Account(1)
+			)),
+		);
+		assert_eq!(
+			derive_origin_account::<TestRuntime, DefaultInstance>(SOURCE_CHAIN_ID, SourceAccount::Root),
+			AccountIdConverter::convert(bp_runtime::derive_account_id::<AccountId>(
+				SOURCE_CHAIN_ID,
+				SourceAccount::Root
+			)),
+		);
+	}
+
 	#[test]
 	fn should_fail_on_spec_version_mismatch() {
 		new_test_ext().execute_with(|| {
@@ -523,7 +841,7 @@ mod tests {
 			message.spec_version = BAD_SPEC_VERSION;
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -541,6 +859,119 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_dispatch_message_with_previous_spec_version_during_grace_period() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			const PREVIOUS_SPEC_VERSION: SpecVersion = 99;
+			PreviousSpecVersion::<TestRuntime, DefaultInstance>::put((PREVIOUS_SPEC_VERSION, 1));
+			let mut message =
+				prepare_root_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
+			message.spec_version = PREVIOUS_SPEC_VERSION;
+
+			System::set_block_number(1 + SpecVersionGracePeriod::get());
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert!(matches!(
+				System::events().as_slice(),
+				[EventRecord {
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatched(
+						_,
+						_,
+						_,
+						_,
+						Ok(()),
+						_
+					)),
+					..
+				}],
+			));
+		});
+	}
+
+	#[test]
+	fn should_fail_on_previous_spec_version_after_grace_period() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			const PREVIOUS_SPEC_VERSION: SpecVersion = 99;
+			PreviousSpecVersion::<TestRuntime, DefaultInstance>::put((PREVIOUS_SPEC_VERSION, 1));
+			let mut message =
+				prepare_root_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
+			message.spec_version = PREVIOUS_SPEC_VERSION;
+
+			System::set_block_number(2 + SpecVersionGracePeriod::get());
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageVersionSpecMismatch(
+						SOURCE_CHAIN_ID,
+						id,
+						TEST_SPEC_VERSION,
+						PREVIOUS_SPEC_VERSION
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn should_fail_on_call_decode_with_previous_spec_version() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			const PREVIOUS_SPEC_VERSION: SpecVersion = 99;
+			PreviousSpecVersion::<TestRuntime, DefaultInstance>::put((PREVIOUS_SPEC_VERSION, 1));
+			let mut message =
+				prepare_root_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
+			message.spec_version = PREVIOUS_SPEC_VERSION;
+			message.call.0 = vec![0xFF];
+
+			System::set_block_number(1);
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageCallDecodeFailed(
+						SOURCE_CHAIN_ID,
+						id
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn runtime_upgrade_starts_grace_period() {
+		new_test_ext().execute_with(|| {
+			use frame_support::traits::OnRuntimeUpgrade;
+
+			// first upgrade only remembers the current version
+			System::set_block_number(5);
+			Dispatch::on_runtime_upgrade();
+			assert_eq!(Dispatch::previous_spec_version(), None);
+
+			// no upgrade => no grace period
+			Dispatch::on_runtime_upgrade();
+			assert_eq!(Dispatch::previous_spec_version(), None);
+
+			// actual upgrade starts the grace period
+			ObservedSpecVersion::<DefaultInstance>::put(99);
+			System::set_block_number(10);
+			Dispatch::on_runtime_upgrade();
+			assert_eq!(Dispatch::previous_spec_version(), Some((99, 10)));
+			assert_eq!(ObservedSpecVersion::<DefaultInstance>::get(), Some(TEST_SPEC_VERSION));
+		});
+	}
+
 	#[test]
 	fn should_fail_on_weight_mismatch() {
 		new_test_ext().execute_with(|| {
@@ -550,7 +981,7 @@ mod tests {
 			message.weight = 0;
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -580,7 +1011,7 @@ mod tests {
 			);
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -602,7 +1033,7 @@ mod tests {
 			let id = [0; 4];
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Err(()));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Err(()), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -628,7 +1059,7 @@ mod tests {
 			message.call.0 = vec![];
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -655,7 +1086,7 @@ mod tests {
 			message.weight = weight;
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -675,10 +1106,12 @@ mod tests {
 	fn should_dispatch_bridge_message_from_root_origin() {
 		new_test_ext().execute_with(|| {
 			let id = [0; 4];
-			let message = prepare_root_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
+			let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3]));
+			let weight = call.get_dispatch_info().weight;
+			let message = prepare_root_message(call);
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -687,7 +1120,92 @@ mod tests {
 					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatched(
 						SOURCE_CHAIN_ID,
 						id,
-						Ok(())
+						CallOriginKind::SourceRoot,
+						weight,
+						Ok(()),
+						vec![],
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn should_pay_dispatch_fee_at_target_chain() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+			let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3]));
+			let weight = call.get_dispatch_info().weight;
+			let mut message = prepare_root_message(call);
+			message.dispatch_fee_payment = DispatchFeePayment::AtTargetChain;
+
+			System::set_block_number(1);
+			let mut dispatch_fee_payer = None;
+			let result = Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |payer, weight| {
+				dispatch_fee_payer = Some((*payer, weight));
+				Ok(())
+			});
+
+			assert_eq!(
+				result,
+				MessageDispatchResult {
+					dispatch_result: true,
+					dispatch_fee_paid: true,
+				},
+			);
+			assert_eq!(
+				dispatch_fee_payer,
+				Some((
+					derive_origin_account::<TestRuntime, DefaultInstance>(SOURCE_CHAIN_ID, SourceAccount::Root),
+					TEST_WEIGHT,
+				)),
+			);
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatched(
+						SOURCE_CHAIN_ID,
+						id,
+						CallOriginKind::SourceRoot,
+						weight,
+						Ok(()),
+						vec![],
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn should_not_dispatch_message_if_dispatch_fee_payment_has_failed() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+			let mut message =
+				prepare_root_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
+			message.dispatch_fee_payment = DispatchFeePayment::AtTargetChain;
+
+			System::set_block_number(1);
+			let result = Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| Err(()));
+
+			assert_eq!(
+				result,
+				MessageDispatchResult {
+					dispatch_result: false,
+					dispatch_fee_paid: false,
+				},
+			);
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatchPaymentFailed(
+						SOURCE_CHAIN_ID,
+						id,
+						derive_origin_account::<TestRuntime, DefaultInstance>(SOURCE_CHAIN_ID, SourceAccount::Root),
+						TEST_WEIGHT,
 					)),
 					topics: vec![],
 				}],
@@ -701,10 +1219,11 @@ mod tests {
 			let id = [0; 4];
 
 			let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![]));
+			let weight = call.get_dispatch_info().weight;
 			let message = prepare_target_message(call);
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -713,7 +1232,10 @@ mod tests {
 					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatched(
 						SOURCE_CHAIN_ID,
 						id,
-						Ok(())
+						CallOriginKind::TargetAccount,
+						weight,
+						Ok(()),
+						vec![],
 					)),
 					topics: vec![],
 				}],
@@ -727,10 +1249,11 @@ mod tests {
 			let id = [0; 4];
 
 			let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![]));
+			let weight = call.get_dispatch_info().weight;
 			let message = prepare_source_message(call);
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -739,7 +1262,47 @@ mod tests {
 					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatched(
 						SOURCE_CHAIN_ID,
 						id,
-						Ok(())
+						CallOriginKind::SourceAccount,
+						weight,
+						Ok(()),
+						vec![],
+					)),
+					topics: vec![],
+				}],
+			);
+		})
+	}
+
+	#[test]
+	fn should_emit_dispatch_error_details() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let call = Call::System(<frame_system::Call<TestRuntime>>::set_heap_pages(1));
+			let weight = call.get_dispatch_info().weight;
+			let message = prepare_source_message(call);
+
+			System::set_block_number(1);
+			let result = Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				result,
+				MessageDispatchResult {
+					dispatch_result: false,
+					dispatch_fee_paid: true,
+				},
+			);
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatched(
+						SOURCE_CHAIN_ID,
+						id,
+						CallOriginKind::SourceAccount,
+						weight,
+						Err(sp_runtime::DispatchError::BadOrigin),
+						b"Bad origin".to_vec(),
 					)),
 					topics: vec![],
 				}],
@@ -810,4 +1373,198 @@ mod tests {
 			Err(BadOrigin)
 		));
 	}
+
+	fn multisig_proof(
+		signatories: Vec<AccountId>,
+		threshold: u16,
+		signatures: Vec<(u16, AccountId)>,
+	) -> TargetAccountProof<TestAccountPublic, TestSignature> {
+		TargetAccountProof::Multisig {
+			signatories: signatories.into_iter().map(TestAccountPublic).collect(),
+			threshold,
+			signatures: signatures
+				.into_iter()
+				.map(|(index, signer)| (index, TestSignature(signer)))
+				.collect(),
+		}
+	}
+
+	fn pure_proxy_proof(spawner: AccountId, signer: AccountId) -> TargetAccountProof<TestAccountPublic, TestSignature> {
+		TargetAccountProof::PureProxy {
+			spawner: TestAccountPublic(spawner),
+			spawn_data: (1u64, 0u32, 0u8, 0u16).encode(),
+			signature: TestSignature(signer),
+		}
+	}
+
+	#[test]
+	fn should_dispatch_bridge_message_from_multisig_origin() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![]));
+			let weight = call.get_dispatch_info().weight;
+			let proof = multisig_proof(vec![1, 2, 3], 2, vec![(0, 1), (2, 3)]);
+			let message = prepare_message(CallOrigin::TargetAccountWithProof(1, proof), call);
+
+			System::set_block_number(1);
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatched(
+						SOURCE_CHAIN_ID,
+						id,
+						CallOriginKind::TargetAccountWithProof,
+						weight,
+						Ok(()),
+						vec![],
+					)),
+					topics: vec![],
+				}],
+			);
+		})
+	}
+
+	#[test]
+	fn should_fail_on_invalid_multisig_proof() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let proof = multisig_proof(vec![1, 2, 3], 2, vec![(0, 1)]);
+			let message = prepare_message(
+				CallOrigin::TargetAccountWithProof(1, proof),
+				Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])),
+			);
+
+			System::set_block_number(1);
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageSignatureMismatch(
+						SOURCE_CHAIN_ID,
+						id
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn multisig_proof_is_verified() {
+		let verify = |proof| verify_target_account_proof::<TestRuntime, DefaultInstance>(b"digest", proof);
+
+		// account doesn't depend on order of signatories
+		let account = verify(multisig_proof(vec![1, 2, 3], 2, vec![(0, 1), (1, 2)]));
+		assert!(account.is_some());
+		assert_eq!(verify(multisig_proof(vec![3, 2, 1], 2, vec![(2, 1), (0, 3)])), account);
+		// account depends on threshold
+		assert_ne!(verify(multisig_proof(vec![1, 2, 3], 1, vec![(0, 1)])), account);
+
+		// not enough signatures
+		assert_eq!(verify(multisig_proof(vec![1, 2, 3], 2, vec![(0, 1)])), None);
+		// duplicate signatures
+		assert_eq!(verify(multisig_proof(vec![1, 2, 3], 2, vec![(0, 1), (0, 1)])), None);
+		// invalid signature
+		assert_eq!(verify(multisig_proof(vec![1, 2, 3], 2, vec![(0, 1), (1, 3)])), None);
+		// unknown signatory
+		assert_eq!(verify(multisig_proof(vec![1, 2, 3], 2, vec![(0, 1), (3, 4)])), None);
+		// duplicate signatories
+		assert_eq!(verify(multisig_proof(vec![1, 1, 3], 2, vec![(0, 1), (1, 1)])), None);
+		// zero threshold and threshold above number of signatories
+		assert_eq!(verify(multisig_proof(vec![1, 2, 3], 0, vec![])), None);
+		assert_eq!(
+			verify(multisig_proof(vec![1, 2, 3], 4, vec![(0, 1), (1, 2), (2, 3)])),
+			None
+		);
+		// too many signatories
+		let signatories = (0..MAX_MULTISIG_SIGNATORIES as AccountId + 1).collect::<Vec<_>>();
+		assert_eq!(verify(multisig_proof(signatories, 1, vec![(0, 0)])), None);
+		// more signatures than signatories
+		assert_eq!(
+			verify(multisig_proof(vec![1, 2], 1, vec![(0, 1), (1, 2), (0, 1)])),
+			None
+		);
+	}
+
+	#[test]
+	fn pure_proxy_proof_is_verified() {
+		let verify = |proof| verify_target_account_proof::<TestRuntime, DefaultInstance>(b"digest", proof);
+
+		let mut entropy = (PURE_PROXY_ACCOUNT_DERIVATION_PREFIX, PURE_PROXY_SPAWNER).encode();
+		entropy.extend_from_slice(&(1u64, 0u32, 0u8, 0u16).encode());
+		let pure_proxy = AccountIdConverter::convert(BlakeTwo256::hash(&entropy));
+
+		assert_eq!(
+			verify(pure_proxy_proof(PURE_PROXY_SPAWNER, PURE_PROXY_SPAWNER)),
+			Some(pure_proxy)
+		);
+		// invalid spawner signature
+		assert_eq!(verify(pure_proxy_proof(PURE_PROXY_SPAWNER, 1)), None);
+		// spawner is not a proxy of the pure proxy account anymore
+		assert_eq!(verify(pure_proxy_proof(1, 1)), None);
+		// spawner is only allowed to dispatch some calls
+		assert_eq!(
+			verify(pure_proxy_proof(
+				RESTRICTED_PURE_PROXY_SPAWNER,
+				RESTRICTED_PURE_PROXY_SPAWNER
+			)),
+			None
+		);
+		// spawner must announce calls before dispatching them
+		assert_eq!(
+			verify(pure_proxy_proof(DELAYED_PURE_PROXY_SPAWNER, DELAYED_PURE_PROXY_SPAWNER)),
+			None
+		);
+	}
+
+	#[test]
+	fn dispatch_weight_includes_origin_verification_weight() {
+		let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![]));
+
+		let message = prepare_root_message(call.clone());
+		assert_eq!(Dispatch::dispatch_weight(&message), TEST_WEIGHT);
+
+		let proof = multisig_proof(vec![1, 2, 3], 2, vec![(0, 1), (2, 3)]);
+		let message = prepare_message(CallOrigin::TargetAccountWithProof(1, proof), call.clone());
+		assert_eq!(
+			Dispatch::dispatch_weight(&message),
+			TEST_WEIGHT + 2 * SIGNATURE_VERIFICATION_WEIGHT,
+		);
+
+		let proof = pure_proxy_proof(PURE_PROXY_SPAWNER, PURE_PROXY_SPAWNER);
+		let message = prepare_message(CallOrigin::TargetAccountWithProof(1, proof), call);
+		assert_eq!(
+			Dispatch::dispatch_weight(&message),
+			TEST_WEIGHT + SIGNATURE_VERIFICATION_WEIGHT + PROXY_CHECK_WEIGHT,
+		);
+	}
+
+	#[test]
+	fn origin_is_checked_when_verifying_sending_message_using_target_account_with_proof() {
+		let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![]));
+		let message = prepare_message(
+			CallOrigin::TargetAccountWithProof(1, pure_proxy_proof(PURE_PROXY_SPAWNER, PURE_PROXY_SPAWNER)),
+			call,
+		);
+
+		assert!(matches!(
+			verify_message_origin(&RawOrigin::Signed(1), &message),
+			Ok(Some(1))
+		));
+		assert!(matches!(
+			verify_message_origin(&RawOrigin::Signed(2), &message),
+			Err(BadOrigin)
+		));
+		assert!(matches!(
+			verify_message_origin(&RawOrigin::Root, &message),
+			Err(BadOrigin)
+		));
+	}
 }
@@ -21,7 +21,7 @@
 use crate::finality::{CachedFinalityVotes, FinalityVotes};
 use bp_eth_poa::{Address, AuraHeader, HeaderId, RawTransaction, RawTransactionReceipt, Receipt, H256, U256};
 use codec::{Decode, Encode};
-use frame_support::{decl_module, decl_storage, traits::Get};
+use frame_support::{decl_module, decl_storage, traits::Get, weights::Weight};
 use sp_runtime::{
 	transaction_validity::{
 		InvalidTransaction, TransactionLongevity, TransactionPriority, TransactionSource, TransactionValidity,
@@ -31,11 +31,13 @@ use sp_runtime::{
 };
 use sp_std::{cmp::Ord, collections::btree_map::BTreeMap, prelude::*};
 
+pub use rewards::FinalityRewardsFromPot;
 pub use validators::{ValidatorsConfiguration, ValidatorsSource};
 
 mod error;
 mod finality;
 mod import;
+mod rewards;
 mod validators;
 mod verification;
 
@@ -390,25 +392,65 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 
 	/// Handler for headers submission result.
 	type OnHeadersSubmitted: OnHeadersSubmitted<Self::AccountId>;
+
+	/// Number of blocks (before the oldest block that is not yet pruned) for which we keep
+	/// hashes of pruned finalized headers.
+	///
+	/// Full headers (with transactions and receipts roots, required to verify transactions
+	/// inclusion) are only kept for the range, selected by `PruningStrategy`. After pruning,
+	/// we only remember hashes of finalized headers, so that they are still known to the module.
+	/// These hashes are pruned using leftover block weight (in `on_idle`). If zero, nothing is
+	/// kept after pruning.
+	type AncientHeadersToKeep: Get<u64>;
 }
 
 decl_module! {
 	pub struct Module<T: Config<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+		/// Prune old headers using leftover block weight.
+		fn on_idle(_n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			BridgeStorage::<T, I>::new().prune_on_idle(remaining_weight)
+		}
+
 		/// Import single Aura header. Requires transaction to be **UNSIGNED**.
+		///
+		/// If `rewards_account` is provided, it is stored as the header submitter and is
+		/// rewarded (see `OnHeadersSubmitted::on_valid_headers_finalized`) when the header
+		/// is finalized. Since the transaction is unsigned, anyone may resubmit the same
+		/// header with different `rewards_account` - only the first transaction that gets
+		/// into the pool is accepted, because both share the same `provides` tag.
 		#[weight = 0] // TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
-		pub fn import_unsigned_header(origin, header: AuraHeader, receipts: Option<Vec<Receipt>>) {
+		pub fn import_unsigned_header(
+			origin,
+			header: AuraHeader,
+			receipts: Option<Vec<Receipt>>,
+			rewards_account: Option<T::AccountId>,
+		) {
 			frame_system::ensure_none(origin)?;
 
-			import::import_header(
+			let (_, finalized_headers) = import::import_header(
 				&mut BridgeStorage::<T, I>::new(),
 				&mut T::PruningStrategy::default(),
 				&T::AuraConfiguration::get(),
 				&T::ValidatorsConfiguration::get(),
-				None,
+				rewards_account,
 				header,
 				&T::ChainTime::default(),
 				receipts,
 			).map_err(|e| e.msg())?;
+
+			// reward submitters of all headers that have been finalized by this header
+			let mut finalized_by_submitter = BTreeMap::<_, u64>::new();
+			for (_, f_submitter) in finalized_headers {
+				if let Some(f_submitter) = f_submitter {
+					*finalized_by_submitter.entry(f_submitter).or_default() += 1;
+				}
+			}
+			for (f_submitter, f_count) in finalized_by_submitter {
+				T::OnHeadersSubmitted::on_valid_headers_finalized(
+					f_submitter,
+					f_count,
+				);
+			}
 		}
 
 		/// Import Aura chain headers in a single **SIGNED** transaction.
@@ -480,6 +522,12 @@ decl_storage! {
 		ValidatorsSetsRc: map hasher(twox_64_concat) u64 => Option<u64>;
 		/// Map of validators set changes scheduled by given header.
 		ScheduledChanges: map hasher(identity) H256 => Option<AuraScheduledChange>;
+		/// Numbers of finalized headers that have been pruned, by their hashes.
+		AncientHeaders: map hasher(identity) H256 => Option<u64>;
+		/// Hashes of finalized headers that have been pruned, by their numbers.
+		AncientHeadersByNumber: map hasher(blake2_128_concat) u64 => Option<Vec<H256>>;
+		/// Number of the oldest block that may have entry in the `AncientHeadersByNumber` map.
+		OldestAncientBlock: u64;
 	}
 	add_extra_genesis {
 		config(initial_header): AuraHeader;
@@ -528,8 +576,10 @@ impl<T: Config<I>, I: Instance> Pallet<T, I> {
 	}
 
 	/// Returns true if header is known to the runtime.
+	///
+	/// Finalized headers are still known after pruning, until their hashes are pruned as well.
 	pub fn is_known_block(hash: H256) -> bool {
-		BridgeStorage::<T, I>::new().header(&hash).is_some()
+		BridgeStorage::<T, I>::new().header(&hash).is_some() || AncientHeaders::<I>::contains_key(hash)
 	}
 
 	/// Verify that transaction is included into given finalized block.
@@ -547,7 +597,7 @@ impl<T: Config<I>, I: Instance> frame_support::unsigned::ValidateUnsigned for Pa
 
 	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
 		match *call {
-			Self::Call::import_unsigned_header(ref header, ref receipts) => {
+			Self::Call::import_unsigned_header(ref header, ref receipts, _) => {
 				let accept_result = verification::accept_aura_header_into_pool(
 					&BridgeStorage::<T, I>::new(),
 					&T::AuraConfiguration::get(),
@@ -590,8 +640,10 @@ impl<T: Config<I>, I: Instance> BridgeStorage<T, I> {
 		BridgeStorage(sp_std::marker::PhantomData::<(T, I)>::default())
 	}
 
-	/// Prune old blocks.
-	fn prune_blocks(&self, mut max_blocks_to_prune: u64, finalized_number: u64, prune_end: u64) {
+	/// Prune old blocks. Returns number of pruned blocks.
+	fn prune_blocks(&self, max_blocks_to_prune: u64, finalized_number: u64, prune_end: u64) -> u64 {
+		let keep_ancient_headers = T::AncientHeadersToKeep::get() != 0;
+		let mut remaining_blocks_to_prune = max_blocks_to_prune;
 		let pruning_range = BlocksToPrune::<I>::get();
 		let mut new_pruning_range = pruning_range.clone();
 
@@ -606,7 +658,7 @@ impl<T: Config<I>, I: Instance> BridgeStorage<T, I> {
 		log::trace!(target: "runtime", "Pruning blocks in range [{}..{})", begin, end);
 		for number in begin..end {
 			// if we can't prune anything => break
-			if max_blocks_to_prune == 0 {
+			if remaining_blocks_to_prune == 0 {
 				break;
 			}
 
@@ -614,7 +666,8 @@ impl<T: Config<I>, I: Instance> BridgeStorage<T, I> {
 			let blocks_at_number = HeadersByNumber::<I>::take(number);
 			if let Some(mut blocks_at_number) = blocks_at_number {
 				self.prune_blocks_by_hashes(
-					&mut max_blocks_to_prune,
+					&mut remaining_blocks_to_prune,
+					keep_ancient_headers,
 					finalized_number,
 					number,
 					&mut blocks_at_number,
@@ -640,12 +693,15 @@ impl<T: Config<I>, I: Instance> BridgeStorage<T, I> {
 		if pruning_range != new_pruning_range {
 			BlocksToPrune::<I>::put(new_pruning_range);
 		}
+
+		max_blocks_to_prune - remaining_blocks_to_prune
 	}
 
 	/// Prune old blocks with given hashes.
 	fn prune_blocks_by_hashes(
 		&self,
 		max_blocks_to_prune: &mut u64,
+		keep_ancient_headers: bool,
 		finalized_number: u64,
 		number: u64,
 		blocks_at_number: &mut Vec<H256>,
@@ -674,6 +730,13 @@ impl<T: Config<I>, I: Instance> BridgeStorage<T, I> {
 				});
 			}
 
+			// we only remember hashes of finalized headers (and their competitors, that will
+			// never be finalized)
+			if keep_ancient_headers && number <= finalized_number {
+				AncientHeaders::<I>::insert(hash, number);
+				AncientHeadersByNumber::<I>::append(number, hash);
+			}
+
 			// check if we have already pruned too much headers in this call
 			*max_blocks_to_prune -= 1;
 			if *max_blocks_to_prune == 0 {
@@ -681,6 +744,71 @@ impl<T: Config<I>, I: Instance> BridgeStorage<T, I> {
 			}
 		}
 	}
+
+	/// Prune hashes of ancient headers that are older than `AncientHeadersToKeep` blocks before
+	/// the oldest unpruned block. Returns number of visited block numbers.
+	fn prune_ancient_headers(&self, max_numbers_to_prune: u64) -> u64 {
+		let oldest_unpruned_block = BlocksToPrune::<I>::get().oldest_unpruned_block;
+		let prune_end = oldest_unpruned_block.saturating_sub(T::AncientHeadersToKeep::get());
+		let oldest_ancient_block = OldestAncientBlock::<I>::get();
+
+		let mut pruned_numbers = 0;
+		while oldest_ancient_block + pruned_numbers < prune_end && pruned_numbers < max_numbers_to_prune {
+			let number = oldest_ancient_block + pruned_numbers;
+			let ancient_headers = AncientHeadersByNumber::<I>::take(number).unwrap_or_default();
+			for hash in ancient_headers {
+				log::trace!(
+					target: "runtime",
+					"Pruning ancient PoA header: ({}, {})",
+					number,
+					hash,
+				);
+
+				AncientHeaders::<I>::remove(hash);
+			}
+
+			pruned_numbers += 1;
+		}
+
+		if pruned_numbers != 0 {
+			OldestAncientBlock::<I>::put(oldest_ancient_block + pruned_numbers);
+		}
+
+		pruned_numbers
+	}
+
+	/// Prune full and ancient headers, spending no more than given weight. Returns weight that
+	/// has been actually spent.
+	fn prune_on_idle(&self, remaining_weight: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		// reads: `BlocksToPrune` (twice), `FinalizedBlock`, `OldestAncientBlock`
+		// writes: `BlocksToPrune`, `OldestAncientBlock`
+		let base_weight = db_weight.reads_writes(4, 2);
+		// reads: `HeadersByNumber`, `Headers`, `ValidatorsSetsRc`, `AncientHeadersByNumber`
+		// writes: `HeadersByNumber`, `Headers`, `ScheduledChanges`, `FinalityCache`, `ValidatorsSetsRc`,
+		// `AncientHeaders`, `AncientHeadersByNumber`
+		let prune_full_header_weight = db_weight.reads_writes(4, 7);
+		// reads: `AncientHeadersByNumber`
+		// writes: `AncientHeadersByNumber`, `AncientHeaders`
+		let prune_ancient_header_weight = db_weight.reads_writes(1, 2);
+		if remaining_weight < base_weight {
+			return 0;
+		}
+
+		let mut used_weight = base_weight;
+		let max_full_headers_to_prune = (remaining_weight - used_weight)
+			.checked_div(prune_full_header_weight)
+			.unwrap_or(MAX_BLOCKS_TO_PRUNE_IN_SINGLE_IMPORT);
+		let pruned_full_headers = self.prune_blocks(max_full_headers_to_prune, FinalizedBlock::<I>::get().number, 0);
+		used_weight = used_weight.saturating_add(prune_full_header_weight.saturating_mul(pruned_full_headers));
+
+		let max_ancient_numbers_to_prune = remaining_weight
+			.saturating_sub(used_weight)
+			.checked_div(prune_ancient_header_weight)
+			.unwrap_or(MAX_BLOCKS_TO_PRUNE_IN_SINGLE_IMPORT);
+		let pruned_ancient_numbers = self.prune_ancient_headers(max_ancient_numbers_to_prune);
+		used_weight.saturating_add(prune_ancient_header_weight.saturating_mul(pruned_ancient_numbers))
+	}
 }
 
 impl<T: Config<I>, I: Instance> Storage for BridgeStorage<T, I> {
@@ -886,6 +1014,7 @@ pub(crate) fn initialize_storage<T: Config<I>, I: Instance>(
 		oldest_unpruned_block: initial_header.number,
 		oldest_block_to_keep: initial_header.number,
 	});
+	OldestAncientBlock::<I>::put(initial_header.number);
 	HeadersByNumber::<I>::insert(initial_header.number, vec![initial_hash]);
 	Headers::<T, I>::insert(
 		initial_hash,
@@ -1046,8 +1175,8 @@ pub(crate) mod tests {
 	use super::*;
 	use crate::finality::FinalityAncestor;
 	use crate::mock::{
-		genesis, insert_header, run_test, run_test_with_genesis, validators_addresses, HeaderBuilder, TestRuntime,
-		GAS_LIMIT,
+		genesis, insert_header, run_test, run_test_with_genesis, validators_addresses, HeaderBuilder, Origin,
+		TestOnHeadersSubmitted, TestRuntime, GAS_LIMIT,
 	};
 	use crate::test_utils::validator_utils::*;
 	use bp_eth_poa::compute_merkle_root;
@@ -1251,6 +1380,60 @@ pub(crate) mod tests {
 		});
 	}
 
+	#[test]
+	fn finalized_headers_hashes_are_kept_after_pruning() {
+		with_headers_to_prune(|storage| {
+			let hash_at_5 = HeadersByNumber::<DefaultInstance>::get(&5).unwrap()[0];
+			let hash_at_6 = HeadersByNumber::<DefaultInstance>::get(&6).unwrap()[0];
+
+			// try to prune blocks [0; 10)
+			// last finalized block is 5
+			storage.prune_blocks(0xFFFF, 5, 10);
+
+			// => hashes of finalized headers (and their competitors) are kept
+			assert_eq!(AncientHeadersByNumber::<DefaultInstance>::get(&5).unwrap().len(), 5);
+			assert_eq!(AncientHeaders::<DefaultInstance>::get(&hash_at_5), Some(5));
+			assert!(Module::<TestRuntime>::is_known_block(hash_at_5));
+
+			// => hashes of unfinalized headers are not kept
+			assert_eq!(AncientHeadersByNumber::<DefaultInstance>::get(&6), None);
+			assert!(!Module::<TestRuntime>::is_known_block(hash_at_6));
+		});
+	}
+
+	#[test]
+	fn ancient_headers_are_pruned_on_idle() {
+		with_headers_to_prune(|storage| {
+			storage.prune_blocks(0xFFFF, 5, 10);
+			let hash_at_4 = AncientHeadersByNumber::<DefaultInstance>::get(&4).unwrap()[0];
+
+			// oldest unpruned block is 7 and we keep hashes for 2 blocks before that => hashes
+			// of headers [0; 5) are pruned
+			let used_weight = storage.prune_on_idle(Weight::max_value());
+			assert_eq!(AncientHeadersByNumber::<DefaultInstance>::get(&4), None);
+			assert_eq!(AncientHeaders::<DefaultInstance>::get(&hash_at_4), None);
+			assert_eq!(AncientHeadersByNumber::<DefaultInstance>::get(&5).unwrap().len(), 5);
+			assert_eq!(OldestAncientBlock::<DefaultInstance>::get(), 5);
+			// base weight (4 reads + 2 writes) + 5 * ancient header weight (1 read + 2 writes)
+			assert_eq!(used_weight, 8 + 5 * 5);
+		});
+	}
+
+	#[test]
+	fn on_idle_pruning_respects_remaining_weight() {
+		with_headers_to_prune(|storage| {
+			storage.prune_blocks(0xFFFF, 5, 10);
+
+			// not enough weight to do anything
+			assert_eq!(storage.prune_on_idle(7), 0);
+			assert_eq!(OldestAncientBlock::<DefaultInstance>::get(), 0);
+
+			// enough weight to prune ancient headers at two numbers
+			assert_eq!(storage.prune_on_idle(8 + 2 * 5 + 1), 8 + 2 * 5);
+			assert_eq!(OldestAncientBlock::<DefaultInstance>::get(), 2);
+		});
+	}
+
 	#[test]
 	fn finality_votes_are_cached() {
 		run_test(TOTAL_VALIDATORS, |ctx| {
@@ -1377,6 +1560,42 @@ pub(crate) mod tests {
 		});
 	}
 
+	#[test]
+	fn unsigned_headers_submitters_are_rewarded_when_headers_are_finalized() {
+		run_test(TOTAL_VALIDATORS, |ctx| {
+			// header#1 is imported, but not finalized => no rewards
+			let header1 = HeaderBuilder::with_parent_number(0).sign_by_set(&ctx.validators);
+			frame_support::assert_ok!(Module::<TestRuntime>::import_unsigned_header(
+				Origin::none(),
+				header1,
+				None,
+				Some(100),
+			));
+			assert_eq!(TestOnHeadersSubmitted::take_finalized(), vec![]);
+
+			// header#2 is imported without rewards account and it finalizes header#1 => submitter
+			// of header#1 is rewarded
+			let header2 = HeaderBuilder::with_parent_number(1).sign_by_set(&ctx.validators);
+			frame_support::assert_ok!(Module::<TestRuntime>::import_unsigned_header(
+				Origin::none(),
+				header2,
+				None,
+				None,
+			));
+			assert_eq!(TestOnHeadersSubmitted::take_finalized(), vec![(100, 1)]);
+
+			// header#3 finalizes header#2, which has no submitter => no rewards
+			let header3 = HeaderBuilder::with_parent_number(2).sign_by_set(&ctx.validators);
+			frame_support::assert_ok!(Module::<TestRuntime>::import_unsigned_header(
+				Origin::none(),
+				header3,
+				None,
+				Some(100),
+			));
+			assert_eq!(TestOnHeadersSubmitted::take_finalized(), vec![]);
+		});
+	}
+
 	#[test]
 	fn verify_transaction_finalized_works_for_best_finalized_header() {
 		run_test_with_genesis(example_header(), TOTAL_VALIDATORS, |_| {
@@ -21,9 +21,14 @@ pub use crate::test_utils::{insert_header, validator_utils::*, validators_change
 pub use bp_eth_poa::signatures::secret_to_address;
 
 use crate::validators::{ValidatorsConfiguration, ValidatorsSource};
-use crate::{AuraConfiguration, ChainTime, Config, GenesisConfig as CrateGenesisConfig, PruningStrategy};
+use crate::{
+	AuraConfiguration, ChainTime, Config, GenesisConfig as CrateGenesisConfig, OnHeadersSubmitted, PruningStrategy,
+};
 use bp_eth_poa::{Address, AuraHeader, H256, U256};
-use frame_support::{parameter_types, weights::Weight};
+use frame_support::{
+	parameter_types,
+	weights::{RuntimeDbWeight, Weight},
+};
 use secp256k1::SecretKey;
 use sp_runtime::{
 	testing::Header as SubstrateHeader,
@@ -54,6 +59,7 @@ parameter_types! {
 	pub const MaximumBlockWeight: Weight = 1024;
 	pub const MaximumBlockLength: u32 = 2 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::one();
+	pub const TestDbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 1, write: 2 };
 }
 
 impl frame_system::Config for TestRuntime {
@@ -77,13 +83,14 @@ impl frame_system::Config for TestRuntime {
 	type SystemWeightInfo = ();
 	type BlockWeights = ();
 	type BlockLength = ();
-	type DbWeight = ();
+	type DbWeight = TestDbWeight;
 	type SS58Prefix = ();
 	type OnSetCode = ();
 }
 
 parameter_types! {
 	pub const TestFinalityVotesCachingInterval: Option<u64> = Some(16);
+	pub const TestAncientHeadersToKeep: u64 = 2;
 	pub TestAuraConfiguration: AuraConfiguration = test_aura_config();
 	pub TestValidatorsConfiguration: ValidatorsConfiguration = test_validators_config();
 }
@@ -94,7 +101,30 @@ impl Config for TestRuntime {
 	type FinalityVotesCachingInterval = TestFinalityVotesCachingInterval;
 	type PruningStrategy = KeepSomeHeadersBehindBest;
 	type ChainTime = ConstChainTime;
-	type OnHeadersSubmitted = ();
+	type OnHeadersSubmitted = TestOnHeadersSubmitted;
+	type AncientHeadersToKeep = TestAncientHeadersToKeep;
+}
+
+thread_local! {
+	static FINALIZED_HEADERS_SUBMITTERS: std::cell::RefCell<Vec<(AccountId, u64)>> = Default::default();
+}
+
+/// Headers submission callbacks that remember submitters of finalized headers.
+pub struct TestOnHeadersSubmitted;
+
+impl TestOnHeadersSubmitted {
+	/// Take all `on_valid_headers_finalized` calls that have happened since last call.
+	pub fn take_finalized() -> Vec<(AccountId, u64)> {
+		FINALIZED_HEADERS_SUBMITTERS.with(|f| std::mem::take(&mut *f.borrow_mut()))
+	}
+}
+
+impl OnHeadersSubmitted<AccountId> for TestOnHeadersSubmitted {
+	fn on_valid_headers_submitted(_submitter: AccountId, _useful: u64, _useless: u64) {}
+	fn on_invalid_headers_submitted(_submitter: AccountId) {}
+	fn on_valid_headers_finalized(submitter: AccountId, finalized: u64) {
+		FINALIZED_HEADERS_SUBMITTERS.with(|f| f.borrow_mut().push((submitter, finalized)));
+	}
 }
 
 /// Test context.
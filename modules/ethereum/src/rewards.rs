@@ -0,0 +1,82 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Implementation of `OnHeadersSubmitted` trait on top of `Currency` trait.
+//!
+//! Header submitters are only rewarded when their headers are finalized. The reward
+//! is paid from the pot account, which must be funded by the runtime owner.
+
+use crate::OnHeadersSubmitted;
+
+use frame_support::traits::{Currency as CurrencyT, ExistenceRequirement, Get};
+use sp_runtime::traits::{Saturating, Zero};
+
+/// Rewards submitters of finalized headers with fixed reward per header, paid from the pot account.
+///
+/// Since unsigned headers are imported for free, the reward is the only incentive for importing
+/// them. Submitted (but not yet finalized) and invalid headers are neither rewarded nor penalized.
+///
+/// NOTE The pot account must always stay above Existential Deposit. If there are not enough funds
+/// in the pot, submitters are not rewarded at all.
+pub struct FinalityRewardsFromPot<T, Currency, PotAccount, RewardPerHeader> {
+	_phantom: sp_std::marker::PhantomData<(T, Currency, PotAccount, RewardPerHeader)>,
+}
+
+impl<T, Currency, PotAccount, RewardPerHeader> OnHeadersSubmitted<T::AccountId>
+	for FinalityRewardsFromPot<T, Currency, PotAccount, RewardPerHeader>
+where
+	T: frame_system::Config,
+	Currency: CurrencyT<T::AccountId>,
+	Currency::Balance: From<u64>,
+	PotAccount: Get<T::AccountId>,
+	RewardPerHeader: Get<Currency::Balance>,
+{
+	fn on_valid_headers_submitted(_submitter: T::AccountId, _useful: u64, _useless: u64) {}
+
+	fn on_invalid_headers_submitted(_submitter: T::AccountId) {}
+
+	fn on_valid_headers_finalized(submitter: T::AccountId, finalized: u64) {
+		let reward = RewardPerHeader::get().saturating_mul(finalized.into());
+		if reward.is_zero() {
+			return;
+		}
+
+		let pay_result = Currency::transfer(
+			&PotAccount::get(),
+			&submitter,
+			reward,
+			// the pot account must stay above ED (needs to be pre-funded)
+			ExistenceRequirement::KeepAlive,
+		);
+
+		match pay_result {
+			Ok(_) => log::trace!(
+				target: "runtime",
+				"Rewarded PoA headers submitter {:?} with {:?} for {} finalized headers",
+				submitter,
+				reward,
+				finalized,
+			),
+			Err(error) => log::trace!(
+				target: "runtime",
+				"Failed to pay PoA headers submitter {:?} reward {:?}: {:?}",
+				submitter,
+				reward,
+				error,
+			),
+		}
+	}
+}
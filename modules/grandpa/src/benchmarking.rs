@@ -39,6 +39,12 @@
 //! Note that the worst case scenario here would be a justification where each validator has it's
 //! own fork which is `SESSION_LENGTH` blocks long.
 //!
+//! Only precommits that are required to reach the GRANDPA threshold have their signatures verified,
+//! so precommits above the threshold and duplicate precommits do not increase the worst case
+//! weight. The `submit_finality_proof_with_extra_precommits` and
+//! `submit_finality_proof_with_duplicate_precommits` benchmarks show this for a set of 1000
+//! validators.
+//!
 //! As far as benchmarking results go, the only benchmark that should be used in
 //! `pallet-bridge-grandpa` to annotate weights is the `submit_finality_proof` one. The others are
 //! looking at the effects of specific code paths and do not actually reflect the overall worst case
@@ -67,6 +73,46 @@ const MAX_VOTE_ANCESTRIES: u32 = 1000;
 // number of validators.
 const MAX_VALIDATOR_SET_SIZE: u32 = 1024;
 
+// The number of validators in the set that is used to check the effect of precommits that are not
+// required to reach the threshold.
+const LARGE_VALIDATOR_SET_SIZE: u32 = 1000;
+
+// The minimal number of (equally weighted) precommits that is required to finalize a header by the
+// `LARGE_VALIDATOR_SET_SIZE` validators.
+const LARGE_VALIDATOR_SET_THRESHOLD: u32 = LARGE_VALIDATOR_SET_SIZE - (LARGE_VALIDATOR_SET_SIZE - 1) / 3;
+
+/// Bootstrap the bridge with the `LARGE_VALIDATOR_SET_SIZE` validators and return justification for
+/// the next header, signed by the first `precommits` of them.
+fn bootstrap_large_validator_set<T: Config<I>, I: 'static>(
+	precommits: u32,
+) -> (BridgedHeader<T, I>, GrandpaJustification<BridgedHeader<T, I>>) {
+	let authority_list = accounts(LARGE_VALIDATOR_SET_SIZE as u16)
+		.iter()
+		.map(|id| (AuthorityId::from(*id), 1))
+		.collect::<Vec<_>>();
+
+	let init_data = InitializationData {
+		header: bp_test_utils::test_header(Zero::zero()),
+		authority_list,
+		set_id: TEST_GRANDPA_SET_ID,
+		is_halted: false,
+	};
+
+	bootstrap_bridge::<T, I>(init_data);
+
+	let header: BridgedHeader<T, I> = bp_test_utils::test_header(header_number::<T, I, _>());
+	let params = JustificationGeneratorParams {
+		header: header.clone(),
+		round: TEST_GRANDPA_ROUND,
+		set_id: TEST_GRANDPA_SET_ID,
+		authorities: accounts(precommits as u16).iter().map(|k| (*k, 1)).collect::<Vec<_>>(),
+		votes: 1,
+		forks: 1,
+	};
+
+	(header, make_justification_for_header(params))
+}
+
 /// Returns number of first header to be imported.
 ///
 /// Since we boostrap the pallet with `HeadersToKeep` already imported headers,
@@ -202,6 +248,46 @@ benchmarks_instance_pallet! {
 		assert!(<ImportedHeaders<T, I>>::contains_key(expected_hash));
 	}
 
+	// What we want to check here is the effect of precommits that are not required to reach the
+	// threshold. Signatures of such precommits are not verified, so the cost of the call should
+	// stay almost the same when `p` grows above the `LARGE_VALIDATOR_SET_THRESHOLD`.
+	submit_finality_proof_with_extra_precommits {
+		let p in LARGE_VALIDATOR_SET_THRESHOLD..LARGE_VALIDATOR_SET_SIZE;
+
+		let caller: T::AccountId = whitelisted_caller();
+		let (header, justification) = bootstrap_large_validator_set::<T, I>(p);
+
+	}: submit_finality_proof(RawOrigin::Signed(caller), header, justification)
+	verify {
+		let header: BridgedHeader<T, I> = bp_test_utils::test_header(header_number::<T, I, _>());
+		let expected_hash = header.hash();
+
+		assert_eq!(<BestFinalized<T, I>>::get(), expected_hash);
+		assert!(<ImportedHeaders<T, I>>::contains_key(expected_hash));
+	}
+
+	// What we want to check here is the effect of duplicate precommits (i.e. precommits of the
+	// same validator). Signatures of such precommits are verified only once, so the cost of the
+	// call should stay almost the same when `d` grows.
+	submit_finality_proof_with_duplicate_precommits {
+		let d in 0..LARGE_VALIDATOR_SET_SIZE;
+
+		let caller: T::AccountId = whitelisted_caller();
+		let (header, mut justification) = bootstrap_large_validator_set::<T, I>(LARGE_VALIDATOR_SET_THRESHOLD);
+		let duplicate_precommit = justification.commit.precommits[0].clone();
+		for _ in 0..d {
+			justification.commit.precommits.insert(1, duplicate_precommit.clone());
+		}
+
+	}: submit_finality_proof(RawOrigin::Signed(caller), header, justification)
+	verify {
+		let header: BridgedHeader<T, I> = bp_test_utils::test_header(header_number::<T, I, _>());
+		let expected_hash = header.hash();
+
+		assert_eq!(<BestFinalized<T, I>>::get(), expected_hash);
+		assert!(<ImportedHeaders<T, I>>::contains_key(expected_hash));
+	}
+
 	// Here we want to find out the overheaded of looking through consensus digests found in a
 	// header. As the number of logs in a header grows, how much more work do we require to look
 	// through them?
@@ -278,4 +364,22 @@ mod tests {
 			assert_ok!(test_benchmark_submit_finality_proof_on_many_forks::<mock::TestRuntime>());
 		});
 	}
+
+	#[test]
+	fn finality_proof_with_extra_precommits_is_valid() {
+		mock::run_test(|| {
+			assert_ok!(test_benchmark_submit_finality_proof_with_extra_precommits::<
+				mock::TestRuntime,
+			>());
+		});
+	}
+
+	#[test]
+	fn finality_proof_with_duplicate_precommits_is_valid() {
+		mock::run_test(|| {
+			assert_ok!(test_benchmark_submit_finality_proof_with_duplicate_precommits::<
+				mock::TestRuntime,
+			>());
+		});
+	}
 }
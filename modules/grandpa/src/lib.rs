@@ -40,16 +40,20 @@ use crate::weights::WeightInfo;
 
 use bp_header_chain::justification::GrandpaJustification;
 use bp_header_chain::InitializationData;
-use bp_runtime::{BlockNumberOf, Chain, HashOf, HasherOf, HeaderOf};
+use bp_runtime::{BlockNumberOf, Chain, HashOf, HasherOf, HeaderOf, StorageVersion};
 use finality_grandpa::voter_set::VoterSet;
 use frame_support::{ensure, fail};
 use frame_system::{ensure_signed, RawOrigin};
 use sp_finality_grandpa::{ConsensusLog, GRANDPA_ENGINE_ID};
-use sp_runtime::traits::{BadOrigin, Header as HeaderT, Zero};
+use sp_runtime::traits::{BadOrigin, Header as HeaderT, One, UniqueSaturatedInto, Zero};
+use sp_runtime::transaction_validity::TransactionLongevity;
+use sp_std::collections::btree_map::BTreeMap;
 
 #[cfg(test)]
 mod mock;
 
+/// Migrations of the pallet storage.
+pub mod migrations;
 /// Pallet containing weights for this pallet.
 pub mod weights;
 
@@ -59,6 +63,12 @@ pub mod benchmarking;
 // Re-export in crate namespace for `construct_runtime!`
 pub use pallet::*;
 
+/// Number of blocks during which the unsigned finality proof stays valid in the transaction pool.
+///
+/// Newer finality proofs are constantly produced by relayers, so there's no point in keeping old
+/// proofs for long.
+pub const UNSIGNED_FINALITY_PROOF_LONGEVITY: TransactionLongevity = 8;
+
 /// Block number of the bridged chain.
 pub type BridgedBlockNumber<T, I> = BlockNumberOf<<T as Config<I>>::BridgedChain>;
 /// Block hash of the bridged chain.
@@ -96,6 +106,13 @@ pub mod pallet {
 		#[pallet::constant]
 		type HeadersToKeep: Get<u32>;
 
+		/// Maximal number of headers in the ancestry of the `submit_finality_proof_with_ancestry`
+		/// call.
+		///
+		/// Longer ancestries are rejected before they're verified.
+		#[pallet::constant]
+		type MaxAncestryLength: Get<u32>;
+
 		/// Weights gathered through benchmarking.
 		type WeightInfo: WeightInfo;
 	}
@@ -112,6 +129,17 @@ pub mod pallet {
 				.saturating_add(T::DbWeight::get().reads(1))
 				.saturating_add(T::DbWeight::get().writes(1))
 		}
+
+		fn on_idle(
+			_n: T::BlockNumber,
+			remaining_weight: frame_support::weights::Weight,
+		) -> frame_support::weights::Weight {
+			prune_outdated_headers::<T, I>(remaining_weight)
+		}
+
+		fn on_runtime_upgrade() -> frame_support::weights::Weight {
+			migrations::migrate::<T, I>()
+		}
 	}
 
 	#[pallet::call]
@@ -132,43 +160,8 @@ pub mod pallet {
 			finality_target: BridgedHeader<T, I>,
 			justification: GrandpaJustification<BridgedHeader<T, I>>,
 		) -> DispatchResultWithPostInfo {
-			ensure_operational::<T, I>()?;
 			let _ = ensure_signed(origin)?;
-
-			ensure!(
-				Self::request_count() < T::MaxRequests::get(),
-				<Error<T, I>>::TooManyRequests
-			);
-
-			let (hash, number) = (finality_target.hash(), finality_target.number());
-			log::trace!(target: "runtime::bridge-grandpa", "Going to try and finalize header {:?}", finality_target);
-
-			let best_finalized = match <ImportedHeaders<T, I>>::get(<BestFinalized<T, I>>::get()) {
-				Some(best_finalized) => best_finalized,
-				None => {
-					log::error!(
-						target: "runtime::bridge-grandpa",
-						"Cannot finalize header {:?} because pallet is not yet initialized",
-						finality_target,
-					);
-					fail!(<Error<T, I>>::NotInitialized);
-				}
-			};
-
-			// We do a quick check here to ensure that our header chain is making progress and isn't
-			// "travelling back in time" (which could be indicative of something bad, e.g a hard-fork).
-			ensure!(best_finalized.number() < number, <Error<T, I>>::OldHeader);
-
-			let authority_set = <CurrentAuthoritySet<T, I>>::get();
-			let set_id = authority_set.set_id;
-			verify_justification::<T, I>(&justification, hash, *number, authority_set)?;
-
-			let _enacted = try_enact_authority_change::<T, I>(&finality_target, set_id)?;
-			<RequestCount<T, I>>::mutate(|count| *count += 1);
-			insert_header::<T, I>(finality_target, hash);
-			log::info!(target: "runtime::bridge-grandpa", "Succesfully imported finalized header with hash {:?}!", hash);
-
-			Ok(().into())
+			import_finality_proof::<T, I>(finality_target, justification, None)
 		}
 
 		/// Bootstrap the bridge pallet with an initial header and authority set from which to sync.
@@ -236,6 +229,106 @@ pub mod pallet {
 
 			Ok(().into())
 		}
+
+		/// Verify a target header is finalized according to the given finality proof and that it
+		/// is connected to the best finalized header through the given ancestry.
+		///
+		/// The `ancestry` must contain all headers between the best finalized header and the target
+		/// header (both exclusive), in any order. The ancestry must have at most `MaxAncestryLength`
+		/// headers. Unlike `submit_finality_proof`, this call guarantees that headers which change
+		/// the authority set are never skipped.
+		#[pallet::weight(T::WeightInfo::submit_finality_proof(
+			justification.votes_ancestries.len().saturating_add(ancestry.len()) as u32,
+			justification.commit.precommits.len() as u32,
+		))]
+		pub fn submit_finality_proof_with_ancestry(
+			origin: OriginFor<T>,
+			finality_target: BridgedHeader<T, I>,
+			justification: GrandpaJustification<BridgedHeader<T, I>>,
+			ancestry: Vec<BridgedHeader<T, I>>,
+		) -> DispatchResultWithPostInfo {
+			let _ = ensure_signed(origin)?;
+			import_finality_proof::<T, I>(finality_target, justification, Some(ancestry))
+		}
+
+		/// Schedule replacement of the current authority set with the given one.
+		///
+		/// The `authority_set` is used to verify justifications of all headers starting from the
+		/// bridged chain block `at`. It becomes the current authority set once the first such header
+		/// is imported. This is a recovery path for bridged chains that have rotated their
+		/// authorities without signalling it in headers. Any previously scheduled override is
+		/// replaced.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::weight((T::DbWeight::get().reads_writes(2, 1), DispatchClass::Operational))]
+		pub fn schedule_authority_set_override(
+			origin: OriginFor<T>,
+			at: BridgedBlockNumber<T, I>,
+			authority_set: bp_header_chain::AuthoritySet,
+		) -> DispatchResultWithPostInfo {
+			ensure_owner_or_root::<T, I>(origin)?;
+
+			let best_finalized =
+				<ImportedHeaders<T, I>>::get(<BestFinalized<T, I>>::get()).ok_or(<Error<T, I>>::NotInitialized)?;
+			ensure!(*best_finalized.number() < at, <Error<T, I>>::OldHeader);
+			ensure!(
+				VoterSet::new(authority_set.authorities.clone()).is_some(),
+				<Error<T, I>>::InvalidAuthoritySet
+			);
+
+			log::info!(
+				target: "runtime::bridge-grandpa",
+				"Scheduled authority set override at bridged block {:?}: {:?}",
+				at,
+				authority_set,
+			);
+			<AuthoritySetOverride<T, I>>::put((at, authority_set));
+
+			Ok(().into())
+		}
+
+		/// Verify a target header is finalized according to the given finality proof.
+		///
+		/// This is the unsigned version of `submit_finality_proof`, so anyone may submit finality
+		/// proof without having a funded account at this chain. The finality proof is verified when
+		/// the transaction is validated, so only proofs that may be imported are accepted to the
+		/// transaction pool.
+		#[pallet::weight(T::WeightInfo::submit_finality_proof(
+			justification.votes_ancestries.len() as u32,
+			justification.commit.precommits.len() as u32,
+		))]
+		pub fn submit_finality_proof_unsigned(
+			origin: OriginFor<T>,
+			finality_target: BridgedHeader<T, I>,
+			justification: GrandpaJustification<BridgedHeader<T, I>>,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+			import_finality_proof::<T, I>(finality_target, justification, None)
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config<I>, I: 'static> ValidateUnsigned for Pallet<T, I> {
+		type Call = Call<T, I>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			match *call {
+				Call::submit_finality_proof_unsigned(ref finality_target, ref justification) => {
+					validate_unsigned_finality_proof::<T, I>(finality_target, justification)?;
+
+					// all finality proofs, submitted to the same pallet instance, are competing with
+					// each other and the proof of the best header wins, because there's no need to
+					// import every finalized header
+					ValidTransaction::with_tag_prefix("BridgeGrandpaFinalityProof")
+						.priority((*finality_target.number()).unique_saturated_into())
+						.and_provides(<Pallet<T, I> as frame_support::traits::PalletInfoAccess>::index())
+						.longevity(UNSIGNED_FINALITY_PROOF_LONGEVITY)
+						.propagate(true)
+						.build()
+				}
+				_ => InvalidTransaction::Call.into(),
+			}
+		}
 	}
 
 	/// The current number of requests which have written to storage.
@@ -266,6 +359,14 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type ImportedHashesPointer<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
 
+	/// Position of the next outdated ring buffer entry to prune.
+	///
+	/// Entries at positions that are greater than or equal to `HeadersToKeep` are outdated. They only
+	/// exist if the `HeadersToKeep` has been decreased and are lazily pruned in `on_idle`. Zero means
+	/// that the pruning starts from the `HeadersToKeep` position.
+	#[pallet::storage]
+	pub(super) type OutdatedHashesPointer<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
+
 	/// Headers which have been imported into the pallet.
 	#[pallet::storage]
 	pub(super) type ImportedHeaders<T: Config<I>, I: 'static = ()> =
@@ -276,6 +377,12 @@ pub mod pallet {
 	pub(super) type CurrentAuthoritySet<T: Config<I>, I: 'static = ()> =
 		StorageValue<_, bp_header_chain::AuthoritySet, ValueQuery>;
 
+	/// Authority set that replaces the current authority set, starting from the given bridged
+	/// chain block.
+	#[pallet::storage]
+	pub(super) type AuthoritySetOverride<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, (BridgedBlockNumber<T, I>, bp_header_chain::AuthoritySet), OptionQuery>;
+
 	/// Optional pallet owner.
 	///
 	/// Pallet owner has a right to halt all pallet operations and then resume it. If it is
@@ -289,6 +396,11 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type IsHalted<T: Config<I>, I: 'static = ()> = StorageValue<_, bool, ValueQuery>;
 
+	/// Version of the pallet storage layout.
+	#[pallet::storage]
+	#[pallet::getter(fn storage_version)]
+	pub(super) type PalletStorageVersion<T: Config<I>, I: 'static = ()> = StorageValue<_, StorageVersion, ValueQuery>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
 		/// Optional module owner account.
@@ -310,6 +422,8 @@ pub mod pallet {
 	#[pallet::genesis_build]
 	impl<T: Config<I>, I: 'static> GenesisBuild<T, I> for GenesisConfig<T, I> {
 		fn build(&self) {
+			<PalletStorageVersion<T, I>>::put(migrations::STORAGE_VERSION);
+
 			if let Some(ref owner) = self.owner {
 				<PalletOwner<T, I>>::put(owner);
 			}
@@ -348,6 +462,171 @@ pub mod pallet {
 		Halted,
 		/// The storage proof doesn't contains storage root. So it is invalid for given header.
 		StorageRootMismatch,
+		/// The header is not connected to the best finalized header through the given ancestry.
+		HeaderDoesNotConnect,
+		/// The ancestry is missing some headers between the best finalized header and the header
+		/// being imported.
+		IncompleteAncestry,
+		/// The ancestry contains headers that are not ancestors of the header being imported.
+		RedundantAncestry,
+		/// The ancestry has more than `MaxAncestryLength` headers.
+		TooLongAncestry,
+		/// The header that changes the authority set (and so must be imported) is skipped.
+		MandatoryHeaderSkipped,
+	}
+
+	/// Import the header, finalized by the given justification.
+	///
+	/// If `ancestry` is provided, it is used to check that the header is connected to the best
+	/// finalized header and that no mandatory headers are skipped.
+	pub(crate) fn import_finality_proof<T: Config<I>, I: 'static>(
+		finality_target: BridgedHeader<T, I>,
+		justification: GrandpaJustification<BridgedHeader<T, I>>,
+		ancestry: Option<Vec<BridgedHeader<T, I>>>,
+	) -> DispatchResultWithPostInfo {
+		ensure_operational::<T, I>()?;
+
+		ensure!(
+			Pallet::<T, I>::request_count() < T::MaxRequests::get(),
+			<Error<T, I>>::TooManyRequests
+		);
+
+		let (hash, number) = (finality_target.hash(), finality_target.number());
+		log::trace!(target: "runtime::bridge-grandpa", "Going to try and finalize header {:?}", finality_target);
+
+		let best_finalized = match <ImportedHeaders<T, I>>::get(<BestFinalized<T, I>>::get()) {
+			Some(best_finalized) => best_finalized,
+			None => {
+				log::error!(
+					target: "runtime::bridge-grandpa",
+					"Cannot finalize header {:?} because pallet is not yet initialized",
+					finality_target,
+				);
+				fail!(<Error<T, I>>::NotInitialized);
+			}
+		};
+
+		// We do a quick check here to ensure that our header chain is making progress and isn't
+		// "travelling back in time" (which could be indicative of something bad, e.g a hard-fork).
+		ensure!(best_finalized.number() < number, <Error<T, I>>::OldHeader);
+
+		if let Some(ancestry) = ancestry {
+			verify_ancestry::<T, I>(&best_finalized, &finality_target, &ancestry)?;
+		}
+
+		let (authority_set, is_overridden) = header_authority_set::<T, I>(*number);
+		let set_id = authority_set.set_id;
+		verify_justification::<T, I>(&justification, hash, *number, authority_set.clone())?;
+
+		if is_overridden {
+			log::info!(
+				target: "runtime::bridge-grandpa",
+				"Overriding authority set at header {:?} with: {:?}",
+				hash,
+				authority_set,
+			);
+			<CurrentAuthoritySet<T, I>>::put(authority_set);
+			<AuthoritySetOverride<T, I>>::kill();
+		}
+
+		let _enacted = try_enact_authority_change::<T, I>(&finality_target, set_id)?;
+		<RequestCount<T, I>>::mutate(|count| *count += 1);
+		insert_header::<T, I>(finality_target, hash);
+		log::info!(target: "runtime::bridge-grandpa", "Succesfully imported finalized header with hash {:?}!", hash);
+
+		Ok(().into())
+	}
+
+	/// Check that the unsigned finality proof may be imported by the pallet.
+	///
+	/// Since the header number must be greater than the number of the best finalized header, the
+	/// same (or an older) finality proof can't be imported twice.
+	pub(crate) fn validate_unsigned_finality_proof<T: Config<I>, I: 'static>(
+		finality_target: &BridgedHeader<T, I>,
+		justification: &GrandpaJustification<BridgedHeader<T, I>>,
+	) -> Result<(), TransactionValidityError> {
+		if ensure_operational::<T, I>().is_err() {
+			return Err(InvalidTransaction::Call.into());
+		}
+		if Pallet::<T, I>::request_count() >= T::MaxRequests::get() {
+			return Err(InvalidTransaction::ExhaustsResources.into());
+		}
+
+		let (hash, number) = (finality_target.hash(), *finality_target.number());
+		let best_finalized =
+			<ImportedHeaders<T, I>>::get(<BestFinalized<T, I>>::get()).ok_or(InvalidTransaction::Call)?;
+		if *best_finalized.number() >= number {
+			return Err(InvalidTransaction::Stale.into());
+		}
+
+		let (authority_set, _) = header_authority_set::<T, I>(number);
+		verify_justification::<T, I>(justification, hash, number, authority_set)
+			.map_err(|_| InvalidTransaction::BadProof)?;
+
+		Ok(())
+	}
+
+	/// Returns the authority set that must finalize the header with given number.
+	///
+	/// Headers starting from the scheduled override are finalized by the overriding authority set.
+	/// The returned flag is true if the override is used.
+	pub(crate) fn header_authority_set<T: Config<I>, I: 'static>(
+		number: BridgedBlockNumber<T, I>,
+	) -> (bp_header_chain::AuthoritySet, bool) {
+		match <AuthoritySetOverride<T, I>>::get() {
+			Some((at, authority_set)) if number >= at => (authority_set, true),
+			_ => (<CurrentAuthoritySet<T, I>>::get(), false),
+		}
+	}
+
+	/// Verify that the header is connected to the best finalized header through the given
+	/// ancestry and that none of ancestry headers changes the authority set.
+	pub(crate) fn verify_ancestry<T: Config<I>, I: 'static>(
+		best_finalized: &BridgedHeader<T, I>,
+		header: &BridgedHeader<T, I>,
+		ancestry: &[BridgedHeader<T, I>],
+	) -> Result<(), Error<T, I>> {
+		ensure!(
+			ancestry.len() <= T::MaxAncestryLength::get() as usize,
+			<Error<T, I>>::TooLongAncestry
+		);
+
+		let best_finalized_hash = best_finalized.hash();
+		let ancestry_by_hash = ancestry
+			.iter()
+			.map(|ancestor| (ancestor.hash(), ancestor))
+			.collect::<BTreeMap<_, _>>();
+
+		let mut child = header;
+		let mut used_ancestors = 0;
+		while *child.parent_hash() != best_finalized_hash {
+			let parent = *ancestry_by_hash
+				.get(child.parent_hash())
+				.ok_or(<Error<T, I>>::IncompleteAncestry)?;
+
+			// the parent must be a direct ancestor of the child and a descendant of the best
+			// finalized header
+			ensure!(
+				*parent.number() + One::one() == *child.number() && parent.number() > best_finalized.number(),
+				<Error<T, I>>::HeaderDoesNotConnect
+			);
+			// headers that change the authority set must be imported by the pallet
+			ensure!(
+				super::find_scheduled_change(parent).is_none() && super::find_forced_change(parent).is_none(),
+				<Error<T, I>>::MandatoryHeaderSkipped
+			);
+
+			child = parent;
+			used_ancestors += 1;
+		}
+
+		ensure!(
+			*best_finalized.number() + One::one() == *child.number(),
+			<Error<T, I>>::HeaderDoesNotConnect
+		);
+		ensure!(used_ancestors == ancestry.len(), <Error<T, I>>::RedundantAncestry);
+
+		Ok(())
 	}
 
 	/// Check the given header for a GRANDPA scheduled authority set change. If a change
@@ -428,7 +707,8 @@ pub mod pallet {
 	/// Note this function solely takes care of updating the storage and pruning old entries,
 	/// but does not verify the validaty of such import.
 	pub(crate) fn insert_header<T: Config<I>, I: 'static>(header: BridgedHeader<T, I>, hash: BridgedBlockHash<T, I>) {
-		let index = <ImportedHashesPointer<T, I>>::get();
+		// the pointer may be outside of the ring buffer if `HeadersToKeep` has been decreased
+		let index = <ImportedHashesPointer<T, I>>::get() % T::HeadersToKeep::get();
 		let pruning = <ImportedHashes<T, I>>::try_get(index);
 		<BestFinalized<T, I>>::put(hash);
 		<ImportedHeaders<T, I>>::insert(hash, header);
@@ -442,6 +722,62 @@ pub mod pallet {
 		}
 	}
 
+	/// Prune outdated ring buffer entries (and their headers), spending no more than given weight.
+	///
+	/// Returns weight that has been actually spent.
+	pub(crate) fn prune_outdated_headers<T: Config<I>, I: 'static>(
+		remaining_weight: frame_support::weights::Weight,
+	) -> frame_support::weights::Weight {
+		let db_weight = T::DbWeight::get();
+		// reads: `OutdatedHashesPointer`, `BestFinalized`, `ImportedHashes` (the entry that doesn't exist)
+		// writes: `OutdatedHashesPointer`
+		let base_weight = db_weight.reads_writes(3, 1);
+		// reads: `ImportedHashes`
+		// writes: `ImportedHashes`, `ImportedHeaders`
+		let prune_header_weight = db_weight.reads_writes(1, 2);
+		if remaining_weight < base_weight {
+			return 0;
+		}
+
+		let best_finalized = <BestFinalized<T, I>>::get();
+		let stored_pointer = <OutdatedHashesPointer<T, I>>::get();
+		let mut index = stored_pointer.max(T::HeadersToKeep::get());
+		let mut used_weight = base_weight;
+		let mut all_pruned = false;
+		while used_weight.saturating_add(prune_header_weight) <= remaining_weight {
+			let hash = match <ImportedHashes<T, I>>::take(index) {
+				Some(hash) => hash,
+				None => {
+					all_pruned = true;
+					break;
+				}
+			};
+
+			// the best finalized header must be kept, even if it is no longer in the ring buffer
+			if hash != best_finalized {
+				log::debug!(target: "runtime::bridge-grandpa", "Pruning outdated header: {:?}.", hash);
+				<ImportedHeaders<T, I>>::remove(hash);
+			}
+
+			used_weight = used_weight.saturating_add(prune_header_weight);
+			index = match index.checked_add(1) {
+				Some(index) => index,
+				None => {
+					all_pruned = true;
+					break;
+				}
+			};
+		}
+
+		match (all_pruned, stored_pointer) {
+			(true, 0) => (),
+			(true, _) => <OutdatedHashesPointer<T, I>>::kill(),
+			(false, _) => <OutdatedHashesPointer<T, I>>::put(index),
+		}
+
+		used_weight
+	}
+
 	/// Since this writes to storage with no real checks this should only be used in functions that
 	/// were called by a trusted origin.
 	pub(crate) fn initialize_bridge<T: Config<I>, I: 'static>(
@@ -530,6 +866,11 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		<ImportedHeaders<T, I>>::contains_key(hash)
 	}
 
+	/// Get the finalized header with given hash, if it is known to the bridge pallet.
+	pub fn finalized_header(hash: BridgedBlockHash<T, I>) -> Option<BridgedHeader<T, I>> {
+		<ImportedHeaders<T, I>>::get(hash)
+	}
+
 	/// Verify that the passed storage proof is valid, given it is crafted using
 	/// known finalized header. If the proof is valid, then the `parse` callback
 	/// is called and the function returns its result.
@@ -600,9 +941,12 @@ mod tests {
 		BOB,
 	};
 	use codec::Encode;
-	use frame_support::weights::PostDispatchInfo;
-	use frame_support::{assert_err, assert_noop, assert_ok};
-	use sp_runtime::{Digest, DigestItem, DispatchError};
+	use frame_support::weights::{PostDispatchInfo, Weight};
+	use frame_support::{assert_err, assert_noop, assert_ok, traits::Get, unsigned::ValidateUnsigned};
+	use sp_runtime::{
+		transaction_validity::{InvalidTransaction, TransactionSource, TransactionValidity},
+		Digest, DigestItem, DispatchError,
+	};
 
 	fn initialize_substrate_bridge() {
 		assert_ok!(init_with_origin(Origin::root()));
@@ -630,6 +974,32 @@ mod tests {
 		Pallet::<TestRuntime>::submit_finality_proof(Origin::signed(1), header, justification)
 	}
 
+	fn submit_finality_proof_with_ancestry(
+		header: &TestHeader,
+		ancestry: Vec<TestHeader>,
+	) -> frame_support::dispatch::DispatchResultWithPostInfo {
+		let justification = make_default_justification(header);
+
+		Pallet::<TestRuntime>::submit_finality_proof_with_ancestry(
+			Origin::signed(1),
+			header.clone(),
+			justification,
+			ancestry,
+		)
+	}
+
+	/// Returns chain of `count` headers, built on top of the `parent` header.
+	fn headers_chain(parent: &TestHeader, count: usize) -> Vec<TestHeader> {
+		let mut headers: Vec<TestHeader> = Vec::with_capacity(count);
+		for _ in 0..count {
+			let parent = headers.last().unwrap_or(parent);
+			let mut header = test_header(parent.number + 1);
+			header.parent_hash = parent.hash();
+			headers.push(header);
+		}
+		headers
+	}
+
 	fn next_block() {
 		use frame_support::traits::OnInitialize;
 
@@ -782,6 +1152,133 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn succesfully_imports_header_with_ancestry() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let mut headers = headers_chain(&test_header(0), 4);
+			let header = headers.pop().unwrap();
+			headers.reverse();
+			assert_ok!(submit_finality_proof_with_ancestry(&header, headers));
+
+			assert_eq!(<BestFinalized<TestRuntime>>::get(), header.hash());
+			assert!(<ImportedHeaders<TestRuntime>>::contains_key(header.hash()));
+		})
+	}
+
+	#[test]
+	fn succesfully_imports_direct_child_of_best_finalized_header_with_empty_ancestry() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let header = headers_chain(&test_header(0), 1).pop().unwrap();
+			assert_ok!(submit_finality_proof_with_ancestry(&header, vec![]));
+
+			assert_eq!(<BestFinalized<TestRuntime>>::get(), header.hash());
+		})
+	}
+
+	#[test]
+	fn rejects_old_header_with_ancestry() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let mut headers = headers_chain(&test_header(0), 2);
+			let header = headers.pop().unwrap();
+			assert_ok!(submit_finality_proof_with_ancestry(&header, headers.clone()));
+			assert_noop!(
+				submit_finality_proof_with_ancestry(&headers[0], vec![]),
+				<Error<TestRuntime>>::OldHeader
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_header_with_incomplete_ancestry() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let mut headers = headers_chain(&test_header(0), 4);
+			let header = headers.pop().unwrap();
+			headers.remove(1);
+			assert_noop!(
+				submit_finality_proof_with_ancestry(&header, headers),
+				<Error<TestRuntime>>::IncompleteAncestry
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_header_with_redundant_ancestry() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let mut headers = headers_chain(&test_header(0), 4);
+			let header = headers.pop().unwrap();
+			headers.push(test_header(10));
+			assert_noop!(
+				submit_finality_proof_with_ancestry(&header, headers),
+				<Error<TestRuntime>>::RedundantAncestry
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_header_with_too_long_ancestry() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let max_ancestry_length = <TestRuntime as Config>::MaxAncestryLength::get() as usize;
+			let mut headers = headers_chain(&test_header(0), max_ancestry_length + 2);
+			let header = headers.pop().unwrap();
+			assert_noop!(
+				submit_finality_proof_with_ancestry(&header, headers),
+				<Error<TestRuntime>>::TooLongAncestry
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_header_that_does_not_connect_to_best_finalized_header() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let mut headers = headers_chain(&test_header(0), 2);
+			let header = headers.pop().unwrap();
+			assert_ok!(submit_finality_proof_with_ancestry(&header, headers.clone()));
+
+			// fork that starts below the best finalized header
+			let mut fork_start = test_header(1);
+			fork_start.extrinsics_root = [42u8; 32].into();
+			let mut fork = headers_chain(&fork_start, 2);
+			let fork_header = fork.pop().unwrap();
+			fork.push(fork_start);
+			assert_noop!(
+				submit_finality_proof_with_ancestry(&fork_header, fork),
+				<Error<TestRuntime>>::HeaderDoesNotConnect
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_header_with_skipped_authority_set_change() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let genesis = test_header(0);
+			let mut header_with_change = headers_chain(&genesis, 1).pop().unwrap();
+			header_with_change.digest = change_log(0);
+			let mut headers = headers_chain(&header_with_change, 2);
+			let header = headers.pop().unwrap();
+			headers.push(header_with_change);
+			assert_noop!(
+				submit_finality_proof_with_ancestry(&header, headers),
+				<Error<TestRuntime>>::MandatoryHeaderSkipped
+			);
+		})
+	}
+
 	#[test]
 	fn succesfully_imports_header_with_valid_finality() {
 		run_test(|| {
@@ -943,6 +1440,244 @@ mod tests {
 		})
 	}
 
+	fn schedule_authority_set_override(
+		origin: Origin,
+		at: TestNumber,
+	) -> frame_support::dispatch::DispatchResultWithPostInfo {
+		Pallet::<TestRuntime>::schedule_authority_set_override(
+			origin,
+			at,
+			bp_header_chain::AuthoritySet::new(authority_list(), 2),
+		)
+	}
+
+	fn submit_finality_proof_with_set_id(
+		header: u8,
+		set_id: u64,
+	) -> frame_support::dispatch::DispatchResultWithPostInfo {
+		let header = test_header(header.into());
+		let params = JustificationGeneratorParams::<TestHeader> {
+			header: header.clone(),
+			set_id,
+			..Default::default()
+		};
+		let justification = make_justification_for_header(params);
+
+		Pallet::<TestRuntime>::submit_finality_proof(Origin::signed(1), header, justification)
+	}
+
+	#[test]
+	fn authority_set_override_may_be_scheduled_by_root_or_owner_only() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_noop!(
+				schedule_authority_set_override(Origin::signed(1), 5),
+				DispatchError::BadOrigin
+			);
+			assert_ok!(schedule_authority_set_override(Origin::root(), 5));
+			assert_ok!(schedule_authority_set_override(Origin::signed(2), 6));
+			assert_eq!(
+				<AuthoritySetOverride<TestRuntime>>::get(),
+				Some((6, bp_header_chain::AuthoritySet::new(authority_list(), 2))),
+			);
+		})
+	}
+
+	#[test]
+	fn authority_set_override_is_rejected_for_finalized_header() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(2));
+
+			assert_noop!(
+				schedule_authority_set_override(Origin::root(), 2),
+				<Error<TestRuntime>>::OldHeader
+			);
+		})
+	}
+
+	#[test]
+	fn invalid_authority_set_override_is_rejected() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let invalid_authority_list = vec![(ALICE.into(), u64::MAX), (BOB.into(), u64::MAX)];
+			assert_noop!(
+				Pallet::<TestRuntime>::schedule_authority_set_override(
+					Origin::root(),
+					5,
+					bp_header_chain::AuthoritySet::new(invalid_authority_list, 2),
+				),
+				<Error<TestRuntime>>::InvalidAuthoritySet
+			);
+		})
+	}
+
+	#[test]
+	fn authority_set_override_is_applied_at_scheduled_header() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_ok!(schedule_authority_set_override(Origin::root(), 3));
+
+			// headers before the override are still finalized by the current set
+			assert_err!(
+				submit_finality_proof_with_set_id(2, 2),
+				<Error<TestRuntime>>::InvalidJustification
+			);
+			assert_ok!(submit_finality_proof_with_set_id(2, 1));
+			assert!(<AuthoritySetOverride<TestRuntime>>::exists());
+
+			// headers starting from the override are finalized by the overriding set
+			assert_err!(
+				submit_finality_proof_with_set_id(4, 1),
+				<Error<TestRuntime>>::InvalidJustification
+			);
+			assert_ok!(submit_finality_proof_with_set_id(4, 2));
+			assert!(!<AuthoritySetOverride<TestRuntime>>::exists());
+			assert_eq!(
+				<CurrentAuthoritySet<TestRuntime>>::get(),
+				bp_header_chain::AuthoritySet::new(authority_list(), 2),
+			);
+			assert_ok!(submit_finality_proof_with_set_id(5, 2));
+		})
+	}
+
+	fn validate_unsigned_finality_proof(header: u8) -> TransactionValidity {
+		let header = test_header(header.into());
+		let justification = make_default_justification(&header);
+
+		Pallet::<TestRuntime>::validate_unsigned(
+			TransactionSource::External,
+			&Call::submit_finality_proof_unsigned(header, justification),
+		)
+	}
+
+	#[test]
+	fn unsigned_finality_proof_is_validated_and_imported() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let validity = validate_unsigned_finality_proof(1).unwrap();
+			assert_eq!(validity.priority, 1);
+			assert_eq!(validity.provides.len(), 1);
+			assert_eq!(validity.longevity, UNSIGNED_FINALITY_PROOF_LONGEVITY);
+
+			let header = test_header(1);
+			let justification = make_default_justification(&header);
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_finality_proof_unsigned(
+					Origin::signed(1),
+					header.clone(),
+					justification.clone(),
+				),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::submit_finality_proof_unsigned(
+				Origin::none(),
+				header.clone(),
+				justification,
+			));
+			assert_eq!(<BestFinalized<TestRuntime>>::get(), header.hash());
+		})
+	}
+
+	#[test]
+	fn unsigned_finality_proofs_of_different_headers_are_competing() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let validity1 = validate_unsigned_finality_proof(1).unwrap();
+			let validity2 = validate_unsigned_finality_proof(2).unwrap();
+
+			// both proofs provide the same tag, so only one of them may be in the pool
+			assert_eq!(validity1.provides, validity2.provides);
+			// and the proof of the better header is preferred
+			assert!(validity2.priority > validity1.priority);
+		})
+	}
+
+	#[test]
+	fn unsigned_finality_proof_is_rejected_if_header_is_already_finalized() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(2));
+
+			assert_eq!(
+				validate_unsigned_finality_proof(1),
+				Err(InvalidTransaction::Stale.into()),
+			);
+			assert_eq!(
+				validate_unsigned_finality_proof(2),
+				Err(InvalidTransaction::Stale.into()),
+			);
+			assert!(validate_unsigned_finality_proof(3).is_ok());
+		})
+	}
+
+	#[test]
+	fn unsigned_finality_proof_is_rejected_if_justification_is_invalid() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let header = test_header(1);
+			let mut justification = make_default_justification(&header);
+			justification.round = 42;
+
+			assert_eq!(
+				Pallet::<TestRuntime>::validate_unsigned(
+					TransactionSource::External,
+					&Call::submit_finality_proof_unsigned(header, justification),
+				),
+				Err(InvalidTransaction::BadProof.into()),
+			);
+		})
+	}
+
+	#[test]
+	fn unsigned_finality_proof_is_rejected_if_pallet_is_halted() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			<IsHalted<TestRuntime>>::put(true);
+
+			assert_eq!(
+				validate_unsigned_finality_proof(1),
+				Err(InvalidTransaction::Call.into()),
+			);
+		})
+	}
+
+	#[test]
+	fn unsigned_finality_proof_is_rejected_if_too_many_requests() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			<RequestCount<TestRuntime>>::put(<TestRuntime as Config>::MaxRequests::get());
+
+			assert_eq!(
+				validate_unsigned_finality_proof(1),
+				Err(InvalidTransaction::ExhaustsResources.into()),
+			);
+		})
+	}
+
+	#[test]
+	fn signed_calls_are_not_validated_as_unsigned() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let header = test_header(1);
+			let justification = make_default_justification(&header);
+			assert_eq!(
+				Pallet::<TestRuntime>::validate_unsigned(
+					TransactionSource::External,
+					&Call::submit_finality_proof(header, justification),
+				),
+				Err(InvalidTransaction::Call.into()),
+			);
+		})
+	}
+
 	#[test]
 	fn parse_finalized_storage_proof_rejects_proof_on_unknown_header() {
 		run_test(|| {
@@ -1078,4 +1813,94 @@ mod tests {
 			);
 		})
 	}
+
+	/// Insert headers at ring buffer positions that are outdated after `HeadersToKeep` decrease.
+	fn insert_outdated_headers(numbers: &[TestNumber]) -> Vec<TestHash> {
+		let first_outdated_index = <TestRuntime as Config>::HeadersToKeep::get();
+		numbers
+			.iter()
+			.enumerate()
+			.map(|(i, number)| {
+				let header = test_header(*number);
+				let hash = header.hash();
+				<ImportedHeaders<TestRuntime>>::insert(hash, header);
+				<ImportedHashes<TestRuntime>>::insert(first_outdated_index + i as u32, hash);
+				hash
+			})
+			.collect()
+	}
+
+	#[test]
+	fn outdated_headers_are_pruned_on_idle() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			let outdated_hashes = insert_outdated_headers(&[10, 11, 12]);
+
+			// base weight (3 reads + 1 write) + 3 * prune header weight (1 read + 2 writes)
+			assert_eq!(
+				prune_outdated_headers::<TestRuntime, ()>(Weight::max_value()),
+				5 + 3 * 5
+			);
+			for hash in outdated_hashes {
+				assert!(!Pallet::<TestRuntime>::is_known_header(hash));
+			}
+			assert!(Pallet::<TestRuntime>::is_known_header(
+				Pallet::<TestRuntime>::best_finalized().hash()
+			));
+			assert_eq!(<OutdatedHashesPointer<TestRuntime>>::get(), 0);
+		})
+	}
+
+	#[test]
+	fn outdated_headers_pruning_respects_remaining_weight() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			let outdated_hashes = insert_outdated_headers(&[10, 11, 12]);
+
+			// not enough weight to do anything
+			assert_eq!(prune_outdated_headers::<TestRuntime, ()>(4), 0);
+			assert!(Pallet::<TestRuntime>::is_known_header(outdated_hashes[0]));
+
+			// enough weight to prune single header
+			assert_eq!(prune_outdated_headers::<TestRuntime, ()>(5 + 5 + 4), 5 + 5);
+			assert!(!Pallet::<TestRuntime>::is_known_header(outdated_hashes[0]));
+			assert!(Pallet::<TestRuntime>::is_known_header(outdated_hashes[1]));
+			assert_eq!(<OutdatedHashesPointer<TestRuntime>>::get(), 6);
+
+			// pruning continues from where it has stopped
+			assert_eq!(
+				prune_outdated_headers::<TestRuntime, ()>(Weight::max_value()),
+				5 + 2 * 5
+			);
+			assert!(!Pallet::<TestRuntime>::is_known_header(outdated_hashes[1]));
+			assert!(!Pallet::<TestRuntime>::is_known_header(outdated_hashes[2]));
+			assert_eq!(<OutdatedHashesPointer<TestRuntime>>::get(), 0);
+		})
+	}
+
+	#[test]
+	fn outdated_best_finalized_header_is_not_pruned() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			let outdated_hashes = insert_outdated_headers(&[10]);
+			<BestFinalized<TestRuntime>>::put(outdated_hashes[0]);
+
+			prune_outdated_headers::<TestRuntime, ()>(Weight::max_value());
+			assert!(Pallet::<TestRuntime>::is_known_header(outdated_hashes[0]));
+			assert_eq!(<ImportedHashes<TestRuntime>>::get(5), None);
+		})
+	}
+
+	#[test]
+	fn headers_are_imported_into_ring_buffer_after_headers_to_keep_decrease() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			<ImportedHashesPointer<TestRuntime>>::put(7);
+
+			assert_ok!(submit_finality_proof(1));
+			assert_eq!(<ImportedHashes<TestRuntime>>::get(2), Some(test_header(1).hash()));
+			assert_eq!(<ImportedHashes<TestRuntime>>::get(7), None);
+			assert_eq!(<ImportedHashesPointer<TestRuntime>>::get(), 3);
+		})
+	}
 }
@@ -0,0 +1,161 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Migrations of the pallet storage.
+//!
+//! Every time the storage layout of the pallet is changed, the `STORAGE_VERSION` is bumped and
+//! the migration from the previous version is added here. Migrations are applied one by one in
+//! the `on_runtime_upgrade` hook, starting from the version that is stored in the runtime storage.
+
+use crate::{Config, PalletStorageVersion};
+
+use bp_runtime::StorageVersion;
+use frame_support::{traits::Get, weights::Weight};
+
+/// Current version of the pallet storage layout.
+///
+/// Versions history:
+///
+/// - `0`: storage version is not stored in the runtime storage;
+/// - `1`: storage version is stored in the runtime storage. The layout of other storage items
+///   is the same as in version `0`.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion(1);
+
+/// Migrate pallet storage to the current version.
+///
+/// Returns the weight of the migration.
+pub fn migrate<T: Config<I>, I: 'static>() -> Weight {
+	let initial_version = PalletStorageVersion::<T, I>::get();
+	let mut weight = T::DbWeight::get().reads(1);
+
+	if initial_version > STORAGE_VERSION {
+		log::error!(
+			target: "runtime::bridge-grandpa",
+			"Storage version {:?} is newer than the version {:?}, supported by the pallet. Migrations are skipped",
+			initial_version,
+			STORAGE_VERSION,
+		);
+		return weight;
+	}
+	if initial_version == STORAGE_VERSION {
+		return weight;
+	}
+
+	let mut version = initial_version;
+	if version == StorageVersion(0) {
+		weight = weight.saturating_add(v1::migrate::<T, I>());
+		version = StorageVersion(1);
+	}
+
+	PalletStorageVersion::<T, I>::put(version);
+	log::info!(
+		target: "runtime::bridge-grandpa",
+		"Migrated pallet storage from version {:?} to version {:?}",
+		initial_version,
+		version,
+	);
+
+	weight.saturating_add(T::DbWeight::get().writes(1))
+}
+
+/// Migration to the storage version `1`.
+pub mod v1 {
+	use super::*;
+
+	/// Migrate pallet storage from version `0` to version `1`.
+	///
+	/// The version `1` has only started tracking storage version, so there's nothing to migrate.
+	pub fn migrate<T: Config<I>, I: 'static>() -> Weight {
+		0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{run_test, test_header, TestHeader, TestRuntime};
+	use crate::{BestFinalized, CurrentAuthoritySet, GenesisConfig, InitialHash, IsHalted};
+	use bp_test_utils::authority_list;
+	use codec::Encode;
+	use frame_support::traits::GenesisBuild;
+	use sp_runtime::traits::Header;
+
+	/// Returns storage key of the value at the `Grandpa` pallet, used in the mock runtime.
+	fn value_key(storage_name: &[u8]) -> Vec<u8> {
+		let mut key = sp_io::hashing::twox_128(b"Grandpa").to_vec();
+		key.extend_from_slice(&sp_io::hashing::twox_128(storage_name));
+		key
+	}
+
+	/// Write storage of the initialized pallet, using the storage layout of version `0`.
+	fn write_v0_storage(header: &TestHeader) {
+		let authority_set = bp_header_chain::AuthoritySet::new(authority_list(), 1);
+		frame_support::storage::unhashed::put_raw(&value_key(b"InitialHash"), &header.hash().encode());
+		frame_support::storage::unhashed::put_raw(&value_key(b"BestFinalized"), &header.hash().encode());
+		frame_support::storage::unhashed::put_raw(&value_key(b"CurrentAuthoritySet"), &authority_set.encode());
+		frame_support::storage::unhashed::put_raw(&value_key(b"IsHalted"), &true.encode());
+	}
+
+	#[test]
+	fn genesis_sets_current_storage_version() {
+		run_test(|| {
+			GenesisBuild::<TestRuntime>::build(&GenesisConfig::<TestRuntime>::default());
+			assert_eq!(PalletStorageVersion::<TestRuntime>::get(), STORAGE_VERSION);
+		});
+	}
+
+	#[test]
+	fn unversioned_storage_is_migrated_to_current_version() {
+		run_test(|| {
+			let header = test_header(1);
+			write_v0_storage(&header);
+
+			migrate::<TestRuntime, ()>();
+
+			assert_eq!(PalletStorageVersion::<TestRuntime>::get(), STORAGE_VERSION);
+			assert_eq!(InitialHash::<TestRuntime>::get(), header.hash());
+			assert_eq!(BestFinalized::<TestRuntime>::get(), header.hash());
+			assert_eq!(
+				CurrentAuthoritySet::<TestRuntime>::get(),
+				bp_header_chain::AuthoritySet::new(authority_list(), 1),
+			);
+			assert!(IsHalted::<TestRuntime>::get());
+		});
+	}
+
+	#[test]
+	fn storage_of_current_version_is_not_migrated() {
+		run_test(|| {
+			PalletStorageVersion::<TestRuntime>::put(STORAGE_VERSION);
+
+			migrate::<TestRuntime, ()>();
+
+			assert_eq!(PalletStorageVersion::<TestRuntime>::get(), STORAGE_VERSION);
+		});
+	}
+
+	#[test]
+	fn storage_of_unknown_version_is_not_migrated() {
+		run_test(|| {
+			let unknown_version = StorageVersion(STORAGE_VERSION.0 + 1);
+			PalletStorageVersion::<TestRuntime>::put(unknown_version);
+
+			migrate::<TestRuntime, ()>();
+
+			assert_eq!(PalletStorageVersion::<TestRuntime>::get(), unknown_version);
+		});
+	}
+}
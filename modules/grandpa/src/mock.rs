@@ -18,7 +18,10 @@
 #![allow(clippy::from_over_into)]
 
 use bp_runtime::Chain;
-use frame_support::{construct_runtime, parameter_types, weights::Weight};
+use frame_support::{
+	construct_runtime, parameter_types,
+	weights::{RuntimeDbWeight, Weight},
+};
 use sp_runtime::{
 	testing::{Header, H256},
 	traits::{BlakeTwo256, IdentityLookup},
@@ -51,6 +54,7 @@ parameter_types! {
 	pub const MaximumBlockWeight: Weight = 1024;
 	pub const MaximumBlockLength: u32 = 2 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::one();
+	pub const TestDbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 1, write: 2 };
 }
 
 impl frame_system::Config for TestRuntime {
@@ -72,7 +76,7 @@ impl frame_system::Config for TestRuntime {
 	type OnKilledAccount = ();
 	type BaseCallFilter = ();
 	type SystemWeightInfo = ();
-	type DbWeight = ();
+	type DbWeight = TestDbWeight;
 	type BlockWeights = ();
 	type BlockLength = ();
 	type SS58Prefix = ();
@@ -82,6 +86,7 @@ impl frame_system::Config for TestRuntime {
 parameter_types! {
 	pub const MaxRequests: u32 = 2;
 	pub const HeadersToKeep: u32 = 5;
+	pub const MaxAncestryLength: u32 = 8;
 	pub const SessionLength: u64 = 5;
 	pub const NumValidators: u32 = 5;
 }
@@ -90,6 +95,7 @@ impl grandpa::Config for TestRuntime {
 	type BridgedChain = TestBridgedChain;
 	type MaxRequests = MaxRequests;
 	type HeadersToKeep = HeadersToKeep;
+	type MaxAncestryLength = MaxAncestryLength;
 	type WeightInfo = ();
 }
 
@@ -0,0 +1,299 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Node RPC extension for querying state of the bridge messages module.
+//!
+//! The extension reads the module storage directly, so it doesn't require any runtime APIs. Single
+//! extension serves all messages module instances of the runtime. Every instance is identified by
+//! the name (normally - name of the bridged chain), that is passed to every RPC method.
+
+#![warn(missing_docs)]
+
+use bp_messages::{InboundLaneData, LaneId, MessageData, MessageNonce, OutboundLaneData};
+use codec::Decode;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use pallet_bridge_messages::{storage_keys, Config, Instance};
+use sc_client_api::{backend::Backend, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, Bytes};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+
+/// Error code, returned when unknown messages module instance is requested.
+const UNKNOWN_INSTANCE_ERROR: i64 = 1;
+/// Error code, returned when storage read has failed.
+const STORAGE_READ_ERROR: i64 = 2;
+/// Error code, returned when storage value has failed to decode.
+const DECODE_ERROR: i64 = 3;
+
+/// Outbound lane state.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboundLaneState {
+	/// Nonce of oldest message that is not yet pruned.
+	pub oldest_unpruned_nonce: MessageNonce,
+	/// Nonce of latest message, received by bridged chain.
+	pub latest_received_nonce: MessageNonce,
+	/// Nonce of latest message, generated by this chain.
+	pub latest_generated_nonce: MessageNonce,
+}
+
+/// Inbound lane state.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboundLaneState {
+	/// Nonce of latest message, delivered to this chain.
+	pub last_delivered_nonce: MessageNonce,
+	/// Nonce of latest message, which delivery has been confirmed at the bridged chain.
+	pub last_confirmed_nonce: MessageNonce,
+	/// Number of unrewarded relayer entries.
+	pub unrewarded_relayer_entries: MessageNonce,
+}
+
+/// Unrewarded relayer entry of the inbound lane.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnrewardedRelayer<RelayerId> {
+	/// Nonce of the first message, delivered by the relayer.
+	pub begin: MessageNonce,
+	/// Nonce of the last message, delivered by the relayer.
+	pub end: MessageNonce,
+	/// Relayer account at the bridged chain.
+	pub relayer: RelayerId,
+}
+
+/// Outbound message details.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboundMessage<Fee> {
+	/// Message nonce.
+	pub nonce: MessageNonce,
+	/// Encoded message payload.
+	pub payload: Bytes,
+	/// Message delivery and dispatch fee, paid by the submitter.
+	pub fee: Fee,
+}
+
+/// Messages module RPC methods.
+#[rpc(server)]
+pub trait MessagesApi<BlockHash, RelayerId, Fee> {
+	/// Returns state of the outbound lane.
+	#[rpc(name = "messages_outboundLaneState")]
+	fn outbound_lane_state(
+		&self,
+		instance: String,
+		lane: LaneId,
+		at: Option<BlockHash>,
+	) -> RpcResult<OutboundLaneState>;
+
+	/// Returns state of the inbound lane.
+	#[rpc(name = "messages_inboundLaneState")]
+	fn inbound_lane_state(&self, instance: String, lane: LaneId, at: Option<BlockHash>) -> RpcResult<InboundLaneState>;
+
+	/// Returns unrewarded relayer entries of the inbound lane, ordered by message nonce.
+	#[rpc(name = "messages_unrewardedRelayers")]
+	fn unrewarded_relayers(
+		&self,
+		instance: String,
+		lane: LaneId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<UnrewardedRelayer<RelayerId>>>;
+
+	/// Returns details of the outbound message or `None` if message is not (or no longer) stored.
+	#[rpc(name = "messages_outboundMessage")]
+	fn outbound_message(
+		&self,
+		instance: String,
+		lane: LaneId,
+		nonce: MessageNonce,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<OutboundMessage<Fee>>>;
+}
+
+/// Storage keys of the single messages module instance.
+#[derive(Clone, Copy)]
+pub struct MessagesInstance {
+	outbound_lane_data_key: fn(&LaneId) -> StorageKey,
+	inbound_lane_data_key: fn(&LaneId) -> StorageKey,
+	message_key: fn(&LaneId, MessageNonce) -> StorageKey,
+}
+
+impl MessagesInstance {
+	/// Create instance for given runtime and messages module instance.
+	pub fn new<T: Config<I>, I: Instance>() -> Self {
+		MessagesInstance {
+			outbound_lane_data_key: storage_keys::outbound_lane_data_key::<I>,
+			inbound_lane_data_key: storage_keys::inbound_lane_data_key::<T, I>,
+			message_key: storage_keys::message_key::<T, I>,
+		}
+	}
+}
+
+/// Implementation of the `MessagesApi`.
+pub struct Messages<C, Block, BE, RelayerId, Fee> {
+	client: Arc<C>,
+	instances: BTreeMap<String, MessagesInstance>,
+	_phantom: PhantomData<fn() -> (Block, BE, RelayerId, Fee)>,
+}
+
+impl<C, Block, BE, RelayerId, Fee> Messages<C, Block, BE, RelayerId, Fee> {
+	/// Create new messages RPC handler, serving given module instances.
+	pub fn new(client: Arc<C>, instances: impl IntoIterator<Item = (String, MessagesInstance)>) -> Self {
+		Messages {
+			client,
+			instances: instances.into_iter().collect(),
+			_phantom: Default::default(),
+		}
+	}
+}
+
+impl<C, Block, BE, RelayerId, Fee> Messages<C, Block, BE, RelayerId, Fee>
+where
+	Block: BlockT,
+	BE: Backend<Block>,
+	C: StorageProvider<Block, BE> + HeaderBackend<Block>,
+{
+	/// Return module instance by its name.
+	fn instance(&self, instance: &str) -> RpcResult<&MessagesInstance> {
+		self.instances.get(instance).ok_or_else(|| RpcError {
+			code: ErrorCode::ServerError(UNKNOWN_INSTANCE_ERROR),
+			message: format!("Unknown messages module instance: {}", instance),
+			data: None,
+		})
+	}
+
+	/// Read and decode value from the runtime storage.
+	fn storage_value<T: Decode>(&self, at: Option<Block::Hash>, key: StorageKey) -> RpcResult<Option<T>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		self.client
+			.storage(&at, &key)
+			.map_err(|e| RpcError {
+				code: ErrorCode::ServerError(STORAGE_READ_ERROR),
+				message: "Failed to read runtime storage".into(),
+				data: Some(format!("{:?}", e).into()),
+			})?
+			.map(|data| {
+				T::decode(&mut &data.0[..]).map_err(|e| RpcError {
+					code: ErrorCode::ServerError(DECODE_ERROR),
+					message: "Failed to decode runtime storage value".into(),
+					data: Some(format!("{:?}", e).into()),
+				})
+			})
+			.transpose()
+	}
+
+	/// Read inbound lane data.
+	fn inbound_lane_data(
+		&self,
+		instance: &str,
+		lane: LaneId,
+		at: Option<Block::Hash>,
+	) -> RpcResult<InboundLaneData<RelayerId>>
+	where
+		RelayerId: Decode,
+	{
+		let key = (self.instance(instance)?.inbound_lane_data_key)(&lane);
+		Ok(self.storage_value(at, key)?.unwrap_or_default())
+	}
+}
+
+impl<C, Block, BE, RelayerId, Fee> MessagesApi<Block::Hash, RelayerId, Fee> for Messages<C, Block, BE, RelayerId, Fee>
+where
+	Block: BlockT,
+	BE: Backend<Block> + 'static,
+	C: StorageProvider<Block, BE> + HeaderBackend<Block> + Send + Sync + 'static,
+	RelayerId: Decode + Serialize + Send + Sync + 'static,
+	Fee: Decode + Serialize + Send + Sync + 'static,
+{
+	fn outbound_lane_state(
+		&self,
+		instance: String,
+		lane: LaneId,
+		at: Option<Block::Hash>,
+	) -> RpcResult<OutboundLaneState> {
+		let key = (self.instance(&instance)?.outbound_lane_data_key)(&lane);
+		let data: OutboundLaneData = self.storage_value(at, key)?.unwrap_or_default();
+		Ok(OutboundLaneState {
+			oldest_unpruned_nonce: data.oldest_unpruned_nonce,
+			latest_received_nonce: data.latest_received_nonce,
+			latest_generated_nonce: data.latest_generated_nonce,
+		})
+	}
+
+	fn inbound_lane_state(
+		&self,
+		instance: String,
+		lane: LaneId,
+		at: Option<Block::Hash>,
+	) -> RpcResult<InboundLaneState> {
+		let data = self.inbound_lane_data(&instance, lane, at)?;
+		Ok(InboundLaneState {
+			last_delivered_nonce: data.last_delivered_nonce(),
+			last_confirmed_nonce: data.last_confirmed_nonce,
+			unrewarded_relayer_entries: data.relayers.len() as _,
+		})
+	}
+
+	fn unrewarded_relayers(
+		&self,
+		instance: String,
+		lane: LaneId,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Vec<UnrewardedRelayer<RelayerId>>> {
+		let data = self.inbound_lane_data(&instance, lane, at)?;
+		Ok(data
+			.relayers
+			.into_iter()
+			.map(|(begin, end, relayer)| UnrewardedRelayer { begin, end, relayer })
+			.collect())
+	}
+
+	fn outbound_message(
+		&self,
+		instance: String,
+		lane: LaneId,
+		nonce: MessageNonce,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<OutboundMessage<Fee>>> {
+		let key = (self.instance(&instance)?.message_key)(&lane, nonce);
+		let data: Option<MessageData<Fee>> = self.storage_value(at, key)?;
+		Ok(data.map(|data| OutboundMessage {
+			nonce,
+			payload: data.payload.into(),
+			fee: data.fee,
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn outbound_lane_state_is_serialized_in_camel_case() {
+		let state = OutboundLaneState {
+			oldest_unpruned_nonce: 1,
+			latest_received_nonce: 2,
+			latest_generated_nonce: 3,
+		};
+		assert_eq!(
+			serde_json::to_string(&state).unwrap(),
+			r#"{"oldestUnprunedNonce":1,"latestReceivedNonce":2,"latestGeneratedNonce":3}"#,
+		);
+	}
+}
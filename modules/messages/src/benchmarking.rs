@@ -422,6 +422,8 @@ benchmarks_instance! {
 			inbound_lane_data: InboundLaneData {
 				relayers: vec![(1, 1, relayer_id.clone())].into_iter().collect(),
 				last_confirmed_nonce: 0,
+				expired_messages: Default::default(),
+				unpaid_messages: Default::default(),
 			},
 			size: ProofSize::Minimal(0),
 		});
@@ -460,6 +462,8 @@ benchmarks_instance! {
 			inbound_lane_data: InboundLaneData {
 				relayers: vec![(1, 2, relayer_id.clone())].into_iter().collect(),
 				last_confirmed_nonce: 0,
+				expired_messages: Default::default(),
+				unpaid_messages: Default::default(),
 			},
 			size: ProofSize::Minimal(0),
 		});
@@ -500,6 +504,8 @@ benchmarks_instance! {
 					(2, 2, relayer2_id.clone()),
 				].into_iter().collect(),
 				last_confirmed_nonce: 0,
+				expired_messages: Default::default(),
+				unpaid_messages: Default::default(),
 			},
 			size: ProofSize::Minimal(0),
 		});
@@ -733,6 +739,8 @@ benchmarks_instance! {
 			inbound_lane_data: InboundLaneData {
 				relayers: vec![(1, i as MessageNonce, relayer_id.clone())].into_iter().collect(),
 				last_confirmed_nonce: 0,
+				expired_messages: Default::default(),
+				unpaid_messages: Default::default(),
 			},
 			size: ProofSize::Minimal(0),
 		});
@@ -779,6 +787,8 @@ benchmarks_instance! {
 					.map(|(j, relayer_id)| (j as MessageNonce + 1, j as MessageNonce + 1, relayer_id.clone()))
 					.collect(),
 				last_confirmed_nonce: 0,
+				expired_messages: Default::default(),
+				unpaid_messages: Default::default(),
 			},
 			size: ProofSize::Minimal(0),
 		});
@@ -816,6 +826,8 @@ fn receive_messages<T: Config<I>, I: Instance>(nonce: MessageNonce) {
 	inbound_lane_storage.set_data(InboundLaneData {
 		relayers: vec![(1, nonce, T::bridged_relayer_id())].into_iter().collect(),
 		last_confirmed_nonce: 0,
+		expired_messages: Default::default(),
+		unpaid_messages: Default::default(),
 	});
 }
 
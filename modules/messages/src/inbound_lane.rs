@@ -20,8 +20,22 @@ use bp_messages::{
 	target_chain::{DispatchMessage, DispatchMessageData, MessageDispatch},
 	InboundLaneData, LaneId, MessageKey, MessageNonce, OutboundLaneData,
 };
+use bp_runtime::messages::MessageDispatchResult;
+use frame_support::RuntimeDebug;
 use sp_std::prelude::PartialEq;
 
+/// Result of the inbound message receival.
+#[derive(RuntimeDebug, PartialEq, Eq)]
+pub enum ReceivalResult {
+	/// Message has been received and handed over to the dispatcher.
+	Dispatched(MessageDispatchResult),
+	/// Message has been received, but it has expired, so it hasn't been dispatched.
+	Expired,
+	/// Message has been rejected by the lane (invalid nonce, too many unrewarded relayer entries
+	/// or too many unconfirmed messages).
+	Rejected,
+}
+
 /// Inbound lane storage.
 pub trait InboundLaneStorage {
 	/// Delivery and dispatch fee type on source chain.
@@ -52,6 +66,11 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 		InboundLane { storage }
 	}
 
+	/// Returns nonce of the latest message, received by this lane.
+	pub fn last_delivered_nonce(&self) -> MessageNonce {
+		self.storage.data().last_delivered_nonce()
+	}
+
 	/// Receive state of the corresponding outbound lane.
 	pub fn receive_state_update(&mut self, outbound_lane_data: OutboundLaneData) -> Option<MessageNonce> {
 		let mut data = self.storage.data();
@@ -84,33 +103,55 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 			}
 			_ => {}
 		}
+		// Finally, forget about expired messages that the source chain already knows about.
+		while data
+			.expired_messages
+			.front()
+			.map(|nonce| *nonce <= new_confirmed_nonce)
+			.unwrap_or(false)
+		{
+			data.expired_messages.pop_front();
+		}
+		// ... and about messages that have failed to pay dispatch fee.
+		while data
+			.unpaid_messages
+			.front()
+			.map(|nonce| *nonce <= new_confirmed_nonce)
+			.unwrap_or(false)
+		{
+			data.unpaid_messages.pop_front();
+		}
 
 		self.storage.set_data(data);
 		Some(outbound_lane_data.latest_received_nonce)
 	}
 
 	/// Receive new message.
-	pub fn receive_message<P: MessageDispatch<S::MessageFee>>(
+	///
+	/// The `relayer_at_this_chain` is the account that has submitted the delivery transaction.
+	/// It receives the dispatch fee if the message pays it at this chain.
+	pub fn receive_message<P: MessageDispatch<AccountId, S::MessageFee>, AccountId>(
 		&mut self,
 		relayer: S::Relayer,
+		relayer_at_this_chain: &AccountId,
 		nonce: MessageNonce,
 		message_data: DispatchMessageData<P::DispatchPayload, S::MessageFee>,
-	) -> bool {
+	) -> ReceivalResult {
 		let mut data = self.storage.data();
 		let is_correct_message = nonce == data.last_delivered_nonce() + 1;
 		if !is_correct_message {
-			return false;
+			return ReceivalResult::Rejected;
 		}
 
 		// if there are more unrewarded relayer entries than we may accept, reject this message
 		if data.relayers.len() as MessageNonce >= self.storage.max_unrewarded_relayer_entries() {
-			return false;
+			return ReceivalResult::Rejected;
 		}
 
 		// if there are more unconfirmed messages than we may accept, reject this message
 		let unconfirmed_messages_count = nonce.saturating_sub(data.last_confirmed_nonce);
 		if unconfirmed_messages_count > self.storage.max_unconfirmed_messages() {
-			return false;
+			return ReceivalResult::Rejected;
 		}
 
 		let push_new = match data.relayers.back_mut() {
@@ -124,17 +165,34 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 			data.relayers.push_back((nonce, nonce, relayer));
 		}
 
-		self.storage.set_data(data);
-
-		P::dispatch(DispatchMessage {
+		let dispatch_message = DispatchMessage {
 			key: MessageKey {
 				lane_id: self.storage.id(),
 				nonce,
 			},
 			data: message_data,
-		});
+		};
+
+		// the message is delivered too late => we only remember that it has expired, so that
+		// the source chain is notified with the delivery confirmation
+		if P::is_expired(&dispatch_message) {
+			data.expired_messages.push_back(nonce);
+			self.storage.set_data(data);
+			return ReceivalResult::Expired;
+		}
+
+		self.storage.set_data(data);
 
-		true
+		// the message has failed to pay dispatch fee at this chain => we remember that, so that
+		// the source chain is notified with the delivery confirmation
+		let dispatch_result = P::dispatch(relayer_at_this_chain, dispatch_message);
+		if !dispatch_result.dispatch_fee_paid {
+			let mut data = self.storage.data();
+			data.unpaid_messages.push_back(nonce);
+			self.storage.set_data(data);
+		}
+
+		ReceivalResult::Dispatched(dispatch_result)
 	}
 }
 
@@ -144,21 +202,32 @@ mod tests {
 	use crate::{
 		inbound_lane,
 		mock::{
-			message_data, run_test, TestMessageDispatch, TestRuntime, REGULAR_PAYLOAD, TEST_LANE_ID, TEST_RELAYER_A,
-			TEST_RELAYER_B, TEST_RELAYER_C,
+			message_data, run_test, TestMessageDispatch, TestRuntime, EXPIRED_PAYLOAD, REGULAR_PAYLOAD, TEST_LANE_ID,
+			TEST_RELAYER_A, TEST_RELAYER_B, TEST_RELAYER_C, UNPAID_PAYLOAD,
 		},
 		DefaultInstance, RuntimeInboundLaneStorage,
 	};
 
+	fn dispatched() -> ReceivalResult {
+		ReceivalResult::Dispatched(MessageDispatchResult {
+			dispatch_result: true,
+			dispatch_fee_paid: true,
+		})
+	}
+
 	fn receive_regular_message(
 		lane: &mut InboundLane<RuntimeInboundLaneStorage<TestRuntime, DefaultInstance>>,
 		nonce: MessageNonce,
 	) {
-		assert!(lane.receive_message::<TestMessageDispatch>(
-			TEST_RELAYER_A,
-			nonce,
-			message_data(REGULAR_PAYLOAD).into()
-		));
+		assert_eq!(
+			lane.receive_message::<TestMessageDispatch, _>(
+				TEST_RELAYER_A,
+				&TEST_RELAYER_A,
+				nonce,
+				message_data(REGULAR_PAYLOAD).into()
+			),
+			dispatched()
+		);
 	}
 
 	#[test]
@@ -265,15 +334,114 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn receive_status_update_prunes_confirmed_expired_messages() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			let mut seed_storage_data = lane.storage.data();
+			seed_storage_data.relayers.push_back((1, 4, TEST_RELAYER_A));
+			seed_storage_data.expired_messages = vec![1, 3, 4].into_iter().collect();
+			lane.storage.set_data(seed_storage_data);
+
+			assert_eq!(
+				lane.receive_state_update(OutboundLaneData {
+					latest_received_nonce: 3,
+					..Default::default()
+				}),
+				Some(3),
+			);
+			assert_eq!(lane.storage.data().expired_messages, vec![4]);
+		});
+	}
+
+	#[test]
+	fn receive_status_update_prunes_confirmed_unpaid_messages() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			let mut seed_storage_data = lane.storage.data();
+			seed_storage_data.relayers.push_back((1, 4, TEST_RELAYER_A));
+			seed_storage_data.unpaid_messages = vec![1, 3, 4].into_iter().collect();
+			lane.storage.set_data(seed_storage_data);
+
+			assert_eq!(
+				lane.receive_state_update(OutboundLaneData {
+					latest_received_nonce: 3,
+					..Default::default()
+				}),
+				Some(3),
+			);
+			assert_eq!(lane.storage.data().unpaid_messages, vec![4]);
+		});
+	}
+
+	#[test]
+	fn expired_message_is_received_but_not_dispatched() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			receive_regular_message(&mut lane, 1);
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					2,
+					message_data(EXPIRED_PAYLOAD).into()
+				),
+				ReceivalResult::Expired,
+			);
+
+			assert_eq!(lane.storage.data().last_delivered_nonce(), 2);
+			assert_eq!(lane.storage.data().expired_messages, vec![2]);
+			assert!(TestMessageDispatch::is_dispatched(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 1
+			}));
+			assert!(!TestMessageDispatch::is_dispatched(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 2
+			}));
+		});
+	}
+
+	#[test]
+	fn unpaid_message_is_received_but_not_dispatched() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			receive_regular_message(&mut lane, 1);
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					2,
+					message_data(UNPAID_PAYLOAD).into()
+				),
+				ReceivalResult::Dispatched(MessageDispatchResult {
+					dispatch_result: false,
+					dispatch_fee_paid: false,
+				}),
+			);
+
+			assert_eq!(lane.storage.data().last_delivered_nonce(), 2);
+			assert_eq!(lane.storage.data().unpaid_messages, vec![2]);
+			assert!(!TestMessageDispatch::is_dispatched(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 2
+			}));
+		});
+	}
+
 	#[test]
 	fn fails_to_receive_message_with_incorrect_nonce() {
 		run_test(|| {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
-			assert!(!lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A,
-				10,
-				message_data(REGULAR_PAYLOAD).into()
-			));
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					10,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::Rejected
+			);
 			assert_eq!(lane.storage.data().last_delivered_nonce(), 0);
 		});
 	}
@@ -284,26 +452,32 @@ mod tests {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
 			let max_nonce = <TestRuntime as crate::Config>::MaxUnrewardedRelayerEntriesAtInboundLane::get();
 			for current_nonce in 1..max_nonce + 1 {
-				assert!(lane.receive_message::<TestMessageDispatch>(
-					TEST_RELAYER_A + current_nonce,
-					current_nonce,
-					message_data(REGULAR_PAYLOAD).into()
-				));
+				assert_eq!(
+					lane.receive_message::<TestMessageDispatch, _>(
+						TEST_RELAYER_A + current_nonce,
+						&TEST_RELAYER_A,
+						current_nonce,
+						message_data(REGULAR_PAYLOAD).into()
+					),
+					dispatched()
+				);
 			}
 			// Fails to dispatch new message from different than latest relayer.
 			assert_eq!(
-				false,
-				lane.receive_message::<TestMessageDispatch>(
+				ReceivalResult::Rejected,
+				lane.receive_message::<TestMessageDispatch, _>(
 					TEST_RELAYER_A + max_nonce + 1,
+					&TEST_RELAYER_A,
 					max_nonce + 1,
 					message_data(REGULAR_PAYLOAD).into()
 				)
 			);
 			// Fails to dispatch new messages from latest relayer. Prevents griefing attacks.
 			assert_eq!(
-				false,
-				lane.receive_message::<TestMessageDispatch>(
+				ReceivalResult::Rejected,
+				lane.receive_message::<TestMessageDispatch, _>(
 					TEST_RELAYER_A + max_nonce,
+					&TEST_RELAYER_A,
 					max_nonce + 1,
 					message_data(REGULAR_PAYLOAD).into()
 				)
@@ -317,26 +491,32 @@ mod tests {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
 			let max_nonce = <TestRuntime as crate::Config>::MaxUnconfirmedMessagesAtInboundLane::get();
 			for current_nonce in 1..=max_nonce {
-				assert!(lane.receive_message::<TestMessageDispatch>(
-					TEST_RELAYER_A,
-					current_nonce,
-					message_data(REGULAR_PAYLOAD).into()
-				));
+				assert_eq!(
+					lane.receive_message::<TestMessageDispatch, _>(
+						TEST_RELAYER_A,
+						&TEST_RELAYER_A,
+						current_nonce,
+						message_data(REGULAR_PAYLOAD).into()
+					),
+					dispatched()
+				);
 			}
 			// Fails to dispatch new message from different than latest relayer.
 			assert_eq!(
-				false,
-				lane.receive_message::<TestMessageDispatch>(
+				ReceivalResult::Rejected,
+				lane.receive_message::<TestMessageDispatch, _>(
 					TEST_RELAYER_B,
+					&TEST_RELAYER_A,
 					max_nonce + 1,
 					message_data(REGULAR_PAYLOAD).into()
 				)
 			);
 			// Fails to dispatch new messages from latest relayer.
 			assert_eq!(
-				false,
-				lane.receive_message::<TestMessageDispatch>(
+				ReceivalResult::Rejected,
+				lane.receive_message::<TestMessageDispatch, _>(
 					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
 					max_nonce + 1,
 					message_data(REGULAR_PAYLOAD).into()
 				)
@@ -348,21 +528,33 @@ mod tests {
 	fn correctly_receives_following_messages_from_two_relayers_alternately() {
 		run_test(|| {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
-			assert!(lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A,
-				1,
-				message_data(REGULAR_PAYLOAD).into()
-			));
-			assert!(lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_B,
-				2,
-				message_data(REGULAR_PAYLOAD).into()
-			));
-			assert!(lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A,
-				3,
-				message_data(REGULAR_PAYLOAD).into()
-			));
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					1,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				dispatched()
+			);
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_B,
+					&TEST_RELAYER_A,
+					2,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				dispatched()
+			);
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					3,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				dispatched()
+			);
 			assert_eq!(
 				lane.storage.data().relayers,
 				vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B), (3, 3, TEST_RELAYER_A)]
@@ -374,14 +566,23 @@ mod tests {
 	fn rejects_same_message_from_two_different_relayers() {
 		run_test(|| {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
-			assert!(lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A,
-				1,
-				message_data(REGULAR_PAYLOAD).into()
-			));
 			assert_eq!(
-				false,
-				lane.receive_message::<TestMessageDispatch>(TEST_RELAYER_B, 1, message_data(REGULAR_PAYLOAD).into())
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					1,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				dispatched()
+			);
+			assert_eq!(
+				ReceivalResult::Rejected,
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_B,
+					&TEST_RELAYER_A,
+					1,
+					message_data(REGULAR_PAYLOAD).into()
+				)
 			);
 		});
 	}
@@ -27,6 +27,11 @@
 //! The assigned nonce is reported using `MessageAccepted` event. When message is
 //! delivered to the the bridged chain, it is reported using `MessagesDelivered` event.
 //!
+//! Every message that is delivered to this chain is also recorded in the offchain indexing
+//! storage (see `offchain_keys::delivered_message_key`), so that block explorers may show the
+//! message status without replaying all pallet events. The node must be started with the
+//! `--enable-offchain-indexing true` flag to keep these records.
+//!
 //! **IMPORTANT NOTE**: after generating weights (custom `WeighInfo` implementation) for
 //! your runtime (where this module is plugged to), please add test for these weights.
 //! The test should call the `ensure_weights_are_correct` function from this module.
@@ -40,17 +45,21 @@ pub use crate::weights_ext::{
 	EXPECTED_DEFAULT_MESSAGE_LENGTH,
 };
 
-use crate::inbound_lane::{InboundLane, InboundLaneStorage};
+use crate::inbound_lane::{InboundLane, InboundLaneStorage, ReceivalResult};
 use crate::outbound_lane::{OutboundLane, OutboundLaneStorage};
 use crate::weights::WeightInfo;
 
 use bp_messages::{
+	nonces::DeliveredMessages,
 	source_chain::{LaneMessageVerifier, MessageDeliveryAndDispatchPayment, RelayersRewards, TargetHeaderChain},
-	target_chain::{DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages, SourceHeaderChain},
-	total_unrewarded_messages, InboundLaneData, LaneId, MessageData, MessageKey, MessageNonce, MessagePayload,
-	OutboundLaneData, Parameter as MessagesParameter, UnrewardedRelayersState,
+	target_chain::{
+		DispatchMessage, MessageDispatch, OnMessagesDelivered, ProvedLaneMessages, ProvedMessages, SourceHeaderChain,
+	},
+	total_unrewarded_messages, DeliveredMessageRecord, InboundLaneData, LaneId, MessageData, MessageKey, MessageNonce,
+	MessagePayload, MessagesDeliveryLimits, OutboundLaneData, Parameter as MessagesParameter, RelayingPolicy,
+	UnrewardedRelayersState,
 };
-use bp_runtime::Size;
+use bp_runtime::{DeclaredWeight, Size, StorageVersion};
 use codec::{Decode, Encode};
 use frame_support::{
 	decl_error, decl_event, decl_module, decl_storage, ensure,
@@ -68,6 +77,7 @@ mod outbound_lane;
 mod weights_ext;
 
 pub mod instant_payments;
+pub mod migrations;
 pub mod weights;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -117,8 +127,16 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 	/// from different lanes.
 	type MaxUnconfirmedMessagesAtInboundLane: Get<MessageNonce>;
 
+	/// Maximal size of the outbound message payload. Larger messages are rejected by the
+	/// `send_message` call, because they would never fit into the delivery transaction.
+	type MaximalOutboundPayloadSize: Get<u32>;
+	/// Maximal dispatch weight of the outbound message. Messages with larger declared dispatch
+	/// weight are rejected by the `send_message` call, because they would never fit into the
+	/// delivery transaction.
+	type MaximalOutboundDispatchWeight: Get<Weight>;
+
 	/// Payload type of outbound messages. This payload is dispatched on the bridged chain.
-	type OutboundPayload: Parameter + Size;
+	type OutboundPayload: Parameter + Size + DeclaredWeight;
 	/// Message fee type of outbound messages. This fee is paid on this chain.
 	type OutboundMessageFee: Default + From<u64> + PartialOrd + Parameter + SaturatingAdd + Zero;
 
@@ -148,7 +166,13 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 	/// Source header chain, as it is represented on target chain.
 	type SourceHeaderChain: SourceHeaderChain<Self::InboundMessageFee>;
 	/// Message dispatch.
-	type MessageDispatch: MessageDispatch<Self::InboundMessageFee, DispatchPayload = Self::InboundPayload>;
+	type MessageDispatch: MessageDispatch<
+		Self::AccountId,
+		Self::InboundMessageFee,
+		DispatchPayload = Self::InboundPayload,
+	>;
+	/// Hook that is called when new messages are delivered to this chain.
+	type OnMessagesDelivered: OnMessagesDelivered<Self::AccountId>;
 }
 
 /// Shortcut to messages proof type for Config.
@@ -164,6 +188,10 @@ decl_error! {
 	pub enum Error for Pallet<T: Config<I>, I: Instance> {
 		/// All pallet operations are halted.
 		Halted,
+		/// Message is too large to be delivered to the bridged chain.
+		MessageIsTooLarge,
+		/// Declared dispatch weight of the message is too large to be delivered to the bridged chain.
+		MessageDispatchWeightIsTooLarge,
 		/// Message has been treated as invalid by chain verifier.
 		MessageRejectedByChainVerifier,
 		/// Message has been treated as invalid by lane verifier.
@@ -176,6 +204,8 @@ decl_error! {
 		InvalidMessagesProof,
 		/// Invalid messages dispatch weight has been declared by the relayer.
 		InvalidMessagesDispatchWeight,
+		/// The relayer has declared messages dispatch weight that exceeds the maximal extrinsic weight.
+		MessagesDispatchWeightIsTooLarge,
 		/// Invalid messages delivery proof has been submitted.
 		InvalidMessagesDeliveryProof,
 		/// The relayer has declared invalid unrewarded relayers state in the `receive_messages_delivery_proof` call.
@@ -183,7 +213,9 @@ decl_error! {
 		/// The message someone is trying to work with (i.e. increase fee) is already-delivered.
 		MessageIsAlreadyDelivered,
 		/// The message someone is trying to work with (i.e. increase fee) is not yet sent.
-		MessageIsNotYetSent
+		MessageIsNotYetSent,
+		/// The submitter is not allowed to deliver messages over the permissioned lane.
+		RelayerIsNotAllowed
 	}
 }
 
@@ -204,6 +236,17 @@ decl_storage! {
 		pub OutboundLanes: map hasher(blake2_128_concat) LaneId => OutboundLaneData;
 		/// All queued outbound messages.
 		pub OutboundMessages: map hasher(blake2_128_concat) MessageKey => Option<MessageData<T::OutboundMessageFee>>;
+		/// The last outbound lane that has been visited by the `on_idle` pruning. Next time pruning
+		/// continues from the lane that follows it. `None` means that pruning starts from the first lane.
+		pub OutboundLanesPruningCursor: Option<LaneId>;
+		/// Map of lane id => policy of relaying messages over the inbound lane.
+		pub InboundLaneRelayingPolicies get(fn inbound_lane_relaying_policy):
+			map hasher(blake2_128_concat) LaneId => RelayingPolicy;
+		/// Relayers that are allowed to deliver messages over permissioned inbound lanes.
+		pub InboundLaneRelayers get(fn is_inbound_lane_relayer):
+			double_map hasher(blake2_128_concat) LaneId, hasher(blake2_128_concat) T::AccountId => bool;
+		/// Version of the pallet storage layout.
+		pub PalletStorageVersion get(fn storage_version) build(|_| migrations::STORAGE_VERSION): StorageVersion;
 	}
 	add_extra_genesis {
 		config(phantom): sp_std::marker::PhantomData<I>;
@@ -228,6 +271,17 @@ decl_event!(
 		MessageAccepted(LaneId, MessageNonce),
 		/// Messages in the inclusive range have been delivered and processed by the bridged chain.
 		MessagesDelivered(LaneId, MessageNonce, MessageNonce),
+		/// Message has been delivered after its delivery deadline and hasn't been dispatched by the bridged chain.
+		MessageExpired(LaneId, MessageNonce),
+		/// Message had to pay dispatch fee at the bridged chain, but the payment has failed and the
+		/// message hasn't been dispatched by the bridged chain.
+		MessageDispatchFeeNotPaid(LaneId, MessageNonce),
+		/// Relaying policy of the inbound lane has been updated.
+		InboundLaneRelayingPolicyUpdated(LaneId, RelayingPolicy),
+		/// Relayer has been added to the whitelist of the inbound lane.
+		InboundLaneRelayerAdded(LaneId, AccountId),
+		/// Relayer has been removed from the whitelist of the inbound lane.
+		InboundLaneRelayerRemoved(LaneId, AccountId),
 		/// Phantom member, never used.
 		Dummy(PhantomData<(AccountId, I)>),
 	}
@@ -238,12 +292,18 @@ decl_module! {
 		/// Deposit one of this module's events by using the default implementation.
 		fn deposit_event() = default;
 
-		/// Ensure runtime invariants.
+		/// Prune confirmed messages using leftover block weight.
+		fn on_idle(_n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			prune_confirmed_messages::<T, I>(remaining_weight)
+		}
+
+		/// Migrate pallet storage and ensure runtime invariants.
 		fn on_runtime_upgrade() -> Weight {
+			let migration_weight = migrations::migrate::<T, I>();
 			let reads = T::MessageDeliveryAndDispatchPayment::initialize(
 				&Self::relayer_fund_account_id()
 			);
-			T::DbWeight::get().reads(reads as u64)
+			migration_weight.saturating_add(T::DbWeight::get().reads(reads as u64))
 		}
 
 		/// Change `PalletOwner`.
@@ -291,6 +351,57 @@ decl_module! {
 			Self::deposit_event(RawEvent::ParameterUpdated(parameter));
 		}
 
+		/// Change relaying policy of the inbound lane.
+		///
+		/// If the lane is permissioned, messages may only be delivered over this lane by relayers
+		/// from the lane whitelist.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(1, 2), DispatchClass::Operational)]
+		pub fn set_inbound_lane_relaying_policy(origin, lane_id: LaneId, policy: RelayingPolicy) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			InboundLaneRelayingPolicies::<I>::insert(lane_id, policy);
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Setting relaying policy of inbound lane {:?} to: {:?}",
+				lane_id,
+				policy,
+			);
+			Self::deposit_event(RawEvent::InboundLaneRelayingPolicyUpdated(lane_id, policy));
+		}
+
+		/// Add relayer to the whitelist of the inbound lane.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(1, 2), DispatchClass::Operational)]
+		pub fn add_inbound_lane_relayer(origin, lane_id: LaneId, relayer: T::AccountId) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			InboundLaneRelayers::<T, I>::insert(lane_id, &relayer, true);
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Added relayer {:?} to the whitelist of inbound lane {:?}",
+				relayer,
+				lane_id,
+			);
+			Self::deposit_event(RawEvent::InboundLaneRelayerAdded(lane_id, relayer));
+		}
+
+		/// Remove relayer from the whitelist of the inbound lane.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(1, 2), DispatchClass::Operational)]
+		pub fn remove_inbound_lane_relayer(origin, lane_id: LaneId, relayer: T::AccountId) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			InboundLaneRelayers::<T, I>::remove(lane_id, &relayer);
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Removed relayer {:?} from the whitelist of inbound lane {:?}",
+				relayer,
+				lane_id,
+			);
+			Self::deposit_event(RawEvent::InboundLaneRelayerRemoved(lane_id, relayer));
+		}
+
 		/// Send message over lane.
 		#[weight = T::WeightInfo::send_message_weight(payload)]
 		pub fn send_message(
@@ -299,79 +410,8 @@ decl_module! {
 			payload: T::OutboundPayload,
 			delivery_and_dispatch_fee: T::OutboundMessageFee,
 		) -> DispatchResult {
-			ensure_operational::<T, I>()?;
 			let submitter = origin.into().map_err(|_| BadOrigin)?;
-
-			// let's first check if message can be delivered to target chain
-			T::TargetHeaderChain::verify_message(&payload)
-				.map_err(|err| {
-					log::trace!(
-						target: "runtime::bridge-messages",
-						"Message to lane {:?} is rejected by target chain: {:?}",
-						lane_id,
-						err,
-					);
-
-					Error::<T, I>::MessageRejectedByChainVerifier
-				})?;
-
-			// now let's enforce any additional lane rules
-			let mut lane = outbound_lane::<T, I>(lane_id);
-			T::LaneMessageVerifier::verify_message(
-				&submitter,
-				&delivery_and_dispatch_fee,
-				&lane_id,
-				&lane.data(),
-				&payload,
-			).map_err(|err| {
-				log::trace!(
-					target: "runtime::bridge-messages",
-					"Message to lane {:?} is rejected by lane verifier: {:?}",
-					lane_id,
-					err,
-				);
-
-				Error::<T, I>::MessageRejectedByLaneVerifier
-			})?;
-
-			// let's withdraw delivery and dispatch fee from submitter
-			T::MessageDeliveryAndDispatchPayment::pay_delivery_and_dispatch_fee(
-				&submitter,
-				&delivery_and_dispatch_fee,
-				&Self::relayer_fund_account_id(),
-			).map_err(|err| {
-				log::trace!(
-					target: "runtime::bridge-messages",
-					"Message to lane {:?} is rejected because submitter {:?} is unable to pay fee {:?}: {:?}",
-					lane_id,
-					submitter,
-					delivery_and_dispatch_fee,
-					err,
-				);
-
-				Error::<T, I>::FailedToWithdrawMessageFee
-			})?;
-
-			// finally, save message in outbound storage and emit event
-			let encoded_payload = payload.encode();
-			let encoded_payload_len = encoded_payload.len();
-			let nonce = lane.send_message(MessageData {
-				payload: encoded_payload,
-				fee: delivery_and_dispatch_fee,
-			});
-			lane.prune_messages(T::MaxMessagesToPruneAtOnce::get());
-
-			log::trace!(
-				target: "runtime::bridge-messages",
-				"Accepted message {} to lane {:?}. Message size: {:?}",
-				nonce,
-				lane_id,
-				encoded_payload_len,
-			);
-
-			Self::deposit_event(RawEvent::MessageAccepted(lane_id, nonce));
-
-			Ok(())
+			send_message::<T, I>(submitter, lane_id, payload, delivery_and_dispatch_fee).map(drop)
 		}
 
 		/// Pay additional fee for the message.
@@ -440,7 +480,7 @@ decl_module! {
 			dispatch_weight: Weight,
 		) -> DispatchResult {
 			ensure_operational::<T, I>()?;
-			let _ = ensure_signed(origin)?;
+			let submitter = ensure_signed(origin)?;
 
 			// reject transactions that are declaring too many messages
 			ensure!(
@@ -448,6 +488,19 @@ decl_module! {
 				Error::<T, I>::TooManyMessagesInTheProof
 			);
 
+			// reject transactions that are declaring dispatch weight that can't fit into the block
+			let max_dispatch_weight = Self::inbound_delivery_limits().max_extrinsic_weight;
+			if dispatch_weight > max_dispatch_weight {
+				log::trace!(
+					target: "runtime::bridge-messages",
+					"Rejecting messages proof because of too large declared dispatch weight: declared={}, maximal={}",
+					dispatch_weight,
+					max_dispatch_weight,
+				);
+
+				return Err(Error::<T, I>::MessagesDispatchWeightIsTooLarge.into());
+			}
+
 			// verify messages proof && convert proof into messages
 			let messages = verify_and_decode_messages_proof::<
 				T::SourceHeaderChain,
@@ -485,12 +538,28 @@ decl_module! {
 				return Err(Error::<T, I>::InvalidMessagesDispatchWeight.into());
 			}
 
+			// verify that relayer is allowed to deliver messages over all (permissioned) lanes
+			for lane_id in messages.keys() {
+				if !is_allowed_inbound_lane_relayer::<T, I>(lane_id, &submitter) {
+					log::trace!(
+						target: "runtime::bridge-messages",
+						"Rejecting messages proof because {:?} is not allowed to deliver messages over lane {:?}",
+						submitter,
+						lane_id,
+					);
+
+					return Err(Error::<T, I>::RelayerIsNotAllowed.into());
+				}
+			}
+
 			// dispatch messages and (optionally) update lane(s) state(s)
 			let mut total_messages = 0;
 			let mut valid_messages = 0;
 			for (lane_id, lane_data) in messages {
 				let mut lane = inbound_lane::<T, I>(lane_id);
 
+				let proved_latest_generated_nonce =
+					lane_data.lane_state.as_ref().map(|lane_state| lane_state.latest_generated_nonce);
 				if let Some(lane_state) = lane_data.lane_state {
 					let updated_latest_confirmed_nonce = lane.receive_state_update(lane_state);
 					if let Some(updated_latest_confirmed_nonce) = updated_latest_confirmed_nonce {
@@ -503,14 +572,36 @@ decl_module! {
 					}
 				}
 
+				let mut lane_valid_messages = 0;
 				for message in lane_data.messages {
 					debug_assert_eq!(message.key.lane_id, lane_id);
 
 					total_messages += 1;
-					if lane.receive_message::<T::MessageDispatch>(relayer_id.clone(), message.key.nonce, message.data) {
-						valid_messages += 1;
-					}
+					let dispatch_result = match lane.receive_message::<T::MessageDispatch, T::AccountId>(
+						relayer_id.clone(),
+						&submitter,
+						message.key.nonce,
+						message.data,
+					) {
+						ReceivalResult::Dispatched(dispatch_result) => dispatch_result.dispatch_result,
+						ReceivalResult::Expired => false,
+						ReceivalResult::Rejected => continue,
+					};
+
+					lane_valid_messages += 1;
+					index_delivered_message::<T, I>(lane_id, message.key.nonce, relayer_id.clone(), dispatch_result);
+				}
+
+				if lane_valid_messages != 0 {
+					T::OnMessagesDelivered::on_messages_delivered(lane_id, &submitter, lane_valid_messages);
 				}
+				if let Some(proved_latest_generated_nonce) = proved_latest_generated_nonce {
+					T::OnMessagesDelivered::on_pending_messages_proved(
+						lane_id,
+						proved_latest_generated_nonce.saturating_sub(lane.last_delivered_nonce()),
+					);
+				}
+				valid_messages += lane_valid_messages;
 			}
 
 			log::trace!(
@@ -558,18 +649,34 @@ decl_module! {
 			let last_delivered_nonce = lane_data.last_delivered_nonce();
 			let received_range = lane.confirm_delivery(last_delivered_nonce);
 			if let Some(received_range) = received_range {
-				Self::deposit_event(RawEvent::MessagesDelivered(lane_id, received_range.0, received_range.1));
+				Self::deposit_event(RawEvent::MessagesDelivered(
+					lane_id,
+					received_range.begin(),
+					received_range.end(),
+				));
+
+				// let everyone know about messages that have been delivered too late
+				// this loop is bounded by `T::MaxUnconfirmedMessagesAtInboundLane` on the bridged chain
+				for expired_nonce in lane_data.expired_messages.iter().filter(|nonce| received_range.contains_message(**nonce)) {
+					Self::deposit_event(RawEvent::MessageExpired(lane_id, *expired_nonce));
+				}
+
+				// let everyone know about messages that have failed to pay dispatch fee at the bridged chain
+				// this loop is bounded by `T::MaxUnconfirmedMessagesAtInboundLane` on the bridged chain
+				for unpaid_nonce in lane_data.unpaid_messages.iter().filter(|nonce| received_range.contains_message(**nonce)) {
+					Self::deposit_event(RawEvent::MessageDispatchFeeNotPaid(lane_id, *unpaid_nonce));
+				}
 
 				// remember to reward relayers that have delivered messages
 				// this loop is bounded by `T::MaxUnrewardedRelayerEntriesAtInboundLane` on the bridged chain
 				for (nonce_low, nonce_high, relayer) in lane_data.relayers {
-					let nonce_begin = sp_std::cmp::max(nonce_low, received_range.0);
-					let nonce_end = sp_std::cmp::min(nonce_high, received_range.1);
+					let delivered_by_relayer = DeliveredMessages::new(nonce_low, nonce_high)
+						.and_then(|nonces| nonces.intersect(&received_range));
 
-					// loop won't proceed if current entry is ahead of received range (begin > end).
+					// loop won't proceed if current entry is ahead of received range (no intersection).
 					// this loop is bound by `T::MaxUnconfirmedMessagesAtInboundLane` on the bridged chain
 					let mut relayer_reward = relayers_rewards.entry(relayer).or_default();
-					for nonce in nonce_begin..nonce_end + 1 {
+					for nonce in delivered_by_relayer.iter().flat_map(DeliveredMessages::iter) {
 						let message_data = OutboundMessages::<T, I>::get(MessageKey {
 							lane_id,
 							nonce,
@@ -608,6 +715,11 @@ impl<T: Config<I>, I: Instance> Pallet<T, I> {
 		OutboundMessages::<T, I>::get(MessageKey { lane_id: lane, nonce }).map(|message_data| message_data.payload)
 	}
 
+	/// Get payload and fee of given outbound message.
+	pub fn outbound_message_data(lane: LaneId, nonce: MessageNonce) -> Option<MessageData<T::OutboundMessageFee>> {
+		OutboundMessages::<T, I>::get(MessageKey { lane_id: lane, nonce })
+	}
+
 	/// Get nonce of latest generated message at given outbound lane.
 	pub fn outbound_latest_generated_nonce(lane: LaneId) -> MessageNonce {
 		OutboundLanes::<I>::get(&lane).latest_generated_nonce
@@ -638,6 +750,24 @@ impl<T: Config<I>, I: Instance> Pallet<T, I> {
 		}
 	}
 
+	/// Get data of given inbound lane.
+	pub fn inbound_lane_data(lane: LaneId) -> InboundLaneData<T::InboundRelayer> {
+		InboundLanes::<T, I>::get(&lane)
+	}
+
+	/// Get limits of messages delivery transactions at this chain.
+	pub fn inbound_delivery_limits() -> MessagesDeliveryLimits {
+		MessagesDeliveryLimits {
+			max_extrinsic_weight: T::BlockWeights::get()
+				.get(DispatchClass::Normal)
+				.max_extrinsic
+				.unwrap_or(Weight::MAX),
+			max_extrinsic_size: *T::BlockLength::get().max.get(DispatchClass::Normal),
+			max_unrewarded_relayer_entries_at_inbound_lane: T::MaxUnrewardedRelayerEntriesAtInboundLane::get(),
+			max_unconfirmed_messages_at_inbound_lane: T::MaxUnconfirmedMessagesAtInboundLane::get(),
+		}
+	}
+
 	/// AccountId of the shared relayer fund account.
 	///
 	/// This account is passed to `MessageDeliveryAndDispatchPayment` trait, and depending
@@ -650,6 +780,21 @@ impl<T: Config<I>, I: Instance> Pallet<T, I> {
 	}
 }
 
+impl<T: Config<I>, I: Instance>
+	bp_messages::source_chain::MessagesBridge<T::AccountId, T::OutboundMessageFee, T::OutboundPayload> for Pallet<T, I>
+{
+	type Error = sp_runtime::DispatchError;
+
+	fn send_message(
+		sender: bp_messages::source_chain::Sender<T::AccountId>,
+		lane: LaneId,
+		message: T::OutboundPayload,
+		delivery_and_dispatch_fee: T::OutboundMessageFee,
+	) -> Result<MessageNonce, Self::Error> {
+		send_message::<T, I>(sender, lane, message, delivery_and_dispatch_fee)
+	}
+}
+
 /// Getting storage keys for messages and lanes states. These keys are normally used when building
 /// messages and lanes states proofs.
 ///
@@ -665,7 +810,7 @@ impl<T: Config<I>, I: Instance> Pallet<T, I> {
 /// trying to avoid here) - by using strings like "Instance2", "OutboundMessages", etc.
 pub mod storage_keys {
 	use super::*;
-	use frame_support::storage::generator::StorageMap;
+	use frame_support::storage::generator::{StorageMap, StorageValue};
 	use sp_core::storage::StorageKey;
 
 	/// Storage key of the outbound message in the runtime storage.
@@ -680,10 +825,245 @@ pub mod storage_keys {
 		StorageKey(OutboundLanes::<I>::storage_map_final_key(*lane))
 	}
 
+	/// Prefix of all outbound lanes states in the runtime storage.
+	///
+	/// Outbound lane state key is the prefix, followed by the `blake2_128_concat` hash of the lane id.
+	pub fn outbound_lanes_prefix<I: Instance>() -> StorageKey {
+		StorageKey(OutboundLanes::<I>::prefix_hash())
+	}
+
 	/// Storage key of the inbound message lane state in the runtime storage.
 	pub fn inbound_lane_data_key<T: Config<I>, I: Instance>(lane: &LaneId) -> StorageKey {
 		StorageKey(InboundLanes::<T, I>::storage_map_final_key(*lane))
 	}
+
+	/// Storage key of the flag that is set when all pallet operations are halted.
+	pub fn is_halted_key<I: Instance>() -> StorageKey {
+		StorageKey(IsHalted::<I>::storage_value_final_key().to_vec())
+	}
+}
+
+/// Keys of records in the offchain indexing storage.
+pub mod offchain_keys {
+	use super::*;
+	use frame_support::storage::generator::StorageValue;
+
+	/// Prefix of all delivered message records.
+	pub const DELIVERED_MESSAGE_PREFIX: &[u8] = b"bridge-messages/delivered/";
+
+	/// Key of the `DeliveredMessageRecord` in the offchain indexing storage.
+	///
+	/// The key is `DELIVERED_MESSAGE_PREFIX ++ twox128(pallet instance prefix) ++ lane ++ nonce`, where
+	/// nonce is encoded as little-endian 64-bit integer.
+	pub fn delivered_message_key<I: Instance>(lane: &LaneId, nonce: MessageNonce) -> Vec<u8> {
+		let instance_prefix = &IsHalted::<I>::storage_value_final_key()[..16];
+		let mut key = Vec::with_capacity(DELIVERED_MESSAGE_PREFIX.len() + instance_prefix.len() + lane.0.len() + 8);
+		key.extend_from_slice(DELIVERED_MESSAGE_PREFIX);
+		key.extend_from_slice(instance_prefix);
+		key.extend_from_slice(&lane.0);
+		key.extend_from_slice(&nonce.to_le_bytes());
+		key
+	}
+}
+
+/// Write delivered message record to the offchain indexing storage.
+fn index_delivered_message<T: Config<I>, I: Instance>(
+	lane_id: LaneId,
+	nonce: MessageNonce,
+	relayer: T::InboundRelayer,
+	dispatch_result: bool,
+) {
+	let record = DeliveredMessageRecord {
+		lane_id,
+		nonce,
+		relayer,
+		block_number: frame_system::Pallet::<T>::block_number(),
+		dispatch_result,
+	};
+	sp_io::offchain_index::set(
+		&offchain_keys::delivered_message_key::<I>(&lane_id, nonce),
+		&record.encode(),
+	);
+}
+
+/// Send message over lane.
+///
+/// Returns nonce of the message, assigned by the outbound lane.
+fn send_message<T: Config<I>, I: Instance>(
+	submitter: RawOrigin<T::AccountId>,
+	lane_id: LaneId,
+	payload: T::OutboundPayload,
+	delivery_and_dispatch_fee: T::OutboundMessageFee,
+) -> Result<MessageNonce, sp_runtime::DispatchError> {
+	ensure_operational::<T, I>()?;
+
+	// reject messages that will never be delivered to the target chain
+	let payload_size = payload.size_hint();
+	let maximal_payload_size = T::MaximalOutboundPayloadSize::get();
+	if payload_size > maximal_payload_size {
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Message to lane {:?} is too large: {} > {}",
+			lane_id,
+			payload_size,
+			maximal_payload_size,
+		);
+
+		return Err(Error::<T, I>::MessageIsTooLarge.into());
+	}
+	let dispatch_weight = payload.declared_weight();
+	let maximal_dispatch_weight = T::MaximalOutboundDispatchWeight::get();
+	if dispatch_weight > maximal_dispatch_weight {
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Message to lane {:?} has too large dispatch weight: {} > {}",
+			lane_id,
+			dispatch_weight,
+			maximal_dispatch_weight,
+		);
+
+		return Err(Error::<T, I>::MessageDispatchWeightIsTooLarge.into());
+	}
+
+	// let's first check if message can be delivered to target chain
+	T::TargetHeaderChain::verify_message(&payload).map_err(|err| {
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Message to lane {:?} is rejected by target chain: {:?}",
+			lane_id,
+			err,
+		);
+
+		Error::<T, I>::MessageRejectedByChainVerifier
+	})?;
+
+	// now let's enforce any additional lane rules
+	let mut lane = outbound_lane::<T, I>(lane_id);
+	T::LaneMessageVerifier::verify_message(&submitter, &delivery_and_dispatch_fee, &lane_id, &lane.data(), &payload)
+		.map_err(|err| {
+			log::trace!(
+				target: "runtime::bridge-messages",
+				"Message to lane {:?} is rejected by lane verifier: {:?}",
+				lane_id,
+				err,
+			);
+
+			Error::<T, I>::MessageRejectedByLaneVerifier
+		})?;
+
+	// let's withdraw delivery and dispatch fee from submitter
+	T::MessageDeliveryAndDispatchPayment::pay_delivery_and_dispatch_fee(
+		&submitter,
+		&delivery_and_dispatch_fee,
+		&Pallet::<T, I>::relayer_fund_account_id(),
+	)
+	.map_err(|err| {
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Message to lane {:?} is rejected because submitter {:?} is unable to pay fee {:?}: {:?}",
+			lane_id,
+			submitter,
+			delivery_and_dispatch_fee,
+			err,
+		);
+
+		Error::<T, I>::FailedToWithdrawMessageFee
+	})?;
+
+	// finally, save message in outbound storage and emit event
+	let encoded_payload = payload.encode();
+	let encoded_payload_len = encoded_payload.len();
+	let nonce = lane.send_message(MessageData {
+		payload: encoded_payload,
+		fee: delivery_and_dispatch_fee,
+	});
+	lane.prune_messages(T::MaxMessagesToPruneAtOnce::get());
+
+	log::trace!(
+		target: "runtime::bridge-messages",
+		"Accepted message {} to lane {:?}. Message size: {:?}",
+		nonce,
+		lane_id,
+		encoded_payload_len,
+	);
+
+	Pallet::<T, I>::deposit_event(RawEvent::MessageAccepted(lane_id, nonce));
+
+	Ok(nonce)
+}
+
+/// Prune messages that have been confirmed by the bridged chain at all outbound lanes, spending
+/// no more than given weight.
+///
+/// Normally, confirmed messages are pruned when new messages are sent over the same lane. So this
+/// only matters for lanes that are no longer used for sending messages. Every call starts from the
+/// lane that follows the `OutboundLanesPruningCursor`, so all lanes are eventually visited even if
+/// there's only weight to visit a single lane per block. Returns weight that has been actually spent.
+fn prune_confirmed_messages<T: Config<I>, I: Instance>(remaining_weight: Weight) -> Weight {
+	let db_weight = T::DbWeight::get();
+	// reads: `OutboundLanesPruningCursor`
+	// writes: `OutboundLanesPruningCursor`
+	let cursor_weight = db_weight.reads_writes(1, 1);
+	// reads: `OutboundLanes` (iteration)
+	let lane_weight = db_weight.reads(1);
+	// reads: `OutboundLanes`
+	// writes: `OutboundLanes`
+	let prune_lane_messages_weight = db_weight.reads_writes(1, 1);
+	// writes: `OutboundMessages`
+	let prune_message_weight = db_weight.writes(1);
+
+	if cursor_weight.saturating_add(lane_weight) > remaining_weight {
+		return 0;
+	}
+
+	let mut used_weight = cursor_weight;
+	let mut cursor = OutboundLanesPruningCursor::<I>::get();
+	let mut lanes = match cursor {
+		Some(ref last_visited_lane) => {
+			OutboundLanes::<I>::iter_from(OutboundLanes::<I>::hashed_key_for(last_visited_lane))
+		}
+		None => OutboundLanes::<I>::iter(),
+	};
+	loop {
+		if used_weight.saturating_add(lane_weight) > remaining_weight {
+			break;
+		}
+		let (lane_id, lane_data) = match lanes.next() {
+			Some(lane) => lane,
+			None => {
+				// all lanes have been visited => start from the first lane next time
+				cursor = None;
+				break;
+			}
+		};
+		used_weight = used_weight.saturating_add(lane_weight);
+
+		if lane_data.oldest_unpruned_nonce <= lane_data.latest_received_nonce {
+			let max_messages_to_prune = remaining_weight
+				.saturating_sub(used_weight)
+				.saturating_sub(prune_lane_messages_weight)
+				.checked_div(prune_message_weight)
+				.unwrap_or(MessageNonce::MAX);
+			if max_messages_to_prune == 0 {
+				break;
+			}
+
+			let pruned_messages = outbound_lane::<T, I>(lane_id).prune_messages(max_messages_to_prune);
+			used_weight = used_weight
+				.saturating_add(prune_lane_messages_weight)
+				.saturating_add(prune_message_weight.saturating_mul(pruned_messages));
+
+			// the lane still has confirmed messages => continue from this lane next time
+			if lane_data.oldest_unpruned_nonce.saturating_add(pruned_messages) <= lane_data.latest_received_nonce {
+				break;
+			}
+		}
+
+		cursor = Some(lane_id);
+	}
+	OutboundLanesPruningCursor::<I>::set(cursor);
+
+	used_weight
 }
 
 /// Ensure that the origin is either root, or `PalletOwner`.
@@ -695,6 +1075,14 @@ fn ensure_owner_or_root<T: Config<I>, I: Instance>(origin: T::Origin) -> Result<
 	}
 }
 
+/// Returns true if given relayer is allowed to deliver messages over the inbound lane.
+fn is_allowed_inbound_lane_relayer<T: Config<I>, I: Instance>(lane_id: &LaneId, relayer: &T::AccountId) -> bool {
+	match InboundLaneRelayingPolicies::<I>::get(lane_id) {
+		RelayingPolicy::Permissionless => true,
+		RelayingPolicy::Permissioned => InboundLaneRelayers::<T, I>::get(lane_id, relayer),
+	}
+}
+
 /// Ensure that the pallet is in operational mode (not halted).
 fn ensure_operational<T: Config<I>, I: Instance>() -> Result<(), Error<T, I>> {
 	if IsHalted::<I>::get() {
@@ -847,10 +1235,12 @@ fn verify_and_decode_messages_proof<Chain: SourceHeaderChain<Fee>, Fee, Dispatch
 mod tests {
 	use super::*;
 	use crate::mock::{
-		message, run_test, Event as TestEvent, Origin, TestMessageDeliveryAndDispatchPayment,
-		TestMessagesDeliveryProof, TestMessagesParameter, TestMessagesProof, TestPayload, TestRuntime,
-		TokenConversionRate, PAYLOAD_REJECTED_BY_TARGET_CHAIN, REGULAR_PAYLOAD, TEST_LANE_ID, TEST_RELAYER_A,
-		TEST_RELAYER_B,
+		message, message_data, run_test, Event as TestEvent, MaxUnconfirmedMessagesAtInboundLane,
+		MaxUnrewardedRelayerEntriesAtInboundLane, MaximalOutboundPayloadSize, Origin,
+		TestMessageDeliveryAndDispatchPayment, TestMessageDispatch, TestMessagesDeliveryProof, TestMessagesParameter,
+		TestMessagesProof, TestOnMessagesDelivered, TestPayload, TestRuntime, TokenConversionRate, EXPIRED_PAYLOAD,
+		MAXIMAL_OUTBOUND_DISPATCH_WEIGHT, PAYLOAD_REJECTED_BY_TARGET_CHAIN, REGULAR_PAYLOAD, TEST_LANE_ID,
+		TEST_RELAYER_A, TEST_RELAYER_B, UNPAID_PAYLOAD,
 	};
 	use bp_messages::UnrewardedRelayersState;
 	use frame_support::{assert_noop, assert_ok};
@@ -913,6 +1303,102 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn receive_messages_delivery_proof_reports_expired_messages() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
+
+			get_ready_for_events();
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![(1, 2, TEST_RELAYER_A)].into_iter().collect(),
+						expired_messages: vec![2].into_iter().collect(),
+						..Default::default()
+					}
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 1,
+					total_messages: 2,
+					..Default::default()
+				},
+			));
+
+			assert_eq!(
+				System::<TestRuntime>::events()
+					.into_iter()
+					.map(|event_record| event_record.event)
+					.collect::<Vec<_>>(),
+				vec![
+					TestEvent::pallet_bridge_messages(RawEvent::MessagesDelivered(TEST_LANE_ID, 1, 2)),
+					TestEvent::pallet_bridge_messages(RawEvent::MessageExpired(TEST_LANE_ID, 2)),
+				],
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_reports_unpaid_messages() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
+
+			get_ready_for_events();
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![(1, 2, TEST_RELAYER_A)].into_iter().collect(),
+						unpaid_messages: vec![1].into_iter().collect(),
+						..Default::default()
+					}
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 1,
+					total_messages: 2,
+					..Default::default()
+				},
+			));
+
+			assert_eq!(
+				System::<TestRuntime>::events()
+					.into_iter()
+					.map(|event_record| event_record.event)
+					.collect::<Vec<_>>(),
+				vec![
+					TestEvent::pallet_bridge_messages(RawEvent::MessagesDelivered(TEST_LANE_ID, 1, 2)),
+					TestEvent::pallet_bridge_messages(RawEvent::MessageDispatchFeeNotPaid(TEST_LANE_ID, 1)),
+				],
+			);
+		});
+	}
+
 	#[test]
 	fn pallet_owner_may_change_owner() {
 		run_test(|| {
@@ -1120,14 +1606,58 @@ mod tests {
 	}
 
 	#[test]
-	fn chain_verifier_rejects_invalid_message_in_send_message() {
+	fn send_message_works_through_messages_bridge() {
 		run_test(|| {
-			// messages with this payload are rejected by target chain verifier
-			assert_noop!(
-				Pallet::<TestRuntime>::send_message(
-					Origin::signed(1),
-					TEST_LANE_ID,
-					PAYLOAD_REJECTED_BY_TARGET_CHAIN,
+			use bp_messages::source_chain::MessagesBridge;
+
+			let nonce = <Pallet<TestRuntime> as MessagesBridge<_, _, _>>::send_message(
+				RawOrigin::Signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			);
+			assert_eq!(nonce, Ok(1));
+			assert_eq!(Pallet::<TestRuntime>::outbound_latest_generated_nonce(TEST_LANE_ID), 1);
+			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_paid(1, REGULAR_PAYLOAD.1));
+		});
+	}
+
+	#[test]
+	fn send_message_rejects_too_large_message() {
+		run_test(|| {
+			MaximalOutboundPayloadSize::set(&(REGULAR_PAYLOAD.size_hint() - 1));
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::MessageIsTooLarge,
+			);
+		});
+	}
+
+	#[test]
+	fn send_message_rejects_message_with_too_large_dispatch_weight() {
+		run_test(|| {
+			let payload = TestPayload(0, MAXIMAL_OUTBOUND_DISPATCH_WEIGHT + 1);
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(Origin::signed(1), TEST_LANE_ID, payload.clone(), payload.1,),
+				Error::<TestRuntime, DefaultInstance>::MessageDispatchWeightIsTooLarge,
+			);
+		});
+	}
+
+	#[test]
+	fn chain_verifier_rejects_invalid_message_in_send_message() {
+		run_test(|| {
+			// messages with this payload are rejected by target chain verifier
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					PAYLOAD_REJECTED_BY_TARGET_CHAIN,
 					PAYLOAD_REJECTED_BY_TARGET_CHAIN.1
 				),
 				Error::<TestRuntime, DefaultInstance>::MessageRejectedByChainVerifier,
@@ -1174,6 +1704,220 @@ mod tests {
 			));
 
 			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 1);
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_lane_data(TEST_LANE_ID),
+				InboundLaneData {
+					relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
+					last_confirmed_nonce: 0,
+					expired_messages: Default::default(),
+					unpaid_messages: Default::default(),
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn inbound_lane_relaying_policy_may_only_be_changed_by_owner_or_root() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_ok!(Pallet::<TestRuntime>::set_inbound_lane_relaying_policy(
+				Origin::root(),
+				TEST_LANE_ID,
+				RelayingPolicy::Permissioned,
+			));
+			assert_ok!(Pallet::<TestRuntime>::add_inbound_lane_relayer(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				1,
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::set_inbound_lane_relaying_policy(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					RelayingPolicy::Permissionless,
+				),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::remove_inbound_lane_relayer(Origin::signed(1), TEST_LANE_ID, 1),
+				DispatchError::BadOrigin,
+			);
+
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_lane_relaying_policy(TEST_LANE_ID),
+				RelayingPolicy::Permissioned,
+			);
+			assert!(Pallet::<TestRuntime>::is_inbound_lane_relayer(TEST_LANE_ID, 1));
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_not_allowed_relayer_at_permissioned_lane() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_inbound_lane_relaying_policy(
+				Origin::root(),
+				TEST_LANE_ID,
+				RelayingPolicy::Permissioned,
+			));
+			assert_ok!(Pallet::<TestRuntime>::add_inbound_lane_relayer(
+				Origin::root(),
+				TEST_LANE_ID,
+				1,
+			));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_proof(
+					Origin::signed(2),
+					TEST_RELAYER_A,
+					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::RelayerIsNotAllowed,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 1);
+
+			// once relayer is removed from the whitelist, it can't deliver messages anymore
+			assert_ok!(Pallet::<TestRuntime>::remove_inbound_lane_relayer(
+				Origin::root(),
+				TEST_LANE_ID,
+				1,
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(2, REGULAR_PAYLOAD)]).into(),
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::RelayerIsNotAllowed,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_calls_delivery_hook_for_new_messages_only() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into(),
+				2,
+				REGULAR_PAYLOAD.1 * 2,
+			));
+			assert_eq!(TestOnMessagesDelivered::delivered_messages(TEST_LANE_ID, 1), 2);
+
+			// message 2 has already been delivered, so only message 3 is new
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(2),
+				TEST_RELAYER_B,
+				Ok(vec![message(2, REGULAR_PAYLOAD), message(3, REGULAR_PAYLOAD)]).into(),
+				2,
+				REGULAR_PAYLOAD.1 * 2,
+			));
+			assert_eq!(TestOnMessagesDelivered::delivered_messages(TEST_LANE_ID, 2), 1);
+
+			// no new messages => hook is not called
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(3),
+				TEST_RELAYER_B,
+				Ok(vec![message(3, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(TestOnMessagesDelivered::delivered_messages(TEST_LANE_ID, 3), 0);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_does_not_dispatch_expired_messages() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD), message(2, EXPIRED_PAYLOAD)]).into(),
+				2,
+				REGULAR_PAYLOAD.1 + EXPIRED_PAYLOAD.1,
+			));
+
+			assert!(TestMessageDispatch::is_dispatched(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 1
+			}));
+			assert!(!TestMessageDispatch::is_dispatched(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 2
+			}));
+
+			let inbound_lane_data = InboundLanes::<TestRuntime>::get(TEST_LANE_ID);
+			assert_eq!(inbound_lane_data.last_delivered_nonce(), 2);
+			assert_eq!(inbound_lane_data.expired_messages, vec![2]);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_does_not_dispatch_unpaid_messages() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD), message(2, UNPAID_PAYLOAD)]).into(),
+				2,
+				REGULAR_PAYLOAD.1 + UNPAID_PAYLOAD.1,
+			));
+
+			assert!(TestMessageDispatch::is_dispatched(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 1
+			}));
+			assert!(!TestMessageDispatch::is_dispatched(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 2
+			}));
+
+			let inbound_lane_data = InboundLanes::<TestRuntime>::get(TEST_LANE_ID);
+			assert_eq!(inbound_lane_data.last_delivered_nonce(), 2);
+			assert_eq!(inbound_lane_data.unpaid_messages, vec![2]);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_reports_pending_messages_if_lane_state_is_proved() {
+		run_test(|| {
+			// no lane state => hook is not called
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(TestOnMessagesDelivered::pending_messages(TEST_LANE_ID), None);
+
+			// bridged chain has generated 5 messages and 2 of them are delivered
+			let mut message_proof: TestMessagesProof = Ok(vec![message(2, REGULAR_PAYLOAD)]).into();
+			message_proof.result.as_mut().unwrap()[0].1.lane_state = Some(OutboundLaneData {
+				latest_generated_nonce: 5,
+				..Default::default()
+			});
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				message_proof,
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(TestOnMessagesDelivered::pending_messages(TEST_LANE_ID), Some(3));
 		});
 	}
 
@@ -1188,6 +1932,8 @@ mod tests {
 					relayers: vec![(9, 9, TEST_RELAYER_A), (10, 10, TEST_RELAYER_B)]
 						.into_iter()
 						.collect(),
+					expired_messages: Default::default(),
+					unpaid_messages: Default::default(),
 				},
 			);
 			assert_eq!(
@@ -1221,6 +1967,8 @@ mod tests {
 					relayers: vec![(10, 10, TEST_RELAYER_B), (11, 11, TEST_RELAYER_A)]
 						.into_iter()
 						.collect(),
+					expired_messages: Default::default(),
+					unpaid_messages: Default::default(),
 				},
 			);
 			assert_eq!(
@@ -1250,6 +1998,36 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn receive_messages_proof_accepts_over_declared_dispatch_weight() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1 + 1,
+			));
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_too_large_dispatch_weight() {
+		run_test(|| {
+			let max_dispatch_weight = Pallet::<TestRuntime>::inbound_delivery_limits().max_extrinsic_weight;
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+					1,
+					max_dispatch_weight + 1,
+				),
+				Error::<TestRuntime, DefaultInstance>::MessagesDispatchWeightIsTooLarge,
+			);
+		});
+	}
+
 	#[test]
 	fn receive_messages_proof_rejects_invalid_proof() {
 		run_test(|| {
@@ -1479,7 +2257,7 @@ mod tests {
 	fn storage_message_key_computed_properly() {
 		// If this test fails, then something has been changed in module storage that is breaking all
 		// previously crafted messages proofs.
-		let storage_key = storage_keys::message_key::<TestRuntime, DefaultInstance>(&*b"test", 42).0;
+		let storage_key = storage_keys::message_key::<TestRuntime, DefaultInstance>(&LaneId(*b"test"), 42).0;
 		assert_eq!(
 			storage_key,
 			hex!("dd16c784ebd3390a9bc0357c7511ed018a395e6242c6813b196ca31ed0547ea79446af0e09063bd4a7874aef8a997cec746573742a00000000000000").to_vec(),
@@ -1492,7 +2270,7 @@ mod tests {
 	fn outbound_lane_data_key_computed_properly() {
 		// If this test fails, then something has been changed in module storage that is breaking all
 		// previously crafted outbound lane state proofs.
-		let storage_key = storage_keys::outbound_lane_data_key::<DefaultInstance>(&*b"test").0;
+		let storage_key = storage_keys::outbound_lane_data_key::<DefaultInstance>(&LaneId(*b"test")).0;
 		assert_eq!(
 			storage_key,
 			hex!("dd16c784ebd3390a9bc0357c7511ed0196c246acb9b55077390e3ca723a0ca1f44a8995dd50b6657a037a7839304535b74657374").to_vec(),
@@ -1501,11 +2279,57 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn outbound_lanes_prefix_computed_properly() {
+		// If this test fails, then something has been changed in module storage that is breaking
+		// relays, which are discovering lanes from the runtime storage.
+		let storage_key = storage_keys::outbound_lanes_prefix::<DefaultInstance>().0;
+		assert_eq!(
+			storage_key,
+			hex!("dd16c784ebd3390a9bc0357c7511ed0196c246acb9b55077390e3ca723a0ca1f").to_vec(),
+			"Unexpected storage key: {}",
+			hex::encode(&storage_key),
+		);
+		assert!(
+			storage_keys::outbound_lane_data_key::<DefaultInstance>(&LaneId(*b"test"))
+				.0
+				.starts_with(&storage_key)
+		);
+	}
+
+	#[test]
+	fn inbound_delivery_limits_are_read_from_runtime_configuration() {
+		run_test(|| {
+			let limits = Pallet::<TestRuntime>::inbound_delivery_limits();
+			assert_eq!(
+				limits.max_extrinsic_weight,
+				<TestRuntime as frame_system::Config>::BlockWeights::get()
+					.get(DispatchClass::Normal)
+					.max_extrinsic
+					.unwrap_or(Weight::MAX),
+			);
+			assert_eq!(
+				limits.max_extrinsic_size,
+				*<TestRuntime as frame_system::Config>::BlockLength::get()
+					.max
+					.get(DispatchClass::Normal),
+			);
+			assert_eq!(
+				limits.max_unrewarded_relayer_entries_at_inbound_lane,
+				MaxUnrewardedRelayerEntriesAtInboundLane::get(),
+			);
+			assert_eq!(
+				limits.max_unconfirmed_messages_at_inbound_lane,
+				MaxUnconfirmedMessagesAtInboundLane::get(),
+			);
+		});
+	}
+
 	#[test]
 	fn inbound_lane_data_key_computed_properly() {
 		// If this test fails, then something has been changed in module storage that is breaking all
 		// previously crafted inbound lane state proofs.
-		let storage_key = storage_keys::inbound_lane_data_key::<TestRuntime, DefaultInstance>(&*b"test").0;
+		let storage_key = storage_keys::inbound_lane_data_key::<TestRuntime, DefaultInstance>(&LaneId(*b"test")).0;
 		assert_eq!(
 			storage_key,
 			hex!("dd16c784ebd3390a9bc0357c7511ed01e5f83cf83f2127eb47afdc35d6e43fab44a8995dd50b6657a037a7839304535b74657374").to_vec(),
@@ -1514,6 +2338,38 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn delivered_message_offchain_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage that is breaking all
+		// indexers of delivered messages.
+		let key = offchain_keys::delivered_message_key::<DefaultInstance>(&LaneId(*b"test"), 42);
+		assert_eq!(
+			key,
+			[
+				&b"bridge-messages/delivered/"[..],
+				&hex!("dd16c784ebd3390a9bc0357c7511ed01")[..],
+				&b"test"[..],
+				&hex!("2a00000000000000")[..],
+			]
+			.concat(),
+			"Unexpected offchain key: {}",
+			hex::encode(&key),
+		);
+	}
+
+	#[test]
+	fn is_halted_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage that is breaking
+		// relays, which are watching pallet operating mode.
+		let storage_key = storage_keys::is_halted_key::<DefaultInstance>().0;
+		assert_eq!(
+			storage_key,
+			hex!("dd16c784ebd3390a9bc0357c7511ed019611a984bbd04e2fd39f97bbc006115f").to_vec(),
+			"Unexpected storage key: {}",
+			hex::encode(&storage_key),
+		);
+	}
+
 	#[test]
 	fn actual_dispatch_weight_does_not_overlow() {
 		run_test(|| {
@@ -1586,4 +2442,96 @@ mod tests {
 			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_paid(1, 100));
 		});
 	}
+
+	fn send_and_confirm_messages(lane_id: LaneId, messages: MessageNonce, confirmed: MessageNonce) {
+		let mut lane = outbound_lane::<TestRuntime, DefaultInstance>(lane_id);
+		for _ in 0..messages {
+			lane.send_message(message_data(REGULAR_PAYLOAD));
+		}
+		assert!(lane.confirm_delivery(confirmed).is_some());
+	}
+
+	#[test]
+	fn confirmed_messages_are_pruned_on_idle() {
+		run_test(|| {
+			send_and_confirm_messages(TEST_LANE_ID, 3, 2);
+
+			// (1 read + 1 write) to update cursor + 1 read to iterate lane + (1 read + 1 write) to
+			// update lane + 2 * 1 write to prune messages
+			assert_eq!(
+				prune_confirmed_messages::<TestRuntime, DefaultInstance>(Weight::max_value()),
+				3 + 1 + 3 + 2 * 2
+			);
+			assert_eq!(
+				OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID).oldest_unpruned_nonce,
+				3
+			);
+			assert!(Pallet::<TestRuntime>::outbound_message_payload(TEST_LANE_ID, 2).is_none());
+			assert!(Pallet::<TestRuntime>::outbound_message_payload(TEST_LANE_ID, 3).is_some());
+
+			// nothing to prune => only cursor is updated and lane is read
+			assert_eq!(
+				prune_confirmed_messages::<TestRuntime, DefaultInstance>(Weight::max_value()),
+				3 + 1
+			);
+		});
+	}
+
+	#[test]
+	fn confirmed_messages_pruning_respects_remaining_weight() {
+		run_test(|| {
+			send_and_confirm_messages(TEST_LANE_ID, 3, 2);
+
+			// not enough weight to read lane
+			assert_eq!(prune_confirmed_messages::<TestRuntime, DefaultInstance>(3), 0);
+			// enough weight to prune single message only
+			assert_eq!(prune_confirmed_messages::<TestRuntime, DefaultInstance>(9), 9);
+			assert_eq!(
+				OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID).oldest_unpruned_nonce,
+				2
+			);
+			// the rest is pruned when there's enough weight
+			assert_eq!(
+				prune_confirmed_messages::<TestRuntime, DefaultInstance>(Weight::max_value()),
+				9
+			);
+			assert_eq!(
+				OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID).oldest_unpruned_nonce,
+				3
+			);
+		});
+	}
+
+	#[test]
+	fn confirmed_messages_pruning_visits_all_lanes_with_budget_of_single_lane_per_block() {
+		run_test(|| {
+			let lanes = [TEST_LANE_ID, LaneId([0, 0, 0, 2]), LaneId([0, 0, 0, 3])];
+			for lane_id in &lanes {
+				send_and_confirm_messages(*lane_id, 3, 2);
+			}
+			let pruned_lanes = || {
+				lanes
+					.iter()
+					.filter(|lane_id| OutboundLanes::<DefaultInstance>::get(lane_id).oldest_unpruned_nonce == 3)
+					.count()
+			};
+
+			// every block has weight to prune messages of single lane only
+			let single_lane_weight = 3 + 1 + 3 + 2 * 2;
+			for block in 1..=lanes.len() {
+				assert_eq!(
+					prune_confirmed_messages::<TestRuntime, DefaultInstance>(single_lane_weight),
+					single_lane_weight,
+				);
+				assert_eq!(pruned_lanes(), block);
+			}
+
+			// all lanes have been visited => cursor is reset
+			assert_eq!(
+				prune_confirmed_messages::<TestRuntime, DefaultInstance>(single_lane_weight),
+				3
+			);
+			assert_eq!(OutboundLanesPruningCursor::<DefaultInstance>::get(), None);
+		});
+	}
 }
@@ -0,0 +1,309 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Migrations of the pallet storage.
+//!
+//! Every time the storage layout of the pallet is changed, the `STORAGE_VERSION` is bumped and
+//! the migration from the previous version is added here. Migrations are applied one by one in
+//! the `on_runtime_upgrade` hook, starting from the version that is stored in the runtime storage.
+
+use crate::{Config, Instance, PalletStorageVersion};
+
+use bp_runtime::StorageVersion;
+use frame_support::{traits::Get, weights::Weight};
+
+/// Current version of the pallet storage layout.
+///
+/// Versions history:
+///
+/// - `0`: storage version is not stored in the runtime storage;
+/// - `1`: `InboundLaneData::expired_messages` field has been added;
+/// - `2`: `InboundLaneData::unpaid_messages` field has been added.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion(2);
+
+/// Migrate pallet storage to the current version.
+///
+/// Returns the weight of the migration.
+pub fn migrate<T: Config<I>, I: Instance>() -> Weight {
+	let initial_version = PalletStorageVersion::<I>::get();
+	let mut weight = T::DbWeight::get().reads(1);
+
+	if initial_version > STORAGE_VERSION {
+		log::error!(
+			target: "runtime::bridge-messages",
+			"Storage version {:?} is newer than the version {:?}, supported by the pallet. Migrations are skipped",
+			initial_version,
+			STORAGE_VERSION,
+		);
+		return weight;
+	}
+	if initial_version == STORAGE_VERSION {
+		return weight;
+	}
+
+	let mut version = initial_version;
+	if version == StorageVersion(0) {
+		// inbound lanes data is translated straight to the version `2` layout here
+		weight = weight.saturating_add(v1::migrate::<T, I>());
+		version = StorageVersion(2);
+	}
+	if version == StorageVersion(1) {
+		weight = weight.saturating_add(v2::migrate::<T, I>());
+		version = StorageVersion(2);
+	}
+
+	PalletStorageVersion::<I>::put(version);
+	log::info!(
+		target: "runtime::bridge-messages",
+		"Migrated pallet storage from version {:?} to version {:?}",
+		initial_version,
+		version,
+	);
+
+	weight.saturating_add(T::DbWeight::get().writes(1))
+}
+
+/// Migration to the storage version `1`.
+pub mod v1 {
+	use super::*;
+	use crate::InboundLanes;
+
+	use bp_messages::{InboundLaneData, MessageNonce};
+	use codec::{Decode, Encode};
+	use frame_support::storage::IterableStorageMap;
+	use sp_std::collections::vec_deque::VecDeque;
+
+	/// Inbound lane data, as it has been stored in the storage version `0`.
+	#[derive(Encode, Decode)]
+	pub struct InboundLaneDataV0<RelayerId> {
+		/// Identifiers of relayers and messages that they have delivered to this lane.
+		pub relayers: VecDeque<(MessageNonce, MessageNonce, RelayerId)>,
+		/// Nonce of the last message that has been delivered and confirmed.
+		pub last_confirmed_nonce: MessageNonce,
+	}
+
+	/// Migrate pallet storage from version `0` to version `1`.
+	///
+	/// Adds empty `expired_messages` queue to the data of every inbound lane. Since the data is
+	/// written using the current layout, the empty `unpaid_messages` queue (version `2`) is also
+	/// added here.
+	pub fn migrate<T: Config<I>, I: Instance>() -> Weight {
+		let mut migrated_lanes = 0u64;
+		InboundLanes::<T, I>::translate::<InboundLaneDataV0<T::InboundRelayer>, _>(|_, old_data| {
+			migrated_lanes += 1;
+			Some(InboundLaneData {
+				relayers: old_data.relayers,
+				last_confirmed_nonce: old_data.last_confirmed_nonce,
+				expired_messages: VecDeque::new(),
+				unpaid_messages: VecDeque::new(),
+			})
+		});
+
+		T::DbWeight::get().reads_writes(migrated_lanes, migrated_lanes)
+	}
+}
+
+/// Migration to the storage version `2`.
+pub mod v2 {
+	use super::*;
+	use crate::InboundLanes;
+
+	use bp_messages::{InboundLaneData, MessageNonce};
+	use codec::{Decode, Encode};
+	use frame_support::storage::IterableStorageMap;
+	use sp_std::collections::vec_deque::VecDeque;
+
+	/// Inbound lane data, as it has been stored in the storage version `1`.
+	#[derive(Encode, Decode)]
+	pub struct InboundLaneDataV1<RelayerId> {
+		/// Identifiers of relayers and messages that they have delivered to this lane.
+		pub relayers: VecDeque<(MessageNonce, MessageNonce, RelayerId)>,
+		/// Nonce of the last message that has been delivered and confirmed.
+		pub last_confirmed_nonce: MessageNonce,
+		/// Nonces of messages that have been delivered after their delivery deadline.
+		pub expired_messages: VecDeque<MessageNonce>,
+	}
+
+	/// Migrate pallet storage from version `1` to version `2`.
+	///
+	/// Adds empty `unpaid_messages` queue to the data of every inbound lane.
+	pub fn migrate<T: Config<I>, I: Instance>() -> Weight {
+		let mut migrated_lanes = 0u64;
+		InboundLanes::<T, I>::translate::<InboundLaneDataV1<T::InboundRelayer>, _>(|_, old_data| {
+			migrated_lanes += 1;
+			Some(InboundLaneData {
+				relayers: old_data.relayers,
+				last_confirmed_nonce: old_data.last_confirmed_nonce,
+				expired_messages: old_data.expired_messages,
+				unpaid_messages: VecDeque::new(),
+			})
+		});
+
+		T::DbWeight::get().reads_writes(migrated_lanes, migrated_lanes)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::v1::InboundLaneDataV0;
+	use super::v2::InboundLaneDataV1;
+	use super::*;
+	use crate::mock::{run_test, TestRelayer, TestRuntime, TEST_LANE_ID, TEST_RELAYER_A, TEST_RELAYER_B};
+	use crate::{DefaultInstance, GenesisConfig, InboundLanes, OutboundLanes};
+	use bp_messages::{InboundLaneData, LaneId, OutboundLaneData};
+	use frame_support::{storage::unhashed, StorageMap};
+
+	/// Write inbound lane data, using the storage layout of version `0`.
+	fn write_v0_inbound_lane_data(lane: LaneId, data: InboundLaneDataV0<TestRelayer>) {
+		unhashed::put(&InboundLanes::<TestRuntime>::hashed_key_for(lane), &data);
+	}
+
+	/// Write inbound lane data, using the storage layout of version `1`.
+	fn write_v1_inbound_lane_data(lane: LaneId, data: InboundLaneDataV1<TestRelayer>) {
+		unhashed::put(&InboundLanes::<TestRuntime>::hashed_key_for(lane), &data);
+	}
+
+	#[test]
+	fn genesis_sets_current_storage_version() {
+		let storage = GenesisConfig::<TestRuntime, DefaultInstance>::default()
+			.build_storage()
+			.unwrap();
+		sp_io::TestExternalities::new(storage).execute_with(|| {
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), STORAGE_VERSION);
+		});
+	}
+
+	#[test]
+	fn unversioned_storage_is_migrated_to_current_version() {
+		run_test(|| {
+			write_v0_inbound_lane_data(
+				TEST_LANE_ID,
+				InboundLaneDataV0 {
+					relayers: vec![(3, 4, TEST_RELAYER_A), (5, 5, TEST_RELAYER_B)]
+						.into_iter()
+						.collect(),
+					last_confirmed_nonce: 2,
+				},
+			);
+			write_v0_inbound_lane_data(
+				LaneId(*b"test"),
+				InboundLaneDataV0 {
+					relayers: VecDeque::new(),
+					last_confirmed_nonce: 10,
+				},
+			);
+			OutboundLanes::<DefaultInstance>::insert(
+				TEST_LANE_ID,
+				OutboundLaneData {
+					oldest_unpruned_nonce: 1,
+					latest_received_nonce: 5,
+					latest_generated_nonce: 7,
+				},
+			);
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), STORAGE_VERSION);
+			assert_eq!(
+				InboundLanes::<TestRuntime>::get(TEST_LANE_ID),
+				InboundLaneData {
+					relayers: vec![(3, 4, TEST_RELAYER_A), (5, 5, TEST_RELAYER_B)]
+						.into_iter()
+						.collect(),
+					last_confirmed_nonce: 2,
+					expired_messages: VecDeque::new(),
+					unpaid_messages: VecDeque::new(),
+				},
+			);
+			assert_eq!(
+				InboundLanes::<TestRuntime>::get(LaneId(*b"test")),
+				InboundLaneData {
+					relayers: VecDeque::new(),
+					last_confirmed_nonce: 10,
+					expired_messages: VecDeque::new(),
+					unpaid_messages: VecDeque::new(),
+				},
+			);
+			assert_eq!(
+				OutboundLanes::<DefaultInstance>::get(TEST_LANE_ID),
+				OutboundLaneData {
+					oldest_unpruned_nonce: 1,
+					latest_received_nonce: 5,
+					latest_generated_nonce: 7,
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn storage_of_version_1_is_migrated_to_current_version() {
+		run_test(|| {
+			PalletStorageVersion::<DefaultInstance>::put(StorageVersion(1));
+			write_v1_inbound_lane_data(
+				TEST_LANE_ID,
+				InboundLaneDataV1 {
+					relayers: vec![(3, 4, TEST_RELAYER_A)].into_iter().collect(),
+					last_confirmed_nonce: 2,
+					expired_messages: vec![4].into_iter().collect(),
+				},
+			);
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), STORAGE_VERSION);
+			assert_eq!(
+				InboundLanes::<TestRuntime>::get(TEST_LANE_ID),
+				InboundLaneData {
+					relayers: vec![(3, 4, TEST_RELAYER_A)].into_iter().collect(),
+					last_confirmed_nonce: 2,
+					expired_messages: vec![4].into_iter().collect(),
+					unpaid_messages: VecDeque::new(),
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn storage_of_current_version_is_not_migrated() {
+		run_test(|| {
+			PalletStorageVersion::<DefaultInstance>::put(STORAGE_VERSION);
+			let lane_data = InboundLaneData {
+				relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
+				last_confirmed_nonce: 0,
+				expired_messages: vec![1].into_iter().collect(),
+				unpaid_messages: vec![1].into_iter().collect(),
+			};
+			InboundLanes::<TestRuntime>::insert(TEST_LANE_ID, lane_data.clone());
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), STORAGE_VERSION);
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID), lane_data);
+		});
+	}
+
+	#[test]
+	fn storage_of_unknown_version_is_not_migrated() {
+		run_test(|| {
+			let unknown_version = StorageVersion(STORAGE_VERSION.0 + 1);
+			PalletStorageVersion::<DefaultInstance>::put(unknown_version);
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), unknown_version);
+		});
+	}
+}
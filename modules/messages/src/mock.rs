@@ -23,13 +23,18 @@ use bp_messages::{
 	source_chain::{
 		LaneMessageVerifier, MessageDeliveryAndDispatchPayment, RelayersRewards, Sender, TargetHeaderChain,
 	},
-	target_chain::{DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages, SourceHeaderChain},
+	target_chain::{
+		DispatchMessage, MessageDispatch, OnMessagesDelivered, ProvedLaneMessages, ProvedMessages, SourceHeaderChain,
+	},
 	InboundLaneData, LaneId, Message, MessageData, MessageKey, MessageNonce, OutboundLaneData,
 	Parameter as MessagesParameter,
 };
-use bp_runtime::Size;
+use bp_runtime::{messages::MessageDispatchResult, DeclaredWeight, Size};
 use codec::{Decode, Encode};
-use frame_support::{parameter_types, weights::Weight};
+use frame_support::{
+	parameter_types,
+	weights::{RuntimeDbWeight, Weight},
+};
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header as SubstrateHeader,
@@ -75,6 +80,7 @@ parameter_types! {
 	pub const MaximumBlockWeight: Weight = 1024;
 	pub const MaximumBlockLength: u32 = 2 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::one();
+	pub const TestDbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 1, write: 2 };
 }
 
 impl frame_system::Config for TestRuntime {
@@ -98,7 +104,7 @@ impl frame_system::Config for TestRuntime {
 	type SystemWeightInfo = ();
 	type BlockWeights = ();
 	type BlockLength = ();
-	type DbWeight = ();
+	type DbWeight = TestDbWeight;
 	type SS58Prefix = ();
 	type OnSetCode = ();
 }
@@ -121,6 +127,8 @@ parameter_types! {
 	pub const MaxMessagesToPruneAtOnce: u64 = 10;
 	pub const MaxUnrewardedRelayerEntriesAtInboundLane: u64 = 16;
 	pub const MaxUnconfirmedMessagesAtInboundLane: u64 = 32;
+	pub storage MaximalOutboundPayloadSize: u32 = 1024;
+	pub const MaximalOutboundDispatchWeight: Weight = MAXIMAL_OUTBOUND_DISPATCH_WEIGHT;
 	pub storage TokenConversionRate: FixedU128 = 1.into();
 }
 
@@ -145,6 +153,8 @@ impl Config for TestRuntime {
 	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
 
+	type MaximalOutboundPayloadSize = MaximalOutboundPayloadSize;
+	type MaximalOutboundDispatchWeight = MaximalOutboundDispatchWeight;
 	type OutboundPayload = TestPayload;
 	type OutboundMessageFee = TestMessageFee;
 
@@ -160,6 +170,7 @@ impl Config for TestRuntime {
 
 	type SourceHeaderChain = TestSourceHeaderChain;
 	type MessageDispatch = TestMessageDispatch;
+	type OnMessagesDelivered = TestOnMessagesDelivered;
 }
 
 impl Size for TestPayload {
@@ -168,6 +179,15 @@ impl Size for TestPayload {
 	}
 }
 
+impl DeclaredWeight for TestPayload {
+	fn declared_weight(&self) -> Weight {
+		self.1
+	}
+}
+
+/// Maximal dispatch weight of outbound messages.
+pub const MAXIMAL_OUTBOUND_DISPATCH_WEIGHT: Weight = 1_000_000;
+
 /// Account that has balance to use in tests.
 pub const ENDOWED_ACCOUNT: AccountId = 0xDEAD;
 
@@ -184,7 +204,7 @@ pub const TEST_RELAYER_C: AccountId = 102;
 pub const TEST_ERROR: &str = "Test error";
 
 /// Lane that we're using in tests.
-pub const TEST_LANE_ID: LaneId = [0, 0, 0, 1];
+pub const TEST_LANE_ID: LaneId = LaneId([0, 0, 0, 1]);
 
 /// Regular message payload.
 pub const REGULAR_PAYLOAD: TestPayload = TestPayload(0, 50);
@@ -192,6 +212,12 @@ pub const REGULAR_PAYLOAD: TestPayload = TestPayload(0, 50);
 /// Payload that is rejected by `TestTargetHeaderChain`.
 pub const PAYLOAD_REJECTED_BY_TARGET_CHAIN: TestPayload = TestPayload(1, 50);
 
+/// Payload that is considered expired by `TestMessageDispatch`.
+pub const EXPIRED_PAYLOAD: TestPayload = TestPayload(2, 50);
+
+/// Payload that fails to pay dispatch fee at the target chain in `TestMessageDispatch`.
+pub const UNPAID_PAYLOAD: TestPayload = TestPayload(3, 50);
+
 /// Vec of proved messages, grouped by lane.
 pub type MessagesByLaneVec = Vec<(LaneId, ProvedLaneMessages<Message<TestMessageFee>>)>;
 
@@ -357,7 +383,14 @@ impl SourceHeaderChain<TestMessageFee> for TestSourceHeaderChain {
 #[derive(Debug)]
 pub struct TestMessageDispatch;
 
-impl MessageDispatch<TestMessageFee> for TestMessageDispatch {
+impl TestMessageDispatch {
+	/// Returns true if message with given key has been dispatched.
+	pub fn is_dispatched(key: MessageKey) -> bool {
+		frame_support::storage::unhashed::get_or_default(&(b":dispatched:", key).encode())
+	}
+}
+
+impl MessageDispatch<AccountId, TestMessageFee> for TestMessageDispatch {
 	type DispatchPayload = TestPayload;
 
 	fn dispatch_weight(message: &DispatchMessage<TestPayload, TestMessageFee>) -> Weight {
@@ -367,7 +400,56 @@ impl MessageDispatch<TestMessageFee> for TestMessageDispatch {
 		}
 	}
 
-	fn dispatch(_message: DispatchMessage<TestPayload, TestMessageFee>) {}
+	fn is_expired(message: &DispatchMessage<TestPayload, TestMessageFee>) -> bool {
+		matches!(message.data.payload, Ok(ref payload) if *payload == EXPIRED_PAYLOAD)
+	}
+
+	fn dispatch(
+		_relayer_account: &AccountId,
+		message: DispatchMessage<TestPayload, TestMessageFee>,
+	) -> MessageDispatchResult {
+		if matches!(message.data.payload, Ok(ref payload) if *payload == UNPAID_PAYLOAD) {
+			return MessageDispatchResult {
+				dispatch_result: false,
+				dispatch_fee_paid: false,
+			};
+		}
+
+		frame_support::storage::unhashed::put(&(b":dispatched:", message.key).encode(), &true);
+		MessageDispatchResult {
+			dispatch_result: true,
+			dispatch_fee_paid: true,
+		}
+	}
+}
+
+/// Messages delivery hook that is used in tests.
+#[derive(Debug)]
+pub struct TestOnMessagesDelivered;
+
+impl TestOnMessagesDelivered {
+	/// Returns number of messages that the hook has been called with for given lane and submitter.
+	pub fn delivered_messages(lane: LaneId, submitter: AccountId) -> MessageNonce {
+		let key = (b":delivered-messages:", lane, submitter).encode();
+		frame_support::storage::unhashed::get_or_default(&key)
+	}
+
+	/// Returns number of pending messages that the hook has been called with for given lane.
+	pub fn pending_messages(lane: LaneId) -> Option<MessageNonce> {
+		frame_support::storage::unhashed::get(&(b":pending-messages:", lane).encode())
+	}
+}
+
+impl OnMessagesDelivered<AccountId> for TestOnMessagesDelivered {
+	fn on_messages_delivered(lane: LaneId, submitter: &AccountId, messages_count: MessageNonce) {
+		let key = (b":delivered-messages:", lane, submitter).encode();
+		let delivered_messages: MessageNonce = frame_support::storage::unhashed::get_or_default(&key);
+		frame_support::storage::unhashed::put(&key, &(delivered_messages + messages_count));
+	}
+
+	fn on_pending_messages_proved(lane: LaneId, pending_messages: MessageNonce) {
+		frame_support::storage::unhashed::put(&(b":pending-messages:", lane).encode(), &pending_messages);
+	}
 }
 
 /// Return test lane message with given nonce and payload.
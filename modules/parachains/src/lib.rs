@@ -0,0 +1,674 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parachains Finality Pallet
+//!
+//! This pallet tracks finalized heads of parachains, that are connected to the bridged relay
+//! chain. It needs to be deployed next to the bridge GRANDPA pallet, which is tracking finality
+//! of the relay chain itself.
+//!
+//! The main entrypoint of the pallet is `submit_parachain_heads`. It accepts storage proof of
+//! parachain heads (values of the `Paras::Heads` map), crafted at some relay chain block, that
+//! has already been finalized by the GRANDPA pallet. Every proved head is then stored in the
+//! pallet and may be used by other pallets (e.g. messages pallet) to verify storage proofs,
+//! crafted at the parachain.
+//!
+//! Parachain heads are opaque to the relay chain, so the pallet can't decode them and is not
+//! able to tell which head is better. Instead, the head that has been read from the latest relay
+//! chain block is considered the best.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Runtime-generated enums
+#![allow(clippy::large_enum_variant)]
+
+use bp_parachains::{parachain_head_storage_key_at_source, BestParaHead, ParaHash, ParaHead, ParaHeadsProof, ParaId};
+use bp_runtime::{Chain, HasherOf, HeaderOf, StorageProofChecker};
+use codec::Decode;
+use sp_runtime::traits::Header as HeaderT;
+use sp_std::vec::Vec;
+use sp_trie::StorageProof;
+
+#[cfg(test)]
+mod mock;
+
+// Re-export in crate namespace for `construct_runtime!`
+pub use pallet::*;
+
+/// Block number of the bridged relay chain.
+pub type RelayBlockNumber<T, I> = pallet_bridge_grandpa::BridgedBlockNumber<T, GrandpaPalletInstanceOf<T, I>>;
+/// Block hash of the bridged relay chain.
+pub type RelayBlockHash<T, I> = pallet_bridge_grandpa::BridgedBlockHash<T, GrandpaPalletInstanceOf<T, I>>;
+/// Instance of the bridge GRANDPA pallet, that this pallet is linked to.
+pub type GrandpaPalletInstanceOf<T, I> = <T as Config<I>>::BridgesGrandpaPalletInstance;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config<I: 'static = ()>:
+		pallet_bridge_grandpa::Config<Self::BridgesGrandpaPalletInstance> + frame_system::Config
+	{
+		/// Instance of the bridge GRANDPA pallet (within this runtime) that this pallet is linked to.
+		///
+		/// The GRANDPA pallet instance must be configured to import headers of the relay chain,
+		/// that we're bridging with.
+		type BridgesGrandpaPalletInstance: 'static;
+
+		/// Name of the `paras` pallet in the `construct_runtime!()` call at the bridged relay chain.
+		#[pallet::constant]
+		type ParasPalletName: Get<&'static str>;
+
+		/// Maximal number of single parachain heads to keep in the storage.
+		///
+		/// The setting is there to prevent growing the on-chain state indefinitely. Note
+		/// the setting does not relate to parachain block numbers - we will simply keep as much
+		/// items in the storage, so it doesn't guarantee any fixed timeframe for heads.
+		#[pallet::constant]
+		type HeadsToKeep: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
+
+	#[pallet::hooks]
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {}
+
+	#[pallet::call]
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
+		/// Submit proof of one or several parachain heads.
+		///
+		/// The proof is supposed to be proof of some `Heads` entries from the
+		/// `polkadot-runtime-parachains::paras` pallet instance, deployed at the bridged chain.
+		/// The proof is supposed to be crafted at the `relay_block_hash` that must already be
+		/// imported by corresponding GRANDPA pallet at this chain.
+		///
+		/// Parachains that are missing from the proof and heads that are not newer than the
+		/// heads we already know of are ignored.
+		#[pallet::weight(T::DbWeight::get().reads_writes(
+			1 + 3 * parachains.len() as u64,
+			4 * parachains.len() as u64,
+		))]
+		pub fn submit_parachain_heads(
+			origin: OriginFor<T>,
+			relay_block_hash: RelayBlockHash<T, I>,
+			parachains: Vec<ParaId>,
+			parachain_heads_proof: ParaHeadsProof,
+		) -> DispatchResultWithPostInfo {
+			let _ = ensure_signed(origin)?;
+
+			// we'll need relay chain block number to compare parachain heads
+			let relay_block =
+				pallet_bridge_grandpa::Pallet::<T, T::BridgesGrandpaPalletInstance>::finalized_header(relay_block_hash)
+					.ok_or(Error::<T, I>::UnknownRelayChainBlock)?;
+			let relay_block_number = *relay_block.number();
+
+			pallet_bridge_grandpa::Pallet::<T, T::BridgesGrandpaPalletInstance>::parse_finalized_storage_proof(
+				relay_block_hash,
+				StorageProof::new(parachain_heads_proof),
+				move |storage| {
+					for parachain in parachains {
+						let parachain_head = match read_parachain_head::<T, I, _>(&storage, parachain) {
+							Some(parachain_head) => parachain_head,
+							None => {
+								log::trace!(
+									target: "runtime::bridge-parachains",
+									"The head of parachain {:?} is missing from the proof. Skipping",
+									parachain,
+								);
+								continue;
+							}
+						};
+
+						let _ = update_parachain_head::<T, I>(parachain, relay_block_number, parachain_head);
+					}
+				},
+			)
+			.map_err(|_| Error::<T, I>::InvalidStorageProof)?;
+
+			Ok(().into())
+		}
+	}
+
+	/// Best known heads of parachains.
+	#[pallet::storage]
+	pub(super) type BestParaHeads<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, ParaId, BestParaHead<RelayBlockNumber<T, I>>>;
+
+	/// Parachain heads which have been imported into the pallet.
+	#[pallet::storage]
+	pub(super) type ImportedParaHeads<T: Config<I>, I: 'static = ()> =
+		StorageDoubleMap<_, Blake2_128Concat, ParaId, Identity, ParaHash, ParaHead>;
+
+	/// A ring buffer of imported parachain head hashes. Ordered by the insertion time.
+	#[pallet::storage]
+	pub(super) type ImportedParaHashes<T: Config<I>, I: 'static = ()> =
+		StorageDoubleMap<_, Blake2_128Concat, ParaId, Twox64Concat, u32, ParaHash>;
+
+	#[pallet::error]
+	pub enum Error<T, I = ()> {
+		/// Relay chain block is unknown to the bridge GRANDPA pallet.
+		UnknownRelayChainBlock,
+		/// Invalid storage proof has been passed.
+		InvalidStorageProof,
+		/// Parachain head is unknown to the pallet.
+		UnknownParaHead,
+		/// Parachain head can't be decoded into the parachain header.
+		InvalidParaHead,
+		/// The storage proof doesn't contains storage root. So it is invalid for given parachain head.
+		StorageRootMismatch,
+	}
+
+	/// Read parachain head from the storage proof, crafted at the bridged relay chain.
+	pub(crate) fn read_parachain_head<T: Config<I>, I: 'static, H: sp_core::Hasher>(
+		storage: &StorageProofChecker<H>,
+		parachain: ParaId,
+	) -> Option<ParaHead> {
+		let parachain_head_key = parachain_head_storage_key_at_source(T::ParasPalletName::get(), parachain);
+		let parachain_head = storage.read_value(parachain_head_key.0.as_ref()).ok()??;
+		match ParaHead::decode(&mut &parachain_head[..]) {
+			Ok(parachain_head) => Some(parachain_head),
+			Err(error) => {
+				log::trace!(
+					target: "runtime::bridge-parachains",
+					"Failed to decode head of parachain {:?}: {:?}",
+					parachain,
+					error,
+				);
+				None
+			}
+		}
+	}
+
+	/// Try to update parachain head, read from the relay chain block with given number.
+	///
+	/// Returns `Err(())` if the head is not newer than the head we already know of.
+	pub(crate) fn update_parachain_head<T: Config<I>, I: 'static>(
+		parachain: ParaId,
+		relay_block_number: RelayBlockNumber<T, I>,
+		parachain_head: ParaHead,
+	) -> Result<(), ()> {
+		BestParaHeads::<T, I>::try_mutate(parachain, |stored_best_head| {
+			let head_hash = parachain_head.hash();
+			let next_imported_hash_position = match *stored_best_head {
+				Some(ref stored_best_head) if stored_best_head.at_relay_block_number >= relay_block_number => {
+					log::trace!(
+						target: "runtime::bridge-parachains",
+						"The head of parachain {:?} was already updated at relay block {:?}. Ignoring head \
+						read at relay block {:?}",
+						parachain,
+						stored_best_head.at_relay_block_number,
+						relay_block_number,
+					);
+					return Err(());
+				}
+				Some(ref stored_best_head) if stored_best_head.head_hash == head_hash => {
+					// parachain head hasn't been changed => only update relay block number
+					*stored_best_head = Some(BestParaHead {
+						at_relay_block_number: relay_block_number,
+						head_hash,
+						next_imported_hash_position: stored_best_head.next_imported_hash_position,
+					});
+					return Ok(());
+				}
+				Some(ref stored_best_head) => stored_best_head.next_imported_hash_position,
+				None => 0,
+			};
+
+			// insert new head and prune the old one
+			let head_hash_to_prune = ImportedParaHashes::<T, I>::try_get(parachain, next_imported_hash_position);
+			ImportedParaHashes::<T, I>::insert(parachain, next_imported_hash_position, head_hash);
+			ImportedParaHeads::<T, I>::insert(parachain, head_hash, parachain_head);
+			if let Ok(head_hash_to_prune) = head_hash_to_prune {
+				log::trace!(
+					target: "runtime::bridge-parachains",
+					"Pruning old head of parachain {:?}: {:?}",
+					parachain,
+					head_hash_to_prune,
+				);
+				ImportedParaHeads::<T, I>::remove(parachain, head_hash_to_prune);
+			}
+
+			log::trace!(
+				target: "runtime::bridge-parachains",
+				"Updated head of parachain {:?} to {:?}",
+				parachain,
+				head_hash,
+			);
+
+			*stored_best_head = Some(BestParaHead {
+				at_relay_block_number: relay_block_number,
+				head_hash,
+				next_imported_hash_position: (next_imported_hash_position + 1) % T::HeadsToKeep::get(),
+			});
+			Ok(())
+		})
+	}
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Get best finalized head of the given parachain.
+	pub fn best_parachain_head(parachain: ParaId) -> Option<ParaHead> {
+		let best_para_head_hash = BestParaHeads::<T, I>::get(parachain)?.head_hash;
+		ImportedParaHeads::<T, I>::get(parachain, best_para_head_hash)
+	}
+
+	/// Get parachain head with given hash.
+	pub fn parachain_head(parachain: ParaId, hash: ParaHash) -> Option<ParaHead> {
+		ImportedParaHeads::<T, I>::get(parachain, hash)
+	}
+
+	/// Verify that the passed storage proof is valid, given it is crafted using
+	/// known finalized head of the parachain `C`. If the proof is valid, then the `parse`
+	/// callback is called and the function returns its result.
+	pub fn parse_finalized_storage_proof<C: Chain, R>(
+		parachain: ParaId,
+		hash: ParaHash,
+		storage_proof: StorageProof,
+		parse: impl FnOnce(StorageProofChecker<HasherOf<C>>) -> R,
+	) -> Result<R, sp_runtime::DispatchError> {
+		let parachain_head = Self::parachain_head(parachain, hash).ok_or(Error::<T, I>::UnknownParaHead)?;
+		let parachain_header =
+			HeaderOf::<C>::decode(&mut &parachain_head.0[..]).map_err(|_| Error::<T, I>::InvalidParaHead)?;
+		let storage_proof_checker = StorageProofChecker::new(*parachain_header.state_root(), storage_proof)
+			.map_err(|_| Error::<T, I>::StorageRootMismatch)?;
+
+		Ok(parse(storage_proof_checker))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{
+		run_test, test_relay_header, Origin, ParachainHeader, RelayBlockHash, RelayBlockNumber, TestRuntime,
+	};
+
+	use bp_test_utils::{authority_list, make_default_justification};
+	use codec::Encode;
+	use frame_support::{assert_noop, assert_ok};
+	use sp_trie::{record_all_keys, trie_types::TrieDBMut, Layout, MemoryDB, Recorder, TrieMut};
+
+	type BridgesGrandpaPalletInstance = ();
+	type Pallet = crate::Pallet<TestRuntime, ()>;
+
+	fn parachain_header(number: u64, state_root: RelayBlockHash) -> ParachainHeader {
+		ParachainHeader::new(
+			number,
+			Default::default(),
+			state_root,
+			Default::default(),
+			Default::default(),
+		)
+	}
+
+	fn head_data(parachain: u32, head_number: u64) -> ParaHead {
+		ParaHead(parachain_header(head_number, RelayBlockHash::from([parachain as u8; 32])).encode())
+	}
+
+	fn head_hash(parachain: u32, head_number: u64) -> ParaHash {
+		head_data(parachain, head_number).hash()
+	}
+
+	/// Build storage with given entries and return its root and the storage proof of all entries.
+	fn prepare_storage_proof(entries: Vec<(Vec<u8>, Vec<u8>)>) -> (RelayBlockHash, ParaHeadsProof) {
+		let mut root = Default::default();
+		let mut mdb = MemoryDB::default();
+		{
+			let mut trie = TrieDBMut::<sp_runtime::traits::BlakeTwo256>::new(&mut mdb, &mut root);
+			for (key, value) in entries {
+				trie.insert(&key, &value).unwrap();
+			}
+		}
+
+		let mut proof_recorder = Recorder::<RelayBlockHash>::new();
+		record_all_keys::<Layout<sp_runtime::traits::BlakeTwo256>, _>(&mdb, &root, &mut proof_recorder).unwrap();
+		let storage_proof = proof_recorder.drain().into_iter().map(|n| n.data.to_vec()).collect();
+
+		(root, storage_proof)
+	}
+
+	/// Prepare proof of given parachain heads at the relay chain.
+	fn prepare_parachain_heads_proof(heads: Vec<(u32, ParaHead)>) -> (RelayBlockHash, ParaHeadsProof) {
+		prepare_storage_proof(
+			heads
+				.into_iter()
+				.map(|(parachain, head)| {
+					(
+						parachain_head_storage_key_at_source("Paras", ParaId(parachain)).0,
+						head.encode(),
+					)
+				})
+				.collect(),
+		)
+	}
+
+	fn initialize(state_root: RelayBlockHash) {
+		pallet_bridge_grandpa::Pallet::<TestRuntime, BridgesGrandpaPalletInstance>::initialize(
+			Origin::root(),
+			bp_header_chain::InitializationData {
+				header: test_relay_header(0, state_root),
+				authority_list: authority_list(),
+				set_id: 1,
+				is_halted: false,
+			},
+		)
+		.unwrap();
+	}
+
+	fn proceed(num: RelayBlockNumber, state_root: RelayBlockHash) -> RelayBlockHash {
+		let header = test_relay_header(num, state_root);
+		let hash = header.hash();
+		let justification = make_default_justification(&header);
+		assert_ok!(
+			pallet_bridge_grandpa::Pallet::<TestRuntime, BridgesGrandpaPalletInstance>::submit_finality_proof(
+				Origin::signed(1),
+				header,
+				justification,
+			)
+		);
+		hash
+	}
+
+	fn best_para_head(
+		at_relay_block_number: RelayBlockNumber,
+		head_hash: ParaHash,
+		next_imported_hash_position: u32,
+	) -> BestParaHead<RelayBlockNumber> {
+		BestParaHead {
+			at_relay_block_number,
+			head_hash,
+			next_imported_hash_position,
+		}
+	}
+
+	#[test]
+	fn imports_initial_parachain_heads() {
+		let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 0)), (3, head_data(3, 10))]);
+		run_test(|| {
+			initialize(state_root);
+
+			// we're trying to update heads of parachains 1, 2 and 3
+			assert_ok!(Pallet::submit_parachain_heads(
+				Origin::signed(1),
+				test_relay_header(0, state_root).hash(),
+				vec![ParaId(1), ParaId(2), ParaId(3)],
+				proof,
+			));
+
+			// but only 1 and 3 are updated, because proof is missing head of parachain#2
+			assert_eq!(
+				BestParaHeads::<TestRuntime>::get(ParaId(1)),
+				Some(best_para_head(0, head_hash(1, 0), 1)),
+			);
+			assert_eq!(BestParaHeads::<TestRuntime>::get(ParaId(2)), None);
+			assert_eq!(
+				BestParaHeads::<TestRuntime>::get(ParaId(3)),
+				Some(best_para_head(0, head_hash(3, 10), 1)),
+			);
+
+			assert_eq!(Pallet::best_parachain_head(ParaId(1)), Some(head_data(1, 0)));
+			assert_eq!(Pallet::best_parachain_head(ParaId(2)), None);
+			assert_eq!(Pallet::best_parachain_head(ParaId(3)), Some(head_data(3, 10)));
+		});
+	}
+
+	#[test]
+	fn imports_parachain_heads_is_able_to_progress() {
+		let (state_root_5, proof_5) = prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+		let (state_root_10, proof_10) = prepare_parachain_heads_proof(vec![(1, head_data(1, 10))]);
+		run_test(|| {
+			// start with relay block #0 and import head#5 of parachain#1
+			initialize(state_root_5);
+			assert_ok!(Pallet::submit_parachain_heads(
+				Origin::signed(1),
+				test_relay_header(0, state_root_5).hash(),
+				vec![ParaId(1)],
+				proof_5,
+			));
+			assert_eq!(
+				BestParaHeads::<TestRuntime>::get(ParaId(1)),
+				Some(best_para_head(0, head_hash(1, 5), 1)),
+			);
+			assert_eq!(
+				Pallet::parachain_head(ParaId(1), head_hash(1, 5)),
+				Some(head_data(1, 5))
+			);
+			assert_eq!(Pallet::parachain_head(ParaId(1), head_hash(1, 10)), None);
+
+			// import head#10 of parachain#1 at relay block #1
+			let relay_1_hash = proceed(1, state_root_10);
+			assert_ok!(Pallet::submit_parachain_heads(
+				Origin::signed(1),
+				relay_1_hash,
+				vec![ParaId(1)],
+				proof_10,
+			));
+			assert_eq!(
+				BestParaHeads::<TestRuntime>::get(ParaId(1)),
+				Some(best_para_head(1, head_hash(1, 10), 2)),
+			);
+			assert_eq!(
+				Pallet::parachain_head(ParaId(1), head_hash(1, 5)),
+				Some(head_data(1, 5))
+			);
+			assert_eq!(
+				Pallet::parachain_head(ParaId(1), head_hash(1, 10)),
+				Some(head_data(1, 10))
+			);
+		});
+	}
+
+	#[test]
+	fn does_nothing_when_already_imported_this_head_at_previous_relay_header() {
+		let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 0))]);
+		run_test(|| {
+			// import head#0 of parachain#1 at relay block#0
+			initialize(state_root);
+			assert_ok!(Pallet::submit_parachain_heads(
+				Origin::signed(1),
+				test_relay_header(0, state_root).hash(),
+				vec![ParaId(1)],
+				proof.clone(),
+			));
+			assert_eq!(
+				BestParaHeads::<TestRuntime>::get(ParaId(1)),
+				Some(best_para_head(0, head_hash(1, 0), 1)),
+			);
+
+			// try to import head#0 of parachain#1 at relay block#1
+			// => we'll leave previous value, but only update relay block number
+			let relay_1_hash = proceed(1, state_root);
+			assert_ok!(Pallet::submit_parachain_heads(
+				Origin::signed(1),
+				relay_1_hash,
+				vec![ParaId(1)],
+				proof,
+			));
+			assert_eq!(
+				BestParaHeads::<TestRuntime>::get(ParaId(1)),
+				Some(best_para_head(1, head_hash(1, 0), 1)),
+			);
+		});
+	}
+
+	#[test]
+	fn ignores_heads_read_at_older_relay_blocks() {
+		let (state_root_5, proof_5) = prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+		let (state_root_10, proof_10) = prepare_parachain_heads_proof(vec![(1, head_data(1, 10))]);
+		run_test(|| {
+			// import head#10 of parachain#1 at relay block #1
+			initialize(state_root_5);
+			let relay_1_hash = proceed(1, state_root_10);
+			assert_ok!(Pallet::submit_parachain_heads(
+				Origin::signed(1),
+				relay_1_hash,
+				vec![ParaId(1)],
+				proof_10,
+			));
+
+			// then try to import head#5 of parachain#1 at relay block #0
+			assert_ok!(Pallet::submit_parachain_heads(
+				Origin::signed(1),
+				test_relay_header(0, state_root_5).hash(),
+				vec![ParaId(1)],
+				proof_5,
+			));
+
+			// => head#5 is ignored
+			assert_eq!(
+				BestParaHeads::<TestRuntime>::get(ParaId(1)),
+				Some(best_para_head(1, head_hash(1, 10), 1)),
+			);
+			assert_eq!(Pallet::parachain_head(ParaId(1), head_hash(1, 5)), None);
+		});
+	}
+
+	#[test]
+	fn prunes_old_heads() {
+		run_test(|| {
+			let heads_to_keep = crate::mock::HeadsToKeep::get();
+
+			// import exactly `HeadsToKeep` headers
+			for i in 0..heads_to_keep {
+				let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, i as _))]);
+				let relay_block_hash = if i == 0 {
+					initialize(state_root);
+					test_relay_header(0, state_root).hash()
+				} else {
+					proceed(i as _, state_root)
+				};
+				assert_ok!(Pallet::submit_parachain_heads(
+					Origin::signed(1),
+					relay_block_hash,
+					vec![ParaId(1)],
+					proof,
+				));
+			}
+
+			// nothing is pruned yet
+			for i in 0..heads_to_keep {
+				assert!(ImportedParaHeads::<TestRuntime>::contains_key(
+					ParaId(1),
+					head_hash(1, i as _)
+				));
+			}
+
+			// import next relay chain header and next parachain head
+			let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, heads_to_keep as _))]);
+			let relay_block_hash = proceed(heads_to_keep as _, state_root);
+			assert_ok!(Pallet::submit_parachain_heads(
+				Origin::signed(1),
+				relay_block_hash,
+				vec![ParaId(1)],
+				proof,
+			));
+
+			// and the head#0 is pruned
+			assert!(!ImportedParaHeads::<TestRuntime>::contains_key(
+				ParaId(1),
+				head_hash(1, 0)
+			));
+			for i in 1..=heads_to_keep {
+				assert!(ImportedParaHeads::<TestRuntime>::contains_key(
+					ParaId(1),
+					head_hash(1, i as _)
+				));
+			}
+		});
+	}
+
+	#[test]
+	fn fails_on_unknown_relay_chain_block() {
+		let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+		run_test(|| {
+			// start with relay block #0
+			initialize(state_root);
+
+			// try to import head#5 of parachain#1 at unknown relay chain block #1
+			assert_noop!(
+				Pallet::submit_parachain_heads(
+					Origin::signed(1),
+					test_relay_header(1, state_root).hash(),
+					vec![ParaId(1)],
+					proof,
+				),
+				Error::<TestRuntime, ()>::UnknownRelayChainBlock
+			);
+		});
+	}
+
+	#[test]
+	fn fails_on_invalid_storage_proof() {
+		let (_state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+		run_test(|| {
+			// start with relay block #0
+			initialize(Default::default());
+
+			// try to import head#5 of parachain#1 at relay chain block #0
+			assert_noop!(
+				Pallet::submit_parachain_heads(
+					Origin::signed(1),
+					test_relay_header(0, Default::default()).hash(),
+					vec![ParaId(1)],
+					proof,
+				),
+				Error::<TestRuntime, ()>::InvalidStorageProof
+			);
+		});
+	}
+
+	#[test]
+	fn parse_finalized_storage_proof_works() {
+		let storage_key = b"parachain-storage-key".to_vec();
+		let storage_value = b"parachain-storage-value".to_vec();
+		let (parachain_state_root, parachain_storage_proof) =
+			prepare_storage_proof(vec![(storage_key.clone(), storage_value.clone())]);
+		let parachain_head = ParaHead(parachain_header(0, parachain_state_root).encode());
+		let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, parachain_head.clone())]);
+		run_test(|| {
+			initialize(state_root);
+			assert_ok!(Pallet::submit_parachain_heads(
+				Origin::signed(1),
+				test_relay_header(0, state_root).hash(),
+				vec![ParaId(1)],
+				proof,
+			));
+
+			assert_eq!(
+				Pallet::parse_finalized_storage_proof::<crate::mock::TestParachain, _>(
+					ParaId(1),
+					parachain_head.hash(),
+					StorageProof::new(parachain_storage_proof.clone()),
+					|storage| storage.read_value(&storage_key).unwrap(),
+				),
+				Ok(Some(storage_value)),
+			);
+
+			assert_eq!(
+				Pallet::parse_finalized_storage_proof::<crate::mock::TestParachain, _>(
+					ParaId(2),
+					parachain_head.hash(),
+					StorageProof::new(parachain_storage_proof),
+					|_| (),
+				),
+				Err(Error::<TestRuntime, ()>::UnknownParaHead.into()),
+			);
+		});
+	}
+}
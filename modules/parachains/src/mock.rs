@@ -0,0 +1,139 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+// From construct_runtime macro
+#![allow(clippy::from_over_into)]
+
+use bp_runtime::Chain;
+use frame_support::{construct_runtime, parameter_types, weights::Weight};
+use sp_runtime::{
+	testing::{Header, H256},
+	traits::{BlakeTwo256, Header as HeaderT, IdentityLookup},
+	Perbill,
+};
+
+use crate as pallet_bridge_parachains;
+
+pub type AccountId = u64;
+pub type RelayBlockHeader = Header;
+pub type RelayBlockNumber = u64;
+pub type RelayBlockHash = H256;
+pub type ParachainHeader = Header;
+
+type Block = frame_system::mocking::MockBlock<TestRuntime>;
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<TestRuntime>;
+
+construct_runtime! {
+	pub enum TestRuntime where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Grandpa: pallet_bridge_grandpa::{Pallet},
+		Parachains: pallet_bridge_parachains::{Pallet},
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+
+impl frame_system::Config for TestRuntime {
+	type Origin = Origin;
+	type Index = u64;
+	type Call = Call;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type DbWeight = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const MaxRequests: u32 = 32;
+	pub const GrandpaHeadersToKeep: u32 = 32;
+	pub const MaxAncestryLength: u32 = 32;
+	pub const HeadsToKeep: u32 = 5;
+	pub const ParasPalletName: &'static str = "Paras";
+}
+
+impl pallet_bridge_grandpa::Config for TestRuntime {
+	type BridgedChain = TestRelayChain;
+	type MaxRequests = MaxRequests;
+	type HeadersToKeep = GrandpaHeadersToKeep;
+	type MaxAncestryLength = MaxAncestryLength;
+	type WeightInfo = ();
+}
+
+impl pallet_bridge_parachains::Config for TestRuntime {
+	type BridgesGrandpaPalletInstance = ();
+	type ParasPalletName = ParasPalletName;
+	type HeadsToKeep = HeadsToKeep;
+}
+
+#[derive(Debug)]
+pub struct TestRelayChain;
+
+impl Chain for TestRelayChain {
+	type BlockNumber = RelayBlockNumber;
+	type Hash = RelayBlockHash;
+	type Hasher = BlakeTwo256;
+	type Header = RelayBlockHeader;
+}
+
+#[derive(Debug)]
+pub struct TestParachain;
+
+impl Chain for TestParachain {
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hasher = BlakeTwo256;
+	type Header = ParachainHeader;
+}
+
+pub fn run_test<T>(test: impl FnOnce() -> T) -> T {
+	sp_io::TestExternalities::new(Default::default()).execute_with(test)
+}
+
+/// Return relay chain header with given number and state root.
+pub fn test_relay_header(num: RelayBlockNumber, state_root: RelayBlockHash) -> RelayBlockHeader {
+	RelayBlockHeader::new(
+		num,
+		Default::default(),
+		state_root,
+		Default::default(),
+		Default::default(),
+	)
+}
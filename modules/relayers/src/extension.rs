@@ -0,0 +1,254 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signed extension that slashes registered relayers for failed bridge transactions.
+
+use crate::{Config, Pallet, SlashReason};
+
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{Filter, Get},
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::TransactionValidityError,
+	DispatchResult,
+};
+use sp_std::{fmt, marker::PhantomData};
+
+/// Signed extension that slashes bond of the registered relayer if its bridge transaction fails
+/// because of invalid proof.
+///
+/// Only transactions that are selected by the `Config::BridgeTransactionsFilter` are checked and
+/// only errors that are selected by the `Config::InvalidProofErrors` are slashed. Other errors may
+/// be caused by honest relayers racing for the same messages, so they're not slashed. The
+/// extension doesn't change transaction validity, so failed transactions are still included into
+/// the block and the relayer pays fee for them.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct SlashInvalidBridgeTransactions<T, I = ()>(PhantomData<(T, I)>);
+
+impl<T, I> SlashInvalidBridgeTransactions<T, I> {
+	/// Create new signed extension.
+	pub fn new() -> Self {
+		SlashInvalidBridgeTransactions(PhantomData)
+	}
+}
+
+impl<T, I> Default for SlashInvalidBridgeTransactions<T, I> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T, I> fmt::Debug for SlashInvalidBridgeTransactions<T, I> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "SlashInvalidBridgeTransactions")
+	}
+}
+
+impl<T, I> SignedExtension for SlashInvalidBridgeTransactions<T, I>
+where
+	T: Config<I> + Send + Sync,
+	I: 'static + Send + Sync + Clone + Eq,
+	<T as frame_system::Config>::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	const IDENTIFIER: &'static str = "SlashInvalidBridgeTransactions";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = Option<T::AccountId>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		if T::BridgeTransactionsFilter::filter(call) && Pallet::<T, I>::is_registered(who) {
+			Ok(Some(who.clone()))
+		} else {
+			Ok(None)
+		}
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		_info: &DispatchInfoOf<Self::Call>,
+		_post_info: &PostDispatchInfoOf<Self::Call>,
+		_len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		let (relayer, err) = match (pre, result) {
+			(Some(relayer), Err(err)) => (relayer, err),
+			_ => return Ok(()),
+		};
+
+		if !T::InvalidProofErrors::filter(err) {
+			log::trace!(
+				target: "runtime::bridge-relayers",
+				"Bridge transaction of relayer {:?} has failed, but not because of invalid proof: {:?}",
+				relayer,
+				err,
+			);
+
+			return Ok(());
+		}
+
+		log::trace!(
+			target: "runtime::bridge-relayers",
+			"Bridge transaction of relayer {:?} has failed because of invalid proof: {:?}",
+			relayer,
+			err,
+		);
+
+		Pallet::<T, I>::slash_and_deregister(
+			&relayer,
+			T::InvalidTransactionSlash::get(),
+			SlashReason::InvalidTransaction,
+		);
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::*;
+	use frame_support::{assert_ok, weights::GetDispatchInfo};
+
+	fn bridge_call() -> Call {
+		Call::Balances(pallet_balances::Call::transfer(RELAYER_B, 1))
+	}
+
+	fn other_call() -> Call {
+		Call::System(frame_system::Call::remark(vec![]))
+	}
+
+	fn invalid_proof() -> DispatchResult {
+		Err(INVALID_PROOF_ERROR.into())
+	}
+
+	fn no_new_messages() -> DispatchResult {
+		Err("no new messages".into())
+	}
+
+	fn run_extension(who: AccountId, call: Call, result: DispatchResult) {
+		let info = call.get_dispatch_info();
+		let pre = SlashInvalidBridgeTransactions::<TestRuntime>::new()
+			.pre_dispatch(&who, &call, &info, 0)
+			.unwrap();
+		assert_ok!(SlashInvalidBridgeTransactions::<TestRuntime>::post_dispatch(
+			pre,
+			&info,
+			&Default::default(),
+			0,
+			&result,
+		));
+	}
+
+	#[test]
+	fn registered_relayer_is_slashed_for_bridge_transaction_with_invalid_proof() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(RELAYER_A)));
+			run_extension(RELAYER_A, bridge_call(), invalid_proof());
+
+			assert!(!Pallet::<TestRuntime>::is_registered(&RELAYER_A));
+			assert_eq!(Balances::reserved_balance(&RELAYER_A), 0);
+			assert_eq!(
+				Balances::free_balance(&RELAYER_A),
+				RELAYER_BALANCE - INVALID_TRANSACTION_SLASH,
+			);
+		});
+	}
+
+	#[test]
+	fn registered_relayer_is_not_slashed_for_bridge_transaction_failed_for_other_reason() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(RELAYER_A)));
+			run_extension(RELAYER_A, bridge_call(), no_new_messages());
+
+			assert!(Pallet::<TestRuntime>::is_registered(&RELAYER_A));
+			assert_eq!(Balances::reserved_balance(&RELAYER_A), REGISTRATION_BOND);
+		});
+	}
+
+	#[test]
+	fn relayer_that_has_lost_delivery_race_is_not_slashed() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(RELAYER_A)));
+			assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(RELAYER_B)));
+
+			// both relayers submit the same (valid) proof, but only the first transaction brings
+			// new messages
+			run_extension(RELAYER_B, bridge_call(), Ok(()));
+			run_extension(RELAYER_A, bridge_call(), no_new_messages());
+
+			assert!(Pallet::<TestRuntime>::is_registered(&RELAYER_A));
+			assert!(Pallet::<TestRuntime>::is_registered(&RELAYER_B));
+		});
+	}
+
+	#[test]
+	fn relayer_racing_with_invalid_proof_is_slashed() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(RELAYER_A)));
+			assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(RELAYER_B)));
+
+			// relayer A submits valid proof and relayer B submits forged proof of the same messages
+			run_extension(RELAYER_A, bridge_call(), Ok(()));
+			run_extension(RELAYER_B, bridge_call(), invalid_proof());
+
+			assert!(Pallet::<TestRuntime>::is_registered(&RELAYER_A));
+			assert!(!Pallet::<TestRuntime>::is_registered(&RELAYER_B));
+		});
+	}
+
+	#[test]
+	fn registered_relayer_is_not_slashed_for_successful_bridge_transaction() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(RELAYER_A)));
+			run_extension(RELAYER_A, bridge_call(), Ok(()));
+
+			assert!(Pallet::<TestRuntime>::is_registered(&RELAYER_A));
+		});
+	}
+
+	#[test]
+	fn registered_relayer_is_not_slashed_for_failed_other_transaction() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(RELAYER_A)));
+			run_extension(RELAYER_A, other_call(), invalid_proof());
+
+			assert!(Pallet::<TestRuntime>::is_registered(&RELAYER_A));
+		});
+	}
+
+	#[test]
+	fn unregistered_relayer_is_not_slashed() {
+		run_test(|| {
+			run_extension(RELAYER_A, bridge_call(), invalid_proof());
+
+			assert_eq!(Balances::free_balance(&RELAYER_A), RELAYER_BALANCE);
+		});
+	}
+}
@@ -0,0 +1,1145 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bridge Relayers Pallet
+//!
+//! This pallet is deployed at the chain that receives messages from the bridged chain. It keeps
+//! the set of registered messages relayers and the ledger of rewards that relayers have earned
+//! by delivering messages to this chain.
+//!
+//! Every lane is served by registered relayers in turns: the time is split into slots of
+//! `SlotLength` blocks and every slot of every lane is assigned to one of registered relayers.
+//! Different lanes are starting the rotation from different relayers, so at any given block
+//! the lanes are spread among all registered relayers. During the slot, only deliveries of the
+//! slot owner earn the full `DeliveryReward` for every new message. All other deliveries (made by
+//! unregistered relayers or by registered relayers outside of their slots) only earn the
+//! `OutOfSlotRewardRatio` part of the reward. This makes copying the delivery transaction of
+//! the slot owner from the transaction pool (and submitting it with a higher priority) much
+//! less profitable. If there are no registered relayers, every delivery earns the full reward.
+//!
+//! Relayers register with a `RegistrationBond` that is reserved at their accounts while they're
+//! registered. Registered relayers are getting additional `RegisteredRewardBoost` part of every
+//! reward they earn. In exchange, their bond is slashed (and they're deregistered) if they
+//! misbehave:
+//!
+//! 1) if bridge transaction (selected by the `BridgeTransactionsFilter`) of the registered
+//!    relayer fails because of invalid proof (selected by the `InvalidProofErrors`). This is
+//!    detected by the `SlashInvalidBridgeTransactions` signed extension, so it must be included
+//!    in the runtime signed extensions. Other failures (e.g. when several relayers are racing to
+//!    deliver the same messages and only the first transaction brings new messages) are not
+//!    slashed;
+//! 2) if relayer abandons `MaxAbandonedSlots` consecutive delivery slots. The slot is considered
+//!    abandoned if, when it ends, there are pending messages at the lane and the slot owner has
+//!    delivered nothing during the slot. Messages are pending if the delivery transaction has
+//!    proved that the bridged chain has generated messages that are not yet delivered to this
+//!    chain. Deliveries of other relayers are never considered an abandonment - they may simply
+//!    have copied the delivery transaction of the slot owner.
+//!
+//! Ended slots are finished when the next delivery happens at the lane. Slots of idle lanes are
+//! finished in `on_initialize`, starting from the first block of the next slot. At most
+//! `MaxFinishedSlotsPerBlock` lanes are processed in every block, so it may take several blocks
+//! to finish slots of all lanes.
+//!
+//! Rewards are paid from the relayers fund account (derived from the `PalletId`) when the relayer
+//! calls `claim_rewards`. Slashed bonds are moved to the same account. It is up to the runtime to
+//! keep this account funded. Rewards that have not been claimed or updated for the
+//! `RewardsRetentionPeriod` blocks are considered stale. They're removed from the ledger using
+//! leftover block weight (in `on_idle`) and their funds stay at the relayers fund account.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Runtime-generated enums
+#![allow(clippy::large_enum_variant)]
+
+use bp_messages::{target_chain::OnMessagesDelivered, LaneId, MessageNonce};
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{BalanceStatus, Currency, ExistenceRequirement, Filter, Get, ReservableCurrency},
+	weights::Weight,
+	Blake2_128Concat, PalletId, ReversibleStorageHasher, RuntimeDebug, StoragePrefixedMap,
+};
+use sp_runtime::{
+	traits::{AccountIdConversion, One, Saturating, UniqueSaturatedInto, Zero},
+	DispatchError, Perbill, SaturatedConversion,
+};
+use sp_std::prelude::*;
+
+mod extension;
+#[cfg(test)]
+mod mock;
+
+pub use extension::SlashInvalidBridgeTransactions;
+// Re-export in crate namespace for `construct_runtime!`
+pub use pallet::*;
+
+/// Registration of the relayer.
+#[derive(Encode, Decode, Clone, Default, RuntimeDebug, PartialEq, Eq)]
+pub struct Registration<Balance> {
+	/// Bond that is reserved at the relayer account while it is registered.
+	pub bond: Balance,
+	/// Number of consecutive delivery slots that have been abandoned by the relayer.
+	pub abandoned_slots: u32,
+}
+
+/// State of the current delivery slot of the lane.
+#[derive(Encode, Decode, Clone, Default, RuntimeDebug, PartialEq, Eq)]
+pub struct LaneSlot {
+	/// Index of the current delivery slot.
+	pub slot: u64,
+	/// True if the slot owner has delivered messages during the current slot.
+	pub owner_delivered: bool,
+	/// Number of messages that are known to be generated at the bridged chain, but not yet
+	/// delivered to this chain.
+	pub pending_messages: MessageNonce,
+}
+
+/// Reason of the relayer bond slashing.
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum SlashReason {
+	/// Bridge transaction of the relayer has failed.
+	InvalidTransaction,
+	/// Relayer has abandoned too many consecutive delivery slots.
+	SlotAbandonment,
+}
+
+/// Balance of this chain.
+pub type BalanceOf<T, I> = <<T as Config<I>>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config<I: 'static = ()>: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self, I>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Identifier of the relayers pallet. Used to derive the relayers fund account.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Currency that is used to pay relayers rewards and to reserve relayers bonds.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Bond that is reserved at the relayer account when it registers.
+		#[pallet::constant]
+		type RegistrationBond: Get<BalanceOf<Self, I>>;
+		/// Maximal number of registered relayers.
+		#[pallet::constant]
+		type MaxRelayers: Get<u32>;
+		/// Number of blocks in the single delivery slot.
+		#[pallet::constant]
+		type SlotLength: Get<Self::BlockNumber>;
+		/// Reward for delivering single new message to this chain.
+		#[pallet::constant]
+		type DeliveryReward: Get<BalanceOf<Self, I>>;
+		/// Part of the `DeliveryReward` that is paid for deliveries that are made outside of
+		/// the delivery slot of the submitter.
+		#[pallet::constant]
+		type OutOfSlotRewardRatio: Get<Perbill>;
+		/// Part of the reward that is additionally paid to registered relayers.
+		#[pallet::constant]
+		type RegisteredRewardBoost: Get<Perbill>;
+
+		/// Number of consecutive abandoned delivery slots after which the relayer is slashed.
+		#[pallet::constant]
+		type MaxAbandonedSlots: Get<u32>;
+		/// Amount that is slashed from the bond of the relayer that has abandoned `MaxAbandonedSlots`
+		/// consecutive delivery slots.
+		#[pallet::constant]
+		type SlotAbandonmentSlash: Get<BalanceOf<Self, I>>;
+		/// Amount that is slashed from the bond of the relayer, whose bridge transaction has failed.
+		#[pallet::constant]
+		type InvalidTransactionSlash: Get<BalanceOf<Self, I>>;
+		/// Bridge transactions that are checked by the `SlashInvalidBridgeTransactions` signed extension.
+		type BridgeTransactionsFilter: Filter<<Self as frame_system::Config>::Call>;
+		/// Errors of bridge transactions that mean that the transaction has brought invalid proof
+		/// (e.g. invalid storage proof or proof of unknown header). Only transactions that have failed
+		/// with these errors are slashed by the `SlashInvalidBridgeTransactions` signed extension.
+		type InvalidProofErrors: Filter<DispatchError>;
+
+		/// Number of blocks after which unclaimed relayer rewards, that have not been updated
+		/// since, are removed from the ledger. If zero, rewards are never removed.
+		#[pallet::constant]
+		type RewardsRetentionPeriod: Get<Self::BlockNumber>;
+		/// Maximal number of lanes, whose ended delivery slots are finished in `on_initialize`
+		/// of the single block. Remaining lanes are processed in the following blocks.
+		#[pallet::constant]
+		type MaxFinishedSlotsPerBlock: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
+
+	#[pallet::hooks]
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			// delivery slots of all lanes are finished in batches, starting from the first block
+			// of the next slot
+			let current_slot = slot_index::<T, I>(n);
+			let is_slot_start = !n.is_zero() && current_slot != slot_index::<T, I>(n - One::one());
+			finish_ended_slots::<T, I>(current_slot, is_slot_start)
+		}
+
+		fn on_idle(n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			prune_stale_rewards::<T, I>(n, remaining_weight)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
+		/// Register the origin as messages relayer.
+		///
+		/// The `RegistrationBond` is reserved at the relayer account. Registered relayer gets its
+		/// own delivery slots at every lane, starting from the next block.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		pub fn register(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let relayer = ensure_signed(origin)?;
+
+			Relayers::<T, I>::try_mutate(|relayers| {
+				let index = match relayers.binary_search(&relayer) {
+					Ok(_) => return Err(Error::<T, I>::AlreadyRegistered),
+					Err(index) => index,
+				};
+				ensure!(
+					(relayers.len() as u32) < T::MaxRelayers::get(),
+					Error::<T, I>::TooManyRelayers
+				);
+
+				let bond = T::RegistrationBond::get();
+				T::Currency::reserve(&relayer, bond).map_err(|err| {
+					log::trace!(
+						target: "runtime::bridge-relayers",
+						"Failed to reserve bond of relayer {:?}: {:?}",
+						relayer,
+						err,
+					);
+
+					Error::<T, I>::FailedToReserveBond
+				})?;
+
+				relayers.insert(index, relayer.clone());
+				Registrations::<T, I>::insert(
+					&relayer,
+					Registration {
+						bond,
+						abandoned_slots: 0,
+					},
+				);
+				Ok(())
+			})?;
+
+			log::trace!(target: "runtime::bridge-relayers", "Registered relayer {:?}", relayer);
+
+			Self::deposit_event(Event::RelayerRegistered(relayer));
+
+			Ok(().into())
+		}
+
+		/// Deregister the origin.
+		///
+		/// The relayer bond is unreserved and its delivery slots are reassigned to other registered
+		/// relayers. Rewards that have been earned by the relayer may still be claimed.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		pub fn deregister(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let relayer = ensure_signed(origin)?;
+
+			let registration = remove_relayer::<T, I>(&relayer).ok_or(Error::<T, I>::NotRegistered)?;
+			T::Currency::unreserve(&relayer, registration.bond);
+
+			log::trace!(target: "runtime::bridge-relayers", "Deregistered relayer {:?}", relayer);
+
+			Self::deposit_event(Event::RelayerDeregistered(relayer));
+
+			Ok(().into())
+		}
+
+		/// Transfer all rewards, earned by the origin, from the relayers fund account.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		pub fn claim_rewards(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let relayer = ensure_signed(origin)?;
+
+			let reward = RelayerRewards::<T, I>::get(&relayer);
+			ensure!(!reward.is_zero(), Error::<T, I>::NoRewardsToClaim);
+
+			T::Currency::transfer(
+				&relayers_fund_account_id::<T, I>(),
+				&relayer,
+				reward,
+				ExistenceRequirement::AllowDeath,
+			)
+			.map_err(|err| {
+				log::trace!(
+					target: "runtime::bridge-relayers",
+					"Failed to pay {:?} rewards to relayer {:?}: {:?}",
+					reward,
+					relayer,
+					err,
+				);
+
+				Error::<T, I>::FailedToPayRewards
+			})?;
+
+			RelayerRewards::<T, I>::remove(&relayer);
+			RewardsUpdatedAt::<T, I>::remove(&relayer);
+
+			log::trace!(
+				target: "runtime::bridge-relayers",
+				"Paid {:?} rewards to relayer {:?}",
+				reward,
+				relayer,
+			);
+
+			Self::deposit_event(Event::RewardsClaimed(relayer, reward));
+
+			Ok(().into())
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	#[pallet::metadata(T::AccountId = "AccountId", BalanceOf<T, I> = "Balance", SlashReason = "SlashReason")]
+	pub enum Event<T: Config<I>, I: 'static = ()> {
+		/// Relayer has been registered. [relayer]
+		RelayerRegistered(T::AccountId),
+		/// Relayer has been deregistered. [relayer]
+		RelayerDeregistered(T::AccountId),
+		/// Relayer has earned reward for delivering messages. [lane_id, relayer, reward]
+		RewardEarned(LaneId, T::AccountId, BalanceOf<T, I>),
+		/// Relayer has claimed its rewards. [relayer, reward]
+		RewardsClaimed(T::AccountId, BalanceOf<T, I>),
+		/// Relayer bond has been slashed and the relayer has been deregistered. [relayer, slashed, reason]
+		RelayerSlashed(T::AccountId, BalanceOf<T, I>, SlashReason),
+		/// Stale relayer rewards have been removed from the ledger. [relayer, reward]
+		RewardsExpired(T::AccountId, BalanceOf<T, I>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T, I = ()> {
+		/// Relayer is already registered.
+		AlreadyRegistered,
+		/// Relayer is not registered.
+		NotRegistered,
+		/// Maximal number of relayers is already registered.
+		TooManyRelayers,
+		/// Failed to reserve the registration bond at the relayer account.
+		FailedToReserveBond,
+		/// Relayer has no rewards to claim.
+		NoRewardsToClaim,
+		/// Failed to transfer rewards from the relayers fund account.
+		FailedToPayRewards,
+	}
+
+	/// Registered relayers, sorted by their account ids.
+	#[pallet::storage]
+	#[pallet::getter(fn relayers)]
+	pub type Relayers<T: Config<I>, I: 'static = ()> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+	/// Registrations of registered relayers.
+	#[pallet::storage]
+	#[pallet::getter(fn registration)]
+	pub type Registrations<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, Registration<BalanceOf<T, I>>, OptionQuery>;
+
+	/// Rewards that have been earned by relayers, but not yet claimed.
+	#[pallet::storage]
+	#[pallet::getter(fn relayer_reward)]
+	pub type RelayerRewards<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T, I>, ValueQuery>;
+
+	/// Numbers of blocks where unclaimed relayer rewards have been updated last time.
+	#[pallet::storage]
+	#[pallet::getter(fn rewards_updated_at)]
+	pub type RewardsUpdatedAt<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::BlockNumber, OptionQuery>;
+
+	/// States of current delivery slots of lanes, where messages have been delivered.
+	#[pallet::storage]
+	#[pallet::getter(fn lane_slot)]
+	pub type LaneSlots<T: Config<I>, I: 'static = ()> = StorageMap<_, Blake2_128Concat, LaneId, LaneSlot, ValueQuery>;
+
+	/// Raw storage key of the last `RewardsUpdatedAt` entry that has been checked by the stale
+	/// rewards cleanup. If `None`, the next cleanup starts from the first entry.
+	#[pallet::storage]
+	pub(super) type StaleRewardsCursor<T: Config<I>, I: 'static = ()> = StorageValue<_, Vec<u8>, OptionQuery>;
+
+	/// Raw storage key of the last `LaneSlots` entry that has been checked by the ended slots
+	/// cleanup. If `None`, all ended slots are already finished.
+	#[pallet::storage]
+	pub(super) type EndedSlotsCursor<T: Config<I>, I: 'static = ()> = StorageValue<_, Vec<u8>, OptionQuery>;
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Return relayer that owns delivery slot of given lane at given block.
+	///
+	/// Returns `None` if there are no registered relayers.
+	pub fn slot_owner(lane: LaneId, block_number: T::BlockNumber) -> Option<T::AccountId> {
+		slot_owner::<T, I>(lane, slot_index::<T, I>(block_number))
+	}
+
+	/// Return true if given relayer is registered.
+	pub fn is_registered(relayer: &T::AccountId) -> bool {
+		Registrations::<T, I>::contains_key(relayer)
+	}
+
+	/// Return reward for delivering `messages_count` new messages by the `submitter` during
+	/// the delivery slot of the `slot_owner`.
+	pub fn delivery_reward(
+		submitter: &T::AccountId,
+		slot_owner: Option<&T::AccountId>,
+		messages_count: MessageNonce,
+	) -> BalanceOf<T, I> {
+		let reward = T::DeliveryReward::get().saturating_mul(messages_count.saturated_into());
+		let reward = match slot_owner {
+			Some(slot_owner) if slot_owner != submitter => T::OutOfSlotRewardRatio::get() * reward,
+			_ => reward,
+		};
+
+		if Self::is_registered(submitter) {
+			reward.saturating_add(T::RegisteredRewardBoost::get() * reward)
+		} else {
+			reward
+		}
+	}
+
+	/// Slash bond of the registered relayer and deregister it.
+	///
+	/// At most `amount` is slashed. Slashed funds are moved to the relayers fund account. The
+	/// rest of the bond is unreserved.
+	pub fn slash_and_deregister(relayer: &T::AccountId, amount: BalanceOf<T, I>, reason: SlashReason) {
+		let registration = match remove_relayer::<T, I>(relayer) {
+			Some(registration) => registration,
+			None => return,
+		};
+
+		let to_slash = amount.min(registration.bond);
+		let slashed = match T::Currency::repatriate_reserved(
+			relayer,
+			&relayers_fund_account_id::<T, I>(),
+			to_slash,
+			BalanceStatus::Free,
+		) {
+			Ok(not_slashed) => to_slash.saturating_sub(not_slashed),
+			Err(err) => {
+				log::trace!(
+					target: "runtime::bridge-relayers",
+					"Failed to move slashed bond of relayer {:?} to the relayers fund: {:?}. Burning it",
+					relayer,
+					err,
+				);
+
+				let (_, not_slashed) = T::Currency::slash_reserved(relayer, to_slash);
+				to_slash.saturating_sub(not_slashed)
+			}
+		};
+		T::Currency::unreserve(relayer, registration.bond.saturating_sub(slashed));
+
+		log::trace!(
+			target: "runtime::bridge-relayers",
+			"Slashed {:?} from the bond of relayer {:?}: {:?}",
+			slashed,
+			relayer,
+			reason,
+		);
+
+		Self::deposit_event(Event::RelayerSlashed(relayer.clone(), slashed, reason));
+	}
+
+	/// Remember that the slot owner has abandoned given delivery slot and slash it if it has
+	/// abandoned too many consecutive slots.
+	fn note_abandoned_slot(slot_owner: &T::AccountId, lane: LaneId, slot: u64) {
+		let should_slash = Registrations::<T, I>::mutate(slot_owner, |registration| match registration {
+			Some(registration) => {
+				registration.abandoned_slots = registration.abandoned_slots.saturating_add(1);
+				registration.abandoned_slots >= T::MaxAbandonedSlots::get()
+			}
+			None => false,
+		});
+
+		log::trace!(
+			target: "runtime::bridge-relayers",
+			"Relayer {:?} has abandoned delivery slot {} of lane {:?}",
+			slot_owner,
+			slot,
+			lane,
+		);
+
+		if should_slash {
+			Self::slash_and_deregister(slot_owner, T::SlotAbandonmentSlash::get(), SlashReason::SlotAbandonment);
+		}
+	}
+}
+
+impl<T: Config<I>, I: 'static> OnMessagesDelivered<T::AccountId> for Pallet<T, I> {
+	fn on_messages_delivered(lane: LaneId, submitter: &T::AccountId, messages_count: MessageNonce) {
+		let slot = slot_index::<T, I>(frame_system::Pallet::<T>::block_number());
+		let mut lane_slot = LaneSlots::<T, I>::get(lane);
+		finish_ended_slot::<T, I>(lane, &mut lane_slot, slot);
+
+		let slot_owner = slot_owner::<T, I>(lane, slot);
+		if slot_owner.as_ref() == Some(submitter) {
+			lane_slot.owner_delivered = true;
+			Registrations::<T, I>::mutate(submitter, |registration| {
+				if let Some(registration) = registration {
+					registration.abandoned_slots = 0;
+				}
+			});
+		}
+		lane_slot.pending_messages = lane_slot.pending_messages.saturating_sub(messages_count);
+		LaneSlots::<T, I>::insert(lane, lane_slot);
+
+		let reward = Self::delivery_reward(submitter, slot_owner.as_ref(), messages_count);
+		if reward.is_zero() {
+			return;
+		}
+
+		RelayerRewards::<T, I>::mutate(submitter, |total_reward| {
+			*total_reward = total_reward.saturating_add(reward);
+		});
+		RewardsUpdatedAt::<T, I>::insert(submitter, frame_system::Pallet::<T>::block_number());
+
+		Self::deposit_event(Event::RewardEarned(lane, submitter.clone(), reward));
+	}
+
+	fn on_pending_messages_proved(lane: LaneId, pending_messages: MessageNonce) {
+		let slot = slot_index::<T, I>(frame_system::Pallet::<T>::block_number());
+		LaneSlots::<T, I>::mutate(lane, |lane_slot| {
+			finish_ended_slot::<T, I>(lane, lane_slot, slot);
+			lane_slot.pending_messages = pending_messages;
+		});
+	}
+}
+
+/// Return account that is used to pay relayers rewards.
+pub fn relayers_fund_account_id<T: Config<I>, I: 'static>() -> T::AccountId {
+	T::PalletId::get().into_account()
+}
+
+/// Return index of the delivery slot at given block.
+fn slot_index<T: Config<I>, I: 'static>(block_number: T::BlockNumber) -> u64 {
+	let slot_length = T::SlotLength::get().max(One::one());
+	(block_number / slot_length).unique_saturated_into()
+}
+
+/// Return relayer that owns given delivery slot of given lane.
+fn slot_owner<T: Config<I>, I: 'static>(lane: LaneId, slot: u64) -> Option<T::AccountId> {
+	let relayers = Relayers::<T, I>::get();
+	if relayers.is_empty() {
+		return None;
+	}
+
+	let lane_offset = u64::from(u32::from_le_bytes(lane.0));
+	let index = slot.wrapping_add(lane_offset) % relayers.len() as u64;
+	relayers.get(index as usize).cloned()
+}
+
+/// Finish delivery slots of at most `MaxFinishedSlotsPerBlock` lanes that have ended before given
+/// slot.
+///
+/// Lanes are checked in storage order, starting from the entry that follows the last checked
+/// entry. If `is_slot_start` is true, the check is restarted from the first entry. Returns weight
+/// that has been spent.
+fn finish_ended_slots<T: Config<I>, I: 'static>(current_slot: u64, is_slot_start: bool) -> Weight {
+	let db_weight = T::DbWeight::get();
+	let prefix = LaneSlots::<T, I>::final_prefix();
+	let mut cursor = if is_slot_start {
+		prefix.to_vec()
+	} else {
+		// reads: `EndedSlotsCursor`
+		match EndedSlotsCursor::<T, I>::get() {
+			Some(cursor) => cursor,
+			None => return db_weight.reads(1),
+		}
+	};
+
+	// reads: `EndedSlotsCursor`
+	// writes: `EndedSlotsCursor`
+	let mut used_weight = db_weight.reads_writes(1, 1);
+	// reads: next `LaneSlots` key + `LaneSlots` entry + `Relayers` + `Registrations`
+	// writes: `LaneSlots` entry + `Registrations`
+	let lane_weight = db_weight.reads_writes(4, 2);
+	let mut all_checked = false;
+	for _ in 0..T::MaxFinishedSlotsPerBlock::get().max(1) {
+		cursor = match sp_io::storage::next_key(&cursor) {
+			Some(next_key) if next_key.starts_with(&prefix) => next_key,
+			_ => {
+				all_checked = true;
+				break;
+			}
+		};
+		used_weight = used_weight.saturating_add(lane_weight);
+
+		let lane = match LaneId::decode(&mut Blake2_128Concat::reverse(&cursor[prefix.len()..])) {
+			Ok(lane) => lane,
+			Err(_) => continue,
+		};
+		LaneSlots::<T, I>::mutate(lane, |lane_slot| {
+			finish_ended_slot::<T, I>(lane, lane_slot, current_slot)
+		});
+	}
+
+	if all_checked {
+		EndedSlotsCursor::<T, I>::kill();
+	} else {
+		EndedSlotsCursor::<T, I>::put(cursor);
+	}
+
+	used_weight
+}
+
+/// Finish the delivery slot of the lane if it has ended before given slot.
+///
+/// The slot is abandoned if there are pending messages at the lane when the slot ends and the
+/// slot owner has delivered nothing during the slot.
+fn finish_ended_slot<T: Config<I>, I: 'static>(lane: LaneId, lane_slot: &mut LaneSlot, current_slot: u64) {
+	if lane_slot.slot >= current_slot {
+		return;
+	}
+
+	if !lane_slot.owner_delivered && lane_slot.pending_messages != 0 {
+		if let Some(slot_owner) = slot_owner::<T, I>(lane, lane_slot.slot) {
+			Pallet::<T, I>::note_abandoned_slot(&slot_owner, lane, lane_slot.slot);
+		}
+	}
+
+	lane_slot.slot = current_slot;
+	lane_slot.owner_delivered = false;
+}
+
+/// Remove stale relayer rewards from the ledger, spending no more than given weight.
+///
+/// Entries are checked in storage order, starting from the entry that follows the last checked
+/// entry. Returns weight that has been actually spent.
+fn prune_stale_rewards<T: Config<I>, I: 'static>(block_number: T::BlockNumber, remaining_weight: Weight) -> Weight {
+	let retention_period = T::RewardsRetentionPeriod::get();
+	if retention_period.is_zero() {
+		return 0;
+	}
+
+	let db_weight = T::DbWeight::get();
+	// reads: `StaleRewardsCursor`
+	// writes: `StaleRewardsCursor`
+	let base_weight = db_weight.reads_writes(1, 1);
+	// reads: next `RewardsUpdatedAt` key + `RewardsUpdatedAt`
+	let entry_weight = db_weight.reads(2);
+	// reads: `RelayerRewards`
+	// writes: `RelayerRewards` + `RewardsUpdatedAt`
+	let prune_entry_weight = db_weight.reads_writes(1, 2);
+	let max_entry_weight = entry_weight.saturating_add(prune_entry_weight);
+	if remaining_weight < base_weight.saturating_add(max_entry_weight) {
+		return 0;
+	}
+
+	let prefix = RewardsUpdatedAt::<T, I>::final_prefix();
+	let mut cursor = StaleRewardsCursor::<T, I>::get().unwrap_or_else(|| prefix.to_vec());
+	let mut used_weight = base_weight;
+	let mut all_checked = false;
+	while used_weight.saturating_add(max_entry_weight) <= remaining_weight {
+		cursor = match sp_io::storage::next_key(&cursor) {
+			Some(next_key) if next_key.starts_with(&prefix) => next_key,
+			_ => {
+				all_checked = true;
+				break;
+			}
+		};
+		used_weight = used_weight.saturating_add(entry_weight);
+
+		let relayer = match T::AccountId::decode(&mut Blake2_128Concat::reverse(&cursor[prefix.len()..])) {
+			Ok(relayer) => relayer,
+			Err(_) => continue,
+		};
+		let is_stale = RewardsUpdatedAt::<T, I>::get(&relayer)
+			.map(|updated_at| block_number.saturating_sub(updated_at) >= retention_period)
+			.unwrap_or(false);
+		if !is_stale {
+			continue;
+		}
+
+		let reward = RelayerRewards::<T, I>::take(&relayer);
+		RewardsUpdatedAt::<T, I>::remove(&relayer);
+		used_weight = used_weight.saturating_add(prune_entry_weight);
+
+		log::trace!(
+			target: "runtime::bridge-relayers",
+			"Removed stale {:?} rewards of relayer {:?}",
+			reward,
+			relayer,
+		);
+
+		Pallet::<T, I>::deposit_event(Event::RewardsExpired(relayer, reward));
+	}
+
+	if all_checked {
+		StaleRewardsCursor::<T, I>::kill();
+	} else {
+		StaleRewardsCursor::<T, I>::put(cursor);
+	}
+
+	used_weight
+}
+
+/// Remove relayer from the set of registered relayers, returning its registration.
+fn remove_relayer<T: Config<I>, I: 'static>(relayer: &T::AccountId) -> Option<Registration<BalanceOf<T, I>>> {
+	let registration = Registrations::<T, I>::take(relayer)?;
+	Relayers::<T, I>::mutate(|relayers| {
+		if let Ok(index) = relayers.binary_search(relayer) {
+			relayers.remove(index);
+		}
+	});
+	Some(registration)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::*;
+	use frame_support::{assert_noop, assert_ok, traits::OnInitialize};
+
+	const LANE: LaneId = LaneId([0, 0, 0, 0]);
+	const OTHER_LANE: LaneId = LaneId([1, 0, 0, 0]);
+
+	fn register_relayers() {
+		assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(RELAYER_B)));
+		assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(RELAYER_A)));
+	}
+
+	#[test]
+	fn register_keeps_relayers_sorted() {
+		run_test(|| {
+			register_relayers();
+			assert_eq!(Pallet::<TestRuntime>::relayers(), vec![RELAYER_A, RELAYER_B]);
+		});
+	}
+
+	#[test]
+	fn register_fails_if_relayer_is_already_registered() {
+		run_test(|| {
+			register_relayers();
+			assert_noop!(
+				Pallet::<TestRuntime>::register(Origin::signed(RELAYER_A)),
+				Error::<TestRuntime, ()>::AlreadyRegistered,
+			);
+		});
+	}
+
+	#[test]
+	fn register_fails_if_there_are_too_many_relayers() {
+		run_test(|| {
+			for relayer in 0..MAX_RELAYERS {
+				let relayer = 100 + relayer as AccountId;
+				Balances::make_free_balance_be(&relayer, RELAYER_BALANCE);
+				assert_ok!(Pallet::<TestRuntime>::register(Origin::signed(relayer)));
+			}
+			assert_noop!(
+				Pallet::<TestRuntime>::register(Origin::signed(RELAYER_A)),
+				Error::<TestRuntime, ()>::TooManyRelayers,
+			);
+		});
+	}
+
+	#[test]
+	fn register_reserves_bond() {
+		run_test(|| {
+			register_relayers();
+			assert_eq!(Balances::reserved_balance(&RELAYER_A), REGISTRATION_BOND);
+			assert_eq!(
+				Pallet::<TestRuntime>::registration(RELAYER_A),
+				Some(Registration {
+					bond: REGISTRATION_BOND,
+					abandoned_slots: 0,
+				}),
+			);
+		});
+	}
+
+	#[test]
+	fn register_fails_if_relayer_cant_pay_bond() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::register(Origin::signed(UNREGISTERED_RELAYER)),
+				Error::<TestRuntime, ()>::FailedToReserveBond,
+			);
+		});
+	}
+
+	#[test]
+	fn deregister_works() {
+		run_test(|| {
+			register_relayers();
+			assert_ok!(Pallet::<TestRuntime>::deregister(Origin::signed(RELAYER_A)));
+			assert_eq!(Pallet::<TestRuntime>::relayers(), vec![RELAYER_B]);
+			assert_eq!(Pallet::<TestRuntime>::registration(RELAYER_A), None);
+			assert_eq!(Balances::reserved_balance(&RELAYER_A), 0);
+			assert_eq!(Balances::free_balance(&RELAYER_A), RELAYER_BALANCE);
+		});
+	}
+
+	#[test]
+	fn deregister_fails_if_relayer_is_not_registered() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::deregister(Origin::signed(RELAYER_A)),
+				Error::<TestRuntime, ()>::NotRegistered,
+			);
+		});
+	}
+
+	#[test]
+	fn there_are_no_slot_owners_if_relayers_are_not_registered() {
+		run_test(|| {
+			assert_eq!(Pallet::<TestRuntime>::slot_owner(LANE, 0), None);
+		});
+	}
+
+	#[test]
+	fn slots_are_rotated_among_registered_relayers() {
+		run_test(|| {
+			register_relayers();
+
+			assert_eq!(Pallet::<TestRuntime>::slot_owner(LANE, 0), Some(RELAYER_A));
+			assert_eq!(
+				Pallet::<TestRuntime>::slot_owner(LANE, SLOT_LENGTH - 1),
+				Some(RELAYER_A)
+			);
+			assert_eq!(Pallet::<TestRuntime>::slot_owner(LANE, SLOT_LENGTH), Some(RELAYER_B));
+			assert_eq!(
+				Pallet::<TestRuntime>::slot_owner(LANE, 2 * SLOT_LENGTH),
+				Some(RELAYER_A)
+			);
+		});
+	}
+
+	#[test]
+	fn different_lanes_have_different_slot_owners() {
+		run_test(|| {
+			register_relayers();
+
+			assert_eq!(Pallet::<TestRuntime>::slot_owner(LANE, 0), Some(RELAYER_A));
+			assert_eq!(Pallet::<TestRuntime>::slot_owner(OTHER_LANE, 0), Some(RELAYER_B));
+		});
+	}
+
+	#[test]
+	fn slot_owner_earns_full_boosted_reward() {
+		run_test(|| {
+			register_relayers();
+
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_A, 2);
+			let reward = 2 * DELIVERY_REWARD;
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward(RELAYER_A),
+				reward + RegisteredRewardBoost::get() * reward,
+			);
+		});
+	}
+
+	#[test]
+	fn out_of_slot_delivery_earns_reduced_reward() {
+		run_test(|| {
+			register_relayers();
+
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_B, 2);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &UNREGISTERED_RELAYER, 2);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward(RELAYER_B),
+				DELIVERY_REWARD + RegisteredRewardBoost::get() * DELIVERY_REWARD,
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward(UNREGISTERED_RELAYER),
+				DELIVERY_REWARD
+			);
+		});
+	}
+
+	fn start_slot(slot: u64) {
+		let block_number = slot * SLOT_LENGTH;
+		System::set_block_number(block_number);
+		Pallet::<TestRuntime>::on_initialize(block_number);
+	}
+
+	fn abandoned_slots(relayer: AccountId) -> Option<u32> {
+		Pallet::<TestRuntime>::registration(relayer).map(|r| r.abandoned_slots)
+	}
+
+	#[test]
+	fn slot_is_not_abandoned_if_there_are_no_pending_messages() {
+		run_test(|| {
+			register_relayers();
+
+			Pallet::<TestRuntime>::on_pending_messages_proved(LANE, 0);
+			start_slot(1);
+			assert_eq!(abandoned_slots(RELAYER_A), Some(0));
+		});
+	}
+
+	#[test]
+	fn slot_is_abandoned_if_nobody_delivers_pending_messages() {
+		run_test(|| {
+			register_relayers();
+
+			Pallet::<TestRuntime>::on_pending_messages_proved(LANE, 1);
+			start_slot(1);
+			assert_eq!(abandoned_slots(RELAYER_A), Some(1));
+			assert_eq!(abandoned_slots(RELAYER_B), Some(0));
+		});
+	}
+
+	#[test]
+	fn slot_is_not_abandoned_if_other_relayer_has_delivered_all_pending_messages() {
+		run_test(|| {
+			register_relayers();
+
+			// e.g. other relayer has copied delivery transaction of the slot owner
+			Pallet::<TestRuntime>::on_pending_messages_proved(LANE, 2);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &UNREGISTERED_RELAYER, 2);
+			start_slot(1);
+			assert_eq!(abandoned_slots(RELAYER_A), Some(0));
+		});
+	}
+
+	#[test]
+	fn slot_is_abandoned_if_owner_has_delivered_nothing_while_messages_were_pending() {
+		run_test(|| {
+			register_relayers();
+
+			Pallet::<TestRuntime>::on_pending_messages_proved(LANE, 3);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &UNREGISTERED_RELAYER, 1);
+			start_slot(1);
+			assert_eq!(abandoned_slots(RELAYER_A), Some(1));
+		});
+	}
+
+	#[test]
+	fn slot_is_not_abandoned_if_owner_has_delivered_messages() {
+		run_test(|| {
+			register_relayers();
+
+			Pallet::<TestRuntime>::on_pending_messages_proved(LANE, 3);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_A, 1);
+			start_slot(1);
+			assert_eq!(abandoned_slots(RELAYER_A), Some(0));
+		});
+	}
+
+	#[test]
+	fn slot_is_finished_by_delivery_in_the_next_slot() {
+		run_test(|| {
+			register_relayers();
+
+			Pallet::<TestRuntime>::on_pending_messages_proved(LANE, 3);
+			System::set_block_number(SLOT_LENGTH);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_B, 1);
+			assert_eq!(abandoned_slots(RELAYER_A), Some(1));
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_slot(LANE),
+				LaneSlot {
+					slot: 1,
+					owner_delivered: true,
+					pending_messages: 2,
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn ended_slots_are_finished_in_batches() {
+		run_test(|| {
+			register_relayers();
+
+			let lanes = (0..MAX_FINISHED_SLOTS_PER_BLOCK + 1)
+				.map(|i| LaneId([i as u8, 0, 0, 0]))
+				.collect::<Vec<_>>();
+			for lane in &lanes {
+				Pallet::<TestRuntime>::on_pending_messages_proved(*lane, 1);
+			}
+			let finished_lanes = || {
+				lanes
+					.iter()
+					.filter(|lane| Pallet::<TestRuntime>::lane_slot(**lane).slot == 1)
+					.count() as u32
+			};
+
+			start_slot(1);
+			assert_eq!(finished_lanes(), MAX_FINISHED_SLOTS_PER_BLOCK);
+			assert!(EndedSlotsCursor::<TestRuntime>::get().is_some());
+
+			Pallet::<TestRuntime>::on_initialize(SLOT_LENGTH + 1);
+			assert_eq!(finished_lanes(), MAX_FINISHED_SLOTS_PER_BLOCK + 1);
+			assert_eq!(EndedSlotsCursor::<TestRuntime>::get(), None);
+
+			// nothing to do until the next slot starts
+			assert_eq!(
+				Pallet::<TestRuntime>::on_initialize(SLOT_LENGTH + 2),
+				TestDbWeight::get().reads(1)
+			);
+		});
+	}
+
+	#[test]
+	fn slot_owner_delivery_resets_abandoned_slots() {
+		run_test(|| {
+			register_relayers();
+
+			Pallet::<TestRuntime>::on_pending_messages_proved(LANE, 3);
+			start_slot(2);
+			assert_eq!(abandoned_slots(RELAYER_A), Some(1));
+
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_A, 1);
+			assert_eq!(abandoned_slots(RELAYER_A), Some(0));
+		});
+	}
+
+	#[test]
+	fn relayer_is_slashed_for_abandoning_too_many_slots() {
+		run_test(|| {
+			register_relayers();
+
+			// slots 0 and 2 are owned by relayer A, slot 1 is owned by relayer B
+			Pallet::<TestRuntime>::on_pending_messages_proved(LANE, 1);
+			start_slot(1);
+			start_slot(2);
+			assert_eq!(abandoned_slots(RELAYER_B), Some(1));
+			start_slot(3);
+
+			assert!(!Pallet::<TestRuntime>::is_registered(&RELAYER_A));
+			assert_eq!(Pallet::<TestRuntime>::relayers(), vec![RELAYER_B]);
+			assert_eq!(Balances::reserved_balance(&RELAYER_A), 0);
+			assert_eq!(
+				Balances::free_balance(&RELAYER_A),
+				RELAYER_BALANCE - SLOT_ABANDONMENT_SLASH
+			);
+			assert_eq!(
+				Balances::free_balance(&relayers_fund_account_id::<TestRuntime, ()>()),
+				RELAYERS_FUND_BALANCE + SLOT_ABANDONMENT_SLASH,
+			);
+		});
+	}
+
+	#[test]
+	fn every_delivery_earns_full_reward_if_relayers_are_not_registered() {
+		run_test(|| {
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &UNREGISTERED_RELAYER, 2);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward(UNREGISTERED_RELAYER),
+				2 * DELIVERY_REWARD
+			);
+		});
+	}
+
+	#[test]
+	fn claim_rewards_works() {
+		run_test(|| {
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_A, 2);
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards(Origin::signed(RELAYER_A)));
+
+			assert_eq!(Pallet::<TestRuntime>::relayer_reward(RELAYER_A), 0);
+			assert_eq!(Pallet::<TestRuntime>::rewards_updated_at(RELAYER_A), None);
+			assert_eq!(
+				Balances::free_balance(&RELAYER_A),
+				RELAYER_BALANCE + 2 * DELIVERY_REWARD
+			);
+			assert_eq!(
+				Balances::free_balance(&relayers_fund_account_id::<TestRuntime, ()>()),
+				RELAYERS_FUND_BALANCE - 2 * DELIVERY_REWARD,
+			);
+		});
+	}
+
+	#[test]
+	fn claim_rewards_fails_if_there_are_no_rewards() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_rewards(Origin::signed(RELAYER_A)),
+				Error::<TestRuntime, ()>::NoRewardsToClaim,
+			);
+		});
+	}
+
+	#[test]
+	fn claim_rewards_fails_if_fund_has_not_enough_balance() {
+		run_test(|| {
+			RelayerRewards::<TestRuntime>::insert(RELAYER_A, RELAYERS_FUND_BALANCE + 1);
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_rewards(Origin::signed(RELAYER_A)),
+				Error::<TestRuntime, ()>::FailedToPayRewards,
+			);
+		});
+	}
+
+	#[test]
+	fn stale_rewards_are_pruned_on_idle() {
+		run_test(|| {
+			System::set_block_number(1);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_A, 1);
+			System::set_block_number(50);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_B, 1);
+
+			// (1 read + 1 write) for cursor + 2 * 2 reads to check entries + (1 read + 2 writes) to prune entry
+			let now = REWARDS_RETENTION_PERIOD + 1;
+			assert_eq!(
+				prune_stale_rewards::<TestRuntime, ()>(now, Weight::max_value()),
+				3 + 2 * 2 + 5
+			);
+			assert_eq!(Pallet::<TestRuntime>::relayer_reward(RELAYER_A), 0);
+			assert_eq!(Pallet::<TestRuntime>::rewards_updated_at(RELAYER_A), None);
+			assert_eq!(Pallet::<TestRuntime>::relayer_reward(RELAYER_B), DELIVERY_REWARD);
+			assert_eq!(Pallet::<TestRuntime>::rewards_updated_at(RELAYER_B), Some(50));
+			assert_eq!(StaleRewardsCursor::<TestRuntime>::get(), None);
+			assert_eq!(
+				Balances::free_balance(&relayers_fund_account_id::<TestRuntime, ()>()),
+				RELAYERS_FUND_BALANCE,
+			);
+		});
+	}
+
+	#[test]
+	fn stale_rewards_pruning_respects_remaining_weight() {
+		run_test(|| {
+			System::set_block_number(1);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_A, 1);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_B, 1);
+
+			// not enough weight to prune single entry
+			let now = REWARDS_RETENTION_PERIOD + 1;
+			assert_eq!(prune_stale_rewards::<TestRuntime, ()>(now, 3 + 2 + 4), 0);
+			// enough weight to prune single entry only
+			assert_eq!(prune_stale_rewards::<TestRuntime, ()>(now, 3 + 2 + 5), 3 + 2 + 5);
+			assert!(StaleRewardsCursor::<TestRuntime>::get().is_some());
+			assert_eq!(RelayerRewards::<TestRuntime>::iter().count(), 1);
+			// the rest is pruned when there's enough weight
+			assert_eq!(
+				prune_stale_rewards::<TestRuntime, ()>(now, Weight::max_value()),
+				3 + 2 + 5
+			);
+			assert_eq!(StaleRewardsCursor::<TestRuntime>::get(), None);
+			assert_eq!(RelayerRewards::<TestRuntime>::iter().count(), 0);
+		});
+	}
+
+	#[test]
+	fn updated_rewards_are_not_pruned_on_idle() {
+		run_test(|| {
+			System::set_block_number(1);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_A, 1);
+			System::set_block_number(REWARDS_RETENTION_PERIOD);
+			Pallet::<TestRuntime>::on_messages_delivered(LANE, &RELAYER_A, 1);
+
+			let now = REWARDS_RETENTION_PERIOD + 1;
+			assert_eq!(prune_stale_rewards::<TestRuntime, ()>(now, Weight::max_value()), 3 + 2);
+			assert_eq!(Pallet::<TestRuntime>::relayer_reward(RELAYER_A), 2 * DELIVERY_REWARD);
+		});
+	}
+}
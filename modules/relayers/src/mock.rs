@@ -0,0 +1,187 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+// From construct_runtime macro
+#![allow(clippy::from_over_into)]
+
+use frame_support::{
+	construct_runtime, parameter_types,
+	traits::Filter,
+	weights::{RuntimeDbWeight, Weight},
+	PalletId,
+};
+use sp_runtime::{
+	testing::{Header, H256},
+	traits::{BlakeTwo256, IdentityLookup},
+	DispatchError, Perbill,
+};
+
+pub type AccountId = u64;
+pub type Balance = u64;
+
+type Block = frame_system::mocking::MockBlock<TestRuntime>;
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<TestRuntime>;
+
+pub const MAX_RELAYERS: u32 = 4;
+pub const SLOT_LENGTH: u64 = 8;
+pub const DELIVERY_REWARD: Balance = 100;
+pub const REGISTRATION_BOND: Balance = 10_000;
+pub const MAX_ABANDONED_SLOTS: u32 = 2;
+pub const SLOT_ABANDONMENT_SLASH: Balance = 5_000;
+pub const INVALID_TRANSACTION_SLASH: Balance = 1_000;
+pub const REWARDS_RETENTION_PERIOD: u64 = 100;
+pub const MAX_FINISHED_SLOTS_PER_BLOCK: u32 = 2;
+pub const RELAYERS_FUND_BALANCE: Balance = 100_000;
+pub const RELAYER_BALANCE: Balance = 100_000;
+pub const RELAYER_A: AccountId = 1;
+pub const RELAYER_B: AccountId = 2;
+pub const UNREGISTERED_RELAYER: AccountId = 3;
+pub const INVALID_PROOF_ERROR: &str = "invalid proof";
+
+use crate as pallet_bridge_relayers;
+
+construct_runtime! {
+	pub enum TestRuntime where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Event<T>},
+		BridgeRelayers: pallet_bridge_relayers::{Pallet, Call, Event<T>},
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+	pub const TestDbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 1, write: 2 };
+}
+
+impl frame_system::Config for TestRuntime {
+	type Origin = Origin;
+	type Index = u64;
+	type Call = Call;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type DbWeight = TestDbWeight;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for TestRuntime {
+	type MaxLocks = ();
+	type Balance = Balance;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = frame_system::Pallet<TestRuntime>;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const RelayersPalletId: PalletId = PalletId(*b"brl/test");
+	pub const RegistrationBond: Balance = REGISTRATION_BOND;
+	pub const MaxRelayers: u32 = MAX_RELAYERS;
+	pub const SlotLength: u64 = SLOT_LENGTH;
+	pub const DeliveryReward: Balance = DELIVERY_REWARD;
+	pub const OutOfSlotRewardRatio: Perbill = Perbill::from_percent(50);
+	pub const RegisteredRewardBoost: Perbill = Perbill::from_percent(10);
+	pub const MaxAbandonedSlots: u32 = MAX_ABANDONED_SLOTS;
+	pub const SlotAbandonmentSlash: Balance = SLOT_ABANDONMENT_SLASH;
+	pub const InvalidTransactionSlash: Balance = INVALID_TRANSACTION_SLASH;
+	pub const RewardsRetentionPeriod: u64 = REWARDS_RETENTION_PERIOD;
+	pub const MaxFinishedSlotsPerBlock: u32 = MAX_FINISHED_SLOTS_PER_BLOCK;
+}
+
+impl pallet_bridge_relayers::Config for TestRuntime {
+	type Event = Event;
+	type PalletId = RelayersPalletId;
+	type Currency = pallet_balances::Pallet<TestRuntime>;
+	type RegistrationBond = RegistrationBond;
+	type MaxRelayers = MaxRelayers;
+	type SlotLength = SlotLength;
+	type DeliveryReward = DeliveryReward;
+	type OutOfSlotRewardRatio = OutOfSlotRewardRatio;
+	type RegisteredRewardBoost = RegisteredRewardBoost;
+	type MaxAbandonedSlots = MaxAbandonedSlots;
+	type SlotAbandonmentSlash = SlotAbandonmentSlash;
+	type InvalidTransactionSlash = InvalidTransactionSlash;
+	type BridgeTransactionsFilter = TestBridgeTransactionsFilter;
+	type InvalidProofErrors = TestInvalidProofErrors;
+	type RewardsRetentionPeriod = RewardsRetentionPeriod;
+	type MaxFinishedSlotsPerBlock = MaxFinishedSlotsPerBlock;
+}
+
+/// In tests, all balances transfers are considered bridge transactions.
+pub struct TestBridgeTransactionsFilter;
+
+impl Filter<Call> for TestBridgeTransactionsFilter {
+	fn filter(call: &Call) -> bool {
+		matches!(call, Call::Balances(..))
+	}
+}
+
+/// In tests, only `INVALID_PROOF_ERROR` means that the transaction has brought invalid proof.
+pub struct TestInvalidProofErrors;
+
+impl Filter<DispatchError> for TestInvalidProofErrors {
+	fn filter(error: &DispatchError) -> bool {
+		*error == DispatchError::Other(INVALID_PROOF_ERROR)
+	}
+}
+
+/// Run pallet test.
+pub fn run_test<T>(test: impl FnOnce() -> T) -> T {
+	let mut t = frame_system::GenesisConfig::default()
+		.build_storage::<TestRuntime>()
+		.unwrap();
+	pallet_balances::GenesisConfig::<TestRuntime> {
+		balances: vec![
+			(
+				pallet_bridge_relayers::relayers_fund_account_id::<TestRuntime, ()>(),
+				RELAYERS_FUND_BALANCE,
+			),
+			(RELAYER_A, RELAYER_BALANCE),
+			(RELAYER_B, RELAYER_BALANCE),
+		],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(test)
+}
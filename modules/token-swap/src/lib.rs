@@ -0,0 +1,1521 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Token Swap Pallet
+//!
+//! This pallet allows two parties on different chains (bridged using the messages pallet) to swap
+//! their tokens. The same pallet is deployed at both chains and it plays different roles in the
+//! swap:
+//!
+//! 1) the source party calls `create_swap` at the source chain. Source tokens are moved to the
+//!    swap account (derived from the swap hash) and the swap becomes pending;
+//! 2) the target party calls `accept_swap` at the target chain. Target tokens are moved from the
+//!    target party account to the swap account at the target chain. Then the pallet sends message
+//!    to the source chain, asking to claim the swap. The message may only be dispatched before
+//!    the `cancel_after` block of the source chain (it is used as the message delivery deadline);
+//! 3) when the message is dispatched at the source chain, `claim_swap` is called with the origin
+//!    of the token swap pallet at the target chain. If the swap has never been created at the
+//!    source chain (so there's no fee for the result message), the claim is rejected. If the swap
+//!    is still pending and its `cancel_after` block has not been reached yet, source tokens are
+//!    transferred from the swap account to the account of the target party at the source chain
+//!    (derived from the target party account using `bp_runtime::derive_account_id`). Otherwise,
+//!    source tokens are returned back to the source party. In both cases, the swap result is
+//!    stored and the pallet sends the swap result message back to the target chain;
+//! 4) when the result message is dispatched at the target chain, `settle_swap` is called with the
+//!    origin of the token swap pallet at the source chain. If the swap has been claimed, target
+//!    tokens are transferred to the account of the source party at the target chain. Otherwise,
+//!    they're returned back to the target party;
+//! 5) if the swap hasn't been claimed before `cancel_after` block, the source party may call
+//!    `cancel_swap` to get its tokens back. The cancellation result is sent to the target chain,
+//!    so target tokens are returned back to the target party.
+//!
+//! Swap results are kept at the source chain, so anyone may call `send_swap_result` to (re)send
+//! the result message if it has failed to be sent or dispatched (e.g. because of runtime upgrade).
+//! After the `cancel_after` block, `send_swap_result` may also be used to time out swaps that
+//! haven't been claimed (including swaps that have never been created at the source chain), so
+//! target tokens are never locked forever. The caller of `send_swap_result` pays for the message.
+//!
+//! So both sides of the swap are either completed, or cancelled. The source party pays for the
+//! result message when the swap is created. This pallet is intended to be used in test
+//! (Millau <> Rialto) deployments only.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Runtime-generated enums
+#![allow(clippy::large_enum_variant)]
+
+use bp_message_dispatch::{CallOrigin, DispatchFeePayment, MessagePayload};
+use bp_messages::{
+	source_chain::{MessagesBridge, Sender},
+	LaneId, MessageNonce,
+};
+use bp_runtime::{derive_account_id, BlockNumberOf, Chain, ChainId, SourceAccount};
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{Currency, ExistenceRequirement, Get},
+	weights::Weight,
+	PalletId, RuntimeDebug,
+};
+use sp_core::H256;
+use sp_runtime::traits::{AccountIdConversion, AtLeast32BitUnsigned, Convert, One, Saturating, UniqueSaturatedInto};
+use sp_std::{marker::PhantomData, prelude::*};
+
+#[cfg(test)]
+mod mock;
+
+// Re-export in crate namespace for `construct_runtime!`
+pub use pallet::*;
+
+/// Index of the `claim_swap` call in the pallet calls enum.
+pub const CLAIM_SWAP_CALL_INDEX: u8 = 1;
+/// Index of the `settle_swap` call in the pallet calls enum.
+pub const SETTLE_SWAP_CALL_INDEX: u8 = 4;
+
+/// Token swap between two chains.
+///
+/// The swap is always created at the source chain, so all `source_*` fields are related to the
+/// source chain and all `target_*` fields are related to the target chain.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct TokenSwap<SourceBlockNumber, SourceBalance, SourceAccountId, TargetBalance, TargetAccountId> {
+	/// Source chain block after which the swap may be cancelled by the source party.
+	pub cancel_after: SourceBlockNumber,
+	/// Account of the source party at the source chain.
+	pub source_account: SourceAccountId,
+	/// Amount of source tokens that the source party gives to the target party.
+	pub source_balance: SourceBalance,
+	/// Account of the target party at the target chain.
+	pub target_account: TargetAccountId,
+	/// Amount of target tokens that the target party gives to the source party.
+	pub target_balance: TargetBalance,
+}
+
+impl<SourceBlockNumber, SourceBalance, SourceAccountId, TargetBalance, TargetAccountId>
+	TokenSwap<SourceBlockNumber, SourceBalance, SourceAccountId, TargetBalance, TargetAccountId>
+where
+	Self: Encode,
+{
+	/// Return swap hash that is used as swap identifier at both chains.
+	pub fn hash(&self) -> H256 {
+		self.using_encoded(sp_io::hashing::blake2_256).into()
+	}
+}
+
+/// Return delivery deadline of the message that claims swap with given `cancel_after` block.
+///
+/// The swap may be claimed at the `cancel_after` block, so the message must be delivered before
+/// the next block.
+pub fn claim_message_delivery_deadline<SourceBlockNumber: AtLeast32BitUnsigned>(
+	cancel_after: SourceBlockNumber,
+) -> u64 {
+	cancel_after.saturating_add(One::one()).unique_saturated_into()
+}
+
+/// Calls of the token swap pallet at the bridged chain.
+pub trait BridgedTokenSwapCalls<Swap> {
+	/// Return encoded `claim_swap` call and its dispatch weight at the bridged chain.
+	///
+	/// The `this_chain_spec_version` is used by the bridged chain to send the swap result message
+	/// back to this chain.
+	fn claim_swap(swap: Swap, this_chain_spec_version: u32) -> (Vec<u8>, Weight);
+	/// Return encoded `settle_swap` call and its dispatch weight at the bridged chain.
+	fn settle_swap(swap_hash: H256, is_claimed: bool) -> (Vec<u8>, Weight);
+}
+
+/// `BridgedTokenSwapCalls` implementation for the case when the same token swap pallet is
+/// deployed at the bridged chain at `PalletIndex`, `claim_swap` call has `ClaimSwapWeight`
+/// weight and `settle_swap` call has `SettleSwapWeight` weight there.
+pub struct BridgedTokenSwapPalletCalls<PalletIndex, ClaimSwapWeight, SettleSwapWeight>(
+	PhantomData<(PalletIndex, ClaimSwapWeight, SettleSwapWeight)>,
+);
+
+impl<Swap, PalletIndex, ClaimSwapWeight, SettleSwapWeight> BridgedTokenSwapCalls<Swap>
+	for BridgedTokenSwapPalletCalls<PalletIndex, ClaimSwapWeight, SettleSwapWeight>
+where
+	Swap: Encode,
+	PalletIndex: Get<u8>,
+	ClaimSwapWeight: Get<Weight>,
+	SettleSwapWeight: Get<Weight>,
+{
+	fn claim_swap(swap: Swap, this_chain_spec_version: u32) -> (Vec<u8>, Weight) {
+		(
+			(PalletIndex::get(), CLAIM_SWAP_CALL_INDEX, swap, this_chain_spec_version).encode(),
+			ClaimSwapWeight::get(),
+		)
+	}
+
+	fn settle_swap(swap_hash: H256, is_claimed: bool) -> (Vec<u8>, Weight) {
+		(
+			(PalletIndex::get(), SETTLE_SWAP_CALL_INDEX, swap_hash, is_claimed).encode(),
+			SettleSwapWeight::get(),
+		)
+	}
+}
+
+/// Balance of this chain.
+pub type BalanceOf<T, I> =
+	<<T as Config<I>>::ThisCurrency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+/// Block number of the bridged chain.
+pub type BridgedBlockNumberOf<T, I> = BlockNumberOf<<T as Config<I>>::BridgedChain>;
+/// Swap that is created at this chain and needs to be accepted at the bridged chain.
+pub type TokenSwapOf<T, I> = TokenSwap<
+	<T as frame_system::Config>::BlockNumber,
+	BalanceOf<T, I>,
+	<T as frame_system::Config>::AccountId,
+	<T as Config<I>>::BridgedBalance,
+	<T as Config<I>>::BridgedAccountId,
+>;
+/// Swap that is created at the bridged chain and needs to be accepted at this chain.
+pub type BridgedTokenSwapOf<T, I> = TokenSwap<
+	BridgedBlockNumberOf<T, I>,
+	<T as Config<I>>::BridgedBalance,
+	<T as Config<I>>::BridgedAccountId,
+	BalanceOf<T, I>,
+	<T as frame_system::Config>::AccountId,
+>;
+/// Payload of the message that is sent to the bridged chain.
+pub type MessagePayloadOf<T, I> = MessagePayload<
+	<T as frame_system::Config>::AccountId,
+	<T as Config<I>>::BridgedAccountPublic,
+	<T as Config<I>>::BridgedAccountSignature,
+	Vec<u8>,
+>;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{pallet_prelude::*, transactional};
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config<I: 'static = ()>: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self, I>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Identifier of the token swap pallet. Used to derive the pallet account.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Identifier of the bridged chain. Used to derive accounts of bridged chain parties.
+		#[pallet::constant]
+		type BridgedChainId: Get<ChainId>;
+		/// Identifier of the lane that is used to send messages to the bridged chain.
+		#[pallet::constant]
+		type OutboundMessageLaneId: Get<LaneId>;
+
+		/// Messages bridge with the bridged chain.
+		type MessagesBridge: MessagesBridge<Self::AccountId, BalanceOf<Self, I>, MessagePayloadOf<Self, I>>;
+		/// Currency of this chain.
+		type ThisCurrency: Currency<Self::AccountId>;
+		/// Converter from derived account id (hash) to the account id of this chain. Used to derive
+		/// swap accounts and accounts of bridged chain parties.
+		type AccountIdConverter: Convert<H256, Self::AccountId>;
+
+		/// The chain we are bridging to here.
+		type BridgedChain: Chain;
+		/// Balance type of the bridged chain.
+		type BridgedBalance: Parameter;
+		/// Account id type of the bridged chain.
+		type BridgedAccountId: Parameter;
+		/// Account public key type of the bridged chain.
+		type BridgedAccountPublic: Parameter;
+		/// Account signature type of the bridged chain.
+		type BridgedAccountSignature: Parameter;
+		/// Account of the token swap pallet at the bridged chain.
+		type BridgedPalletAccountId: Get<Self::BridgedAccountId>;
+		/// Calls of the token swap pallet at the bridged chain.
+		type BridgedTokenSwapCalls: BridgedTokenSwapCalls<BridgedTokenSwapOf<Self, I>>;
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
+
+	#[pallet::hooks]
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {}
+
+	#[pallet::call]
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
+		/// Start token swap procedure.
+		///
+		/// Source tokens are moved from the source party account to the swap account. They're
+		/// released when the swap is claimed, or returned back when the swap is cancelled. The
+		/// `result_message_fee` is paid for delivering the swap result message to the bridged chain.
+		#[pallet::weight(T::DbWeight::get().reads_writes(5, 5))]
+		#[transactional]
+		pub fn create_swap(
+			origin: OriginFor<T>,
+			swap: TokenSwapOf<T, I>,
+			result_message_fee: BalanceOf<T, I>,
+		) -> DispatchResultWithPostInfo {
+			let source_account = ensure_signed(origin)?;
+			ensure!(
+				source_account == swap.source_account,
+				Error::<T, I>::MismatchedSwapSourceOrigin
+			);
+
+			let swap_hash = swap.hash();
+			ensure!(
+				!PendingSwaps::<T, I>::contains_key(swap_hash) && !SwapResults::<T, I>::contains_key(swap_hash),
+				Error::<T, I>::SwapAlreadyStarted
+			);
+
+			T::ThisCurrency::transfer(
+				&swap.source_account,
+				&swap_account_id::<T, I>(swap_hash),
+				swap.source_balance,
+				ExistenceRequirement::KeepAlive,
+			)
+			.map_err(|err| {
+				log::trace!(
+					target: "runtime::bridge-token-swap",
+					"Failed to transfer source tokens of swap {:?} to the swap account: {:?}",
+					swap_hash,
+					err,
+				);
+
+				Error::<T, I>::FailedToTransferToSwapAccount
+			})?;
+			T::ThisCurrency::transfer(
+				&swap.source_account,
+				&pallet_account_id::<T, I>(),
+				result_message_fee,
+				ExistenceRequirement::KeepAlive,
+			)
+			.map_err(|err| {
+				log::trace!(
+					target: "runtime::bridge-token-swap",
+					"Failed to transfer result message fee of swap {:?}: {:?}",
+					swap_hash,
+					err,
+				);
+
+				Error::<T, I>::FailedToTransferMessageFee
+			})?;
+
+			PendingSwaps::<T, I>::insert(swap_hash, swap);
+			ResultMessageFees::<T, I>::insert(swap_hash, result_message_fee);
+
+			log::trace!(target: "runtime::bridge-token-swap", "Created swap {:?}", swap_hash);
+
+			Self::deposit_event(Event::SwapCreated(swap_hash));
+
+			Ok(().into())
+		}
+
+		/// Claim previously created swap.
+		///
+		/// This call may only be dispatched by the bridge, when the swap is accepted by the target
+		/// party at the bridged chain. If the swap may not be cancelled yet, source tokens are moved
+		/// from the swap account to the account of the target party at this chain. Otherwise the
+		/// swap is cancelled. In both cases, the swap result is stored and the swap result message is
+		/// sent to the bridged chain. Failure to send the result message doesn't fail the call,
+		/// because the result may be resent later with `send_swap_result`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(6, 6))]
+		#[transactional]
+		pub fn claim_swap(
+			origin: OriginFor<T>,
+			swap: TokenSwapOf<T, I>,
+			bridged_chain_spec_version: u32,
+		) -> DispatchResultWithPostInfo {
+			let claimer = ensure_signed(origin)?;
+			ensure!(
+				claimer == bridged_pallet_account_id::<T, I>(),
+				Error::<T, I>::InvalidClaimOrigin
+			);
+
+			let swap_hash = swap.hash();
+			ensure!(
+				!SwapResults::<T, I>::contains_key(swap_hash),
+				Error::<T, I>::SwapIsAlreadyCompleted
+			);
+			// the fee is paid when the swap is created and it is removed when the swap is completed, so
+			// if it is missing, the swap has never been created at this chain
+			let result_message_fee =
+				ResultMessageFees::<T, I>::take(swap_hash).ok_or(Error::<T, I>::ResultMessageFeeIsMissing)?;
+
+			let is_claimed = if frame_system::Pallet::<T>::block_number() > swap.cancel_after {
+				cancel_pending_swap::<T, I>(swap_hash, &swap)?;
+
+				false
+			} else {
+				let target_account = T::AccountIdConverter::convert(derive_account_id(
+					T::BridgedChainId::get(),
+					SourceAccount::Account(swap.target_account.clone()),
+				));
+				complete_swap::<T, I>(swap_hash, &swap, &target_account, true)?;
+
+				log::trace!(target: "runtime::bridge-token-swap", "Claimed swap {:?}", swap_hash);
+
+				Self::deposit_event(Event::SwapClaimed(swap_hash));
+
+				true
+			};
+
+			// the result is stored, so it may be resent if we fail to send it now
+			let _ =
+				send_swap_result_message::<T, I>(swap_hash, is_claimed, bridged_chain_spec_version, result_message_fee);
+
+			Ok(().into())
+		}
+
+		/// Cancel previously created swap.
+		///
+		/// The swap may only be cancelled by the source party, after `swap.cancel_after` block.
+		/// Source tokens are returned back to the source party and the swap result message is sent
+		/// to the bridged chain, using the fee that has been paid when the swap has been created.
+		/// Failure to send the result message doesn't fail the call, because the result may be
+		/// resent later with `send_swap_result`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(5, 6))]
+		#[transactional]
+		pub fn cancel_swap(
+			origin: OriginFor<T>,
+			swap: TokenSwapOf<T, I>,
+			bridged_chain_spec_version: u32,
+		) -> DispatchResultWithPostInfo {
+			let source_account = ensure_signed(origin)?;
+			ensure!(
+				source_account == swap.source_account,
+				Error::<T, I>::MismatchedSwapSourceOrigin
+			);
+
+			let swap_hash = swap.hash();
+			ensure!(
+				PendingSwaps::<T, I>::contains_key(swap_hash),
+				Error::<T, I>::SwapIsNotPending
+			);
+			ensure!(
+				frame_system::Pallet::<T>::block_number() > swap.cancel_after,
+				Error::<T, I>::SwapCancellationIsTooEarly
+			);
+
+			let result_message_fee =
+				ResultMessageFees::<T, I>::take(swap_hash).ok_or(Error::<T, I>::ResultMessageFeeIsMissing)?;
+			cancel_pending_swap::<T, I>(swap_hash, &swap)?;
+
+			// the result is stored, so it may be resent if we fail to send it now
+			let _ = send_swap_result_message::<T, I>(swap_hash, false, bridged_chain_spec_version, result_message_fee);
+
+			Ok(().into())
+		}
+
+		/// Accept swap that has been created at the bridged chain.
+		///
+		/// Target tokens are moved from the target party account to the swap account. They're
+		/// released when the swap result message is received from the bridged chain. Then the
+		/// message that claims the swap is sent to the bridged chain. The target party pays
+		/// `message_fee` for delivering this message. If the message isn't delivered before the
+		/// `swap.cancel_after` block of the bridged chain, it is not dispatched and the swap is
+		/// cancelled.
+		#[pallet::weight(T::DbWeight::get().reads_writes(4, 5))]
+		#[transactional]
+		pub fn accept_swap(
+			origin: OriginFor<T>,
+			swap: BridgedTokenSwapOf<T, I>,
+			bridged_chain_spec_version: u32,
+			message_fee: BalanceOf<T, I>,
+		) -> DispatchResultWithPostInfo {
+			let target_account = ensure_signed(origin)?;
+			ensure!(
+				target_account == swap.target_account,
+				Error::<T, I>::MismatchedSwapTargetOrigin
+			);
+
+			let swap_hash = swap.hash();
+			ensure!(
+				!AcceptedSwaps::<T, I>::contains_key(swap_hash),
+				Error::<T, I>::SwapAlreadyAccepted
+			);
+
+			let transfer_result = T::ThisCurrency::transfer(
+				&swap.target_account,
+				&swap_account_id::<T, I>(swap_hash),
+				swap.target_balance,
+				ExistenceRequirement::KeepAlive,
+			)
+			.and_then(|_| {
+				T::ThisCurrency::transfer(
+					&swap.target_account,
+					&pallet_account_id::<T, I>(),
+					message_fee,
+					ExistenceRequirement::KeepAlive,
+				)
+			});
+			if let Err(err) = transfer_result {
+				log::trace!(
+					target: "runtime::bridge-token-swap",
+					"Failed to transfer target tokens of swap {:?}: {:?}",
+					swap_hash,
+					err,
+				);
+
+				return Err(Error::<T, I>::FailedToTransferToSwapAccount.into());
+			}
+
+			let this_chain_spec_version = T::Version::get().spec_version;
+			let (claim_call, claim_call_weight) =
+				T::BridgedTokenSwapCalls::claim_swap(swap.clone(), this_chain_spec_version);
+			let nonce = send_message_to_bridged_chain::<T, I>(
+				claim_call,
+				claim_call_weight,
+				Some(claim_message_delivery_deadline(swap.cancel_after)),
+				bridged_chain_spec_version,
+				message_fee,
+			)
+			.map_err(|err| {
+				log::trace!(
+					target: "runtime::bridge-token-swap",
+					"Failed to send claim message of swap {:?}: {:?}",
+					swap_hash,
+					err,
+				);
+
+				Error::<T, I>::FailedToSendClaimMessage
+			})?;
+
+			AcceptedSwaps::<T, I>::insert(swap_hash, nonce);
+			LockedSwaps::<T, I>::insert(swap_hash, swap);
+
+			log::trace!(
+				target: "runtime::bridge-token-swap",
+				"Accepted swap {:?}. Claim message nonce: {}",
+				swap_hash,
+				nonce,
+			);
+
+			Self::deposit_event(Event::SwapAccepted(swap_hash, nonce));
+
+			Ok(().into())
+		}
+
+		/// Settle swap that has been accepted at this chain.
+		///
+		/// This call may only be dispatched by the bridge, when the swap result message is
+		/// received from the bridged chain. If the swap has been claimed, target tokens are moved
+		/// from the swap account to the account of the source party at this chain. Otherwise, they
+		/// are returned back to the target party.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		#[transactional]
+		pub fn settle_swap(origin: OriginFor<T>, swap_hash: H256, is_claimed: bool) -> DispatchResultWithPostInfo {
+			let settler = ensure_signed(origin)?;
+			ensure!(
+				settler == bridged_pallet_account_id::<T, I>(),
+				Error::<T, I>::InvalidSettleOrigin
+			);
+
+			let swap = LockedSwaps::<T, I>::take(swap_hash).ok_or(Error::<T, I>::SwapIsNotLocked)?;
+			let destination = if is_claimed {
+				T::AccountIdConverter::convert(derive_account_id(
+					T::BridgedChainId::get(),
+					SourceAccount::Account(swap.source_account.clone()),
+				))
+			} else {
+				swap.target_account.clone()
+			};
+			T::ThisCurrency::transfer(
+				&swap_account_id::<T, I>(swap_hash),
+				&destination,
+				swap.target_balance,
+				ExistenceRequirement::AllowDeath,
+			)
+			.map_err(|err| {
+				log::trace!(
+					target: "runtime::bridge-token-swap",
+					"Failed to transfer target tokens of swap {:?} from the swap account: {:?}",
+					swap_hash,
+					err,
+				);
+
+				Error::<T, I>::FailedToTransferFromSwapAccount
+			})?;
+
+			log::trace!(
+				target: "runtime::bridge-token-swap",
+				"Settled swap {:?}. Claimed: {}",
+				swap_hash,
+				is_claimed,
+			);
+
+			Self::deposit_event(Event::SwapSettled(swap_hash, is_claimed));
+
+			Ok(().into())
+		}
+
+		/// Send result of the swap that has been created at this chain to the bridged chain.
+		///
+		/// Anyone may call this, paying `message_fee` for delivering the result message. If the swap
+		/// result is already known (the swap has been claimed or cancelled), it is resent. Otherwise,
+		/// if the `swap.cancel_after` block has been reached, the swap may no longer be claimed, so it
+		/// is cancelled (if it is still pending) and the cancellation result is sent. This is used to
+		/// unlock target tokens at the bridged chain if the claim message has never been dispatched
+		/// at this chain.
+		#[pallet::weight(T::DbWeight::get().reads_writes(6, 6))]
+		#[transactional]
+		pub fn send_swap_result(
+			origin: OriginFor<T>,
+			swap: TokenSwapOf<T, I>,
+			bridged_chain_spec_version: u32,
+			message_fee: BalanceOf<T, I>,
+		) -> DispatchResultWithPostInfo {
+			let submitter = ensure_signed(origin)?;
+
+			let swap_hash = swap.hash();
+			let mut result_message_fee = message_fee;
+			let is_claimed = match SwapResults::<T, I>::get(swap_hash) {
+				Some(is_claimed) => is_claimed,
+				None => {
+					ensure!(
+						frame_system::Pallet::<T>::block_number() > swap.cancel_after,
+						Error::<T, I>::SwapResultIsUnknown
+					);
+
+					// the claim message may not be dispatched after `cancel_after` block, so the swap is
+					// cancelled
+					match ResultMessageFees::<T, I>::take(swap_hash) {
+						Some(paid_result_message_fee) => {
+							cancel_pending_swap::<T, I>(swap_hash, &swap)?;
+							result_message_fee = result_message_fee.saturating_add(paid_result_message_fee);
+						}
+						None => {
+							// the swap has never been created at this chain
+							SwapResults::<T, I>::insert(swap_hash, false);
+						}
+					}
+
+					false
+				}
+			};
+
+			T::ThisCurrency::transfer(
+				&submitter,
+				&pallet_account_id::<T, I>(),
+				message_fee,
+				ExistenceRequirement::KeepAlive,
+			)
+			.map_err(|err| {
+				log::trace!(
+					target: "runtime::bridge-token-swap",
+					"Failed to transfer result message fee of swap {:?}: {:?}",
+					swap_hash,
+					err,
+				);
+
+				Error::<T, I>::FailedToTransferMessageFee
+			})?;
+
+			send_swap_result_message::<T, I>(swap_hash, is_claimed, bridged_chain_spec_version, result_message_fee)?;
+
+			Ok(().into())
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config<I>, I: 'static = ()> {
+		/// Swap has been created. [swap_hash]
+		SwapCreated(H256),
+		/// Swap has been claimed. [swap_hash]
+		SwapClaimed(H256),
+		/// Swap has been cancelled. [swap_hash]
+		SwapCancelled(H256),
+		/// Swap result has been sent to the bridged chain. [swap_hash, is_claimed, message_nonce]
+		SwapResultSent(H256, bool, MessageNonce),
+		/// Swap, created at the bridged chain, has been accepted. [swap_hash, message_nonce]
+		SwapAccepted(H256, MessageNonce),
+		/// Swap, created at the bridged chain, has been settled. [swap_hash, is_claimed]
+		SwapSettled(H256, bool),
+	}
+
+	#[pallet::error]
+	pub enum Error<T, I = ()> {
+		/// Swap may only be created or cancelled by its source party.
+		MismatchedSwapSourceOrigin,
+		/// Swap may only be accepted by its target party.
+		MismatchedSwapTargetOrigin,
+		/// Swap with the same hash has already been created (and maybe completed).
+		SwapAlreadyStarted,
+		/// Swap with the same hash has already been accepted.
+		SwapAlreadyAccepted,
+		/// Swap is not pending (it has never been created, or it has been claimed or cancelled).
+		SwapIsNotPending,
+		/// Swap is not locked (it has never been accepted, or it has been settled).
+		SwapIsNotLocked,
+		/// Swap may only be claimed by the token swap pallet at the bridged chain.
+		InvalidClaimOrigin,
+		/// Swap may only be settled by the token swap pallet at the bridged chain.
+		InvalidSettleOrigin,
+		/// Swap may not be cancelled before `swap.cancel_after` block.
+		SwapCancellationIsTooEarly,
+		/// Failed to transfer tokens to the swap account.
+		FailedToTransferToSwapAccount,
+		/// Failed to transfer tokens from the swap account.
+		FailedToTransferFromSwapAccount,
+		/// Failed to transfer message fee to the pallet account.
+		FailedToTransferMessageFee,
+		/// Failed to send message that claims the swap.
+		FailedToSendClaimMessage,
+		/// Failed to send message with the swap result.
+		FailedToSendResultMessage,
+		/// The swap has never been created at this chain, so there's no fee for the result message.
+		ResultMessageFeeIsMissing,
+		/// The swap has already been claimed or cancelled.
+		SwapIsAlreadyCompleted,
+		/// The swap result is not known yet (the swap may still be claimed).
+		SwapResultIsUnknown,
+	}
+
+	/// Swaps that have been created at this chain and are waiting to be claimed or cancelled.
+	#[pallet::storage]
+	pub(super) type PendingSwaps<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, H256, TokenSwapOf<T, I>, OptionQuery>;
+
+	/// Fees, paid by source parties of swaps that have been created at this chain, for delivering
+	/// swap result messages to the bridged chain.
+	#[pallet::storage]
+	pub(super) type ResultMessageFees<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, H256, BalanceOf<T, I>, OptionQuery>;
+
+	/// Results of swaps that have been completed at this chain: `true` if the swap has been claimed
+	/// and `false` if it has been cancelled. Results are kept, so that the swap result message may
+	/// be resent to the bridged chain.
+	#[pallet::storage]
+	pub(super) type SwapResults<T: Config<I>, I: 'static = ()> = StorageMap<_, Identity, H256, bool, OptionQuery>;
+
+	/// Swaps that have been created at the bridged chain and accepted at this chain, mapped to
+	/// nonces of their claim messages.
+	#[pallet::storage]
+	pub(super) type AcceptedSwaps<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, H256, MessageNonce, OptionQuery>;
+
+	/// Swaps that have been created at the bridged chain and accepted at this chain, and are
+	/// waiting for the swap result message.
+	#[pallet::storage]
+	pub(super) type LockedSwaps<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, H256, BridgedTokenSwapOf<T, I>, OptionQuery>;
+}
+
+/// Return account of the token swap pallet at this chain.
+pub fn pallet_account_id<T: Config<I>, I: 'static>() -> T::AccountId {
+	T::PalletId::get().into_account()
+}
+
+/// Return account that holds source tokens of given swap.
+pub fn swap_account_id<T: Config<I>, I: 'static>(swap_hash: H256) -> T::AccountId {
+	T::AccountIdConverter::convert(swap_hash)
+}
+
+/// Return account that represents the token swap pallet of the bridged chain at this chain.
+fn bridged_pallet_account_id<T: Config<I>, I: 'static>() -> T::AccountId {
+	T::AccountIdConverter::convert(derive_account_id(
+		T::BridgedChainId::get(),
+		SourceAccount::Account(T::BridgedPalletAccountId::get()),
+	))
+}
+
+/// Send message to the token swap pallet at the bridged chain.
+fn send_message_to_bridged_chain<T: Config<I>, I: 'static>(
+	call: Vec<u8>,
+	call_weight: Weight,
+	delivery_deadline: Option<u64>,
+	bridged_chain_spec_version: u32,
+	message_fee: BalanceOf<T, I>,
+) -> Result<
+	MessageNonce,
+	<T::MessagesBridge as MessagesBridge<T::AccountId, BalanceOf<T, I>, MessagePayloadOf<T, I>>>::Error,
+> {
+	let pallet_account = pallet_account_id::<T, I>();
+	T::MessagesBridge::send_message(
+		Sender::<T::AccountId>::Signed(pallet_account.clone()),
+		T::OutboundMessageLaneId::get(),
+		MessagePayload {
+			spec_version: bridged_chain_spec_version,
+			weight: call_weight,
+			delivery_deadline,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+			origin: CallOrigin::SourceAccount(pallet_account),
+			call,
+		},
+		message_fee,
+	)
+}
+
+/// Send swap result message to the token swap pallet at the bridged chain.
+fn send_swap_result_message<T: Config<I>, I: 'static>(
+	swap_hash: H256,
+	is_claimed: bool,
+	bridged_chain_spec_version: u32,
+	message_fee: BalanceOf<T, I>,
+) -> Result<(), Error<T, I>> {
+	let (settle_call, settle_call_weight) = T::BridgedTokenSwapCalls::settle_swap(swap_hash, is_claimed);
+	let nonce = send_message_to_bridged_chain::<T, I>(
+		settle_call,
+		settle_call_weight,
+		None,
+		bridged_chain_spec_version,
+		message_fee,
+	)
+	.map_err(|err| {
+		log::trace!(
+			target: "runtime::bridge-token-swap",
+			"Failed to send result message of swap {:?}: {:?}",
+			swap_hash,
+			err,
+		);
+
+		Error::<T, I>::FailedToSendResultMessage
+	})?;
+
+	Pallet::<T, I>::deposit_event(Event::SwapResultSent(swap_hash, is_claimed, nonce));
+
+	Ok(())
+}
+
+/// Return source tokens of the pending swap back to the source party.
+fn cancel_pending_swap<T: Config<I>, I: 'static>(swap_hash: H256, swap: &TokenSwapOf<T, I>) -> Result<(), Error<T, I>> {
+	complete_swap::<T, I>(swap_hash, swap, &swap.source_account, false)?;
+
+	log::trace!(target: "runtime::bridge-token-swap", "Cancelled swap {:?}", swap_hash);
+
+	Pallet::<T, I>::deposit_event(Event::SwapCancelled(swap_hash));
+
+	Ok(())
+}
+
+/// Move all source tokens from the swap account to given account and remember the swap result.
+fn complete_swap<T: Config<I>, I: 'static>(
+	swap_hash: H256,
+	swap: &TokenSwapOf<T, I>,
+	destination: &T::AccountId,
+	is_claimed: bool,
+) -> Result<(), Error<T, I>> {
+	T::ThisCurrency::transfer(
+		&swap_account_id::<T, I>(swap_hash),
+		destination,
+		swap.source_balance,
+		ExistenceRequirement::AllowDeath,
+	)
+	.map_err(|err| {
+		log::trace!(
+			target: "runtime::bridge-token-swap",
+			"Failed to transfer source tokens of swap {:?} from the swap account: {:?}",
+			swap_hash,
+			err,
+		);
+
+		Error::<T, I>::FailedToTransferFromSwapAccount
+	})?;
+
+	PendingSwaps::<T, I>::remove(swap_hash);
+	SwapResults::<T, I>::insert(swap_hash, is_claimed);
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::*;
+	use frame_support::{assert_noop, assert_ok};
+
+	const CAN_CANCEL_AFTER: u64 = 10;
+
+	fn test_swap() -> TokenSwapOf<TestRuntime, ()> {
+		TokenSwap {
+			cancel_after: CAN_CANCEL_AFTER,
+			source_account: THIS_CHAIN_ACCOUNT,
+			source_balance: 100,
+			target_account: BRIDGED_CHAIN_ACCOUNT,
+			target_balance: 200,
+		}
+	}
+
+	fn test_bridged_swap() -> BridgedTokenSwapOf<TestRuntime, ()> {
+		TokenSwap {
+			cancel_after: CAN_CANCEL_AFTER,
+			source_account: BRIDGED_CHAIN_ACCOUNT,
+			source_balance: 200,
+			target_account: THIS_CHAIN_ACCOUNT,
+			target_balance: 100,
+		}
+	}
+
+	fn bridged_pallet_origin() -> Origin {
+		Origin::signed(bridged_pallet_account_id::<TestRuntime, ()>())
+	}
+
+	fn target_account_at_this_chain() -> AccountId {
+		TestAccountIdConverter::convert(derive_account_id(
+			BRIDGED_CHAIN_ID,
+			SourceAccount::Account(BRIDGED_CHAIN_ACCOUNT),
+		))
+	}
+
+	fn create_test_swap() {
+		assert_ok!(Pallet::<TestRuntime>::create_swap(
+			Origin::signed(THIS_CHAIN_ACCOUNT),
+			test_swap(),
+			MESSAGE_FEE,
+		));
+	}
+
+	fn accept_test_bridged_swap() {
+		assert_ok!(Pallet::<TestRuntime>::accept_swap(
+			Origin::signed(THIS_CHAIN_ACCOUNT),
+			test_bridged_swap(),
+			BRIDGED_CHAIN_SPEC_VERSION,
+			MESSAGE_FEE,
+		));
+	}
+
+	fn source_account_at_this_chain() -> AccountId {
+		TestAccountIdConverter::convert(derive_account_id(
+			BRIDGED_CHAIN_ID,
+			SourceAccount::Account(BRIDGED_CHAIN_ACCOUNT),
+		))
+	}
+
+	fn settle_call(swap_hash: H256, is_claimed: bool) -> MessagePayloadOf<TestRuntime, ()> {
+		let (settle_call, settle_call_weight) =
+			<TestRuntime as Config>::BridgedTokenSwapCalls::settle_swap(swap_hash, is_claimed);
+		MessagePayload {
+			spec_version: BRIDGED_CHAIN_SPEC_VERSION,
+			weight: settle_call_weight,
+			delivery_deadline: None,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+			origin: CallOrigin::SourceAccount(pallet_account_id::<TestRuntime, ()>()),
+			call: settle_call,
+		}
+	}
+
+	#[test]
+	fn create_swap_fails_if_origin_is_not_swap_source() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::create_swap(Origin::signed(THIS_CHAIN_ACCOUNT + 1), test_swap(), MESSAGE_FEE),
+				Error::<TestRuntime, ()>::MismatchedSwapSourceOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn create_swap_fails_if_source_has_not_enough_funds() {
+		run_test(|| {
+			let mut swap = test_swap();
+			swap.source_balance = THIS_CHAIN_ACCOUNT_BALANCE;
+			assert_noop!(
+				Pallet::<TestRuntime>::create_swap(Origin::signed(THIS_CHAIN_ACCOUNT), swap, MESSAGE_FEE),
+				Error::<TestRuntime, ()>::FailedToTransferToSwapAccount,
+			);
+		});
+	}
+
+	#[test]
+	fn create_swap_fails_if_swap_is_already_started() {
+		run_test(|| {
+			create_test_swap();
+			assert_noop!(
+				Pallet::<TestRuntime>::create_swap(Origin::signed(THIS_CHAIN_ACCOUNT), test_swap(), MESSAGE_FEE),
+				Error::<TestRuntime, ()>::SwapAlreadyStarted,
+			);
+		});
+	}
+
+	#[test]
+	fn create_swap_locks_source_tokens() {
+		run_test(|| {
+			create_test_swap();
+
+			let swap_hash = test_swap().hash();
+			assert_eq!(PendingSwaps::<TestRuntime>::get(swap_hash), Some(test_swap()));
+			assert_eq!(
+				Balances::free_balance(&swap_account_id::<TestRuntime, ()>(swap_hash)),
+				test_swap().source_balance,
+			);
+			assert_eq!(
+				Balances::free_balance(&THIS_CHAIN_ACCOUNT),
+				THIS_CHAIN_ACCOUNT_BALANCE - test_swap().source_balance - MESSAGE_FEE,
+			);
+			assert_eq!(ResultMessageFees::<TestRuntime>::get(swap_hash), Some(MESSAGE_FEE));
+			assert_eq!(
+				Balances::free_balance(&pallet_account_id::<TestRuntime, ()>()),
+				MESSAGE_FEE
+			);
+		});
+	}
+
+	#[test]
+	fn create_swap_fails_if_source_has_not_enough_funds_to_pay_result_message_fee() {
+		run_test(|| {
+			let mut swap = test_swap();
+			swap.source_balance = THIS_CHAIN_ACCOUNT_BALANCE - MESSAGE_FEE;
+			assert_noop!(
+				Pallet::<TestRuntime>::create_swap(Origin::signed(THIS_CHAIN_ACCOUNT), swap, MESSAGE_FEE),
+				Error::<TestRuntime, ()>::FailedToTransferMessageFee,
+			);
+		});
+	}
+
+	#[test]
+	fn claim_swap_fails_if_origin_is_not_bridged_pallet() {
+		run_test(|| {
+			create_test_swap();
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_swap(
+					Origin::signed(target_account_at_this_chain()),
+					test_swap(),
+					BRIDGED_CHAIN_SPEC_VERSION,
+				),
+				Error::<TestRuntime, ()>::InvalidClaimOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn claim_swap_fails_if_swap_has_never_been_created() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_swap(bridged_pallet_origin(), test_swap(), BRIDGED_CHAIN_SPEC_VERSION),
+				Error::<TestRuntime, ()>::ResultMessageFeeIsMissing,
+			);
+			assert_eq!(sent_message(), None);
+		});
+	}
+
+	#[test]
+	fn claim_swap_fails_if_swap_is_already_cancelled() {
+		run_test(|| {
+			create_test_swap();
+			frame_system::Pallet::<TestRuntime>::set_block_number(CAN_CANCEL_AFTER + 1);
+			assert_ok!(Pallet::<TestRuntime>::cancel_swap(
+				Origin::signed(THIS_CHAIN_ACCOUNT),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION,
+			));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_swap(bridged_pallet_origin(), test_swap(), BRIDGED_CHAIN_SPEC_VERSION),
+				Error::<TestRuntime, ()>::SwapIsAlreadyCompleted,
+			);
+			assert_eq!(Balances::free_balance(&target_account_at_this_chain()), 0);
+		});
+	}
+
+	#[test]
+	fn claim_swap_fails_if_swap_is_already_claimed() {
+		run_test(|| {
+			create_test_swap();
+			assert_ok!(Pallet::<TestRuntime>::claim_swap(
+				bridged_pallet_origin(),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION
+			));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_swap(bridged_pallet_origin(), test_swap(), BRIDGED_CHAIN_SPEC_VERSION),
+				Error::<TestRuntime, ()>::SwapIsAlreadyCompleted,
+			);
+		});
+	}
+
+	#[test]
+	fn claim_swap_stores_result_if_result_message_is_rejected() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::create_swap(
+				Origin::signed(THIS_CHAIN_ACCOUNT),
+				test_swap(),
+				REJECTED_MESSAGE_FEE,
+			));
+			assert_ok!(Pallet::<TestRuntime>::claim_swap(
+				bridged_pallet_origin(),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION
+			));
+
+			let swap_hash = test_swap().hash();
+			assert_eq!(SwapResults::<TestRuntime>::get(swap_hash), Some(true));
+			assert_eq!(
+				Balances::free_balance(&target_account_at_this_chain()),
+				test_swap().source_balance,
+			);
+			assert_eq!(sent_message(), None);
+
+			// the result may be resent later
+			assert_ok!(Pallet::<TestRuntime>::send_swap_result(
+				Origin::signed(THIS_CHAIN_ACCOUNT),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION,
+				MESSAGE_FEE,
+			));
+			assert_eq!(sent_message(), Some(settle_call(swap_hash, true)));
+		});
+	}
+
+	#[test]
+	fn claim_swap_cancels_expired_swap() {
+		run_test(|| {
+			create_test_swap();
+			frame_system::Pallet::<TestRuntime>::set_block_number(CAN_CANCEL_AFTER + 1);
+			assert_ok!(Pallet::<TestRuntime>::claim_swap(
+				bridged_pallet_origin(),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION
+			));
+
+			let swap_hash = test_swap().hash();
+			assert!(!PendingSwaps::<TestRuntime>::contains_key(swap_hash));
+			assert_eq!(SwapResults::<TestRuntime>::get(swap_hash), Some(false));
+			assert_eq!(
+				Balances::free_balance(&THIS_CHAIN_ACCOUNT),
+				THIS_CHAIN_ACCOUNT_BALANCE - MESSAGE_FEE
+			);
+			assert_eq!(Balances::free_balance(&target_account_at_this_chain()), 0);
+			assert_eq!(sent_message(), Some(settle_call(swap_hash, false)));
+		});
+	}
+
+	#[test]
+	fn claim_swap_releases_source_tokens() {
+		run_test(|| {
+			create_test_swap();
+			assert_ok!(Pallet::<TestRuntime>::claim_swap(
+				bridged_pallet_origin(),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION
+			));
+
+			let swap_hash = test_swap().hash();
+			assert!(!PendingSwaps::<TestRuntime>::contains_key(swap_hash));
+			assert_eq!(
+				Balances::free_balance(&swap_account_id::<TestRuntime, ()>(swap_hash)),
+				0
+			);
+			assert_eq!(
+				Balances::free_balance(&target_account_at_this_chain()),
+				test_swap().source_balance,
+			);
+			assert!(!ResultMessageFees::<TestRuntime>::contains_key(swap_hash));
+			assert_eq!(SwapResults::<TestRuntime>::get(swap_hash), Some(true));
+			assert_eq!(sent_message(), Some(settle_call(swap_hash, true)));
+		});
+	}
+
+	#[test]
+	fn cancel_swap_fails_if_origin_is_not_swap_source() {
+		run_test(|| {
+			create_test_swap();
+			frame_system::Pallet::<TestRuntime>::set_block_number(CAN_CANCEL_AFTER + 1);
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_swap(
+					Origin::signed(THIS_CHAIN_ACCOUNT + 1),
+					test_swap(),
+					BRIDGED_CHAIN_SPEC_VERSION,
+				),
+				Error::<TestRuntime, ()>::MismatchedSwapSourceOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn cancel_swap_fails_if_it_is_too_early() {
+		run_test(|| {
+			create_test_swap();
+			frame_system::Pallet::<TestRuntime>::set_block_number(CAN_CANCEL_AFTER);
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_swap(
+					Origin::signed(THIS_CHAIN_ACCOUNT),
+					test_swap(),
+					BRIDGED_CHAIN_SPEC_VERSION,
+				),
+				Error::<TestRuntime, ()>::SwapCancellationIsTooEarly,
+			);
+		});
+	}
+
+	#[test]
+	fn cancel_swap_fails_if_swap_is_already_claimed() {
+		run_test(|| {
+			create_test_swap();
+			assert_ok!(Pallet::<TestRuntime>::claim_swap(
+				bridged_pallet_origin(),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION
+			));
+			frame_system::Pallet::<TestRuntime>::set_block_number(CAN_CANCEL_AFTER + 1);
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_swap(
+					Origin::signed(THIS_CHAIN_ACCOUNT),
+					test_swap(),
+					BRIDGED_CHAIN_SPEC_VERSION,
+				),
+				Error::<TestRuntime, ()>::SwapIsNotPending,
+			);
+		});
+	}
+
+	#[test]
+	fn cancel_swap_returns_source_tokens() {
+		run_test(|| {
+			create_test_swap();
+			frame_system::Pallet::<TestRuntime>::set_block_number(CAN_CANCEL_AFTER + 1);
+			assert_ok!(Pallet::<TestRuntime>::cancel_swap(
+				Origin::signed(THIS_CHAIN_ACCOUNT),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION,
+			));
+
+			let swap_hash = test_swap().hash();
+			assert!(!PendingSwaps::<TestRuntime>::contains_key(swap_hash));
+			assert_eq!(
+				Balances::free_balance(&THIS_CHAIN_ACCOUNT),
+				THIS_CHAIN_ACCOUNT_BALANCE - MESSAGE_FEE
+			);
+			// the fee is spent on the result message
+			assert!(!ResultMessageFees::<TestRuntime>::contains_key(swap_hash));
+			assert_eq!(SwapResults::<TestRuntime>::get(swap_hash), Some(false));
+			assert_eq!(sent_message(), Some(settle_call(swap_hash, false)));
+		});
+	}
+
+	#[test]
+	fn create_swap_fails_if_swap_is_already_completed() {
+		run_test(|| {
+			create_test_swap();
+			frame_system::Pallet::<TestRuntime>::set_block_number(CAN_CANCEL_AFTER + 1);
+			assert_ok!(Pallet::<TestRuntime>::cancel_swap(
+				Origin::signed(THIS_CHAIN_ACCOUNT),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION,
+			));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::create_swap(Origin::signed(THIS_CHAIN_ACCOUNT), test_swap(), MESSAGE_FEE),
+				Error::<TestRuntime, ()>::SwapAlreadyStarted,
+			);
+		});
+	}
+
+	#[test]
+	fn send_swap_result_fails_if_swap_may_still_be_claimed() {
+		run_test(|| {
+			create_test_swap();
+			frame_system::Pallet::<TestRuntime>::set_block_number(CAN_CANCEL_AFTER);
+			assert_noop!(
+				Pallet::<TestRuntime>::send_swap_result(
+					Origin::signed(THIS_CHAIN_ACCOUNT),
+					test_swap(),
+					BRIDGED_CHAIN_SPEC_VERSION,
+					MESSAGE_FEE,
+				),
+				Error::<TestRuntime, ()>::SwapResultIsUnknown,
+			);
+		});
+	}
+
+	#[test]
+	fn send_swap_result_fails_if_message_is_rejected() {
+		run_test(|| {
+			create_test_swap();
+			assert_ok!(Pallet::<TestRuntime>::claim_swap(
+				bridged_pallet_origin(),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::send_swap_result(
+					Origin::signed(THIS_CHAIN_ACCOUNT),
+					test_swap(),
+					BRIDGED_CHAIN_SPEC_VERSION,
+					REJECTED_MESSAGE_FEE,
+				),
+				Error::<TestRuntime, ()>::FailedToSendResultMessage,
+			);
+		});
+	}
+
+	#[test]
+	fn send_swap_result_resends_known_result() {
+		run_test(|| {
+			create_test_swap();
+			assert_ok!(Pallet::<TestRuntime>::claim_swap(
+				bridged_pallet_origin(),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION
+			));
+			clear_sent_message();
+
+			let balance_before = Balances::free_balance(&THIS_CHAIN_ACCOUNT);
+			assert_ok!(Pallet::<TestRuntime>::send_swap_result(
+				Origin::signed(THIS_CHAIN_ACCOUNT),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION,
+				MESSAGE_FEE,
+			));
+
+			assert_eq!(
+				Balances::free_balance(&THIS_CHAIN_ACCOUNT),
+				balance_before - MESSAGE_FEE
+			);
+			assert_eq!(sent_message(), Some(settle_call(test_swap().hash(), true)));
+		});
+	}
+
+	#[test]
+	fn send_swap_result_cancels_expired_pending_swap() {
+		run_test(|| {
+			create_test_swap();
+			frame_system::Pallet::<TestRuntime>::set_block_number(CAN_CANCEL_AFTER + 1);
+			assert_ok!(Pallet::<TestRuntime>::send_swap_result(
+				Origin::signed(THIS_CHAIN_ACCOUNT),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION,
+				MESSAGE_FEE,
+			));
+
+			let swap_hash = test_swap().hash();
+			assert!(!PendingSwaps::<TestRuntime>::contains_key(swap_hash));
+			assert!(!ResultMessageFees::<TestRuntime>::contains_key(swap_hash));
+			assert_eq!(SwapResults::<TestRuntime>::get(swap_hash), Some(false));
+			assert_eq!(
+				Balances::free_balance(&swap_account_id::<TestRuntime, ()>(swap_hash)),
+				0
+			);
+			assert_eq!(sent_message(), Some(settle_call(swap_hash, false)));
+		});
+	}
+
+	#[test]
+	fn send_swap_result_cancels_expired_swap_that_has_never_been_created() {
+		run_test(|| {
+			frame_system::Pallet::<TestRuntime>::set_block_number(CAN_CANCEL_AFTER + 1);
+			assert_ok!(Pallet::<TestRuntime>::send_swap_result(
+				Origin::signed(THIS_CHAIN_ACCOUNT),
+				test_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION,
+				MESSAGE_FEE,
+			));
+
+			let swap_hash = test_swap().hash();
+			assert_eq!(SwapResults::<TestRuntime>::get(swap_hash), Some(false));
+			assert_eq!(sent_message(), Some(settle_call(swap_hash, false)));
+
+			// the swap may not be created and claimed after it has been cancelled
+			assert_noop!(
+				Pallet::<TestRuntime>::create_swap(Origin::signed(THIS_CHAIN_ACCOUNT), test_swap(), MESSAGE_FEE),
+				Error::<TestRuntime, ()>::SwapAlreadyStarted,
+			);
+		});
+	}
+
+	#[test]
+	fn accept_swap_fails_if_origin_is_not_swap_target() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::accept_swap(
+					Origin::signed(THIS_CHAIN_ACCOUNT + 1),
+					test_bridged_swap(),
+					BRIDGED_CHAIN_SPEC_VERSION,
+					MESSAGE_FEE,
+				),
+				Error::<TestRuntime, ()>::MismatchedSwapTargetOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn accept_swap_fails_if_message_is_rejected() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::accept_swap(
+					Origin::signed(THIS_CHAIN_ACCOUNT),
+					test_bridged_swap(),
+					BRIDGED_CHAIN_SPEC_VERSION,
+					REJECTED_MESSAGE_FEE,
+				),
+				Error::<TestRuntime, ()>::FailedToSendClaimMessage,
+			);
+		});
+	}
+
+	#[test]
+	fn accept_swap_locks_target_tokens_and_sends_claim_message() {
+		run_test(|| {
+			accept_test_bridged_swap();
+
+			let swap_hash = test_bridged_swap().hash();
+			assert_eq!(Balances::free_balance(&source_account_at_this_chain()), 0);
+			assert_eq!(
+				Balances::free_balance(&swap_account_id::<TestRuntime, ()>(swap_hash)),
+				test_bridged_swap().target_balance,
+			);
+			assert_eq!(AcceptedSwaps::<TestRuntime>::get(swap_hash), Some(MESSAGE_NONCE));
+			assert_eq!(LockedSwaps::<TestRuntime>::get(swap_hash), Some(test_bridged_swap()));
+
+			let (claim_call, claim_call_weight) = <TestRuntime as Config>::BridgedTokenSwapCalls::claim_swap(
+				test_bridged_swap(),
+				<TestRuntime as frame_system::Config>::Version::get().spec_version,
+			);
+			assert_eq!(
+				sent_message(),
+				Some(MessagePayload {
+					spec_version: BRIDGED_CHAIN_SPEC_VERSION,
+					weight: claim_call_weight,
+					delivery_deadline: Some(CAN_CANCEL_AFTER + 1),
+					dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+					origin: CallOrigin::SourceAccount(pallet_account_id::<TestRuntime, ()>()),
+					call: claim_call,
+				}),
+			);
+		});
+	}
+
+	#[test]
+	fn accept_swap_fails_if_swap_is_already_accepted() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::accept_swap(
+				Origin::signed(THIS_CHAIN_ACCOUNT),
+				test_bridged_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION,
+				MESSAGE_FEE,
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::accept_swap(
+					Origin::signed(THIS_CHAIN_ACCOUNT),
+					test_bridged_swap(),
+					BRIDGED_CHAIN_SPEC_VERSION,
+					MESSAGE_FEE,
+				),
+				Error::<TestRuntime, ()>::SwapAlreadyAccepted,
+			);
+		});
+	}
+
+	#[test]
+	fn settle_swap_fails_if_origin_is_not_bridged_pallet() {
+		run_test(|| {
+			accept_test_bridged_swap();
+			assert_noop!(
+				Pallet::<TestRuntime>::settle_swap(
+					Origin::signed(source_account_at_this_chain()),
+					test_bridged_swap().hash(),
+					true,
+				),
+				Error::<TestRuntime, ()>::InvalidSettleOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn settle_swap_fails_if_swap_is_not_locked() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::settle_swap(bridged_pallet_origin(), test_bridged_swap().hash(), true),
+				Error::<TestRuntime, ()>::SwapIsNotLocked,
+			);
+		});
+	}
+
+	#[test]
+	fn settle_swap_releases_target_tokens_if_swap_is_claimed() {
+		run_test(|| {
+			accept_test_bridged_swap();
+			let swap_hash = test_bridged_swap().hash();
+			assert_ok!(Pallet::<TestRuntime>::settle_swap(
+				bridged_pallet_origin(),
+				swap_hash,
+				true
+			));
+
+			assert!(!LockedSwaps::<TestRuntime>::contains_key(swap_hash));
+			assert_eq!(
+				Balances::free_balance(&swap_account_id::<TestRuntime, ()>(swap_hash)),
+				0
+			);
+			assert_eq!(
+				Balances::free_balance(&source_account_at_this_chain()),
+				test_bridged_swap().target_balance,
+			);
+		});
+	}
+
+	#[test]
+	fn settle_swap_returns_target_tokens_if_swap_is_cancelled() {
+		run_test(|| {
+			accept_test_bridged_swap();
+			let swap_hash = test_bridged_swap().hash();
+			assert_ok!(Pallet::<TestRuntime>::settle_swap(
+				bridged_pallet_origin(),
+				swap_hash,
+				false
+			));
+
+			assert!(!LockedSwaps::<TestRuntime>::contains_key(swap_hash));
+			assert_eq!(Balances::free_balance(&source_account_at_this_chain()), 0);
+			assert_eq!(
+				Balances::free_balance(&THIS_CHAIN_ACCOUNT),
+				THIS_CHAIN_ACCOUNT_BALANCE - MESSAGE_FEE,
+			);
+		});
+	}
+
+	#[test]
+	fn settle_swap_fails_if_swap_is_already_settled() {
+		run_test(|| {
+			accept_test_bridged_swap();
+			let swap_hash = test_bridged_swap().hash();
+			assert_ok!(Pallet::<TestRuntime>::settle_swap(
+				bridged_pallet_origin(),
+				swap_hash,
+				true
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::settle_swap(bridged_pallet_origin(), swap_hash, false),
+				Error::<TestRuntime, ()>::SwapIsNotLocked,
+			);
+		});
+	}
+
+	#[test]
+	fn claim_swap_call_is_encoded_as_pallet_call() {
+		run_test(|| {
+			let (claim_call, _) = <TestRuntime as Config>::BridgedTokenSwapCalls::claim_swap(
+				test_bridged_swap(),
+				BRIDGED_CHAIN_SPEC_VERSION,
+			);
+			assert_eq!(
+				claim_call,
+				Call::TokenSwap(crate::Call::claim_swap(
+					test_swap_from_bridged(test_bridged_swap()),
+					BRIDGED_CHAIN_SPEC_VERSION,
+				))
+				.encode(),
+			);
+		});
+	}
+
+	#[test]
+	fn settle_swap_call_is_encoded_as_pallet_call() {
+		run_test(|| {
+			let swap_hash = test_bridged_swap().hash();
+			let (settle_call, _) = <TestRuntime as Config>::BridgedTokenSwapCalls::settle_swap(swap_hash, true);
+			assert_eq!(
+				settle_call,
+				Call::TokenSwap(crate::Call::settle_swap(swap_hash, true)).encode(),
+			);
+		});
+	}
+
+	/// In mock, both chains are using the same types, so we may reinterpret bridged swap as
+	/// the swap of this chain.
+	fn test_swap_from_bridged(swap: BridgedTokenSwapOf<TestRuntime, ()>) -> TokenSwapOf<TestRuntime, ()> {
+		Decode::decode(&mut &swap.encode()[..]).unwrap()
+	}
+}
@@ -0,0 +1,214 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+// From construct_runtime macro
+#![allow(clippy::from_over_into)]
+
+use crate::MessagePayloadOf;
+
+use bp_messages::{
+	source_chain::{MessagesBridge, Sender},
+	LaneId, MessageNonce,
+};
+use bp_runtime::{Chain, ChainId};
+use codec::{Decode, Encode};
+use frame_support::{construct_runtime, parameter_types, weights::Weight, PalletId};
+use sp_runtime::{
+	testing::{Header, H256},
+	traits::{BlakeTwo256, Convert, IdentityLookup},
+	Perbill,
+};
+
+pub type AccountId = u64;
+pub type Balance = u64;
+
+type Block = frame_system::mocking::MockBlock<TestRuntime>;
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<TestRuntime>;
+
+pub const BRIDGED_CHAIN_ID: ChainId = *b"inst";
+pub const BRIDGED_CHAIN_SPEC_VERSION: u32 = 4;
+pub const OUTBOUND_LANE_ID: LaneId = LaneId([0, 0, 0, 0]);
+pub const THIS_CHAIN_ACCOUNT: AccountId = 1;
+pub const THIS_CHAIN_ACCOUNT_BALANCE: Balance = 100_000;
+pub const BRIDGED_CHAIN_ACCOUNT: AccountId = 3;
+pub const BRIDGED_PALLET_ACCOUNT: AccountId = 4;
+pub const MESSAGE_FEE: Balance = 10;
+pub const REJECTED_MESSAGE_FEE: Balance = 11;
+pub const MESSAGE_NONCE: MessageNonce = 3;
+
+use crate as pallet_bridge_token_swap;
+
+construct_runtime! {
+	pub enum TestRuntime where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Event<T>},
+		TokenSwap: pallet_bridge_token_swap::{Pallet, Call, Event<T>},
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+
+impl frame_system::Config for TestRuntime {
+	type Origin = Origin;
+	type Index = u64;
+	type Call = Call;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type DbWeight = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for TestRuntime {
+	type MaxLocks = ();
+	type Balance = Balance;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = frame_system::Pallet<TestRuntime>;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const TokenSwapPalletId: PalletId = PalletId(*b"bsw/test");
+	pub const BridgedChainId: ChainId = BRIDGED_CHAIN_ID;
+	pub const OutboundMessageLaneId: LaneId = OUTBOUND_LANE_ID;
+	pub const BridgedPalletAccountId: AccountId = BRIDGED_PALLET_ACCOUNT;
+	// in tests, the bridged chain is the same chain, so claim call is dispatched by this pallet
+	pub const BridgedTokenSwapPalletIndex: u8 = 2;
+	pub const BridgedClaimSwapWeight: Weight = 100;
+	pub const BridgedSettleSwapWeight: Weight = 100;
+}
+
+impl pallet_bridge_token_swap::Config for TestRuntime {
+	type Event = Event;
+
+	type PalletId = TokenSwapPalletId;
+	type BridgedChainId = BridgedChainId;
+	type OutboundMessageLaneId = OutboundMessageLaneId;
+
+	type MessagesBridge = TestMessagesBridge;
+	type ThisCurrency = pallet_balances::Pallet<TestRuntime>;
+	type AccountIdConverter = TestAccountIdConverter;
+
+	type BridgedChain = TestBridgedChain;
+	type BridgedBalance = Balance;
+	type BridgedAccountId = AccountId;
+	type BridgedAccountPublic = AccountId;
+	type BridgedAccountSignature = ();
+	type BridgedPalletAccountId = BridgedPalletAccountId;
+	type BridgedTokenSwapCalls = pallet_bridge_token_swap::BridgedTokenSwapPalletCalls<
+		BridgedTokenSwapPalletIndex,
+		BridgedClaimSwapWeight,
+		BridgedSettleSwapWeight,
+	>;
+}
+
+#[derive(Debug)]
+pub struct TestBridgedChain;
+
+impl Chain for TestBridgedChain {
+	type BlockNumber = <TestRuntime as frame_system::Config>::BlockNumber;
+	type Hash = <TestRuntime as frame_system::Config>::Hash;
+	type Hasher = <TestRuntime as frame_system::Config>::Hashing;
+	type Header = <TestRuntime as frame_system::Config>::Header;
+}
+
+pub struct TestAccountIdConverter;
+
+impl Convert<H256, AccountId> for TestAccountIdConverter {
+	fn convert(hash: H256) -> AccountId {
+		hash.to_low_u64_ne()
+	}
+}
+
+/// Storage key of the last message, sent by the `TestMessagesBridge`.
+const SENT_MESSAGE_KEY: &[u8] = b":sent-message:";
+
+/// Messages bridge that remembers last sent message.
+pub struct TestMessagesBridge;
+
+impl MessagesBridge<AccountId, Balance, MessagePayloadOf<TestRuntime, ()>> for TestMessagesBridge {
+	type Error = ();
+
+	fn send_message(
+		sender: Sender<AccountId>,
+		lane: LaneId,
+		message: MessagePayloadOf<TestRuntime, ()>,
+		delivery_and_dispatch_fee: Balance,
+	) -> Result<MessageNonce, Self::Error> {
+		assert_ne!(sender, frame_system::RawOrigin::Root);
+		assert_eq!(lane, OUTBOUND_LANE_ID);
+		if delivery_and_dispatch_fee == REJECTED_MESSAGE_FEE {
+			return Err(());
+		}
+
+		sp_io::storage::set(SENT_MESSAGE_KEY, &message.encode());
+		Ok(MESSAGE_NONCE)
+	}
+}
+
+/// Return last message, sent by the `TestMessagesBridge`.
+pub fn sent_message() -> Option<MessagePayloadOf<TestRuntime, ()>> {
+	sp_io::storage::get(SENT_MESSAGE_KEY).map(|message| Decode::decode(&mut &message[..]).unwrap())
+}
+
+/// Forget last message, sent by the `TestMessagesBridge`.
+pub fn clear_sent_message() {
+	sp_io::storage::clear(SENT_MESSAGE_KEY);
+}
+
+/// Run pallet test.
+pub fn run_test<T>(test: impl FnOnce() -> T) -> T {
+	let mut t = frame_system::GenesisConfig::default()
+		.build_storage::<TestRuntime>()
+		.unwrap();
+	pallet_balances::GenesisConfig::<TestRuntime> {
+		balances: vec![(THIS_CHAIN_ACCOUNT, THIS_CHAIN_ACCOUNT_BALANCE)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(test)
+}
@@ -20,7 +20,9 @@
 // Runtime-generated DecodeLimit::decode_all_with_depth_limit
 #![allow(clippy::unnecessary_mut_passed)]
 
-use bp_messages::{LaneId, MessageNonce, UnrewardedRelayersState, Weight};
+use bp_messages::{
+	InboundLaneData, LaneId, MessageDetails, MessageNonce, MessagesDeliveryLimits, UnrewardedRelayersState,
+};
 use sp_std::prelude::*;
 
 pub use bp_polkadot_core::*;
@@ -43,8 +45,8 @@ pub const IS_KNOWN_KUSAMA_HEADER_METHOD: &str = "KusamaFinalityApi_is_known_head
 /// Name of the `ToKusamaOutboundLaneApi::estimate_message_delivery_and_dispatch_fee` runtime method.
 pub const TO_KUSAMA_ESTIMATE_MESSAGE_FEE_METHOD: &str =
 	"ToKusamaOutboundLaneApi_estimate_message_delivery_and_dispatch_fee";
-/// Name of the `ToKusamaOutboundLaneApi::messages_dispatch_weight` runtime method.
-pub const TO_KUSAMA_MESSAGES_DISPATCH_WEIGHT_METHOD: &str = "ToKusamaOutboundLaneApi_messages_dispatch_weight";
+/// Name of the `ToKusamaOutboundLaneApi::message_details` runtime method.
+pub const TO_KUSAMA_MESSAGE_DETAILS_METHOD: &str = "ToKusamaOutboundLaneApi_message_details";
 /// Name of the `ToKusamaOutboundLaneApi::latest_generated_nonce` runtime method.
 pub const TO_KUSAMA_LATEST_GENERATED_NONCE_METHOD: &str = "ToKusamaOutboundLaneApi_latest_generated_nonce";
 /// Name of the `ToKusamaOutboundLaneApi::latest_received_nonce` runtime method.
@@ -56,6 +58,10 @@ pub const FROM_KUSAMA_LATEST_RECEIVED_NONCE_METHOD: &str = "FromKusamaInboundLan
 pub const FROM_KUSAMA_LATEST_CONFIRMED_NONCE_METHOD: &str = "FromKusamaInboundLaneApi_latest_confirmed_nonce";
 /// Name of the `FromKusamaInboundLaneApi::unrewarded_relayers_state` runtime method.
 pub const FROM_KUSAMA_UNREWARDED_RELAYERS_STATE: &str = "FromKusamaInboundLaneApi_unrewarded_relayers_state";
+/// Name of the `FromKusamaInboundLaneApi::inbound_lane_data` runtime method.
+pub const FROM_KUSAMA_INBOUND_LANE_DATA_METHOD: &str = "FromKusamaInboundLaneApi_inbound_lane_data";
+/// Name of the `FromKusamaInboundLaneApi::delivery_limits` runtime method.
+pub const FROM_KUSAMA_DELIVERY_LIMITS_METHOD: &str = "FromKusamaInboundLaneApi_delivery_limits";
 
 sp_api::decl_runtime_apis! {
 	/// API for querying information about the finalized Kusama headers.
@@ -87,15 +93,16 @@ sp_api::decl_runtime_apis! {
 			lane_id: LaneId,
 			payload: OutboundPayload,
 		) -> Option<OutboundMessageFee>;
-		/// Returns total dispatch weight and encoded payload size of all messages in given inclusive range.
+		/// Returns dispatch weight, encoded payload size and delivery+dispatch fee of all
+		/// messages in given inclusive range.
 		///
 		/// If some (or all) messages are missing from the storage, they'll also will
 		/// be missing from the resulting vector. The vector is ordered by the nonce.
-		fn messages_dispatch_weight(
+		fn message_details(
 			lane: LaneId,
 			begin: MessageNonce,
 			end: MessageNonce,
-		) -> Vec<(MessageNonce, Weight, u32)>;
+		) -> Vec<MessageDetails<OutboundMessageFee>>;
 		/// Returns nonce of the latest message, received by bridged chain.
 		fn latest_received_nonce(lane: LaneId) -> MessageNonce;
 		/// Returns nonce of the latest message, generated by given lane.
@@ -113,5 +120,11 @@ sp_api::decl_runtime_apis! {
 		fn latest_confirmed_nonce(lane: LaneId) -> MessageNonce;
 		/// State of the unrewarded relayers set at given lane.
 		fn unrewarded_relayers_state(lane: LaneId) -> UnrewardedRelayersState;
+		/// Returns data of the given inbound lane, including unrewarded relayer entries.
+		///
+		/// Relayers are identified by their Kusama accounts.
+		fn inbound_lane_data(lane: LaneId) -> InboundLaneData<AccountId>;
+		/// Returns limits of messages delivery transactions at this chain.
+		fn delivery_limits() -> MessagesDeliveryLimits;
 	}
 }
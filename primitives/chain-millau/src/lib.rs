@@ -22,7 +22,9 @@
 
 mod millau_hash;
 
-use bp_messages::{LaneId, MessageNonce, UnrewardedRelayersState};
+use bp_messages::{
+	InboundLaneData, LaneId, MessageDetails, MessageNonce, MessagesDeliveryLimits, UnrewardedRelayersState,
+};
 use bp_runtime::Chain;
 use frame_support::{
 	weights::{constants::WEIGHT_PER_SECOND, DispatchClass, Weight},
@@ -51,7 +53,7 @@ pub const EXTRA_STORAGE_PROOF_SIZE: u32 = 1024;
 /// Number of bytes, included in the signed Millau transaction apart from the encoded call itself.
 ///
 /// Can be computed by subtracting encoded call size from raw transaction size.
-pub const TX_EXTRA_BYTES: u32 = 103;
+pub const TX_EXTRA_BYTES: u32 = 104;
 
 /// Maximal size (in bytes) of encoded (using `Encode::encode()`) account id.
 pub const MAXIMAL_ENCODED_ACCOUNT_ID_SIZE: u32 = 32;
@@ -95,6 +97,21 @@ pub const ADDITIONAL_MESSAGE_BYTE_DELIVERY_WEIGHT: Weight = 25_000;
 /// runtime upgrades.
 pub const MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT: Weight = 2_000_000_000;
 
+/// Index of the token swap pallet in the Millau runtime.
+pub const TOKEN_SWAP_PALLET_INDEX: u8 = 14;
+
+/// Maximal weight of the token swap pallet `claim_swap` call on Millau chain.
+///
+/// The call is dispatched by the bridge, so this value is used as the declared dispatch weight
+/// of the claim message. It must be rounded up to account possible future runtime upgrades.
+pub const MAX_CLAIM_SWAP_WEIGHT: Weight = 500_000_000;
+
+/// Maximal weight of the token swap pallet `settle_swap` call on Millau chain.
+///
+/// The call is dispatched by the bridge, so this value is used as the declared dispatch weight
+/// of the swap result message. It must be rounded up to account possible future runtime upgrades.
+pub const MAX_SETTLE_SWAP_WEIGHT: Weight = 500_000_000;
+
 /// The target length of a session (how often authorities change) on Millau measured in of number of
 /// blocks.
 ///
@@ -102,6 +119,9 @@ pub const MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT: Weight = 2_000_000
 /// conditions.
 pub const SESSION_LENGTH: BlockNumber = 5 * time_units::MINUTES;
 
+/// SS58 prefix of Millau accounts.
+pub const SS58_PREFIX: u8 = 60;
+
 /// Re-export `time_units` to make usage easier.
 pub use time_units::*;
 
@@ -238,14 +258,17 @@ pub fn max_extrinsic_size() -> u32 {
 	*BlockLength::get().max.get(DispatchClass::Normal)
 }
 
+/// Name of the GRANDPA bridge pallet that is tracking Millau finality in other runtimes.
+pub const WITH_MILLAU_GRANDPA_PALLET_NAME: &str = "BridgeMillauGrandpa";
+
 /// Name of the `MillauFinalityApi::best_finalized` runtime method.
 pub const BEST_FINALIZED_MILLAU_HEADER_METHOD: &str = "MillauFinalityApi_best_finalized";
 
 /// Name of the `ToMillauOutboundLaneApi::estimate_message_delivery_and_dispatch_fee` runtime method.
 pub const TO_MILLAU_ESTIMATE_MESSAGE_FEE_METHOD: &str =
 	"ToMillauOutboundLaneApi_estimate_message_delivery_and_dispatch_fee";
-/// Name of the `ToMillauOutboundLaneApi::messages_dispatch_weight` runtime method.
-pub const TO_MILLAU_MESSAGES_DISPATCH_WEIGHT_METHOD: &str = "ToMillauOutboundLaneApi_messages_dispatch_weight";
+/// Name of the `ToMillauOutboundLaneApi::message_details` runtime method.
+pub const TO_MILLAU_MESSAGE_DETAILS_METHOD: &str = "ToMillauOutboundLaneApi_message_details";
 /// Name of the `ToMillauOutboundLaneApi::latest_received_nonce` runtime method.
 pub const TO_MILLAU_LATEST_RECEIVED_NONCE_METHOD: &str = "ToMillauOutboundLaneApi_latest_received_nonce";
 /// Name of the `ToMillauOutboundLaneApi::latest_generated_nonce` runtime method.
@@ -257,6 +280,10 @@ pub const FROM_MILLAU_LATEST_RECEIVED_NONCE_METHOD: &str = "FromMillauInboundLan
 pub const FROM_MILLAU_LATEST_CONFIRMED_NONCE_METHOD: &str = "FromMillauInboundLaneApi_latest_confirmed_nonce";
 /// Name of the `FromMillauInboundLaneApi::unrewarded_relayers_state` runtime method.
 pub const FROM_MILLAU_UNREWARDED_RELAYERS_STATE: &str = "FromMillauInboundLaneApi_unrewarded_relayers_state";
+/// Name of the `FromMillauInboundLaneApi::inbound_lane_data` runtime method.
+pub const FROM_MILLAU_INBOUND_LANE_DATA_METHOD: &str = "FromMillauInboundLaneApi_inbound_lane_data";
+/// Name of the `FromMillauInboundLaneApi::delivery_limits` runtime method.
+pub const FROM_MILLAU_DELIVERY_LIMITS_METHOD: &str = "FromMillauInboundLaneApi_delivery_limits";
 
 sp_api::decl_runtime_apis! {
 	/// API for querying information about the finalized Millau headers.
@@ -288,15 +315,16 @@ sp_api::decl_runtime_apis! {
 			lane_id: LaneId,
 			payload: OutboundPayload,
 		) -> Option<OutboundMessageFee>;
-		/// Returns total dispatch weight and encoded payload size of all messages in given inclusive range.
+		/// Returns dispatch weight, encoded payload size and delivery+dispatch fee of all
+		/// messages in given inclusive range.
 		///
 		/// If some (or all) messages are missing from the storage, they'll also will
 		/// be missing from the resulting vector. The vector is ordered by the nonce.
-		fn messages_dispatch_weight(
+		fn message_details(
 			lane: LaneId,
 			begin: MessageNonce,
 			end: MessageNonce,
-		) -> Vec<(MessageNonce, Weight, u32)>;
+		) -> Vec<MessageDetails<OutboundMessageFee>>;
 		/// Returns nonce of the latest message, received by bridged chain.
 		fn latest_received_nonce(lane: LaneId) -> MessageNonce;
 		/// Returns nonce of the latest message, generated by given lane.
@@ -314,6 +342,12 @@ sp_api::decl_runtime_apis! {
 		fn latest_confirmed_nonce(lane: LaneId) -> MessageNonce;
 		/// State of the unrewarded relayers set at given lane.
 		fn unrewarded_relayers_state(lane: LaneId) -> UnrewardedRelayersState;
+		/// Returns data of the given inbound lane, including unrewarded relayer entries.
+		///
+		/// Relayers are identified by their Millau accounts.
+		fn inbound_lane_data(lane: LaneId) -> InboundLaneData<AccountId>;
+		/// Returns limits of messages delivery transactions at this chain.
+		fn delivery_limits() -> MessagesDeliveryLimits;
 	}
 }
 
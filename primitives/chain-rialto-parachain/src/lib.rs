@@ -0,0 +1,290 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// RuntimeApi generated functions
+#![allow(clippy::too_many_arguments)]
+// Runtime-generated DecodeLimit::decode_all_With_depth_limit
+#![allow(clippy::unnecessary_mut_passed)]
+
+use bp_messages::{
+	InboundLaneData, LaneId, MessageDetails, MessageNonce, MessagesDeliveryLimits, UnrewardedRelayersState,
+};
+use bp_parachains::ParaId;
+use bp_runtime::Chain;
+use frame_support::{
+	weights::{constants::WEIGHT_PER_SECOND, DispatchClass, Weight},
+	Parameter, RuntimeDebug,
+};
+use frame_system::limits;
+use sp_core::Hasher as HasherT;
+use sp_runtime::{
+	traits::{BlakeTwo256, Convert, IdentifyAccount, Verify},
+	MultiSignature, MultiSigner, Perbill,
+};
+use sp_std::prelude::*;
+
+/// Identifier of the Rialto parachain at the Rialto relay chain.
+pub const RIALTO_PARACHAIN_ID: ParaId = ParaId(2000);
+
+/// Number of extra bytes (excluding size of storage value itself) of storage proof, built at
+/// Rialto parachain. This mostly depends on number of entries (and their density) in the storage trie.
+/// Some reserve is reserved to account future chain growth.
+pub const EXTRA_STORAGE_PROOF_SIZE: u32 = 1024;
+
+/// Number of bytes, included in the signed Rialto parachain transaction apart from the encoded call itself.
+///
+/// Can be computed by subtracting encoded call size from raw transaction size.
+pub const TX_EXTRA_BYTES: u32 = 104;
+
+/// Maximal size (in bytes) of encoded (using `Encode::encode()`) account id.
+pub const MAXIMAL_ENCODED_ACCOUNT_ID_SIZE: u32 = 32;
+
+/// Maximal weight of single Rialto parachain block.
+///
+/// This represents half a second of compute, which is the limit of the parachain validation
+/// function execution at the relay chain.
+pub const MAXIMUM_BLOCK_WEIGHT: Weight = WEIGHT_PER_SECOND / 2;
+
+/// Represents the average portion of a block's weight that will be used by an
+/// `on_initialize()` runtime call.
+pub const AVERAGE_ON_INITIALIZE_RATIO: Perbill = Perbill::from_percent(10);
+
+/// Represents the portion of a block that will be used by Normal extrinsics.
+pub const NORMAL_DISPATCH_RATIO: Perbill = Perbill::from_percent(75);
+
+/// Maximal number of unrewarded relayer entries at inbound lane.
+pub const MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE: MessageNonce = 128;
+
+/// Maximal number of unconfirmed messages at inbound lane.
+pub const MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE: MessageNonce = 128;
+
+/// Weight of single regular message delivery transaction on Rialto parachain.
+///
+/// This value is a result of `pallet_bridge_messages::Pallet::receive_messages_proof_weight()` call
+/// for the case when single message of `pallet_bridge_messages::EXPECTED_DEFAULT_MESSAGE_LENGTH` bytes is delivered.
+/// The message must have dispatch weight set to zero. The result then must be rounded up to account
+/// possible future runtime upgrades.
+pub const DEFAULT_MESSAGE_DELIVERY_TX_WEIGHT: Weight = 100_000_000;
+
+/// Increase of delivery transaction weight on Rialto parachain with every additional message byte.
+///
+/// This value is a result of `pallet_bridge_messages::WeightInfoExt::storage_proof_size_overhead(1)` call. The
+/// result then must be rounded up to account possible future runtime upgrades.
+pub const ADDITIONAL_MESSAGE_BYTE_DELIVERY_WEIGHT: Weight = 25_000;
+
+/// Maximal weight of single message delivery confirmation transaction on Rialto parachain.
+///
+/// This value is a result of `pallet_bridge_messages::Pallet::receive_messages_delivery_proof` weight formula computation
+/// for the case when single message is confirmed. The result then must be rounded up to account possible future
+/// runtime upgrades.
+pub const MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT: Weight = 200_000_000;
+
+/// SS58 prefix of Rialto parachain accounts.
+pub const SS58_PREFIX: u8 = 48;
+
+/// Re-export `time_units` to make usage easier.
+pub use time_units::*;
+
+/// Human readable time units defined in terms of number of blocks.
+pub mod time_units {
+	use super::BlockNumber;
+
+	pub const MILLISECS_PER_BLOCK: u64 = 12000;
+	pub const SLOT_DURATION: u64 = MILLISECS_PER_BLOCK;
+
+	pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
+	pub const HOURS: BlockNumber = MINUTES * 60;
+	pub const DAYS: BlockNumber = HOURS * 24;
+}
+
+/// Block number type used in Rialto parachain.
+pub type BlockNumber = u32;
+
+/// Hash type used in Rialto parachain.
+pub type Hash = <BlakeTwo256 as HasherT>::Out;
+
+/// The type of an object that can produce hashes on Rialto parachain.
+pub type Hasher = BlakeTwo256;
+
+/// The header type used by Rialto parachain.
+pub type Header = sp_runtime::generic::Header<BlockNumber, Hasher>;
+
+/// Alias to 512-bit hash when used in the context of a transaction signature on the chain.
+pub type Signature = MultiSignature;
+
+/// Some way of identifying an account on the chain. We intentionally make it equivalent
+/// to the public key of our transaction signing scheme.
+pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+/// Public key of the chain account that may be used to verify signatures.
+pub type AccountSigner = MultiSigner;
+
+/// Balance of an account.
+pub type Balance = u128;
+
+/// Index of a transaction in the chain.
+pub type Index = u32;
+
+/// Rialto parachain.
+#[derive(RuntimeDebug)]
+pub struct RialtoParachain;
+
+impl Chain for RialtoParachain {
+	type BlockNumber = BlockNumber;
+	type Hash = Hash;
+	type Hasher = Hasher;
+	type Header = Header;
+}
+
+/// Convert a 256-bit hash into an AccountId.
+pub struct AccountIdConverter;
+
+impl Convert<sp_core::H256, AccountId> for AccountIdConverter {
+	fn convert(hash: sp_core::H256) -> AccountId {
+		hash.to_fixed_bytes().into()
+	}
+}
+
+frame_support::parameter_types! {
+	pub BlockLength: limits::BlockLength =
+		limits::BlockLength::max_with_normal_ratio(5 * 1024 * 1024, NORMAL_DISPATCH_RATIO);
+	pub BlockWeights: limits::BlockWeights = limits::BlockWeights::builder()
+		// Allowance for Normal class
+		.for_class(DispatchClass::Normal, |weights| {
+			weights.max_total = Some(NORMAL_DISPATCH_RATIO * MAXIMUM_BLOCK_WEIGHT);
+		})
+		// Allowance for Operational class
+		.for_class(DispatchClass::Operational, |weights| {
+			weights.max_total = Some(MAXIMUM_BLOCK_WEIGHT);
+			// Extra reserved space for Operational class
+			weights.reserved = Some(MAXIMUM_BLOCK_WEIGHT - NORMAL_DISPATCH_RATIO * MAXIMUM_BLOCK_WEIGHT);
+		})
+		// By default Mandatory class is not limited at all.
+		// This parameter is used to derive maximal size of a single extrinsic.
+		.avg_block_initialization(AVERAGE_ON_INITIALIZE_RATIO)
+		.build_or_panic();
+}
+
+/// Get the maximum weight (compute time) that a Normal extrinsic on the Rialto parachain can use.
+pub fn max_extrinsic_weight() -> Weight {
+	BlockWeights::get()
+		.get(DispatchClass::Normal)
+		.max_extrinsic
+		.unwrap_or(Weight::MAX)
+}
+
+/// Get the maximum length in bytes that a Normal extrinsic on the Rialto parachain requires.
+pub fn max_extrinsic_size() -> u32 {
+	*BlockLength::get().max.get(DispatchClass::Normal)
+}
+
+/// Name of the `ToRialtoParachainOutboundLaneApi::estimate_message_delivery_and_dispatch_fee` runtime method.
+pub const TO_RIALTO_PARACHAIN_ESTIMATE_MESSAGE_FEE_METHOD: &str =
+	"ToRialtoParachainOutboundLaneApi_estimate_message_delivery_and_dispatch_fee";
+/// Name of the `ToRialtoParachainOutboundLaneApi::message_details` runtime method.
+pub const TO_RIALTO_PARACHAIN_MESSAGE_DETAILS_METHOD: &str = "ToRialtoParachainOutboundLaneApi_message_details";
+/// Name of the `ToRialtoParachainOutboundLaneApi::latest_generated_nonce` runtime method.
+pub const TO_RIALTO_PARACHAIN_LATEST_GENERATED_NONCE_METHOD: &str =
+	"ToRialtoParachainOutboundLaneApi_latest_generated_nonce";
+/// Name of the `ToRialtoParachainOutboundLaneApi::latest_received_nonce` runtime method.
+pub const TO_RIALTO_PARACHAIN_LATEST_RECEIVED_NONCE_METHOD: &str =
+	"ToRialtoParachainOutboundLaneApi_latest_received_nonce";
+
+/// Name of the `FromRialtoParachainInboundLaneApi::latest_received_nonce` runtime method.
+pub const FROM_RIALTO_PARACHAIN_LATEST_RECEIVED_NONCE_METHOD: &str =
+	"FromRialtoParachainInboundLaneApi_latest_received_nonce";
+/// Name of the `FromRialtoParachainInboundLaneApi::latest_onfirmed_nonce` runtime method.
+pub const FROM_RIALTO_PARACHAIN_LATEST_CONFIRMED_NONCE_METHOD: &str =
+	"FromRialtoParachainInboundLaneApi_latest_confirmed_nonce";
+/// Name of the `FromRialtoParachainInboundLaneApi::unrewarded_relayers_state` runtime method.
+pub const FROM_RIALTO_PARACHAIN_UNREWARDED_RELAYERS_STATE: &str =
+	"FromRialtoParachainInboundLaneApi_unrewarded_relayers_state";
+/// Name of the `FromRialtoParachainInboundLaneApi::inbound_lane_data` runtime method.
+pub const FROM_RIALTO_PARACHAIN_INBOUND_LANE_DATA_METHOD: &str = "FromRialtoParachainInboundLaneApi_inbound_lane_data";
+/// Name of the `FromRialtoParachainInboundLaneApi::delivery_limits` runtime method.
+pub const FROM_RIALTO_PARACHAIN_DELIVERY_LIMITS_METHOD: &str = "FromRialtoParachainInboundLaneApi_delivery_limits";
+
+sp_api::decl_runtime_apis! {
+	/// Outbound message lane API for messages that are sent to Rialto parachain.
+	///
+	/// This API is implemented by runtimes that are sending messages to Rialto parachain, not the
+	/// Rialto parachain runtime itself.
+	pub trait ToRialtoParachainOutboundLaneApi<OutboundMessageFee: Parameter, OutboundPayload: Parameter> {
+		/// Estimate message delivery and dispatch fee that needs to be paid by the sender on
+		/// this chain.
+		///
+		/// Returns `None` if message is too expensive to be sent to Rialto parachain from this chain.
+		///
+		/// Please keep in mind that this method returns lowest message fee required for message
+		/// to be accepted to the lane. It may be good idea to pay a bit over this price to account
+		/// future exchange rate changes and guarantee that relayer would deliver your message
+		/// to the target chain.
+		fn estimate_message_delivery_and_dispatch_fee(
+			lane_id: LaneId,
+			payload: OutboundPayload,
+		) -> Option<OutboundMessageFee>;
+		/// Returns dispatch weight, encoded payload size and delivery+dispatch fee of all
+		/// messages in given inclusive range.
+		///
+		/// If some (or all) messages are missing from the storage, they'll also will
+		/// be missing from the resulting vector. The vector is ordered by the nonce.
+		fn message_details(
+			lane: LaneId,
+			begin: MessageNonce,
+			end: MessageNonce,
+		) -> Vec<MessageDetails<OutboundMessageFee>>;
+		/// Returns nonce of the latest message, received by bridged chain.
+		fn latest_received_nonce(lane: LaneId) -> MessageNonce;
+		/// Returns nonce of the latest message, generated by given lane.
+		fn latest_generated_nonce(lane: LaneId) -> MessageNonce;
+	}
+
+	/// Inbound message lane API for messages sent by Rialto parachain.
+	///
+	/// This API is implemented by runtimes that are receiving messages from Rialto parachain, not the
+	/// Rialto parachain runtime itself.
+	pub trait FromRialtoParachainInboundLaneApi {
+		/// Returns nonce of the latest message, received by given lane.
+		fn latest_received_nonce(lane: LaneId) -> MessageNonce;
+		/// Nonce of latest message that has been confirmed to the bridged chain.
+		fn latest_confirmed_nonce(lane: LaneId) -> MessageNonce;
+		/// State of the unrewarded relayers set at given lane.
+		fn unrewarded_relayers_state(lane: LaneId) -> UnrewardedRelayersState;
+		/// Returns data of the given inbound lane, including unrewarded relayer entries.
+		///
+		/// Relayers are identified by their Rialto parachain accounts.
+		fn inbound_lane_data(lane: LaneId) -> InboundLaneData<AccountId>;
+		/// Returns limits of messages delivery transactions at this chain.
+		fn delivery_limits() -> MessagesDeliveryLimits;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::codec::Encode;
+
+	#[test]
+	fn maximal_account_size_does_not_overflow_constant() {
+		assert!(
+			MAXIMAL_ENCODED_ACCOUNT_ID_SIZE as usize >= AccountId::default().encode().len(),
+			"Actual maximal size of encoded AccountId ({}) overflows expected ({})",
+			AccountId::default().encode().len(),
+			MAXIMAL_ENCODED_ACCOUNT_ID_SIZE,
+		);
+	}
+}
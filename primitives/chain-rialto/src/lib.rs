@@ -20,7 +20,9 @@
 // Runtime-generated DecodeLimit::decode_all_With_depth_limit
 #![allow(clippy::unnecessary_mut_passed)]
 
-use bp_messages::{LaneId, MessageNonce, UnrewardedRelayersState};
+use bp_messages::{
+	InboundLaneData, LaneId, MessageDetails, MessageNonce, MessagesDeliveryLimits, UnrewardedRelayersState,
+};
 use bp_runtime::Chain;
 use frame_support::{
 	weights::{constants::WEIGHT_PER_SECOND, DispatchClass, Weight},
@@ -42,7 +44,7 @@ pub const EXTRA_STORAGE_PROOF_SIZE: u32 = 1024;
 /// Number of bytes, included in the signed Rialto transaction apart from the encoded call itself.
 ///
 /// Can be computed by subtracting encoded call size from raw transaction size.
-pub const TX_EXTRA_BYTES: u32 = 103;
+pub const TX_EXTRA_BYTES: u32 = 104;
 
 /// Maximal size (in bytes) of encoded (using `Encode::encode()`) account id.
 pub const MAXIMAL_ENCODED_ACCOUNT_ID_SIZE: u32 = 32;
@@ -86,6 +88,21 @@ pub const ADDITIONAL_MESSAGE_BYTE_DELIVERY_WEIGHT: Weight = 25_000;
 /// runtime upgrades.
 pub const MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT: Weight = 2_000_000_000;
 
+/// Index of the token swap pallet in the Rialto runtime.
+pub const TOKEN_SWAP_PALLET_INDEX: u8 = 18;
+
+/// Maximal weight of the token swap pallet `claim_swap` call on Rialto chain.
+///
+/// The call is dispatched by the bridge, so this value is used as the declared dispatch weight
+/// of the claim message. It must be rounded up to account possible future runtime upgrades.
+pub const MAX_CLAIM_SWAP_WEIGHT: Weight = 500_000_000;
+
+/// Maximal weight of the token swap pallet `settle_swap` call on Rialto chain.
+///
+/// The call is dispatched by the bridge, so this value is used as the declared dispatch weight
+/// of the swap result message. It must be rounded up to account possible future runtime upgrades.
+pub const MAX_SETTLE_SWAP_WEIGHT: Weight = 500_000_000;
+
 /// The target length of a session (how often authorities change) on Rialto measured in of number of
 /// blocks.
 ///
@@ -93,6 +110,9 @@ pub const MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT: Weight = 2_000_000
 /// conditions.
 pub const SESSION_LENGTH: BlockNumber = 4;
 
+/// SS58 prefix of Rialto accounts.
+pub const SS58_PREFIX: u8 = 48;
+
 /// Re-export `time_units` to make usage easier.
 pub use time_units::*;
 
@@ -199,14 +219,17 @@ pub fn max_extrinsic_size() -> u32 {
 	*BlockLength::get().max.get(DispatchClass::Normal)
 }
 
+/// Name of the GRANDPA bridge pallet that is tracking Rialto finality in other runtimes.
+pub const WITH_RIALTO_GRANDPA_PALLET_NAME: &str = "BridgeRialtoGrandpa";
+
 /// Name of the `RialtoFinalityApi::best_finalized` runtime method.
 pub const BEST_FINALIZED_RIALTO_HEADER_METHOD: &str = "RialtoFinalityApi_best_finalized";
 
 /// Name of the `ToRialtoOutboundLaneApi::estimate_message_delivery_and_dispatch_fee` runtime method.
 pub const TO_RIALTO_ESTIMATE_MESSAGE_FEE_METHOD: &str =
 	"ToRialtoOutboundLaneApi_estimate_message_delivery_and_dispatch_fee";
-/// Name of the `ToRialtoOutboundLaneApi::messages_dispatch_weight` runtime method.
-pub const TO_RIALTO_MESSAGES_DISPATCH_WEIGHT_METHOD: &str = "ToRialtoOutboundLaneApi_messages_dispatch_weight";
+/// Name of the `ToRialtoOutboundLaneApi::message_details` runtime method.
+pub const TO_RIALTO_MESSAGE_DETAILS_METHOD: &str = "ToRialtoOutboundLaneApi_message_details";
 /// Name of the `ToRialtoOutboundLaneApi::latest_generated_nonce` runtime method.
 pub const TO_RIALTO_LATEST_GENERATED_NONCE_METHOD: &str = "ToRialtoOutboundLaneApi_latest_generated_nonce";
 /// Name of the `ToRialtoOutboundLaneApi::latest_received_nonce` runtime method.
@@ -218,6 +241,10 @@ pub const FROM_RIALTO_LATEST_RECEIVED_NONCE_METHOD: &str = "FromRialtoInboundLan
 pub const FROM_RIALTO_LATEST_CONFIRMED_NONCE_METHOD: &str = "FromRialtoInboundLaneApi_latest_confirmed_nonce";
 /// Name of the `FromRialtoInboundLaneApi::unrewarded_relayers_state` runtime method.
 pub const FROM_RIALTO_UNREWARDED_RELAYERS_STATE: &str = "FromRialtoInboundLaneApi_unrewarded_relayers_state";
+/// Name of the `FromRialtoInboundLaneApi::inbound_lane_data` runtime method.
+pub const FROM_RIALTO_INBOUND_LANE_DATA_METHOD: &str = "FromRialtoInboundLaneApi_inbound_lane_data";
+/// Name of the `FromRialtoInboundLaneApi::delivery_limits` runtime method.
+pub const FROM_RIALTO_DELIVERY_LIMITS_METHOD: &str = "FromRialtoInboundLaneApi_delivery_limits";
 
 sp_api::decl_runtime_apis! {
 	/// API for querying information about the finalized Rialto headers.
@@ -249,15 +276,16 @@ sp_api::decl_runtime_apis! {
 			lane_id: LaneId,
 			payload: OutboundPayload,
 		) -> Option<OutboundMessageFee>;
-		/// Returns total dispatch weight and encoded payload size of all messages in given inclusive range.
+		/// Returns dispatch weight, encoded payload size and delivery+dispatch fee of all
+		/// messages in given inclusive range.
 		///
 		/// If some (or all) messages are missing from the storage, they'll also will
 		/// be missing from the resulting vector. The vector is ordered by the nonce.
-		fn messages_dispatch_weight(
+		fn message_details(
 			lane: LaneId,
 			begin: MessageNonce,
 			end: MessageNonce,
-		) -> Vec<(MessageNonce, Weight, u32)>;
+		) -> Vec<MessageDetails<OutboundMessageFee>>;
 		/// Returns nonce of the latest message, received by bridged chain.
 		fn latest_received_nonce(lane: LaneId) -> MessageNonce;
 		/// Returns nonce of the latest message, generated by given lane.
@@ -275,6 +303,12 @@ sp_api::decl_runtime_apis! {
 		fn latest_confirmed_nonce(lane: LaneId) -> MessageNonce;
 		/// State of the unrewarded relayers set at given lane.
 		fn unrewarded_relayers_state(lane: LaneId) -> UnrewardedRelayersState;
+		/// Returns data of the given inbound lane, including unrewarded relayer entries.
+		///
+		/// Relayers are identified by their Rialto accounts.
+		fn inbound_lane_data(lane: LaneId) -> InboundLaneData<AccountId>;
+		/// Returns limits of messages delivery transactions at this chain.
+		fn delivery_limits() -> MessagesDeliveryLimits;
 	}
 }
 
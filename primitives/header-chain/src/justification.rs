@@ -20,7 +20,7 @@
 //! will ever be moved to the sp_finality_grandpa, we should reuse that implementation.
 
 use codec::{Decode, Encode};
-use finality_grandpa::{voter_set::VoterSet, Chain, Error as GrandpaError};
+use finality_grandpa::voter_set::VoterSet;
 use frame_support::RuntimeDebug;
 use sp_finality_grandpa::{AuthorityId, AuthoritySignature, SetId};
 use sp_runtime::traits::Header as HeaderT;
@@ -68,19 +68,32 @@ where
 		return Err(Error::InvalidJustificationTarget);
 	}
 
-	// Validate commit of the justification. Note that `validate_commit()` assumes that all
-	// signatures are valid. We'll check the validity of the signatures later since they're more
-	// resource intensive to verify.
-	let ancestry_chain = AncestryChain::new(&justification.votes_ancestries);
-	match finality_grandpa::validate_commit(&justification.commit, authorities_set, &ancestry_chain) {
-		Ok(ref result) if result.ghost().is_some() => {}
-		_ => return Err(Error::InvalidJustificationCommit),
-	}
-
-	// Now that we know that the commit is correct, check authorities signatures
+	// Collect votes of known authorities until the threshold is reached. Precommits of unknown
+	// authorities and duplicate precommits of the same authority are ignored and signatures of
+	// precommits that are not required to reach the threshold are not verified. But ancestry
+	// routes of all precommits are still checked, so that every vote ancestry is used.
+	let threshold = authorities_set.threshold().0.get();
+	let mut ancestry_chain = AncestryChain::new(&justification.votes_ancestries);
 	let mut buf = Vec::new();
-	let mut visited_hashes = BTreeSet::new();
+	let mut voted_authorities = BTreeSet::new();
+	let mut cumulative_weight = 0u64;
 	for signed in &justification.commit.precommits {
+		// all precommits must be descendants of (or equal to) the justification target
+		if !ancestry_chain.ensure_descendant(&justification.commit.target_hash, &signed.precommit.target_hash) {
+			return Err(Error::InvalidPrecommitAncestryProof);
+		}
+
+		if cumulative_weight >= threshold {
+			continue;
+		}
+		let authority_info = match authorities_set.get(&signed.id) {
+			Some(authority_info) => authority_info,
+			None => continue,
+		};
+		if !voted_authorities.insert(signed.id.clone()) {
+			continue;
+		}
+
 		if !sp_finality_grandpa::check_message_signature_with_buffer(
 			&finality_grandpa::Message::Precommit(signed.precommit.clone()),
 			&signed.id,
@@ -92,25 +105,16 @@ where
 			return Err(Error::InvalidAuthoritySignature);
 		}
 
-		if justification.commit.target_hash == signed.precommit.target_hash {
-			continue;
-		}
+		cumulative_weight = cumulative_weight.saturating_add(authority_info.weight().0.get());
+	}
 
-		match ancestry_chain.ancestry(justification.commit.target_hash, signed.precommit.target_hash) {
-			Ok(route) => {
-				// ancestry starts from parent hash but the precommit target hash has been visited
-				visited_hashes.insert(signed.precommit.target_hash);
-				visited_hashes.extend(route);
-			}
-			_ => {
-				// could this happen in practice? I don't think so, but original code has this check
-				return Err(Error::InvalidPrecommitAncestryProof);
-			}
-		}
+	// the justification must be signed by the supermajority of authorities
+	if cumulative_weight < threshold {
+		return Err(Error::InvalidJustificationCommit);
 	}
 
-	// both iterators are `BTree*` iterators, so have the same order => safe to compare
-	if !visited_hashes.iter().eq(ancestry_chain.ancestry.keys()) {
+	// the justification must not contain headers that are not used by any precommit
+	if !ancestry_chain.unvisited.is_empty() {
 		return Err(Error::InvalidPrecommitAncestries);
 	}
 
@@ -138,44 +142,40 @@ impl<H: HeaderT> crate::FinalityProof<H::Number> for GrandpaJustification<H> {
 	}
 }
 
-/// A utility trait implementing `finality_grandpa::Chain` using a given set of headers.
+/// Ancestry of precommit targets, built from the justification votes ancestries.
 #[derive(RuntimeDebug)]
 struct AncestryChain<Header: HeaderT> {
-	ancestry: BTreeMap<Header::Hash, Header::Hash>,
+	/// Header hash => parent header hash mapping.
+	parents: BTreeMap<Header::Hash, Header::Hash>,
+	/// Hashes of headers that have not been visited yet.
+	unvisited: BTreeSet<Header::Hash>,
 }
 
 impl<Header: HeaderT> AncestryChain<Header> {
 	fn new(ancestry: &[Header]) -> AncestryChain<Header> {
-		AncestryChain {
-			ancestry: ancestry
-				.iter()
-				.map(|header| (header.hash(), *header.parent_hash()))
-				.collect(),
-		}
+		let parents = ancestry
+			.iter()
+			.map(|header| (header.hash(), *header.parent_hash()))
+			.collect::<BTreeMap<_, _>>();
+		let unvisited = parents.keys().cloned().collect();
+		AncestryChain { parents, unvisited }
 	}
-}
 
-impl<Header: HeaderT> finality_grandpa::Chain<Header::Hash, Header::Number> for AncestryChain<Header>
-where
-	Header::Number: finality_grandpa::BlockNumberOps,
-{
-	fn ancestry(&self, base: Header::Hash, block: Header::Hash) -> Result<Vec<Header::Hash>, GrandpaError> {
-		let mut route = Vec::new();
-		let mut current_hash = block;
-		loop {
-			if current_hash == base {
-				break;
-			}
-			match self.ancestry.get(&current_hash).cloned() {
-				Some(parent_hash) => {
-					current_hash = parent_hash;
-					route.push(current_hash);
-				}
-				_ => return Err(GrandpaError::NotDescendent),
-			}
+	/// Returns true if `precommit_target` is the `commit_target` or its descendant.
+	///
+	/// Headers on the route are marked as visited. The walk stops at the first header that has
+	/// been visited before, because its route to the `commit_target` has already been checked.
+	fn ensure_descendant(&mut self, commit_target: &Header::Hash, precommit_target: &Header::Hash) -> bool {
+		let mut current_hash = *precommit_target;
+		while current_hash != *commit_target {
+			let is_visited_before = !self.unvisited.remove(&current_hash);
+			current_hash = match self.parents.get(&current_hash) {
+				Some(_) if is_visited_before => return true,
+				Some(parent_hash) => *parent_hash,
+				None => return false,
+			};
 		}
-		route.pop(); // remove the base
 
-		Ok(route)
+		true
 	}
 }
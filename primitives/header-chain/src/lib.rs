@@ -31,6 +31,7 @@ use sp_runtime::RuntimeDebug;
 use sp_runtime::{generic::OpaqueDigestItemId, traits::Header as HeaderT};
 
 pub mod justification;
+pub mod solidity;
 
 /// A type that can be used as a parameter in a dispatchable function.
 ///
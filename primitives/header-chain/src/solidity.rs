@@ -0,0 +1,370 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Finality artifacts in the format that is consumed by the Solidity light client.
+//!
+//! The light client may either verify GRANDPA finality (finalized header, justification and
+//! authority sets), or BEEFY finality (signed commitment, validator set and MMR proof of the
+//! finalized header). BEEFY is cheaper to verify, because its validators are using ECDSA
+//! signatures that may be checked with `ecrecover`.
+//!
+//! Solidity contracts can't decode SCALE-encoded structures cheaply, so all artifacts are
+//! encoded using tightly packed layout, where:
+//!
+//! - all integers are encoded as big-endian numbers of fixed size (`uint8`, `uint32` or `uint64`);
+//! - hashes and public keys are encoded as `bytes32`, signatures are encoded as `bytes64`;
+//! - every list is prefixed with its length, encoded as `uint32`;
+//! - optional values are prefixed with `uint8` flag (`0` if value is missing and `1` otherwise).
+//!
+//! The layout of encoded `GrandpaFinalityArtifacts` is:
+//!
+//! ```text
+//! header:               number: uint64, hash: bytes32, parent_hash: bytes32,
+//!                       state_root: bytes32, extrinsics_root: bytes32
+//! authority_set:        set_id: uint64, authorities: list of (public: bytes32, weight: uint64)
+//! next_authority_set:   optional authority set, encoded as above
+//! round:                uint64
+//! precommits:           list of (target_hash: bytes32, target_number: uint64, authority: bytes32,
+//!                       signature: bytes64, signed_payload: list of uint8)
+//! votes_ancestries:     list of (hash: bytes32, parent_hash: bytes32)
+//! ```
+//!
+//! The `signed_payload` is the exact (SCALE-encoded) message that has been signed by the authority.
+//! It is provided because the encoding of precommit target number depends on the bridged chain
+//! (it is either `u32` or `u64`). The verifier must check that the payload is a precommit for the
+//! `target_hash` and that it is signed within given `round` and `authority_set.set_id`.
+//!
+//! The layout of encoded `BeefyFinalityArtifacts` is:
+//!
+//! ```text
+//! commitment:           payload: bytes32, block_number: uint64, validator_set_id: uint64
+//! encoded_commitment:   list of uint8
+//! validator_set:        id: uint64, validators: list of address (bytes20)
+//! signatures:           list of (validator_index: uint32, signature: bytes65)
+//! header:               header, encoded as above
+//! leaf_proof:           leaf: list of uint8, leaf_index: uint64, leaf_count: uint64,
+//!                       items: list of bytes32
+//! ```
+//!
+//! The `encoded_commitment` is the SCALE-encoded commitment. Validators are signing `keccak256` of
+//! this encoding, so the verifier must check that it matches the `commitment` and recover signers
+//! of the `signatures`. The commitment `payload` is the MMR root. The MMR leaf, proved by the
+//! `leaf_proof`, is the SCALE-encoded leaf that contains number and hash of the `header`.
+
+use crate::justification::GrandpaJustification;
+
+use finality_grandpa::Message;
+use sp_core::H256;
+use sp_finality_grandpa::{AuthorityId, AuthorityList, SetId};
+use sp_runtime::{
+	traits::{Header as HeaderT, UniqueSaturatedInto},
+	RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+/// Finalized header commitment.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct HeaderCommitment {
+	/// Header number.
+	pub number: u64,
+	/// Header hash.
+	pub hash: H256,
+	/// Parent header hash.
+	pub parent_hash: H256,
+	/// Storage root of the header, that may be used to verify storage proofs.
+	pub state_root: H256,
+	/// Extrinsics root of the header, that may be used to verify transactions inclusion.
+	pub extrinsics_root: H256,
+}
+
+/// GRANDPA authority set commitment.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct AuthoritySetCommitment {
+	/// Authority set id.
+	pub set_id: SetId,
+	/// Public keys and weights of authorities.
+	pub authorities: Vec<([u8; 32], u64)>,
+}
+
+/// Precommit, signed by the GRANDPA authority.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct SignedPrecommit {
+	/// Hash of the precommit target.
+	pub target_hash: H256,
+	/// Number of the precommit target.
+	pub target_number: u64,
+	/// Public key of the authority that has signed the precommit.
+	pub authority: [u8; 32],
+	/// The precommit signature.
+	pub signature: [u8; 64],
+	/// The message that has been signed.
+	pub signed_payload: Vec<u8>,
+}
+
+/// All data that is required by the Solidity light client to import finalized header.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct GrandpaFinalityArtifacts {
+	/// Finalized header.
+	pub header: HeaderCommitment,
+	/// Authority set that has finalized the header.
+	pub authority_set: AuthoritySetCommitment,
+	/// Authority set that is enacted by the finalized header, if it is changing the set.
+	pub next_authority_set: Option<AuthoritySetCommitment>,
+	/// The round this justification is valid for.
+	pub round: u64,
+	/// Precommits of the justification.
+	pub precommits: Vec<SignedPrecommit>,
+	/// Hashes and parent hashes of the headers that prove that precommit targets are descendants of
+	/// the finalized header.
+	pub votes_ancestries: Vec<(H256, H256)>,
+}
+
+/// BEEFY commitment, signed by the validators.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct BeefyCommitment {
+	/// MMR root at the commitment block.
+	pub payload: H256,
+	/// Number of the commitment block.
+	pub block_number: u64,
+	/// Id of the validator set that has signed the commitment.
+	pub validator_set_id: u64,
+}
+
+/// BEEFY validator set commitment.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct BeefyValidatorSetCommitment {
+	/// Validator set id.
+	pub id: u64,
+	/// Ethereum addresses of validators.
+	pub validators: Vec<[u8; 20]>,
+}
+
+/// Commitment signature of the BEEFY validator.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct BeefySignature {
+	/// Index of the validator in the validator set.
+	pub validator_index: u32,
+	/// ECDSA signature of the `keccak256` hash of the encoded commitment.
+	pub signature: [u8; 65],
+}
+
+/// Proof of the MMR leaf.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct MmrLeafProof {
+	/// The SCALE-encoded leaf.
+	pub leaf: Vec<u8>,
+	/// Index of the leaf in the MMR.
+	pub leaf_index: u64,
+	/// Number of leaves in the MMR.
+	pub leaf_count: u64,
+	/// Proof items (hashes of MMR nodes).
+	pub items: Vec<H256>,
+}
+
+/// All data that is required by the Solidity light client to import header, finalized by BEEFY.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct BeefyFinalityArtifacts {
+	/// Signed commitment.
+	pub commitment: BeefyCommitment,
+	/// The SCALE-encoded commitment.
+	pub encoded_commitment: Vec<u8>,
+	/// Validator set that has signed the commitment.
+	pub validator_set: BeefyValidatorSetCommitment,
+	/// Signatures of validators that have signed the commitment.
+	pub signatures: Vec<BeefySignature>,
+	/// Header that is committed to by the MMR leaf.
+	pub header: HeaderCommitment,
+	/// Proof of the MMR leaf.
+	pub leaf_proof: MmrLeafProof,
+}
+
+impl HeaderCommitment {
+	/// Create commitment to the given header.
+	pub fn new<Header: HeaderT<Hash = H256>>(header: &Header) -> Self {
+		HeaderCommitment {
+			number: (*header.number()).unique_saturated_into(),
+			hash: header.hash(),
+			parent_hash: *header.parent_hash(),
+			state_root: *header.state_root(),
+			extrinsics_root: *header.extrinsics_root(),
+		}
+	}
+
+	/// Append packed header to the buffer.
+	fn encode_packed_to(&self, buffer: &mut Vec<u8>) {
+		buffer.extend_from_slice(&self.number.to_be_bytes());
+		buffer.extend_from_slice(self.hash.as_bytes());
+		buffer.extend_from_slice(self.parent_hash.as_bytes());
+		buffer.extend_from_slice(self.state_root.as_bytes());
+		buffer.extend_from_slice(self.extrinsics_root.as_bytes());
+	}
+}
+
+impl AuthoritySetCommitment {
+	/// Create commitment to the given GRANDPA authority set.
+	pub fn new(set_id: SetId, authorities: &AuthorityList) -> Self {
+		AuthoritySetCommitment {
+			set_id,
+			authorities: authorities
+				.iter()
+				.map(|(id, weight)| (raw_authority_id(id), *weight))
+				.collect(),
+		}
+	}
+
+	/// Append packed authority set to the buffer.
+	fn encode_packed_to(&self, buffer: &mut Vec<u8>) {
+		buffer.extend_from_slice(&self.set_id.to_be_bytes());
+		encode_packed_length(self.authorities.len(), buffer);
+		for (authority, weight) in &self.authorities {
+			buffer.extend_from_slice(authority);
+			buffer.extend_from_slice(&weight.to_be_bytes());
+		}
+	}
+}
+
+impl GrandpaFinalityArtifacts {
+	/// Prepare finality artifacts for the given header.
+	///
+	/// The `justification` must be generated by the `authorities` set with given `set_id`. If the header
+	/// enacts new authority set, it must be passed as `next_authorities`.
+	pub fn new<Header: HeaderT<Hash = H256>>(
+		header: &Header,
+		justification: &GrandpaJustification<Header>,
+		set_id: SetId,
+		authorities: &AuthorityList,
+		next_authorities: Option<&AuthorityList>,
+	) -> Self {
+		GrandpaFinalityArtifacts {
+			header: HeaderCommitment::new(header),
+			authority_set: AuthoritySetCommitment::new(set_id, authorities),
+			next_authority_set: next_authorities
+				.map(|next_authorities| AuthoritySetCommitment::new(set_id + 1, next_authorities)),
+			round: justification.round,
+			precommits: justification
+				.commit
+				.precommits
+				.iter()
+				.map(|signed| {
+					let raw_signature: &[u8] = signed.signature.as_ref();
+					let mut signature = [0u8; 64];
+					signature.copy_from_slice(raw_signature);
+					SignedPrecommit {
+						target_hash: signed.precommit.target_hash,
+						target_number: signed.precommit.target_number.unique_saturated_into(),
+						authority: raw_authority_id(&signed.id),
+						signature,
+						signed_payload: sp_finality_grandpa::localized_payload(
+							justification.round,
+							set_id,
+							&Message::Precommit(signed.precommit.clone()),
+						),
+					}
+				})
+				.collect(),
+			votes_ancestries: justification
+				.votes_ancestries
+				.iter()
+				.map(|header| (header.hash(), *header.parent_hash()))
+				.collect(),
+		}
+	}
+
+	/// Encode artifacts using tightly packed layout.
+	pub fn encode_packed(&self) -> Vec<u8> {
+		let mut buffer = Vec::new();
+
+		self.header.encode_packed_to(&mut buffer);
+		self.authority_set.encode_packed_to(&mut buffer);
+		match self.next_authority_set {
+			Some(ref next_authority_set) => {
+				buffer.push(1);
+				next_authority_set.encode_packed_to(&mut buffer);
+			}
+			None => buffer.push(0),
+		}
+
+		buffer.extend_from_slice(&self.round.to_be_bytes());
+		encode_packed_length(self.precommits.len(), &mut buffer);
+		for precommit in &self.precommits {
+			buffer.extend_from_slice(precommit.target_hash.as_bytes());
+			buffer.extend_from_slice(&precommit.target_number.to_be_bytes());
+			buffer.extend_from_slice(&precommit.authority);
+			buffer.extend_from_slice(&precommit.signature);
+			encode_packed_length(precommit.signed_payload.len(), &mut buffer);
+			buffer.extend_from_slice(&precommit.signed_payload);
+		}
+
+		encode_packed_length(self.votes_ancestries.len(), &mut buffer);
+		for (hash, parent_hash) in &self.votes_ancestries {
+			buffer.extend_from_slice(hash.as_bytes());
+			buffer.extend_from_slice(parent_hash.as_bytes());
+		}
+
+		buffer
+	}
+}
+
+impl BeefyFinalityArtifacts {
+	/// Encode artifacts using tightly packed layout.
+	pub fn encode_packed(&self) -> Vec<u8> {
+		let mut buffer = Vec::new();
+
+		buffer.extend_from_slice(self.commitment.payload.as_bytes());
+		buffer.extend_from_slice(&self.commitment.block_number.to_be_bytes());
+		buffer.extend_from_slice(&self.commitment.validator_set_id.to_be_bytes());
+		encode_packed_length(self.encoded_commitment.len(), &mut buffer);
+		buffer.extend_from_slice(&self.encoded_commitment);
+
+		buffer.extend_from_slice(&self.validator_set.id.to_be_bytes());
+		encode_packed_length(self.validator_set.validators.len(), &mut buffer);
+		for validator in &self.validator_set.validators {
+			buffer.extend_from_slice(validator);
+		}
+
+		encode_packed_length(self.signatures.len(), &mut buffer);
+		for signature in &self.signatures {
+			buffer.extend_from_slice(&signature.validator_index.to_be_bytes());
+			buffer.extend_from_slice(&signature.signature);
+		}
+
+		self.header.encode_packed_to(&mut buffer);
+
+		encode_packed_length(self.leaf_proof.leaf.len(), &mut buffer);
+		buffer.extend_from_slice(&self.leaf_proof.leaf);
+		buffer.extend_from_slice(&self.leaf_proof.leaf_index.to_be_bytes());
+		buffer.extend_from_slice(&self.leaf_proof.leaf_count.to_be_bytes());
+		encode_packed_length(self.leaf_proof.items.len(), &mut buffer);
+		for item in &self.leaf_proof.items {
+			buffer.extend_from_slice(item.as_bytes());
+		}
+
+		buffer
+	}
+}
+
+/// Append list length to the buffer.
+fn encode_packed_length(length: usize, buffer: &mut Vec<u8>) {
+	buffer.extend_from_slice(&(length as u32).to_be_bytes());
+}
+
+/// Return raw public key of GRANDPA authority.
+fn raw_authority_id(id: &AuthorityId) -> [u8; 32] {
+	let raw_id: &[u8] = id.as_ref();
+	let mut result = [0u8; 32];
+	result.copy_from_slice(raw_id);
+	result
+}
@@ -21,6 +21,21 @@ use bp_test_utils::*;
 
 type TestHeader = sp_runtime::testing::Header;
 
+/// Authorities of the voter set where single precommit is not required to reach the threshold.
+fn four_authorities() -> Vec<(Account, u64)> {
+	vec![(ALICE, 1), (BOB, 1), (CHARLIE, 1), (DAVE, 1)]
+}
+
+fn four_authorities_voter_set() -> finality_grandpa::voter_set::VoterSet<sp_finality_grandpa::AuthorityId> {
+	finality_grandpa::voter_set::VoterSet::new(
+		four_authorities()
+			.into_iter()
+			.map(|(id, w)| (id.into(), w))
+			.collect::<Vec<_>>(),
+	)
+	.unwrap()
+}
+
 #[test]
 fn valid_justification_accepted() {
 	let authorities = vec![(ALICE, 1), (BOB, 1), (CHARLIE, 1), (DAVE, 1), (EVE, 1)];
@@ -189,3 +204,86 @@ fn justification_is_invalid_if_we_dont_meet_threshold() {
 		Err(Error::InvalidJustificationCommit),
 	);
 }
+
+#[test]
+fn justification_with_duplicate_precommits_is_invalid_if_we_dont_meet_threshold() {
+	let mut justification = make_default_justification::<TestHeader>(&test_header(1));
+	let duplicate_precommit = justification.commit.precommits[0].clone();
+	justification.commit.precommits[1] = duplicate_precommit.clone();
+	justification.commit.precommits[2] = duplicate_precommit;
+
+	assert_eq!(
+		verify_justification::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set(),
+			&justification,
+		),
+		Err(Error::InvalidJustificationCommit),
+	);
+}
+
+#[test]
+fn justification_with_duplicate_precommits_accepted_if_threshold_is_reached() {
+	let mut justification = make_default_justification::<TestHeader>(&test_header(1));
+	let duplicate_precommit = justification.commit.precommits[0].clone();
+	justification.commit.precommits.insert(1, duplicate_precommit);
+
+	assert_eq!(
+		verify_justification::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set(),
+			&justification,
+		),
+		Ok(()),
+	);
+}
+
+#[test]
+fn signatures_of_precommits_above_threshold_are_not_verified() {
+	let params = JustificationGeneratorParams {
+		header: test_header(1),
+		round: TEST_GRANDPA_ROUND,
+		set_id: TEST_GRANDPA_SET_ID,
+		authorities: four_authorities(),
+		votes: 1,
+		forks: 1,
+	};
+	let mut justification = make_justification_for_header::<TestHeader>(params);
+	justification.commit.precommits[3].signature = Default::default();
+
+	assert_eq!(
+		verify_justification::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&four_authorities_voter_set(),
+			&justification,
+		),
+		Ok(()),
+	);
+}
+
+#[test]
+fn justification_with_precommit_above_threshold_not_descendant_of_target_rejected() {
+	let params = JustificationGeneratorParams {
+		header: test_header(1),
+		round: TEST_GRANDPA_ROUND,
+		set_id: TEST_GRANDPA_SET_ID,
+		authorities: four_authorities(),
+		votes: 1,
+		forks: 1,
+	};
+	let mut justification = make_justification_for_header::<TestHeader>(params);
+	justification.commit.precommits[3].precommit.target_hash = header_id::<TestHeader>(10).0;
+
+	assert_eq!(
+		verify_justification::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&four_authorities_voter_set(),
+			&justification,
+		),
+		Err(Error::InvalidPrecommitAncestryProof),
+	);
+}
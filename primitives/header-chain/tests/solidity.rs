@@ -0,0 +1,135 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for finality artifacts that are consumed by the Solidity light client.
+
+use bp_header_chain::solidity::{
+	BeefyCommitment, BeefyFinalityArtifacts, BeefySignature, BeefyValidatorSetCommitment, GrandpaFinalityArtifacts,
+	HeaderCommitment, MmrLeafProof,
+};
+use bp_test_utils::*;
+use sp_core::{ed25519, Pair, H256};
+use sp_runtime::traits::Header as HeaderT;
+
+type TestHeader = sp_runtime::testing::Header;
+
+#[test]
+fn finality_artifacts_contain_verifiable_precommits() {
+	let header = test_header::<TestHeader>(1);
+	let justification = make_default_justification(&header);
+	let artifacts =
+		GrandpaFinalityArtifacts::new(&header, &justification, TEST_GRANDPA_SET_ID, &authority_list(), None);
+
+	assert_eq!(artifacts.header.number, 1);
+	assert_eq!(artifacts.header.hash, header.hash());
+	assert_eq!(artifacts.authority_set.set_id, TEST_GRANDPA_SET_ID);
+	assert_eq!(artifacts.authority_set.authorities.len(), authority_list().len());
+	assert_eq!(artifacts.next_authority_set, None);
+	assert_eq!(artifacts.round, TEST_GRANDPA_ROUND);
+	assert_eq!(artifacts.precommits.len(), justification.commit.precommits.len());
+	assert_eq!(artifacts.votes_ancestries.len(), justification.votes_ancestries.len());
+
+	for precommit in &artifacts.precommits {
+		assert!(ed25519::Pair::verify(
+			&ed25519::Signature::from_raw(precommit.signature),
+			&precommit.signed_payload,
+			&ed25519::Public::from_raw(precommit.authority),
+		));
+	}
+}
+
+#[test]
+fn finality_artifacts_are_tightly_packed() {
+	let header = test_header::<TestHeader>(1);
+	let justification = make_default_justification(&header);
+	let artifacts = GrandpaFinalityArtifacts::new(
+		&header,
+		&justification,
+		TEST_GRANDPA_SET_ID,
+		&authority_list(),
+		Some(&authority_list()),
+	);
+	let encoded = artifacts.encode_packed();
+
+	let header_size = 8 + 4 * 32;
+	let authority_set_size = 8 + 4 + artifacts.authority_set.authorities.len() * (32 + 8);
+	let precommits_size = 4 + artifacts
+		.precommits
+		.iter()
+		.map(|precommit| 32 + 8 + 32 + 64 + 4 + precommit.signed_payload.len())
+		.sum::<usize>();
+	let ancestries_size = 4 + artifacts.votes_ancestries.len() * 2 * 32;
+	assert_eq!(
+		encoded.len(),
+		header_size + authority_set_size + 1 + authority_set_size + 8 + precommits_size + ancestries_size,
+	);
+
+	// header number is encoded as big-endian uint64
+	assert_eq!(&encoded[..8], &[0, 0, 0, 0, 0, 0, 0, 1]);
+	// next authority set id follows the current set id
+	assert_eq!(
+		artifacts.next_authority_set.map(|set| set.set_id),
+		Some(TEST_GRANDPA_SET_ID + 1),
+	);
+}
+
+#[test]
+fn beefy_finality_artifacts_are_tightly_packed() {
+	let header = test_header::<TestHeader>(1);
+	let artifacts = BeefyFinalityArtifacts {
+		commitment: BeefyCommitment {
+			payload: H256::repeat_byte(1),
+			block_number: 2,
+			validator_set_id: 3,
+		},
+		encoded_commitment: vec![4; 44],
+		validator_set: BeefyValidatorSetCommitment {
+			id: 3,
+			validators: vec![[5; 20], [6; 20]],
+		},
+		signatures: vec![BeefySignature {
+			validator_index: 1,
+			signature: [7; 65],
+		}],
+		header: HeaderCommitment::new(&header),
+		leaf_proof: MmrLeafProof {
+			leaf: vec![8; 10],
+			leaf_index: 1,
+			leaf_count: 2,
+			items: vec![H256::repeat_byte(9)],
+		},
+	};
+	let encoded = artifacts.encode_packed();
+
+	let commitment_size = 32 + 8 + 8 + 4 + 44;
+	let validator_set_size = 8 + 4 + 2 * 20;
+	let signatures_size = 4 + 4 + 65;
+	let header_size = 8 + 4 * 32;
+	let leaf_proof_size = 4 + 10 + 8 + 8 + 4 + 32;
+	assert_eq!(
+		encoded.len(),
+		commitment_size + validator_set_size + signatures_size + header_size + leaf_proof_size,
+	);
+
+	// commitment block number is encoded as big-endian uint64
+	assert_eq!(&encoded[32..40], &[0, 0, 0, 0, 0, 0, 0, 2]);
+	// signature is prefixed with big-endian uint32 validator index
+	let signatures_offset = commitment_size + validator_set_size;
+	assert_eq!(
+		&encoded[signatures_offset..signatures_offset + 8],
+		&[0, 0, 0, 1, 0, 0, 0, 1]
+	);
+}
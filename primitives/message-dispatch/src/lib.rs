@@ -19,7 +19,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
-use bp_runtime::{ChainId, Size};
+use bp_runtime::{messages::MessageDispatchResult, ChainId, DeclaredWeight, Size};
 use codec::{Decode, Encode};
 use frame_support::RuntimeDebug;
 use sp_std::prelude::*;
@@ -30,8 +30,28 @@ pub type Weight = u64;
 /// Spec version type.
 pub type SpecVersion = u32;
 
+/// Prefix that is used by `pallet-multisig` to derive multisig account ids.
+pub const MULTISIG_ACCOUNT_DERIVATION_PREFIX: &[u8; 16] = b"modlpy/utilisuba";
+
+/// Prefix that is used by `pallet-proxy` to derive pure (anonymous) proxy account ids.
+pub const PURE_PROXY_ACCOUNT_DERIVATION_PREFIX: &[u8; 16] = b"modlpy/proxy____";
+
+/// Maximal number of signatories of the multisig account that may be used in the
+/// `CallOrigin::TargetAccountWithProof` origin.
+pub const MAX_MULTISIG_SIGNATORIES: u16 = 100;
+
+/// Weight of single signature verification, performed when the call origin is verified.
+///
+/// This is the weight of `sr25519` signature verification (which is the most expensive of
+/// supported signature schemes), rounded up.
+pub const SIGNATURE_VERIFICATION_WEIGHT: Weight = 50_000_000;
+
+/// Weight of reading proxies of the target chain account, performed when the
+/// `TargetAccountProof::PureProxy` proof is verified.
+pub const PROXY_CHECK_WEIGHT: Weight = 25_000_000;
+
 /// A generic trait to dispatch arbitrary messages delivered over the bridge.
-pub trait MessageDispatch<MessageId> {
+pub trait MessageDispatch<AccountId, MessageId> {
 	/// A type of the message to be dispatched.
 	type Message: codec::Decode;
 
@@ -52,7 +72,38 @@ pub trait MessageDispatch<MessageId> {
 	/// a sign that some other component has rejected the message even before it has
 	/// reached `dispatch` method (right now this may only be caused if we fail to decode
 	/// the whole message).
-	fn dispatch(source_chain: ChainId, target_chain: ChainId, id: MessageId, message: Result<Self::Message, ()>);
+	///
+	/// If the message pays dispatch fee at the target chain, the `pay_dispatch_fee` is called
+	/// with the dispatch origin account and the declared dispatch weight, right before the
+	/// call is dispatched. The message is not dispatched if the payment fails.
+	///
+	/// Returns the message dispatch result: whether the call has been dispatched successfully and
+	/// whether the dispatch fee has been paid at the target chain (if required).
+	fn dispatch<P: FnOnce(&AccountId, Weight) -> Result<(), ()>>(
+		source_chain: ChainId,
+		target_chain: ChainId,
+		id: MessageId,
+		message: Result<Self::Message, ()>,
+		pay_dispatch_fee: P,
+	) -> MessageDispatchResult;
+}
+
+/// Where the message dispatch fee is paid.
+#[derive(RuntimeDebug, Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchFeePayment {
+	/// The dispatch fee is paid by the message sender at the source chain, together with the
+	/// delivery fee.
+	AtSourceChain,
+	/// The dispatch fee is paid by the dispatch origin account at the target chain, right before
+	/// the message is dispatched. The message sender only pays the delivery fee at the source
+	/// chain.
+	AtTargetChain,
+}
+
+impl Default for DispatchFeePayment {
+	fn default() -> Self {
+		DispatchFeePayment::AtSourceChain
+	}
 }
 
 /// Origin of a Call when it is dispatched on the target chain.
@@ -97,6 +148,105 @@ pub enum CallOrigin<SourceChainAccountId, TargetChainAccountPublic, TargetChainS
 	/// Note that the derived account does not need to have a private key on the target chain. This
 	/// origin can therefore represent proxies, pallets, etc. as well as "regular" accounts.
 	SourceAccount(SourceChainAccountId),
+
+	/// Call is sent by `SourceChainAccountId` on the source chain. On the target chain it is
+	/// dispatched from an account that has no private key, but is controlled by other accounts
+	/// of the target chain: multisig account or pure proxy account.
+	///
+	/// The proof that the `SourceChainAccountId` controls the target account is carried by the
+	/// `TargetAccountProof`. Signatures are over the same payload that is used by the
+	/// `TargetAccount` origin, so the same replay considerations apply here.
+	TargetAccountWithProof(
+		SourceChainAccountId,
+		TargetAccountProof<TargetChainAccountPublic, TargetChainSignature>,
+	),
+}
+
+/// Proof of control over the target chain account that has no private key.
+#[derive(RuntimeDebug, Encode, Decode, Clone, PartialEq, Eq)]
+pub enum TargetAccountProof<TargetChainAccountPublic, TargetChainSignature> {
+	/// The target account is the multisig account of `pallet-multisig`.
+	///
+	/// The account is derived from all signatories and the threshold. The proof is valid if it
+	/// has at least `threshold` valid signatures of distinct signatories.
+	Multisig {
+		/// All signatories of the multisig account, in any order.
+		signatories: Vec<TargetChainAccountPublic>,
+		/// Number of signatures that are required to act on behalf of the multisig account.
+		threshold: u16,
+		/// Signatures of signatories, each paired with the signatory index in `signatories`.
+		signatures: Vec<(u16, TargetChainSignature)>,
+	},
+	/// The target account is the pure (anonymous) proxy account, spawned by `pallet-proxy`.
+	///
+	/// The account is derived from the spawner account and spawn data. The proof is valid if it
+	/// is signed by the spawner and the spawner is still a proxy of the pure proxy account.
+	PureProxy {
+		/// The account that has spawned the pure proxy account.
+		spawner: TargetChainAccountPublic,
+		/// SCALE-encoded `(height, ext_index, proxy_type, index)`, that has been used by the
+		/// `pallet-proxy` to derive the pure proxy account id.
+		spawn_data: Vec<u8>,
+		/// Signature of the spawner.
+		signature: TargetChainSignature,
+	},
+}
+
+impl<SourceChainAccountId, TargetChainAccountPublic, TargetChainSignature>
+	CallOrigin<SourceChainAccountId, TargetChainAccountPublic, TargetChainSignature>
+{
+	/// Returns kind of this origin.
+	pub fn kind(&self) -> CallOriginKind {
+		match *self {
+			CallOrigin::SourceRoot => CallOriginKind::SourceRoot,
+			CallOrigin::TargetAccount(..) => CallOriginKind::TargetAccount,
+			CallOrigin::SourceAccount(..) => CallOriginKind::SourceAccount,
+			CallOrigin::TargetAccountWithProof(..) => CallOriginKind::TargetAccountWithProof,
+		}
+	}
+
+	/// Returns weight of the origin verification, that is performed before the call is dispatched.
+	///
+	/// This weight is not declared by the message sender, so it must be added to the declared
+	/// call weight.
+	pub fn verification_weight(&self) -> Weight {
+		match *self {
+			CallOrigin::SourceRoot | CallOrigin::SourceAccount(..) => 0,
+			CallOrigin::TargetAccount(..) => SIGNATURE_VERIFICATION_WEIGHT,
+			CallOrigin::TargetAccountWithProof(_, ref proof) => proof.verification_weight(),
+		}
+	}
+}
+
+impl<TargetChainAccountPublic, TargetChainSignature>
+	TargetAccountProof<TargetChainAccountPublic, TargetChainSignature>
+{
+	/// Returns weight of the proof verification.
+	///
+	/// The multisig proof is rejected if it has more signatures than there are signatories, so
+	/// the number of verified signatures never exceeds `MAX_MULTISIG_SIGNATORIES`.
+	pub fn verification_weight(&self) -> Weight {
+		match *self {
+			TargetAccountProof::Multisig { ref signatures, .. } => {
+				let signatures = sp_std::cmp::min(signatures.len(), MAX_MULTISIG_SIGNATORIES as usize);
+				SIGNATURE_VERIFICATION_WEIGHT.saturating_mul(signatures as Weight)
+			}
+			TargetAccountProof::PureProxy { .. } => SIGNATURE_VERIFICATION_WEIGHT.saturating_add(PROXY_CHECK_WEIGHT),
+		}
+	}
+}
+
+/// Kind of the `CallOrigin`, without origin details.
+#[derive(RuntimeDebug, Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum CallOriginKind {
+	/// The `CallOrigin::SourceRoot` origin.
+	SourceRoot,
+	/// The `CallOrigin::TargetAccount` origin.
+	TargetAccount,
+	/// The `CallOrigin::SourceAccount` origin.
+	SourceAccount,
+	/// The `CallOrigin::TargetAccountWithProof` origin.
+	TargetAccountWithProof,
 }
 
 /// Message payload type used by dispatch module.
@@ -108,6 +258,12 @@ pub struct MessagePayload<SourceChainAccountId, TargetChainAccountPublic, Target
 	/// Weight of the call, declared by the message sender. If it is less than actual
 	/// static weight, the call is not dispatched.
 	pub weight: Weight,
+	/// Number of the target chain block, before which the message must be delivered. If the
+	/// message is delivered at this block or later, it is not dispatched. `None` if the message
+	/// never expires.
+	pub delivery_deadline: Option<u64>,
+	/// Where the dispatch fee is paid.
+	pub dispatch_fee_payment: DispatchFeePayment,
 	/// Call origin to be used during dispatch.
 	pub origin: CallOrigin<SourceChainAccountId, TargetChainAccountPublic, TargetChainSignature>,
 	/// The call itself.
@@ -121,3 +277,11 @@ impl<SourceChainAccountId, TargetChainAccountPublic, TargetChainSignature> Size
 		self.call.len() as _
 	}
 }
+
+impl<SourceChainAccountId, TargetChainAccountPublic, TargetChainSignature, Call> DeclaredWeight
+	for MessagePayload<SourceChainAccountId, TargetChainAccountPublic, TargetChainSignature, Call>
+{
+	fn declared_weight(&self) -> Weight {
+		self.weight.saturating_add(self.origin.verification_weight())
+	}
+}
@@ -22,10 +22,11 @@
 // Generated by `DecodeLimit::decode_with_depth_limit`
 #![allow(clippy::unnecessary_mut_passed)]
 
-use codec::{Decode, Encode};
+use codec::{Compact, CompactLen, Decode, Encode};
 use frame_support::RuntimeDebug;
-use sp_std::{collections::vec_deque::VecDeque, prelude::*};
+use sp_std::{collections::vec_deque::VecDeque, fmt, prelude::*, str::FromStr};
 
+pub mod nonces;
 pub mod source_chain;
 pub mod target_chain;
 
@@ -39,7 +40,130 @@ pub trait Parameter: frame_support::Parameter {
 }
 
 /// Lane identifier.
-pub type LaneId = [u8; 4];
+///
+/// Lane identifier is encoded as raw 4 bytes, so it is compatible with the old `[u8; 4]` lane
+/// identifiers and all storage keys/proofs that are using it. In human-readable form (`Display`,
+/// `FromStr` and serde), the lane is represented either by its hex-encoded bytes (e.g. `00000001`),
+/// or by the name of one of `WELL_KNOWN_LANES` (e.g. `p2p-governance`).
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, RuntimeDebug)]
+pub struct LaneId(pub [u8; 4]);
+
+/// Prefix that is used when lane identifier is derived from lane endpoints.
+pub const LANE_ID_DERIVATION_PREFIX: &[u8] = b"pallet-bridge/lane-id-derivation";
+
+/// Lanes that may be referenced by name instead of hex-encoded identifier.
+pub const WELL_KNOWN_LANES: &[(&str, LaneId)] =
+	&[("default", LaneId([0, 0, 0, 0])), ("p2p-governance", LaneId(*b"pgov"))];
+
+impl LaneId {
+	/// Derive lane identifier from the pair of lane endpoints (e.g. accounts, chains or pallets
+	/// that are exchanging messages over this lane).
+	///
+	/// The identifier doesn't depend on the order of endpoints, so both sides of the lane will
+	/// compute the same identifier.
+	pub fn from_endpoints<T: Encode>(endpoint1: &T, endpoint2: &T) -> Self {
+		let (endpoint1, endpoint2) = (endpoint1.encode(), endpoint2.encode());
+		let (min, max) = if endpoint1 <= endpoint2 {
+			(endpoint1, endpoint2)
+		} else {
+			(endpoint2, endpoint1)
+		};
+		let hash = (LANE_ID_DERIVATION_PREFIX, min, max).using_encoded(sp_io::hashing::blake2_256);
+		LaneId([hash[0], hash[1], hash[2], hash[3]])
+	}
+
+	/// Returns name of the lane if it is one of `WELL_KNOWN_LANES`.
+	pub fn name(&self) -> Option<&'static str> {
+		WELL_KNOWN_LANES
+			.iter()
+			.find(|(_, lane)| lane == self)
+			.map(|(name, _)| *name)
+	}
+}
+
+impl From<[u8; 4]> for LaneId {
+	fn from(lane: [u8; 4]) -> Self {
+		LaneId(lane)
+	}
+}
+
+impl From<LaneId> for [u8; 4] {
+	fn from(lane: LaneId) -> Self {
+		lane.0
+	}
+}
+
+impl AsRef<[u8]> for LaneId {
+	fn as_ref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl fmt::Display for LaneId {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for byte in &self.0 {
+			write!(f, "{:02x}", byte)?;
+		}
+		Ok(())
+	}
+}
+
+/// Error that is returned when lane identifier can't be parsed.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum LaneIdParseError {
+	/// The string is neither a name of well-known lane, nor a hex-encoded 4-bytes identifier.
+	InvalidLaneId,
+}
+
+impl fmt::Display for LaneIdParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			LaneIdParseError::InvalidLaneId => {
+				write!(f, "expected 8 hex digits or the name of well-known lane")
+			}
+		}
+	}
+}
+
+impl FromStr for LaneId {
+	type Err = LaneIdParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Some((_, lane)) = WELL_KNOWN_LANES.iter().find(|(name, _)| *name == s) {
+			return Ok(*lane);
+		}
+
+		let s = s.strip_prefix("0x").unwrap_or(s);
+		if s.len() != 8 {
+			return Err(LaneIdParseError::InvalidLaneId);
+		}
+
+		let mut lane = [0u8; 4];
+		for (i, byte) in lane.iter_mut().enumerate() {
+			*byte = s
+				.get(i * 2..i * 2 + 2)
+				.and_then(|digits| u8::from_str_radix(digits, 16).ok())
+				.ok_or(LaneIdParseError::InvalidLaneId)?;
+		}
+		Ok(LaneId(lane))
+	}
+}
+
+#[cfg(feature = "std")]
+impl serde::Serialize for LaneId {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.to_string())
+	}
+}
+
+#[cfg(feature = "std")]
+impl<'de> serde::Deserialize<'de> for LaneId {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let s = String::deserialize(deserializer)?;
+		s.parse()
+			.map_err(|e: LaneIdParseError| serde::de::Error::custom(e.to_string()))
+	}
+}
 
 /// Message nonce. Valid messages will never have 0 nonce.
 pub type MessageNonce = u64;
@@ -107,6 +231,20 @@ pub struct InboundLaneData<RelayerId> {
 	/// This value is updated indirectly when an `OutboundLane` state of the source
 	/// chain is received alongside with new messages delivery.
 	pub last_confirmed_nonce: MessageNonce,
+
+	/// Nonces of messages that have been delivered after their delivery deadline and haven't been
+	/// dispatched (ordered by message nonce).
+	///
+	/// All nonces in this queue are in range: `(self.last_confirmed_nonce; self.last_delivered_nonce()]`,
+	/// so it is bounded by the maximal number of unconfirmed messages at the inbound lane.
+	pub expired_messages: VecDeque<MessageNonce>,
+
+	/// Nonces of messages that had to pay dispatch fee at this chain, but the payment has failed
+	/// and they haven't been dispatched (ordered by message nonce).
+	///
+	/// All nonces in this queue are in range: `(self.last_confirmed_nonce; self.last_delivered_nonce()]`,
+	/// so it is bounded by the maximal number of unconfirmed messages at the inbound lane.
+	pub unpaid_messages: VecDeque<MessageNonce>,
 }
 
 impl<RelayerId> Default for InboundLaneData<RelayerId> {
@@ -114,20 +252,39 @@ impl<RelayerId> Default for InboundLaneData<RelayerId> {
 		InboundLaneData {
 			relayers: VecDeque::new(),
 			last_confirmed_nonce: 0,
+			expired_messages: VecDeque::new(),
+			unpaid_messages: VecDeque::new(),
 		}
 	}
 }
 
 impl<RelayerId> InboundLaneData<RelayerId> {
-	/// Returns approximate size of the struct, given number of entries in the `relayers` set and
-	/// size of each entry.
+	/// Returns approximate size of the struct, given number of entries in the `relayers` set,
+	/// size of each entry and number of entries in the `expired_messages` and `unpaid_messages`
+	/// queues.
+	///
+	/// Both queues are bounded by the maximal number of unconfirmed messages at the inbound lane,
+	/// so callers that need the upper bound should pass this number here.
 	///
 	/// Returns `None` if size overflows `u32` limits.
-	pub fn encoded_size_hint(relayer_id_encoded_size: u32, relayers_entries: u32) -> Option<u32> {
+	pub fn encoded_size_hint(
+		relayer_id_encoded_size: u32,
+		relayers_entries: u32,
+		expired_messages: u32,
+		unpaid_messages: u32,
+	) -> Option<u32> {
 		let message_nonce_size = 8;
 		let relayers_entry_size = relayer_id_encoded_size.checked_add(2 * message_nonce_size)?;
 		let relayers_size = relayers_entries.checked_mul(relayers_entry_size)?;
-		relayers_size.checked_add(message_nonce_size)
+		let nonces_queue_size = |entries: u32| {
+			entries
+				.checked_mul(message_nonce_size)?
+				.checked_add(Compact::<u32>::compact_len(&entries) as u32)
+		};
+		relayers_size
+			.checked_add(message_nonce_size)?
+			.checked_add(nonces_queue_size(expired_messages)?)?
+			.checked_add(nonces_queue_size(unpaid_messages)?)
 	}
 
 	/// Nonce of the last message that has been delivered to this (target) chain.
@@ -139,6 +296,39 @@ impl<RelayerId> InboundLaneData<RelayerId> {
 	}
 }
 
+/// Outbound message details, returned by runtime APIs.
+#[derive(Clone, Encode, Decode, RuntimeDebug, PartialEq, Eq)]
+pub struct MessageDetails<OutboundMessageFee> {
+	/// Nonce assigned to the message.
+	pub nonce: MessageNonce,
+	/// Message dispatch weight, declared by the submitter.
+	pub dispatch_weight: Weight,
+	/// Size of the encoded message.
+	pub size: u32,
+	/// Delivery+dispatch fee paid by the message submitter at the source chain.
+	pub delivery_and_dispatch_fee: OutboundMessageFee,
+}
+
+/// Record of the delivered message, that the messages pallet writes to the offchain indexing storage.
+///
+/// The record is written when the message is accepted by the inbound lane. Details of the dispatch
+/// (e.g. the dispatch error) are reported by the dispatcher using events of the `block_number` block.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct DeliveredMessageRecord<RelayerId, BlockNumber> {
+	/// ID of the message lane.
+	pub lane_id: LaneId,
+	/// Message nonce.
+	pub nonce: MessageNonce,
+	/// Relayer that has delivered the message.
+	pub relayer: RelayerId,
+	/// Number of the block where the message has been delivered.
+	pub block_number: BlockNumber,
+	/// True if the message has been dispatched successfully. False if the message has expired, has
+	/// been rejected by the dispatcher, has failed to pay dispatch fee or if the dispatched call has
+	/// failed.
+	pub dispatch_result: bool,
+}
+
 /// Gist of `InboundLaneData::relayers` field used by runtime APIs.
 #[derive(Clone, Default, Encode, Decode, RuntimeDebug, PartialEq, Eq)]
 pub struct UnrewardedRelayersState {
@@ -151,6 +341,19 @@ pub struct UnrewardedRelayersState {
 	pub total_messages: MessageNonce,
 }
 
+/// Limits of messages delivery transactions, imposed by the chain that is receiving messages.
+#[derive(Clone, Copy, Default, Encode, Decode, RuntimeDebug, PartialEq, Eq)]
+pub struct MessagesDeliveryLimits {
+	/// Maximal weight of the single normal extrinsic.
+	pub max_extrinsic_weight: Weight,
+	/// Maximal size of the single normal extrinsic.
+	pub max_extrinsic_size: u32,
+	/// Maximal number of unrewarded relayer entries at the inbound lane.
+	pub max_unrewarded_relayer_entries_at_inbound_lane: MessageNonce,
+	/// Maximal number of unconfirmed messages at the inbound lane.
+	pub max_unconfirmed_messages_at_inbound_lane: MessageNonce,
+}
+
 /// Outbound lane data.
 #[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
 pub struct OutboundLaneData {
@@ -174,6 +377,21 @@ impl Default for OutboundLaneData {
 	}
 }
 
+/// Policy of relaying messages over the inbound lane.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum RelayingPolicy {
+	/// Messages may be delivered by anyone.
+	Permissionless,
+	/// Messages may only be delivered by relayers from the lane whitelist.
+	Permissioned,
+}
+
+impl Default for RelayingPolicy {
+	fn default() -> Self {
+		RelayingPolicy::Permissionless
+	}
+}
+
 /// Returns total number of messages in the `InboundLaneData::relayers` vector.
 ///
 /// Returns `None` if there are more messages that `MessageNonce` may fit (i.e. `MessageNonce + 1`).
@@ -210,10 +428,12 @@ mod tests {
 
 	#[test]
 	fn inbound_lane_data_returns_correct_hint() {
-		let expected_size = InboundLaneData::<u8>::encoded_size_hint(1, 13);
+		let expected_size = InboundLaneData::<u8>::encoded_size_hint(1, 13, 64, 32);
 		let actual_size = InboundLaneData {
 			relayers: (1u8..=13u8).map(|i| (i as _, i as _, i)).collect(),
 			last_confirmed_nonce: 13,
+			expired_messages: (14..78).collect(),
+			unpaid_messages: (78..110).collect(),
 		}
 		.encode()
 		.len();
@@ -225,4 +445,45 @@ mod tests {
 			expected_size,
 		);
 	}
+
+	#[test]
+	fn lane_id_is_encoded_as_raw_bytes() {
+		assert_eq!(LaneId(*b"test").encode(), b"test".to_vec());
+		assert_eq!(LaneId::decode(&mut &b"test"[..]), Ok(LaneId(*b"test")));
+	}
+
+	#[test]
+	fn lane_id_is_formatted_and_parsed() {
+		assert_eq!(LaneId([0, 0, 0, 1]).to_string(), "00000001");
+		assert_eq!("00000001".parse(), Ok(LaneId([0, 0, 0, 1])));
+		assert_eq!("0x746573ff".parse(), Ok(LaneId([0x74, 0x65, 0x73, 0xff])));
+		assert_eq!("default".parse(), Ok(LaneId([0, 0, 0, 0])));
+		assert_eq!("p2p-governance".parse(), Ok(LaneId(*b"pgov")));
+		assert_eq!(LaneId(*b"pgov").name(), Some("p2p-governance"));
+		assert_eq!(LaneId([0, 0, 0, 1]).name(), None);
+
+		assert!("0000001".parse::<LaneId>().is_err());
+		assert!("0000000g".parse::<LaneId>().is_err());
+		assert!("00000000a".parse::<LaneId>().is_err());
+		assert!("unknown-lane".parse::<LaneId>().is_err());
+	}
+
+	#[test]
+	fn lane_id_is_serialized_as_string() {
+		let lane = LaneId([0, 0, 0, 1]);
+		let serialized = serde_json::to_string(&lane).unwrap();
+		assert_eq!(serialized, "\"00000001\"");
+		assert_eq!(serde_json::from_str::<LaneId>(&serialized).unwrap(), lane);
+		assert_eq!(
+			serde_json::from_str::<LaneId>("\"p2p-governance\"").unwrap(),
+			LaneId(*b"pgov")
+		);
+	}
+
+	#[test]
+	fn lane_id_derived_from_endpoints_does_not_depend_on_order() {
+		let lane = LaneId::from_endpoints(&1u64, &2u64);
+		assert_eq!(lane, LaneId::from_endpoints(&2u64, &1u64));
+		assert_ne!(lane, LaneId::from_endpoints(&1u64, &3u64));
+	}
 }
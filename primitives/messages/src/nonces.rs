@@ -0,0 +1,319 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Utilities for working with ranges of message nonces.
+//!
+//! Both the messages pallet (e.g. when it computes messages that are confirmed by the delivery proof)
+//! and the messages relay (e.g. when it tracks nonces that are queued for delivery) are working with
+//! inclusive ranges of nonces. This module provides the single implementation of the range arithmetic
+//! that is shared by both sides.
+
+use crate::MessageNonce;
+
+use frame_support::RuntimeDebug;
+use sp_std::{cmp, ops::RangeInclusive, prelude::*};
+
+/// Non-empty inclusive range of message nonces.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct DeliveredMessages {
+	/// Nonce of the first message in the range.
+	begin: MessageNonce,
+	/// Nonce of the last message in the range.
+	end: MessageNonce,
+}
+
+impl DeliveredMessages {
+	/// Create range of messages with nonces in `begin..=end`.
+	///
+	/// Returns `None` if the range is empty (i.e. `begin > end`).
+	pub fn new(begin: MessageNonce, end: MessageNonce) -> Option<Self> {
+		if begin <= end {
+			Some(DeliveredMessages { begin, end })
+		} else {
+			None
+		}
+	}
+
+	/// Create range that contains single message.
+	pub fn single(nonce: MessageNonce) -> Self {
+		DeliveredMessages {
+			begin: nonce,
+			end: nonce,
+		}
+	}
+
+	/// Create range of messages from the inclusive range of nonces.
+	///
+	/// Returns `None` if the range is empty.
+	pub fn from_range(range: &RangeInclusive<MessageNonce>) -> Option<Self> {
+		Self::new(*range.start(), *range.end())
+	}
+
+	/// Nonce of the first message in the range.
+	pub fn begin(&self) -> MessageNonce {
+		self.begin
+	}
+
+	/// Nonce of the last message in the range.
+	pub fn end(&self) -> MessageNonce {
+		self.end
+	}
+
+	/// Returns total number of messages in the range.
+	///
+	/// The only range that has more than `MessageNonce::MAX` messages is `0..=MessageNonce::MAX`. For
+	/// this range, `MessageNonce::MAX` is returned.
+	pub fn total_messages(&self) -> MessageNonce {
+		(self.end - self.begin).saturating_add(1)
+	}
+
+	/// Returns true if the message with given nonce is in the range.
+	pub fn contains_message(&self, nonce: MessageNonce) -> bool {
+		self.begin <= nonce && nonce <= self.end
+	}
+
+	/// Returns true if all messages of the `other` range are in this range.
+	pub fn contains(&self, other: &Self) -> bool {
+		self.begin <= other.begin && other.end <= self.end
+	}
+
+	/// Returns iterator over all nonces in the range.
+	pub fn iter(&self) -> RangeInclusive<MessageNonce> {
+		self.begin..=self.end
+	}
+
+	/// Merge two ranges.
+	///
+	/// Returns `None` if ranges are neither overlapping, nor adjacent, because the result can't be
+	/// represented by the single range.
+	pub fn merge(&self, other: &Self) -> Option<Self> {
+		let are_joinable = self.begin <= other.end.saturating_add(1) && other.begin <= self.end.saturating_add(1);
+		if are_joinable {
+			Some(DeliveredMessages {
+				begin: cmp::min(self.begin, other.begin),
+				end: cmp::max(self.end, other.end),
+			})
+		} else {
+			None
+		}
+	}
+
+	/// Returns messages that are in both ranges.
+	pub fn intersect(&self, other: &Self) -> Option<Self> {
+		Self::new(cmp::max(self.begin, other.begin), cmp::min(self.end, other.end))
+	}
+
+	/// Remove messages of the `other` range from this range.
+	///
+	/// Returns messages that are left before and after the `other` range.
+	pub fn subtract(&self, other: &Self) -> (Option<Self>, Option<Self>) {
+		let before = other
+			.begin
+			.checked_sub(1)
+			.and_then(|end| Self::new(self.begin, cmp::min(self.end, end)));
+		let after = other
+			.end
+			.checked_add(1)
+			.and_then(|begin| Self::new(cmp::max(self.begin, begin), self.end));
+		(before, after)
+	}
+
+	/// Returns messages of this range that have nonces greater than the given nonce.
+	pub fn greater_than(&self, nonce: MessageNonce) -> Option<Self> {
+		nonce
+			.checked_add(1)
+			.and_then(|begin| Self::new(cmp::max(self.begin, begin), self.end))
+	}
+}
+
+impl From<DeliveredMessages> for RangeInclusive<MessageNonce> {
+	fn from(messages: DeliveredMessages) -> Self {
+		messages.iter()
+	}
+}
+
+/// Returns ranges of messages that are missing between given ranges.
+///
+/// Ranges are expected to be ordered by their begin nonces. Overlapping and adjacent ranges have no
+/// gaps between them.
+pub fn find_gaps<'a>(ranges: impl IntoIterator<Item = &'a DeliveredMessages>) -> Vec<DeliveredMessages> {
+	let mut gaps = Vec::new();
+	let mut best_end: Option<MessageNonce> = None;
+	for range in ranges {
+		if let Some(best_end) = best_end {
+			if let Some(gap) = best_end
+				.checked_add(1)
+				.zip(range.begin.checked_sub(1))
+				.and_then(|(begin, end)| DeliveredMessages::new(begin, end))
+			{
+				gaps.push(gap);
+			}
+		}
+		best_end = Some(
+			best_end
+				.map(|best_end| cmp::max(best_end, range.end))
+				.unwrap_or(range.end),
+		);
+	}
+	gaps
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn messages(begin: MessageNonce, end: MessageNonce) -> DeliveredMessages {
+		DeliveredMessages::new(begin, end).unwrap()
+	}
+
+	#[test]
+	fn empty_range_is_rejected() {
+		assert_eq!(DeliveredMessages::new(2, 1), None);
+		assert_eq!(DeliveredMessages::from_range(&(2..=1)), None);
+		assert_eq!(DeliveredMessages::new(1, 1), Some(DeliveredMessages::single(1)));
+		assert_eq!(DeliveredMessages::from_range(&(1..=5)), Some(messages(1, 5)));
+	}
+
+	#[test]
+	fn total_messages_works() {
+		assert_eq!(DeliveredMessages::single(5).total_messages(), 1);
+		assert_eq!(messages(1, 10).total_messages(), 10);
+		assert_eq!(messages(1, MessageNonce::MAX).total_messages(), MessageNonce::MAX);
+		assert_eq!(messages(0, MessageNonce::MAX).total_messages(), MessageNonce::MAX);
+	}
+
+	#[test]
+	fn contains_message_works() {
+		let range = messages(10, 20);
+		assert!(!range.contains_message(9));
+		assert!(range.contains_message(10));
+		assert!(range.contains_message(15));
+		assert!(range.contains_message(20));
+		assert!(!range.contains_message(21));
+	}
+
+	#[test]
+	fn contains_works() {
+		let range = messages(10, 20);
+		assert!(range.contains(&messages(10, 20)));
+		assert!(range.contains(&messages(12, 15)));
+		assert!(range.contains(&messages(15, 20)));
+		assert!(!range.contains(&messages(5, 15)));
+		assert!(!range.contains(&messages(15, 25)));
+		assert!(!range.contains(&messages(5, 25)));
+		assert!(!range.contains(&messages(21, 25)));
+	}
+
+	#[test]
+	fn iter_works() {
+		assert_eq!(messages(1, 3).iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+		assert_eq!(DeliveredMessages::single(7).iter().collect::<Vec<_>>(), vec![7]);
+		assert_eq!(RangeInclusive::from(messages(1, 3)), 1..=3);
+	}
+
+	#[test]
+	fn merge_works() {
+		// overlapping ranges
+		assert_eq!(messages(1, 10).merge(&messages(5, 15)), Some(messages(1, 15)));
+		assert_eq!(messages(5, 15).merge(&messages(1, 10)), Some(messages(1, 15)));
+		// nested ranges
+		assert_eq!(messages(1, 10).merge(&messages(3, 5)), Some(messages(1, 10)));
+		assert_eq!(messages(3, 5).merge(&messages(1, 10)), Some(messages(1, 10)));
+		// adjacent ranges
+		assert_eq!(messages(1, 10).merge(&messages(11, 15)), Some(messages(1, 15)));
+		assert_eq!(messages(11, 15).merge(&messages(1, 10)), Some(messages(1, 15)));
+		// ranges with gap
+		assert_eq!(messages(1, 10).merge(&messages(12, 15)), None);
+		assert_eq!(messages(12, 15).merge(&messages(1, 10)), None);
+		// ranges at the nonce limits
+		assert_eq!(
+			messages(0, MessageNonce::MAX).merge(&messages(MessageNonce::MAX, MessageNonce::MAX)),
+			Some(messages(0, MessageNonce::MAX)),
+		);
+	}
+
+	#[test]
+	fn intersect_works() {
+		assert_eq!(messages(1, 10).intersect(&messages(5, 15)), Some(messages(5, 10)));
+		assert_eq!(messages(5, 15).intersect(&messages(1, 10)), Some(messages(5, 10)));
+		assert_eq!(messages(1, 10).intersect(&messages(3, 5)), Some(messages(3, 5)));
+		assert_eq!(messages(1, 10).intersect(&messages(10, 15)), Some(messages(10, 10)));
+		assert_eq!(messages(1, 10).intersect(&messages(11, 15)), None);
+		assert_eq!(messages(11, 15).intersect(&messages(1, 10)), None);
+	}
+
+	#[test]
+	fn subtract_works() {
+		// other range is inside
+		assert_eq!(
+			messages(1, 10).subtract(&messages(3, 5)),
+			(Some(messages(1, 2)), Some(messages(6, 10))),
+		);
+		// other range covers the begin
+		assert_eq!(messages(5, 10).subtract(&messages(1, 7)), (None, Some(messages(8, 10))));
+		// other range covers the end
+		assert_eq!(messages(5, 10).subtract(&messages(7, 15)), (Some(messages(5, 6)), None));
+		// other range covers everything
+		assert_eq!(messages(5, 10).subtract(&messages(1, 15)), (None, None));
+		assert_eq!(messages(5, 10).subtract(&messages(5, 10)), (None, None));
+		// ranges are not overlapping
+		assert_eq!(
+			messages(5, 10).subtract(&messages(11, 15)),
+			(Some(messages(5, 10)), None)
+		);
+		assert_eq!(messages(5, 10).subtract(&messages(1, 4)), (None, Some(messages(5, 10))));
+		// ranges at the nonce limits
+		assert_eq!(
+			messages(0, MessageNonce::MAX).subtract(&messages(0, 0)),
+			(None, Some(messages(1, MessageNonce::MAX))),
+		);
+		assert_eq!(
+			messages(0, MessageNonce::MAX).subtract(&messages(MessageNonce::MAX, MessageNonce::MAX)),
+			(Some(messages(0, MessageNonce::MAX - 1)), None),
+		);
+	}
+
+	#[test]
+	fn greater_than_works() {
+		let range = messages(20, 30);
+		assert_eq!(range.greater_than(10), Some(messages(20, 30)));
+		assert_eq!(range.greater_than(19), Some(messages(20, 30)));
+		assert_eq!(range.greater_than(20), Some(messages(21, 30)));
+		assert_eq!(range.greater_than(25), Some(messages(26, 30)));
+		assert_eq!(range.greater_than(29), Some(messages(30, 30)));
+		assert_eq!(range.greater_than(30), None);
+		assert_eq!(range.greater_than(MessageNonce::MAX), None);
+		assert_eq!(messages(0, MessageNonce::MAX).greater_than(MessageNonce::MAX), None);
+	}
+
+	#[test]
+	fn find_gaps_works() {
+		assert_eq!(find_gaps(&[]), vec![]);
+		assert_eq!(find_gaps(&[messages(1, 10)]), vec![]);
+		assert_eq!(find_gaps(&[messages(1, 10), messages(11, 20)]), vec![]);
+		assert_eq!(find_gaps(&[messages(1, 10), messages(5, 20)]), vec![]);
+		assert_eq!(
+			find_gaps(&[messages(1, 10), messages(12, 20), messages(30, 40)]),
+			vec![messages(11, 11), messages(21, 29)],
+		);
+		// nested range doesn't produce gaps
+		assert_eq!(find_gaps(&[messages(1, 20), messages(5, 10), messages(15, 25)]), vec![],);
+		assert_eq!(
+			find_gaps(&[messages(1, 20), messages(5, 10), messages(25, 30)]),
+			vec![messages(21, 24)],
+		);
+	}
+}
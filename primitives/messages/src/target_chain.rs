@@ -16,9 +16,9 @@
 
 //! Primitives of messages module, that are used on the target chain.
 
-use crate::{LaneId, Message, MessageData, MessageKey, OutboundLaneData};
+use crate::{LaneId, Message, MessageData, MessageKey, MessageNonce, OutboundLaneData};
 
-use bp_runtime::Size;
+use bp_runtime::{messages::MessageDispatchResult, Size};
 use codec::{Decode, Encode, Error as CodecError};
 use frame_support::{weights::Weight, Parameter, RuntimeDebug};
 use sp_std::{collections::btree_map::BTreeMap, fmt::Debug, prelude::*};
@@ -84,7 +84,7 @@ pub trait SourceHeaderChain<Fee> {
 }
 
 /// Called when inbound message is received.
-pub trait MessageDispatch<Fee> {
+pub trait MessageDispatch<AccountId, Fee> {
 	/// Decoded message payload type. Valid message may contain invalid payload. In this case
 	/// message is delivered, but dispatch fails. Therefore, two separate types of payload
 	/// (opaque `MessagePayload` used in delivery and this `DispatchPayload` used in dispatch).
@@ -96,11 +96,45 @@ pub trait MessageDispatch<Fee> {
 	/// of dispatch weight.
 	fn dispatch_weight(message: &DispatchMessage<Self::DispatchPayload, Fee>) -> Weight;
 
+	/// Returns true if the message has been delivered too late and must not be dispatched.
+	///
+	/// Expired messages are still accepted by the inbound lane (so the lane is not blocked),
+	/// but they're never dispatched. Their nonces are reported back to the source chain
+	/// with the delivery confirmation.
+	fn is_expired(message: &DispatchMessage<Self::DispatchPayload, Fee>) -> bool;
+
 	/// Called when inbound message is received.
 	///
 	/// It is up to the implementers of this trait to determine whether the message
 	/// is invalid (i.e. improperly encoded, has too large weight, ...) or not.
-	fn dispatch(message: DispatchMessage<Self::DispatchPayload, Fee>);
+	///
+	/// If the message pays dispatch fee at this chain, the fee is paid to the `relayer_account`
+	/// (the submitter of the delivery transaction). The returned result says whether the message
+	/// has been dispatched successfully and whether the dispatch fee (if any) has been paid.
+	fn dispatch(
+		relayer_account: &AccountId,
+		message: DispatchMessage<Self::DispatchPayload, Fee>,
+	) -> MessageDispatchResult;
+}
+
+/// Called when new inbound messages are delivered to this chain.
+///
+/// The hook is called once per lane for every messages delivery transaction that has brought
+/// at least one new message. It may be used e.g. to reward the delivery transaction submitter
+/// on this chain. Its weight is not accounted by the messages pallet, so the implementation
+/// must be cheap (few storage reads and writes).
+pub trait OnMessagesDelivered<AccountId> {
+	/// Called when `submitter` has delivered `messages_count` new messages over the `lane`.
+	fn on_messages_delivered(lane: LaneId, submitter: &AccountId, messages_count: MessageNonce);
+
+	/// Called when the delivery transaction has proved the state of the bridged chain outbound
+	/// `lane`. The `pending_messages` is the number of messages that have been generated at the
+	/// bridged chain, but are not yet delivered to this chain.
+	fn on_pending_messages_proved(_lane: LaneId, _pending_messages: MessageNonce) {}
+}
+
+impl<AccountId> OnMessagesDelivered<AccountId> for () {
+	fn on_messages_delivered(_lane: LaneId, _submitter: &AccountId, _messages_count: MessageNonce) {}
 }
 
 impl<Message> Default for ProvedLaneMessages<Message> {
@@ -149,12 +183,24 @@ impl<Fee> SourceHeaderChain<Fee> for ForbidInboundMessages {
 	}
 }
 
-impl<Fee> MessageDispatch<Fee> for ForbidInboundMessages {
+impl<AccountId, Fee> MessageDispatch<AccountId, Fee> for ForbidInboundMessages {
 	type DispatchPayload = ();
 
 	fn dispatch_weight(_message: &DispatchMessage<Self::DispatchPayload, Fee>) -> Weight {
 		Weight::MAX
 	}
 
-	fn dispatch(_message: DispatchMessage<Self::DispatchPayload, Fee>) {}
+	fn is_expired(_message: &DispatchMessage<Self::DispatchPayload, Fee>) -> bool {
+		false
+	}
+
+	fn dispatch(
+		_relayer_account: &AccountId,
+		_message: DispatchMessage<Self::DispatchPayload, Fee>,
+	) -> MessageDispatchResult {
+		MessageDispatchResult {
+			dispatch_result: false,
+			dispatch_fee_paid: true,
+		}
+	}
 }
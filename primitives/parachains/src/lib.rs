@@ -0,0 +1,114 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Primitives of parachains module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{RuntimeDebug, StorageHasher, Twox128, Twox64Concat};
+use sp_core::storage::StorageKey;
+use sp_runtime::traits::{BlakeTwo256, Hash as HashT};
+use sp_std::vec::Vec;
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Identifier of the parachain at the bridged relay chain.
+///
+/// This is the same type as `ParaId` of the Polkadot-like relay chains and it has the same encoding.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ParaId(pub u32);
+
+impl From<u32> for ParaId {
+	fn from(id: u32) -> Self {
+		ParaId(id)
+	}
+}
+
+/// Hash of the parachain head.
+///
+/// Polkadot-like relay chains are using Blake2-256 to compute parachain head hashes. It is
+/// also the hash of the parachain header, assuming the parachain is using the same hasher.
+pub type ParaHash = sp_core::H256;
+
+/// Parachain head, as it is stored at the bridged relay chain.
+///
+/// This is the same type as `HeadData` of the Polkadot-like relay chains and it has the same
+/// encoding. For Substrate-based parachains, this is the encoded parachain header.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ParaHead(pub Vec<u8>);
+
+impl ParaHead {
+	/// Returns the hash of this head data.
+	pub fn hash(&self) -> ParaHash {
+		BlakeTwo256::hash(&self.0)
+	}
+}
+
+/// Storage proof of parachain heads, generated at the bridged relay chain.
+pub type ParaHeadsProof = Vec<Vec<u8>>;
+
+/// Best known parachain head, as it is stored in the runtime storage.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct BestParaHead<RelayBlockNumber> {
+	/// Number of the relay block, where this head has been read from.
+	///
+	/// Parachain head is opaque to the relay chain, so we can't simply decode it and compare
+	/// parachain block numbers. Instead, we're relying on the relay block, where the head
+	/// has been read from: if the head has been read from a newer relay block, we consider it
+	/// to be newer.
+	pub at_relay_block_number: RelayBlockNumber,
+	/// Hash of the parachain head.
+	pub head_hash: ParaHash,
+	/// Current position in the ring buffer of imported parachain head hashes.
+	pub next_imported_hash_position: u32,
+}
+
+/// Return a storage key of the parachain head at the bridged relay chain.
+///
+/// This is based on FRAME storage-generation code from Substrate. The equivalent command to invoke
+/// in case full relay chain `Runtime` is known is this:
+/// `let key = polkadot_runtime_parachains::paras::Heads::<Runtime>::storage_map_final_key(&para_id);`
+pub fn parachain_head_storage_key_at_source(paras_pallet_name: &str, para_id: ParaId) -> StorageKey {
+	let module_prefix_hashed = Twox128::hash(paras_pallet_name.as_bytes());
+	let storage_prefix_hashed = Twox128::hash(b"Heads");
+	let key_hashed = para_id.using_encoded(Twox64Concat::hash);
+
+	let mut final_key = Vec::with_capacity(module_prefix_hashed.len() + storage_prefix_hashed.len() + key_hashed.len());
+
+	final_key.extend_from_slice(&module_prefix_hashed[..]);
+	final_key.extend_from_slice(&storage_prefix_hashed[..]);
+	final_key.extend_from_slice(&key_hashed);
+
+	StorageKey(final_key)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parachain_head_storage_key_at_source_is_correct() {
+		// this key is used to read head of parachain 2000 at Rococo
+		assert_eq!(
+			hex::encode(parachain_head_storage_key_at_source("Paras", ParaId(2000)).0),
+			"cd710b30bd2eab0352ddcc26417aa1941b3c252fcb29d88eff4f3de5de4476c363f5a4efb16ffa83d0070000",
+		);
+	}
+}
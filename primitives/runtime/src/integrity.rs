@@ -0,0 +1,199 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Integrity checks of the chain constants.
+//!
+//! Bridge primitives of the chain (`bp-*` crates) declare chain constants (maximal extrinsic size and
+//! weight, session length, SS58 prefix, ...) that are used by pallets of other chains and by relays.
+//! Nothing forces these constants to match the actual runtime configuration, so they may silently
+//! drift after runtime upgrade. Runtimes should call `ensure_chain_constants` from their tests
+//! and/or from `on_runtime_upgrade` to detect such drift.
+
+use frame_support::{
+	traits::Get,
+	weights::{DispatchClass, Weight},
+	RuntimeDebug,
+};
+
+/// Bridge-related constants of the chain.
+#[derive(Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub struct ChainConstants {
+	/// Maximal size (in bytes) of the normal extrinsic.
+	pub max_extrinsic_size: u32,
+	/// Maximal weight of the normal extrinsic.
+	pub max_extrinsic_weight: Weight,
+	/// Session length (in blocks). `None` if the chain has no sessions.
+	pub session_length: Option<u64>,
+	/// SS58 prefix of the chain.
+	pub ss58_prefix: u8,
+}
+
+impl ChainConstants {
+	/// Read constants from the configuration of the runtime `R`.
+	///
+	/// Session length can't be read from the `frame_system` configuration, so it needs to be
+	/// provided by the caller.
+	pub fn from_runtime<R: frame_system::Config>(session_length: Option<u64>) -> Self {
+		ChainConstants {
+			max_extrinsic_size: *R::BlockLength::get().max.get(DispatchClass::Normal),
+			max_extrinsic_weight: R::BlockWeights::get()
+				.get(DispatchClass::Normal)
+				.max_extrinsic
+				.unwrap_or(Weight::MAX),
+			session_length,
+			ss58_prefix: R::SS58Prefix::get(),
+		}
+	}
+}
+
+/// Chain constant that doesn't match the actual runtime configuration.
+#[derive(Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub struct ConstantMismatch {
+	/// Name of the constant.
+	pub constant: &'static str,
+	/// Value, declared by the bridge primitives of the chain.
+	pub declared: Option<u64>,
+	/// Value from the runtime configuration.
+	pub actual: Option<u64>,
+}
+
+/// Ensure that constants, declared by the bridge primitives of the chain, match actual constants of
+/// the chain runtime.
+///
+/// Returns first mismatching constant if there are any.
+pub fn ensure_chain_constants(declared: &ChainConstants, actual: &ChainConstants) -> Result<(), ConstantMismatch> {
+	ensure_constant(
+		"max_extrinsic_size",
+		Some(declared.max_extrinsic_size.into()),
+		Some(actual.max_extrinsic_size.into()),
+	)?;
+	ensure_constant(
+		"max_extrinsic_weight",
+		Some(declared.max_extrinsic_weight),
+		Some(actual.max_extrinsic_weight),
+	)?;
+	ensure_constant("session_length", declared.session_length, actual.session_length)?;
+	ensure_constant(
+		"ss58_prefix",
+		Some(declared.ss58_prefix.into()),
+		Some(actual.ss58_prefix.into()),
+	)
+}
+
+/// Ensure that declared and actual values of the constant are the same.
+fn ensure_constant(constant: &'static str, declared: Option<u64>, actual: Option<u64>) -> Result<(), ConstantMismatch> {
+	if declared != actual {
+		return Err(ConstantMismatch {
+			constant,
+			declared,
+			actual,
+		});
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn constants() -> ChainConstants {
+		ChainConstants {
+			max_extrinsic_size: 1024,
+			max_extrinsic_weight: 1_000_000,
+			session_length: Some(10),
+			ss58_prefix: 42,
+		}
+	}
+
+	#[test]
+	fn matching_constants_are_accepted() {
+		assert_eq!(ensure_chain_constants(&constants(), &constants()), Ok(()));
+		assert_eq!(
+			ensure_chain_constants(
+				&ChainConstants {
+					session_length: None,
+					..constants()
+				},
+				&ChainConstants {
+					session_length: None,
+					..constants()
+				},
+			),
+			Ok(()),
+		);
+	}
+
+	#[test]
+	fn mismatching_constants_are_rejected() {
+		assert_eq!(
+			ensure_chain_constants(
+				&constants(),
+				&ChainConstants {
+					max_extrinsic_size: 2048,
+					..constants()
+				},
+			),
+			Err(ConstantMismatch {
+				constant: "max_extrinsic_size",
+				declared: Some(1024),
+				actual: Some(2048),
+			}),
+		);
+		assert_eq!(
+			ensure_chain_constants(
+				&constants(),
+				&ChainConstants {
+					max_extrinsic_weight: 2_000_000,
+					..constants()
+				},
+			),
+			Err(ConstantMismatch {
+				constant: "max_extrinsic_weight",
+				declared: Some(1_000_000),
+				actual: Some(2_000_000),
+			}),
+		);
+		assert_eq!(
+			ensure_chain_constants(
+				&constants(),
+				&ChainConstants {
+					session_length: None,
+					..constants()
+				},
+			),
+			Err(ConstantMismatch {
+				constant: "session_length",
+				declared: Some(10),
+				actual: None,
+			}),
+		);
+		assert_eq!(
+			ensure_chain_constants(
+				&constants(),
+				&ChainConstants {
+					ss58_prefix: 0,
+					..constants()
+				},
+			),
+			Err(ConstantMismatch {
+				constant: "ss58_prefix",
+				declared: Some(42),
+				actual: Some(0),
+			}),
+		);
+	}
+}
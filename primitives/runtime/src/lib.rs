@@ -18,7 +18,8 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use codec::Encode;
+use codec::{Decode, Encode};
+use frame_support::RuntimeDebug;
 use sp_core::hash::H256;
 use sp_io::hashing::blake2_256;
 use sp_std::convert::TryFrom;
@@ -29,6 +30,9 @@ pub use storage_proof::{Error as StorageProofError, StorageProofChecker};
 #[cfg(feature = "std")]
 pub use storage_proof::craft_valid_storage_proof;
 
+pub mod integrity;
+pub mod messages;
+
 mod chain;
 mod storage_proof;
 
@@ -41,6 +45,9 @@ pub const RIALTO_CHAIN_ID: ChainId = *b"rlto";
 /// Bridge-with-Millau instance id.
 pub const MILLAU_CHAIN_ID: ChainId = *b"mlau";
 
+/// Bridge-with-RialtoParachain instance id.
+pub const RIALTO_PARACHAIN_CHAIN_ID: ChainId = *b"rlpa";
+
 /// Bridge-with-Polkadot instance id.
 pub const POLKADOT_CHAIN_ID: ChainId = *b"pdot";
 
@@ -81,10 +88,75 @@ pub enum SourceAccount<T> {
 	Account(T),
 }
 
+/// Cross-chain account derivation scheme.
+///
+/// Every derived account depends on the scheme that has been used to derive it. Existing schemes
+/// MUST NOT be changed, because that would silently change addresses of all bridged accounts at
+/// the target chain. Any change to the derivation needs to be introduced as a new scheme version.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum AccountDerivation {
+	/// Initial scheme, used by all existing bridges.
+	///
+	/// Account is derived as `blake2_256(prefix, bridge_id[, account_id])`. The version is not
+	/// encoded.
+	V0,
+	/// Scheme that separates accounts of different bridge instances between the same pair of chains.
+	///
+	/// Account is derived as `blake2_256(prefix, 1u8, bridge_id, bridge_instance[, account_id])`.
+	V1 {
+		/// Identifier of the bridge instance.
+		bridge_instance: ChainId,
+	},
+}
+
+impl AccountDerivation {
+	/// Returns version of the derivation scheme.
+	pub fn version(&self) -> u8 {
+		match *self {
+			AccountDerivation::V0 => 0,
+			AccountDerivation::V1 { .. } => 1,
+		}
+	}
+
+	/// Derive an account ID from a foreign account ID using this scheme.
+	///
+	/// This function returns an encoded Blake2 hash. It is the responsibility of the caller to ensure
+	/// this can be succesfully decoded into an AccountId.
+	pub fn derive<AccountId>(&self, bridge_id: ChainId, id: SourceAccount<AccountId>) -> H256
+	where
+		AccountId: Encode,
+	{
+		match (*self, id) {
+			(AccountDerivation::V0, SourceAccount::Root) => {
+				(ROOT_ACCOUNT_DERIVATION_PREFIX, bridge_id).using_encoded(blake2_256)
+			}
+			(AccountDerivation::V0, SourceAccount::Account(id)) => {
+				(ACCOUNT_DERIVATION_PREFIX, bridge_id, id).using_encoded(blake2_256)
+			}
+			(AccountDerivation::V1 { bridge_instance }, SourceAccount::Root) => (
+				ROOT_ACCOUNT_DERIVATION_PREFIX,
+				self.version(),
+				bridge_id,
+				bridge_instance,
+			)
+				.using_encoded(blake2_256),
+			(AccountDerivation::V1 { bridge_instance }, SourceAccount::Account(id)) => (
+				ACCOUNT_DERIVATION_PREFIX,
+				self.version(),
+				bridge_id,
+				bridge_instance,
+				id,
+			)
+				.using_encoded(blake2_256),
+		}
+		.into()
+	}
+}
+
 /// Derive an account ID from a foreign account ID.
 ///
-/// This function returns an encoded Blake2 hash. It is the responsibility of the caller to ensure
-/// this can be succesfully decoded into an AccountId.
+/// The account is derived using the [`AccountDerivation::V0`] scheme, which is used by all
+/// existing bridges.
 ///
 /// The `bridge_id` is used to provide extra entropy when producing account IDs. This helps prevent
 /// AccountId collisions between different bridges on a single target chain.
@@ -96,11 +168,7 @@ pub fn derive_account_id<AccountId>(bridge_id: ChainId, id: SourceAccount<Accoun
 where
 	AccountId: Encode,
 {
-	match id {
-		SourceAccount::Root => (ROOT_ACCOUNT_DERIVATION_PREFIX, bridge_id).using_encoded(blake2_256),
-		SourceAccount::Account(id) => (ACCOUNT_DERIVATION_PREFIX, bridge_id, id).using_encoded(blake2_256),
-	}
-	.into()
+	AccountDerivation::V0.derive(bridge_id, id)
 }
 
 /// Derive the account ID of the shared relayer fund account.
@@ -113,6 +181,12 @@ pub fn derive_relayer_fund_account_id(bridge_id: ChainId) -> H256 {
 	("relayer-fund-account", bridge_id).using_encoded(blake2_256).into()
 }
 
+/// Anything that declares its dispatch weight.
+pub trait DeclaredWeight {
+	/// Return dispatch weight, declared by this object.
+	fn declared_weight(&self) -> frame_support::weights::Weight;
+}
+
 /// Anything that has size.
 pub trait Size {
 	/// Return approximate size of this object (in bytes).
@@ -136,3 +210,114 @@ impl Size for PreComputedSize {
 		u32::try_from(self.0).unwrap_or(u32::MAX)
 	}
 }
+
+/// Version of the bridge pallet storage layout.
+///
+/// Bridge pallets are keeping the version of their storage layout in the runtime storage. Pallets
+/// that have been deployed before the version has been introduced, have the default (zero) version.
+/// Whenever the storage layout is changed, the pallet bumps its current version and adds migration
+/// from the previous version, which is applied in the `on_runtime_upgrade` hook.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub struct StorageVersion(pub u16);
+
+/// Era of specific transaction.
+#[derive(RuntimeDebug, Clone, Copy)]
+pub enum TransactionEra<BlockNumber, BlockHash> {
+	/// Transaction is immortal.
+	Immortal,
+	/// Transaction is valid for given number of blocks, starting from given block (number and hash).
+	Mortal(BlockNumber, BlockHash, u32),
+}
+
+impl<BlockNumber: Copy + Into<u64>, BlockHash: Copy> TransactionEra<BlockNumber, BlockHash> {
+	/// Prepare transaction era, based on mortality period and current best block number and hash.
+	pub fn new(best_block_number: BlockNumber, best_block_hash: BlockHash, mortality_period: Option<u32>) -> Self {
+		mortality_period
+			.map(|mortality_period| TransactionEra::Mortal(best_block_number, best_block_hash, mortality_period))
+			.unwrap_or(TransactionEra::Immortal)
+	}
+
+	/// Create new immortal transaction era.
+	pub fn immortal() -> Self {
+		TransactionEra::Immortal
+	}
+
+	/// Create new mortal transaction era, that starts at given block.
+	pub fn mortal(block_number: BlockNumber, block_hash: BlockHash, mortality_period: u32) -> Self {
+		TransactionEra::Mortal(block_number, block_hash, mortality_period)
+	}
+
+	/// Returns era that is used by FRAME-based runtimes.
+	pub fn frame_era(&self) -> sp_runtime::generic::Era {
+		match *self {
+			TransactionEra::Immortal => sp_runtime::generic::Era::immortal(),
+			TransactionEra::Mortal(block_number, _, mortality_period) => {
+				sp_runtime::generic::Era::mortal(mortality_period as _, block_number.into())
+			}
+		}
+	}
+
+	/// Returns header hash that needs to be included in the signature payload.
+	pub fn signed_payload(&self, genesis_hash: BlockHash) -> BlockHash {
+		match *self {
+			TransactionEra::Immortal => genesis_hash,
+			TransactionEra::Mortal(_, block_hash, _) => block_hash,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hex_literal::hex;
+
+	const ACCOUNT: [u8; 32] = [1u8; 32];
+	const BRIDGE_INSTANCE: ChainId = *b"inst";
+
+	#[test]
+	fn v0_account_derivation_matches_test_vectors() {
+		assert_eq!(
+			AccountDerivation::V0.derive::<[u8; 32]>(MILLAU_CHAIN_ID, SourceAccount::Root),
+			H256::from(hex!("8e13b96a9c9e3b1832f07935be76c2b331251e26445f520ad1c56b24477ed8dd")),
+		);
+		assert_eq!(
+			AccountDerivation::V0.derive(MILLAU_CHAIN_ID, SourceAccount::Account(ACCOUNT)),
+			H256::from(hex!("2c97986948aba687cb2d12e0e645745b03980e72d85d4e639157f5b9db91d7df")),
+		);
+	}
+
+	#[test]
+	fn v1_account_derivation_matches_test_vectors() {
+		let derivation = AccountDerivation::V1 {
+			bridge_instance: BRIDGE_INSTANCE,
+		};
+		assert_eq!(
+			derivation.derive::<[u8; 32]>(MILLAU_CHAIN_ID, SourceAccount::Root),
+			H256::from(hex!("a3cdb38bd2d6ae72313506574a447c1a343ba4210e989f800fb887de5c8cf124")),
+		);
+		assert_eq!(
+			derivation.derive(MILLAU_CHAIN_ID, SourceAccount::Account(ACCOUNT)),
+			H256::from(hex!("b1a1f995dd23ca5e275d51b7dc89cd866fcfdb265a5a6c4863894c845dd00600")),
+		);
+	}
+
+	#[test]
+	fn derive_account_id_uses_v0_scheme() {
+		assert_eq!(
+			derive_account_id(MILLAU_CHAIN_ID, SourceAccount::Account(ACCOUNT)),
+			AccountDerivation::V0.derive(MILLAU_CHAIN_ID, SourceAccount::Account(ACCOUNT)),
+		);
+	}
+
+	#[test]
+	fn bridge_instances_have_different_accounts() {
+		let derive = |bridge_instance| {
+			AccountDerivation::V1 { bridge_instance }.derive(MILLAU_CHAIN_ID, SourceAccount::Account(ACCOUNT))
+		};
+		assert_ne!(derive(*b"ins1"), derive(*b"ins2"));
+		assert_ne!(
+			derive(NO_INSTANCE_ID),
+			AccountDerivation::V0.derive(MILLAU_CHAIN_ID, SourceAccount::Account(ACCOUNT)),
+		);
+	}
+}
@@ -0,0 +1,31 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Primitives that may be used by different message delivery and dispatch mechanisms.
+
+use codec::{Decode, Encode};
+use frame_support::RuntimeDebug;
+
+/// Message dispatch result.
+#[derive(Encode, Decode, RuntimeDebug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageDispatchResult {
+	/// True if the message call has been dispatched and the dispatch has succeeded. False if the
+	/// message has been rejected before dispatch, or if the dispatched call has failed.
+	pub dispatch_result: bool,
+	/// False if the message had to pay dispatch fee at the target chain, but the payment has
+	/// failed. Otherwise true.
+	pub dispatch_fee_paid: bool,
+}
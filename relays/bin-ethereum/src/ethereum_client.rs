@@ -19,10 +19,10 @@ use crate::substrate_sync_loop::QueuedRialtoHeader;
 
 use async_trait::async_trait;
 use bp_eth_poa::signatures::secret_to_address;
-use codec::{Decode, Encode};
-use ethabi::FunctionOutputDecoder;
+use codec::Encode;
 use headers_relay::sync_types::SubmittedHeaders;
 use relay_ethereum_client::{
+	contract::SubstrateBridgeContract,
 	sign_and_submit_transaction,
 	types::{Address, CallRequest, HeaderId as EthereumHeaderId, Receipt, H256, U256},
 	Client as EthereumClient, Error as EthereumNodeError, SigningParams as EthereumSigningParams,
@@ -32,8 +32,14 @@ use relay_utils::{HeaderId, MaybeConnectionError};
 use sp_runtime::EncodedJustification;
 use std::collections::HashSet;
 
-// to encode/decode contract calls
-ethabi_contract::use_contract!(bridge_contract, "res/substrate-bridge-abi.json");
+/// ABI of the Substrate bridge contract.
+const BRIDGE_CONTRACT_ABI: &[u8] = include_bytes!("../res/substrate-bridge-abi.json");
+
+/// Returns Substrate bridge contract that is used to encode/decode contract calls.
+pub fn bridge_contract() -> SubstrateBridgeContract {
+	SubstrateBridgeContract::from_json_abi(BRIDGE_CONTRACT_ABI)
+		.expect("bridge contract ABI is bundled with the relay and is valid; qed")
+}
 
 type RpcResult<T> = std::result::Result<T, RpcError>;
 
@@ -92,16 +98,15 @@ pub trait EthereumHighLevelRpc {
 #[async_trait]
 impl EthereumHighLevelRpc for EthereumClient {
 	async fn best_substrate_block(&self, contract_address: Address) -> RpcResult<RialtoHeaderId> {
-		let (encoded_call, call_decoder) = bridge_contract::functions::best_known_header::call();
+		let bridge_contract = bridge_contract();
 		let call_request = CallRequest {
 			to: Some(contract_address),
-			data: Some(encoded_call.into()),
+			data: Some(bridge_contract.encode_best_known_header()?.into()),
 			..Default::default()
 		};
 
 		let call_result = self.eth_call(call_request).await?;
-		let (number, raw_hash) = call_decoder.decode(&call_result.0)?;
-		let hash = rialto_runtime::Hash::decode(&mut &raw_hash[..])?;
+		let (number, hash) = bridge_contract.decode_best_known_header(&call_result.0)?;
 
 		if number != number.low_u32().into() {
 			return Err(RpcError::Ethereum(EthereumNodeError::InvalidSubstrateBlockNumber));
@@ -115,15 +120,15 @@ impl EthereumHighLevelRpc for EthereumClient {
 		contract_address: Address,
 		id: RialtoHeaderId,
 	) -> RpcResult<(RialtoHeaderId, bool)> {
-		let (encoded_call, call_decoder) = bridge_contract::functions::is_known_header::call(id.1);
+		let bridge_contract = bridge_contract();
 		let call_request = CallRequest {
 			to: Some(contract_address),
-			data: Some(encoded_call.into()),
+			data: Some(bridge_contract.encode_is_known_header(id.1)?.into()),
 			..Default::default()
 		};
 
 		let call_result = self.eth_call(call_request).await?;
-		let is_known_block = call_decoder.decode(&call_result.0)?;
+		let is_known_block = bridge_contract.decode_is_known_header(&call_result.0)?;
 
 		Ok((id, is_known_block))
 	}
@@ -163,20 +168,19 @@ impl EthereumHighLevelRpc for EthereumClient {
 	}
 
 	async fn incomplete_substrate_headers(&self, contract_address: Address) -> RpcResult<HashSet<RialtoHeaderId>> {
-		let (encoded_call, call_decoder) = bridge_contract::functions::incomplete_headers::call();
+		let bridge_contract = bridge_contract();
 		let call_request = CallRequest {
 			to: Some(contract_address),
-			data: Some(encoded_call.into()),
+			data: Some(bridge_contract.encode_incomplete_headers()?.into()),
 			..Default::default()
 		};
 
 		let call_result = self.eth_call(call_request).await?;
 
 		// Q: Is is correct to call these "incomplete_ids"?
-		let (incomplete_headers_numbers, incomplete_headers_hashes) = call_decoder.decode(&call_result.0)?;
-		let incomplete_ids = incomplete_headers_numbers
+		let incomplete_ids = bridge_contract
+			.decode_incomplete_headers(&call_result.0)?
 			.into_iter()
-			.zip(incomplete_headers_hashes)
 			.filter_map(|(number, hash)| {
 				if number != number.low_u32().into() {
 					return None;
@@ -202,7 +206,7 @@ impl EthereumHighLevelRpc for EthereumClient {
 				Some(contract_address),
 				None,
 				false,
-				bridge_contract::functions::import_finality_proof::encode_input(id.0, id.1, justification),
+				bridge_contract().encode_import_finality_proof(id.0.into(), id.1, justification)?,
 			)
 			.await?;
 
@@ -358,16 +362,15 @@ struct EthereumHeadersSubmitter {
 #[async_trait]
 impl HeadersSubmitter for EthereumHeadersSubmitter {
 	async fn is_headers_incomplete(&self, headers: &HeadersBatch) -> RpcResult<usize> {
-		let [h1, h2, h3, h4] = headers.encode();
-		let (encoded_call, call_decoder) = bridge_contract::functions::is_incomplete_headers::call(h1, h2, h3, h4);
+		let bridge_contract = bridge_contract();
 		let call_request = CallRequest {
 			to: Some(self.contract_address),
-			data: Some(encoded_call.into()),
+			data: Some(bridge_contract.encode_is_incomplete_headers(headers.encode())?.into()),
 			..Default::default()
 		};
 
 		let call_result = self.client.eth_call(call_request).await?;
-		let incomplete_index: U256 = call_decoder.decode(&call_result.0)?;
+		let incomplete_index = bridge_contract.decode_is_incomplete_headers(&call_result.0)?;
 		if incomplete_index > HEADERS_BATCH.into() {
 			return Err(RpcError::Ethereum(EthereumNodeError::InvalidIncompleteIndex));
 		}
@@ -376,7 +379,7 @@ impl HeadersSubmitter for EthereumHeadersSubmitter {
 	}
 
 	async fn submit_headers(&mut self, headers: HeadersBatch) -> RpcResult<()> {
-		let [h1, h2, h3, h4] = headers.encode();
+		let encoded_call = bridge_contract().encode_import_headers(headers.encode())?;
 		let result = self
 			.client
 			.submit_ethereum_transaction(
@@ -384,7 +387,7 @@ impl HeadersSubmitter for EthereumHeadersSubmitter {
 				Some(self.contract_address),
 				Some(self.nonce),
 				false,
-				bridge_contract::functions::import_headers::encode_input(h1, h2, h3, h4),
+				encoded_call,
 			)
 			.await;
 
@@ -147,7 +147,9 @@ async fn deploy_bridge_contract(
 			None,
 			None,
 			false,
-			bridge_contract::constructor(contract_code, initial_header, initial_set_id, initial_authorities),
+			bridge_contract()
+				.encode_constructor(contract_code, initial_header, initial_set_id, initial_authorities)
+				.map_err(|error| format!("Error encoding contract constructor: {:?}", error))?,
 		)
 		.await
 		.map_err(|error| format!("Error deploying contract: {:?}", error))
@@ -27,7 +27,7 @@ use crate::ethereum_sync_loop::QueuedEthereumHeader;
 use crate::substrate_types::{into_substrate_ethereum_header, into_substrate_ethereum_receipts};
 
 use rialto_runtime::exchange::EthereumTransactionInclusionProof as Proof;
-use rialto_runtime::Call;
+use rialto_runtime::{AccountId, Call};
 
 /// Interface for `Calls` which are needed to correctly sync the bridge.
 ///
@@ -37,7 +37,9 @@ pub trait BridgeInstance: Send + Sync + std::fmt::Debug {
 	/// Used to build a `Call` for importing signed headers to a Substrate runtime.
 	fn build_signed_header_call(&self, headers: Vec<QueuedEthereumHeader>) -> Call;
 	/// Used to build a `Call` for importing an unsigned header to a Substrate runtime.
-	fn build_unsigned_header_call(&self, header: QueuedEthereumHeader) -> Call;
+	///
+	/// The `rewards_account` is rewarded when the header is finalized.
+	fn build_unsigned_header_call(&self, header: QueuedEthereumHeader, rewards_account: AccountId) -> Call;
 	/// Used to build a `Call` for importing peer transactions to a Substrate runtime.
 	fn build_currency_exchange_call(&self, proof: Proof) -> Call;
 }
@@ -63,10 +65,11 @@ impl BridgeInstance for RialtoPoA {
 		rialto_runtime::Call::BridgeRialtoPoA(pallet_call)
 	}
 
-	fn build_unsigned_header_call(&self, header: QueuedEthereumHeader) -> Call {
+	fn build_unsigned_header_call(&self, header: QueuedEthereumHeader, rewards_account: AccountId) -> Call {
 		let pallet_call = rialto_runtime::BridgeEthPoACall::import_unsigned_header(
 			into_substrate_ethereum_header(&header.header()),
 			into_substrate_ethereum_receipts(header.extra()),
+			Some(rewards_account),
 		);
 
 		rialto_runtime::Call::BridgeRialtoPoA(pallet_call)
@@ -99,10 +102,11 @@ impl BridgeInstance for Kovan {
 		rialto_runtime::Call::BridgeKovan(pallet_call)
 	}
 
-	fn build_unsigned_header_call(&self, header: QueuedEthereumHeader) -> Call {
+	fn build_unsigned_header_call(&self, header: QueuedEthereumHeader, rewards_account: AccountId) -> Call {
 		let pallet_call = rialto_runtime::BridgeEthPoACall::import_unsigned_header(
 			into_substrate_ethereum_header(header.header()),
 			into_substrate_ethereum_receipts(header.extra()),
+			Some(rewards_account),
 		);
 
 		rialto_runtime::Call::BridgeKovan(pallet_call)
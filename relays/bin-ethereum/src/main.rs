@@ -37,6 +37,7 @@ use instances::{BridgeInstance, Kovan, RialtoPoA};
 use relay_utils::{
 	initialize::initialize_relay,
 	metrics::{MetricsAddress, MetricsParams},
+	transport::TlsParams,
 };
 use secp256k1::SecretKey;
 use sp_core::crypto::Pair;
@@ -138,6 +139,15 @@ fn ethereum_connection_params(matches: &clap::ArgMatches) -> Result<EthereumConn
 			.parse()
 			.map_err(|e| format!("Failed to parse eth-port: {}", e))?;
 	}
+	if let Some(eth_proxy) = matches.value_of("eth-proxy") {
+		params.transport.proxy = Some(
+			eth_proxy
+				.parse()
+				.map_err(|e| format!("Failed to parse eth-proxy: {}", e))?,
+		);
+	}
+	params.secure = matches.is_present("eth-secure");
+	params.transport.tls = tls_params(matches, "eth");
 	Ok(params)
 }
 
@@ -166,9 +176,27 @@ fn substrate_connection_params(matches: &clap::ArgMatches) -> Result<SubstrateCo
 			.parse()
 			.map_err(|e| format!("Failed to parse sub-port: {}", e))?;
 	}
+	if let Some(sub_proxy) = matches.value_of("sub-proxy") {
+		params.transport.proxy = Some(
+			sub_proxy
+				.parse()
+				.map_err(|e| format!("Failed to parse sub-proxy: {}", e))?,
+		);
+	}
+	params.secure = matches.is_present("sub-secure");
+	params.transport.tls = tls_params(matches, "sub");
 	Ok(params)
 }
 
+fn tls_params(matches: &clap::ArgMatches, prefix: &str) -> TlsParams {
+	let path = |name: &str| matches.value_of(format!("{}-tls-{}", prefix, name)).map(Into::into);
+	TlsParams {
+		ca_bundle: path("ca-bundle"),
+		client_certificate: path("client-certificate"),
+		client_key: path("client-key"),
+	}
+}
+
 fn rialto_signing_params(matches: &clap::ArgMatches) -> Result<RialtoSigningParams, String> {
 	let mut params = sp_keyring::AccountKeyring::Alice.pair();
 
@@ -24,7 +24,7 @@ use codec::{Decode, Encode};
 use headers_relay::sync_types::SubmittedHeaders;
 use relay_ethereum_client::types::HeaderId as EthereumHeaderId;
 use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
-use relay_substrate_client::{Client as SubstrateClient, TransactionSignScheme};
+use relay_substrate_client::{Client as SubstrateClient, TransactionEra, TransactionSignScheme};
 use relay_utils::HeaderId;
 use sp_core::{crypto::Pair, Bytes};
 use std::{collections::VecDeque, sync::Arc};
@@ -125,8 +125,11 @@ pub trait SubmitEthereumHeaders {
 	) -> SubmittedHeaders<EthereumHeaderId, RpcError>;
 
 	/// Submits unsigned Ethereum header to Substrate runtime.
+	///
+	/// The signer account is only used to receive rewards for submitted headers.
 	async fn submit_unsigned_ethereum_headers(
 		&self,
+		params: RialtoSigningParams,
 		instance: Arc<dyn BridgeInstance>,
 		headers: Vec<QueuedEthereumHeader>,
 	) -> SubmittedHeaders<EthereumHeaderId, RpcError>;
@@ -144,7 +147,7 @@ impl SubmitEthereumHeaders for SubstrateClient<Rialto> {
 		if sign_transactions {
 			self.submit_signed_ethereum_headers(params, instance, headers).await
 		} else {
-			self.submit_unsigned_ethereum_headers(instance, headers).await
+			self.submit_unsigned_ethereum_headers(params, instance, headers).await
 		}
 	}
 
@@ -156,12 +159,14 @@ impl SubmitEthereumHeaders for SubstrateClient<Rialto> {
 	) -> SubmittedHeaders<EthereumHeaderId, RpcError> {
 		let ids = headers.iter().map(|header| header.id()).collect();
 		let submission_result = async {
-			self.submit_signed_extrinsic((*params.public().as_array_ref()).into(), |transaction_nonce| {
+			self.submit_signed_extrinsic((*params.public().as_array_ref()).into(), |_, transaction_nonce| {
 				Bytes(
 					Rialto::sign_transaction(
 						*self.genesis_hash(),
 						&params,
+						TransactionEra::immortal(),
 						transaction_nonce,
+						0,
 						instance.build_signed_header_call(headers),
 					)
 					.encode(),
@@ -190,16 +195,18 @@ impl SubmitEthereumHeaders for SubstrateClient<Rialto> {
 
 	async fn submit_unsigned_ethereum_headers(
 		&self,
+		params: RialtoSigningParams,
 		instance: Arc<dyn BridgeInstance>,
 		headers: Vec<QueuedEthereumHeader>,
 	) -> SubmittedHeaders<EthereumHeaderId, RpcError> {
+		let rewards_account: rialto_runtime::AccountId = (*params.public().as_array_ref()).into();
 		let mut ids = headers.iter().map(|header| header.id()).collect::<VecDeque<_>>();
 		let mut submitted_headers = SubmittedHeaders::default();
 
 		for header in headers {
 			let id = ids.pop_front().expect("both collections have same size; qed");
 
-			let call = instance.build_unsigned_header_call(header);
+			let call = instance.build_unsigned_header_call(header, rewards_account.clone());
 			let transaction = create_unsigned_submit_transaction(call);
 
 			match self.submit_unsigned_extrinsic(Bytes(transaction.encode())).await {
@@ -257,12 +264,14 @@ impl SubmitEthereumExchangeTransactionProof for SubstrateClient<Rialto> {
 		instance: Arc<dyn BridgeInstance>,
 		proof: rialto_runtime::exchange::EthereumTransactionInclusionProof,
 	) -> RpcResult<()> {
-		self.submit_signed_extrinsic((*params.public().as_array_ref()).into(), |transaction_nonce| {
+		self.submit_signed_extrinsic((*params.public().as_array_ref()).into(), |_, transaction_nonce| {
 			Bytes(
 				Rialto::sign_transaction(
 					*self.genesis_hash(),
 					&params,
+					TransactionEra::immortal(),
 					transaction_nonce,
+					0,
 					instance.build_currency_exchange_call(proof),
 				)
 				.encode(),
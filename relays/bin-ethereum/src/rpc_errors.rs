@@ -62,12 +62,6 @@ impl From<SubstrateNodeError> for RpcError {
 	}
 }
 
-impl From<ethabi::Error> for RpcError {
-	fn from(err: ethabi::Error) -> Self {
-		Self::Ethereum(EthereumNodeError::ResponseParseFailed(format!("{}", err)))
-	}
-}
-
 impl MaybeConnectionError for RpcError {
 	fn is_connection_error(&self) -> bool {
 		match self {
@@ -0,0 +1,48 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::process::Command;
+use substrate_build_script_utils::rerun_if_git_head_changed;
+
+/// Name of environment variable that may be used to override git commit of the build (e.g. when
+/// relay is built outside of git repository).
+const GIT_COMMIT_ENV: &str = "SUBSTRATE_RELAY_GIT_COMMIT";
+
+fn main() {
+	let commit = std::env::var(GIT_COMMIT_ENV)
+		.ok()
+		.or_else(git_commit)
+		.unwrap_or_else(|| "unknown".into());
+	println!("cargo:rustc-env={}={}", GIT_COMMIT_ENV, commit);
+	println!("cargo:rerun-if-env-changed={}", GIT_COMMIT_ENV);
+
+	rerun_if_git_head_changed();
+}
+
+/// Returns short hash of the current git commit.
+fn git_commit() -> Option<String> {
+	let output = Command::new("git")
+		.args(&["rev-parse", "--short=11", "HEAD"])
+		.output()
+		.ok()?;
+	if !output.status.success() {
+		return None;
+	}
+
+	String::from_utf8(output.stdout)
+		.ok()
+		.map(|commit| commit.trim().to_owned())
+}
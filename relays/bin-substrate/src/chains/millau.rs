@@ -19,12 +19,19 @@
 use crate::cli::{
 	bridge,
 	encode_call::{self, Call, CliEncodeCall},
-	encode_message, send_message, CliChain,
+	encode_message, send_message, CliChain, ExplicitOrMaximal,
 };
+use crate::conversion_rate_update::ChainWithConversionRate;
+use crate::relayer_rewards::ChainWithRelayersRewards;
 use bp_message_dispatch::{CallOrigin, MessagePayload};
 use codec::Decode;
-use frame_support::weights::{GetDispatchInfo, Weight};
+use frame_support::{
+	storage::StorageValue,
+	weights::{GetDispatchInfo, Weight},
+};
 use relay_millau_client::Millau;
+use sp_core::storage::StorageKey;
+use sp_runtime::FixedU128;
 use sp_version::RuntimeVersion;
 
 impl CliEncodeCall for Millau {
@@ -39,7 +46,7 @@ impl CliEncodeCall for Millau {
 				remark_payload.as_ref().map(|x| x.0.clone()).unwrap_or_default(),
 			)),
 			Call::Transfer { recipient, amount } => millau_runtime::Call::Balances(
-				millau_runtime::BalancesCall::transfer(recipient.raw_id(), amount.cast()),
+				millau_runtime::BalancesCall::transfer(recipient.raw_id(), amount.cast()?),
 			),
 			Call::BridgeSendMessage {
 				lane,
@@ -50,9 +57,9 @@ impl CliEncodeCall for Millau {
 				bridge::MILLAU_TO_RIALTO_INDEX => {
 					let payload = Decode::decode(&mut &*payload.0)?;
 					millau_runtime::Call::BridgeRialtoMessages(millau_runtime::MessagesCall::send_message(
-						lane.0,
+						*lane,
 						payload,
-						fee.cast(),
+						fee.cast()?,
 					))
 				}
 				_ => anyhow::bail!(
@@ -64,6 +71,32 @@ impl CliEncodeCall for Millau {
 	}
 }
 
+impl ChainWithRelayersRewards for Millau {
+	fn relayer_rewards_key(relayer: &Self::AccountId) -> StorageKey {
+		StorageKey(pallet_bridge_relayers::RelayerRewards::<millau_runtime::Runtime>::hashed_key_for(relayer))
+	}
+
+	fn claim_rewards_call() -> Self::Call {
+		millau_runtime::Call::BridgeRialtoRelayers(millau_runtime::RelayersCall::claim_rewards())
+	}
+}
+
+impl ChainWithConversionRate for Millau {
+	const INITIAL_CONVERSION_RATE: FixedU128 =
+		millau_runtime::rialto_messages::INITIAL_RIALTO_TO_MILLAU_CONVERSION_RATE;
+
+	fn conversion_rate_key() -> StorageKey {
+		StorageKey(pallet_bridge_conversion_rate::ConversionRate::<millau_runtime::Runtime>::hashed_key().to_vec())
+	}
+
+	fn update_conversion_rate_call(conversion_rate: FixedU128) -> Self::Call {
+		millau_runtime::SudoCall::sudo(Box::new(
+			millau_runtime::ConversionRateCall::update_conversion_rate(conversion_rate).into(),
+		))
+		.into()
+	}
+}
+
 impl CliChain for Millau {
 	const RUNTIME_VERSION: RuntimeVersion = millau_runtime::VERSION;
 
@@ -83,7 +116,13 @@ impl CliChain for Millau {
 		match message {
 			encode_message::MessagePayload::Raw { data } => MessagePayload::decode(&mut &*data.0)
 				.map_err(|e| format!("Failed to decode Millau's MessagePayload: {:?}", e)),
-			encode_message::MessagePayload::Call { mut call, mut sender } => {
+			encode_message::MessagePayload::Call {
+				mut call,
+				mut sender,
+				dispatch_weight,
+				delivery_deadline,
+				dispatch_fee_payment,
+			} => {
 				type Source = Millau;
 				type Target = relay_rialto_client::Rialto;
 
@@ -92,9 +131,20 @@ impl CliChain for Millau {
 				let origin = CallOrigin::SourceAccount(sender.raw_id());
 				encode_call::preprocess_call::<Source, Target>(&mut call, bridge::MILLAU_TO_RIALTO_INDEX);
 				let call = Target::encode_call(&call).map_err(|e| e.to_string())?;
-				let weight = call.get_dispatch_info().weight;
+				let weight = send_message::prepare_call_dispatch_weight(
+					&dispatch_weight,
+					ExplicitOrMaximal::Explicit(call.get_dispatch_info().weight),
+					send_message::compute_maximal_message_dispatch_weight(Target::max_extrinsic_weight()),
+				);
 
-				Ok(send_message::message_payload(spec_version, weight, origin, &call))
+				Ok(send_message::message_payload(
+					spec_version,
+					weight,
+					delivery_deadline,
+					dispatch_fee_payment.into(),
+					origin,
+					&call,
+				))
 			}
 		}
 	}
@@ -21,8 +21,8 @@ use crate::finality_pipeline::{SubstrateFinalitySyncPipeline, SubstrateFinalityT
 use bp_header_chain::justification::GrandpaJustification;
 use codec::Encode;
 use relay_millau_client::{Millau, SyncHeader as MillauSyncHeader};
-use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
-use relay_substrate_client::{Chain, TransactionSignScheme};
+use relay_rialto_client::{HeaderId as RialtoHeaderId, Rialto, SigningParams as RialtoSigningParams};
+use relay_substrate_client::{Chain, ChainWithProxy, TransactionEra, TransactionSignScheme};
 use sp_core::{Bytes, Pair};
 
 /// Millau-to-Rialto finality sync pipeline.
@@ -30,6 +30,7 @@ pub(crate) type MillauFinalityToRialto = SubstrateFinalityToSubstrate<Millau, Ri
 
 impl SubstrateFinalitySyncPipeline for MillauFinalityToRialto {
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_millau::BEST_FINALIZED_MILLAU_HEADER_METHOD;
+	const GRANDPA_PALLET_NAME_AT_TARGET: &'static str = bp_millau::WITH_MILLAU_GRANDPA_PALLET_NAME;
 
 	type TargetChain = Rialto;
 
@@ -37,16 +38,30 @@ impl SubstrateFinalitySyncPipeline for MillauFinalityToRialto {
 		(*self.target_sign.public().as_array_ref()).into()
 	}
 
+	fn max_transaction_tip(&self) -> bp_rialto::Balance {
+		self.target_max_tip
+	}
+
 	fn make_submit_finality_proof_transaction(
 		&self,
+		best_block_id: RialtoHeaderId,
 		transaction_nonce: <Rialto as Chain>::Index,
+		tip: bp_rialto::Balance,
 		header: MillauSyncHeader,
 		proof: GrandpaJustification<bp_millau::Header>,
 	) -> Bytes {
 		let call = rialto_runtime::BridgeGrandpaMillauCall::submit_finality_proof(header.into_inner(), proof).into();
+		let call = Rialto::maybe_proxy_call(self.target_proxied_account.clone(), call);
 
 		let genesis_hash = *self.target_client.genesis_hash();
-		let transaction = Rialto::sign_transaction(genesis_hash, &self.target_sign, transaction_nonce, call);
+		let transaction = Rialto::sign_transaction(
+			genesis_hash,
+			&self.target_sign,
+			TransactionEra::new(best_block_id.0, best_block_id.1, self.transactions_mortality),
+			transaction_nonce,
+			tip,
+			call,
+		);
 
 		Bytes(transaction.encode())
 	}
@@ -0,0 +1,72 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Millau-to-RialtoParachain headers sync entrypoint.
+
+use crate::finality_pipeline::{SubstrateFinalitySyncPipeline, SubstrateFinalityToSubstrate};
+
+use bp_header_chain::justification::GrandpaJustification;
+use codec::Encode;
+use relay_millau_client::{Millau, SyncHeader as MillauSyncHeader};
+use relay_rialto_parachain_client::{
+	HeaderId as RialtoParachainHeaderId, RialtoParachain, SigningParams as RialtoParachainSigningParams,
+};
+use relay_substrate_client::{Chain, TransactionEra, TransactionSignScheme};
+use sp_core::{Bytes, Pair};
+
+/// Millau-to-RialtoParachain finality sync pipeline.
+pub(crate) type MillauFinalityToRialtoParachain =
+	SubstrateFinalityToSubstrate<Millau, RialtoParachain, RialtoParachainSigningParams>;
+
+impl SubstrateFinalitySyncPipeline for MillauFinalityToRialtoParachain {
+	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_millau::BEST_FINALIZED_MILLAU_HEADER_METHOD;
+	const GRANDPA_PALLET_NAME_AT_TARGET: &'static str = bp_millau::WITH_MILLAU_GRANDPA_PALLET_NAME;
+
+	type TargetChain = RialtoParachain;
+
+	fn transactions_author(&self) -> bp_rialto_parachain::AccountId {
+		(*self.target_sign.public().as_array_ref()).into()
+	}
+
+	fn max_transaction_tip(&self) -> bp_rialto_parachain::Balance {
+		self.target_max_tip
+	}
+
+	fn make_submit_finality_proof_transaction(
+		&self,
+		best_block_id: RialtoParachainHeaderId,
+		transaction_nonce: <RialtoParachain as Chain>::Index,
+		tip: bp_rialto_parachain::Balance,
+		header: MillauSyncHeader,
+		proof: GrandpaJustification<bp_millau::Header>,
+	) -> Bytes {
+		// there's no proxy pallet in the RialtoParachain runtime, so transactions are never proxied
+		let call =
+			rialto_parachain_runtime::BridgeGrandpaMillauCall::submit_finality_proof(header.into_inner(), proof).into();
+
+		let genesis_hash = *self.target_client.genesis_hash();
+		let transaction = RialtoParachain::sign_transaction(
+			genesis_hash,
+			&self.target_sign,
+			TransactionEra::new(best_block_id.0, best_block_id.1, self.transactions_mortality),
+			transaction_nonce,
+			tip,
+			call,
+		);
+
+		Bytes(transaction.encode())
+	}
+}
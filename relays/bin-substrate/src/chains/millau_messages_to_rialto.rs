@@ -16,34 +16,43 @@
 
 //! Millau-to-Rialto messages sync entrypoint.
 
+use crate::conversion_rate_update::ChainWithConversionRate;
 use crate::messages_lane::{
-	select_delivery_transaction_limits, MessagesRelayParams, SubstrateMessageLane, SubstrateMessageLaneToSubstrate,
+	abort_on_delivery_limits_change, read_delivery_limits, select_delivery_transaction_limits, MessagesRelayParams,
+	SubstrateDeliveryCostEstimator, SubstrateMessageLane, SubstrateMessageLaneToSubstrate,
 };
+use crate::messages_metrics::MessagesTransactionsMetrics;
 use crate::messages_source::SubstrateMessagesSource;
-use crate::messages_target::SubstrateMessagesTarget;
+use crate::messages_target::{ReverseLaneConfirmations, SubstrateMessagesReceivingProof, SubstrateMessagesTarget};
 
 use bp_messages::MessageNonce;
 use bp_runtime::{MILLAU_CHAIN_ID, RIALTO_CHAIN_ID};
-use bridge_runtime_common::messages::target::FromBridgedChainMessagesProof;
-use codec::Encode;
+use bridge_runtime_common::messages::{target::FromBridgedChainMessagesProof, BridgedChainWithMessages};
+use codec::{Decode, Encode};
 use frame_support::dispatch::GetDispatchInfo;
 use messages_relay::message_lane::MessageLane;
 use relay_millau_client::{HeaderId as MillauHeaderId, Millau, SigningParams as MillauSigningParams};
 use relay_rialto_client::{HeaderId as RialtoHeaderId, Rialto, SigningParams as RialtoSigningParams};
 use relay_substrate_client::{
 	metrics::{FloatStorageValueMetric, StorageProofOverheadMetric},
-	Chain, TransactionSignScheme,
+	Chain, ChainWithProxy, TransactionEra, TransactionSignScheme,
 };
+use relay_utils::metrics::MetricsParams;
 use sp_core::{Bytes, Pair};
-use std::{ops::RangeInclusive, time::Duration};
+use std::{ops::RangeInclusive, sync::Arc, time::Duration};
+
+/// Synchronization loop timeout that is used if relay transactions are immortal.
+///
+/// Millau and Rialto are producing blocks every few seconds, so if transaction isn't mined
+/// in 5 minutes, something is probably wrong.
+const STALL_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 
 /// Millau-to-Rialto message lane.
 pub type MillauMessagesToRialto =
 	SubstrateMessageLaneToSubstrate<Millau, MillauSigningParams, Rialto, RialtoSigningParams>;
 
 impl SubstrateMessageLane for MillauMessagesToRialto {
-	const OUTBOUND_LANE_MESSAGES_DISPATCH_WEIGHT_METHOD: &'static str =
-		bp_rialto::TO_RIALTO_MESSAGES_DISPATCH_WEIGHT_METHOD;
+	const OUTBOUND_LANE_MESSAGE_DETAILS_METHOD: &'static str = bp_rialto::TO_RIALTO_MESSAGE_DETAILS_METHOD;
 	const OUTBOUND_LANE_LATEST_GENERATED_NONCE_METHOD: &'static str =
 		bp_rialto::TO_RIALTO_LATEST_GENERATED_NONCE_METHOD;
 	const OUTBOUND_LANE_LATEST_RECEIVED_NONCE_METHOD: &'static str = bp_rialto::TO_RIALTO_LATEST_RECEIVED_NONCE_METHOD;
@@ -52,6 +61,7 @@ impl SubstrateMessageLane for MillauMessagesToRialto {
 	const INBOUND_LANE_LATEST_CONFIRMED_NONCE_METHOD: &'static str =
 		bp_millau::FROM_MILLAU_LATEST_CONFIRMED_NONCE_METHOD;
 	const INBOUND_LANE_UNREWARDED_RELAYERS_STATE: &'static str = bp_millau::FROM_MILLAU_UNREWARDED_RELAYERS_STATE;
+	const INBOUND_LANE_DELIVERY_LIMITS_METHOD: &'static str = bp_millau::FROM_MILLAU_DELIVERY_LIMITS_METHOD;
 
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_millau::BEST_FINALIZED_MILLAU_HEADER_METHOD;
 	const BEST_FINALIZED_TARGET_HEADER_ID_AT_SOURCE: &'static str = bp_rialto::BEST_FINALIZED_RIALTO_HEADER_METHOD;
@@ -59,12 +69,25 @@ impl SubstrateMessageLane for MillauMessagesToRialto {
 	type SourceChain = Millau;
 	type TargetChain = Rialto;
 
+	fn message_sender(encoded_payload: &[u8]) -> Option<bp_millau::AccountId> {
+		match millau_runtime::rialto_messages::ToRialtoMessagePayload::decode(&mut &encoded_payload[..])
+			.ok()?
+			.origin
+		{
+			bp_message_dispatch::CallOrigin::SourceRoot => None,
+			bp_message_dispatch::CallOrigin::TargetAccount(sender, _, _) => Some(sender),
+			bp_message_dispatch::CallOrigin::SourceAccount(sender) => Some(sender),
+			bp_message_dispatch::CallOrigin::TargetAccountWithProof(sender, _) => Some(sender),
+		}
+	}
+
 	fn source_transactions_author(&self) -> bp_millau::AccountId {
 		(*self.source_sign.public().as_array_ref()).into()
 	}
 
 	fn make_messages_receiving_proof_transaction(
 		&self,
+		best_block_id: MillauHeaderId,
 		transaction_nonce: <Millau as Chain>::Index,
 		_generated_at_block: RialtoHeaderId,
 		proof: <Self as MessageLane>::MessagesReceivingProof,
@@ -72,9 +95,17 @@ impl SubstrateMessageLane for MillauMessagesToRialto {
 		let (relayers_state, proof) = proof;
 		let call: millau_runtime::Call =
 			millau_runtime::MessagesCall::receive_messages_delivery_proof(proof, relayers_state).into();
+		let call = Millau::maybe_proxy_call(self.source_proxied_account.clone(), call);
 		let call_weight = call.get_dispatch_info().weight;
 		let genesis_hash = *self.source_client.genesis_hash();
-		let transaction = Millau::sign_transaction(genesis_hash, &self.source_sign, transaction_nonce, call);
+		let transaction = Millau::sign_transaction(
+			genesis_hash,
+			&self.source_sign,
+			TransactionEra::new(best_block_id.0, best_block_id.1, self.source_transactions_mortality),
+			transaction_nonce,
+			0,
+			call,
+		);
 		log::trace!(
 			target: "bridge",
 			"Prepared Rialto -> Millau confirmation transaction. Weight: {}/{}, size: {}/{}",
@@ -90,12 +121,19 @@ impl SubstrateMessageLane for MillauMessagesToRialto {
 		(*self.target_sign.public().as_array_ref()).into()
 	}
 
+	fn target_max_transaction_tip(&self) -> bp_rialto::Balance {
+		self.target_max_tip
+	}
+
 	fn make_messages_delivery_transaction(
 		&self,
+		best_block_id: RialtoHeaderId,
 		transaction_nonce: <Rialto as Chain>::Index,
+		tip: bp_rialto::Balance,
 		_generated_at_header: MillauHeaderId,
 		_nonces: RangeInclusive<MessageNonce>,
 		proof: <Self as MessageLane>::MessagesProof,
+		reverse_lane_confirmation: Option<SubstrateMessagesReceivingProof<Millau>>,
 	) -> Bytes {
 		let (dispatch_weight, proof) = proof;
 		let FromBridgedChainMessagesProof {
@@ -111,9 +149,33 @@ impl SubstrateMessageLane for MillauMessagesToRialto {
 			dispatch_weight,
 		)
 		.into();
+		let call = match reverse_lane_confirmation {
+			Some((relayers_state, confirmation_proof)) => {
+				let confirmation_call: rialto_runtime::Call =
+					rialto_runtime::MessagesCall::receive_messages_delivery_proof(confirmation_proof, relayers_state)
+						.into();
+				let batch_call: rialto_runtime::Call =
+					rialto_runtime::UtilityCall::batch_all(vec![call.clone(), confirmation_call]).into();
+				// never let confirmations break messages delivery
+				if batch_call.get_dispatch_info().weight <= bp_rialto::max_extrinsic_weight() {
+					batch_call
+				} else {
+					call
+				}
+			}
+			None => call,
+		};
+		let call = Rialto::maybe_proxy_call(self.target_proxied_account.clone(), call);
 		let call_weight = call.get_dispatch_info().weight;
 		let genesis_hash = *self.target_client.genesis_hash();
-		let transaction = Rialto::sign_transaction(genesis_hash, &self.target_sign, transaction_nonce, call);
+		let transaction = Rialto::sign_transaction(
+			genesis_hash,
+			&self.target_sign,
+			TransactionEra::new(best_block_id.0, best_block_id.1, self.target_transactions_mortality),
+			transaction_nonce,
+			tip,
+			call,
+		);
 		log::trace!(
 			target: "bridge",
 			"Prepared Millau -> Rialto delivery transaction. Weight: {}/{}, size: {}/{}",
@@ -146,40 +208,126 @@ type RialtoTargetClient = SubstrateMessagesTarget<
 pub async fn run(
 	params: MessagesRelayParams<Millau, MillauSigningParams, Rialto, RialtoSigningParams>,
 ) -> Result<(), String> {
-	let stall_timeout = Duration::from_secs(5 * 60);
-	let relayer_id_at_millau = (*params.source_sign.public().as_array_ref()).into();
+	let stall_timeout = relay_substrate_client::bidirectional_transaction_stall_timeout(
+		params.source_transactions_mortality,
+		params.target_transactions_mortality,
+		Millau::AVERAGE_BLOCK_INTERVAL,
+		Rialto::AVERAGE_BLOCK_INTERVAL,
+		STALL_TIMEOUT,
+	);
+	let relayer_id_at_millau = params
+		.source_proxied_account
+		.clone()
+		.unwrap_or_else(|| (*params.source_sign.public().as_array_ref()).into());
 
 	let lane_id = params.lane_id;
 	let source_client = params.source_client;
 	let lane = MillauMessagesToRialto {
 		source_client: source_client.clone(),
 		source_sign: params.source_sign,
+		source_transactions_mortality: params.source_transactions_mortality,
+		source_proxied_account: params.source_proxied_account,
 		target_client: params.target_client.clone(),
 		target_sign: params.target_sign,
+		target_transactions_mortality: params.target_transactions_mortality,
+		target_proxied_account: params.target_proxied_account,
+		target_max_tip: params.target_max_tip,
 		relayer_id_at_source: relayer_id_at_millau,
 	};
 
-	// 2/3 is reserved for proofs and tx overhead
-	let max_messages_size_in_single_batch = bp_rialto::max_extrinsic_size() as usize / 3;
+	// limits are read from the target chain runtime and the relay is restarted if they're changed
+	let delivery_limits = read_delivery_limits::<MillauMessagesToRialto>(&params.target_client)
+		.await
+		.map_err(|e| format!("Failed to read messages delivery limits: {:?}", e))?;
+	abort_on_delivery_limits_change::<MillauMessagesToRialto>(params.target_client.clone(), delivery_limits);
+
 	// TODO: use Millau weights after https://github.com/paritytech/parity-bridges-common/issues/390
-	let (max_messages_in_single_batch, max_messages_weight_in_single_batch) =
+	let limits =
 		select_delivery_transaction_limits::<pallet_bridge_messages::weights::RialtoWeight<millau_runtime::Runtime>>(
-			bp_rialto::max_extrinsic_weight(),
-			bp_rialto::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE,
+			delivery_limits.max_extrinsic_weight,
+			delivery_limits.max_extrinsic_size,
+			delivery_limits.max_unconfirmed_messages_at_inbound_lane,
 		);
 
 	log::info!(
 		target: "bridge",
 		"Starting Millau -> Rialto messages relay.\n\t\
 			Millau relayer account id: {:?}\n\t\
+			Relayer mode: {:?}\n\t\
+			Max confirmations delay: {}\n\t\
+			Piggyback confirmations: {}\n\t\
+			Max delivery transactions in flight: {}\n\t\
 			Max messages in single transaction: {}\n\t\
 			Max messages size in single transaction: {}\n\t\
-			Max messages weight in single transaction: {}",
+			Max messages weight in single transaction: {}\n\t\
+			Per-message size overhead: {}\n\t\
+			Per-message weight overhead: {}",
 		lane.relayer_id_at_source,
-		max_messages_in_single_batch,
-		max_messages_size_in_single_batch,
-		max_messages_weight_in_single_batch,
+		params.relayer_mode,
+		params.max_confirmations_delay.get(),
+		params.piggyback_confirmations,
+		params.max_delivery_transactions_in_flight.get(),
+		limits.max_messages_in_single_batch,
+		limits.max_messages_size_in_single_batch,
+		limits.max_messages_weight_in_single_batch,
+		limits.per_message_size_overhead,
+		limits.per_message_weight_overhead,
+	);
+
+	relay_substrate_client::guard::alert_when_pallet_halted(
+		source_client.clone(),
+		"Rialto messages pallet".into(),
+		pallet_bridge_messages::storage_keys::is_halted_key::<millau_runtime::WithRialtoMessagesInstance>(),
 	);
+	relay_substrate_client::guard::alert_when_pallet_halted(
+		params.target_client.clone(),
+		"Millau messages pallet".into(),
+		pallet_bridge_messages::storage_keys::is_halted_key::<rialto_runtime::WithMillauMessagesInstance>(),
+	);
+
+	let reverse_lane_confirmations = if params.piggyback_confirmations {
+		Some(ReverseLaneConfirmations {
+			source_client: source_client.clone(),
+			inbound_lane_data_key: pallet_bridge_messages::storage_keys::inbound_lane_data_key::<
+				millau_runtime::Runtime,
+				millau_runtime::WithRialtoMessagesInstance,
+			>(&lane_id),
+			inbound_lane_latest_received_nonce_method: bp_rialto::FROM_RIALTO_LATEST_RECEIVED_NONCE_METHOD,
+			inbound_lane_unrewarded_relayers_state_method: bp_rialto::FROM_RIALTO_UNREWARDED_RELAYERS_STATE,
+			outbound_lane_latest_received_nonce_method: bp_millau::TO_MILLAU_LATEST_RECEIVED_NONCE_METHOD,
+		})
+	} else {
+		None
+	};
+
+	let metrics_params: MetricsParams = relay_utils::relay_metrics(
+		Some(messages_relay::message_lane_loop::metrics_prefix::<
+			MillauMessagesToRialto,
+		>(&lane_id)),
+		params.metrics_params,
+	)
+	.standalone_metric(|registry, prefix| {
+		StorageProofOverheadMetric::new(
+			registry,
+			prefix,
+			source_client.clone(),
+			"millau_storage_proof_overhead".into(),
+			"Millau storage proof overhead".into(),
+		)
+	})?
+	.standalone_metric(|registry, prefix| {
+		FloatStorageValueMetric::<_, sp_runtime::FixedU128>::new(
+			registry,
+			prefix,
+			source_client.clone(),
+			<Millau as ChainWithConversionRate>::conversion_rate_key(),
+			Some(Millau::INITIAL_CONVERSION_RATE),
+			"millau_rialto_to_millau_conversion_rate".into(),
+			"Rialto to Millau tokens conversion rate (used by Rialto)".into(),
+		)
+	})?
+	.into_params();
+	let transactions_metrics = MessagesTransactionsMetrics::new(&metrics_params)?;
 
 	messages_relay::message_lane_loop::run(
 		messages_relay::message_lane_loop::Params {
@@ -187,21 +335,52 @@ pub async fn run(
 			source_tick: Millau::AVERAGE_BLOCK_INTERVAL,
 			target_tick: Rialto::AVERAGE_BLOCK_INTERVAL,
 			reconnect_delay: relay_utils::relay_loop::RECONNECT_DELAY,
+			source_recovery_policy: params.source_recovery_policy,
+			target_recovery_policy: params.target_recovery_policy,
 			stall_timeout,
 			delivery_params: messages_relay::message_lane_loop::MessageDeliveryParams {
-				max_unrewarded_relayer_entries_at_target: bp_rialto::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE,
-				max_unconfirmed_nonces_at_target: bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
-				max_messages_in_single_batch,
-				max_messages_weight_in_single_batch,
-				max_messages_size_in_single_batch,
+				relayer_mode: params.relayer_mode,
+				max_unrewarded_relayer_entries_at_target: delivery_limits
+					.max_unrewarded_relayer_entries_at_inbound_lane,
+				max_unconfirmed_nonces_at_target: delivery_limits.max_unconfirmed_messages_at_inbound_lane,
+				max_messages_in_single_batch: limits.max_messages_in_single_batch,
+				max_transactions_in_flight: params.max_delivery_transactions_in_flight,
+				max_messages_weight_in_single_batch: limits.max_messages_weight_in_single_batch,
+				max_messages_size_in_single_batch: limits.max_messages_size_in_single_batch,
+				per_message_weight_overhead: limits.per_message_weight_overhead,
+				per_message_size_overhead: limits.per_message_size_overhead,
+				delivery_transaction_base_weight: limits.delivery_transaction_base_weight,
+				delivery_cost_estimator: Arc::new(SubstrateDeliveryCostEstimator::new(
+					source_client.clone(),
+					<Millau as ChainWithConversionRate>::conversion_rate_key(),
+					Millau::INITIAL_CONVERSION_RATE,
+					|transaction| {
+						<millau_runtime::rialto_messages::Rialto as BridgedChainWithMessages>::transaction_payment(
+							transaction,
+						)
+						.into()
+					},
+				)),
 			},
+			receiving_params: messages_relay::message_lane_loop::MessageReceivingParams {
+				max_confirmations_delay: params.max_confirmations_delay,
+				max_unconfirmed_nonces_at_target: delivery_limits.max_unconfirmed_messages_at_inbound_lane,
+			},
+			forced_delivery: params.forced_delivery,
+			control: params
+				.control
+				.loop_control(messages_relay::message_lane_loop::metrics_prefix::<
+					MillauMessagesToRialto,
+				>(&lane_id)),
 		},
 		MillauSourceClient::new(
-			source_client.clone(),
+			source_client,
 			lane.clone(),
 			lane_id,
 			RIALTO_CHAIN_ID,
 			params.target_to_source_headers_relay,
+			params.only_messages_from,
+			transactions_metrics.clone(),
 		),
 		RialtoTargetClient::new(
 			params.target_client,
@@ -209,36 +388,10 @@ pub async fn run(
 			lane_id,
 			MILLAU_CHAIN_ID,
 			params.source_to_target_headers_relay,
+			reverse_lane_confirmations,
+			transactions_metrics,
 		),
-		relay_utils::relay_metrics(
-			Some(messages_relay::message_lane_loop::metrics_prefix::<
-				MillauMessagesToRialto,
-			>(&lane_id)),
-			params.metrics_params,
-		)
-		.standalone_metric(|registry, prefix| {
-			StorageProofOverheadMetric::new(
-				registry,
-				prefix,
-				source_client.clone(),
-				"millau_storage_proof_overhead".into(),
-				"Millau storage proof overhead".into(),
-			)
-		})?
-		.standalone_metric(|registry, prefix| {
-			FloatStorageValueMetric::<_, sp_runtime::FixedU128>::new(
-				registry,
-				prefix,
-				source_client,
-				sp_core::storage::StorageKey(
-					millau_runtime::rialto_messages::RialtoToMillauConversionRate::key().to_vec(),
-				),
-				Some(millau_runtime::rialto_messages::INITIAL_RIALTO_TO_MILLAU_CONVERSION_RATE),
-				"millau_rialto_to_millau_conversion_rate".into(),
-				"Rialto to Millau tokens conversion rate (used by Rialto)".into(),
-			)
-		})?
-		.into_params(),
+		metrics_params,
 		futures::future::pending(),
 	)
 	.await
@@ -0,0 +1,96 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Millau <-> Rialto complex headers+messages relay configuration.
+
+use crate::chains::millau_headers_to_rialto::MillauFinalityToRialto;
+use crate::chains::millau_messages_to_rialto::MillauMessagesToRialto;
+use crate::chains::rialto_headers_to_millau::RialtoFinalityToMillau;
+use crate::chains::rialto_messages_to_millau::RialtoMessagesToMillau;
+use crate::headers_and_messages::HeadersAndMessagesBridge;
+use crate::messages_lane::MessagesRelayParams;
+use crate::on_demand_headers::OnDemandHeadersRelay;
+
+use futures::{future::BoxFuture, FutureExt};
+use relay_millau_client::{Millau, SigningParams as MillauSigningParams};
+use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
+use relay_substrate_client::Client;
+use sp_runtime::AccountId32;
+
+/// Millau <-> Rialto bridge, served by the complex headers+messages relay.
+pub struct MillauRialtoHeadersAndMessagesBridge;
+
+impl HeadersAndMessagesBridge for MillauRialtoHeadersAndMessagesBridge {
+	type Left = Millau;
+	type LeftSign = MillauSigningParams;
+	type Right = Rialto;
+	type RightSign = RialtoSigningParams;
+
+	type LeftToRightMessages = MillauMessagesToRialto;
+	type RightToLeftMessages = RialtoMessagesToMillau;
+
+	fn left_to_right_on_demand_headers(
+		left_client: Client<Millau>,
+		right_client: Client<Rialto>,
+		right_sign: RialtoSigningParams,
+		right_transactions_mortality: Option<u32>,
+		right_proxied_account: Option<AccountId32>,
+		right_max_tip: bp_rialto::Balance,
+		only_mandatory_headers: bool,
+	) -> OnDemandHeadersRelay<Millau> {
+		OnDemandHeadersRelay::new(
+			left_client,
+			right_client.clone(),
+			MillauFinalityToRialto::new(right_client, right_sign, right_transactions_mortality)
+				.with_target_proxied_account(right_proxied_account)
+				.with_target_max_tip(right_max_tip),
+			bp_millau::SESSION_LENGTH,
+			only_mandatory_headers,
+		)
+	}
+
+	fn right_to_left_on_demand_headers(
+		right_client: Client<Rialto>,
+		left_client: Client<Millau>,
+		left_sign: MillauSigningParams,
+		left_transactions_mortality: Option<u32>,
+		left_proxied_account: Option<AccountId32>,
+		left_max_tip: bp_millau::Balance,
+		only_mandatory_headers: bool,
+	) -> OnDemandHeadersRelay<Rialto> {
+		OnDemandHeadersRelay::new(
+			right_client,
+			left_client.clone(),
+			RialtoFinalityToMillau::new(left_client, left_sign, left_transactions_mortality)
+				.with_target_proxied_account(left_proxied_account)
+				.with_target_max_tip(left_max_tip),
+			bp_rialto::SESSION_LENGTH,
+			only_mandatory_headers,
+		)
+	}
+
+	fn left_to_right_messages(
+		params: MessagesRelayParams<Millau, MillauSigningParams, Rialto, RialtoSigningParams>,
+	) -> BoxFuture<'static, Result<(), String>> {
+		crate::chains::millau_messages_to_rialto::run(params).boxed()
+	}
+
+	fn right_to_left_messages(
+		params: MessagesRelayParams<Rialto, RialtoSigningParams, Millau, MillauSigningParams>,
+	) -> BoxFuture<'static, Result<(), String>> {
+		crate::chains::rialto_messages_to_millau::run(params).boxed()
+	}
+}
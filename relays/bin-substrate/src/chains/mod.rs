@@ -17,7 +17,9 @@
 //! Chain-specific relayer configuration.
 
 pub mod millau_headers_to_rialto;
+pub mod millau_headers_to_rialto_parachain;
 pub mod millau_messages_to_rialto;
+pub mod millau_rialto_headers_and_messages;
 pub mod rialto_headers_to_millau;
 pub mod rialto_messages_to_millau;
 pub mod rococo_headers_to_wococo;
@@ -26,6 +28,7 @@ pub mod wococo_headers_to_rococo;
 
 mod millau;
 mod rialto;
+mod rialto_parachain;
 mod rococo;
 mod westend;
 mod wococo;
@@ -73,7 +76,7 @@ mod tests {
 	use frame_support::dispatch::GetDispatchInfo;
 	use relay_millau_client::Millau;
 	use relay_rialto_client::Rialto;
-	use relay_substrate_client::TransactionSignScheme;
+	use relay_substrate_client::{TransactionEra, TransactionSignScheme};
 	use sp_core::Pair;
 	use sp_runtime::traits::{IdentifyAccount, Verify};
 
@@ -132,6 +135,8 @@ mod tests {
 		let payload = send_message::message_payload(
 			Default::default(),
 			call.get_dispatch_info().weight,
+			None,
+			bp_message_dispatch::DispatchFeePayment::AtSourceChain,
 			bp_message_dispatch::CallOrigin::SourceRoot,
 			&call,
 		);
@@ -142,6 +147,8 @@ mod tests {
 		let payload = send_message::message_payload(
 			Default::default(),
 			call.get_dispatch_info().weight,
+			None,
+			bp_message_dispatch::DispatchFeePayment::AtSourceChain,
 			bp_message_dispatch::CallOrigin::SourceRoot,
 			&call,
 		);
@@ -169,6 +176,8 @@ mod tests {
 		let payload = send_message::message_payload(
 			Default::default(),
 			maximal_dispatch_weight,
+			None,
+			bp_message_dispatch::DispatchFeePayment::AtSourceChain,
 			bp_message_dispatch::CallOrigin::SourceRoot,
 			&call,
 		);
@@ -177,6 +186,8 @@ mod tests {
 		let payload = send_message::message_payload(
 			Default::default(),
 			maximal_dispatch_weight + 1,
+			None,
+			bp_message_dispatch::DispatchFeePayment::AtSourceChain,
 			bp_message_dispatch::CallOrigin::SourceRoot,
 			&call,
 		);
@@ -194,6 +205,8 @@ mod tests {
 		let payload = send_message::message_payload(
 			Default::default(),
 			maximal_dispatch_weight,
+			None,
+			bp_message_dispatch::DispatchFeePayment::AtSourceChain,
 			bp_message_dispatch::CallOrigin::SourceRoot,
 			&call,
 		);
@@ -202,6 +215,8 @@ mod tests {
 		let payload = send_message::message_payload(
 			Default::default(),
 			maximal_dispatch_weight + 1,
+			None,
+			bp_message_dispatch::DispatchFeePayment::AtSourceChain,
 			bp_message_dispatch::CallOrigin::SourceRoot,
 			&call,
 		);
@@ -214,6 +229,8 @@ mod tests {
 		let rialto_tx = Rialto::sign_transaction(
 			Default::default(),
 			&sp_keyring::AccountKeyring::Alice.pair(),
+			TransactionEra::mortal(Default::default(), Default::default(), 64),
+			0,
 			0,
 			rialto_call.clone(),
 		);
@@ -232,6 +249,8 @@ mod tests {
 		let millau_tx = Millau::sign_transaction(
 			Default::default(),
 			&sp_keyring::AccountKeyring::Alice.pair(),
+			TransactionEra::mortal(Default::default(), Default::default(), 64),
+			0,
 			0,
 			millau_call.clone(),
 		);
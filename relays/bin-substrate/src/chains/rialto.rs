@@ -19,12 +19,14 @@
 use crate::cli::{
 	bridge,
 	encode_call::{self, Call, CliEncodeCall},
-	encode_message, send_message, CliChain,
+	encode_message, send_message, CliChain, ExplicitOrMaximal,
 };
+use crate::relayer_rewards::ChainWithRelayersRewards;
 use bp_message_dispatch::{CallOrigin, MessagePayload};
 use codec::Decode;
 use frame_support::weights::{GetDispatchInfo, Weight};
 use relay_rialto_client::Rialto;
+use sp_core::storage::StorageKey;
 use sp_version::RuntimeVersion;
 
 impl CliEncodeCall for Rialto {
@@ -38,9 +40,9 @@ impl CliEncodeCall for Rialto {
 			Call::Remark { remark_payload, .. } => rialto_runtime::Call::System(rialto_runtime::SystemCall::remark(
 				remark_payload.as_ref().map(|x| x.0.clone()).unwrap_or_default(),
 			)),
-			Call::Transfer { recipient, amount } => {
-				rialto_runtime::Call::Balances(rialto_runtime::BalancesCall::transfer(recipient.raw_id(), amount.0))
-			}
+			Call::Transfer { recipient, amount } => rialto_runtime::Call::Balances(
+				rialto_runtime::BalancesCall::transfer(recipient.raw_id(), amount.cast()?),
+			),
 			Call::BridgeSendMessage {
 				lane,
 				payload,
@@ -50,7 +52,9 @@ impl CliEncodeCall for Rialto {
 				bridge::RIALTO_TO_MILLAU_INDEX => {
 					let payload = Decode::decode(&mut &*payload.0)?;
 					rialto_runtime::Call::BridgeMillauMessages(rialto_runtime::MessagesCall::send_message(
-						lane.0, payload, fee.0,
+						*lane,
+						payload,
+						fee.cast()?,
 					))
 				}
 				_ => anyhow::bail!(
@@ -62,6 +66,16 @@ impl CliEncodeCall for Rialto {
 	}
 }
 
+impl ChainWithRelayersRewards for Rialto {
+	fn relayer_rewards_key(relayer: &Self::AccountId) -> StorageKey {
+		StorageKey(pallet_bridge_relayers::RelayerRewards::<rialto_runtime::Runtime>::hashed_key_for(relayer))
+	}
+
+	fn claim_rewards_call() -> Self::Call {
+		rialto_runtime::Call::BridgeMillauRelayers(rialto_runtime::RelayersCall::claim_rewards())
+	}
+}
+
 impl CliChain for Rialto {
 	const RUNTIME_VERSION: RuntimeVersion = rialto_runtime::VERSION;
 
@@ -80,7 +94,13 @@ impl CliChain for Rialto {
 		match message {
 			encode_message::MessagePayload::Raw { data } => MessagePayload::decode(&mut &*data.0)
 				.map_err(|e| format!("Failed to decode Rialto's MessagePayload: {:?}", e)),
-			encode_message::MessagePayload::Call { mut call, mut sender } => {
+			encode_message::MessagePayload::Call {
+				mut call,
+				mut sender,
+				dispatch_weight,
+				delivery_deadline,
+				dispatch_fee_payment,
+			} => {
 				type Source = Rialto;
 				type Target = relay_millau_client::Millau;
 
@@ -89,9 +109,20 @@ impl CliChain for Rialto {
 				let origin = CallOrigin::SourceAccount(sender.raw_id());
 				encode_call::preprocess_call::<Source, Target>(&mut call, bridge::RIALTO_TO_MILLAU_INDEX);
 				let call = Target::encode_call(&call).map_err(|e| e.to_string())?;
-				let weight = call.get_dispatch_info().weight;
+				let weight = send_message::prepare_call_dispatch_weight(
+					&dispatch_weight,
+					ExplicitOrMaximal::Explicit(call.get_dispatch_info().weight),
+					send_message::compute_maximal_message_dispatch_weight(Target::max_extrinsic_weight()),
+				);
 
-				Ok(send_message::message_payload(spec_version, weight, origin, &call))
+				Ok(send_message::message_payload(
+					spec_version,
+					weight,
+					delivery_deadline,
+					dispatch_fee_payment.into(),
+					origin,
+					&call,
+				))
 			}
 		}
 	}
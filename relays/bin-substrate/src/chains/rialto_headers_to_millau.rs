@@ -20,9 +20,9 @@ use crate::finality_pipeline::{SubstrateFinalitySyncPipeline, SubstrateFinalityT
 
 use bp_header_chain::justification::GrandpaJustification;
 use codec::Encode;
-use relay_millau_client::{Millau, SigningParams as MillauSigningParams};
+use relay_millau_client::{HeaderId as MillauHeaderId, Millau, SigningParams as MillauSigningParams};
 use relay_rialto_client::{Rialto, SyncHeader as RialtoSyncHeader};
-use relay_substrate_client::{Chain, TransactionSignScheme};
+use relay_substrate_client::{Chain, ChainWithProxy, TransactionEra, TransactionSignScheme};
 use sp_core::{Bytes, Pair};
 
 /// Rialto-to-Millau finality sync pipeline.
@@ -30,6 +30,7 @@ pub(crate) type RialtoFinalityToMillau = SubstrateFinalityToSubstrate<Rialto, Mi
 
 impl SubstrateFinalitySyncPipeline for RialtoFinalityToMillau {
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_rialto::BEST_FINALIZED_RIALTO_HEADER_METHOD;
+	const GRANDPA_PALLET_NAME_AT_TARGET: &'static str = bp_rialto::WITH_RIALTO_GRANDPA_PALLET_NAME;
 
 	type TargetChain = Millau;
 
@@ -37,9 +38,15 @@ impl SubstrateFinalitySyncPipeline for RialtoFinalityToMillau {
 		(*self.target_sign.public().as_array_ref()).into()
 	}
 
+	fn max_transaction_tip(&self) -> bp_millau::Balance {
+		self.target_max_tip
+	}
+
 	fn make_submit_finality_proof_transaction(
 		&self,
+		best_block_id: MillauHeaderId,
 		transaction_nonce: <Millau as Chain>::Index,
+		tip: bp_millau::Balance,
 		header: RialtoSyncHeader,
 		proof: GrandpaJustification<bp_rialto::Header>,
 	) -> Bytes {
@@ -48,9 +55,17 @@ impl SubstrateFinalitySyncPipeline for RialtoFinalityToMillau {
 			millau_runtime::RialtoGrandpaInstance,
 		>::submit_finality_proof(header.into_inner(), proof)
 		.into();
+		let call = Millau::maybe_proxy_call(self.target_proxied_account.clone(), call);
 
 		let genesis_hash = *self.target_client.genesis_hash();
-		let transaction = Millau::sign_transaction(genesis_hash, &self.target_sign, transaction_nonce, call);
+		let transaction = Millau::sign_transaction(
+			genesis_hash,
+			&self.target_sign,
+			TransactionEra::new(best_block_id.0, best_block_id.1, self.transactions_mortality),
+			transaction_nonce,
+			tip,
+			call,
+		);
 
 		Bytes(transaction.encode())
 	}
@@ -21,9 +21,9 @@ use crate::finality_pipeline::{SubstrateFinalitySyncPipeline, SubstrateFinalityT
 use bp_header_chain::justification::GrandpaJustification;
 use codec::Encode;
 use relay_rococo_client::{Rococo, SyncHeader as RococoSyncHeader};
-use relay_substrate_client::{Chain, Client, TransactionSignScheme};
+use relay_substrate_client::{Chain, Client, TransactionEra, TransactionSignScheme};
 use relay_utils::metrics::MetricsParams;
-use relay_wococo_client::{SigningParams as WococoSigningParams, Wococo};
+use relay_wococo_client::{HeaderId as WococoHeaderId, SigningParams as WococoSigningParams, Wococo};
 use sp_core::{Bytes, Pair};
 
 /// Rococo-to-Wococo finality sync pipeline.
@@ -31,6 +31,7 @@ pub(crate) type RococoFinalityToWococo = SubstrateFinalityToSubstrate<Rococo, Wo
 
 impl SubstrateFinalitySyncPipeline for RococoFinalityToWococo {
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_rococo::BEST_FINALIZED_ROCOCO_HEADER_METHOD;
+	const GRANDPA_PALLET_NAME_AT_TARGET: &'static str = bp_rococo::WITH_ROCOCO_GRANDPA_PALLET_NAME;
 
 	type TargetChain = Wococo;
 
@@ -49,9 +50,15 @@ impl SubstrateFinalitySyncPipeline for RococoFinalityToWococo {
 		(*self.target_sign.public().as_array_ref()).into()
 	}
 
+	fn max_transaction_tip(&self) -> bp_wococo::Balance {
+		self.target_max_tip
+	}
+
 	fn make_submit_finality_proof_transaction(
 		&self,
+		best_block_id: WococoHeaderId,
 		transaction_nonce: <Wococo as Chain>::Index,
+		tip: bp_wococo::Balance,
 		header: RococoSyncHeader,
 		proof: GrandpaJustification<bp_rococo::Header>,
 	) -> Bytes {
@@ -60,7 +67,14 @@ impl SubstrateFinalitySyncPipeline for RococoFinalityToWococo {
 			proof,
 		));
 		let genesis_hash = *self.target_client.genesis_hash();
-		let transaction = Wococo::sign_transaction(genesis_hash, &self.target_sign, transaction_nonce, call);
+		let transaction = Wococo::sign_transaction(
+			genesis_hash,
+			&self.target_sign,
+			TransactionEra::new(best_block_id.0, best_block_id.1, self.transactions_mortality),
+			transaction_nonce,
+			tip,
+			call,
+		);
 
 		Bytes(transaction.encode())
 	}
@@ -20,8 +20,8 @@ use crate::finality_pipeline::{SubstrateFinalitySyncPipeline, SubstrateFinalityT
 
 use bp_header_chain::justification::GrandpaJustification;
 use codec::Encode;
-use relay_millau_client::{Millau, SigningParams as MillauSigningParams};
-use relay_substrate_client::{Chain, TransactionSignScheme};
+use relay_millau_client::{HeaderId as MillauHeaderId, Millau, SigningParams as MillauSigningParams};
+use relay_substrate_client::{Chain, ChainWithProxy, TransactionEra, TransactionSignScheme};
 use relay_utils::metrics::MetricsParams;
 use relay_westend_client::{SyncHeader as WestendSyncHeader, Westend};
 use sp_core::{Bytes, Pair};
@@ -31,6 +31,7 @@ pub(crate) type WestendFinalityToMillau = SubstrateFinalityToSubstrate<Westend,
 
 impl SubstrateFinalitySyncPipeline for WestendFinalityToMillau {
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_westend::BEST_FINALIZED_WESTEND_HEADER_METHOD;
+	const GRANDPA_PALLET_NAME_AT_TARGET: &'static str = bp_westend::WITH_WESTEND_GRANDPA_PALLET_NAME;
 
 	type TargetChain = Millau;
 
@@ -42,9 +43,15 @@ impl SubstrateFinalitySyncPipeline for WestendFinalityToMillau {
 		(*self.target_sign.public().as_array_ref()).into()
 	}
 
+	fn max_transaction_tip(&self) -> bp_millau::Balance {
+		self.target_max_tip
+	}
+
 	fn make_submit_finality_proof_transaction(
 		&self,
+		best_block_id: MillauHeaderId,
 		transaction_nonce: <Millau as Chain>::Index,
+		tip: bp_millau::Balance,
 		header: WestendSyncHeader,
 		proof: GrandpaJustification<bp_westend::Header>,
 	) -> Bytes {
@@ -53,9 +60,17 @@ impl SubstrateFinalitySyncPipeline for WestendFinalityToMillau {
 			millau_runtime::WestendGrandpaInstance,
 		>::submit_finality_proof(header.into_inner(), proof)
 		.into();
+		let call = Millau::maybe_proxy_call(self.target_proxied_account.clone(), call);
 
 		let genesis_hash = *self.target_client.genesis_hash();
-		let transaction = Millau::sign_transaction(genesis_hash, &self.target_sign, transaction_nonce, call);
+		let transaction = Millau::sign_transaction(
+			genesis_hash,
+			&self.target_sign,
+			TransactionEra::new(best_block_id.0, best_block_id.1, self.transactions_mortality),
+			transaction_nonce,
+			tip,
+			call,
+		);
 
 		Bytes(transaction.encode())
 	}
@@ -20,8 +20,8 @@ use crate::finality_pipeline::{SubstrateFinalitySyncPipeline, SubstrateFinalityT
 
 use bp_header_chain::justification::GrandpaJustification;
 use codec::Encode;
-use relay_rococo_client::{Rococo, SigningParams as RococoSigningParams};
-use relay_substrate_client::{Chain, Client, TransactionSignScheme};
+use relay_rococo_client::{HeaderId as RococoHeaderId, Rococo, SigningParams as RococoSigningParams};
+use relay_substrate_client::{Chain, Client, TransactionEra, TransactionSignScheme};
 use relay_utils::metrics::MetricsParams;
 use relay_wococo_client::{SyncHeader as WococoSyncHeader, Wococo};
 use sp_core::{Bytes, Pair};
@@ -31,6 +31,7 @@ pub(crate) type WococoFinalityToRococo = SubstrateFinalityToSubstrate<Wococo, Ro
 
 impl SubstrateFinalitySyncPipeline for WococoFinalityToRococo {
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_wococo::BEST_FINALIZED_WOCOCO_HEADER_METHOD;
+	const GRANDPA_PALLET_NAME_AT_TARGET: &'static str = bp_wococo::WITH_WOCOCO_GRANDPA_PALLET_NAME;
 
 	type TargetChain = Rococo;
 
@@ -49,9 +50,15 @@ impl SubstrateFinalitySyncPipeline for WococoFinalityToRococo {
 		(*self.target_sign.public().as_array_ref()).into()
 	}
 
+	fn max_transaction_tip(&self) -> bp_rococo::Balance {
+		self.target_max_tip
+	}
+
 	fn make_submit_finality_proof_transaction(
 		&self,
+		best_block_id: RococoHeaderId,
 		transaction_nonce: <Rococo as Chain>::Index,
+		tip: bp_rococo::Balance,
 		header: WococoSyncHeader,
 		proof: GrandpaJustification<bp_wococo::Header>,
 	) -> Bytes {
@@ -60,7 +67,14 @@ impl SubstrateFinalitySyncPipeline for WococoFinalityToRococo {
 			proof,
 		));
 		let genesis_hash = *self.target_client.genesis_hash();
-		let transaction = Rococo::sign_transaction(genesis_hash, &self.target_sign, transaction_nonce, call);
+		let transaction = Rococo::sign_transaction(
+			genesis_hash,
+			&self.target_sign,
+			TransactionEra::new(best_block_id.0, best_block_id.1, self.transactions_mortality),
+			transaction_nonce,
+			tip,
+			call,
+		);
 
 		Bytes(transaction.encode())
 	}
@@ -58,14 +58,33 @@ macro_rules! select_full_bridge {
 				// Relay-messages
 				#[allow(unused_imports)]
 				use crate::chains::millau_messages_to_rialto::run as relay_messages;
+				#[allow(unused_imports)]
+				use crate::chains::millau_messages_to_rialto::MillauMessagesToRialto as MessagesLane;
 
 				// Send-message / Estimate-fee
 				#[allow(unused_imports)]
 				use bp_rialto::TO_RIALTO_ESTIMATE_MESSAGE_FEE_METHOD as ESTIMATE_MESSAGE_FEE_METHOD;
+				// Estimate-fee
+				#[allow(dead_code)]
+				type MessageBridge = millau_runtime::rialto_messages::WithRialtoMessageBridge;
+				#[allow(unused_imports)]
+				use millau_runtime::rialto_messages::INITIAL_RIALTO_TO_MILLAU_CONVERSION_RATE as INITIAL_CONVERSION_RATE;
+				#[allow(dead_code)]
+				fn conversion_rate_key() -> sp_core::storage::StorageKey {
+					<Source as crate::conversion_rate_update::ChainWithConversionRate>::conversion_rate_key()
+				}
 				// Send-message
 				#[allow(unused_imports)]
 				use millau_runtime::millau_to_rialto_account_ownership_digest as account_ownership_digest;
 
+				// Doctor
+				#[allow(unused_imports)]
+				use bp_millau::WITH_MILLAU_GRANDPA_PALLET_NAME as GRANDPA_PALLET_NAME_AT_TARGET;
+				#[allow(dead_code)]
+				type SourceMessagesInstance = millau_runtime::WithRialtoMessagesInstance;
+				#[allow(dead_code)]
+				type TargetMessagesInstance = rialto_runtime::WithMillauMessagesInstance;
+
 				$generic
 			}
 			FullBridge::RialtoToMillau => {
@@ -80,15 +99,36 @@ macro_rules! select_full_bridge {
 				// Relay-messages
 				#[allow(unused_imports)]
 				use crate::chains::rialto_messages_to_millau::run as relay_messages;
+				#[allow(unused_imports)]
+				use crate::chains::rialto_messages_to_millau::RialtoMessagesToMillau as MessagesLane;
 
 				// Send-message / Estimate-fee
 				#[allow(unused_imports)]
 				use bp_millau::TO_MILLAU_ESTIMATE_MESSAGE_FEE_METHOD as ESTIMATE_MESSAGE_FEE_METHOD;
+				// Estimate-fee
+				#[allow(dead_code)]
+				type MessageBridge = rialto_runtime::millau_messages::WithMillauMessageBridge;
+				#[allow(unused_imports)]
+				use rialto_runtime::millau_messages::INITIAL_MILLAU_TO_RIALTO_CONVERSION_RATE as INITIAL_CONVERSION_RATE;
+				#[allow(dead_code)]
+				fn conversion_rate_key() -> sp_core::storage::StorageKey {
+					sp_core::storage::StorageKey(
+						rialto_runtime::millau_messages::MillauToRialtoConversionRate::key().to_vec(),
+					)
+				}
 
 				// Send-message
 				#[allow(unused_imports)]
 				use rialto_runtime::rialto_to_millau_account_ownership_digest as account_ownership_digest;
 
+				// Doctor
+				#[allow(unused_imports)]
+				use bp_rialto::WITH_RIALTO_GRANDPA_PALLET_NAME as GRANDPA_PALLET_NAME_AT_TARGET;
+				#[allow(dead_code)]
+				type SourceMessagesInstance = rialto_runtime::WithMillauMessagesInstance;
+				#[allow(dead_code)]
+				type TargetMessagesInstance = millau_runtime::WithRialtoMessagesInstance;
+
 				$generic
 			}
 		}
@@ -0,0 +1,97 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::cli::{Balance, TargetConnectionParams, TargetSigningParams};
+use crate::relayer_rewards::{claim_rewards, relayer_rewards};
+
+use relay_substrate_client::Chain;
+use sp_core::Pair;
+use structopt::{clap::arg_enum, StructOpt};
+
+/// Claim rewards, earned by the relayer at the bridge relayers pallet.
+#[derive(StructOpt)]
+pub struct ClaimRewards {
+	/// A chain to claim rewards at.
+	#[structopt(possible_values = &ClaimRewardsChain::variants(), case_insensitive = true)]
+	chain: ClaimRewardsChain,
+	#[structopt(flatten)]
+	target: TargetConnectionParams,
+	#[structopt(flatten)]
+	target_sign: TargetSigningParams,
+}
+
+// TODO [#851] Use kebab-case.
+arg_enum! {
+	#[derive(Debug)]
+	/// Chain to claim rewards at.
+	pub enum ClaimRewardsChain {
+		Millau,
+		Rialto,
+	}
+}
+
+macro_rules! select_chain {
+	($chain: expr, $generic: tt) => {
+		match $chain {
+			ClaimRewardsChain::Millau => {
+				type Target = relay_millau_client::Millau;
+
+				$generic
+			}
+			ClaimRewardsChain::Rialto => {
+				type Target = relay_rialto_client::Rialto;
+
+				$generic
+			}
+		}
+	};
+}
+
+impl ClaimRewards {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		select_chain!(self.chain, {
+			let client = self.target.to_client::<Target>().await?;
+			let key_pair = self.target_sign.to_keypair::<Target>()?;
+			let proxied_account = self.target_sign.proxied_account::<Target>();
+			let relayer = proxied_account.clone().unwrap_or_else(|| key_pair.public().into());
+
+			let rewards = relayer_rewards(&client, &relayer).await?;
+			if rewards == 0 {
+				log::info!(target: "bridge", "{} relayer {} has no rewards to claim", Target::NAME, relayer);
+				return Ok(());
+			}
+
+			let transaction_hash = claim_rewards(
+				&client,
+				key_pair,
+				proxied_account,
+				self.target_sign.transactions_mortality()?,
+			)
+			.await?;
+			log::info!(
+				target: "bridge",
+				"Claiming {} rewards of {} relayer {}: {:?}",
+				Balance::from_native(rewards),
+				Target::NAME,
+				relayer,
+				transaction_hash,
+			);
+
+			Ok(())
+		})
+	}
+}
@@ -0,0 +1,98 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::cli::{TargetConnectionParams, TargetSigningParams};
+use crate::conversion_rate_update::run_conversion_rate_update_loop;
+
+use relay_substrate_client::Chain;
+use structopt::{clap::arg_enum, StructOpt};
+
+/// Keep conversion rate of bridged chain tokens, stored at the conversion rate pallet, in sync
+/// with the external price service.
+#[derive(StructOpt)]
+pub struct ConversionRateOracle {
+	/// A chain to update conversion rate at.
+	#[structopt(possible_values = &ConversionRateOracleChain::variants(), case_insensitive = true)]
+	chain: ConversionRateOracleChain,
+	#[structopt(flatten)]
+	target: TargetConnectionParams,
+	#[structopt(flatten)]
+	target_sign: TargetSigningParams,
+	/// URL of the price service, returning JSON with the conversion rate.
+	#[structopt(long, env = "BRIDGE_PRICE_URL")]
+	price_url: String,
+	/// Path to the conversion rate in the JSON, returned by the price service.
+	#[structopt(long, env = "BRIDGE_PRICE_JSON_PATH")]
+	price_json_path: String,
+	/// Difference (in percents) between on-chain and external conversion rates that triggers the update.
+	#[structopt(long, env = "BRIDGE_THRESHOLD", default_value = "5")]
+	threshold: f64,
+}
+
+// TODO [#851] Use kebab-case.
+arg_enum! {
+	#[derive(Debug)]
+	/// Chain to update conversion rate at.
+	pub enum ConversionRateOracleChain {
+		Millau,
+	}
+}
+
+macro_rules! select_chain {
+	($chain: expr, $generic: tt) => {
+		match $chain {
+			ConversionRateOracleChain::Millau => {
+				type Target = relay_millau_client::Millau;
+
+				$generic
+			}
+		}
+	};
+}
+
+impl ConversionRateOracle {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		if self.threshold <= 0.0 || !self.threshold.is_finite() {
+			anyhow::bail!("Conversion rate update threshold must be positive: {}", self.threshold);
+		}
+
+		select_chain!(self.chain, {
+			let client = self.target.to_client::<Target>().await?;
+			let key_pair = self.target_sign.to_keypair::<Target>()?;
+			let _target_signer_lock = self.target_sign.lock_signer::<Target>(&client).await?;
+
+			log::info!(
+				target: "bridge",
+				"Starting {} conversion rate oracle. Price service: {}",
+				Target::NAME,
+				self.price_url,
+			);
+
+			run_conversion_rate_update_loop(
+				client,
+				key_pair,
+				self.target_sign.transactions_mortality()?,
+				self.price_url,
+				self.price_json_path,
+				self.threshold / 100.0,
+			)
+			.await;
+
+			Ok(())
+		})
+	}
+}
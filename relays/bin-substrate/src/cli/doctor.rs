@@ -0,0 +1,704 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Diagnose the bridge between two chains.
+
+use crate::cli::bridge::FullBridge;
+use crate::cli::{
+	Balance, CliChain, SourceConnectionParams, SourceSigningParams, TargetConnectionParams, TargetSigningParams,
+};
+use crate::messages_lane::{read_delivery_limits, SubstrateMessageLane};
+use crate::select_full_bridge;
+
+use ansi_term::Colour;
+use bp_messages::{LaneId, MessageNonce};
+use bp_runtime::StorageVersion;
+use codec::{Decode, Encode};
+use relay_substrate_client::{Chain, ChainWithBalances, Client, ConnectionParams, Error as SubstrateError};
+use sp_core::{storage::StorageKey, Bytes, Pair};
+use sp_runtime::traits::{Header as HeaderT, Zero};
+use structopt::StructOpt;
+
+/// Number of finalized source chain headers that the target chain may be missing before the
+/// finality lag is reported.
+const MAX_FINALITY_LAG_IN_BLOCKS: u32 = 64;
+
+/// Run diagnostics checks for the bridge and print report with suggested fixes.
+#[derive(StructOpt)]
+pub struct Doctor {
+	/// A bridge instance to diagnose.
+	#[structopt(possible_values = &FullBridge::variants(), case_insensitive = true)]
+	bridge: FullBridge,
+	/// Hex-encoded id of the lane to diagnose. Defaults to `00000000`.
+	#[structopt(long, env = "BRIDGE_LANE", default_value = "00000000")]
+	lane: LaneId,
+	#[structopt(flatten)]
+	source: SourceConnectionParams,
+	#[structopt(flatten)]
+	source_sign: SourceSigningParams,
+	#[structopt(flatten)]
+	target: TargetConnectionParams,
+	#[structopt(flatten)]
+	target_sign: TargetSigningParams,
+}
+
+impl Doctor {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		let mut report = Report::default();
+		select_full_bridge!(self.bridge, {
+			let source_client = check_connection::<Source>(
+				&mut report,
+				"source",
+				self.source.to_connection_params(),
+				self.source.expected_genesis_hash::<Source>()?,
+			)
+			.await;
+			let target_client = check_connection::<Target>(
+				&mut report,
+				"target",
+				self.target.to_connection_params(),
+				self.target.expected_genesis_hash::<Target>()?,
+			)
+			.await;
+
+			if let Some(ref source_client) = source_client {
+				check_messages_pallet::<Source, Target>(
+					&mut report,
+					source_client,
+					pallet_bridge_messages::storage_keys::is_halted_key::<SourceMessagesInstance>(),
+				)
+				.await;
+				check_signer_balance::<Source>(
+					&mut report,
+					source_client,
+					"source",
+					self.source_sign.to_keypair::<Source>(),
+				)
+				.await;
+			}
+			if let Some(ref target_client) = target_client {
+				check_grandpa_pallet::<Source, Target>(
+					&mut report,
+					target_client,
+					GRANDPA_PALLET_NAME_AT_TARGET,
+					&self.bridge,
+				)
+				.await;
+				check_messages_pallet::<Target, Source>(
+					&mut report,
+					target_client,
+					pallet_bridge_messages::storage_keys::is_halted_key::<TargetMessagesInstance>(),
+				)
+				.await;
+				check_signer_balance::<Target>(
+					&mut report,
+					target_client,
+					"target",
+					self.target_sign.to_keypair::<Target>(),
+				)
+				.await;
+			}
+			if let (Some(ref source_client), Some(ref target_client)) = (source_client, target_client) {
+				check_finality_lag::<Source, Target>(
+					&mut report,
+					source_client,
+					target_client,
+					MessagesLane::BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET,
+				)
+				.await;
+				check_lane::<MessagesLane>(&mut report, source_client, target_client, self.lane).await;
+			}
+		});
+
+		print!("{}", report.render(true));
+
+		match report.failures() {
+			0 => Ok(()),
+			failures => Err(anyhow::format_err!(
+				"{} of {} bridge diagnostics checks have failed",
+				failures,
+				report.checks.len(),
+			)),
+		}
+	}
+}
+
+/// Status of the single diagnostics check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CheckStatus {
+	/// Check has passed.
+	Ok,
+	/// Bridge may operate, but something requires attention.
+	Warning,
+	/// Bridge can't operate until the problem is fixed.
+	Failure,
+}
+
+impl CheckStatus {
+	/// Label of the status in the report.
+	fn label(&self) -> &'static str {
+		match self {
+			Self::Ok => "[ OK ]",
+			Self::Warning => "[WARN]",
+			Self::Failure => "[FAIL]",
+		}
+	}
+
+	/// Colour of the status label in the report.
+	fn colour(&self) -> Colour {
+		match self {
+			Self::Ok => Colour::Green,
+			Self::Warning => Colour::Yellow,
+			Self::Failure => Colour::Red,
+		}
+	}
+}
+
+/// Result of the single diagnostics check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Check {
+	/// Check status.
+	status: CheckStatus,
+	/// What has been checked and what has been found.
+	description: String,
+	/// Suggested fix, if check has not passed.
+	suggestion: Option<String>,
+}
+
+/// Bridge diagnostics report.
+#[derive(Debug, Default)]
+struct Report {
+	/// All performed checks, in order.
+	checks: Vec<Check>,
+}
+
+impl Report {
+	/// Record passed check.
+	fn ok(&mut self, description: String) {
+		self.push(CheckStatus::Ok, description, None);
+	}
+
+	/// Record check that has found something that requires attention.
+	fn warning(&mut self, description: String, suggestion: String) {
+		self.push(CheckStatus::Warning, description, Some(suggestion));
+	}
+
+	/// Record failed check.
+	fn failure(&mut self, description: String, suggestion: String) {
+		self.push(CheckStatus::Failure, description, Some(suggestion));
+	}
+
+	/// Return number of failed checks.
+	fn failures(&self) -> usize {
+		self.count(CheckStatus::Failure)
+	}
+
+	/// Render report, optionally colouring status labels.
+	fn render(&self, coloured: bool) -> String {
+		let mut output = String::new();
+		for check in &self.checks {
+			let label = if coloured {
+				check.status.colour().bold().paint(check.status.label()).to_string()
+			} else {
+				check.status.label().to_string()
+			};
+			output.push_str(&format!("{} {}\n", label, check.description));
+			if let Some(ref suggestion) = check.suggestion {
+				output.push_str(&format!("       Suggestion: {}\n", suggestion));
+			}
+		}
+		output.push_str(&format!(
+			"\n{} checks: {} passed, {} warnings, {} failures\n",
+			self.checks.len(),
+			self.count(CheckStatus::Ok),
+			self.count(CheckStatus::Warning),
+			self.count(CheckStatus::Failure),
+		));
+		output
+	}
+
+	fn push(&mut self, status: CheckStatus, description: String, suggestion: Option<String>) {
+		self.checks.push(Check {
+			status,
+			description,
+			suggestion,
+		});
+	}
+
+	fn count(&self, status: CheckStatus) -> usize {
+		self.checks.iter().filter(|check| check.status == status).count()
+	}
+}
+
+/// Connect to the chain node and check that it is the node we're expecting.
+///
+/// Returns `None` if the node is not reachable.
+async fn check_connection<C: CliChain>(
+	report: &mut Report,
+	cli_prefix: &str,
+	params: ConnectionParams,
+	expected_genesis_hash: Option<C::Hash>,
+) -> Option<Client<C>> {
+	let endpoint = format!("{}:{}", params.host, params.port);
+	let client = match Client::<C>::try_connect(params).await {
+		Ok(client) => client,
+		Err(e) => {
+			report.failure(
+				format!("{} node at {} is not reachable: {}", C::NAME, endpoint, e),
+				format!(
+					"Check that the node is running and the --{0}-host, --{0}-port and --{0}-secure options are correct",
+					cli_prefix,
+				),
+			);
+			return None;
+		}
+	};
+	report.ok(format!("{} node at {} is reachable", C::NAME, endpoint));
+
+	match client
+		.ensure_correct_chain(C::ss58_format(), expected_genesis_hash)
+		.await
+	{
+		Ok(()) => report.ok(format!(
+			"{} node runs the expected chain with genesis hash {:?}",
+			C::NAME,
+			client.genesis_hash(),
+		)),
+		Err(e) => report.failure(
+			format!("{} node runs unexpected chain: {}", C::NAME, e),
+			format!(
+				"Connect to the {} node or fix the --{}-genesis-hash option",
+				C::NAME,
+				cli_prefix,
+			),
+		),
+	}
+
+	match client.runtime_version().await {
+		Ok(version) if version.spec_version == C::RUNTIME_VERSION.spec_version => report.ok(format!(
+			"{} node runs the runtime version {}",
+			C::NAME,
+			version.spec_version,
+		)),
+		Ok(version) => report.warning(
+			format!(
+				"{} node runs the runtime version {}, but the relay has been built for version {}",
+				C::NAME,
+				version.spec_version,
+				C::RUNTIME_VERSION.spec_version,
+			),
+			"Make sure that the relay is compatible with the upgraded runtime, or upgrade the relay".into(),
+		),
+		Err(e) => report.failure(
+			format!("Failed to read {} runtime version: {}", C::NAME, e),
+			format!("Check the {} node logs", C::NAME),
+		),
+	}
+
+	match client.ensure_synced().await {
+		Ok(()) => report.ok(format!("{} node is synced", C::NAME)),
+		Err(e) => report.warning(
+			format!("{} node is not ready: {}", C::NAME, e),
+			"Wait until the node is connected to its peers and synced".into(),
+		),
+	}
+
+	Some(client)
+}
+
+/// Check the GRANDPA pallet that is tracking source chain finality at the target chain.
+async fn check_grandpa_pallet<Source: Chain, Target: Chain>(
+	report: &mut Report,
+	target_client: &Client<Target>,
+	pallet_name: &str,
+	bridge: &FullBridge,
+) {
+	let pallet_prefix = sp_core::twox_128(pallet_name.as_bytes());
+	let pallet = format!("{} pallet at {}", pallet_name, Target::NAME);
+	let is_present = check_pallet_storage_version(
+		report,
+		target_client,
+		&pallet,
+		&pallet_prefix,
+		pallet_bridge_grandpa::migrations::STORAGE_VERSION,
+	)
+	.await;
+	if !is_present {
+		return;
+	}
+
+	match target_client
+		.storage_value::<Source::Hash>(storage_value_key(&pallet_prefix, "InitialHash"))
+		.await
+	{
+		Ok(Some(initial_hash)) => report.ok(format!(
+			"{} is initialized with {} header {:?}",
+			pallet,
+			Source::NAME,
+			initial_hash,
+		)),
+		Ok(None) => report.failure(
+			format!("{} is not initialized", pallet),
+			format!("Initialize the bridge using `substrate-relay init-bridge {:?}`", bridge),
+		),
+		Err(e) => report.failure(
+			format!("Failed to read the initial header of {}: {}", pallet, e),
+			format!("Check the {} node logs", Target::NAME),
+		),
+	}
+
+	check_pallet_is_operational(
+		report,
+		target_client,
+		&pallet,
+		storage_value_key(&pallet_prefix, "IsHalted"),
+	)
+	.await;
+}
+
+/// Check the messages pallet that is bridged with the `Peer` chain.
+async fn check_messages_pallet<C: Chain, Peer: Chain>(
+	report: &mut Report,
+	client: &Client<C>,
+	is_halted_key: StorageKey,
+) {
+	let pallet = format!("{} messages pallet at {}", Peer::NAME, C::NAME);
+	let is_present = check_pallet_storage_version(
+		report,
+		client,
+		&pallet,
+		&is_halted_key.0[..16],
+		pallet_bridge_messages::migrations::STORAGE_VERSION,
+	)
+	.await;
+	if !is_present {
+		return;
+	}
+
+	check_pallet_is_operational(report, client, &pallet, is_halted_key).await;
+}
+
+/// Check that the pallet is deployed and its storage version is supported by the relay.
+///
+/// Returns true if the pallet is deployed.
+async fn check_pallet_storage_version<C: Chain>(
+	report: &mut Report,
+	client: &Client<C>,
+	pallet: &str,
+	pallet_prefix: &[u8],
+	expected_version: StorageVersion,
+) -> bool {
+	match client
+		.storage_value::<StorageVersion>(storage_value_key(pallet_prefix, "PalletStorageVersion"))
+		.await
+	{
+		Ok(Some(version)) if version == expected_version => {
+			report.ok(format!("{} is deployed with storage version {}", pallet, version.0));
+			true
+		}
+		Ok(Some(version)) => {
+			report.warning(
+				format!(
+					"{} has storage version {}, but the relay expects version {}",
+					pallet, version.0, expected_version.0,
+				),
+				format!("Upgrade the relay or wait for the {} runtime upgrade", C::NAME),
+			);
+			true
+		}
+		Ok(None) => {
+			report.failure(
+				format!("{} is missing, or its storage has never been migrated", pallet),
+				format!(
+					"Check that the {} runtime includes the pallet and all its migrations are applied",
+					C::NAME,
+				),
+			);
+			false
+		}
+		Err(e) => {
+			report.failure(
+				format!("Failed to read storage version of {}: {}", pallet, e),
+				format!("Check the {} node logs", C::NAME),
+			);
+			false
+		}
+	}
+}
+
+/// Check that all pallet operations are not halted.
+async fn check_pallet_is_operational<C: Chain>(
+	report: &mut Report,
+	client: &Client<C>,
+	pallet: &str,
+	is_halted_key: StorageKey,
+) {
+	match client.storage_value::<bool>(is_halted_key).await {
+		Ok(Some(true)) => report.failure(
+			format!("{} is halted", pallet),
+			"Resume pallet operations by calling `set_operational(true)` from the pallet owner or root account".into(),
+		),
+		Ok(_) => report.ok(format!("{} is operational", pallet)),
+		Err(e) => report.failure(
+			format!("Failed to read halted flag of {}: {}", pallet, e),
+			format!("Check the {} node logs", C::NAME),
+		),
+	}
+}
+
+/// Check that the signer has funds to pay for relay transactions.
+async fn check_signer_balance<C: CliChain + ChainWithBalances>(
+	report: &mut Report,
+	client: &Client<C>,
+	cli_prefix: &str,
+	key_pair: anyhow::Result<C::KeyPair>,
+) where
+	C::AccountId: From<<C::KeyPair as Pair>::Public>,
+	C::NativeBalance: Into<u128>,
+{
+	let signer: C::AccountId = match key_pair {
+		Ok(key_pair) => key_pair.public().into(),
+		Err(e) => {
+			report.warning(
+				format!("{} signer balance has not been checked: {}", C::NAME, e),
+				format!("Specify the --{}-signer option to check the signer balance", cli_prefix),
+			);
+			return;
+		}
+	};
+
+	let suggestion = format!(
+		"Transfer some {} tokens to the {} signer account {}",
+		C::NAME,
+		C::NAME,
+		signer
+	);
+	match client.free_native_balance(signer.clone()).await {
+		Ok(balance) if balance.is_zero() => {
+			report.failure(format!("{} signer {} has no funds", C::NAME, signer), suggestion)
+		}
+		Ok(balance) => {
+			let balance = Balance::from_native(balance);
+			let balance = match client.token_properties().await {
+				Ok(token) => balance.format_tokens(&token),
+				Err(_) => balance.to_string(),
+			};
+			report.ok(format!("{} signer {} has {}", C::NAME, signer, balance))
+		}
+		Err(SubstrateError::AccountDoesNotExist) => {
+			report.failure(format!("{} signer {} does not exist", C::NAME, signer), suggestion)
+		}
+		Err(e) => report.failure(
+			format!("Failed to read balance of {} signer {}: {}", C::NAME, signer, e),
+			format!("Check the {} node logs", C::NAME),
+		),
+	}
+}
+
+/// Check that the target chain is aware of recently finalized source chain headers.
+async fn check_finality_lag<Source: Chain, Target: Chain>(
+	report: &mut Report,
+	source_client: &Client<Source>,
+	target_client: &Client<Target>,
+	best_finalized_source_header_id_at_target_method: &str,
+) where
+	Source::Header: sp_runtime::DeserializeOwned,
+{
+	let finalized_at_source = best_finalized_header_number(source_client).await;
+	let known_at_target = best_finalized_peer_header_number::<Target, Source>(
+		target_client,
+		best_finalized_source_header_id_at_target_method,
+	)
+	.await;
+	match (finalized_at_source, known_at_target) {
+		(Ok(finalized_at_source), Ok(known_at_target)) => {
+			let lag = finalized_at_source.saturating_sub(known_at_target);
+			let description = format!(
+				"{} knows finalized {} header #{}, best finalized {} header is #{}",
+				Target::NAME,
+				Source::NAME,
+				known_at_target,
+				Source::NAME,
+				finalized_at_source,
+			);
+			if lag > MAX_FINALITY_LAG_IN_BLOCKS.into() {
+				report.warning(
+					description,
+					format!(
+						"Make sure that the {} -> {} headers relay is running. Lag is expected if headers \
+						are relayed on demand and there are no messages to deliver",
+						Source::NAME,
+						Target::NAME,
+					),
+				);
+			} else {
+				report.ok(description);
+			}
+		}
+		(Err(e), _) => report.failure(
+			format!("Failed to read best finalized {} header: {}", Source::NAME, e),
+			format!("Check the {} node logs", Source::NAME),
+		),
+		(_, Err(e)) => report.failure(
+			format!(
+				"Failed to read best finalized {} header, known to {}: {}",
+				Source::NAME,
+				Target::NAME,
+				e
+			),
+			format!("Check the {} node logs", Target::NAME),
+		),
+	}
+}
+
+/// Check that the lane is not congested and not blocked by missing confirmations.
+async fn check_lane<P: SubstrateMessageLane>(
+	report: &mut Report,
+	source_client: &Client<P::SourceChain>,
+	target_client: &Client<P::TargetChain>,
+	lane: LaneId,
+) {
+	let lane_state = async {
+		let latest_generated_nonce =
+			read_lane_nonce(source_client, P::OUTBOUND_LANE_LATEST_GENERATED_NONCE_METHOD, lane).await?;
+		let latest_confirmed_nonce =
+			read_lane_nonce(source_client, P::OUTBOUND_LANE_LATEST_RECEIVED_NONCE_METHOD, lane).await?;
+		let latest_received_nonce =
+			read_lane_nonce(target_client, P::INBOUND_LANE_LATEST_RECEIVED_NONCE_METHOD, lane).await?;
+		let limits = read_delivery_limits::<P>(target_client).await?;
+		Ok::<_, SubstrateError>((
+			latest_generated_nonce,
+			latest_confirmed_nonce,
+			latest_received_nonce,
+			limits,
+		))
+	};
+
+	let source_name = P::SourceChain::NAME;
+	let target_name = P::TargetChain::NAME;
+	let (latest_generated_nonce, latest_confirmed_nonce, latest_received_nonce, limits) = match lane_state.await {
+		Ok(lane_state) => lane_state,
+		Err(e) => {
+			report.failure(
+				format!("Failed to read state of lane {}: {}", hex::encode(lane), e),
+				format!("Check the {} and {} node logs", source_name, target_name),
+			);
+			return;
+		}
+	};
+
+	let undelivered_messages = latest_generated_nonce.saturating_sub(latest_received_nonce);
+	let unconfirmed_messages = latest_received_nonce.saturating_sub(latest_confirmed_nonce);
+	let max_unconfirmed_messages = limits.max_unconfirmed_messages_at_inbound_lane;
+	let description = format!(
+		"Lane {}: {} messages are not delivered to {}, {} messages are not confirmed at {}",
+		hex::encode(lane),
+		undelivered_messages,
+		target_name,
+		unconfirmed_messages,
+		source_name,
+	);
+	if unconfirmed_messages >= max_unconfirmed_messages {
+		report.failure(
+			format!(
+				"{}. Delivery is blocked until confirmations are delivered (limit is {} messages)",
+				description, max_unconfirmed_messages,
+			),
+			format!(
+				"Make sure that the {} -> {} messages relay is running and delivers confirmations",
+				source_name, target_name,
+			),
+		);
+	} else if undelivered_messages >= max_unconfirmed_messages {
+		report.warning(
+			format!("{}. Lane is congested", description),
+			format!(
+				"Make sure that the {} -> {} messages relay is running",
+				source_name, target_name,
+			),
+		);
+	} else {
+		report.ok(description);
+	}
+}
+
+/// Read number of the best finalized header of the chain.
+async fn best_finalized_header_number<C: Chain>(client: &Client<C>) -> Result<C::BlockNumber, SubstrateError>
+where
+	C::Header: sp_runtime::DeserializeOwned,
+{
+	let best_finalized_header_hash = client.best_finalized_header_hash().await?;
+	Ok(*client.header_by_hash(best_finalized_header_hash).await?.number())
+}
+
+/// Read number of the best finalized `Peer` header, known to the chain.
+async fn best_finalized_peer_header_number<C: Chain, Peer: Chain>(
+	client: &Client<C>,
+	method: &str,
+) -> Result<Peer::BlockNumber, SubstrateError> {
+	let encoded_response = client.state_call(method.into(), Bytes(Vec::new()), None).await?;
+	let (number, _): (Peer::BlockNumber, Peer::Hash) =
+		Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
+	Ok(number)
+}
+
+/// Read message nonce of the lane using given runtime API method.
+pub(crate) async fn read_lane_nonce<C: Chain>(
+	client: &Client<C>,
+	method: &str,
+	lane: LaneId,
+) -> Result<MessageNonce, SubstrateError> {
+	let encoded_response = client.state_call(method.into(), Bytes(lane.encode()), None).await?;
+	MessageNonce::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)
+}
+
+/// Storage key of the value with given name, stored by the pallet with given prefix.
+fn storage_value_key(pallet_prefix: &[u8], value_name: &str) -> StorageKey {
+	let mut key = pallet_prefix.to_vec();
+	key.extend_from_slice(&sp_core::twox_128(value_name.as_bytes()));
+	StorageKey(key)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn test_report() -> Report {
+		let mut report = Report::default();
+		report.ok("Millau node is synced".into());
+		report.warning("Rialto node is not ready".into(), "Wait".into());
+		report.failure("Rialto signer has no funds".into(), "Transfer".into());
+		report
+	}
+
+	#[test]
+	fn report_counts_failed_checks() {
+		assert_eq!(test_report().failures(), 1);
+		assert_eq!(Report::default().failures(), 0);
+	}
+
+	#[test]
+	fn report_is_rendered_with_suggestions() {
+		assert_eq!(
+			test_report().render(false),
+			"[ OK ] Millau node is synced\n\
+			[WARN] Rialto node is not ready\n       Suggestion: Wait\n\
+			[FAIL] Rialto signer has no funds\n       Suggestion: Transfer\n\
+			\n3 checks: 1 passed, 1 warnings, 1 failures\n",
+		);
+	}
+}
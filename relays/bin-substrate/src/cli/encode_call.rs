@@ -15,8 +15,9 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::cli::bridge::FullBridge;
-use crate::cli::{AccountId, Balance, CliChain, ExplicitOrMaximal, HexBytes, HexLaneId};
+use crate::cli::{AccountId, Balance, CliChain, ExplicitOrMaximal, HexBytes};
 use crate::select_full_bridge;
+use bp_messages::LaneId;
 use frame_support::dispatch::GetDispatchInfo;
 use relay_substrate_client::Chain;
 use structopt::StructOpt;
@@ -65,9 +66,9 @@ pub enum Call {
 		/// An index of the bridge instance which represents the expected target chain.
 		#[structopt(skip = 255)]
 		bridge_instance_index: u8,
-		/// Hex-encoded lane id that should be served by the relay. Defaults to `00000000`.
+		/// Lane id (hex-encoded or well-known lane name) that should be served by the relay. Defaults to `00000000`.
 		#[structopt(long, default_value = "00000000")]
-		lane: HexLaneId,
+		lane: LaneId,
 		/// Raw SCALE-encoded Message Payload to submit to the messages pallet.
 		///
 		/// This can be obtained by encoding call for the target chain.
@@ -14,8 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::cli::{bridge::FullBridge, AccountId, CliChain, HexBytes};
+use crate::cli::{bridge::FullBridge, AccountId, CliChain, DispatchFeePayment, ExplicitOrMaximal, HexBytes};
 use crate::select_full_bridge;
+use frame_support::weights::Weight;
 use structopt::StructOpt;
 
 /// Generic message payload.
@@ -34,6 +35,21 @@ pub enum MessagePayload {
 		/// SS58 encoded Source account that will send the payload.
 		#[structopt(long)]
 		sender: AccountId,
+		/// Dispatch weight of the message. If not passed, determined automatically.
+		#[structopt(long)]
+		dispatch_weight: Option<ExplicitOrMaximal<Weight>>,
+		/// Number of the target chain block, before which the message must be delivered. Messages that
+		/// are delivered later are not dispatched. If not passed, the message never expires.
+		#[structopt(long)]
+		delivery_deadline: Option<u64>,
+		/// Where the dispatch fee is paid.
+		#[structopt(
+			long,
+			possible_values = &DispatchFeePayment::variants(),
+			case_insensitive = true,
+			default_value = "AtSourceChain"
+		)]
+		dispatch_fee_payment: DispatchFeePayment,
 	},
 }
 
@@ -101,6 +117,6 @@ mod tests {
 		let hex = encode_message.encode().unwrap();
 
 		// then
-		assert_eq!(format!("{:?}", hex), "0x01000000e88514000000000002d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d3c040130000000000000000000000000");
+		assert_eq!(format!("{:?}", hex), "0x01000000e885140000000000000002d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d3c040130000000000000000000000000");
 	}
 }
@@ -15,10 +15,16 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::cli::bridge::FullBridge;
-use crate::cli::{Balance, CliChain, HexBytes, HexLaneId, SourceConnectionParams};
+use crate::cli::{Balance, CliChain, HexBytes, SourceConnectionParams};
 use crate::select_full_bridge;
+use bp_messages::LaneId;
+use bridge_runtime_common::messages::{
+	BridgedChainWithMessages, ChainWithMessages, MessageBridge, MessageTransaction, ThisChainWithMessages,
+};
 use codec::{Decode, Encode};
+use frame_support::weights::Weight;
 use relay_substrate_client::{Chain, ChainWithBalances};
+use sp_runtime::{FixedPointNumber, FixedU128};
 use structopt::StructOpt;
 
 /// Estimate Delivery & Dispatch Fee command.
@@ -29,9 +35,13 @@ pub struct EstimateFee {
 	bridge: FullBridge,
 	#[structopt(flatten)]
 	source: SourceConnectionParams,
-	/// Hex-encoded id of lane that will be delivering the message.
+	/// Id (hex-encoded or well-known lane name) of lane that will be delivering the message.
 	#[structopt(long, default_value = "00000000")]
-	lane: HexLaneId,
+	lane: LaneId,
+	/// Extra fee (in percents of the estimated fee) that is suggested to cover conversion rate
+	/// and transaction fee changes between estimation and delivery.
+	#[structopt(long, default_value = "10")]
+	safety_margin: u32,
 	/// Payload to send over the bridge.
 	#[structopt(flatten)]
 	payload: crate::cli::encode_message::MessagePayload,
@@ -44,29 +54,160 @@ impl EstimateFee {
 			source,
 			bridge,
 			lane,
+			safety_margin,
 			payload,
 		} = self;
 
 		select_full_bridge!(bridge, {
 			let source_client = source.to_client::<Source>().await?;
-			let lane = lane.into();
 			let payload = Source::encode_message(payload).map_err(|e| anyhow::format_err!("{:?}", e))?;
+			let conversion_rate = source_client
+				.storage_value(conversion_rate_key())
+				.await?
+				.unwrap_or(INITIAL_CONVERSION_RATE);
+			// the dispatch fee is not paid at the source chain if the message pays it at the target chain
+			let paid_dispatch_weight = match payload.dispatch_fee_payment {
+				bp_message_dispatch::DispatchFeePayment::AtSourceChain => payload.weight,
+				bp_message_dispatch::DispatchFeePayment::AtTargetChain => 0,
+			};
+			let breakdown = estimate_fee_breakdown::<MessageBridge>(
+				&payload.call,
+				paid_dispatch_weight,
+				conversion_rate,
+				safety_margin,
+			);
 
 			let fee: <Source as ChainWithBalances>::NativeBalance =
 				estimate_message_delivery_and_dispatch_fee(&source_client, ESTIMATE_MESSAGE_FEE_METHOD, lane, payload)
 					.await?;
 
-			log::info!(target: "bridge", "Fee: {:?}", Balance(fee as _));
+			let source_token = source_client.token_properties().await?;
+			let format = |fee: u128| Balance(fee).format_tokens(&source_token);
+			log::info!(
+				target: "bridge",
+				"Fee breakdown ({} -> {} conversion rate: {:?}):\n\
+				\tdelivery transaction base fee: {}\n\
+				\tdelivery transaction per-byte fee: {}\n\
+				\t{} dispatch fee: {}\n\
+				\tdelivery confirmation transaction fee: {}\n\
+				\trelayer interest: {}\n\
+				\tsafety margin ({}%): {}",
+				Target::NAME,
+				Source::NAME,
+				conversion_rate,
+				format(breakdown.delivery_base_fee),
+				format(breakdown.delivery_per_byte_fee),
+				Target::NAME,
+				format(breakdown.target_dispatch_fee),
+				format(breakdown.confirmation_fee),
+				format(breakdown.relayer_interest),
+				safety_margin,
+				format(breakdown.safety_margin),
+			);
+			log::info!(
+				target: "bridge",
+				"Fee: {}. Suggested fee (with safety margin): {}",
+				Balance::from_native(fee).format_tokens(&source_token),
+				format(breakdown.suggested_fee()),
+			);
 			println!("{}", fee);
 			Ok(())
 		})
 	}
 }
 
+/// Message delivery and dispatch fee, split into components. All values are in source chain tokens.
+#[derive(Debug, PartialEq)]
+pub(crate) struct FeeBreakdown {
+	/// Base fee of the delivery transaction at the target chain.
+	pub delivery_base_fee: u128,
+	/// Fee for the size of the delivery transaction (the message and its proof) at the target chain.
+	pub delivery_per_byte_fee: u128,
+	/// Fee for the weight of the delivery transaction (the message dispatch included) at the target chain.
+	pub target_dispatch_fee: u128,
+	/// Fee of the delivery confirmation transaction at the source chain.
+	pub confirmation_fee: u128,
+	/// Extra fee that is paid to the relayer.
+	pub relayer_interest: u128,
+	/// Extra fee that is suggested to cover fees changes between estimation and delivery.
+	pub safety_margin: u128,
+}
+
+impl FeeBreakdown {
+	/// Returns minimal fee that is accepted by the source chain runtime.
+	pub fn minimal_fee(&self) -> u128 {
+		self.delivery_base_fee
+			.saturating_add(self.delivery_per_byte_fee)
+			.saturating_add(self.target_dispatch_fee)
+			.saturating_add(self.confirmation_fee)
+			.saturating_add(self.relayer_interest)
+	}
+
+	/// Returns fee that is suggested to pay for the message.
+	pub fn suggested_fee(&self) -> u128 {
+		self.minimal_fee().saturating_add(self.safety_margin)
+	}
+}
+
+/// Split the message delivery and dispatch fee into components, mirroring the computations of the source
+/// chain runtime.
+///
+/// Target chain fees are converted into source chain tokens using the given `conversion_rate`.
+pub(crate) fn estimate_fee_breakdown<B: MessageBridge>(
+	message_call: &[u8],
+	message_dispatch_weight: Weight,
+	conversion_rate: FixedU128,
+	safety_margin_percent: u32,
+) -> FeeBreakdown
+where
+	<B::ThisChain as ChainWithMessages>::Balance: Into<u128>,
+	<B::BridgedChain as ChainWithMessages>::Balance: Into<u128>,
+{
+	let MessageTransaction { dispatch_weight, size } =
+		B::BridgedChain::estimate_delivery_transaction(message_call, message_dispatch_weight.into());
+	let delivery_fee = |transaction| -> u128 {
+		conversion_rate.saturating_mul_int(B::BridgedChain::transaction_payment(transaction).into())
+	};
+
+	let no_weight: Weight = 0;
+	let delivery_base_fee = delivery_fee(MessageTransaction {
+		dispatch_weight: no_weight.into(),
+		size: 0,
+	});
+	let delivery_fee_without_dispatch = delivery_fee(MessageTransaction {
+		dispatch_weight: no_weight.into(),
+		size,
+	});
+	let delivery_fee_with_dispatch = delivery_fee(MessageTransaction { dispatch_weight, size });
+	let confirmation_fee =
+		B::ThisChain::transaction_payment(B::ThisChain::estimate_delivery_confirmation_transaction()).into();
+
+	let fee_without_interest = delivery_fee_with_dispatch.saturating_add(confirmation_fee);
+	let relayer_interest = percents(fee_without_interest, B::RELAYER_FEE_PERCENT);
+	let safety_margin = percents(
+		fee_without_interest.saturating_add(relayer_interest),
+		safety_margin_percent,
+	);
+
+	FeeBreakdown {
+		delivery_base_fee,
+		delivery_per_byte_fee: delivery_fee_without_dispatch.saturating_sub(delivery_base_fee),
+		target_dispatch_fee: delivery_fee_with_dispatch.saturating_sub(delivery_fee_without_dispatch),
+		confirmation_fee,
+		relayer_interest,
+		safety_margin,
+	}
+}
+
+/// Returns given percents of the value.
+fn percents(value: u128, percents: u32) -> u128 {
+	value.saturating_mul(percents.into()) / 100
+}
+
 pub(crate) async fn estimate_message_delivery_and_dispatch_fee<Fee: Decode, C: Chain, P: Encode>(
 	client: &relay_substrate_client::Client<C>,
 	estimate_fee_method: &str,
-	lane: bp_messages::LaneId,
+	lane: LaneId,
 	payload: P,
 ) -> anyhow::Result<Fee> {
 	let encoded_response = client
@@ -109,20 +250,105 @@ mod tests {
 			res,
 			EstimateFee {
 				bridge: FullBridge::RialtoToMillau,
-				lane: HexLaneId([0, 0, 0, 0]),
+				lane: LaneId([0, 0, 0, 0]),
+				safety_margin: 10,
 				source: SourceConnectionParams {
 					source_host: "127.0.0.1".into(),
 					source_port: 1234,
 					source_secure: false,
+					source_genesis_hash: None,
+					source_failover_endpoint: Vec::new(),
+					source_recovery_policy: Default::default(),
+					source_proxy: None,
+					source_tls_ca_bundle: None,
+					source_tls_client_certificate: None,
+					source_tls_client_key: None,
 				},
 				payload: crate::cli::encode_message::MessagePayload::Call {
 					sender: alice.parse().unwrap(),
 					call: encode_call::Call::Remark {
 						remark_payload: Some(HexBytes(vec![0x12, 0x34])),
 						remark_size: None,
-					}
+					},
+					dispatch_weight: None,
+					delivery_deadline: None,
+					dispatch_fee_payment: crate::cli::DispatchFeePayment::AtSourceChain,
 				}
 			}
 		);
 	}
+
+	#[test]
+	fn should_parse_message_dispatch_options() {
+		// given
+		let alice = sp_keyring::AccountKeyring::Alice.to_account_id().to_ss58check();
+
+		// when
+		let res = EstimateFee::from_iter(vec![
+			"estimate_fee",
+			"MillauToRialto",
+			"--safety-margin",
+			"25",
+			"call",
+			"--sender",
+			&alice,
+			"--dispatch-weight",
+			"max",
+			"--delivery-deadline",
+			"100",
+			"--dispatch-fee-payment",
+			"AtTargetChain",
+			"remark",
+		]);
+
+		// then
+		assert_eq!(res.safety_margin, 25);
+		assert_eq!(
+			res.payload,
+			crate::cli::encode_message::MessagePayload::Call {
+				sender: alice.parse().unwrap(),
+				call: encode_call::Call::Remark {
+					remark_payload: None,
+					remark_size: None,
+				},
+				dispatch_weight: Some(crate::cli::ExplicitOrMaximal::Maximal),
+				delivery_deadline: Some(100),
+				dispatch_fee_payment: crate::cli::DispatchFeePayment::AtTargetChain,
+			}
+		);
+	}
+
+	#[test]
+	fn fee_breakdown_is_computed_properly() {
+		type Bridge = millau_runtime::rialto_messages::WithRialtoMessageBridge;
+
+		let call = vec![42u8; 128];
+		let breakdown = estimate_fee_breakdown::<Bridge>(&call, 1_000_000, FixedU128::from(1), 10);
+		let breakdown_with_double_rate = estimate_fee_breakdown::<Bridge>(&call, 1_000_000, FixedU128::from(2), 10);
+
+		// target chain fees are converted using the conversion rate
+		assert_eq!(
+			breakdown_with_double_rate.delivery_base_fee,
+			breakdown.delivery_base_fee * 2
+		);
+		assert_eq!(
+			breakdown_with_double_rate.delivery_per_byte_fee,
+			breakdown.delivery_per_byte_fee * 2,
+		);
+		assert_eq!(
+			breakdown_with_double_rate.target_dispatch_fee,
+			breakdown.target_dispatch_fee * 2
+		);
+		// source chain fees are not converted
+		assert_eq!(breakdown_with_double_rate.confirmation_fee, breakdown.confirmation_fee);
+
+		// relayer interest and safety margin are computed from the minimal fee
+		let fee_without_interest = breakdown.minimal_fee() - breakdown.relayer_interest;
+		assert_eq!(breakdown.relayer_interest, fee_without_interest / 10);
+		assert_eq!(breakdown.safety_margin, breakdown.minimal_fee() / 10);
+		assert_eq!(
+			breakdown.suggested_fee(),
+			breakdown.minimal_fee() + breakdown.safety_margin
+		);
+	}
 }
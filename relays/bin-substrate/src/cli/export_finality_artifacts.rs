@@ -0,0 +1,243 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::cli::{HexBytes, SourceConnectionParams};
+use beefy_primitives::{crypto::AuthorityId as BeefyId, MmrRootHash, SignedCommitment, ValidatorSet};
+use bp_header_chain::solidity::{
+	BeefyCommitment, BeefyFinalityArtifacts, BeefySignature, BeefyValidatorSetCommitment, GrandpaFinalityArtifacts,
+	HeaderCommitment, MmrLeafProof,
+};
+use codec::{Decode, Encode};
+use pallet_mmr_primitives::{EncodableOpaqueLeaf, Proof as MmrProof};
+use relay_substrate_client::{BlockNumberOf, Chain, Client};
+use serde::de::DeserializeOwned;
+use sp_core::H256;
+use sp_runtime::traits::{Convert, Header as HeaderT};
+use std::{convert::TryInto, path::PathBuf};
+use structopt::{clap::arg_enum, StructOpt};
+
+/// Export finality artifacts of the source chain in the format of the Solidity light client.
+#[derive(StructOpt)]
+pub struct ExportFinalityArtifacts {
+	/// A source chain to export finality artifacts of.
+	#[structopt(possible_values = &ExportFinalityArtifactsSource::variants(), case_insensitive = true)]
+	source_chain: ExportFinalityArtifactsSource,
+	/// Finality gadget, which artifacts are exported.
+	#[structopt(
+		long,
+		possible_values = &FinalityGadget::variants(),
+		case_insensitive = true,
+		default_value = "Grandpa",
+	)]
+	finality: FinalityGadget,
+	#[structopt(flatten)]
+	source: SourceConnectionParams,
+	/// Write raw encoded artifacts to this file instead of printing them as hex to the stdout.
+	#[structopt(long)]
+	output: Option<PathBuf>,
+}
+
+// TODO [#851] Use kebab-case.
+arg_enum! {
+	#[derive(Debug)]
+	/// Chain to export finality artifacts of.
+	pub enum ExportFinalityArtifactsSource {
+		Millau,
+		Rialto,
+		Westend,
+		Rococo,
+		Wococo,
+	}
+}
+
+arg_enum! {
+	#[derive(Debug, Clone, Copy)]
+	/// Finality gadget, which artifacts are exported.
+	///
+	/// - `Grandpa` exports GRANDPA justification and authority sets.
+	/// - `Beefy` exports BEEFY signed commitment, validator set and MMR leaf proof. Source chain
+	///   must have BEEFY and MMR pallets deployed since genesis.
+	pub enum FinalityGadget {
+		Grandpa,
+		Beefy,
+	}
+}
+
+macro_rules! select_source {
+	($source: expr, $generic: tt) => {
+		match $source {
+			ExportFinalityArtifactsSource::Millau => {
+				type Source = relay_millau_client::Millau;
+				$generic
+			}
+			ExportFinalityArtifactsSource::Rialto => {
+				type Source = relay_rialto_client::Rialto;
+				$generic
+			}
+			ExportFinalityArtifactsSource::Westend => {
+				type Source = relay_westend_client::Westend;
+				$generic
+			}
+			ExportFinalityArtifactsSource::Rococo => {
+				type Source = relay_rococo_client::Rococo;
+				$generic
+			}
+			ExportFinalityArtifactsSource::Wococo => {
+				type Source = relay_wococo_client::Wococo;
+				$generic
+			}
+		}
+	};
+}
+
+impl ExportFinalityArtifacts {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		select_source!(self.source_chain, {
+			let source_client = self.source.to_client::<Source>().await?;
+			let encoded_artifacts = match self.finality {
+				FinalityGadget::Grandpa => {
+					let finality_proof_data = crate::headers_initialize::prepare_finality_proof_data(source_client)
+						.await
+						.map_err(anyhow::Error::msg)?;
+					let artifacts = GrandpaFinalityArtifacts::new(
+						&finality_proof_data.header,
+						&finality_proof_data.justification,
+						finality_proof_data.set_id,
+						&finality_proof_data.authorities,
+						finality_proof_data.next_authorities.as_ref(),
+					);
+					log::info!(
+						target: "bridge",
+						"Prepared {} GRANDPA finality artifacts: {:?}",
+						Source::NAME,
+						artifacts,
+					);
+					artifacts.encode_packed()
+				}
+				FinalityGadget::Beefy => {
+					let artifacts = prepare_beefy_artifacts(&source_client).await?;
+					log::info!(
+						target: "bridge",
+						"Prepared {} BEEFY finality artifacts: {:?}",
+						Source::NAME,
+						artifacts,
+					);
+					artifacts.encode_packed()
+				}
+			};
+			match self.output {
+				Some(output) => std::fs::write(output, encoded_artifacts)?,
+				None => println!("{}", HexBytes(encoded_artifacts)),
+			}
+
+			Ok(())
+		})
+	}
+}
+
+/// Wait for the next BEEFY signed commitment and prepare finality artifacts for it.
+///
+/// The MMR root at the commitment block `N` includes the leaf with index `N - 1`, which commits
+/// to the parent (`N - 1`) header. So the artifacts are proving finality of the `N - 1` header.
+async fn prepare_beefy_artifacts<C: Chain>(client: &Client<C>) -> anyhow::Result<BeefyFinalityArtifacts>
+where
+	C::Header: DeserializeOwned + HeaderT<Hash = H256>,
+	BlockNumberOf<C>: Into<u64>,
+{
+	let mut justifications = client
+		.subscribe_beefy_justifications()
+		.await
+		.map_err(|e| anyhow::format_err!("Failed to subscribe to {} BEEFY justifications: {:?}", C::NAME, e))?;
+	let encoded_signed_commitment = justifications
+		.next()
+		.await
+		.ok_or_else(|| anyhow::format_err!("{} BEEFY justifications subscription has ended", C::NAME))?;
+	let signed_commitment =
+		SignedCommitment::<BlockNumberOf<C>, MmrRootHash>::decode(&mut &encoded_signed_commitment.0[..])
+			.map_err(|e| anyhow::format_err!("Failed to decode {} BEEFY signed commitment: {:?}", C::NAME, e))?;
+	let commitment = signed_commitment.commitment;
+	let block_number: u64 = commitment.block_number.into();
+	let leaf_index = block_number
+		.checked_sub(1)
+		.ok_or_else(|| anyhow::format_err!("{} BEEFY commitment to the genesis block can't be exported", C::NAME))?;
+
+	let commitment_block_hash = client.block_hash_by_number(commitment.block_number).await?;
+	let validator_set =
+		ValidatorSet::<BeefyId>::decode(&mut &client.beefy_validator_set(commitment_block_hash).await?[..])
+			.map_err(|e| anyhow::format_err!("Failed to decode {} BEEFY validator set: {:?}", C::NAME, e))?;
+	if validator_set.id != commitment.validator_set_id {
+		return Err(anyhow::format_err!(
+			"{} BEEFY commitment is signed by validator set {}, but set {} is active at block {}",
+			C::NAME,
+			commitment.validator_set_id,
+			validator_set.id,
+			block_number,
+		));
+	}
+
+	let leaf_proof = client.mmr_generate_proof(leaf_index, commitment_block_hash).await?;
+	let leaf = EncodableOpaqueLeaf::decode(&mut &leaf_proof.leaf.0[..])
+		.map_err(|e| anyhow::format_err!("Failed to decode {} MMR leaf: {:?}", C::NAME, e))?;
+	let proof = MmrProof::<H256>::decode(&mut &leaf_proof.proof.0[..])
+		.map_err(|e| anyhow::format_err!("Failed to decode {} MMR leaf proof: {:?}", C::NAME, e))?;
+	let commitment_header = client.header_by_hash(commitment_block_hash).await?;
+	let leaf_header = client.header_by_hash(*commitment_header.parent_hash()).await?;
+
+	Ok(BeefyFinalityArtifacts {
+		encoded_commitment: commitment.encode(),
+		commitment: BeefyCommitment {
+			payload: commitment.payload,
+			block_number,
+			validator_set_id: commitment.validator_set_id,
+		},
+		validator_set: BeefyValidatorSetCommitment {
+			id: validator_set.id,
+			validators: validator_set
+				.validators
+				.into_iter()
+				.map(|validator| {
+					pallet_beefy_mmr::BeefyEcdsaToEthereum::convert(validator)
+						.try_into()
+						.map_err(|_| anyhow::format_err!("Failed to convert {} BEEFY validator to address", C::NAME))
+				})
+				.collect::<anyhow::Result<_>>()?,
+		},
+		signatures: signed_commitment
+			.signatures
+			.into_iter()
+			.enumerate()
+			.filter_map(|(validator_index, signature)| {
+				signature.map(|signature| {
+					let raw_signature: &[u8] = signature.as_ref();
+					let mut signature = [0u8; 65];
+					signature.copy_from_slice(raw_signature);
+					BeefySignature {
+						validator_index: validator_index as u32,
+						signature,
+					}
+				})
+			})
+			.collect(),
+		header: HeaderCommitment::new(&leaf_header),
+		leaf_proof: MmrLeafProof {
+			leaf: leaf.0,
+			leaf_index: proof.leaf_index,
+			leaf_count: proof.leaf_count,
+			items: proof.items,
+		},
+	})
+}
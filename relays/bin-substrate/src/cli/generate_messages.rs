@@ -0,0 +1,372 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generate synthetic messages load over the bridge lane.
+
+use crate::cli::bridge::FullBridge;
+use crate::cli::doctor::read_lane_nonce;
+use crate::cli::encode_call::{self, CliEncodeCall};
+use crate::cli::estimate_fee::estimate_message_delivery_and_dispatch_fee;
+use crate::cli::send_message::{
+	compute_maximal_message_dispatch_weight, message_payload, prepare_call_dispatch_weight,
+};
+use crate::cli::{
+	Balance, CliChain, DispatchFeePayment, ExplicitOrMaximal, HexBytes, SourceConnectionParams, SourceSigningParams,
+	TargetConnectionParams,
+};
+use crate::messages_lane::SubstrateMessageLane;
+use crate::select_full_bridge;
+
+use bp_message_dispatch::CallOrigin;
+use bp_messages::{LaneId, MessageNonce};
+use codec::Encode;
+use frame_support::{dispatch::GetDispatchInfo, weights::Weight};
+use relay_substrate_client::{Chain, TransactionSignScheme};
+use sp_core::{Bytes, Pair};
+use sp_runtime::{traits::IdentifyAccount, MultiSigner};
+use std::{
+	collections::VecDeque,
+	time::{Duration, Instant},
+};
+use structopt::StructOpt;
+
+/// Continuously send test messages over the lane and measure their delivery latency.
+#[derive(StructOpt)]
+pub struct GenerateMessages {
+	/// A bridge instance to generate messages for.
+	#[structopt(possible_values = &FullBridge::variants(), case_insensitive = true)]
+	bridge: FullBridge,
+	#[structopt(flatten)]
+	source: SourceConnectionParams,
+	#[structopt(flatten)]
+	source_sign: SourceSigningParams,
+	#[structopt(flatten)]
+	target: TargetConnectionParams,
+	/// Hex-encoded id of the lane to send messages over. Defaults to `00000000`.
+	///
+	/// Latency is only measured correctly if no one else is sending messages over this lane.
+	#[structopt(long, default_value = "00000000")]
+	lane: LaneId,
+	/// Number of messages sent every minute.
+	#[structopt(long, default_value = "1")]
+	rate: u32,
+	/// Total number of messages to send. The command exits once all messages are delivered.
+	/// If not passed, messages are sent until the command is stopped.
+	#[structopt(long)]
+	messages: Option<u64>,
+	/// Size of the remark, dispatched at the target chain. If not passed, small UTF8-encoded
+	/// string is generated by relay as remark.
+	#[structopt(long)]
+	remark_size: Option<ExplicitOrMaximal<usize>>,
+	/// Declared dispatch weight of every message. If not passed, determined automatically.
+	#[structopt(long)]
+	dispatch_weight: Option<ExplicitOrMaximal<Weight>>,
+	/// Delivery and dispatch fee of every message in source chain base currency units. If not passed,
+	/// determined automatically.
+	#[structopt(long)]
+	fee: Option<Balance>,
+	/// Where the dispatch fee is paid. Defaults to `AtSourceChain`.
+	#[structopt(
+		long,
+		possible_values = &DispatchFeePayment::variants(),
+		case_insensitive = true,
+		default_value = "AtSourceChain"
+	)]
+	dispatch_fee_payment: DispatchFeePayment,
+}
+
+impl GenerateMessages {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		if self.rate == 0 {
+			return Err(anyhow::format_err!("--rate must be positive"));
+		}
+		let send_interval = Duration::from_secs(60) / self.rate;
+
+		select_full_bridge!(self.bridge, {
+			let source_client = self.source.to_client::<Source>().await?;
+			let source_sign = self.source_sign.to_keypair::<Source>()?;
+			let source_transactions_mortality = self.source_sign.transactions_mortality()?;
+			let target_client = self.target.to_client::<Target>().await?;
+
+			let mut call = encode_call::Call::Remark {
+				remark_payload: None,
+				remark_size: self.remark_size.clone(),
+			};
+			encode_call::preprocess_call::<Source, Target>(&mut call, self.bridge.bridge_instance_index());
+			let target_call = Target::encode_call(&call)?;
+			let source_sender_public: MultiSigner = source_sign.public().into();
+			let payload = message_payload(
+				Target::RUNTIME_VERSION.spec_version,
+				prepare_call_dispatch_weight(
+					&self.dispatch_weight,
+					ExplicitOrMaximal::Explicit(target_call.get_dispatch_info().weight),
+					compute_maximal_message_dispatch_weight(Target::max_extrinsic_weight()),
+				),
+				None,
+				self.dispatch_fee_payment.into(),
+				CallOrigin::SourceAccount(source_sender_public.into_account()),
+				&target_call,
+			);
+			let fee = match self.fee {
+				Some(fee) => fee,
+				None => Balance::from_native(
+					estimate_message_delivery_and_dispatch_fee::<
+						<Source as relay_substrate_client::ChainWithBalances>::NativeBalance,
+						_,
+						_,
+					>(&source_client, ESTIMATE_MESSAGE_FEE_METHOD, self.lane, payload.clone())
+					.await?,
+				),
+			};
+
+			// we're assuming that all messages, sent over the lane, are generated by this command
+			let latest_generated_nonce = read_lane_nonce(
+				&source_client,
+				MessagesLane::OUTBOUND_LANE_LATEST_GENERATED_NONCE_METHOD,
+				self.lane,
+			)
+			.await?;
+			let mut latency = MessagesLatency::new(latest_generated_nonce + 1);
+			let mut next_send_time = Instant::now();
+			let mut next_poll_time = Instant::now();
+			loop {
+				let send_more = self.messages.map(|messages| latency.sent() < messages).unwrap_or(true);
+				if send_more && Instant::now() >= next_send_time {
+					let send_message_call = Source::encode_call(&encode_call::Call::BridgeSendMessage {
+						bridge_instance_index: self.bridge.bridge_instance_index(),
+						lane: self.lane,
+						payload: HexBytes::encode(&payload),
+						fee,
+					})?;
+					let submit_result = source_client
+						.submit_signed_extrinsic(source_sign.public().into(), |best_block_id, transaction_nonce| {
+							Bytes(
+								Source::sign_transaction(
+									*source_client.genesis_hash(),
+									&source_sign,
+									relay_substrate_client::TransactionEra::new(
+										best_block_id.0,
+										best_block_id.1,
+										source_transactions_mortality,
+									),
+									transaction_nonce,
+									0,
+									send_message_call,
+								)
+								.encode(),
+							)
+						})
+						.await;
+					match submit_result {
+						Ok(_) => {
+							let nonce = latency.message_sent(Instant::now());
+							log::info!(target: "bridge", "Sent message {} to {}", nonce, Target::NAME);
+						}
+						Err(error) => log::warn!(
+							target: "bridge",
+							"Failed to send message to {}: {:?}",
+							Target::NAME,
+							error,
+						),
+					}
+					next_send_time += send_interval;
+				}
+
+				if Instant::now() >= next_poll_time {
+					match read_lane_nonce(
+						&target_client,
+						MessagesLane::INBOUND_LANE_LATEST_RECEIVED_NONCE_METHOD,
+						self.lane,
+					)
+					.await
+					{
+						Ok(latest_received_nonce) => {
+							for (nonce, message_latency) in
+								latency.messages_delivered(latest_received_nonce, Instant::now())
+							{
+								log::info!(
+									target: "bridge",
+									"Message {} has been delivered to {} and dispatched in {}s. Average latency: {}s",
+									nonce,
+									Target::NAME,
+									message_latency.as_secs_f64(),
+									latency.average().unwrap_or_default().as_secs_f64(),
+								);
+							}
+						}
+						Err(error) => log::warn!(
+							target: "bridge",
+							"Failed to read latest received nonce from {}: {:?}",
+							Target::NAME,
+							error,
+						),
+					}
+					next_poll_time = Instant::now() + Target::AVERAGE_BLOCK_INTERVAL;
+				}
+
+				if !send_more && !latency.has_pending() {
+					break;
+				}
+
+				let next_event_time = if send_more {
+					std::cmp::min(next_send_time, next_poll_time)
+				} else {
+					next_poll_time
+				};
+				async_std::task::sleep(next_event_time.saturating_duration_since(Instant::now())).await;
+			}
+
+			log::info!(
+				target: "bridge",
+				"All {} messages have been delivered to {}. Latency min: {}s, average: {}s, max: {}s",
+				latency.delivered,
+				Target::NAME,
+				latency.min.unwrap_or_default().as_secs_f64(),
+				latency.average().unwrap_or_default().as_secs_f64(),
+				latency.max.as_secs_f64(),
+			);
+
+			Ok(())
+		})
+	}
+}
+
+/// Delivery latency of generated messages.
+///
+/// Messages are dispatched at the target chain during delivery, so the latency is the time between
+/// message submission and the moment when the target chain has received it.
+#[derive(Debug)]
+struct MessagesLatency {
+	/// Nonce that is assigned to the next sent message.
+	next_nonce: MessageNonce,
+	/// Nonces and submission times of messages that are not yet delivered.
+	pending: VecDeque<(MessageNonce, Instant)>,
+	/// Number of delivered messages.
+	delivered: u64,
+	/// Total latency of all delivered messages.
+	total: Duration,
+	/// Minimal latency of delivered messages.
+	min: Option<Duration>,
+	/// Maximal latency of delivered messages.
+	max: Duration,
+}
+
+impl MessagesLatency {
+	/// Create new latency tracker, assuming that the next message gets given nonce.
+	fn new(next_nonce: MessageNonce) -> Self {
+		MessagesLatency {
+			next_nonce,
+			pending: VecDeque::new(),
+			delivered: 0,
+			total: Duration::default(),
+			min: None,
+			max: Duration::default(),
+		}
+	}
+
+	/// Returns total number of sent messages.
+	fn sent(&self) -> u64 {
+		self.delivered + self.pending.len() as u64
+	}
+
+	/// Returns true if some sent messages are not yet delivered.
+	fn has_pending(&self) -> bool {
+		!self.pending.is_empty()
+	}
+
+	/// Remember that the message has been sent. Returns nonce of the sent message.
+	fn message_sent(&mut self, at: Instant) -> MessageNonce {
+		let nonce = self.next_nonce;
+		self.next_nonce += 1;
+		self.pending.push_back((nonce, at));
+		nonce
+	}
+
+	/// Remember that all messages up to given nonce have been delivered. Returns nonces and
+	/// latencies of newly delivered messages.
+	fn messages_delivered(
+		&mut self,
+		latest_received_nonce: MessageNonce,
+		at: Instant,
+	) -> Vec<(MessageNonce, Duration)> {
+		let mut delivered = Vec::new();
+		while let Some((nonce, sent_at)) = self.pending.front().cloned() {
+			if nonce > latest_received_nonce {
+				break;
+			}
+
+			let latency = at.saturating_duration_since(sent_at);
+			self.pending.pop_front();
+			self.delivered += 1;
+			self.total += latency;
+			self.min = Some(self.min.map(|min| std::cmp::min(min, latency)).unwrap_or(latency));
+			self.max = std::cmp::max(self.max, latency);
+			delivered.push((nonce, latency));
+		}
+		delivered
+	}
+
+	/// Returns average latency of delivered messages.
+	fn average(&self) -> Option<Duration> {
+		if self.delivered == 0 {
+			return None;
+		}
+
+		Some(self.total / self.delivered as u32)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn messages_latency_is_computed_for_delivered_messages() {
+		let start = Instant::now();
+		let mut latency = MessagesLatency::new(10);
+		assert_eq!(latency.message_sent(start), 10);
+		assert_eq!(latency.message_sent(start + Duration::from_secs(10)), 11);
+		assert_eq!(latency.message_sent(start + Duration::from_secs(20)), 12);
+
+		assert_eq!(
+			latency.messages_delivered(11, start + Duration::from_secs(30)),
+			vec![(10, Duration::from_secs(30)), (11, Duration::from_secs(20))],
+		);
+		assert!(latency.has_pending());
+		assert_eq!(latency.sent(), 3);
+		assert_eq!(latency.average(), Some(Duration::from_secs(25)));
+
+		assert_eq!(
+			latency.messages_delivered(12, start + Duration::from_secs(60)),
+			vec![(12, Duration::from_secs(40))],
+		);
+		assert!(!latency.has_pending());
+		assert_eq!(latency.min, Some(Duration::from_secs(20)));
+		assert_eq!(latency.max, Duration::from_secs(40));
+		assert_eq!(latency.average(), Some(Duration::from_secs(30)));
+	}
+
+	#[test]
+	fn messages_latency_ignores_messages_delivered_before_start() {
+		let start = Instant::now();
+		let mut latency = MessagesLatency::new(10);
+		latency.message_sent(start);
+
+		assert_eq!(latency.messages_delivered(9, start + Duration::from_secs(10)), vec![]);
+		assert_eq!(latency.average(), None);
+	}
+}
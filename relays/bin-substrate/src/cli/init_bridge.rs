@@ -42,6 +42,7 @@ arg_enum! {
 	/// Bridge to initialize.
 	pub enum InitBridgeName {
 		MillauToRialto,
+		MillauToRialtoParachain,
 		RialtoToMillau,
 		WestendToMillau,
 		RococoToWococo,
@@ -67,6 +68,21 @@ macro_rules! select_bridge {
 
 				$generic
 			}
+			InitBridgeName::MillauToRialtoParachain => {
+				type Source = relay_millau_client::Millau;
+				type Target = relay_rialto_parachain_client::RialtoParachain;
+
+				fn encode_init_bridge(
+					init_data: InitializationData<<Source as ChainBase>::Header>,
+				) -> <Target as Chain>::Call {
+					rialto_parachain_runtime::SudoCall::sudo(Box::new(
+						rialto_parachain_runtime::BridgeGrandpaMillauCall::initialize(init_data).into(),
+					))
+					.into()
+				}
+
+				$generic
+			}
 			InitBridgeName::RialtoToMillau => {
 				type Source = relay_rialto_client::Rialto;
 				type Target = relay_millau_client::Millau;
@@ -137,17 +153,24 @@ impl InitBridge {
 			let source_client = self.source.to_client::<Source>().await?;
 			let target_client = self.target.to_client::<Target>().await?;
 			let target_sign = self.target_sign.to_keypair::<Target>()?;
+			let target_transactions_mortality = self.target_sign.transactions_mortality()?;
 
 			crate::headers_initialize::initialize(
 				source_client,
 				target_client.clone(),
 				target_sign.public().into(),
-				move |transaction_nonce, initialization_data| {
+				move |best_block_id, transaction_nonce, initialization_data| {
 					Bytes(
 						Target::sign_transaction(
 							*target_client.genesis_hash(),
 							&target_sign,
+							relay_substrate_client::TransactionEra::new(
+								best_block_id.0,
+								best_block_id.1,
+								target_transactions_mortality,
+							),
 							transaction_nonce,
+							0,
 							encode_init_bridge(initialization_data),
 						)
 						.encode(),
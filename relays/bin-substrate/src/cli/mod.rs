@@ -16,9 +16,13 @@
 
 //! Deal with CLI args of substrate-to-substrate relay.
 
-use std::convert::TryInto;
+use std::{
+	convert::{TryFrom, TryInto},
+	ops::RangeInclusive,
+	path::{Path, PathBuf},
+};
 
-use bp_messages::LaneId;
+use bp_messages::{LaneId, MessageNonce};
 use codec::{Decode, Encode};
 use frame_support::weights::Weight;
 use sp_runtime::app_crypto::Ss58Codec;
@@ -28,13 +32,22 @@ pub(crate) mod bridge;
 pub(crate) mod encode_call;
 pub(crate) mod encode_message;
 pub(crate) mod estimate_fee;
+pub(crate) mod relay_messages;
 pub(crate) mod send_message;
 
+mod claim_rewards;
+mod conversion_rate_oracle;
 mod derive_account;
+mod doctor;
+mod export_finality_artifacts;
+mod generate_messages;
 mod init_bridge;
+mod relay_fan_out;
 mod relay_headers;
 mod relay_headers_and_messages;
-mod relay_messages;
+mod relay_messages_range;
+mod resubmit_transactions;
+mod swap_tokens;
 
 /// Parse relay CLI args.
 pub fn parse_args() -> Command {
@@ -62,6 +75,19 @@ pub enum Command {
 	/// the message relays - i.e. when there are messages or confirmations that needs to be
 	/// relayed between chains.
 	RelayHeadersAndMessages(relay_headers_and_messages::RelayHeadersAndMessages),
+	/// Start relays from one source chain to several target chains.
+	///
+	/// All relays are sharing the same connection to the source node, so source headers and
+	/// justifications are only fetched once. Every target is served by the most complete relay
+	/// it supports: e.g. Millau -> Rialto is served by the headers+messages relay, while only
+	/// Millau headers are relayed to the RialtoParachain.
+	RelayFanOut(relay_fan_out::RelayFanOut),
+	/// Deliver given range of messages and exit.
+	///
+	/// Messages are delivered regardless of the lane state, which may be required to unstick the
+	/// lane after bugs or manual interventions. All messages must be generated at the best source
+	/// header, known to the target chain.
+	RelayMessagesRange(relay_messages_range::RelayMessagesRange),
 	/// Initialize on-chain bridge pallet with current header data.
 	///
 	/// Sends initialization transaction to bootstrap the bridge with current finalized block data.
@@ -72,6 +98,11 @@ pub enum Command {
 	/// The message is being sent to the source chain, delivered to the target chain and dispatched
 	/// there.
 	SendMessage(send_message::SendMessage),
+	/// Continuously send test messages over the bridge lane and measure their delivery latency.
+	///
+	/// Messages of configured size and dispatch weight are sent at the given rate. May be used
+	/// to load-test lanes and to validate weight formulas at test networks.
+	GenerateMessages(generate_messages::GenerateMessages),
 	/// Generate SCALE-encoded `Call` for choosen network.
 	///
 	/// The call can be used either as message payload or can be wrapped into a transaction
@@ -86,6 +117,39 @@ pub enum Command {
 	EstimateFee(estimate_fee::EstimateFee),
 	/// Given a source chain `AccountId`, derive the corresponding `AccountId` for the target chain.
 	DeriveAccount(derive_account::DeriveAccount),
+	/// Export finality artifacts of the source chain for the Solidity light client.
+	///
+	/// Waits for the next GRANDPA justification of the source chain and exports finalized header,
+	/// justification and GRANDPA authorities set using the tightly packed encoding. With
+	/// `--finality beefy`, waits for the next BEEFY signed commitment instead and exports the
+	/// commitment, BEEFY validator set and MMR proof of the finalized header.
+	ExportFinalityArtifacts(export_finality_artifacts::ExportFinalityArtifacts),
+	/// Swap tokens of the source chain for tokens of the target chain.
+	///
+	/// Creates token swap at the source chain and accepts it at the target chain. Source tokens
+	/// are claimed when the claim message is delivered from the target chain to the source chain.
+	SwapTokens(swap_tokens::SwapTokens),
+	/// Resubmit transactions of the signer that are stuck in the transaction pool.
+	///
+	/// Transactions that are not mined for given number of blocks are signed again with the
+	/// increased tip. May be used for manual interventions, or to watch the pool in background.
+	ResubmitTransactions(resubmit_transactions::ResubmitTransactions),
+	/// Claim rewards, earned by the relayer at the bridge relayers pallet.
+	///
+	/// Rewards are transferred from the relayers fund account to the relayer account. Rewards may
+	/// also be claimed automatically by relays, using the `--<chain>-rewards-claim-threshold` option.
+	ClaimRewards(claim_rewards::ClaimRewards),
+	/// Diagnose the bridge between two chains.
+	///
+	/// Checks nodes connectivity, deployed bridge pallets, bridge initialization, signers balances
+	/// and lane state. Prints the report with suggested fixes and fails if any check has failed.
+	Doctor(doctor::Doctor),
+	/// Keep conversion rate of bridged chain tokens in sync with the external price service.
+	///
+	/// The rate is stored at the conversion rate pallet and is used to compute message delivery
+	/// fees. The update transaction is only submitted when the on-chain rate differs from the
+	/// external one by more than the `--threshold`.
+	ConversionRateOracle(conversion_rate_oracle::ConversionRateOracle),
 }
 
 impl Command {
@@ -94,7 +158,15 @@ impl Command {
 		use relay_utils::initialize::{initialize_logger, initialize_relay};
 
 		match self {
-			Self::RelayHeaders(_) | Self::RelayMessages(_) | Self::RelayHeadersAndMessages(_) | Self::InitBridge(_) => {
+			Self::RelayHeaders(_)
+			| Self::RelayMessages(_)
+			| Self::RelayHeadersAndMessages(_)
+			| Self::RelayFanOut(_)
+			| Self::RelayMessagesRange(_)
+			| Self::InitBridge(_)
+			| Self::ResubmitTransactions(_)
+			| Self::ConversionRateOracle(_)
+			| Self::GenerateMessages(_) => {
 				initialize_relay();
 			}
 			_ => {
@@ -103,19 +175,43 @@ impl Command {
 		}
 	}
 
+	// Start systemd notifications if the command starts long-running relay.
+	fn init_watchdog(&self) {
+		match self {
+			Self::RelayHeaders(_)
+			| Self::RelayMessages(_)
+			| Self::RelayHeadersAndMessages(_)
+			| Self::RelayFanOut(_)
+			| Self::ConversionRateOracle(_) => {
+				relay_utils::watchdog::start();
+			}
+			_ => (),
+		}
+	}
+
 	/// Run the command.
 	pub async fn run(self) -> anyhow::Result<()> {
 		self.init_logger();
+		self.init_watchdog();
 		match self {
 			Self::RelayHeaders(arg) => arg.run().await?,
 			Self::RelayMessages(arg) => arg.run().await?,
 			Self::RelayHeadersAndMessages(arg) => arg.run().await?,
+			Self::RelayFanOut(arg) => arg.run().await?,
+			Self::RelayMessagesRange(arg) => arg.run().await?,
 			Self::InitBridge(arg) => arg.run().await?,
 			Self::SendMessage(arg) => arg.run().await?,
+			Self::GenerateMessages(arg) => arg.run().await?,
 			Self::EncodeCall(arg) => arg.run().await?,
 			Self::EncodeMessage(arg) => arg.run().await?,
 			Self::EstimateFee(arg) => arg.run().await?,
 			Self::DeriveAccount(arg) => arg.run().await?,
+			Self::ExportFinalityArtifacts(arg) => arg.run().await?,
+			Self::SwapTokens(arg) => arg.run().await?,
+			Self::ResubmitTransactions(arg) => arg.run().await?,
+			Self::ClaimRewards(arg) => arg.run().await?,
+			Self::Doctor(arg) => arg.run().await?,
+			Self::ConversionRateOracle(arg) => arg.run().await?,
 		}
 		Ok(())
 	}
@@ -133,7 +229,31 @@ arg_enum! {
 	}
 }
 
+arg_enum! {
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	/// Where the message dispatch fee is paid.
+	///
+	/// - `AtSourceChain` - the sender pays dispatch fee at the source chain, with the delivery fee.
+	/// - `AtTargetChain` - the dispatch origin account pays dispatch fee at the target chain.
+	pub enum DispatchFeePayment {
+		AtSourceChain,
+		AtTargetChain,
+	}
+}
+
+impl From<DispatchFeePayment> for bp_message_dispatch::DispatchFeePayment {
+	fn from(dispatch_fee_payment: DispatchFeePayment) -> Self {
+		match dispatch_fee_payment {
+			DispatchFeePayment::AtSourceChain => bp_message_dispatch::DispatchFeePayment::AtSourceChain,
+			DispatchFeePayment::AtTargetChain => bp_message_dispatch::DispatchFeePayment::AtTargetChain,
+		}
+	}
+}
+
 /// Generic balance type.
+///
+/// Wide enough to hold balance of any supported chain. Use [`Balance::cast`] to convert it
+/// to the chain balance type and [`Balance::from_native`] to convert it back.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Balance(pub u128);
 
@@ -153,9 +273,35 @@ impl std::str::FromStr for Balance {
 }
 
 impl Balance {
-	/// Cast balance to `u64` type, panicking if it's too large.
-	pub fn cast(&self) -> u64 {
-		self.0.try_into().expect("Balance is too high for this chain.")
+	/// Create generic balance from the chain balance.
+	pub fn from_native<ChainBalance: Into<u128>>(balance: ChainBalance) -> Self {
+		Self(balance.into())
+	}
+
+	/// Cast balance to the chain balance type, failing if it's too large.
+	pub fn cast<ChainBalance: TryFrom<u128>>(&self) -> anyhow::Result<ChainBalance> {
+		ChainBalance::try_from(self.0).map_err(|_| anyhow::format_err!("Balance {} is too high for the chain", self))
+	}
+
+	/// Format balance as amount of tokens, using given token properties.
+	///
+	/// E.g. balance `1_500_000_000` of token with 9 decimals and `MLAU` symbol is formatted
+	/// as `1.5 MLAU`.
+	pub fn format_tokens(&self, token: &relay_substrate_client::TokenProperties) -> String {
+		use num_format::{Locale, ToFormattedString};
+
+		let unit = match 10u128.checked_pow(token.decimals) {
+			Some(unit) => unit,
+			None => return format!("{} (base units of {})", self, token.symbol),
+		};
+		let integer = (self.0 / unit).to_formatted_string(&Locale::en);
+		let fraction = format!("{:0width$}", self.0 % unit, width = token.decimals as usize);
+		let fraction = fraction.trim_end_matches('0');
+		if fraction.is_empty() {
+			format!("{} {}", integer, token.symbol)
+		} else {
+			format!("{}.{} {}", integer, fraction, token.symbol)
+		}
 	}
 }
 
@@ -248,23 +394,127 @@ pub trait CliChain: relay_substrate_client::Chain {
 	fn max_extrinsic_weight() -> Weight;
 }
 
-/// Lane id.
+/// Inclusive range of message nonces.
+///
+/// Parsed from `<begin>..<end>` string, where both `begin` and `end` are included in the range.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct HexLaneId(pub LaneId);
+pub struct NoncesRange(pub RangeInclusive<MessageNonce>);
 
-impl From<HexLaneId> for LaneId {
-	fn from(lane_id: HexLaneId) -> LaneId {
-		lane_id.0
+impl From<NoncesRange> for RangeInclusive<MessageNonce> {
+	fn from(range: NoncesRange) -> RangeInclusive<MessageNonce> {
+		range.0
 	}
 }
 
-impl std::str::FromStr for HexLaneId {
-	type Err = hex::FromHexError;
+impl std::str::FromStr for NoncesRange {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.splitn(2, "..");
+		let (begin, end) = match (parts.next(), parts.next()) {
+			(Some(begin), Some(end)) => (begin, end),
+			_ => return Err(format!("Expected <BEGIN>..<END>, got: {}", s)),
+		};
+		let begin: MessageNonce = begin
+			.parse()
+			.map_err(|err| format!("Unable to decode range begin: {:?}", err))?;
+		let end: MessageNonce = end
+			.parse()
+			.map_err(|err| format!("Unable to decode range end: {:?}", err))?;
+		if begin == 0 || begin > end {
+			return Err(format!("Invalid nonces range: {}", s));
+		}
+		Ok(NoncesRange(begin..=end))
+	}
+}
+
+/// Lanes that are served by the messages relay.
+///
+/// Parsed either from `auto` string, or from comma-separated list of lanes, where every lane id
+/// is either hex-encoded, or the name of well-known lane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lanes {
+	/// Lanes are discovered from the source chain storage.
+	Auto,
+	/// Given lanes are served.
+	Static(Vec<LaneId>),
+}
+
+impl std::str::FromStr for Lanes {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.eq_ignore_ascii_case("auto") {
+			return Ok(Lanes::Auto);
+		}
+
+		s.split(',')
+			.map(|lane| {
+				lane.trim()
+					.parse()
+					.map_err(|err| format!("Unable to decode lane id: {}", err))
+			})
+			.collect::<Result<Vec<_>, _>>()
+			.map(Lanes::Static)
+	}
+}
+
+/// Account that has sent messages over given lane.
+///
+/// Parsed from `<lane-id>:<ss58-account-id>` string, where lane id is either hex-encoded, or the name
+/// of well-known lane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaneMessagesSender {
+	/// Lane identifier.
+	pub lane: LaneId,
+	/// Messages sender.
+	pub sender: AccountId,
+}
+
+impl std::str::FromStr for LaneMessagesSender {
+	type Err = String;
 
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let mut lane_id = LaneId::default();
-		hex::decode_to_slice(s, &mut lane_id)?;
-		Ok(HexLaneId(lane_id))
+		let mut parts = s.splitn(2, ':');
+		let (lane, sender) = match (parts.next(), parts.next()) {
+			(Some(lane), Some(sender)) => (lane, sender),
+			_ => return Err(format!("Expected <LANE>:<ACCOUNT>, got: {}", s)),
+		};
+		Ok(LaneMessagesSender {
+			lane: lane
+				.parse()
+				.map_err(|err| format!("Unable to decode lane id: {}", err))?,
+			sender: sender.parse()?,
+		})
+	}
+}
+
+/// Websocket server endpoint of the node.
+///
+/// Parsed from `<host>:<port>` string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeEndpoint {
+	/// Websocket server hostname.
+	pub host: String,
+	/// Websocket server TCP port.
+	pub port: u16,
+}
+
+impl std::str::FromStr for NodeEndpoint {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.splitn(2, ':');
+		let (host, port) = match (parts.next(), parts.next()) {
+			(Some(host), Some(port)) if !host.is_empty() => (host, port),
+			_ => return Err(format!("Expected <HOST>:<PORT>, got: {}", s)),
+		};
+		Ok(NodeEndpoint {
+			host: host.into(),
+			port: port
+				.parse()
+				.map_err(|err| format!("Unable to decode port: {:?}", err))?,
+		})
 	}
 }
 
@@ -276,7 +526,7 @@ impl std::str::FromStr for HexBytes {
 	type Err = hex::FromHexError;
 
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		Ok(Self(hex::decode(s)?))
+		Ok(Self(hex::decode(s.strip_prefix("0x").unwrap_or(s))?))
 	}
 }
 
@@ -306,16 +556,16 @@ pub struct PrometheusParams {
 	#[structopt(long)]
 	pub no_prometheus: bool,
 	/// Expose Prometheus endpoint at given interface.
-	#[structopt(long, default_value = "127.0.0.1")]
+	#[structopt(long, env = "BRIDGE_PROMETHEUS_HOST", default_value = "127.0.0.1")]
 	pub prometheus_host: String,
 	/// Expose Prometheus endpoint at given port.
-	#[structopt(long, default_value = "9616")]
+	#[structopt(long, env = "BRIDGE_PROMETHEUS_PORT", default_value = "9616")]
 	pub prometheus_port: u16,
 }
 
 impl From<PrometheusParams> for relay_utils::metrics::MetricsParams {
 	fn from(cli_params: PrometheusParams) -> relay_utils::metrics::MetricsParams {
-		if !cli_params.no_prometheus {
+		if !flag_or_env(cli_params.no_prometheus, "BRIDGE_NO_PROMETHEUS") {
 			Some(relay_utils::metrics::MetricsAddress {
 				host: cli_params.prometheus_host,
 				port: cli_params.prometheus_port,
@@ -327,6 +577,92 @@ impl From<PrometheusParams> for relay_utils::metrics::MetricsParams {
 	}
 }
 
+/// Relay control interface params.
+#[derive(StructOpt)]
+pub struct ControlParams {
+	/// Expose relay control JSON-RPC interface at given port. The interface is not exposed by default.
+	#[structopt(long, env = "BRIDGE_CONTROL_PORT")]
+	pub control_port: Option<u16>,
+	/// Expose relay control interface at given interface.
+	#[structopt(long, env = "BRIDGE_CONTROL_HOST", default_value = "127.0.0.1")]
+	pub control_host: String,
+}
+
+impl From<ControlParams> for Option<relay_utils::control::ControlAddress> {
+	fn from(cli_params: ControlParams) -> Option<relay_utils::control::ControlAddress> {
+		let host = cli_params.control_host;
+		cli_params
+			.control_port
+			.map(|port| relay_utils::control::ControlAddress { host, port })
+	}
+}
+
+arg_enum! {
+	#[derive(Debug, Clone, Copy)]
+	/// Format of alerts, sent to the webhook.
+	///
+	/// - `Generic` sends JSON object with `kind`, `subject` and `message` fields.
+	/// - `Slack` sends Slack incoming webhook message.
+	/// - `PagerDuty` sends PagerDuty Events API v2 event.
+	pub enum AlertWebhookFormat {
+		Generic,
+		Slack,
+		PagerDuty,
+	}
+}
+
+/// Alerts params.
+#[derive(StructOpt)]
+pub struct AlertsParams {
+	/// Send alerts on critical relay conditions to given webhook. If not specified, alerts are only logged.
+	#[structopt(long, env = "BRIDGE_ALERT_WEBHOOK")]
+	pub alert_webhook: Option<String>,
+	/// Format of alerts, sent to the webhook.
+	#[structopt(
+		long,
+		env = "BRIDGE_ALERT_WEBHOOK_FORMAT",
+		possible_values = &AlertWebhookFormat::variants(),
+		case_insensitive = true,
+		default_value = "Generic",
+	)]
+	pub alert_webhook_format: AlertWebhookFormat,
+	/// PagerDuty integration key. Required if alerts are sent to PagerDuty.
+	#[structopt(long, env = "BRIDGE_ALERT_PAGERDUTY_ROUTING_KEY", hide_env_values = true)]
+	pub alert_pagerduty_routing_key: Option<String>,
+	/// Minimal interval (in minutes) between two webhook alerts of the same kind about the same subject.
+	#[structopt(long, env = "BRIDGE_ALERT_REPEAT_MINUTES", default_value = "60")]
+	pub alert_repeat_minutes: u64,
+	/// Fire alert if the headers relay hasn't synced any headers for given number of minutes, while
+	/// there are headers to sync. Zero disables the alert.
+	#[structopt(long, env = "BRIDGE_ALERT_FINALITY_STALL_MINUTES", default_value = "30")]
+	pub alert_finality_stall_minutes: u64,
+	/// Fire alert if given number of consecutive transactions, submitted by the same relay loop, have failed.
+	/// Zero disables the alert.
+	#[structopt(long, env = "BRIDGE_ALERT_MAX_TRANSACTION_FAILURES", default_value = "5")]
+	pub alert_max_transaction_failures: u32,
+}
+
+impl AlertsParams {
+	/// Configure relay alerts.
+	pub fn initialize(self) -> anyhow::Result<()> {
+		relay_utils::alerts::initialize_alerts(relay_utils::alerts::AlertsParams {
+			webhook: self.alert_webhook,
+			format: match self.alert_webhook_format {
+				AlertWebhookFormat::Generic => relay_utils::alerts::WebhookFormat::Generic,
+				AlertWebhookFormat::Slack => relay_utils::alerts::WebhookFormat::Slack,
+				AlertWebhookFormat::PagerDuty => relay_utils::alerts::WebhookFormat::PagerDuty,
+			},
+			pagerduty_routing_key: self.alert_pagerduty_routing_key,
+			repeat_interval: std::time::Duration::from_secs(self.alert_repeat_minutes * 60),
+			finality_stall_timeout: Some(self.alert_finality_stall_minutes)
+				.filter(|minutes| *minutes != 0)
+				.map(|minutes| std::time::Duration::from_secs(minutes * 60)),
+			max_transaction_failures: self.alert_max_transaction_failures,
+		})
+		.map_err(|e| anyhow::format_err!("{}", e))
+	}
+}
+
 /// Either explicit or maximal allowed value.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ExplicitOrMaximal<V> {
@@ -353,6 +689,78 @@ where
 	}
 }
 
+/// Read SURI of the secret key from the file.
+///
+/// The file may either contain the SURI itself, or be the key file of the Substrate keystore, where
+/// the SURI is stored as JSON string. If the path is the keystore directory, it must contain exactly
+/// one key file.
+pub fn read_signer_file(path: &Path) -> anyhow::Result<String> {
+	let key_file = if path.is_dir() {
+		let mut key_files = std::fs::read_dir(path)
+			.and_then(|entries| {
+				entries
+					.map(|entry| entry.map(|entry| entry.path()))
+					.collect::<Result<Vec<_>, _>>()
+			})
+			.map_err(|e| anyhow::format_err!("Failed to read keystore directory {}: {}", path.display(), e))?;
+		key_files.retain(|key_file| key_file.is_file());
+		if key_files.len() != 1 {
+			return Err(anyhow::format_err!(
+				"Keystore directory {} must contain exactly one key file. Found: {}",
+				path.display(),
+				key_files.len(),
+			));
+		}
+		key_files.remove(0)
+	} else {
+		path.to_path_buf()
+	};
+
+	let contents = read_secret_file(&key_file)?;
+	Ok(serde_json::from_str::<String>(&contents).unwrap_or(contents))
+}
+
+/// Read secret (SURI or password) from the file. Trailing whitespaces are ignored.
+pub fn read_secret_file(path: &Path) -> anyhow::Result<String> {
+	std::fs::read_to_string(path)
+		.map(|contents| contents.trim_end().to_owned())
+		.map_err(|e| anyhow::format_err!("Failed to read secret from {}: {}", path.display(), e))
+}
+
+/// Select secret from either explicitly specified value, or from the file.
+fn select_secret(
+	name: &str,
+	value: Option<&String>,
+	file: Option<&PathBuf>,
+	read_file: impl Fn(&Path) -> anyhow::Result<String>,
+) -> anyhow::Result<Option<String>> {
+	match (value, file) {
+		(Some(_), Some(_)) => Err(anyhow::format_err!(
+			"Only one of --{} and --{}-file may be specified",
+			name,
+			name,
+		)),
+		(Some(value), None) => Ok(Some(value.clone())),
+		(None, Some(file)) => read_file(file).map(Some),
+		(None, None) => Ok(None),
+	}
+}
+
+/// Return true if the boolean flag is set either on the command line, or by the environment variable.
+///
+/// Flags can't be configured using the `env` attribute, because it forces the flag to take a value.
+/// Instead, the flag is set if the environment variable is set to `true` or `1`.
+pub fn flag_or_env(flag: bool, env_name: &str) -> bool {
+	flag || std::env::var(env_name)
+		.map(|value| is_env_flag_set(&value))
+		.unwrap_or(false)
+}
+
+/// Return true if the value of the environment variable sets the boolean flag.
+fn is_env_flag_set(value: &str) -> bool {
+	value == "1" || value.eq_ignore_ascii_case("true")
+}
+
 /// Create chain-specific set of configuration objects: connection parameters,
 /// signing parameters and bridge initialisation parameters.
 #[macro_export]
@@ -363,36 +771,419 @@ macro_rules! declare_chain_options {
 			#[derive(StructOpt, Debug, PartialEq, Eq)]
 			pub struct [<$chain ConnectionParams>] {
 				#[doc = "Connect to " $chain " node at given host."]
-				#[structopt(long, default_value = "127.0.0.1")]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _HOST>])),
+					default_value = "127.0.0.1",
+				)]
 				pub [<$chain_prefix _host>]: String,
 				#[doc = "Connect to " $chain " node websocket server at given port."]
-				#[structopt(long)]
+				#[structopt(long, env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _PORT>])))]
 				pub [<$chain_prefix _port>]: u16,
 				#[doc = "Use secure websocket connection."]
 				#[structopt(long)]
 				pub [<$chain_prefix _secure>]: bool,
+				#[doc = "Refuse to work with " $chain " node if it has different genesis hash."]
+				#[structopt(long, env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _GENESIS_HASH>])))]
+				pub [<$chain_prefix _genesis_hash>]: Option<HexBytes>,
+				#[doc = "Websocket endpoint (<HOST>:<PORT>) of the failover " $chain " node. May be specified several"]
+				#[doc = "times. Failover endpoints are only used by the `failover` recovery policy."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _FAILOVER_ENDPOINT>])),
+					use_delimiter = true,
+				)]
+				pub [<$chain_prefix _failover_endpoint>]: Vec<crate::cli::NodeEndpoint>,
+				#[doc = "Recovery policy of the " $chain " client of relay loops: comma-separated list of"]
+				#[doc = "`reconnect`, `reconnect(<MAX-ATTEMPTS>)`, `resync`, `resync(<MAX-ATTEMPTS>)`, `failover`"]
+				#[doc = "and `abort` policies. Every next policy is used when the previous one is exhausted."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _RECOVERY_POLICY>])),
+					default_value = "reconnect",
+				)]
+				pub [<$chain_prefix _recovery_policy>]: relay_utils::recovery::RecoveryPolicyParams,
+				#[doc = "Connect to " $chain " node through the egress proxy: `socks5://<HOST>:<PORT>` or"]
+				#[doc = "`http://<HOST>:<PORT>` (the proxy must support `CONNECT` method). Credentials may be"]
+				#[doc = "specified as `<SCHEME>://<USERNAME>:<PASSWORD>@<HOST>:<PORT>`."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _PROXY>])),
+					hide_env_values = true,
+				)]
+				pub [<$chain_prefix _proxy>]: Option<relay_substrate_client::ProxyParams>,
+				#[doc = "Path to the PEM file with certificates that are trusted (in addition to the default root"]
+				#[doc = "certificates) when secure connection to the " $chain " node is established."]
+				#[structopt(long, env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _TLS_CA_BUNDLE>])))]
+				pub [<$chain_prefix _tls_ca_bundle>]: Option<std::path::PathBuf>,
+				#[doc = "Path to the PEM file with client certificate chain, that is presented to the " $chain]
+				#[doc = "node when secure connection is established (mutual TLS)."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _TLS_CLIENT_CERTIFICATE>])),
+				)]
+				pub [<$chain_prefix _tls_client_certificate>]: Option<std::path::PathBuf>,
+				#[doc = "Path to the PEM file with private key of the client certificate (mutual TLS)."]
+				#[structopt(long, env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _TLS_CLIENT_KEY>])))]
+				pub [<$chain_prefix _tls_client_key>]: Option<std::path::PathBuf>,
 			}
 
 			#[doc = $chain " signing params."]
 			#[derive(StructOpt, Debug, PartialEq, Eq)]
 			pub struct [<$chain SigningParams>] {
 				#[doc = "The SURI of secret key to use when transactions are submitted to the " $chain " node."]
-				#[structopt(long)]
-				pub [<$chain_prefix _signer>]: String,
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SIGNER>])),
+					hide_env_values = true,
+				)]
+				pub [<$chain_prefix _signer>]: Option<String>,
 				#[doc = "The password for the SURI of secret key to use when transactions are submitted to the " $chain " node."]
-				#[structopt(long)]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SIGNER_PASSWORD>])),
+					hide_env_values = true,
+				)]
 				pub [<$chain_prefix _signer_password>]: Option<String>,
+				#[doc = "Path to the file with SURI of secret key to use when transactions are submitted to the " $chain " node."]
+				#[doc = "May also be the key file or the directory (with single key) of the Substrate keystore."]
+				#[structopt(long, env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SIGNER_FILE>])))]
+				pub [<$chain_prefix _signer_file>]: Option<std::path::PathBuf>,
+				#[doc = "Path to the file with password for the SURI of secret key to use when transactions are submitted"]
+				#[doc = "to the " $chain " node."]
+				#[structopt(long, env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SIGNER_PASSWORD_FILE>])))]
+				pub [<$chain_prefix _signer_password_file>]: Option<std::path::PathBuf>,
+				#[doc = "Before starting, watch nonce of the " $chain " signer for given number of blocks and refuse"]
+				#[doc = "to start if it changes (i.e. if the signer is used by another relay)."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SIGNER_HEARTBEAT_BLOCKS>])),
+				)]
+				pub [<$chain_prefix _signer_heartbeat_blocks>]: Option<u32>,
+				#[doc = "Mortality period (in blocks) of transactions that are submitted to the " $chain " node."]
+				#[doc = "MUST be a power of two in [4; 65536] range. If not specified, transactions are immortal."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _TRANSACTIONS_MORTALITY>])),
+				)]
+				pub [<$chain_prefix _transactions_mortality>]: Option<u32>,
+				#[doc = "Fire alert if free balance of the " $chain " signer is below given value."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SIGNER_BALANCE_ALERT_THRESHOLD>])),
+				)]
+				pub [<$chain_prefix _signer_balance_alert_threshold>]: Option<crate::cli::Balance>,
+				#[doc = "Fire alert if free balance of the " $chain " signer is estimated to be enough for less"]
+				#[doc = "than given number of transactions. The cost of single transaction is estimated using"]
+				#[doc = "recent changes of the signer balance and nonce."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SIGNER_BALANCE_ALERT_TRANSACTIONS>])),
+				)]
+				pub [<$chain_prefix _signer_balance_alert_transactions>]: Option<u32>,
+				#[doc = "Account on whose behalf the " $chain " transactions are submitted, using `proxy` calls."]
+				#[doc = "The signer must be registered as a proxy of this account at the " $chain " chain."]
+				#[structopt(long, env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _PROXIED_ACCOUNT>])))]
+				pub [<$chain_prefix _proxied_account>]: Option<crate::cli::AccountId>,
+				#[doc = "Maximal tip that may be attached to the " $chain " transactions when the chain is congested."]
+				#[doc = "If not specified, transactions are submitted without tip."]
+				#[structopt(long, env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _MAX_TIP>])))]
+				pub [<$chain_prefix _max_tip>]: Option<crate::cli::Balance>,
+				#[doc = "Claim rewards, earned by the " $chain " relayer at the bridge relayers pallet, once they"]
+				#[doc = "reach given value. If not specified, rewards are never claimed automatically."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _REWARDS_CLAIM_THRESHOLD>])),
+				)]
+				pub [<$chain_prefix _rewards_claim_threshold>]: Option<crate::cli::Balance>,
+				#[doc = "Fund the " $chain " signer once its free balance drops below given value. The funding"]
+				#[doc = "transaction is signed by the funding account. Only intended for test networks."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SIGNER_FUNDING_THRESHOLD>])),
+				)]
+				pub [<$chain_prefix _signer_funding_threshold>]: Option<crate::cli::Balance>,
+				#[doc = "Amount that is transferred from the funding account to the " $chain " signer when"]
+				#[doc = "funding is required."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SIGNER_FUNDING_AMOUNT>])),
+				)]
+				pub [<$chain_prefix _signer_funding_amount>]: Option<crate::cli::Balance>,
+				#[doc = "Hex-encoded " $chain " call that is submitted by the funding account when funding"]
+				#[doc = "is required (e.g. the faucet pallet call). Conflicts with the funding amount."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SIGNER_FUNDING_CALL>])),
+				)]
+				pub [<$chain_prefix _signer_funding_call>]: Option<crate::cli::HexBytes>,
+				#[doc = "The SURI of secret key of the " $chain " account that is funding the signer."]
+				#[structopt(long, env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _FUNDING_SIGNER>])))]
+				pub [<$chain_prefix _funding_signer>]: Option<String>,
+				#[doc = "The password for the SURI of secret key of the " $chain " funding account."]
+				#[structopt(
+					long,
+					env = concat!("BRIDGE_", stringify!([<$chain_prefix:upper _FUNDING_SIGNER_PASSWORD>])),
+				)]
+				pub [<$chain_prefix _funding_signer_password>]: Option<String>,
 			}
 
 			impl [<$chain SigningParams>] {
 				/// Parse signing params into chain-specific KeyPair.
+				///
+				/// The SURI and its password are either specified explicitly (using arguments or
+				/// environment variables), or read from files.
 				pub fn to_keypair<Chain: CliChain>(&self) -> anyhow::Result<Chain::KeyPair> {
 					use sp_core::crypto::Pair;
 
-					Chain::KeyPair::from_string(
-						&self.[<$chain_prefix _signer>],
-						self.[<$chain_prefix _signer_password>].as_deref()
-					).map_err(|e| anyhow::format_err!("{:?}", e))
+					let suri = crate::cli::select_secret(
+						concat!(stringify!($chain_prefix), "-signer"),
+						self.[<$chain_prefix _signer>].as_ref(),
+						self.[<$chain_prefix _signer_file>].as_ref(),
+						crate::cli::read_signer_file,
+					)?
+					.ok_or_else(|| anyhow::format_err!(
+						"One of --{}-signer and --{}-signer-file must be specified",
+						stringify!($chain_prefix),
+						stringify!($chain_prefix),
+					))?;
+					let password = crate::cli::select_secret(
+						concat!(stringify!($chain_prefix), "-signer-password"),
+						self.[<$chain_prefix _signer_password>].as_ref(),
+						self.[<$chain_prefix _signer_password_file>].as_ref(),
+						crate::cli::read_secret_file,
+					)?;
+
+					Chain::KeyPair::from_string(&suri, password.as_deref()).map_err(|e| anyhow::format_err!("{:?}", e))
+				}
+
+				/// Return mortality period of submitted transactions.
+				///
+				/// Returns `None` if transactions are immortal.
+				pub fn transactions_mortality(&self) -> anyhow::Result<Option<u32>> {
+					match self.[<$chain_prefix _transactions_mortality>] {
+						Some(mortality) if !(4..=65536).contains(&mortality) || !mortality.is_power_of_two() => {
+							Err(anyhow::format_err!(
+								"Transactions mortality {} is not a power of two in [4; 65536] range",
+								mortality,
+							))
+						}
+						mortality => Ok(mortality),
+					}
+				}
+
+				/// Ensure that the signer is not used by another relay instance.
+				///
+				/// Returned lock must be held while the relay is running.
+				pub async fn lock_signer<Chain: CliChain>(
+					&self,
+					client: &relay_substrate_client::Client<Chain>,
+				) -> anyhow::Result<relay_utils::instance_lock::InstanceLock>
+				where
+					Chain::AccountId: From<<Chain::KeyPair as sp_core::crypto::Pair>::Public>,
+				{
+					use sp_core::crypto::Pair;
+
+					let signer = self.to_keypair::<Chain>()?.public();
+					let lock = relay_utils::instance_lock::InstanceLock::acquire(&format!(
+						"{}-signer-{}",
+						Chain::NAME,
+						hex::encode(&signer),
+					))
+					.map_err(|e| anyhow::format_err!("{}", e))?;
+
+					if let Some(blocks) = self.[<$chain_prefix _signer_heartbeat_blocks>] {
+						log::info!(
+							target: "bridge",
+							"Checking that {} signer is not used by another relay for {} blocks",
+							Chain::NAME,
+							blocks,
+						);
+						client
+							.ensure_account_is_idle(signer.into(), blocks)
+							.await
+							.map_err(|e| anyhow::format_err!("{}", e))?;
+					}
+
+					Ok(lock)
+				}
+
+				/// Start watching balance of the signer, if alert threshold or minimal number of remaining
+				/// transactions is specified.
+				pub fn start_balance_alert<Chain: CliChain + relay_substrate_client::ChainWithBalances>(
+					&self,
+					client: &relay_substrate_client::Client<Chain>,
+				) -> anyhow::Result<()>
+				where
+					Chain::AccountId: From<<Chain::KeyPair as sp_core::crypto::Pair>::Public>,
+					Chain::NativeBalance: TryFrom<u128>,
+				{
+					use sp_core::crypto::Pair;
+
+					if let Some(threshold) = self.[<$chain_prefix _signer_balance_alert_threshold>] {
+						relay_substrate_client::guard::alert_when_account_balance_below(
+							client.clone(),
+							self.to_keypair::<Chain>()?.public().into(),
+							threshold.cast()?,
+						);
+					}
+					if let Some(min_transactions) = self.[<$chain_prefix _signer_balance_alert_transactions>] {
+						relay_substrate_client::guard::alert_when_account_transactions_remaining_below(
+							client.clone(),
+							self.to_keypair::<Chain>()?.public().into(),
+							min_transactions,
+						);
+					}
+
+					Ok(())
+				}
+
+				/// Expose free balance of the signer as a metric in the given registry.
+				///
+				/// Does nothing if metrics are not exposed.
+				pub fn register_balance_metric<Chain: CliChain + relay_substrate_client::ChainWithBalances>(
+					&self,
+					client: &relay_substrate_client::Client<Chain>,
+					metrics_params: &relay_utils::metrics::MetricsParams,
+				) -> anyhow::Result<()>
+				where
+					Chain::AccountId: From<<Chain::KeyPair as sp_core::crypto::Pair>::Public>,
+				{
+					use sp_core::crypto::Pair;
+
+					if metrics_params.address.is_none() {
+						return Ok(());
+					}
+
+					let signer = self.to_keypair::<Chain>()?.public().into();
+					relay_utils::relay_metrics(None, metrics_params.clone())
+						.standalone_metric(|registry, prefix| {
+							relay_substrate_client::metrics::FreeNativeBalanceMetric::new(
+								registry,
+								prefix,
+								client.clone(),
+								signer,
+								format!("{}_signer_free_balance", stringify!($chain_prefix)),
+								format!("Free balance of the {} relay signer", Chain::NAME),
+							)
+						})
+						.map_err(|e| anyhow::format_err!("{}", e))?;
+
+					Ok(())
+				}
+
+				/// Start claiming relayer rewards, if claim threshold is specified.
+				pub fn start_rewards_auto_claim<Chain>(
+					&self,
+					client: &relay_substrate_client::Client<Chain>,
+				) -> anyhow::Result<()>
+				where
+					Chain: CliChain<KeyPair = <Chain as relay_substrate_client::TransactionSignScheme>::AccountKeyPair>
+						+ crate::relayer_rewards::ChainWithRelayersRewards,
+					Chain::AccountId:
+						From<<Chain::KeyPair as sp_core::crypto::Pair>::Public> + From<sp_runtime::AccountId32>,
+					Chain::NativeBalance: std::convert::TryFrom<u128>,
+					Chain::SignedTransaction: codec::Encode,
+				{
+					if let Some(threshold) = self.[<$chain_prefix _rewards_claim_threshold>] {
+						crate::relayer_rewards::start_rewards_auto_claim(
+							client.clone(),
+							self.to_keypair::<Chain>()?,
+							self.proxied_account::<Chain>(),
+							self.transactions_mortality()?,
+							threshold.cast()?,
+						);
+					}
+
+					Ok(())
+				}
+
+				/// Start funding the signer from the funding account, if funding threshold is specified.
+				pub fn start_auto_funding<Chain>(
+					&self,
+					client: &relay_substrate_client::Client<Chain>,
+				) -> anyhow::Result<()>
+				where
+					Chain: CliChain<KeyPair = <Chain as relay_substrate_client::TransactionSignScheme>::AccountKeyPair>
+						+ crate::cli::encode_call::CliEncodeCall
+						+ relay_substrate_client::ChainWithBalances
+						+ relay_substrate_client::TransactionSignScheme<Chain = Chain>,
+					Chain::AccountId:
+						From<<Chain::KeyPair as sp_core::crypto::Pair>::Public> + Into<sp_runtime::AccountId32>,
+					Chain::NativeBalance: TryFrom<u128>,
+					Chain::Call: Send,
+					Chain::SignedTransaction: codec::Encode,
+				{
+					use sp_core::crypto::Pair;
+
+					let threshold = match self.[<$chain_prefix _signer_funding_threshold>] {
+						Some(threshold) => threshold,
+						None => return Ok(()),
+					};
+
+					let funding_signer = self.[<$chain_prefix _funding_signer>].as_ref().ok_or_else(|| {
+						anyhow::format_err!("--{}-funding-signer must be specified", stringify!($chain_prefix))
+					})?;
+					let funding_signer = Chain::KeyPair::from_string(
+						funding_signer,
+						self.[<$chain_prefix _funding_signer_password>].as_deref(),
+					)
+					.map_err(|e| anyhow::format_err!("{:?}", e))?;
+					let relayer: Chain::AccountId = self.to_keypair::<Chain>()?.public().into();
+					let funding_call = match (
+						self.[<$chain_prefix _signer_funding_amount>],
+						self.[<$chain_prefix _signer_funding_call>].as_ref(),
+					) {
+						(Some(amount), None) => crate::cli::encode_call::Call::Transfer {
+							recipient: crate::cli::AccountId::from_raw::<Chain>(relayer.clone().into()),
+							amount,
+						},
+						(None, Some(call)) => crate::cli::encode_call::Call::Raw {
+							data: crate::cli::HexBytes(call.0.clone()),
+						},
+						_ => return Err(anyhow::format_err!(
+							"Exactly one of --{}-signer-funding-amount and --{}-signer-funding-call must be specified",
+							stringify!($chain_prefix),
+							stringify!($chain_prefix),
+						)),
+					};
+					// fail early if the funding call can't be encoded
+					Chain::encode_call(&funding_call)?;
+
+					crate::relayer_funding::start_auto_funding(
+						client.clone(),
+						relayer,
+						funding_signer,
+						move || Chain::encode_call(&funding_call),
+						self.transactions_mortality()?,
+						threshold.cast()?,
+					);
+
+					Ok(())
+				}
+
+				/// Return maximal tip that may be attached to submitted transactions.
+				pub fn max_tip<Chain: CliChain + relay_substrate_client::ChainWithBalances>(
+					&self,
+				) -> anyhow::Result<Chain::NativeBalance>
+				where
+					Chain::NativeBalance: TryFrom<u128>,
+				{
+					match self.[<$chain_prefix _max_tip>] {
+						Some(max_tip) => max_tip.cast(),
+						None => Ok(num_traits::Zero::zero()),
+					}
+				}
+
+				/// Return account on whose behalf transactions are submitted, if specified.
+				pub fn proxied_account<Chain: CliChain>(&self) -> Option<Chain::AccountId>
+				where
+					Chain::AccountId: From<sp_runtime::AccountId32>,
+				{
+					self.[<$chain_prefix _proxied_account>].clone().map(|mut account| {
+						account.enforce_chain::<Chain>();
+						account.raw_id().into()
+					})
 				}
 			}
 
@@ -401,13 +1192,52 @@ macro_rules! declare_chain_options {
 				pub async fn to_client<Chain: CliChain>(
 					&self,
 				) -> anyhow::Result<relay_substrate_client::Client<Chain>> {
-					Ok(relay_substrate_client::Client::new(relay_substrate_client::ConnectionParams {
+					let client = relay_substrate_client::Client::new(self.to_connection_params()).await;
+					client
+						.ensure_correct_chain(Chain::ss58_format(), self.expected_genesis_hash::<Chain>()?)
+						.await
+						.map_err(|e| anyhow::format_err!("{}", e))?;
+
+					Ok(client)
+				}
+
+				/// Convert CLI params into Substrate client connection params.
+				pub fn to_connection_params(&self) -> relay_substrate_client::ConnectionParams {
+					relay_substrate_client::ConnectionParams {
 						host: self.[<$chain_prefix _host>].clone(),
 						port: self.[<$chain_prefix _port>],
-						secure: self.[<$chain_prefix _secure>],
-					})
-					.await
-					)
+						secure: crate::cli::flag_or_env(
+							self.[<$chain_prefix _secure>],
+							concat!("BRIDGE_", stringify!([<$chain_prefix:upper _SECURE>])),
+						),
+						failover_endpoints: self
+							.[<$chain_prefix _failover_endpoint>]
+							.iter()
+							.map(|endpoint| (endpoint.host.clone(), endpoint.port))
+							.collect(),
+						transport: relay_substrate_client::TransportParams {
+							proxy: self.[<$chain_prefix _proxy>].clone(),
+							tls: relay_substrate_client::TlsParams {
+								ca_bundle: self.[<$chain_prefix _tls_ca_bundle>].clone(),
+								client_certificate: self.[<$chain_prefix _tls_client_certificate>].clone(),
+								client_key: self.[<$chain_prefix _tls_client_key>].clone(),
+							},
+						},
+					}
+				}
+
+				/// Return recovery policy of the relay loops client.
+				pub fn recovery_policy(&self) -> relay_utils::recovery::RecoveryPolicyParams {
+					self.[<$chain_prefix _recovery_policy>].clone()
+				}
+
+				/// Return expected genesis hash of the chain, if specified.
+				pub fn expected_genesis_hash<Chain: CliChain>(&self) -> anyhow::Result<Option<Chain::Hash>> {
+					self.[<$chain_prefix _genesis_hash>]
+						.as_ref()
+						.map(|hash| <Chain::Hash as codec::DecodeAll>::decode_all(&hash.0))
+						.transpose()
+						.map_err(|e| anyhow::format_err!("Invalid {} genesis hash: {:?}", Chain::NAME, e))
 				}
 			}
 		}
@@ -444,6 +1274,28 @@ mod tests {
 		assert_eq!(actual, expected)
 	}
 
+	#[test]
+	fn balance_is_cast_to_chain_balance() {
+		assert_eq!(Balance(u64::MAX as u128).cast::<u64>().unwrap(), u64::MAX);
+		assert!(Balance(u64::MAX as u128 + 1).cast::<u64>().is_err());
+		assert_eq!(Balance::from_native(42u64), Balance(42));
+	}
+
+	#[test]
+	fn balance_is_formatted_as_tokens() {
+		let token = relay_substrate_client::TokenProperties {
+			decimals: 9,
+			symbol: "MLAU".into(),
+		};
+		assert_eq!(Balance(1_500_000_000).format_tokens(&token), "1.5 MLAU");
+		assert_eq!(Balance(1_000_000_000_000).format_tokens(&token), "1,000 MLAU");
+		assert_eq!(Balance(1).format_tokens(&token), "0.000000001 MLAU");
+		assert_eq!(
+			Balance(42).format_tokens(&relay_substrate_client::TokenProperties::default()),
+			"42 UNIT"
+		);
+	}
+
 	#[test]
 	fn hex_bytes_display_matches_from_str_for_clap() {
 		// given
@@ -456,4 +1308,167 @@ mod tests {
 		// then
 		assert_eq!(hex.0, hex2.0);
 	}
+
+	#[test]
+	fn hex_bytes_are_parsed_with_and_without_prefix() {
+		assert_eq!(HexBytes::from_str("0x01020304"), Ok(HexBytes(vec![1, 2, 3, 4])));
+		assert_eq!(HexBytes::from_str("01020304"), Ok(HexBytes(vec![1, 2, 3, 4])));
+		assert!(HexBytes::from_str("0x0g").is_err());
+	}
+
+	#[test]
+	fn nonces_range_is_parsed() {
+		assert_eq!(NoncesRange::from_str("1..1"), Ok(NoncesRange(1..=1)));
+		assert_eq!(NoncesRange::from_str("5..10"), Ok(NoncesRange(5..=10)));
+
+		assert!(NoncesRange::from_str("0..10").is_err());
+		assert!(NoncesRange::from_str("10..5").is_err());
+		assert!(NoncesRange::from_str("5").is_err());
+		assert!(NoncesRange::from_str("5..=10").is_err());
+	}
+
+	#[test]
+	fn lanes_are_parsed() {
+		assert_eq!(Lanes::from_str("auto"), Ok(Lanes::Auto));
+		assert_eq!(Lanes::from_str("Auto"), Ok(Lanes::Auto));
+		assert_eq!(
+			Lanes::from_str("00000000,0x00000001"),
+			Ok(Lanes::Static(vec![LaneId([0, 0, 0, 0]), LaneId([0, 0, 0, 1])])),
+		);
+
+		assert!(Lanes::from_str("").is_err());
+		assert!(Lanes::from_str("auto,00000000").is_err());
+	}
+
+	#[test]
+	fn lane_messages_sender_is_parsed() {
+		let sender = "5sauUXUfPjmwxSgmb3tZ5d6yx24eZX4wWJ2JtVUBaQqFbvEU";
+
+		let parsed: LaneMessagesSender = format!("00000001:{}", sender).parse().unwrap();
+		assert_eq!(parsed.lane, LaneId([0, 0, 0, 1]));
+		assert_eq!(parsed.sender, AccountId::from_str(sender).unwrap());
+
+		assert!(LaneMessagesSender::from_str(sender).is_err());
+		assert!(LaneMessagesSender::from_str(&format!("0000000g:{}", sender)).is_err());
+	}
+
+	#[test]
+	fn node_endpoint_is_parsed() {
+		assert_eq!(
+			NodeEndpoint::from_str("rialto-node-1:9944"),
+			Ok(NodeEndpoint {
+				host: "rialto-node-1".into(),
+				port: 9944,
+			}),
+		);
+
+		assert!(NodeEndpoint::from_str("rialto-node-1").is_err());
+		assert!(NodeEndpoint::from_str(":9944").is_err());
+		assert!(NodeEndpoint::from_str("rialto-node-1:port").is_err());
+	}
+
+	fn test_directory(name: &str) -> PathBuf {
+		let path = std::env::temp_dir().join(format!("substrate-relay-{}-{}", name, std::process::id()));
+		let _ = std::fs::remove_dir_all(&path);
+		std::fs::create_dir_all(&path).unwrap();
+		path
+	}
+
+	#[test]
+	fn signer_is_read_from_file() {
+		let directory = test_directory("signer-file");
+		let signer_file = directory.join("signer");
+		std::fs::write(&signer_file, "//Alice\n").unwrap();
+
+		assert_eq!(read_signer_file(&signer_file).unwrap(), "//Alice");
+	}
+
+	#[test]
+	fn signer_is_read_from_keystore() {
+		let directory = test_directory("signer-keystore");
+		std::fs::write(directory.join("6163636f0001"), "\"//Alice\"").unwrap();
+
+		assert_eq!(read_signer_file(&directory).unwrap(), "//Alice");
+
+		std::fs::write(directory.join("6163636f0002"), "\"//Bob\"").unwrap();
+		assert!(read_signer_file(&directory).is_err());
+	}
+
+	#[test]
+	fn signer_and_signer_file_are_exclusive() {
+		use sp_core::Pair;
+
+		let directory = test_directory("signer-exclusive");
+		let signer_file = directory.join("signer");
+		std::fs::write(&signer_file, "//Alice").unwrap();
+
+		let params = SourceSigningParams::from_iter(vec![
+			"signing-params",
+			"--source-signer",
+			"//Bob",
+			"--source-signer-file",
+			signer_file.to_str().unwrap(),
+		]);
+		assert!(params.to_keypair::<relay_rialto_client::Rialto>().is_err());
+
+		let params = SourceSigningParams::from_iter(vec![
+			"signing-params",
+			"--source-signer-file",
+			signer_file.to_str().unwrap(),
+		]);
+		assert_eq!(
+			params.to_keypair::<relay_rialto_client::Rialto>().unwrap().public(),
+			sp_keyring::AccountKeyring::Alice.public(),
+		);
+	}
+
+	declare_chain_options!(EnvTest, env_test);
+
+	#[test]
+	fn options_are_read_from_environment() {
+		std::env::set_var("BRIDGE_ENV_TEST_HOST", "millau-node");
+		std::env::set_var("BRIDGE_ENV_TEST_PORT", "9944");
+		std::env::set_var("BRIDGE_ENV_TEST_SECURE", "true");
+
+		// command line arguments have priority over environment variables
+		let params = EnvTestConnectionParams::from_iter(vec!["connection-params", "--env-test-port", "9945"])
+			.to_connection_params();
+		assert_eq!(params.host, "millau-node");
+		assert_eq!(params.port, 9945);
+		assert!(params.secure);
+	}
+
+	#[test]
+	fn failover_endpoints_and_recovery_policy_are_parsed() {
+		let params = SourceConnectionParams::from_iter(vec![
+			"connection-params",
+			"--source-port",
+			"9944",
+			"--source-failover-endpoint",
+			"rialto-node-1:9944",
+			"--source-failover-endpoint",
+			"rialto-node-2:9945",
+			"--source-recovery-policy",
+			"reconnect(3),failover,abort",
+		]);
+		assert_eq!(
+			params.to_connection_params().failover_endpoints,
+			vec![("rialto-node-1".into(), 9944), ("rialto-node-2".into(), 9945)],
+		);
+		assert_eq!(params.recovery_policy(), "reconnect(3),failover,abort".parse().unwrap());
+
+		let params = SourceConnectionParams::from_iter(vec!["connection-params", "--source-port", "9944"]);
+		assert!(params.to_connection_params().failover_endpoints.is_empty());
+		assert_eq!(params.recovery_policy(), Default::default());
+	}
+
+	#[test]
+	fn env_flag_values_are_parsed() {
+		assert!(is_env_flag_set("1"));
+		assert!(is_env_flag_set("true"));
+		assert!(is_env_flag_set("TRUE"));
+		assert!(!is_env_flag_set("0"));
+		assert!(!is_env_flag_set("false"));
+		assert!(!is_env_flag_set(""));
+	}
 }
@@ -0,0 +1,253 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fan-out relays support.
+//!
+//! The fan-out relay serves several bridges that are sharing the same source chain. There's
+//! only one connection to the source node, so all relays are sharing the same headers cache
+//! and the same justifications subscription.
+//!
+//! To add new target to the existing fan-out relay, you must:
+//!
+//! 1) add `declare_chain_options!(...)` for the target chain;
+//! 2) add target variant to the fan-out targets enum (e.g. `MillauFanOutTarget`);
+//! 3) start relays of the new target in the fan-out `run` method.
+
+use crate::chains::millau_headers_to_rialto_parachain::MillauFinalityToRialtoParachain;
+use crate::chains::millau_rialto_headers_and_messages::MillauRialtoHeadersAndMessagesBridge;
+use crate::cli::{relay_headers_and_messages::HeadersAndMessagesSharedParams, AccountId, CliChain};
+use crate::declare_chain_options;
+use crate::headers_and_messages::HeadersAndMessagesRelayParams;
+use crate::relay_info::{register_relay_info, RelayInfoMode};
+
+use futures::{future::BoxFuture, FutureExt};
+use relay_millau_client::Millau;
+use relay_rialto_client::Rialto;
+use relay_rialto_parachain_client::RialtoParachain;
+use relay_substrate_client::Chain;
+use relay_utils::control::RelayControl;
+use structopt::{clap::arg_enum, StructOpt};
+
+/// Start relays from one source chain to several target chains.
+#[derive(StructOpt)]
+pub enum RelayFanOut {
+	Millau(MillauFanOut),
+}
+
+// TODO [#851] Use kebab-case.
+arg_enum! {
+	#[derive(Debug, PartialEq, Eq)]
+	/// Target of the Millau fan-out relay.
+	pub enum MillauFanOutTarget {
+		Rialto,
+		RialtoParachain,
+	}
+}
+
+/// Millau fan-out relay params.
+#[derive(StructOpt)]
+pub struct MillauFanOut {
+	/// Target chains that are served by the relay. Millau <-> Rialto bridge is served by the
+	/// complex headers+messages relay. Only Millau headers are relayed to the RialtoParachain.
+	#[structopt(
+		long,
+		env = "BRIDGE_TARGET",
+		use_delimiter = true,
+		required = true,
+		possible_values = &MillauFanOutTarget::variants(),
+		case_insensitive = true,
+	)]
+	target: Vec<MillauFanOutTarget>,
+	#[structopt(flatten)]
+	shared: HeadersAndMessagesSharedParams,
+	#[structopt(flatten)]
+	millau: MillauConnectionParams,
+	#[structopt(flatten)]
+	millau_sign: MillauSigningParams,
+	#[structopt(flatten)]
+	rialto: RialtoConnectionParams,
+	#[structopt(flatten)]
+	rialto_sign: RialtoSigningParams,
+	#[structopt(flatten)]
+	rialto_parachain: RialtoParachainConnectionParams,
+	#[structopt(flatten)]
+	rialto_parachain_sign: RialtoParachainSigningParams,
+}
+
+// All supported chains.
+declare_chain_options!(Millau, millau);
+declare_chain_options!(Rialto, rialto);
+declare_chain_options!(RialtoParachain, rialto_parachain);
+
+impl RelayFanOut {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		match self {
+			RelayFanOut::Millau(params) => params.run().await,
+		}
+	}
+}
+
+impl MillauFanOut {
+	/// Run the Millau fan-out relay.
+	async fn run(self) -> anyhow::Result<()> {
+		let only_mandatory_headers =
+			crate::cli::flag_or_env(self.shared.only_mandatory_headers, "BRIDGE_ONLY_MANDATORY_HEADERS");
+
+		// the same client is used by all relays => they're sharing headers cache and subscriptions
+		let millau_client = self.millau.to_client::<Millau>().await?;
+
+		self.shared.alerts_params.initialize()?;
+		let control = RelayControl::default();
+		control
+			.clone()
+			.expose(self.shared.control_params.into())
+			.await
+			.map_err(|e| anyhow::format_err!("{}", e))?;
+		let metrics_params = relay_utils::relay_metrics(None, self.shared.prometheus_params.into()).into_params();
+
+		let mut signer_locks = Vec::new();
+		let mut relays: Vec<BoxFuture<'static, anyhow::Result<()>>> = Vec::new();
+
+		if self.target.contains(&MillauFanOutTarget::Rialto) {
+			let millau_sign = self.millau_sign.to_keypair::<Millau>()?;
+			let millau_transactions_mortality = self.millau_sign.transactions_mortality()?;
+			let rialto_client = self.rialto.to_client::<Rialto>().await?;
+			let rialto_sign = self.rialto_sign.to_keypair::<Rialto>()?;
+			let rialto_transactions_mortality = self.rialto_sign.transactions_mortality()?;
+			signer_locks.push(self.millau_sign.lock_signer::<Millau>(&millau_client).await?);
+			signer_locks.push(self.rialto_sign.lock_signer::<Rialto>(&rialto_client).await?);
+			self.millau_sign.start_balance_alert::<Millau>(&millau_client)?;
+			self.rialto_sign.start_balance_alert::<Rialto>(&rialto_client)?;
+			self.millau_sign
+				.register_balance_metric::<Millau>(&millau_client, &metrics_params)?;
+			self.rialto_sign
+				.register_balance_metric::<Rialto>(&rialto_client, &metrics_params)?;
+			self.millau_sign.start_rewards_auto_claim::<Millau>(&millau_client)?;
+			self.rialto_sign.start_rewards_auto_claim::<Rialto>(&rialto_client)?;
+			self.millau_sign.start_auto_funding::<Millau>(&millau_client)?;
+			self.rialto_sign.start_auto_funding::<Rialto>(&rialto_client)?;
+
+			relays.push(
+				crate::headers_and_messages::run::<MillauRialtoHeadersAndMessagesBridge>(
+					HeadersAndMessagesRelayParams {
+						left_client: millau_client.clone(),
+						left_sign: millau_sign,
+						left_transactions_mortality: millau_transactions_mortality,
+						left_proxied_account: self.millau_sign.proxied_account::<Millau>(),
+						left_max_tip: self.millau_sign.max_tip::<Millau>()?,
+						right_client: rialto_client,
+						right_sign: rialto_sign,
+						right_transactions_mortality: rialto_transactions_mortality,
+						right_proxied_account: self.rialto_sign.proxied_account::<Rialto>(),
+						right_max_tip: self.rialto_sign.max_tip::<Rialto>()?,
+						only_mandatory_headers,
+						lanes: self.shared.lane.clone(),
+						relayer_mode: self.shared.relayer_mode.into(),
+						max_confirmations_delay: self.shared.max_confirmations_delay,
+						piggyback_confirmations: crate::cli::flag_or_env(
+							self.shared.piggyback_confirmations,
+							"BRIDGE_PIGGYBACK_CONFIRMATIONS",
+						),
+						max_delivery_transactions_in_flight: self.shared.max_delivery_transactions_in_flight,
+						only_messages_from: self.shared.only_messages_from.iter().map(AccountId::raw_id).collect(),
+						only_lane_messages_from: self
+							.shared
+							.only_lane_messages_from
+							.iter()
+							.map(|lane_sender| (lane_sender.lane, lane_sender.sender.raw_id()))
+							.collect(),
+						control: control.clone(),
+						left_recovery_policy: self.millau.recovery_policy(),
+						right_recovery_policy: self.rialto.recovery_policy(),
+						// metrics are exposed once, by the fan-out relay itself
+						metrics_params: metrics_params.clone().disable(),
+					},
+				)
+				.boxed(),
+			);
+		}
+
+		if self.target.contains(&MillauFanOutTarget::RialtoParachain) {
+			// RialtoParachain runtime has no proxy pallet
+			if self
+				.rialto_parachain_sign
+				.proxied_account::<RialtoParachain>()
+				.is_some()
+			{
+				return Err(anyhow::format_err!(
+					"{} chain doesn't support submitting transactions through proxy accounts",
+					RialtoParachain::NAME,
+				));
+			}
+
+			let rialto_parachain_client = self.rialto_parachain.to_client::<RialtoParachain>().await?;
+			let rialto_parachain_sign = self.rialto_parachain_sign.to_keypair::<RialtoParachain>()?;
+			let rialto_parachain_transactions_mortality = self.rialto_parachain_sign.transactions_mortality()?;
+			signer_locks.push(
+				self.rialto_parachain_sign
+					.lock_signer::<RialtoParachain>(&rialto_parachain_client)
+					.await?,
+			);
+			self.rialto_parachain_sign
+				.start_balance_alert::<RialtoParachain>(&rialto_parachain_client)?;
+			self.rialto_parachain_sign
+				.register_balance_metric::<RialtoParachain>(&rialto_parachain_client, &metrics_params)?;
+
+			let finality_metrics_params = metrics_params
+				.clone()
+				.disable()
+				.metrics_prefix(finality_relay::metrics_prefix::<MillauFinalityToRialtoParachain>());
+			register_relay_info(
+				&finality_metrics_params,
+				Millau::NAME,
+				RialtoParachain::NAME,
+				&[],
+				RelayInfoMode::Headers,
+			)
+			.map_err(|e| anyhow::format_err!("{}", e))?;
+
+			relays.push(
+				crate::finality_pipeline::run(
+					MillauFinalityToRialtoParachain::new(
+						rialto_parachain_client.clone(),
+						rialto_parachain_sign,
+						rialto_parachain_transactions_mortality,
+					)
+					.with_target_max_tip(self.rialto_parachain_sign.max_tip::<RialtoParachain>()?),
+					millau_client.clone(),
+					rialto_parachain_client,
+					false,
+					only_mandatory_headers,
+					None,
+					1,
+					rialto_parachain_transactions_mortality,
+					self.millau.recovery_policy(),
+					self.rialto_parachain.recovery_policy(),
+					finality_metrics_params,
+				)
+				.boxed(),
+			);
+		}
+
+		relay_utils::relay_metrics(None, metrics_params)
+			.expose()
+			.await
+			.map_err(|e| anyhow::format_err!("{}", e))?;
+
+		futures::future::select_all(relays).await.0
+	}
+}
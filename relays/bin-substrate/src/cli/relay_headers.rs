@@ -14,8 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::cli::{PrometheusParams, SourceConnectionParams, TargetConnectionParams, TargetSigningParams};
+use crate::cli::{AlertsParams, PrometheusParams, SourceConnectionParams, TargetConnectionParams, TargetSigningParams};
 use crate::finality_pipeline::SubstrateFinalitySyncPipeline;
+use crate::relay_info::{register_relay_info, RelayInfoMode};
+
+use relay_substrate_client::Chain;
 use structopt::{clap::arg_enum, StructOpt};
 
 /// Start headers relayer process.
@@ -30,8 +33,24 @@ pub struct RelayHeaders {
 	target: TargetConnectionParams,
 	#[structopt(flatten)]
 	target_sign: TargetSigningParams,
+	/// If passed, only mandatory headers (headers that are changing the GRANDPA authorities set)
+	/// are relayed.
+	#[structopt(long)]
+	only_mandatory_headers: bool,
+	/// Maximal number of source headers that are scanned at every iteration. If not specified,
+	/// all headers up to the best finalized source header are scanned.
+	///
+	/// The value must not be less than the number of headers between persistent finality proofs
+	/// (e.g. session length), or the relay may stall.
+	#[structopt(long, env = "BRIDGE_MAX_HEADERS_TO_SCAN")]
+	max_headers_to_scan: Option<u32>,
+	/// Maximal number of finality proofs that are submitted at every iteration.
+	#[structopt(long, env = "BRIDGE_MAX_HEADERS_TO_SUBMIT", default_value = "1")]
+	max_headers_to_submit: u32,
 	#[structopt(flatten)]
 	prometheus_params: PrometheusParams,
+	#[structopt(flatten)]
+	alerts_params: AlertsParams,
 }
 
 // TODO [#851] Use kebab-case.
@@ -40,6 +59,7 @@ arg_enum! {
 	/// Headers relay bridge.
 	pub enum RelayHeadersBridge {
 		MillauToRialto,
+		MillauToRialtoParachain,
 		RialtoToMillau,
 		WestendToMillau,
 		RococoToWococo,
@@ -54,6 +74,15 @@ macro_rules! select_bridge {
 				type Source = relay_millau_client::Millau;
 				type Target = relay_rialto_client::Rialto;
 				type Finality = crate::chains::millau_headers_to_rialto::MillauFinalityToRialto;
+				const TARGET_SUPPORTS_PROXY: bool = true;
+
+				$generic
+			}
+			RelayHeadersBridge::MillauToRialtoParachain => {
+				type Source = relay_millau_client::Millau;
+				type Target = relay_rialto_parachain_client::RialtoParachain;
+				type Finality = crate::chains::millau_headers_to_rialto_parachain::MillauFinalityToRialtoParachain;
+				const TARGET_SUPPORTS_PROXY: bool = false;
 
 				$generic
 			}
@@ -61,6 +90,7 @@ macro_rules! select_bridge {
 				type Source = relay_rialto_client::Rialto;
 				type Target = relay_millau_client::Millau;
 				type Finality = crate::chains::rialto_headers_to_millau::RialtoFinalityToMillau;
+				const TARGET_SUPPORTS_PROXY: bool = true;
 
 				$generic
 			}
@@ -68,6 +98,7 @@ macro_rules! select_bridge {
 				type Source = relay_westend_client::Westend;
 				type Target = relay_millau_client::Millau;
 				type Finality = crate::chains::westend_headers_to_millau::WestendFinalityToMillau;
+				const TARGET_SUPPORTS_PROXY: bool = true;
 
 				$generic
 			}
@@ -75,6 +106,7 @@ macro_rules! select_bridge {
 				type Source = relay_rococo_client::Rococo;
 				type Target = relay_wococo_client::Wococo;
 				type Finality = crate::chains::rococo_headers_to_wococo::RococoFinalityToWococo;
+				const TARGET_SUPPORTS_PROXY: bool = false;
 
 				$generic
 			}
@@ -82,6 +114,7 @@ macro_rules! select_bridge {
 				type Source = relay_wococo_client::Wococo;
 				type Target = relay_rococo_client::Rococo;
 				type Finality = crate::chains::wococo_headers_to_rococo::WococoFinalityToRococo;
+				const TARGET_SUPPORTS_PROXY: bool = false;
 
 				$generic
 			}
@@ -96,14 +129,41 @@ impl RelayHeaders {
 			let source_client = self.source.to_client::<Source>().await?;
 			let target_client = self.target.to_client::<Target>().await?;
 			let target_sign = self.target_sign.to_keypair::<Target>()?;
-			let metrics_params = Finality::customize_metrics(self.prometheus_params.into())?;
+			let target_transactions_mortality = self.target_sign.transactions_mortality()?;
+			let target_proxied_account = self.target_sign.proxied_account::<Target>();
+			if target_proxied_account.is_some() && !TARGET_SUPPORTS_PROXY {
+				return Err(anyhow::format_err!(
+					"{} chain doesn't support submitting transactions through proxy accounts",
+					Target::NAME,
+				));
+			}
+			let _target_signer_lock = self.target_sign.lock_signer::<Target>(&target_client).await?;
+			self.alerts_params.initialize()?;
+			self.target_sign.start_balance_alert::<Target>(&target_client)?;
+			let metrics_params = relay_utils::relay_metrics(
+				Some(finality_relay::metrics_prefix::<Finality>()),
+				Finality::customize_metrics(self.prometheus_params.into())?,
+			)
+			.into_params();
+			register_relay_info(&metrics_params, Source::NAME, Target::NAME, &[], RelayInfoMode::Headers)
+				.map_err(|e| anyhow::format_err!("{}", e))?;
+			self.target_sign
+				.register_balance_metric::<Target>(&target_client, &metrics_params)?;
 			Finality::start_relay_guards(&target_client);
 
 			crate::finality_pipeline::run(
-				Finality::new(target_client.clone(), target_sign),
+				Finality::new(target_client.clone(), target_sign, target_transactions_mortality)
+					.with_target_proxied_account(target_proxied_account)
+					.with_target_max_tip(self.target_sign.max_tip::<Target>()?),
 				source_client,
 				target_client,
 				false,
+				crate::cli::flag_or_env(self.only_mandatory_headers, "BRIDGE_ONLY_MANDATORY_HEADERS"),
+				self.max_headers_to_scan,
+				self.max_headers_to_submit,
+				target_transactions_mortality,
+				self.source.recovery_policy(),
+				self.target.recovery_policy(),
 				metrics_params,
 			)
 			.await
@@ -20,15 +20,17 @@
 //!
 //! 1) ensure that there's a `declare_chain_options!(...)` for both chains;
 //! 2) add `declare_bridge_options!(...)` for the bridge;
-//! 3) add bridge support to the `select_bridge! { ... }` macro.
+//! 3) implement `HeadersAndMessagesBridge` for the bridge (see `crate::headers_and_messages`);
+//! 4) add bridge support to the `select_bridge! { ... }` macro.
 
-use crate::cli::{CliChain, HexLaneId, PrometheusParams};
+use crate::cli::{
+	relay_messages::RelayerMode, AccountId, AlertsParams, CliChain, ControlParams, LaneMessagesSender, PrometheusParams,
+};
 use crate::declare_chain_options;
-use crate::messages_lane::MessagesRelayParams;
-use crate::on_demand_headers::OnDemandHeadersRelay;
+use crate::headers_and_messages::HeadersAndMessagesRelayParams;
 
-use futures::{FutureExt, TryFutureExt};
-use relay_utils::metrics::MetricsParams;
+use bp_messages::LaneId;
+use relay_utils::control::RelayControl;
 use structopt::StructOpt;
 
 /// Start headers+messages relayer process.
@@ -40,11 +42,48 @@ pub enum RelayHeadersAndMessages {
 /// Parameters that have the same names across all bridges.
 #[derive(StructOpt)]
 pub struct HeadersAndMessagesSharedParams {
-	/// Hex-encoded lane identifiers that should be served by the complex relay.
-	#[structopt(long, default_value = "00000000")]
-	lane: Vec<HexLaneId>,
+	/// Lane identifiers (hex-encoded or well-known lane names) that should be served by the complex relay.
+	#[structopt(long, env = "BRIDGE_LANE", use_delimiter = true, default_value = "00000000")]
+	pub(crate) lane: Vec<LaneId>,
+	/// If passed, only mandatory headers (headers that are changing the GRANDPA authorities set)
+	/// are relayed, unless other headers are required by messages relays.
+	#[structopt(long)]
+	pub(crate) only_mandatory_headers: bool,
+	/// Relayer operating mode of messages relays.
+	#[structopt(
+		long,
+		env = "BRIDGE_RELAYER_MODE",
+		possible_values = &RelayerMode::variants(),
+		case_insensitive = true,
+		default_value = "Rational",
+	)]
+	pub(crate) relayer_mode: RelayerMode,
+	/// Maximal number of blocks for which the messages receiving confirmations may be delayed, so that
+	/// several delivered ranges are confirmed by a single transaction. Zero means no delay.
+	#[structopt(long, env = "BRIDGE_MAX_CONFIRMATIONS_DELAY", default_value = "0")]
+	pub(crate) max_confirmations_delay: u32,
+	/// Confirm delivery of messages in one direction by messages delivery transactions in the opposite
+	/// direction, using `utility::batch_all`.
+	#[structopt(long)]
+	pub(crate) piggyback_confirmations: bool,
+	/// Maximal number of messages delivery transactions (delivering consecutive ranges of messages)
+	/// that may be submitted by every messages relay before previous transactions are mined.
+	#[structopt(long, env = "BRIDGE_MAX_DELIVERY_TRANSACTIONS_IN_FLIGHT", default_value = "1")]
+	pub(crate) max_delivery_transactions_in_flight: usize,
+	/// SS58-encoded account. If specified, relays of all lanes only deliver messages that are sent
+	/// by given accounts (and messages that precede them). May be specified multiple times.
+	#[structopt(long, env = "BRIDGE_ONLY_MESSAGES_FROM", use_delimiter = true)]
+	pub(crate) only_messages_from: Vec<AccountId>,
+	/// `<LANE>:<ACCOUNT>` pair. If specified, relays of given lane only deliver messages that are sent
+	/// by given account (and messages that precede them). May be specified multiple times.
+	#[structopt(long, env = "BRIDGE_ONLY_LANE_MESSAGES_FROM", use_delimiter = true)]
+	pub(crate) only_lane_messages_from: Vec<LaneMessagesSender>,
 	#[structopt(flatten)]
-	prometheus_params: PrometheusParams,
+	pub(crate) prometheus_params: PrometheusParams,
+	#[structopt(flatten)]
+	pub(crate) control_params: ControlParams,
+	#[structopt(flatten)]
+	pub(crate) alerts_params: AlertsParams,
 }
 
 // The reason behind this macro is that 'normal' relays are using source and target chains terminology,
@@ -90,17 +129,7 @@ macro_rules! select_bridge {
 				type Left = relay_millau_client::Millau;
 				type Right = relay_rialto_client::Rialto;
 
-				type LeftToRightFinality = crate::chains::millau_headers_to_rialto::MillauFinalityToRialto;
-				type RightToLeftFinality = crate::chains::rialto_headers_to_millau::RialtoFinalityToMillau;
-
-				type LeftToRightMessages = crate::chains::millau_messages_to_rialto::MillauMessagesToRialto;
-				type RightToLeftMessages = crate::chains::rialto_messages_to_millau::RialtoMessagesToMillau;
-
-				const MAX_MISSING_LEFT_HEADERS_AT_RIGHT: bp_millau::BlockNumber = bp_millau::SESSION_LENGTH;
-				const MAX_MISSING_RIGHT_HEADERS_AT_LEFT: bp_rialto::BlockNumber = bp_rialto::SESSION_LENGTH;
-
-				use crate::chains::millau_messages_to_rialto::run as left_to_right_messages;
-				use crate::chains::rialto_messages_to_millau::run as right_to_left_messages;
+				type Bridge = crate::chains::millau_rialto_headers_and_messages::MillauRialtoHeadersAndMessagesBridge;
 
 				$generic
 			}
@@ -122,70 +151,70 @@ impl RelayHeadersAndMessages {
 
 			let left_client = params.left.to_client::<Left>().await?;
 			let left_sign = params.left_sign.to_keypair::<Left>()?;
+			let left_transactions_mortality = params.left_sign.transactions_mortality()?;
 			let right_client = params.right.to_client::<Right>().await?;
 			let right_sign = params.right_sign.to_keypair::<Right>()?;
-
-			let lanes = params.shared.lane;
-
-			let metrics_params: MetricsParams = params.shared.prometheus_params.into();
-			let metrics_params = relay_utils::relay_metrics(None, metrics_params).into_params();
-
-			let left_to_right_on_demand_headers = OnDemandHeadersRelay::new(
-				left_client.clone(),
-				right_client.clone(),
-				LeftToRightFinality::new(right_client.clone(), right_sign.clone()),
-				MAX_MISSING_LEFT_HEADERS_AT_RIGHT,
-			);
-			let right_to_left_on_demand_headers = OnDemandHeadersRelay::new(
-				right_client.clone(),
-				left_client.clone(),
-				RightToLeftFinality::new(left_client.clone(), left_sign.clone()),
-				MAX_MISSING_RIGHT_HEADERS_AT_LEFT,
-			);
-
-			// Need 2x capacity since we consider both directions for each lane
-			let mut message_relays = Vec::with_capacity(lanes.len() * 2);
-			for lane in lanes {
-				let lane = lane.into();
-				let left_to_right_messages = left_to_right_messages(MessagesRelayParams {
-					source_client: left_client.clone(),
-					source_sign: left_sign.clone(),
-					target_client: right_client.clone(),
-					target_sign: right_sign.clone(),
-					source_to_target_headers_relay: Some(left_to_right_on_demand_headers.clone()),
-					target_to_source_headers_relay: Some(right_to_left_on_demand_headers.clone()),
-					lane_id: lane,
-					metrics_params: metrics_params.clone().disable().metrics_prefix(
-						messages_relay::message_lane_loop::metrics_prefix::<LeftToRightMessages>(&lane),
-					),
-				})
-				.map_err(|e| anyhow::format_err!("{}", e))
-				.boxed();
-				let right_to_left_messages = right_to_left_messages(MessagesRelayParams {
-					source_client: right_client.clone(),
-					source_sign: right_sign.clone(),
-					target_client: left_client.clone(),
-					target_sign: left_sign.clone(),
-					source_to_target_headers_relay: Some(right_to_left_on_demand_headers.clone()),
-					target_to_source_headers_relay: Some(left_to_right_on_demand_headers.clone()),
-					lane_id: lane,
-					metrics_params: metrics_params.clone().disable().metrics_prefix(
-						messages_relay::message_lane_loop::metrics_prefix::<RightToLeftMessages>(&lane),
-					),
-				})
-				.map_err(|e| anyhow::format_err!("{}", e))
-				.boxed();
-
-				message_relays.push(left_to_right_messages);
-				message_relays.push(right_to_left_messages);
-			}
-
-			relay_utils::relay_metrics(None, metrics_params)
-				.expose()
+			let right_transactions_mortality = params.right_sign.transactions_mortality()?;
+			let _left_signer_lock = params.left_sign.lock_signer::<Left>(&left_client).await?;
+			let _right_signer_lock = params.right_sign.lock_signer::<Right>(&right_client).await?;
+			params.shared.alerts_params.initialize()?;
+			params.left_sign.start_balance_alert::<Left>(&left_client)?;
+			params.right_sign.start_balance_alert::<Right>(&right_client)?;
+			params.left_sign.start_rewards_auto_claim::<Left>(&left_client)?;
+			params.right_sign.start_rewards_auto_claim::<Right>(&right_client)?;
+			params.left_sign.start_auto_funding::<Left>(&left_client)?;
+			params.right_sign.start_auto_funding::<Right>(&right_client)?;
+			let control = RelayControl::default();
+			control
+				.clone()
+				.expose(params.shared.control_params.into())
 				.await
 				.map_err(|e| anyhow::format_err!("{}", e))?;
-
-			futures::future::select_all(message_relays).await.0
+			// the registry is created here, so that signer balance metrics are exposed by the relay
+			let metrics_params = relay_utils::relay_metrics(None, params.shared.prometheus_params.into()).into_params();
+			params
+				.left_sign
+				.register_balance_metric::<Left>(&left_client, &metrics_params)?;
+			params
+				.right_sign
+				.register_balance_metric::<Right>(&right_client, &metrics_params)?;
+
+			crate::headers_and_messages::run::<Bridge>(HeadersAndMessagesRelayParams {
+				left_client,
+				left_sign,
+				left_transactions_mortality,
+				left_proxied_account: params.left_sign.proxied_account::<Left>(),
+				left_max_tip: params.left_sign.max_tip::<Left>()?,
+				right_client,
+				right_sign,
+				right_transactions_mortality,
+				right_proxied_account: params.right_sign.proxied_account::<Right>(),
+				right_max_tip: params.right_sign.max_tip::<Right>()?,
+				only_mandatory_headers: crate::cli::flag_or_env(
+					params.shared.only_mandatory_headers,
+					"BRIDGE_ONLY_MANDATORY_HEADERS",
+				),
+				lanes: params.shared.lane,
+				relayer_mode: params.shared.relayer_mode.into(),
+				max_confirmations_delay: params.shared.max_confirmations_delay,
+				piggyback_confirmations: crate::cli::flag_or_env(
+					params.shared.piggyback_confirmations,
+					"BRIDGE_PIGGYBACK_CONFIRMATIONS",
+				),
+				max_delivery_transactions_in_flight: params.shared.max_delivery_transactions_in_flight,
+				only_messages_from: params.shared.only_messages_from.iter().map(AccountId::raw_id).collect(),
+				only_lane_messages_from: params
+					.shared
+					.only_lane_messages_from
+					.into_iter()
+					.map(|lane_sender| (lane_sender.lane, lane_sender.sender.raw_id()))
+					.collect(),
+				control,
+				left_recovery_policy: params.left.recovery_policy(),
+				right_recovery_policy: params.right.recovery_policy(),
+				metrics_params,
+			})
+			.await
 		})
 	}
 }
@@ -16,13 +16,42 @@
 
 use crate::cli::bridge::FullBridge;
 use crate::cli::{
-	HexLaneId, PrometheusParams, SourceConnectionParams, SourceSigningParams, TargetConnectionParams,
-	TargetSigningParams,
+	AccountId, AlertsParams, ControlParams, Lanes, NoncesRange, PrometheusParams, SourceConnectionParams,
+	SourceSigningParams, TargetConnectionParams, TargetSigningParams,
 };
+use crate::messages_config::{MessagesRelayConfig, ReloadableMessagesRelayConfig};
 use crate::messages_lane::MessagesRelayParams;
+use crate::relay_info::{register_relay_info, RelayInfoMode};
 use crate::select_full_bridge;
 
-use structopt::StructOpt;
+use bp_messages::LaneId;
+use futures::FutureExt;
+use messages_relay::message_lane_loop::ForcedDelivery;
+use relay_substrate_client::Chain;
+use relay_utils::control::RelayControl;
+use std::path::PathBuf;
+use structopt::{clap::arg_enum, StructOpt};
+
+arg_enum! {
+	#[derive(Debug, Clone, Copy)]
+	/// Relayer operating mode.
+	///
+	/// - `Altruistic` delivers as many messages as fit into every delivery transaction.
+	/// - `Rational` delivers messages that bring the maximal profit (reward minus delivery transaction cost).
+	pub enum RelayerMode {
+		Altruistic,
+		Rational,
+	}
+}
+
+impl From<RelayerMode> for messages_relay::message_lane_loop::RelayerMode {
+	fn from(mode: RelayerMode) -> Self {
+		match mode {
+			RelayerMode::Altruistic => Self::Altruistic,
+			RelayerMode::Rational => Self::Rational,
+		}
+	}
+}
 
 /// Start messages relayer process.
 #[derive(StructOpt)]
@@ -30,9 +59,50 @@ pub struct RelayMessages {
 	/// A bridge instance to relay messages for.
 	#[structopt(possible_values = &FullBridge::variants(), case_insensitive = true)]
 	bridge: FullBridge,
-	/// Hex-encoded lane id that should be served by the relay. Defaults to `00000000`.
-	#[structopt(long, default_value = "00000000")]
-	lane: HexLaneId,
+	/// Lane id (hex-encoded or well-known lane name) that should be served by the relay. Defaults to `00000000`.
+	#[structopt(long, env = "BRIDGE_LANE", default_value = "00000000")]
+	lane: LaneId,
+	/// Lanes that should be served by the relay: either `auto`, or comma-separated list of lanes. If specified,
+	/// the `--lane` option is ignored. In `auto` mode, lanes are discovered from the source chain storage: relay
+	/// of the lane is started when messages are sent over the lane and paused when all messages are delivered
+	/// and confirmed.
+	#[structopt(long, env = "BRIDGE_LANES")]
+	lanes: Option<Lanes>,
+	/// Relayer operating mode.
+	#[structopt(
+		long,
+		env = "BRIDGE_RELAYER_MODE",
+		possible_values = &RelayerMode::variants(),
+		case_insensitive = true,
+		default_value = "Rational",
+	)]
+	relayer_mode: RelayerMode,
+	/// Maximal number of source chain blocks for which the messages receiving confirmations may be delayed,
+	/// so that several delivered ranges are confirmed by a single transaction. Zero means no delay.
+	#[structopt(long, env = "BRIDGE_MAX_CONFIRMATIONS_DELAY", default_value = "0")]
+	max_confirmations_delay: u32,
+	/// Confirm delivery of messages of the reverse lane (target -> source lane with the same id) by
+	/// messages delivery transactions, using `utility::batch_all`.
+	#[structopt(long)]
+	piggyback_confirmations: bool,
+	/// Maximal number of messages delivery transactions (delivering consecutive ranges of messages)
+	/// that may be submitted before previous transactions are mined.
+	#[structopt(long, env = "BRIDGE_MAX_DELIVERY_TRANSACTIONS_IN_FLIGHT", default_value = "1")]
+	max_delivery_transactions_in_flight: usize,
+	/// SS58-encoded source chain account. If specified, the relay only delivers messages that are sent
+	/// by given accounts (and messages that precede them). May be specified multiple times.
+	#[structopt(long, env = "BRIDGE_ONLY_MESSAGES_FROM", use_delimiter = true)]
+	only_messages_from: Vec<AccountId>,
+	/// Inclusive range of message nonces (`<BEGIN>..<END>`) that must be delivered before the relay
+	/// is started, regardless of the relayer mode and the delivery race state. Used to unstick the lane.
+	#[structopt(long, env = "BRIDGE_RELAY_NONCES")]
+	relay_nonces: Option<NoncesRange>,
+	/// Path to the TOML configuration file. Values of `lanes`, `relayer-mode`, `max-confirmations-delay`
+	/// and `max-delivery-transactions-in-flight` from the file override command line values. The file is
+	/// read again when the relay is asked to reload its configuration using the `relay_reloadConfig`
+	/// control call. Updated values are applied without restarting lane relays.
+	#[structopt(long, env = "BRIDGE_RELAY_CONFIG")]
+	config: Option<PathBuf>,
 	#[structopt(flatten)]
 	source: SourceConnectionParams,
 	#[structopt(flatten)]
@@ -43,6 +113,10 @@ pub struct RelayMessages {
 	target_sign: TargetSigningParams,
 	#[structopt(flatten)]
 	prometheus_params: PrometheusParams,
+	#[structopt(flatten)]
+	control_params: ControlParams,
+	#[structopt(flatten)]
+	alerts_params: AlertsParams,
 }
 
 impl RelayMessages {
@@ -51,21 +125,202 @@ impl RelayMessages {
 		select_full_bridge!(self.bridge, {
 			let source_client = self.source.to_client::<Source>().await?;
 			let source_sign = self.source_sign.to_keypair::<Source>()?;
+			let source_transactions_mortality = self.source_sign.transactions_mortality()?;
 			let target_client = self.target.to_client::<Target>().await?;
 			let target_sign = self.target_sign.to_keypair::<Target>()?;
+			let target_transactions_mortality = self.target_sign.transactions_mortality()?;
+			let _source_signer_lock = self.source_sign.lock_signer::<Source>(&source_client).await?;
+			let _target_signer_lock = self.target_sign.lock_signer::<Target>(&target_client).await?;
+			self.alerts_params.initialize()?;
+			self.source_sign.start_balance_alert::<Source>(&source_client)?;
+			self.target_sign.start_balance_alert::<Target>(&target_client)?;
+			self.target_sign.start_rewards_auto_claim::<Target>(&target_client)?;
+			self.source_sign.start_auto_funding::<Source>(&source_client)?;
+			self.target_sign.start_auto_funding::<Target>(&target_client)?;
+			let control = RelayControl::default();
+			control
+				.clone()
+				.expose(self.control_params.into())
+				.await
+				.map_err(|e| anyhow::format_err!("{}", e))?;
+
+			let cli_config = MessagesRelayConfig {
+				lanes: self.lanes,
+				relayer_mode: self.relayer_mode.into(),
+				max_confirmations_delay: self.max_confirmations_delay,
+				max_delivery_transactions_in_flight: self.max_delivery_transactions_in_flight,
+			};
+			let config = match self.config {
+				Some(ref path) => cli_config.clone().override_from_file(path)?,
+				None => cli_config.clone(),
+			};
+			let relayer_mode = config.relayer_mode;
+			let lanes = config.lanes.clone();
+			let config = ReloadableMessagesRelayConfig::from(config);
+			if let Some(path) = self.config {
+				async_std::task::spawn(crate::messages_config::serve_reload_requests(
+					control.clone(),
+					path,
+					cli_config,
+					config.clone(),
+				));
+			}
+
+			let source_proxied_account = self.source_sign.proxied_account::<Source>();
+			let target_proxied_account = self.target_sign.proxied_account::<Target>();
+			let target_max_tip = self.target_sign.max_tip::<Target>()?;
+			let piggyback_confirmations =
+				crate::cli::flag_or_env(self.piggyback_confirmations, "BRIDGE_PIGGYBACK_CONFIRMATIONS");
+			let only_messages_from = self
+				.only_messages_from
+				.iter()
+				.map(|account| account.raw_id().into())
+				.collect::<Vec<_>>();
+
+			let lanes = match lanes {
+				Some(lanes) => lanes,
+				None => {
+					let lane_id = self.lane;
+					let metrics_params = relay_utils::relay_metrics(
+						Some(messages_relay::message_lane_loop::metrics_prefix::<MessagesLane>(
+							&lane_id,
+						)),
+						self.prometheus_params.into(),
+					)
+					.into_params();
+					register_relay_info(
+						&metrics_params,
+						Source::NAME,
+						Target::NAME,
+						&[lane_id],
+						RelayInfoMode::Messages(relayer_mode),
+					)
+					.map_err(|e| anyhow::format_err!("{}", e))?;
+					self.source_sign
+						.register_balance_metric::<Source>(&source_client, &metrics_params)?;
+					self.target_sign
+						.register_balance_metric::<Target>(&target_client, &metrics_params)?;
+
+					return relay_messages(MessagesRelayParams {
+						source_client,
+						source_sign,
+						source_transactions_mortality,
+						source_proxied_account,
+						target_client,
+						target_sign,
+						target_transactions_mortality,
+						target_proxied_account,
+						target_max_tip,
+						source_to_target_headers_relay: None,
+						target_to_source_headers_relay: None,
+						lane_id,
+						relayer_mode: config.relayer_mode,
+						max_confirmations_delay: config.max_confirmations_delay,
+						piggyback_confirmations,
+						max_delivery_transactions_in_flight: config.max_delivery_transactions_in_flight,
+						only_messages_from,
+						forced_delivery: self.relay_nonces.map(|nonces| ForcedDelivery {
+							nonces: nonces.into(),
+							exit_when_delivered: false,
+						}),
+						control,
+						source_recovery_policy: self.source.recovery_policy(),
+						target_recovery_policy: self.target.recovery_policy(),
+						metrics_params,
+					})
+					.await
+					.map_err(|e| anyhow::format_err!("{}", e));
+				}
+			};
+
+			if self.relay_nonces.is_some() {
+				return Err(anyhow::format_err!(
+					"--relay-nonces may only be used when the relay serves the single lane"
+				));
+			}
+
+			// all lane relays are sharing the same metrics registry, which is exposed once
+			let metrics_params = relay_utils::relay_metrics(None, self.prometheus_params.into()).into_params();
+			let static_lanes = match lanes {
+				Lanes::Auto => Vec::new(),
+				Lanes::Static(ref lanes) => lanes.clone(),
+			};
+			register_relay_info(
+				&metrics_params,
+				Source::NAME,
+				Target::NAME,
+				&static_lanes,
+				RelayInfoMode::Messages(relayer_mode),
+			)
+			.map_err(|e| anyhow::format_err!("{}", e))?;
+			self.source_sign
+				.register_balance_metric::<Source>(&source_client, &metrics_params)?;
+			self.target_sign
+				.register_balance_metric::<Target>(&target_client, &metrics_params)?;
+
+			let discovery_client = source_client.clone();
+			let discovery_control = control.clone();
+			let lanes_metrics_params = metrics_params.clone();
+			let lanes_control = control.clone();
+			let reloadable_lanes = config.lanes.clone();
+			let source_recovery_policy = self.source.recovery_policy();
+			let target_recovery_policy = self.target.recovery_policy();
+			let start_lane = move |lane_id: LaneId| {
+				relay_messages(MessagesRelayParams {
+					source_client: source_client.clone(),
+					source_sign: source_sign.clone(),
+					source_transactions_mortality,
+					source_proxied_account: source_proxied_account.clone(),
+					target_client: target_client.clone(),
+					target_sign: target_sign.clone(),
+					target_transactions_mortality,
+					target_proxied_account: target_proxied_account.clone(),
+					target_max_tip,
+					source_to_target_headers_relay: None,
+					target_to_source_headers_relay: None,
+					lane_id,
+					relayer_mode: config.relayer_mode.clone(),
+					max_confirmations_delay: config.max_confirmations_delay.clone(),
+					piggyback_confirmations,
+					max_delivery_transactions_in_flight: config.max_delivery_transactions_in_flight.clone(),
+					only_messages_from: only_messages_from.clone(),
+					forced_delivery: None,
+					control: control.clone(),
+					source_recovery_policy: source_recovery_policy.clone(),
+					target_recovery_policy: target_recovery_policy.clone(),
+					metrics_params: lanes_metrics_params.clone().disable().metrics_prefix(
+						messages_relay::message_lane_loop::metrics_prefix::<MessagesLane>(&lane_id),
+					),
+				})
+				.boxed()
+			};
+
+			relay_utils::relay_metrics(None, metrics_params)
+				.expose()
+				.await
+				.map_err(|e| anyhow::format_err!("{}", e))?;
 
-			relay_messages(MessagesRelayParams {
-				source_client,
-				source_sign,
-				target_client,
-				target_sign,
-				source_to_target_headers_relay: None,
-				target_to_source_headers_relay: None,
-				lane_id: self.lane.into(),
-				metrics_params: self.prometheus_params.into(),
-			})
-			.await
-			.map_err(|e| anyhow::format_err!("{}", e))
+			match lanes {
+				Lanes::Auto => {
+					crate::lanes_discovery::run_discovered_lanes(
+						discovery_client,
+						pallet_bridge_messages::storage_keys::outbound_lanes_prefix::<SourceMessagesInstance>(),
+						discovery_control,
+						messages_relay::message_lane_loop::metrics_prefix::<MessagesLane>,
+						start_lane,
+					)
+					.await
+				}
+				Lanes::Static(_) => {
+					crate::messages_config::run_static_lanes(
+						reloadable_lanes,
+						lanes_control,
+						messages_relay::message_lane_loop::metrics_prefix::<MessagesLane>,
+						start_lane,
+					)
+					.await
+				}
+			}
 		})
 	}
 }
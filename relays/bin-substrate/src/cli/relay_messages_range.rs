@@ -0,0 +1,95 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::cli::bridge::FullBridge;
+use crate::cli::{
+	NoncesRange, SourceConnectionParams, SourceSigningParams, TargetConnectionParams, TargetSigningParams,
+};
+use crate::messages_lane::MessagesRelayParams;
+use crate::select_full_bridge;
+
+use bp_messages::LaneId;
+use messages_relay::message_lane_loop::{ForcedDelivery, RelayerMode};
+use relay_utils::{control::Reloadable, metrics::MetricsParams};
+use structopt::StructOpt;
+
+/// Deliver given range of messages and exit.
+#[derive(StructOpt)]
+pub struct RelayMessagesRange {
+	/// A bridge instance to relay messages for.
+	#[structopt(possible_values = &FullBridge::variants(), case_insensitive = true)]
+	bridge: FullBridge,
+	/// Lane id (hex-encoded or well-known lane name). Defaults to `00000000`.
+	#[structopt(long, env = "BRIDGE_LANE", default_value = "00000000")]
+	lane: LaneId,
+	/// Inclusive range of message nonces (`<BEGIN>..<END>`) to deliver.
+	#[structopt(long, env = "BRIDGE_RELAY_NONCES")]
+	relay_nonces: NoncesRange,
+	#[structopt(flatten)]
+	source: SourceConnectionParams,
+	#[structopt(flatten)]
+	source_sign: SourceSigningParams,
+	#[structopt(flatten)]
+	target: TargetConnectionParams,
+	#[structopt(flatten)]
+	target_sign: TargetSigningParams,
+}
+
+impl RelayMessagesRange {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		select_full_bridge!(self.bridge, {
+			let source_client = self.source.to_client::<Source>().await?;
+			let source_sign = self.source_sign.to_keypair::<Source>()?;
+			let source_transactions_mortality = self.source_sign.transactions_mortality()?;
+			let target_client = self.target.to_client::<Target>().await?;
+			let target_sign = self.target_sign.to_keypair::<Target>()?;
+			let target_transactions_mortality = self.target_sign.transactions_mortality()?;
+			let _source_signer_lock = self.source_sign.lock_signer::<Source>(&source_client).await?;
+			let _target_signer_lock = self.target_sign.lock_signer::<Target>(&target_client).await?;
+
+			relay_messages(MessagesRelayParams {
+				source_client,
+				source_sign,
+				source_transactions_mortality,
+				source_proxied_account: self.source_sign.proxied_account::<Source>(),
+				target_client,
+				target_sign,
+				target_transactions_mortality,
+				target_proxied_account: self.target_sign.proxied_account::<Target>(),
+				target_max_tip: self.target_sign.max_tip::<Target>()?,
+				source_to_target_headers_relay: None,
+				target_to_source_headers_relay: None,
+				lane_id: self.lane,
+				relayer_mode: Reloadable::new(RelayerMode::Altruistic),
+				max_confirmations_delay: Reloadable::new(0),
+				piggyback_confirmations: false,
+				max_delivery_transactions_in_flight: Reloadable::new(1),
+				only_messages_from: Vec::new(),
+				forced_delivery: Some(ForcedDelivery {
+					nonces: self.relay_nonces.into(),
+					exit_when_delivered: true,
+				}),
+				control: Default::default(),
+				source_recovery_policy: self.source.recovery_policy(),
+				target_recovery_policy: self.target.recovery_policy(),
+				metrics_params: MetricsParams::disabled(),
+			})
+			.await
+			.map_err(|e| anyhow::format_err!("{}", e))
+		})
+	}
+}
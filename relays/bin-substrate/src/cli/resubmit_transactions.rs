@@ -0,0 +1,261 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::cli::{Balance, TargetConnectionParams, TargetSigningParams};
+
+use codec::Encode;
+use num_traits::{SaturatingAdd, SaturatingSub};
+use relay_substrate_client::{Chain, ChainWithBalances, TransactionEra, TransactionSignScheme};
+use sp_core::{Bytes, Pair};
+use sp_runtime::traits::Header as HeaderT;
+use std::collections::{BTreeMap, BTreeSet};
+use structopt::{clap::arg_enum, StructOpt};
+
+/// Resubmit transactions of the signer that are stuck in the transaction pool.
+#[derive(StructOpt)]
+pub struct ResubmitTransactions {
+	/// A chain to resubmit transactions at.
+	#[structopt(possible_values = &ResubmitTransactionsChain::variants(), case_insensitive = true)]
+	chain: ResubmitTransactionsChain,
+	#[structopt(flatten)]
+	target: TargetConnectionParams,
+	#[structopt(flatten)]
+	target_sign: TargetSigningParams,
+	/// Number of blocks after which the transaction that is still in the pool is considered stalled.
+	#[structopt(long, env = "BRIDGE_STALLED_BLOCKS", default_value = "5")]
+	stalled_blocks: u32,
+	/// Tip that is added to the tip of the stalled transaction when it is resubmitted. The tip of the
+	/// resubmitted transaction never exceeds the `--target-max-tip` value.
+	#[structopt(long, env = "BRIDGE_TIP_STEP")]
+	tip_step: Balance,
+	/// Keep watching the transaction pool and resubmit stalled transactions until the process is
+	/// stopped. Otherwise the command exits once stalled transactions are resubmitted, or once
+	/// there are no pending transactions of the signer.
+	#[structopt(long)]
+	watch: bool,
+}
+
+// TODO [#851] Use kebab-case.
+arg_enum! {
+	#[derive(Debug)]
+	/// Chain to resubmit transactions at.
+	pub enum ResubmitTransactionsChain {
+		Millau,
+		Rialto,
+	}
+}
+
+macro_rules! select_chain {
+	($chain: expr, $generic: tt) => {
+		match $chain {
+			ResubmitTransactionsChain::Millau => {
+				type Target = relay_millau_client::Millau;
+
+				$generic
+			}
+			ResubmitTransactionsChain::Rialto => {
+				type Target = relay_rialto_client::Rialto;
+
+				$generic
+			}
+		}
+	};
+}
+
+impl ResubmitTransactions {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		select_chain!(self.chain, {
+			let client = self.target.to_client::<Target>().await?;
+			let key_pair = self.target_sign.to_keypair::<Target>()?;
+			let transactions_mortality = self.target_sign.transactions_mortality()?;
+			let max_tip = self.target_sign.max_tip::<Target>()?;
+			let tip_step: <Target as ChainWithBalances>::NativeBalance = self.tip_step.cast()?;
+			let signer: <Target as Chain>::AccountId = key_pair.public().into();
+
+			let mut pending_transactions = PendingTransactions::default();
+			loop {
+				let best_header = client.best_header().await?;
+				let best_block_number = *best_header.number();
+
+				// select pending transactions of our signer
+				let mut signer_transactions = client
+					.pending_extrinsics()
+					.await?
+					.into_iter()
+					.filter_map(|transaction| Target::parse_transaction(&transaction.0))
+					.filter(|transaction| transaction.signer == signer)
+					.map(|transaction| (transaction.nonce, transaction))
+					.collect::<BTreeMap<_, _>>();
+				let has_signer_transactions = !signer_transactions.is_empty();
+				let stalled_nonces = pending_transactions.update(
+					best_block_number,
+					signer_transactions.keys().cloned(),
+					self.stalled_blocks.into(),
+				);
+
+				for nonce in &stalled_nonces {
+					let transaction = match signer_transactions.remove(nonce) {
+						Some(transaction) => transaction,
+						None => continue,
+					};
+					let new_tip = match bump_tip(transaction.tip, tip_step, max_tip) {
+						Some(new_tip) => new_tip,
+						None => {
+							log::warn!(
+								target: "bridge",
+								"{} transaction with nonce {} is stalled, but its tip {:?} can't be increased",
+								Target::NAME,
+								nonce,
+								transaction.tip,
+							);
+							continue;
+						}
+					};
+
+					let resubmitted_transaction = Target::sign_transaction(
+						*client.genesis_hash(),
+						&key_pair,
+						TransactionEra::new(best_block_number, best_header.hash(), transactions_mortality),
+						*nonce,
+						new_tip,
+						transaction.call,
+					);
+					match client
+						.submit_unsigned_extrinsic(Bytes(resubmitted_transaction.encode()))
+						.await
+					{
+						Ok(transaction_hash) => {
+							log::info!(
+								target: "bridge",
+								"Resubmitted {} transaction with nonce {}, tip {:?} -> {:?}: {:?}",
+								Target::NAME,
+								nonce,
+								transaction.tip,
+								new_tip,
+								transaction_hash,
+							);
+							pending_transactions.resubmitted(*nonce, best_block_number);
+						}
+						Err(error) => log::warn!(
+							target: "bridge",
+							"Failed to resubmit {} transaction with nonce {}: {:?}",
+							Target::NAME,
+							nonce,
+							error,
+						),
+					}
+				}
+
+				if !crate::cli::flag_or_env(self.watch, "BRIDGE_WATCH")
+					&& (!stalled_nonces.is_empty() || !has_signer_transactions)
+				{
+					return Ok(());
+				}
+
+				async_std::task::sleep(Target::AVERAGE_BLOCK_INTERVAL).await;
+			}
+		})
+	}
+}
+
+/// Pending transactions of the signer, tracked by their nonces.
+#[derive(Debug)]
+struct PendingTransactions<Nonce, BlockNumber> {
+	/// Number of the best block at the moment when transaction has been (re)submitted.
+	first_seen_at: BTreeMap<Nonce, BlockNumber>,
+}
+
+impl<Nonce, BlockNumber> Default for PendingTransactions<Nonce, BlockNumber> {
+	fn default() -> Self {
+		PendingTransactions {
+			first_seen_at: BTreeMap::new(),
+		}
+	}
+}
+
+impl<Nonce: Ord + Copy, BlockNumber: SaturatingSub + Ord + Copy> PendingTransactions<Nonce, BlockNumber> {
+	/// Update pending transactions set and return nonces of stalled transactions.
+	///
+	/// The transaction is stalled if it is still in the pool after `stalled_blocks` blocks.
+	fn update(
+		&mut self,
+		best_block_number: BlockNumber,
+		pending_nonces: impl IntoIterator<Item = Nonce>,
+		stalled_blocks: BlockNumber,
+	) -> Vec<Nonce> {
+		let pending_nonces = pending_nonces.into_iter().collect::<BTreeSet<_>>();
+		self.first_seen_at.retain(|nonce, _| pending_nonces.contains(nonce));
+		for nonce in pending_nonces {
+			self.first_seen_at.entry(nonce).or_insert(best_block_number);
+		}
+
+		self.first_seen_at
+			.iter()
+			.filter(|(_, first_seen_at)| best_block_number.saturating_sub(*first_seen_at) >= stalled_blocks)
+			.map(|(nonce, _)| *nonce)
+			.collect()
+	}
+
+	/// Remember that the transaction has been resubmitted.
+	fn resubmitted(&mut self, nonce: Nonce, best_block_number: BlockNumber) {
+		self.first_seen_at.insert(nonce, best_block_number);
+	}
+}
+
+/// Select tip of the resubmitted transaction.
+///
+/// Returns `None` if the tip can't be increased.
+fn bump_tip<Balance: SaturatingAdd + Ord + Copy>(tip: Balance, tip_step: Balance, max_tip: Balance) -> Option<Balance> {
+	let new_tip = std::cmp::min(tip.saturating_add(&tip_step), max_tip);
+	if new_tip > tip {
+		Some(new_tip)
+	} else {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn pending_transactions_are_stalled_after_given_number_of_blocks() {
+		let mut pending_transactions = PendingTransactions::<u32, u32>::default();
+		assert_eq!(pending_transactions.update(100, vec![1, 2], 5), Vec::<u32>::new());
+		assert_eq!(pending_transactions.update(102, vec![1, 2, 3], 5), Vec::<u32>::new());
+		assert_eq!(pending_transactions.update(105, vec![1, 2, 3], 5), vec![1, 2]);
+		assert_eq!(pending_transactions.update(107, vec![2, 3], 5), vec![2, 3]);
+	}
+
+	#[test]
+	fn resubmitted_transactions_are_tracked_from_resubmission_block() {
+		let mut pending_transactions = PendingTransactions::<u32, u32>::default();
+		assert_eq!(pending_transactions.update(100, vec![1], 5), Vec::<u32>::new());
+		assert_eq!(pending_transactions.update(105, vec![1], 5), vec![1]);
+		pending_transactions.resubmitted(1, 105);
+		assert_eq!(pending_transactions.update(109, vec![1], 5), Vec::<u32>::new());
+		assert_eq!(pending_transactions.update(110, vec![1], 5), vec![1]);
+	}
+
+	#[test]
+	fn tip_is_bumped_up_to_max_tip() {
+		assert_eq!(bump_tip(0u32, 10, 100), Some(10));
+		assert_eq!(bump_tip(95u32, 10, 100), Some(100));
+		assert_eq!(bump_tip(100u32, 10, 100), None);
+		assert_eq!(bump_tip(0u32, 0, 100), None);
+	}
+}
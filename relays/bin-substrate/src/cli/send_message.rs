@@ -18,10 +18,11 @@ use crate::cli::bridge::FullBridge;
 use crate::cli::encode_call::{self, CliEncodeCall};
 use crate::cli::estimate_fee::estimate_message_delivery_and_dispatch_fee;
 use crate::cli::{
-	Balance, CliChain, ExplicitOrMaximal, HexBytes, HexLaneId, Origins, SourceConnectionParams, SourceSigningParams,
-	TargetSigningParams,
+	Balance, CliChain, DispatchFeePayment, ExplicitOrMaximal, HexBytes, Origins, SourceConnectionParams,
+	SourceSigningParams, TargetSigningParams,
 };
 use bp_message_dispatch::{CallOrigin, MessagePayload};
+use bp_messages::LaneId;
 use codec::Encode;
 use frame_support::{dispatch::GetDispatchInfo, weights::Weight};
 use relay_substrate_client::{Chain, TransactionSignScheme};
@@ -43,15 +44,27 @@ pub struct SendMessage {
 	// TODO [#885] Move TargetSign to origins
 	#[structopt(flatten)]
 	target_sign: TargetSigningParams,
-	/// Hex-encoded lane id. Defaults to `00000000`.
+	/// Lane id (hex-encoded or well-known lane name). Defaults to `00000000`.
 	#[structopt(long, default_value = "00000000")]
-	lane: HexLaneId,
+	lane: LaneId,
 	/// Dispatch weight of the message. If not passed, determined automatically.
 	#[structopt(long)]
 	dispatch_weight: Option<ExplicitOrMaximal<Weight>>,
 	/// Delivery and dispatch fee in source chain base currency units. If not passed, determined automatically.
 	#[structopt(long)]
 	fee: Option<Balance>,
+	/// Number of the target chain block, before which the message must be delivered. Messages that
+	/// are delivered later are not dispatched. If not passed, the message never expires.
+	#[structopt(long)]
+	delivery_deadline: Option<u64>,
+	/// Where the dispatch fee is paid. Defaults to `AtSourceChain`.
+	#[structopt(
+		long,
+		possible_values = &DispatchFeePayment::variants(),
+		case_insensitive = true,
+		default_value = "AtSourceChain"
+	)]
+	dispatch_fee_payment: DispatchFeePayment,
 	/// Message type.
 	#[structopt(subcommand)]
 	message: crate::cli::encode_call::Call,
@@ -71,6 +84,8 @@ impl SendMessage {
 				target_sign,
 				ref mut message,
 				dispatch_weight,
+				delivery_deadline,
+				dispatch_fee_payment,
 				origin,
 				bridge,
 				..
@@ -94,6 +109,8 @@ impl SendMessage {
 				message_payload(
 					Target::RUNTIME_VERSION.spec_version,
 					target_call_weight,
+					*delivery_deadline,
+					(*dispatch_fee_payment).into(),
 					match origin {
 						Origins::Source => CallOrigin::SourceAccount(source_account_id),
 						Origins::Target => {
@@ -125,17 +142,19 @@ impl SendMessage {
 
 			let source_client = self.source.to_client::<Source>().await?;
 			let source_sign = self.source_sign.to_keypair::<Source>()?;
+			let source_transactions_mortality = self.source_sign.transactions_mortality()?;
+			let source_token = source_client.token_properties().await?;
 
-			let lane = self.lane.clone().into();
+			let lane = self.lane;
 			let fee = match self.fee {
 				Some(fee) => fee,
-				None => Balance(
+				None => Balance::from_native(
 					estimate_message_delivery_and_dispatch_fee::<
 						<Source as relay_substrate_client::ChainWithBalances>::NativeBalance,
 						_,
 						_,
 					>(&source_client, ESTIMATE_MESSAGE_FEE_METHOD, lane, payload.clone())
-					.await? as _,
+					.await?,
 				),
 			};
 			let dispatch_weight = payload.weight;
@@ -147,11 +166,17 @@ impl SendMessage {
 			})?;
 
 			source_client
-				.submit_signed_extrinsic(source_sign.public().into(), |transaction_nonce| {
+				.submit_signed_extrinsic(source_sign.public().into(), |best_block_id, transaction_nonce| {
 					let signed_source_call = Source::sign_transaction(
 						*source_client.genesis_hash(),
 						&source_sign,
+						relay_substrate_client::TransactionEra::new(
+							best_block_id.0,
+							best_block_id.1,
+							source_transactions_mortality,
+						),
 						transaction_nonce,
+						0,
 						send_message_call,
 					)
 					.encode();
@@ -162,7 +187,7 @@ impl SendMessage {
 						Target::NAME,
 						signed_source_call.len(),
 						dispatch_weight,
-						fee,
+						fee.format_tokens(&source_token),
 					);
 					log::info!(
 						target: "bridge",
@@ -180,7 +205,7 @@ impl SendMessage {
 	}
 }
 
-fn prepare_call_dispatch_weight(
+pub(crate) fn prepare_call_dispatch_weight(
 	user_specified_dispatch_weight: &Option<ExplicitOrMaximal<Weight>>,
 	weight_from_pre_dispatch_call: ExplicitOrMaximal<Weight>,
 	maximal_allowed_weight: Weight,
@@ -197,6 +222,8 @@ fn prepare_call_dispatch_weight(
 pub(crate) fn message_payload<SAccountId, TPublic, TSignature>(
 	spec_version: u32,
 	weight: Weight,
+	delivery_deadline: Option<u64>,
+	dispatch_fee_payment: bp_message_dispatch::DispatchFeePayment,
 	origin: CallOrigin<SAccountId, TPublic, TSignature>,
 	call: &impl Encode,
 ) -> MessagePayload<SAccountId, TPublic, TSignature, Vec<u8>>
@@ -209,6 +236,8 @@ where
 	let payload = MessagePayload {
 		spec_version,
 		weight,
+		delivery_deadline,
+		dispatch_fee_payment,
 		origin,
 		call: HexBytes::encode(call),
 	};
@@ -220,12 +249,16 @@ where
 	let MessagePayload {
 		spec_version,
 		weight,
+		delivery_deadline,
+		dispatch_fee_payment,
 		origin,
 		call,
 	} = payload;
 	MessagePayload {
 		spec_version,
 		weight,
+		delivery_deadline,
+		dispatch_fee_payment,
 		origin,
 		call: call.0,
 	}
@@ -266,6 +299,8 @@ mod tests {
 			MessagePayload {
 				spec_version: relay_millau_client::Millau::RUNTIME_VERSION.spec_version,
 				weight: 1345000,
+				delivery_deadline: None,
+				dispatch_fee_payment: bp_message_dispatch::DispatchFeePayment::AtSourceChain,
 				origin: CallOrigin::SourceAccount(sp_keyring::AccountKeyring::Alice.to_account_id()),
 				call: hex!("0401081234").to_vec(),
 			}
@@ -305,6 +340,8 @@ mod tests {
 			MessagePayload {
 				spec_version: relay_millau_client::Millau::RUNTIME_VERSION.spec_version,
 				weight: 1345000,
+				delivery_deadline: None,
+				dispatch_fee_payment: bp_message_dispatch::DispatchFeePayment::AtSourceChain,
 				origin: CallOrigin::TargetAccount(
 					sp_keyring::AccountKeyring::Alice.to_account_id(),
 					sp_keyring::AccountKeyring::Bob.into(),
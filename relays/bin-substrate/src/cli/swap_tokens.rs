@@ -0,0 +1,337 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::cli::bridge::FullBridge;
+use crate::cli::estimate_fee::estimate_message_delivery_and_dispatch_fee;
+use crate::cli::{Balance, SourceConnectionParams, SourceSigningParams, TargetConnectionParams, TargetSigningParams};
+use bp_message_dispatch::{CallOrigin, DispatchFeePayment, MessagePayload};
+use codec::Encode;
+use frame_support::traits::Get;
+use relay_substrate_client::{Chain, TransactionSignScheme};
+use sp_core::{Bytes, Pair};
+use sp_runtime::traits::{AccountIdConversion, Header as HeaderT, IdentifyAccount};
+use sp_runtime::{AccountId32, MultiSignature, MultiSigner};
+use structopt::StructOpt;
+
+/// Swap tokens of the source chain for tokens of the target chain.
+///
+/// The swap is created at the source chain by the source signer and then accepted at the
+/// target chain by the target signer. Both source and target tokens are locked until the swap
+/// is completed. Source tokens are claimed by the target signer when the claim message is
+/// delivered to the source chain before the swap `cancel_after` block. Target tokens are released
+/// to the source signer (or returned back to the target signer, if the swap has been cancelled)
+/// when the swap result message is delivered to the target chain.
+#[derive(StructOpt)]
+pub struct SwapTokens {
+	/// A bridge instance to use for the swap.
+	#[structopt(possible_values = &FullBridge::variants(), case_insensitive = true)]
+	bridge: FullBridge,
+	#[structopt(flatten)]
+	source: SourceConnectionParams,
+	#[structopt(flatten)]
+	source_sign: SourceSigningParams,
+	#[structopt(flatten)]
+	target: TargetConnectionParams,
+	#[structopt(flatten)]
+	target_sign: TargetSigningParams,
+	/// Amount of source chain tokens that the source signer gives.
+	#[structopt(long)]
+	source_balance: Balance,
+	/// Amount of target chain tokens that the target signer gives.
+	#[structopt(long)]
+	target_balance: Balance,
+	/// Number of source chain blocks (starting from the current best block) after which the
+	/// swap may be cancelled by the source signer.
+	#[structopt(long, default_value = "100")]
+	cancel_after_blocks: u32,
+	/// Delivery and dispatch fee of the claim message in target chain base currency units.
+	/// If not passed, determined automatically.
+	#[structopt(long)]
+	claim_message_fee: Option<Balance>,
+	/// Delivery and dispatch fee of the swap result message in source chain base currency units.
+	/// If not passed, determined automatically.
+	#[structopt(long)]
+	result_message_fee: Option<Balance>,
+}
+
+/// The macro allows executing bridge-specific code without going fully generic.
+macro_rules! select_bridge {
+	($bridge: expr, $generic: tt) => {
+		match $bridge {
+			FullBridge::MillauToRialto => {
+				type Source = relay_millau_client::Millau;
+				type Target = relay_rialto_client::Rialto;
+
+				use bp_millau::MAX_CLAIM_SWAP_WEIGHT as SOURCE_MAX_CLAIM_SWAP_WEIGHT;
+				use bp_millau::TO_MILLAU_ESTIMATE_MESSAGE_FEE_METHOD as TARGET_TO_SOURCE_ESTIMATE_MESSAGE_FEE_METHOD;
+				use bp_rialto::MAX_SETTLE_SWAP_WEIGHT as TARGET_MAX_SETTLE_SWAP_WEIGHT;
+				use bp_rialto::TO_RIALTO_ESTIMATE_MESSAGE_FEE_METHOD as SOURCE_TO_TARGET_ESTIMATE_MESSAGE_FEE_METHOD;
+				use millau_runtime::TokenSwapMessagesLane as SourceToTargetLane;
+				use millau_runtime::TokenSwapPalletId as SourceTokenSwapPalletId;
+				use rialto_runtime::TokenSwapMessagesLane as TargetToSourceLane;
+				use rialto_runtime::TokenSwapPalletId as TargetTokenSwapPalletId;
+
+				type SourceTokenSwap = pallet_bridge_token_swap::TokenSwapOf<millau_runtime::Runtime, ()>;
+
+				fn create_swap_call(
+					swap: SourceTokenSwap,
+					result_message_fee: bp_millau::Balance,
+				) -> millau_runtime::Call {
+					millau_runtime::Call::BridgeRialtoTokenSwap(millau_runtime::TokenSwapCall::create_swap(
+						swap,
+						result_message_fee,
+					))
+				}
+
+				fn claim_swap_call(swap: SourceTokenSwap, target_spec_version: u32) -> millau_runtime::Call {
+					millau_runtime::Call::BridgeRialtoTokenSwap(millau_runtime::TokenSwapCall::claim_swap(
+						swap,
+						target_spec_version,
+					))
+				}
+
+				fn settle_swap_call(swap_hash: sp_core::H256, is_claimed: bool) -> rialto_runtime::Call {
+					rialto_runtime::Call::BridgeMillauTokenSwap(rialto_runtime::TokenSwapCall::settle_swap(
+						swap_hash, is_claimed,
+					))
+				}
+
+				fn accept_swap_call(
+					swap: SourceTokenSwap,
+					source_spec_version: u32,
+					claim_message_fee: bp_rialto::Balance,
+				) -> rialto_runtime::Call {
+					rialto_runtime::Call::BridgeMillauTokenSwap(rialto_runtime::TokenSwapCall::accept_swap(
+						swap,
+						source_spec_version,
+						claim_message_fee,
+					))
+				}
+
+				$generic
+			}
+			FullBridge::RialtoToMillau => {
+				type Source = relay_rialto_client::Rialto;
+				type Target = relay_millau_client::Millau;
+
+				use bp_millau::MAX_SETTLE_SWAP_WEIGHT as TARGET_MAX_SETTLE_SWAP_WEIGHT;
+				use bp_millau::TO_MILLAU_ESTIMATE_MESSAGE_FEE_METHOD as SOURCE_TO_TARGET_ESTIMATE_MESSAGE_FEE_METHOD;
+				use bp_rialto::MAX_CLAIM_SWAP_WEIGHT as SOURCE_MAX_CLAIM_SWAP_WEIGHT;
+				use bp_rialto::TO_RIALTO_ESTIMATE_MESSAGE_FEE_METHOD as TARGET_TO_SOURCE_ESTIMATE_MESSAGE_FEE_METHOD;
+				use millau_runtime::TokenSwapMessagesLane as TargetToSourceLane;
+				use millau_runtime::TokenSwapPalletId as TargetTokenSwapPalletId;
+				use rialto_runtime::TokenSwapMessagesLane as SourceToTargetLane;
+				use rialto_runtime::TokenSwapPalletId as SourceTokenSwapPalletId;
+
+				type SourceTokenSwap = pallet_bridge_token_swap::TokenSwapOf<rialto_runtime::Runtime, ()>;
+
+				fn create_swap_call(
+					swap: SourceTokenSwap,
+					result_message_fee: bp_rialto::Balance,
+				) -> rialto_runtime::Call {
+					rialto_runtime::Call::BridgeMillauTokenSwap(rialto_runtime::TokenSwapCall::create_swap(
+						swap,
+						result_message_fee,
+					))
+				}
+
+				fn claim_swap_call(swap: SourceTokenSwap, target_spec_version: u32) -> rialto_runtime::Call {
+					rialto_runtime::Call::BridgeMillauTokenSwap(rialto_runtime::TokenSwapCall::claim_swap(
+						swap,
+						target_spec_version,
+					))
+				}
+
+				fn settle_swap_call(swap_hash: sp_core::H256, is_claimed: bool) -> millau_runtime::Call {
+					millau_runtime::Call::BridgeRialtoTokenSwap(millau_runtime::TokenSwapCall::settle_swap(
+						swap_hash, is_claimed,
+					))
+				}
+
+				fn accept_swap_call(
+					swap: SourceTokenSwap,
+					source_spec_version: u32,
+					claim_message_fee: bp_millau::Balance,
+				) -> millau_runtime::Call {
+					millau_runtime::Call::BridgeRialtoTokenSwap(millau_runtime::TokenSwapCall::accept_swap(
+						swap,
+						source_spec_version,
+						claim_message_fee,
+					))
+				}
+
+				$generic
+			}
+		}
+	};
+}
+
+impl SwapTokens {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		select_bridge!(self.bridge, {
+			let source_client = self.source.to_client::<Source>().await?;
+			let source_sign = self.source_sign.to_keypair::<Source>()?;
+			let source_transactions_mortality = self.source_sign.transactions_mortality()?;
+			let target_client = self.target.to_client::<Target>().await?;
+			let target_sign = self.target_sign.to_keypair::<Target>()?;
+			let target_transactions_mortality = self.target_sign.transactions_mortality()?;
+			let source_token = source_client.token_properties().await?;
+			let target_token = target_client.token_properties().await?;
+
+			let source_public: MultiSigner = source_sign.public().into();
+			let target_public: MultiSigner = target_sign.public().into();
+			let source_account_id: AccountId32 = source_public.into_account();
+			let target_account_id: AccountId32 = target_public.into_account();
+
+			let best_source_header = source_client.best_header().await?;
+			let swap = SourceTokenSwap {
+				cancel_after: best_source_header
+					.number()
+					.saturating_add(self.cancel_after_blocks.into()),
+				source_account: source_account_id.clone(),
+				source_balance: self.source_balance.cast()?,
+				target_account: target_account_id.clone(),
+				target_balance: self.target_balance.cast()?,
+			};
+			let swap_hash = swap.hash();
+			let source_spec_version = Source::RUNTIME_VERSION.spec_version;
+			let target_spec_version = Target::RUNTIME_VERSION.spec_version;
+
+			// start the swap at the source chain
+			let result_message_fee = match self.result_message_fee {
+				Some(result_message_fee) => result_message_fee,
+				None => {
+					let source_pallet_account_id: AccountId32 = SourceTokenSwapPalletId::get().into_account();
+					let result_message_payload = MessagePayload::<_, MultiSigner, MultiSignature, _> {
+						spec_version: target_spec_version,
+						weight: TARGET_MAX_SETTLE_SWAP_WEIGHT,
+						delivery_deadline: None,
+						dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+						origin: CallOrigin::SourceAccount(source_pallet_account_id),
+						call: settle_swap_call(swap_hash, true).encode(),
+					};
+					Balance::from_native(
+						estimate_message_delivery_and_dispatch_fee::<
+							<Source as relay_substrate_client::ChainWithBalances>::NativeBalance,
+							_,
+							_,
+						>(
+							&source_client,
+							SOURCE_TO_TARGET_ESTIMATE_MESSAGE_FEE_METHOD,
+							SourceToTargetLane::get(),
+							result_message_payload,
+						)
+						.await?,
+					)
+				}
+			};
+			let create_swap_call = create_swap_call(swap.clone(), result_message_fee.cast()?);
+			source_client
+				.submit_signed_extrinsic(source_account_id, |best_block_id, transaction_nonce| {
+					let signed_source_call = Source::sign_transaction(
+						*source_client.genesis_hash(),
+						&source_sign,
+						relay_substrate_client::TransactionEra::new(
+							best_block_id.0,
+							best_block_id.1,
+							source_transactions_mortality,
+						),
+						transaction_nonce,
+						0,
+						create_swap_call,
+					)
+					.encode();
+
+					log::info!(
+						target: "bridge",
+						"Creating swap {:?} at {}. Source balance: {}. Target balance: {}. Cancel after: {}. \
+						Result message fee: {}",
+						swap_hash,
+						Source::NAME,
+						self.source_balance.format_tokens(&source_token),
+						self.target_balance.format_tokens(&target_token),
+						swap.cancel_after,
+						result_message_fee.format_tokens(&source_token),
+					);
+
+					Bytes(signed_source_call)
+				})
+				.await?;
+
+			// accept the swap at the target chain
+			let claim_message_fee = match self.claim_message_fee {
+				Some(claim_message_fee) => claim_message_fee,
+				None => {
+					let target_pallet_account_id: AccountId32 = TargetTokenSwapPalletId::get().into_account();
+					let claim_message_payload = MessagePayload::<_, MultiSigner, MultiSignature, _> {
+						spec_version: source_spec_version,
+						weight: SOURCE_MAX_CLAIM_SWAP_WEIGHT,
+						delivery_deadline: Some(pallet_bridge_token_swap::claim_message_delivery_deadline(
+							swap.cancel_after,
+						)),
+						dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+						origin: CallOrigin::SourceAccount(target_pallet_account_id),
+						call: claim_swap_call(swap.clone(), target_spec_version).encode(),
+					};
+					Balance::from_native(
+						estimate_message_delivery_and_dispatch_fee::<
+							<Target as relay_substrate_client::ChainWithBalances>::NativeBalance,
+							_,
+							_,
+						>(
+							&target_client,
+							TARGET_TO_SOURCE_ESTIMATE_MESSAGE_FEE_METHOD,
+							TargetToSourceLane::get(),
+							claim_message_payload,
+						)
+						.await?,
+					)
+				}
+			};
+			let accept_swap_call = accept_swap_call(swap, source_spec_version, claim_message_fee.cast()?);
+			target_client
+				.submit_signed_extrinsic(target_account_id, |best_block_id, transaction_nonce| {
+					let signed_target_call = Target::sign_transaction(
+						*target_client.genesis_hash(),
+						&target_sign,
+						relay_substrate_client::TransactionEra::new(
+							best_block_id.0,
+							best_block_id.1,
+							target_transactions_mortality,
+						),
+						transaction_nonce,
+						0,
+						accept_swap_call,
+					)
+					.encode();
+
+					log::info!(
+						target: "bridge",
+						"Accepting swap {:?} at {}. Claim message fee: {}",
+						swap_hash,
+						Target::NAME,
+						claim_message_fee.format_tokens(&target_token),
+					);
+
+					Bytes(signed_target_call)
+				})
+				.await?;
+		});
+
+		Ok(())
+	}
+}
@@ -0,0 +1,191 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Updating conversion rate of bridged chain tokens, stored at the conversion rate pallet.
+//!
+//! The rate is read from the external price service (e.g. CoinGecko) and the update transaction
+//! is only submitted if the on-chain value differs from the external one by more than configured
+//! threshold.
+
+use codec::Encode;
+use relay_substrate_client::{Chain, Client, Error as SubstrateError, HashOf, TransactionEra, TransactionSignScheme};
+use sp_core::{storage::StorageKey, Bytes, Pair};
+use sp_runtime::{FixedPointNumber, FixedU128};
+use std::time::Duration;
+
+/// Interval between conversion rate checks.
+///
+/// The update transaction is not tracked, so this interval must be large enough for it to be mined.
+const CONVERSION_RATE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Chain with the conversion rate pallet.
+pub trait ChainWithConversionRate: Chain + TransactionSignScheme<Chain = Self> {
+	/// Conversion rate that is used until the first update.
+	const INITIAL_CONVERSION_RATE: FixedU128;
+
+	/// Returns storage key of the conversion rate.
+	fn conversion_rate_key() -> StorageKey;
+
+	/// Returns call that updates the conversion rate.
+	fn update_conversion_rate_call(conversion_rate: FixedU128) -> Self::Call;
+}
+
+/// Returns conversion rate that is currently stored at the chain.
+pub async fn conversion_rate<C: ChainWithConversionRate>(client: &Client<C>) -> Result<FixedU128, SubstrateError> {
+	Ok(client
+		.storage_value(C::conversion_rate_key())
+		.await?
+		.unwrap_or(C::INITIAL_CONVERSION_RATE))
+}
+
+/// Submit transaction that updates the conversion rate.
+pub async fn update_conversion_rate<C: ChainWithConversionRate>(
+	client: &Client<C>,
+	signer: C::AccountKeyPair,
+	transactions_mortality: Option<u32>,
+	conversion_rate: FixedU128,
+) -> Result<HashOf<C>, SubstrateError>
+where
+	C::AccountId: From<<C::AccountKeyPair as Pair>::Public>,
+	C::SignedTransaction: Encode,
+{
+	let genesis_hash = *client.genesis_hash();
+	client
+		.submit_signed_extrinsic(signer.public().into(), move |best_block_id, transaction_nonce| {
+			Bytes(
+				C::sign_transaction(
+					genesis_hash,
+					&signer,
+					TransactionEra::new(best_block_id.0, best_block_id.1, transactions_mortality),
+					transaction_nonce,
+					num_traits::Zero::zero(),
+					C::update_conversion_rate_call(conversion_rate),
+				)
+				.encode(),
+			)
+		})
+		.await
+}
+
+/// Run loop that keeps the on-chain conversion rate in sync with the external price service.
+///
+/// `threshold` is the relative difference (e.g. `0.05` for 5%) between on-chain and external
+/// rates that triggers the update.
+pub async fn run_conversion_rate_update_loop<C: ChainWithConversionRate>(
+	client: Client<C>,
+	signer: C::AccountKeyPair,
+	transactions_mortality: Option<u32>,
+	price_url: String,
+	price_json_path: String,
+	threshold: f64,
+) where
+	C::AccountId: From<<C::AccountKeyPair as Pair>::Public>,
+	C::SignedTransaction: Encode,
+{
+	loop {
+		let external_rate = match relay_utils::metrics::read_json_value(&price_url, &price_json_path).await {
+			Ok(external_rate) => external_rate,
+			Err(error) => {
+				log::warn!(target: "bridge", "Failed to read external conversion rate: {}", error);
+				async_std::task::sleep(CONVERSION_RATE_CHECK_INTERVAL).await;
+				continue;
+			}
+		};
+		let on_chain_rate = match conversion_rate(&client).await {
+			Ok(on_chain_rate) => on_chain_rate,
+			Err(error) => {
+				log::warn!(target: "bridge", "Failed to read {} conversion rate: {:?}", C::NAME, error);
+				async_std::task::sleep(CONVERSION_RATE_CHECK_INTERVAL).await;
+				continue;
+			}
+		};
+
+		let on_chain_rate = fixed_to_f64(on_chain_rate);
+		if should_update_conversion_rate(on_chain_rate, external_rate, threshold) {
+			match update_conversion_rate(
+				&client,
+				signer.clone(),
+				transactions_mortality,
+				f64_to_fixed(external_rate),
+			)
+			.await
+			{
+				Ok(transaction_hash) => log::info!(
+					target: "bridge",
+					"Updating {} conversion rate: {} -> {}: {:?}",
+					C::NAME,
+					on_chain_rate,
+					external_rate,
+					transaction_hash,
+				),
+				Err(error) => log::warn!(
+					target: "bridge",
+					"Failed to update {} conversion rate: {:?}",
+					C::NAME,
+					error,
+				),
+			}
+		}
+
+		async_std::task::sleep(CONVERSION_RATE_CHECK_INTERVAL).await;
+	}
+}
+
+/// Returns true if the on-chain conversion rate needs to be updated.
+fn should_update_conversion_rate(on_chain_rate: f64, external_rate: f64, threshold: f64) -> bool {
+	if external_rate <= 0.0 || !external_rate.is_finite() {
+		return false;
+	}
+
+	(external_rate - on_chain_rate).abs() / on_chain_rate > threshold
+}
+
+/// Convert fixed point number into `f64`.
+fn fixed_to_f64(value: FixedU128) -> f64 {
+	value.into_inner() as f64 / FixedU128::DIV as f64
+}
+
+/// Convert `f64` into fixed point number.
+fn f64_to_fixed(value: f64) -> FixedU128 {
+	FixedU128::from_inner((value * FixedU128::DIV as f64) as u128)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn conversion_rate_is_updated_only_when_threshold_is_exceeded() {
+		assert!(!should_update_conversion_rate(1.0, 1.0, 0.05));
+		assert!(!should_update_conversion_rate(1.0, 1.04, 0.05));
+		assert!(!should_update_conversion_rate(1.0, 0.96, 0.05));
+		assert!(should_update_conversion_rate(1.0, 1.06, 0.05));
+		assert!(should_update_conversion_rate(1.0, 0.94, 0.05));
+	}
+
+	#[test]
+	fn invalid_external_conversion_rate_is_ignored() {
+		assert!(!should_update_conversion_rate(1.0, 0.0, 0.05));
+		assert!(!should_update_conversion_rate(1.0, -1.0, 0.05));
+		assert!(!should_update_conversion_rate(1.0, f64::NAN, 0.05));
+	}
+
+	#[test]
+	fn fixed_point_conversion_works() {
+		assert_eq!(fixed_to_f64(FixedU128::saturating_from_rational(1, 4)), 0.25);
+		assert_eq!(f64_to_fixed(0.25), FixedU128::saturating_from_rational(1, 4));
+	}
+}
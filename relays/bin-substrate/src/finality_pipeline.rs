@@ -20,8 +20,12 @@ use crate::finality_target::SubstrateFinalityTarget;
 
 use bp_header_chain::justification::GrandpaJustification;
 use finality_relay::{FinalitySyncParams, FinalitySyncPipeline};
-use relay_substrate_client::{finality_source::FinalitySource, BlockNumberOf, Chain, Client, HashOf, SyncHeader};
-use relay_utils::{metrics::MetricsParams, BlockNumberBase};
+use num_traits::Zero;
+use relay_substrate_client::{
+	finality_source::FinalitySource, BlockNumberOf, Chain, ChainWithBalances, ChainWithTips, Client, HashOf,
+	HeaderIdOf, SyncHeader,
+};
+use relay_utils::{metrics::MetricsParams, recovery::RecoveryPolicyParams, BlockNumberBase};
 use sp_core::Bytes;
 use std::{fmt::Debug, marker::PhantomData, time::Duration};
 
@@ -32,14 +36,20 @@ const STALL_TIMEOUT: Duration = Duration::from_secs(120);
 /// Finality delay of 4096 blocks is unlikely to happen in practice in
 /// Substrate+GRANDPA based chains (good to know).
 const RECENT_FINALITY_PROOFS_LIMIT: usize = 4096;
+/// Maximal number of concurrent headers requests to the source node.
+const MAX_CONCURRENT_HEADERS_REQUESTS: usize = 16;
 
 /// Headers sync pipeline for Substrate <-> Substrate relays.
 pub trait SubstrateFinalitySyncPipeline: FinalitySyncPipeline {
 	/// Name of the runtime method that returns id of best finalized source header at target chain.
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str;
+	/// Name of the GRANDPA bridge pallet at the target chain, that is tracking source chain finality.
+	///
+	/// It is used to read the authorities set that the target chain uses to verify justifications.
+	const GRANDPA_PALLET_NAME_AT_TARGET: &'static str;
 
 	/// Chain with GRANDPA bridge pallet.
-	type TargetChain: Chain;
+	type TargetChain: ChainWithTips;
 
 	/// Customize metrics exposed by headers sync loop.
 	fn customize_metrics(params: MetricsParams) -> anyhow::Result<MetricsParams> {
@@ -56,10 +66,21 @@ pub trait SubstrateFinalitySyncPipeline: FinalitySyncPipeline {
 	/// Returns id of account that we're using to sign transactions at target chain.
 	fn transactions_author(&self) -> <Self::TargetChain as Chain>::AccountId;
 
+	/// Returns maximal tip that may be attached to the target chain transactions.
+	///
+	/// The actual tip depends on the target chain congestion. Zero means that transactions
+	/// are submitted without tip.
+	fn max_transaction_tip(&self) -> <Self::TargetChain as ChainWithBalances>::NativeBalance;
+
 	/// Make submit header transaction.
+	///
+	/// The `best_block_id` is the id of the best target chain block, that may be used as a start
+	/// of the transaction mortality period.
 	fn make_submit_finality_proof_transaction(
 		&self,
+		best_block_id: HeaderIdOf<Self::TargetChain>,
 		transaction_nonce: <Self::TargetChain as Chain>::Index,
+		tip: <Self::TargetChain as ChainWithBalances>::NativeBalance,
 		header: Self::Header,
 		proof: Self::FinalityProof,
 	) -> Bytes;
@@ -67,16 +88,22 @@ pub trait SubstrateFinalitySyncPipeline: FinalitySyncPipeline {
 
 /// Substrate-to-Substrate finality proof pipeline.
 #[derive(Clone)]
-pub struct SubstrateFinalityToSubstrate<SourceChain, TargetChain: Chain, TargetSign> {
+pub struct SubstrateFinalityToSubstrate<SourceChain, TargetChain: ChainWithBalances, TargetSign> {
 	/// Client for the target chain.
 	pub(crate) target_client: Client<TargetChain>,
 	/// Data required to sign target chain transactions.
 	pub(crate) target_sign: TargetSign,
+	/// Mortality period of target chain transactions. `None` if transactions are immortal.
+	pub(crate) transactions_mortality: Option<u32>,
+	/// If set, all target chain transactions are wrapped into `proxy` calls on behalf of this account.
+	pub(crate) target_proxied_account: Option<TargetChain::AccountId>,
+	/// Maximal tip that may be attached to target chain transactions.
+	pub(crate) target_max_tip: TargetChain::NativeBalance,
 	/// Unused generic arguments dump.
 	_marker: PhantomData<SourceChain>,
 }
 
-impl<SourceChain, TargetChain: Chain, TargetSign> Debug
+impl<SourceChain, TargetChain: ChainWithBalances, TargetSign> Debug
 	for SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign>
 {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -86,15 +113,36 @@ impl<SourceChain, TargetChain: Chain, TargetSign> Debug
 	}
 }
 
-impl<SourceChain, TargetChain: Chain, TargetSign> SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign> {
+impl<SourceChain, TargetChain: ChainWithBalances, TargetSign>
+	SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign>
+{
 	/// Create new Substrate-to-Substrate headers pipeline.
-	pub fn new(target_client: Client<TargetChain>, target_sign: TargetSign) -> Self {
+	pub fn new(
+		target_client: Client<TargetChain>,
+		target_sign: TargetSign,
+		transactions_mortality: Option<u32>,
+	) -> Self {
 		SubstrateFinalityToSubstrate {
 			target_client,
 			target_sign,
+			transactions_mortality,
+			target_proxied_account: None,
+			target_max_tip: Zero::zero(),
 			_marker: Default::default(),
 		}
 	}
+
+	/// Submit all target chain transactions through `proxy` calls on behalf of given account.
+	pub fn with_target_proxied_account(mut self, target_proxied_account: Option<TargetChain::AccountId>) -> Self {
+		self.target_proxied_account = target_proxied_account;
+		self
+	}
+
+	/// Attach tips (that depend on the target chain congestion) to target chain transactions.
+	pub fn with_target_max_tip(mut self, target_max_tip: TargetChain::NativeBalance) -> Self {
+		self.target_max_tip = target_max_tip;
+		self
+	}
 }
 
 impl<SourceChain, TargetChain, TargetSign> FinalitySyncPipeline
@@ -102,7 +150,7 @@ impl<SourceChain, TargetChain, TargetSign> FinalitySyncPipeline
 where
 	SourceChain: Clone + Chain + Debug,
 	BlockNumberOf<SourceChain>: BlockNumberBase,
-	TargetChain: Clone + Chain + Debug,
+	TargetChain: Clone + ChainWithBalances + Debug,
 	TargetSign: 'static + Clone + Send + Sync,
 {
 	const SOURCE_NAME: &'static str = SourceChain::NAME;
@@ -120,6 +168,12 @@ pub async fn run<SourceChain, TargetChain, P>(
 	source_client: Client<SourceChain>,
 	target_client: Client<TargetChain>,
 	is_on_demand_task: bool,
+	only_mandatory_headers: bool,
+	max_headers_to_scan: Option<u32>,
+	max_headers_to_submit: u32,
+	transactions_mortality: Option<u32>,
+	source_recovery_policy: RecoveryPolicyParams,
+	target_recovery_policy: RecoveryPolicyParams,
 	metrics_params: MetricsParams,
 ) -> anyhow::Result<()>
 where
@@ -132,7 +186,7 @@ where
 	>,
 	SourceChain: Clone + Chain,
 	BlockNumberOf<SourceChain>: BlockNumberBase,
-	TargetChain: Clone + Chain,
+	TargetChain: Clone + ChainWithTips,
 {
 	log::info!(
 		target: "bridge",
@@ -148,7 +202,17 @@ where
 			is_on_demand_task,
 			tick: std::cmp::max(SourceChain::AVERAGE_BLOCK_INTERVAL, TargetChain::AVERAGE_BLOCK_INTERVAL),
 			recent_finality_proofs_limit: RECENT_FINALITY_PROOFS_LIMIT,
-			stall_timeout: STALL_TIMEOUT,
+			stall_timeout: relay_substrate_client::transaction_stall_timeout(
+				transactions_mortality,
+				TargetChain::AVERAGE_BLOCK_INTERVAL,
+				STALL_TIMEOUT,
+			),
+			max_concurrent_headers_requests: MAX_CONCURRENT_HEADERS_REQUESTS,
+			only_mandatory_headers,
+			max_headers_to_scan,
+			max_headers_to_submit,
+			source_recovery_policy,
+			target_recovery_policy,
 		},
 		metrics_params,
 		futures::future::pending(),
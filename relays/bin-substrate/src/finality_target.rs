@@ -21,10 +21,17 @@
 use crate::finality_pipeline::SubstrateFinalitySyncPipeline;
 
 use async_trait::async_trait;
+use bp_header_chain::{
+	justification::{verify_justification, GrandpaJustification},
+	AuthoritySet,
+};
 use codec::Decode;
+use finality_grandpa::{voter_set::VoterSet, BlockNumberOps};
 use finality_relay::TargetClient;
-use relay_substrate_client::{Chain, Client, Error as SubstrateError};
+use relay_substrate_client::{Chain, ChainWithTips, Client, Error as SubstrateError, SyncHeader};
 use relay_utils::relay_loop::Client as RelayClient;
+use sp_core::storage::StorageKey;
+use sp_runtime::traits::Header as HeaderT;
 
 /// Substrate client as Substrate finality target.
 pub struct SubstrateFinalityTarget<C: Chain, P> {
@@ -55,15 +62,30 @@ impl<C: Chain, P: SubstrateFinalitySyncPipeline> RelayClient for SubstrateFinali
 	async fn reconnect(&mut self) -> Result<(), SubstrateError> {
 		self.client.reconnect().await
 	}
+
+	async fn resync(&mut self) -> Result<(), SubstrateError> {
+		self.client.resync().await
+	}
+
+	async fn failover(&mut self) -> Result<bool, SubstrateError> {
+		self.client.failover().await
+	}
 }
 
 #[async_trait]
-impl<C, P> TargetClient<P> for SubstrateFinalityTarget<C, P>
+impl<C, P, H> TargetClient<P> for SubstrateFinalityTarget<C, P>
 where
-	C: Chain,
-	P::Number: Decode,
-	P::Hash: Decode,
-	P: SubstrateFinalitySyncPipeline<TargetChain = C>,
+	C: ChainWithTips,
+	H: HeaderT,
+	H::Number: Decode + BlockNumberOps,
+	H::Hash: Decode,
+	P: SubstrateFinalitySyncPipeline<
+		Hash = H::Hash,
+		Number = H::Number,
+		Header = SyncHeader<H>,
+		FinalityProof = GrandpaJustification<H>,
+		TargetChain = C,
+	>,
 {
 	async fn best_finalized_source_block_number(&self) -> Result<P::Number, SubstrateError> {
 		// we can't continue to relay finality if target node is out of sync, because
@@ -80,12 +102,120 @@ where
 	}
 
 	async fn submit_finality_proof(&self, header: P::Header, proof: P::FinalityProof) -> Result<(), SubstrateError> {
+		// verify justification before submitting it, so that we don't pay for the transaction
+		// that is going to fail anyway (e.g. if source node returns junk during authorities
+		// set handoff)
+		let authority_set: AuthoritySet = self
+			.client
+			.storage_value(current_authority_set_key(P::GRANDPA_PALLET_NAME_AT_TARGET))
+			.await?
+			.ok_or(SubstrateError::UninitializedBridgePallet)?;
+		verify_justification_at_target::<P, H>(authority_set, &header, &proof)?;
+
+		let transactions_author = self.pipeline.transactions_author();
+		let tip = self
+			.client
+			.select_transaction_tip(&transactions_author, self.pipeline.max_transaction_tip())
+			.await?;
 		self.client
-			.submit_signed_extrinsic(self.pipeline.transactions_author(), move |transaction_nonce| {
-				self.pipeline
-					.make_submit_finality_proof_transaction(transaction_nonce, header, proof)
+			.submit_signed_extrinsic(transactions_author, move |best_block_id, transaction_nonce| {
+				self.pipeline.make_submit_finality_proof_transaction(
+					best_block_id,
+					transaction_nonce,
+					tip,
+					header,
+					proof,
+				)
 			})
 			.await
 			.map(drop)
 	}
 }
+
+/// Verify justification using the authorities set that is currently used by the GRANDPA pallet
+/// at the target chain.
+fn verify_justification_at_target<P, H>(
+	authority_set: AuthoritySet,
+	header: &H,
+	justification: &GrandpaJustification<H>,
+) -> Result<(), SubstrateError>
+where
+	P: SubstrateFinalitySyncPipeline,
+	H: HeaderT,
+	H::Number: BlockNumberOps,
+{
+	let voter_set = VoterSet::new(authority_set.authorities.clone()).ok_or_else(|| {
+		SubstrateError::Custom(format!(
+			"Read invalid {} authorities set from {}: {:?}",
+			P::SOURCE_NAME,
+			P::TARGET_NAME,
+			authority_set,
+		))
+	})?;
+
+	verify_justification::<H>(
+		(header.hash(), *header.number()),
+		authority_set.set_id,
+		&voter_set,
+		justification,
+	)
+	.map_err(|err| {
+		SubstrateError::Custom(format!(
+			"Refusing to submit invalid justification of {} header {:?} to {}: {:?}",
+			P::SOURCE_NAME,
+			header.hash(),
+			P::TARGET_NAME,
+			err,
+		))
+	})
+}
+
+/// Storage key of the `CurrentAuthoritySet` value of the GRANDPA pallet with given name.
+fn current_authority_set_key(pallet_name: &str) -> StorageKey {
+	let mut key = sp_core::twox_128(pallet_name.as_bytes()).to_vec();
+	key.extend_from_slice(&sp_core::twox_128(b"CurrentAuthoritySet"));
+	StorageKey(key)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::chains::millau_headers_to_rialto::MillauFinalityToRialto;
+	use bp_test_utils::{authority_list, make_default_justification, test_header, TEST_GRANDPA_SET_ID};
+
+	#[test]
+	fn valid_justification_is_accepted() {
+		let header = test_header::<bp_millau::Header>(1);
+		let justification = make_default_justification(&header);
+		assert!(verify_justification_at_target::<MillauFinalityToRialto, _>(
+			AuthoritySet::new(authority_list(), TEST_GRANDPA_SET_ID),
+			&header,
+			&justification,
+		)
+		.is_ok());
+	}
+
+	#[test]
+	fn justification_of_other_authorities_set_is_rejected() {
+		let header = test_header::<bp_millau::Header>(1);
+		let justification = make_default_justification(&header);
+		assert!(verify_justification_at_target::<MillauFinalityToRialto, _>(
+			AuthoritySet::new(authority_list(), TEST_GRANDPA_SET_ID + 1),
+			&header,
+			&justification,
+		)
+		.is_err());
+	}
+
+	#[test]
+	fn justification_of_other_header_is_rejected() {
+		let header = test_header::<bp_millau::Header>(1);
+		let justification = make_default_justification(&test_header::<bp_millau::Header>(2));
+		assert!(verify_justification_at_target::<MillauFinalityToRialto, _>(
+			AuthoritySet::new(authority_list(), TEST_GRANDPA_SET_ID),
+			&header,
+			&justification,
+		)
+		.is_err());
+	}
+}
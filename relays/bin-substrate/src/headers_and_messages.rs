@@ -0,0 +1,276 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Complex (bi-directional) headers+messages relay between two Substrate chains.
+//!
+//! The relay runs two on-demand finality relays (one in every direction) and two messages
+//! relays (again, one in every direction) for every served lane. Message relays are requesting
+//! headers from on-demand finality relays when they need them to prove messages or delivery
+//! confirmations.
+//!
+//! Everything that is specific to the chain pair is provided by the `HeadersAndMessagesBridge`
+//! trait implementation. The left chain is the first chain of the pair and the right chain is
+//! the second one - there's no source/target chains here, because data flows in both directions.
+
+use crate::messages_lane::MessagesRelayParams;
+use crate::on_demand_headers::OnDemandHeadersRelay;
+use crate::relay_info::{register_relay_info, RelayInfoMode};
+
+use bp_messages::LaneId;
+use futures::{future::BoxFuture, FutureExt, TryFutureExt};
+use messages_relay::{message_lane::MessageLane, message_lane_loop::RelayerMode};
+use relay_substrate_client::{BalanceOf, Chain, ChainWithBalances, Client};
+use relay_utils::{
+	control::{RelayControl, Reloadable},
+	metrics::MetricsParams,
+	recovery::RecoveryPolicyParams,
+};
+use sp_runtime::AccountId32;
+
+/// Bridge between two chains that is served by the complex headers+messages relay.
+pub trait HeadersAndMessagesBridge {
+	/// The left chain of the bridge.
+	type Left: ChainWithBalances<AccountId = AccountId32>;
+	/// Signing parameters of the left chain.
+	type LeftSign: Clone + Send + Sync + 'static;
+	/// The right chain of the bridge.
+	type Right: ChainWithBalances<AccountId = AccountId32>;
+	/// Signing parameters of the right chain.
+	type RightSign: Clone + Send + Sync + 'static;
+
+	/// Left -> Right messages lane.
+	type LeftToRightMessages: MessageLane;
+	/// Right -> Left messages lane.
+	type RightToLeftMessages: MessageLane;
+
+	/// Start on-demand Left -> Right headers relay.
+	fn left_to_right_on_demand_headers(
+		left_client: Client<Self::Left>,
+		right_client: Client<Self::Right>,
+		right_sign: Self::RightSign,
+		right_transactions_mortality: Option<u32>,
+		right_proxied_account: Option<AccountId32>,
+		right_max_tip: BalanceOf<Self::Right>,
+		only_mandatory_headers: bool,
+	) -> OnDemandHeadersRelay<Self::Left>;
+
+	/// Start on-demand Right -> Left headers relay.
+	fn right_to_left_on_demand_headers(
+		right_client: Client<Self::Right>,
+		left_client: Client<Self::Left>,
+		left_sign: Self::LeftSign,
+		left_transactions_mortality: Option<u32>,
+		left_proxied_account: Option<AccountId32>,
+		left_max_tip: BalanceOf<Self::Left>,
+		only_mandatory_headers: bool,
+	) -> OnDemandHeadersRelay<Self::Right>;
+
+	/// Run Left -> Right messages relay.
+	fn left_to_right_messages(
+		params: MessagesRelayParams<Self::Left, Self::LeftSign, Self::Right, Self::RightSign>,
+	) -> BoxFuture<'static, Result<(), String>>;
+
+	/// Run Right -> Left messages relay.
+	fn right_to_left_messages(
+		params: MessagesRelayParams<Self::Right, Self::RightSign, Self::Left, Self::LeftSign>,
+	) -> BoxFuture<'static, Result<(), String>>;
+}
+
+/// Parameters of the complex headers+messages relay.
+pub struct HeadersAndMessagesRelayParams<B: HeadersAndMessagesBridge> {
+	/// Left chain client.
+	pub left_client: Client<B::Left>,
+	/// Sign parameters for the left chain.
+	pub left_sign: B::LeftSign,
+	/// Mortality period of the left chain transactions. `None` if transactions are immortal.
+	pub left_transactions_mortality: Option<u32>,
+	/// If set, all left chain transactions are wrapped into `proxy` calls on behalf of this account.
+	pub left_proxied_account: Option<AccountId32>,
+	/// Maximal tip that may be attached to left chain transactions.
+	pub left_max_tip: BalanceOf<B::Left>,
+	/// Right chain client.
+	pub right_client: Client<B::Right>,
+	/// Sign parameters for the right chain.
+	pub right_sign: B::RightSign,
+	/// Mortality period of the right chain transactions. `None` if transactions are immortal.
+	pub right_transactions_mortality: Option<u32>,
+	/// If set, all right chain transactions are wrapped into `proxy` calls on behalf of this account.
+	pub right_proxied_account: Option<AccountId32>,
+	/// Maximal tip that may be attached to right chain transactions.
+	pub right_max_tip: BalanceOf<B::Right>,
+	/// If true, only mandatory headers are relayed, unless other headers are required by messages relays.
+	pub only_mandatory_headers: bool,
+	/// Lanes that should be served by the relay.
+	pub lanes: Vec<LaneId>,
+	/// Relayer operating mode of messages relays.
+	pub relayer_mode: RelayerMode,
+	/// Maximal number of blocks for which the messages receiving confirmations may be delayed.
+	pub max_confirmations_delay: u32,
+	/// If true, messages delivery transactions also confirm delivery of messages in the opposite direction.
+	pub piggyback_confirmations: bool,
+	/// Maximal number of messages delivery transactions that may be submitted, but not yet mined.
+	pub max_delivery_transactions_in_flight: usize,
+	/// If not empty, relays of all lanes only deliver messages that are sent by given accounts.
+	pub only_messages_from: Vec<AccountId32>,
+	/// Additional per-lane accounts whose messages are delivered by the relays of given lanes.
+	pub only_lane_messages_from: Vec<(LaneId, AccountId32)>,
+	/// Relay control interface registry.
+	pub control: RelayControl,
+	/// Recovery policy of the left chain client.
+	pub left_recovery_policy: RecoveryPolicyParams,
+	/// Recovery policy of the right chain client.
+	pub right_recovery_policy: RecoveryPolicyParams,
+	/// Metrics parameters.
+	pub metrics_params: MetricsParams,
+}
+
+/// Run complex headers+messages relay.
+pub async fn run<B: HeadersAndMessagesBridge>(params: HeadersAndMessagesRelayParams<B>) -> anyhow::Result<()> {
+	let HeadersAndMessagesRelayParams {
+		left_client,
+		left_sign,
+		left_transactions_mortality,
+		left_proxied_account,
+		left_max_tip,
+		right_client,
+		right_sign,
+		right_transactions_mortality,
+		right_proxied_account,
+		right_max_tip,
+		only_mandatory_headers,
+		lanes,
+		relayer_mode,
+		max_confirmations_delay,
+		piggyback_confirmations,
+		max_delivery_transactions_in_flight,
+		only_messages_from,
+		only_lane_messages_from,
+		control,
+		left_recovery_policy,
+		right_recovery_policy,
+		metrics_params,
+	} = params;
+
+	let metrics_params = relay_utils::relay_metrics(None, metrics_params).into_params();
+	register_relay_info(
+		&metrics_params,
+		B::Left::NAME,
+		B::Right::NAME,
+		&lanes,
+		RelayInfoMode::HeadersAndMessages(relayer_mode),
+	)
+	.map_err(|e| anyhow::format_err!("{}", e))?;
+
+	let left_to_right_on_demand_headers = B::left_to_right_on_demand_headers(
+		left_client.clone(),
+		right_client.clone(),
+		right_sign.clone(),
+		right_transactions_mortality,
+		right_proxied_account.clone(),
+		right_max_tip,
+		only_mandatory_headers,
+	);
+	let right_to_left_on_demand_headers = B::right_to_left_on_demand_headers(
+		right_client.clone(),
+		left_client.clone(),
+		left_sign.clone(),
+		left_transactions_mortality,
+		left_proxied_account.clone(),
+		left_max_tip,
+		only_mandatory_headers,
+	);
+
+	// Need 2x capacity since we consider both directions for each lane
+	let mut message_relays = Vec::with_capacity(lanes.len() * 2);
+	for lane in lanes {
+		let lane_only_messages_from = only_messages_from
+			.iter()
+			.cloned()
+			.chain(
+				only_lane_messages_from
+					.iter()
+					.filter(|(sender_lane, _)| *sender_lane == lane)
+					.map(|(_, sender)| sender.clone()),
+			)
+			.collect::<Vec<_>>();
+		let left_to_right_messages = B::left_to_right_messages(MessagesRelayParams {
+			source_client: left_client.clone(),
+			source_sign: left_sign.clone(),
+			source_transactions_mortality: left_transactions_mortality,
+			source_proxied_account: left_proxied_account.clone(),
+			target_client: right_client.clone(),
+			target_sign: right_sign.clone(),
+			target_transactions_mortality: right_transactions_mortality,
+			target_proxied_account: right_proxied_account.clone(),
+			target_max_tip: right_max_tip,
+			source_to_target_headers_relay: Some(left_to_right_on_demand_headers.clone()),
+			target_to_source_headers_relay: Some(right_to_left_on_demand_headers.clone()),
+			lane_id: lane,
+			relayer_mode: Reloadable::new(relayer_mode),
+			only_messages_from: lane_only_messages_from.clone(),
+			max_confirmations_delay: Reloadable::new(max_confirmations_delay),
+			piggyback_confirmations,
+			max_delivery_transactions_in_flight: Reloadable::new(max_delivery_transactions_in_flight),
+			forced_delivery: None,
+			control: control.clone(),
+			source_recovery_policy: left_recovery_policy.clone(),
+			target_recovery_policy: right_recovery_policy.clone(),
+			metrics_params: metrics_params.clone().disable().metrics_prefix(
+				messages_relay::message_lane_loop::metrics_prefix::<B::LeftToRightMessages>(&lane),
+			),
+		})
+		.map_err(|e| anyhow::format_err!("{}", e))
+		.boxed();
+		let right_to_left_messages = B::right_to_left_messages(MessagesRelayParams {
+			source_client: right_client.clone(),
+			source_sign: right_sign.clone(),
+			source_transactions_mortality: right_transactions_mortality,
+			source_proxied_account: right_proxied_account.clone(),
+			target_client: left_client.clone(),
+			target_sign: left_sign.clone(),
+			target_transactions_mortality: left_transactions_mortality,
+			target_proxied_account: left_proxied_account.clone(),
+			target_max_tip: left_max_tip,
+			source_to_target_headers_relay: Some(right_to_left_on_demand_headers.clone()),
+			target_to_source_headers_relay: Some(left_to_right_on_demand_headers.clone()),
+			lane_id: lane,
+			relayer_mode: Reloadable::new(relayer_mode),
+			only_messages_from: lane_only_messages_from,
+			max_confirmations_delay: Reloadable::new(max_confirmations_delay),
+			piggyback_confirmations,
+			max_delivery_transactions_in_flight: Reloadable::new(max_delivery_transactions_in_flight),
+			forced_delivery: None,
+			control: control.clone(),
+			source_recovery_policy: right_recovery_policy.clone(),
+			target_recovery_policy: left_recovery_policy.clone(),
+			metrics_params: metrics_params.clone().disable().metrics_prefix(
+				messages_relay::message_lane_loop::metrics_prefix::<B::RightToLeftMessages>(&lane),
+			),
+		})
+		.map_err(|e| anyhow::format_err!("{}", e))
+		.boxed();
+
+		message_relays.push(left_to_right_messages);
+		message_relays.push(right_to_left_messages);
+	}
+
+	relay_utils::relay_metrics(None, metrics_params)
+		.expose()
+		.await
+		.map_err(|e| anyhow::format_err!("{}", e))?;
+
+	futures::future::select_all(message_relays).await.0
+}
@@ -28,10 +28,11 @@ use bp_header_chain::{
 };
 use codec::Decode;
 use finality_grandpa::voter_set::VoterSet;
+use futures::StreamExt;
 use num_traits::{One, Zero};
-use relay_substrate_client::{Chain, Client};
+use relay_substrate_client::{Chain, Client, HeaderIdOf};
 use sp_core::Bytes;
-use sp_finality_grandpa::AuthorityList as GrandpaAuthoritiesSet;
+use sp_finality_grandpa::{AuthorityList as GrandpaAuthoritiesSet, SetId};
 use sp_runtime::traits::Header as HeaderT;
 
 /// Submit headers-bridge initialization transaction.
@@ -39,7 +40,11 @@ pub async fn initialize<SourceChain: Chain, TargetChain: Chain>(
 	source_client: Client<SourceChain>,
 	target_client: Client<TargetChain>,
 	target_transactions_signer: TargetChain::AccountId,
-	prepare_initialize_transaction: impl FnOnce(TargetChain::Index, InitializationData<SourceChain::Header>) -> Bytes,
+	prepare_initialize_transaction: impl FnOnce(
+		HeaderIdOf<TargetChain>,
+		TargetChain::Index,
+		InitializationData<SourceChain::Header>,
+	) -> Bytes,
 ) {
 	let result = do_initialize(
 		source_client,
@@ -72,7 +77,11 @@ async fn do_initialize<SourceChain: Chain, TargetChain: Chain>(
 	source_client: Client<SourceChain>,
 	target_client: Client<TargetChain>,
 	target_transactions_signer: TargetChain::AccountId,
-	prepare_initialize_transaction: impl FnOnce(TargetChain::Index, InitializationData<SourceChain::Header>) -> Bytes,
+	prepare_initialize_transaction: impl FnOnce(
+		HeaderIdOf<TargetChain>,
+		TargetChain::Index,
+		InitializationData<SourceChain::Header>,
+	) -> Bytes,
 ) -> Result<TargetChain::Hash, String> {
 	let initialization_data = prepare_initialization_data(source_client).await?;
 	log::info!(
@@ -84,18 +93,53 @@ async fn do_initialize<SourceChain: Chain, TargetChain: Chain>(
 	);
 
 	let initialization_tx_hash = target_client
-		.submit_signed_extrinsic(target_transactions_signer, move |transaction_nonce| {
-			prepare_initialize_transaction(transaction_nonce, initialization_data)
+		.submit_signed_extrinsic(target_transactions_signer, move |best_block_id, transaction_nonce| {
+			prepare_initialize_transaction(best_block_id, transaction_nonce, initialization_data)
 		})
 		.await
 		.map_err(|err| format!("Failed to submit {} transaction: {:?}", TargetChain::NAME, err))?;
 	Ok(initialization_tx_hash)
 }
 
+/// Finalized header of the source chain with justification and all data required to verify it.
+pub struct FinalityProofData<SourceChain: Chain> {
+	/// Finalized header.
+	pub header: SourceChain::Header,
+	/// Justification that finalizes the header.
+	pub justification: GrandpaJustification<SourceChain::Header>,
+	/// Id of the GRANDPA authorities set that has generated the justification.
+	pub set_id: SetId,
+	/// GRANDPA authorities set that has generated the justification.
+	pub authorities: GrandpaAuthoritiesSet,
+	/// GRANDPA authorities set that is enacted by the header, if it schedules authorities set change.
+	pub next_authorities: Option<GrandpaAuthoritiesSet>,
+}
+
 /// Prepare initialization data for the GRANDPA verifier pallet.
 async fn prepare_initialization_data<SourceChain: Chain>(
 	source_client: Client<SourceChain>,
 ) -> Result<InitializationData<SourceChain::Header>, String> {
+	let finality_proof_data = prepare_finality_proof_data(source_client).await?;
+	Ok(match finality_proof_data.next_authorities {
+		Some(next_authorities) => InitializationData {
+			header: finality_proof_data.header,
+			authority_list: next_authorities,
+			set_id: finality_proof_data.set_id + 1,
+			is_halted: false,
+		},
+		None => InitializationData {
+			header: finality_proof_data.header,
+			authority_list: finality_proof_data.authorities,
+			set_id: finality_proof_data.set_id,
+			is_halted: false,
+		},
+	})
+}
+
+/// Wait for the next justification of the source chain and read all data that is required to verify it.
+pub async fn prepare_finality_proof_data<SourceChain: Chain>(
+	source_client: Client<SourceChain>,
+) -> Result<FinalityProofData<SourceChain>, String> {
 	// In ideal world we just need to get best finalized header and then to read GRANDPA authorities
 	// set (`pallet_grandpa::CurrentSetId` + `GrandpaApi::grandpa_authorities()`) at this header.
 	//
@@ -161,7 +205,7 @@ async fn prepare_initialization_data<SourceChain: Chain>(
 	// Now let's try to guess authorities set id by verifying justification.
 	let mut initial_authorities_set_id = 0;
 	let mut min_possible_block_number = SourceChain::BlockNumber::zero();
-	let authorities_for_verification = VoterSet::new(authorities_for_verification.clone()).ok_or_else(|| {
+	let voter_set = VoterSet::new(authorities_for_verification.clone()).ok_or_else(|| {
 		format!(
 			"Read invalid {} authorities set: {:?}",
 			SourceChain::NAME,
@@ -178,7 +222,7 @@ async fn prepare_initialization_data<SourceChain: Chain>(
 		let is_valid_set_id = verify_justification::<SourceChain::Header>(
 			(initial_header_hash, initial_header_number),
 			initial_authorities_set_id,
-			&authorities_for_verification,
+			&voter_set,
 			&justification,
 		)
 		.is_ok();
@@ -202,15 +246,18 @@ async fn prepare_initialization_data<SourceChain: Chain>(
 		}
 	}
 
-	Ok(InitializationData {
+	let (authorities, next_authorities) = if schedules_change {
+		(authorities_for_verification, Some(initial_authorities_set))
+	} else {
+		(initial_authorities_set, None)
+	};
+
+	Ok(FinalityProofData {
 		header: initial_header,
-		authority_list: initial_authorities_set,
-		set_id: if schedules_change {
-			initial_authorities_set_id + 1
-		} else {
-			initial_authorities_set_id
-		},
-		is_halted: false,
+		justification,
+		set_id: initial_authorities_set_id,
+		authorities,
+		next_authorities,
 	})
 }
 
@@ -0,0 +1,180 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Discovery of message lanes from the source chain storage.
+//!
+//! Instead of serving static list of lanes, the messages relay may discover lanes from the
+//! outbound lanes states, stored by the source chain messages pallet. Relay loop of the lane is
+//! started when there are undelivered messages or unconfirmed deliveries at the lane. When all
+//! messages are delivered and confirmed, the loop is paused (using the relay control interface)
+//! and it is resumed when new messages are sent over the lane.
+
+use bp_messages::{LaneId, OutboundLaneData};
+use futures::{
+	future::{BoxFuture, Either},
+	stream::FuturesUnordered,
+	StreamExt,
+};
+use relay_substrate_client::{Chain, Client};
+use relay_utils::control::RelayControl;
+use sp_core::storage::StorageKey;
+use std::{collections::BTreeSet, time::Duration};
+
+/// Interval between lanes discovery iterations.
+const LANES_DISCOVERY_INTERVAL: Duration = Duration::from_secs(60);
+/// Size of the `blake2_128_concat` hash, which precedes the lane id in the outbound lane state key.
+const BLAKE2_128_HASH_SIZE: usize = 16;
+
+/// Returns true if there are undelivered messages or unconfirmed deliveries at the outbound lane.
+pub fn is_lane_active(lane_data: &OutboundLaneData) -> bool {
+	lane_data.latest_received_nonce < lane_data.latest_generated_nonce
+}
+
+/// Read all outbound lanes (along with their states) from the source chain storage.
+pub async fn read_outbound_lanes<C: Chain>(
+	client: &Client<C>,
+	outbound_lanes_prefix: &StorageKey,
+) -> anyhow::Result<Vec<(LaneId, OutboundLaneData)>> {
+	let keys = client
+		.storage_keys(outbound_lanes_prefix.clone())
+		.await
+		.map_err(|e| anyhow::format_err!("Failed to read {} outbound lanes: {:?}", C::NAME, e))?;
+
+	let mut lanes = Vec::with_capacity(keys.len());
+	for key in keys {
+		let lane = match lane_id_from_outbound_lane_key(outbound_lanes_prefix, &key) {
+			Some(lane) => lane,
+			None => {
+				log::warn!(
+					target: "bridge",
+					"Ignoring unexpected {} outbound lane key: {}",
+					C::NAME,
+					hex::encode(&key.0),
+				);
+				continue;
+			}
+		};
+		let lane_data = client
+			.storage_value::<OutboundLaneData>(key)
+			.await
+			.map_err(|e| anyhow::format_err!("Failed to read {} outbound lane {}: {:?}", C::NAME, lane, e))?;
+		if let Some(lane_data) = lane_data {
+			lanes.push((lane, lane_data));
+		}
+	}
+
+	Ok(lanes)
+}
+
+/// Serve lanes that are discovered from the source chain storage.
+///
+/// The `start_lane` is called when the lane becomes active for the first time. The returned future
+/// is never restarted: when the lane becomes idle, the loop with `loop_name(lane)` name is paused.
+/// Loops that have been paused by other means (e.g. by the operator) are never resumed here.
+/// The function only returns when any of started lane relays has stopped.
+pub async fn run_discovered_lanes<C: Chain>(
+	source_client: Client<C>,
+	outbound_lanes_prefix: StorageKey,
+	control: RelayControl,
+	loop_name: impl Fn(&LaneId) -> String,
+	start_lane: impl Fn(LaneId) -> BoxFuture<'static, Result<(), String>>,
+) -> anyhow::Result<()> {
+	let mut started_lanes = BTreeSet::new();
+	let mut paused_lanes = BTreeSet::new();
+	let mut lane_relays = FuturesUnordered::new();
+	loop {
+		match read_outbound_lanes(&source_client, &outbound_lanes_prefix).await {
+			Ok(lanes) => {
+				for (lane, lane_data) in lanes {
+					let is_active = is_lane_active(&lane_data);
+					if !started_lanes.contains(&lane) {
+						if is_active {
+							log::info!(target: "bridge", "Starting relay of discovered {} lane {}", C::NAME, lane);
+							started_lanes.insert(lane);
+							lane_relays.push(start_lane(lane));
+						}
+						continue;
+					}
+
+					let loop_control = control.loop_control(loop_name(&lane));
+					if is_active && paused_lanes.remove(&lane) {
+						log::info!(target: "bridge", "Resuming relay of active {} lane {}", C::NAME, lane);
+						loop_control.set_paused(false);
+					} else if !is_active && !loop_control.is_paused() {
+						log::info!(target: "bridge", "Pausing relay of idle {} lane {}", C::NAME, lane);
+						loop_control.set_paused(true);
+						paused_lanes.insert(lane);
+					}
+				}
+			}
+			Err(e) => log::warn!(target: "bridge", "{:?}. Going to retry in {:?}", e, LANES_DISCOVERY_INTERVAL),
+		}
+
+		let tick = async_std::task::sleep(LANES_DISCOVERY_INTERVAL);
+		if lane_relays.is_empty() {
+			tick.await;
+			continue;
+		}
+
+		futures::pin_mut!(tick);
+		if let Either::Left((Some(result), _)) = futures::future::select(lane_relays.next(), tick).await {
+			return result.map_err(|e| anyhow::format_err!("{}", e));
+		}
+	}
+}
+
+/// Extract lane id from the outbound lane state storage key.
+fn lane_id_from_outbound_lane_key(outbound_lanes_prefix: &StorageKey, key: &StorageKey) -> Option<LaneId> {
+	let hashed_lane = key.0.strip_prefix(&outbound_lanes_prefix.0[..])?;
+	if hashed_lane.len() != BLAKE2_128_HASH_SIZE + 4 {
+		return None;
+	}
+
+	let mut lane = [0u8; 4];
+	lane.copy_from_slice(&hashed_lane[BLAKE2_128_HASH_SIZE..]);
+	Some(LaneId(lane))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lane_id_is_extracted_from_outbound_lane_key() {
+		let prefix =
+			pallet_bridge_messages::storage_keys::outbound_lanes_prefix::<millau_runtime::WithRialtoMessagesInstance>();
+		let key = pallet_bridge_messages::storage_keys::outbound_lane_data_key::<
+			millau_runtime::WithRialtoMessagesInstance,
+		>(&LaneId(*b"test"));
+
+		assert_eq!(lane_id_from_outbound_lane_key(&prefix, &key), Some(LaneId(*b"test")));
+		assert_eq!(lane_id_from_outbound_lane_key(&prefix, &prefix), None);
+		assert_eq!(lane_id_from_outbound_lane_key(&StorageKey(vec![42]), &key), None);
+	}
+
+	#[test]
+	fn lane_with_pending_messages_or_confirmations_is_active() {
+		let lane_data = |latest_received_nonce, latest_generated_nonce| OutboundLaneData {
+			oldest_unpruned_nonce: 1,
+			latest_received_nonce,
+			latest_generated_nonce,
+		};
+
+		assert!(!is_lane_active(&lane_data(0, 0)));
+		assert!(!is_lane_active(&lane_data(10, 10)));
+		assert!(is_lane_active(&lane_data(5, 10)));
+	}
+}
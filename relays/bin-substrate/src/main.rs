@@ -20,19 +20,42 @@
 
 mod chains;
 mod cli;
+mod conversion_rate_update;
 mod finality_pipeline;
 mod finality_target;
+mod headers_and_messages;
 mod headers_initialize;
+mod lanes_discovery;
+mod messages_config;
 mod messages_lane;
+mod messages_metrics;
 mod messages_source;
 mod messages_target;
 mod on_demand_headers;
+mod relay_info;
+mod relayer_funding;
+mod relayer_rewards;
 
 fn main() {
 	let command = cli::parse_args();
 	let run = command.run();
 	let result = async_std::task::block_on(run);
 	if let Err(error) = result {
-		log::error!(target: "bridge", "Failed to start relay: {}", error);
+		relay_utils::error::exit_on_error(error_kind(&error), format!("failed to start relay: {:#}", error));
 	}
 }
+
+/// Returns kind of the (first known) error in the chain.
+fn error_kind(error: &anyhow::Error) -> Option<relay_utils::ErrorKind> {
+	use relay_utils::MaybeConnectionError;
+
+	error.chain().find_map(|error| {
+		if let Some(error) = error.downcast_ref::<relay_utils::error::Error>() {
+			return Some(error.kind());
+		}
+
+		error
+			.downcast_ref::<relay_substrate_client::Error>()
+			.map(MaybeConnectionError::error_kind)
+	})
+}
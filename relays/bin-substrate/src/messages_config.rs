@@ -0,0 +1,327 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reloadable configuration of the messages relay.
+//!
+//! Some of the messages relay parameters may be read from the TOML configuration file:
+//!
+//! ```toml
+//! lanes = "00000000,00000001"
+//! relayer-mode = "Altruistic"
+//! max-confirmations-delay = 10
+//! max-delivery-transactions-in-flight = 4
+//! ```
+//!
+//! Values from the file override command line values. When the relay is asked to reload its
+//! configuration (using the `relay_reloadConfig` control call), the file is read again and
+//! updated parameters are applied to running lane relays. Connections to nodes are kept and
+//! lane relays are not restarted: new lanes are started, removed lanes are paused and resumed
+//! if they are added back later.
+
+use crate::cli::{relay_messages::RelayerMode as CliRelayerMode, Lanes};
+
+use bp_messages::LaneId;
+use futures::{future::BoxFuture, stream::FuturesUnordered, StreamExt};
+use messages_relay::message_lane_loop::RelayerMode;
+use relay_utils::control::{RelayControl, Reloadable};
+use serde::{Deserialize, Deserializer};
+use std::{
+	collections::BTreeSet,
+	fmt::Display,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+
+/// Messages relay parameters that may be changed by reloading the configuration file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessagesRelayConfig {
+	/// Lanes that are served by the relay. `None` if the relay serves the single lane, specified
+	/// with the `--lane` option.
+	pub lanes: Option<Lanes>,
+	/// Relayer operating mode.
+	pub relayer_mode: RelayerMode,
+	/// Maximal number of source chain blocks for which the messages receiving confirmations may be delayed.
+	pub max_confirmations_delay: u32,
+	/// Maximal number of delivery transactions that may be submitted, but not yet mined.
+	pub max_delivery_transactions_in_flight: usize,
+}
+
+/// Contents of the configuration file. Values that are missing from the file, are not changed.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct ConfigFile {
+	#[serde(default, deserialize_with = "deserialize_from_str")]
+	lanes: Option<Lanes>,
+	#[serde(default, deserialize_with = "deserialize_from_str")]
+	relayer_mode: Option<CliRelayerMode>,
+	max_confirmations_delay: Option<u32>,
+	max_delivery_transactions_in_flight: Option<usize>,
+}
+
+impl MessagesRelayConfig {
+	/// Override parameters with values from given configuration file.
+	pub fn override_from_file(self, path: &Path) -> anyhow::Result<Self> {
+		let config = std::fs::read_to_string(path)
+			.map_err(|e| anyhow::format_err!("Failed to read relay configuration from {}: {}", path.display(), e))?;
+		self.override_from_str(&config)
+			.map_err(|e| anyhow::format_err!("Invalid relay configuration file {}: {}", path.display(), e))
+	}
+
+	/// Override parameters with values from given configuration file contents.
+	fn override_from_str(self, config: &str) -> Result<Self, String> {
+		let config: ConfigFile = toml::from_str(config).map_err(|e| e.to_string())?;
+		Ok(MessagesRelayConfig {
+			lanes: config.lanes.or(self.lanes),
+			relayer_mode: config.relayer_mode.map(Into::into).unwrap_or(self.relayer_mode),
+			max_confirmations_delay: config.max_confirmations_delay.unwrap_or(self.max_confirmations_delay),
+			max_delivery_transactions_in_flight: config
+				.max_delivery_transactions_in_flight
+				.unwrap_or(self.max_delivery_transactions_in_flight),
+		})
+	}
+}
+
+/// Messages relay parameters that are shared by all running lane relays.
+///
+/// Cloning `ReloadableMessagesRelayConfig` is a cheap operation.
+#[derive(Debug, Clone)]
+pub struct ReloadableMessagesRelayConfig {
+	/// Lanes that are served by the relay.
+	pub lanes: Reloadable<Option<Lanes>>,
+	/// Relayer operating mode.
+	pub relayer_mode: Reloadable<RelayerMode>,
+	/// Maximal number of source chain blocks for which the messages receiving confirmations may be delayed.
+	pub max_confirmations_delay: Reloadable<u32>,
+	/// Maximal number of delivery transactions that may be submitted, but not yet mined.
+	pub max_delivery_transactions_in_flight: Reloadable<usize>,
+}
+
+impl From<MessagesRelayConfig> for ReloadableMessagesRelayConfig {
+	fn from(config: MessagesRelayConfig) -> Self {
+		ReloadableMessagesRelayConfig {
+			lanes: Reloadable::new(config.lanes),
+			relayer_mode: Reloadable::new(config.relayer_mode),
+			max_confirmations_delay: Reloadable::new(config.max_confirmations_delay),
+			max_delivery_transactions_in_flight: Reloadable::new(config.max_delivery_transactions_in_flight),
+		}
+	}
+}
+
+impl ReloadableMessagesRelayConfig {
+	/// Apply updated configuration to running lane relays.
+	///
+	/// The set of static lanes may be changed, but the relay can't switch between single lane,
+	/// static lanes and auto-discovered lanes modes without restart.
+	pub fn update(&self, config: MessagesRelayConfig) {
+		let lanes = self.lanes.get();
+		if config.lanes != lanes {
+			match (&lanes, &config.lanes) {
+				(Some(Lanes::Static(_)), Some(Lanes::Static(_))) => self.lanes.set(config.lanes),
+				_ => log::warn!(
+					target: "bridge",
+					"Relay must be restarted to serve {:?} lanes instead of {:?}. Lanes are not changed",
+					config.lanes,
+					lanes,
+				),
+			}
+		}
+
+		self.relayer_mode.set(config.relayer_mode);
+		self.max_confirmations_delay.set(config.max_confirmations_delay);
+		self.max_delivery_transactions_in_flight
+			.set(config.max_delivery_transactions_in_flight);
+	}
+}
+
+/// Reload configuration file every time the relay is asked to.
+///
+/// Values that are missing from the file are taken from the `cli_config`. If the file can't be
+/// read or parsed, the current configuration is kept.
+pub async fn serve_reload_requests(
+	control: RelayControl,
+	path: PathBuf,
+	cli_config: MessagesRelayConfig,
+	config: ReloadableMessagesRelayConfig,
+) {
+	let mut reload_requests = control.reload_requests();
+	while reload_requests.next().await.is_some() {
+		match cli_config.clone().override_from_file(&path) {
+			Ok(new_config) => {
+				log::info!(
+					target: "bridge",
+					"Reloaded messages relay configuration from {}: {:?}",
+					path.display(),
+					new_config,
+				);
+				config.update(new_config);
+			}
+			Err(e) => log::warn!(target: "bridge", "{}. Keeping current configuration", e),
+		}
+	}
+}
+
+/// Serve static set of lanes, that may be changed by reloading the relay configuration.
+///
+/// Relay of the added lane is started with `start_lane`. Relay of the removed lane is paused (using
+/// loop with `loop_name(lane)` name) and it is resumed when the lane is added back. Lane relays
+/// are never restarted. The function only returns when any of started lane relays has stopped.
+pub async fn run_static_lanes(
+	lanes: Reloadable<Option<Lanes>>,
+	control: RelayControl,
+	loop_name: impl Fn(&LaneId) -> String,
+	start_lane: impl Fn(LaneId) -> BoxFuture<'static, Result<(), String>>,
+) -> anyhow::Result<()> {
+	let mut lanes_updates = lanes.updates();
+	let mut started_lanes = BTreeSet::new();
+	let mut removed_lanes = BTreeSet::new();
+	let mut lane_relays = FuturesUnordered::new();
+	loop {
+		let served_lanes = match lanes.get() {
+			Some(Lanes::Static(lanes)) => lanes.into_iter().collect::<BTreeSet<_>>(),
+			_ => BTreeSet::new(),
+		};
+		for lane in &served_lanes {
+			if started_lanes.insert(*lane) {
+				log::info!(target: "bridge", "Starting relay of lane {}", lane);
+				lane_relays.push(start_lane(*lane));
+			} else if removed_lanes.remove(lane) {
+				log::info!(target: "bridge", "Resuming relay of added lane {}", lane);
+				control.loop_control(loop_name(lane)).set_paused(false);
+			}
+		}
+		for lane in started_lanes.difference(&served_lanes) {
+			if removed_lanes.insert(*lane) {
+				log::info!(target: "bridge", "Pausing relay of removed lane {}", lane);
+				control.loop_control(loop_name(lane)).set_paused(true);
+			}
+		}
+
+		futures::select! {
+			result = lane_relays.select_next_some() => return result.map_err(|e| anyhow::format_err!("{}", e)),
+			_ = lanes_updates.next() => {},
+		}
+	}
+}
+
+/// Deserialize optional value from string, using its `FromStr` implementation.
+fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+	D: Deserializer<'de>,
+	T: FromStr,
+	T::Err: Display,
+{
+	String::deserialize(deserializer)?
+		.parse()
+		.map(Some)
+		.map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::FutureExt;
+	use std::sync::{Arc, Mutex};
+
+	fn cli_config() -> MessagesRelayConfig {
+		MessagesRelayConfig {
+			lanes: Some(Lanes::Static(vec![LaneId([0, 0, 0, 0])])),
+			relayer_mode: RelayerMode::Rational,
+			max_confirmations_delay: 0,
+			max_delivery_transactions_in_flight: 1,
+		}
+	}
+
+	#[test]
+	fn config_file_overrides_cli_values() {
+		assert_eq!(
+			cli_config().override_from_str(
+				r#"
+					lanes = "00000001,00000002"
+					relayer-mode = "altruistic"
+					max-delivery-transactions-in-flight = 4
+				"#
+			),
+			Ok(MessagesRelayConfig {
+				lanes: Some(Lanes::Static(vec![LaneId([0, 0, 0, 1]), LaneId([0, 0, 0, 2])])),
+				relayer_mode: RelayerMode::Altruistic,
+				max_confirmations_delay: 0,
+				max_delivery_transactions_in_flight: 4,
+			}),
+		);
+		assert_eq!(cli_config().override_from_str(""), Ok(cli_config()));
+	}
+
+	#[test]
+	fn invalid_config_file_is_rejected() {
+		assert!(cli_config().override_from_str("relayer-mode = \"Greedy\"").is_err());
+		assert!(cli_config().override_from_str("lanes = \"not-a-lane\"").is_err());
+		assert!(cli_config().override_from_str("unknown-option = 1").is_err());
+	}
+
+	#[test]
+	fn lanes_mode_is_not_changed_by_update() {
+		let config = ReloadableMessagesRelayConfig::from(cli_config());
+
+		config.update(MessagesRelayConfig {
+			lanes: Some(Lanes::Auto),
+			relayer_mode: RelayerMode::Altruistic,
+			..cli_config()
+		});
+		assert_eq!(config.lanes.get(), cli_config().lanes);
+		assert_eq!(config.relayer_mode.get(), RelayerMode::Altruistic);
+
+		config.update(MessagesRelayConfig {
+			lanes: Some(Lanes::Static(vec![LaneId([0, 0, 0, 1])])),
+			..cli_config()
+		});
+		assert_eq!(config.lanes.get(), Some(Lanes::Static(vec![LaneId([0, 0, 0, 1])])));
+	}
+
+	#[test]
+	fn static_lanes_are_started_and_paused() {
+		let control = RelayControl::default();
+		let lanes = Reloadable::new(Some(Lanes::Static(vec![LaneId([0, 0, 0, 0])])));
+		let started_lanes = Arc::new(Mutex::new(Vec::new()));
+		let runner = run_static_lanes(lanes.clone(), control.clone(), |lane| format!("{}", lane), {
+			let started_lanes = started_lanes.clone();
+			move |lane| {
+				started_lanes.lock().unwrap().push(lane);
+				futures::future::pending::<Result<(), String>>().boxed()
+			}
+		});
+		futures::pin_mut!(runner);
+		let lane_control = |lane| control.loop_control(format!("{}", LaneId(lane)));
+
+		assert!(runner.as_mut().now_or_never().is_none());
+		assert_eq!(*started_lanes.lock().unwrap(), vec![LaneId([0, 0, 0, 0])]);
+
+		// lane 1 is added and lane 0 is removed
+		lanes.set(Some(Lanes::Static(vec![LaneId([0, 0, 0, 1])])));
+		assert!(runner.as_mut().now_or_never().is_none());
+		assert_eq!(
+			*started_lanes.lock().unwrap(),
+			vec![LaneId([0, 0, 0, 0]), LaneId([0, 0, 0, 1])]
+		);
+		assert!(lane_control([0, 0, 0, 0]).is_paused());
+		assert!(!lane_control([0, 0, 0, 1]).is_paused());
+
+		// lane 0 is added back => it is resumed, not restarted
+		lanes.set(Some(Lanes::Static(vec![LaneId([0, 0, 0, 0]), LaneId([0, 0, 0, 1])])));
+		assert!(runner.as_mut().now_or_never().is_none());
+		assert_eq!(started_lanes.lock().unwrap().len(), 2);
+		assert!(!lane_control([0, 0, 0, 0]).is_paused());
+	}
+}
@@ -18,38 +18,90 @@ use crate::messages_source::SubstrateMessagesProof;
 use crate::messages_target::SubstrateMessagesReceivingProof;
 use crate::on_demand_headers::OnDemandHeadersRelay;
 
-use bp_messages::{LaneId, MessageNonce};
+use bp_messages::{LaneId, MessageNonce, MessagesDeliveryLimits};
+use bridge_runtime_common::messages::MessageTransaction;
+use codec::Decode;
 use frame_support::weights::Weight;
-use messages_relay::message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf};
-use relay_substrate_client::{BlockNumberOf, Chain, Client, HashOf};
-use relay_utils::{metrics::MetricsParams, BlockNumberBase};
-use sp_core::Bytes;
-use std::ops::RangeInclusive;
+use messages_relay::{
+	message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf},
+	message_lane_loop::{DeliveryCostEstimator, ForcedDelivery, RelayerMode},
+};
+use relay_substrate_client::{
+	BlockNumberOf, Chain, ChainWithBalances, ChainWithTips, Client, Error as SubstrateError, HashOf, HeaderIdOf,
+};
+use relay_utils::{
+	control::{RelayControl, Reloadable},
+	metrics::MetricsParams,
+	recovery::RecoveryPolicyParams,
+	BlockNumberBase, ErrorKind,
+};
+use sp_core::{storage::StorageKey, Bytes};
+use sp_runtime::{FixedPointNumber, FixedU128};
+use std::{
+	convert::TryFrom,
+	ops::RangeInclusive,
+	sync::{Arc, RwLock},
+};
+
+/// Number of target chain blocks between checks of messages delivery limits.
+const DELIVERY_LIMITS_CHECK_INTERVAL_IN_BLOCKS: u32 = 10;
+/// Number of source chain blocks between reads of the conversion rate.
+const CONVERSION_RATE_READ_INTERVAL_IN_BLOCKS: u32 = 10;
 
 /// Substrate <-> Substrate messages relay parameters.
-pub struct MessagesRelayParams<SC: Chain, SS, TC: Chain, TS> {
+pub struct MessagesRelayParams<SC: Chain, SS, TC: ChainWithBalances, TS> {
 	/// Messages source client.
 	pub source_client: Client<SC>,
 	/// Sign parameters for messages source chain.
 	pub source_sign: SS,
+	/// Mortality period of source chain transactions. `None` if transactions are immortal.
+	pub source_transactions_mortality: Option<u32>,
+	/// If set, all source chain transactions are wrapped into `proxy` calls on behalf of this account.
+	pub source_proxied_account: Option<SC::AccountId>,
 	/// Messages target client.
 	pub target_client: Client<TC>,
 	/// Sign parameters for messages target chain.
 	pub target_sign: TS,
+	/// Mortality period of target chain transactions. `None` if transactions are immortal.
+	pub target_transactions_mortality: Option<u32>,
+	/// If set, all target chain transactions are wrapped into `proxy` calls on behalf of this account.
+	pub target_proxied_account: Option<TC::AccountId>,
+	/// Maximal tip that may be attached to messages delivery transactions.
+	pub target_max_tip: TC::NativeBalance,
 	/// Optional on-demand source to target headers relay.
 	pub source_to_target_headers_relay: Option<OnDemandHeadersRelay<SC>>,
 	/// Optional on-demand target to source headers relay.
 	pub target_to_source_headers_relay: Option<OnDemandHeadersRelay<TC>>,
 	/// Identifier of lane that needs to be served.
 	pub lane_id: LaneId,
+	/// Relayer operating mode.
+	pub relayer_mode: Reloadable<RelayerMode>,
+	/// If not empty, the relay only wants to deliver messages that are sent by given accounts. Other
+	/// messages are only delivered if they precede wanted messages.
+	pub only_messages_from: Vec<SC::AccountId>,
+	/// Maximal number of source chain blocks for which the messages receiving confirmations may be delayed.
+	pub max_confirmations_delay: Reloadable<u32>,
+	/// If true, delivery transactions also confirm delivery of messages of the reverse lane (if there
+	/// are any unconfirmed messages).
+	pub piggyback_confirmations: bool,
+	/// Maximal number of delivery transactions that may be submitted, but not yet mined.
+	pub max_delivery_transactions_in_flight: Reloadable<usize>,
+	/// Messages that must be delivered regardless of the relayer mode and the lane state.
+	pub forced_delivery: Option<ForcedDelivery>,
+	/// Relay control interface registry.
+	pub control: RelayControl,
+	/// Recovery policy of the source client.
+	pub source_recovery_policy: RecoveryPolicyParams,
+	/// Recovery policy of the target client.
+	pub target_recovery_policy: RecoveryPolicyParams,
 	/// Metrics parameters.
 	pub metrics_params: MetricsParams,
 }
 
 /// Message sync pipeline for Substrate <-> Substrate relays.
 pub trait SubstrateMessageLane: MessageLane {
-	/// Name of the runtime method that returns dispatch weight of outbound messages at the source chain.
-	const OUTBOUND_LANE_MESSAGES_DISPATCH_WEIGHT_METHOD: &'static str;
+	/// Name of the runtime method that returns details of outbound messages at the source chain.
+	const OUTBOUND_LANE_MESSAGE_DETAILS_METHOD: &'static str;
 	/// Name of the runtime method that returns latest generated nonce at the source chain.
 	const OUTBOUND_LANE_LATEST_GENERATED_NONCE_METHOD: &'static str;
 	/// Name of the runtime method that returns latest received (confirmed) nonce at the the source chain.
@@ -61,6 +113,8 @@ pub trait SubstrateMessageLane: MessageLane {
 	const INBOUND_LANE_LATEST_CONFIRMED_NONCE_METHOD: &'static str;
 	/// Numebr of the runtime method that returns state of "unrewarded relayers" set at the target chain.
 	const INBOUND_LANE_UNREWARDED_RELAYERS_STATE: &'static str;
+	/// Name of the runtime method that returns limits of messages delivery transactions at the target chain.
+	const INBOUND_LANE_DELIVERY_LIMITS_METHOD: &'static str;
 
 	/// Name of the runtime method that returns id of best finalized source header at target chain.
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str;
@@ -70,18 +124,35 @@ pub trait SubstrateMessageLane: MessageLane {
 	/// Source chain.
 	type SourceChain: Chain;
 	/// Target chain.
-	type TargetChain: Chain;
+	type TargetChain: ChainWithTips;
+
+	/// Returns source chain account that has sent the message with given encoded payload.
+	///
+	/// Returns `None` if payload can't be decoded or if message has been sent by the source chain root.
+	fn message_sender(encoded_payload: &[u8]) -> Option<<Self::SourceChain as Chain>::AccountId>;
 
 	/// Returns id of account that we're using to sign transactions at target chain (messages proof).
 	fn target_transactions_author(&self) -> <Self::TargetChain as Chain>::AccountId;
 
+	/// Returns maximal tip that may be attached to the messages delivery transaction.
+	///
+	/// The actual tip depends on the target chain congestion. Zero means that transactions
+	/// are submitted without tip.
+	fn target_max_transaction_tip(&self) -> <Self::TargetChain as ChainWithBalances>::NativeBalance;
+
 	/// Make messages delivery transaction.
+	///
+	/// If `reverse_lane_confirmation` is `Some(_)`, the transaction should also confirm delivery of
+	/// messages of the reverse lane, atomically.
 	fn make_messages_delivery_transaction(
 		&self,
+		best_block_id: HeaderIdOf<Self::TargetChain>,
 		transaction_nonce: <Self::TargetChain as Chain>::Index,
+		tip: <Self::TargetChain as ChainWithBalances>::NativeBalance,
 		generated_at_header: SourceHeaderIdOf<Self>,
 		nonces: RangeInclusive<MessageNonce>,
 		proof: Self::MessagesProof,
+		reverse_lane_confirmation: Option<SubstrateMessagesReceivingProof<Self::SourceChain>>,
 	) -> Bytes;
 
 	/// Returns id of account that we're using to sign transactions at source chain (delivery proof).
@@ -90,6 +161,7 @@ pub trait SubstrateMessageLane: MessageLane {
 	/// Make messages receiving proof transaction.
 	fn make_messages_receiving_proof_transaction(
 		&self,
+		best_block_id: HeaderIdOf<Self::SourceChain>,
 		transaction_nonce: <Self::SourceChain as Chain>::Index,
 		generated_at_header: TargetHeaderIdOf<Self>,
 		proof: Self::MessagesReceivingProof,
@@ -98,36 +170,53 @@ pub trait SubstrateMessageLane: MessageLane {
 
 /// Substrate-to-Substrate message lane.
 #[derive(Debug)]
-pub struct SubstrateMessageLaneToSubstrate<Source: Chain, SourceSignParams, Target: Chain, TargetSignParams> {
+pub struct SubstrateMessageLaneToSubstrate<Source: Chain, SourceSignParams, Target: ChainWithBalances, TargetSignParams>
+{
 	/// Client for the source Substrate chain.
 	pub(crate) source_client: Client<Source>,
 	/// Parameters required to sign transactions for source chain.
 	pub(crate) source_sign: SourceSignParams,
+	/// Mortality period of source chain transactions. `None` if transactions are immortal.
+	pub(crate) source_transactions_mortality: Option<u32>,
+	/// If set, all source chain transactions are wrapped into `proxy` calls on behalf of this account.
+	pub(crate) source_proxied_account: Option<Source::AccountId>,
 	/// Client for the target Substrate chain.
 	pub(crate) target_client: Client<Target>,
 	/// Parameters required to sign transactions for target chain.
 	pub(crate) target_sign: TargetSignParams,
+	/// Mortality period of target chain transactions. `None` if transactions are immortal.
+	pub(crate) target_transactions_mortality: Option<u32>,
+	/// If set, all target chain transactions are wrapped into `proxy` calls on behalf of this account.
+	pub(crate) target_proxied_account: Option<Target::AccountId>,
+	/// Maximal tip that may be attached to target chain transactions.
+	pub(crate) target_max_tip: Target::NativeBalance,
 	/// Account id of relayer at the source chain.
 	pub(crate) relayer_id_at_source: Source::AccountId,
 }
 
-impl<Source: Chain, SourceSignParams: Clone, Target: Chain, TargetSignParams: Clone> Clone
+impl<Source: Chain, SourceSignParams: Clone, Target: ChainWithBalances, TargetSignParams: Clone> Clone
 	for SubstrateMessageLaneToSubstrate<Source, SourceSignParams, Target, TargetSignParams>
 {
 	fn clone(&self) -> Self {
 		Self {
 			source_client: self.source_client.clone(),
 			source_sign: self.source_sign.clone(),
+			source_transactions_mortality: self.source_transactions_mortality,
+			source_proxied_account: self.source_proxied_account.clone(),
 			target_client: self.target_client.clone(),
 			target_sign: self.target_sign.clone(),
+			target_transactions_mortality: self.target_transactions_mortality,
+			target_proxied_account: self.target_proxied_account.clone(),
+			target_max_tip: self.target_max_tip,
 			relayer_id_at_source: self.relayer_id_at_source.clone(),
 		}
 	}
 }
 
-impl<Source: Chain, SourceSignParams, Target: Chain, TargetSignParams> MessageLane
+impl<Source: ChainWithBalances, SourceSignParams, Target: ChainWithBalances, TargetSignParams> MessageLane
 	for SubstrateMessageLaneToSubstrate<Source, SourceSignParams, Target, TargetSignParams>
 where
+	Source::NativeBalance: Into<u128>,
 	SourceSignParams: Clone + Send + Sync + 'static,
 	TargetSignParams: Clone + Send + Sync + 'static,
 	BlockNumberOf<Source>: BlockNumberBase,
@@ -139,6 +228,8 @@ where
 	type MessagesProof = SubstrateMessagesProof<Source>;
 	type MessagesReceivingProof = SubstrateMessagesReceivingProof<Target>;
 
+	type SourceChainBalance = Source::NativeBalance;
+
 	type SourceHeaderNumber = BlockNumberOf<Source>;
 	type SourceHeaderHash = HashOf<Source>;
 
@@ -146,42 +237,196 @@ where
 	type TargetHeaderHash = HashOf<Target>;
 }
 
-/// Returns maximal number of messages and their maximal cumulative dispatch weight, based
-/// on given chain parameters.
+/// Limits of the single message delivery transaction.
+#[derive(Debug, PartialEq)]
+pub struct DeliveryTransactionLimits {
+	/// Maximal number of messages in the single delivery transaction.
+	pub max_messages_in_single_batch: MessageNonce,
+	/// Maximal weight of messages (including per-message overhead) in the single delivery transaction.
+	pub max_messages_weight_in_single_batch: Weight,
+	/// Maximal size of messages (including per-message overhead) in the single delivery transaction.
+	pub max_messages_size_in_single_batch: usize,
+	/// Weight that every message adds to the delivery transaction, in addition to its dispatch weight.
+	pub per_message_weight_overhead: Weight,
+	/// Size that every message adds to the delivery transaction, in addition to its payload size.
+	pub per_message_size_overhead: usize,
+	/// Weight of the delivery transaction itself, excluding messages.
+	pub delivery_transaction_base_weight: Weight,
+}
+
+/// Returns limits of the single message delivery transaction, based on given chain parameters.
+///
+/// The relay loop is packing messages into the delivery transaction until their cumulative
+/// (dispatch + per-message overhead) weight or (payload + per-message overhead) size hits
+/// these limits. So the number of messages in the transaction depends on actual messages.
 pub fn select_delivery_transaction_limits<W: pallet_bridge_messages::WeightInfoExt>(
 	max_extrinsic_weight: Weight,
+	max_extrinsic_size: u32,
 	max_unconfirmed_messages_at_inbound_lane: MessageNonce,
-) -> (MessageNonce, Weight) {
-	// We may try to guess accurate value, based on maximal number of messages and per-message
-	// weight overhead, but the relay loop isn't using this info in a super-accurate way anyway.
-	// So just a rough guess: let's say 1/3 of max tx weight is for tx itself and the rest is
-	// for messages dispatch.
+) -> DeliveryTransactionLimits {
+	// Delivery transaction always pays for itself and (in the worst case) for the outbound lane
+	// state proof. Everything else is available for messages.
+	let delivery_tx_base_weight =
+		W::receive_messages_proof_overhead() + W::receive_messages_proof_outbound_lane_state_overhead();
+	let max_messages_weight_in_single_batch = max_extrinsic_weight.saturating_sub(delivery_tx_base_weight);
+	let per_message_weight_overhead = W::receive_messages_proof_messages_overhead(1);
+
+	// The same for size: let's reserve some space for the outbound lane state proof and transaction
+	// itself. Every message is also bringing some extra trie nodes into the storage proof.
+	let expected_extra_storage_proof_size = W::expected_extra_storage_proof_size() as usize;
+	let max_messages_size_in_single_batch =
+		(max_extrinsic_size as usize).saturating_sub(expected_extra_storage_proof_size);
+	let per_message_size_overhead = expected_extra_storage_proof_size;
 
 	// Another thing to keep in mind is that our runtimes (when this code was written) accept
 	// messages with dispatch weight <= max_extrinsic_weight/2. So we can't reserve less than
 	// that for dispatch.
 
-	let weight_for_delivery_tx = max_extrinsic_weight / 3;
-	let weight_for_messages_dispatch = max_extrinsic_weight - weight_for_delivery_tx;
-
-	let delivery_tx_base_weight =
-		W::receive_messages_proof_overhead() + W::receive_messages_proof_outbound_lane_state_overhead();
-	let delivery_tx_weight_rest = weight_for_delivery_tx - delivery_tx_base_weight;
-	let max_number_of_messages = std::cmp::min(
-		delivery_tx_weight_rest / W::receive_messages_proof_messages_overhead(1),
-		max_unconfirmed_messages_at_inbound_lane,
-	);
-
 	assert!(
-		max_number_of_messages > 0,
+		max_messages_weight_in_single_batch >= per_message_weight_overhead,
 		"Relay should fit at least one message in every delivery transaction",
 	);
 	assert!(
-		weight_for_messages_dispatch >= max_extrinsic_weight / 2,
+		max_messages_weight_in_single_batch - per_message_weight_overhead >= max_extrinsic_weight / 2,
 		"Relay shall be able to deliver messages with dispatch weight = max_extrinsic_weight / 2",
 	);
 
-	(max_number_of_messages, weight_for_messages_dispatch)
+	DeliveryTransactionLimits {
+		max_messages_in_single_batch: max_unconfirmed_messages_at_inbound_lane,
+		max_messages_weight_in_single_batch,
+		max_messages_size_in_single_batch,
+		per_message_weight_overhead,
+		per_message_size_overhead,
+		delivery_transaction_base_weight,
+	}
+}
+
+/// Read limits of messages delivery transactions from the target chain runtime.
+///
+/// These limits may be changed by the target chain runtime upgrade, so they're never compiled
+/// into the relay.
+pub async fn read_delivery_limits<P: SubstrateMessageLane>(
+	target_client: &Client<P::TargetChain>,
+) -> Result<MessagesDeliveryLimits, SubstrateError> {
+	let encoded_response = target_client
+		.state_call(P::INBOUND_LANE_DELIVERY_LIMITS_METHOD.into(), Bytes(Vec::new()), None)
+		.await?;
+	MessagesDeliveryLimits::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)
+}
+
+/// Abort relay when messages delivery limits at the target chain are changed by the runtime upgrade.
+///
+/// The `RuntimeMismatch` error is restartable, so the restarted relay will read updated limits.
+pub fn abort_on_delivery_limits_change<P: SubstrateMessageLane>(
+	target_client: Client<P::TargetChain>,
+	limits: MessagesDeliveryLimits,
+) {
+	async_std::task::spawn(async move {
+		let mut checked_spec_version = None;
+		loop {
+			async_std::task::sleep(P::TargetChain::AVERAGE_BLOCK_INTERVAL * DELIVERY_LIMITS_CHECK_INTERVAL_IN_BLOCKS)
+				.await;
+
+			// limits may only be changed by the runtime upgrade
+			let spec_version = match target_client.runtime_version().await {
+				Ok(runtime_version) => runtime_version.spec_version,
+				Err(error) => {
+					log::warn!(
+						target: "bridge",
+						"Failed to read {} runtime version: {:?}",
+						P::TargetChain::NAME,
+						error,
+					);
+					continue;
+				}
+			};
+			if checked_spec_version == Some(spec_version) {
+				continue;
+			}
+
+			match read_delivery_limits::<P>(&target_client).await {
+				Ok(actual_limits) if actual_limits == limits => checked_spec_version = Some(spec_version),
+				Ok(actual_limits) => relay_utils::error::exit_on_error(
+					Some(ErrorKind::RuntimeMismatch),
+					format!(
+						"{} messages delivery limits have changed from {:?} to {:?}",
+						P::TargetChain::NAME,
+						limits,
+						actual_limits,
+					),
+				),
+				Err(error) => log::warn!(
+					target: "bridge",
+					"Failed to read {} messages delivery limits: {:?}",
+					P::TargetChain::NAME,
+					error,
+				),
+			}
+		}
+	});
+}
+
+/// Message delivery transaction cost estimator of Substrate <-> Substrate message lane.
+///
+/// The delivery transaction fee is computed using the target chain transaction payment parameters.
+/// It is then converted to the source chain tokens using the conversion rate that is stored at the
+/// source chain - the same rate is used by the source chain to compute fees of outbound messages.
+#[derive(Debug)]
+pub struct SubstrateDeliveryCostEstimator {
+	target_transaction_payment: fn(MessageTransaction<Weight>) -> u128,
+	conversion_rate: Arc<RwLock<FixedU128>>,
+}
+
+impl SubstrateDeliveryCostEstimator {
+	/// Create estimator that periodically reads conversion rate from the source chain storage.
+	pub fn new<SC: Chain>(
+		source_client: Client<SC>,
+		conversion_rate_key: StorageKey,
+		initial_conversion_rate: FixedU128,
+		target_transaction_payment: fn(MessageTransaction<Weight>) -> u128,
+	) -> Self {
+		let conversion_rate = Arc::new(RwLock::new(initial_conversion_rate));
+		let shared_conversion_rate = conversion_rate.clone();
+		async_std::task::spawn(async move {
+			loop {
+				match source_client
+					.storage_value::<FixedU128>(conversion_rate_key.clone())
+					.await
+				{
+					Ok(actual_conversion_rate) => {
+						*shared_conversion_rate.write().expect("lock is never poisoned; qed") =
+							actual_conversion_rate.unwrap_or(initial_conversion_rate)
+					}
+					Err(error) => log::warn!(
+						target: "bridge",
+						"Failed to read conversion rate from {}: {:?}",
+						SC::NAME,
+						error,
+					),
+				}
+
+				async_std::task::sleep(SC::AVERAGE_BLOCK_INTERVAL * CONVERSION_RATE_READ_INTERVAL_IN_BLOCKS).await;
+			}
+		});
+
+		SubstrateDeliveryCostEstimator {
+			target_transaction_payment,
+			conversion_rate,
+		}
+	}
+}
+
+impl DeliveryCostEstimator for SubstrateDeliveryCostEstimator {
+	fn delivery_transaction_cost(&self, weight: Weight, size: usize) -> u128 {
+		let target_fee = (self.target_transaction_payment)(MessageTransaction {
+			dispatch_weight: weight,
+			size: u32::try_from(size).unwrap_or(u32::MAX),
+		});
+		self.conversion_rate
+			.read()
+			.expect("lock is never poisoned; qed")
+			.saturating_mul_int(target_fee)
+	}
 }
 
 #[cfg(test)]
@@ -192,18 +437,38 @@ mod tests {
 
 	#[test]
 	fn select_delivery_transaction_limits_works() {
-		let (max_count, max_weight) = select_delivery_transaction_limits::<RialtoToMillauMessagesWeights>(
+		let limits = select_delivery_transaction_limits::<RialtoToMillauMessagesWeights>(
 			bp_millau::max_extrinsic_weight(),
-			bp_millau::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE,
+			bp_millau::max_extrinsic_size(),
+			bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
 		);
 		assert_eq!(
-			(max_count, max_weight),
+			limits,
 			// We don't actually care about these values, so feel free to update them whenever test
 			// fails. The only thing to do before that is to ensure that new values looks sane: i.e. weight
 			// reserved for messages dispatch allows dispatch of non-trivial messages.
 			//
 			// Any significant change in this values should attract additional attention.
-			(1013, 216_583_333_334),
+			DeliveryTransactionLimits {
+				max_messages_in_single_batch: 1024,
+				max_messages_weight_in_single_batch: 324_441_736_000,
+				max_messages_size_in_single_batch: 1_571_840,
+				per_message_weight_overhead: 106_467_000,
+				per_message_size_overhead: 1024,
+				delivery_transaction_base_weight: 433_264_000,
+			},
 		);
 	}
+
+	#[test]
+	fn delivery_transaction_cost_is_converted_to_source_tokens() {
+		let estimator = SubstrateDeliveryCostEstimator {
+			target_transaction_payment: |transaction| transaction.dispatch_weight as u128 + transaction.size as u128,
+			conversion_rate: Arc::new(RwLock::new(FixedU128::saturating_from_rational(1, 2))),
+		};
+		assert_eq!(estimator.delivery_transaction_cost(100, 20), 60);
+
+		*estimator.conversion_rate.write().unwrap() = FixedU128::saturating_from_integer(3);
+		assert_eq!(estimator.delivery_transaction_cost(100, 20), 360);
+	}
 }
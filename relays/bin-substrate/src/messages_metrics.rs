@@ -0,0 +1,123 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metrics of transactions that are submitted by the Substrate-to-Substrate messages relay.
+
+use relay_substrate_client::{ChainWithBalances, Client};
+use relay_utils::metrics::{
+	exponential_buckets, metric_name, register, HistogramOpts, HistogramVec, MetricsParams, PrometheusError,
+};
+use sp_core::Bytes;
+
+/// Type of messages delivery transactions, submitted to the target chain.
+pub const DELIVERY_TRANSACTION: &str = "delivery";
+/// Type of messages receiving confirmation transactions, submitted to the source chain.
+pub const CONFIRMATION_TRANSACTION: &str = "confirmation";
+
+/// Metrics of transactions, submitted by the messages relay of single lane in single direction.
+///
+/// Every histogram has the "type" label, which is either `DELIVERY_TRANSACTION`, or
+/// `CONFIRMATION_TRANSACTION`.
+///
+/// Cloning only clones references.
+#[derive(Clone)]
+pub struct MessagesTransactionsMetrics {
+	/// Sizes of storage proofs, included in transactions.
+	proof_sizes: HistogramVec,
+	/// Sizes of submitted transactions.
+	transaction_sizes: HistogramVec,
+	/// Fees of submitted transactions (in native tokens of the chain, where transaction is submitted).
+	transaction_fees: HistogramVec,
+}
+
+impl MessagesTransactionsMetrics {
+	/// Create and register transactions metrics in the registry of given metrics params.
+	///
+	/// The registry must be created (e.g. by `relay_utils::relay_metrics(...).into_params()`)
+	/// before calling this method.
+	pub fn new(params: &MetricsParams) -> Result<Self, String> {
+		let registry = params
+			.registry
+			.as_ref()
+			.ok_or_else(|| "Metrics registry is required to register transactions metrics".to_string())?;
+		let prefix = params.metrics_prefix.as_deref();
+		let register_histogram = |name: &str, help: &str, buckets: Vec<f64>| -> Result<HistogramVec, PrometheusError> {
+			register(
+				HistogramVec::new(
+					HistogramOpts::new(metric_name(prefix, name), help).buckets(buckets),
+					&["type"],
+				)?,
+				registry,
+			)
+		};
+
+		Ok(MessagesTransactionsMetrics {
+			proof_sizes: register_histogram(
+				"proof_sizes",
+				"Sizes of storage proofs (in bytes), included in submitted transactions",
+				exponential_buckets(1024.0, 2.0, 12).map_err(|e| e.to_string())?,
+			)
+			.map_err(|e| e.to_string())?,
+			transaction_sizes: register_histogram(
+				"transaction_sizes",
+				"Sizes of submitted transactions (in bytes)",
+				exponential_buckets(1024.0, 2.0, 12).map_err(|e| e.to_string())?,
+			)
+			.map_err(|e| e.to_string())?,
+			transaction_fees: register_histogram(
+				"transaction_fees",
+				"Estimated fees of submitted transactions (in native tokens of the chain)",
+				exponential_buckets(1.0, 10.0, 24).map_err(|e| e.to_string())?,
+			)
+			.map_err(|e| e.to_string())?,
+		})
+	}
+
+	/// Observe size of the storage proof, that is going to be included in transaction of given type.
+	pub fn observe_proof_size(&self, transaction_type: &str, storage_proof: &[Vec<u8>]) {
+		let proof_size: usize = storage_proof.iter().map(|node| node.len()).sum();
+		self.proof_sizes
+			.with_label_values(&[transaction_type])
+			.observe(proof_size as f64);
+	}
+
+	/// Observe size and estimated fee of submitted transaction of given type.
+	pub async fn observe_transaction<C: ChainWithBalances>(
+		&self,
+		client: &Client<C>,
+		transaction_type: &str,
+		transaction: Bytes,
+	) where
+		C::NativeBalance: Into<u128>,
+	{
+		self.transaction_sizes
+			.with_label_values(&[transaction_type])
+			.observe(transaction.0.len() as f64);
+		match client.estimate_extrinsic_fee(transaction).await {
+			Ok(fee) => self
+				.transaction_fees
+				.with_label_values(&[transaction_type])
+				.observe(fee.into() as f64),
+			Err(error) => log::trace!(
+				target: "bridge-metrics",
+				"Failed to estimate fee of {} {} transaction: {:?}",
+				C::NAME,
+				transaction_type,
+				error,
+			),
+		}
+	}
+}
@@ -19,10 +19,11 @@
 //! <BridgedName> chain.
 
 use crate::messages_lane::SubstrateMessageLane;
+use crate::messages_metrics::{MessagesTransactionsMetrics, CONFIRMATION_TRANSACTION, DELIVERY_TRANSACTION};
 use crate::on_demand_headers::OnDemandHeadersRelay;
 
 use async_trait::async_trait;
-use bp_messages::{LaneId, MessageNonce};
+use bp_messages::{LaneId, MessageData, MessageNonce};
 use bp_runtime::ChainId;
 use bridge_runtime_common::messages::target::FromBridgedChainMessagesProof;
 use codec::{Decode, Encode};
@@ -30,11 +31,11 @@ use frame_support::{traits::Instance, weights::Weight};
 use messages_relay::{
 	message_lane::{SourceHeaderIdOf, TargetHeaderIdOf},
 	message_lane_loop::{
-		ClientState, MessageProofParameters, MessageWeights, MessageWeightsMap, SourceClient, SourceClientState,
+		ClientState, MessageDetails, MessageDetailsMap, MessageProofParameters, SourceClient, SourceClientState,
 	},
 };
 use pallet_bridge_messages::Config as MessagesConfig;
-use relay_substrate_client::{Chain, Client, Error as SubstrateError, HashOf, HeaderIdOf};
+use relay_substrate_client::{Chain, ChainWithBalances, Client, Error as SubstrateError, HashOf, HeaderIdOf};
 use relay_utils::{relay_loop::Client as RelayClient, BlockNumberBase, HeaderId};
 use sp_core::Bytes;
 use sp_runtime::{traits::Header as HeaderT, DeserializeOwned};
@@ -52,17 +53,24 @@ pub struct SubstrateMessagesSource<C: Chain, P: SubstrateMessageLane, R, I> {
 	lane_id: LaneId,
 	instance: ChainId,
 	target_to_source_headers_relay: Option<OnDemandHeadersRelay<P::TargetChain>>,
+	only_messages_from: Vec<C::AccountId>,
+	metrics: MessagesTransactionsMetrics,
 	_phantom: PhantomData<(R, I)>,
 }
 
 impl<C: Chain, P: SubstrateMessageLane, R, I> SubstrateMessagesSource<C, P, R, I> {
 	/// Create new Substrate headers source.
+	///
+	/// If `only_messages_from` is not empty, the relay only wants to deliver messages that are sent
+	/// by given accounts.
 	pub fn new(
 		client: Client<C>,
 		lane: P,
 		lane_id: LaneId,
 		instance: ChainId,
 		target_to_source_headers_relay: Option<OnDemandHeadersRelay<P::TargetChain>>,
+		only_messages_from: Vec<C::AccountId>,
+		metrics: MessagesTransactionsMetrics,
 	) -> Self {
 		SubstrateMessagesSource {
 			client,
@@ -70,6 +78,8 @@ impl<C: Chain, P: SubstrateMessageLane, R, I> SubstrateMessagesSource<C, P, R, I
 			lane_id,
 			instance,
 			target_to_source_headers_relay,
+			only_messages_from,
+			metrics,
 			_phantom: Default::default(),
 		}
 	}
@@ -83,6 +93,8 @@ impl<C: Chain, P: SubstrateMessageLane, R, I> Clone for SubstrateMessagesSource<
 			lane_id: self.lane_id,
 			instance: self.instance,
 			target_to_source_headers_relay: self.target_to_source_headers_relay.clone(),
+			only_messages_from: self.only_messages_from.clone(),
+			metrics: self.metrics.clone(),
 			_phantom: Default::default(),
 		}
 	}
@@ -101,12 +113,21 @@ where
 	async fn reconnect(&mut self) -> Result<(), SubstrateError> {
 		self.client.reconnect().await
 	}
+
+	async fn resync(&mut self) -> Result<(), SubstrateError> {
+		self.client.resync().await
+	}
+
+	async fn failover(&mut self) -> Result<bool, SubstrateError> {
+		self.client.failover().await
+	}
 }
 
 #[async_trait]
 impl<C, P, R, I> SourceClient<P> for SubstrateMessagesSource<C, P, R, I>
 where
-	C: Chain,
+	C: ChainWithBalances,
+	C::NativeBalance: Into<u128>,
 	C::Header: DeserializeOwned,
 	C::Index: DeserializeOwned,
 	C::BlockNumber: BlockNumberBase,
@@ -119,6 +140,7 @@ where
 	P::TargetChain: Chain<Hash = P::TargetHeaderHash, BlockNumber = P::TargetHeaderNumber>,
 	P::TargetHeaderNumber: Decode,
 	P::TargetHeaderHash: Decode,
+	P::SourceChainBalance: Decode,
 	R: Send + Sync + MessagesConfig<I>,
 	I: Send + Sync + Instance,
 {
@@ -168,24 +190,39 @@ where
 		Ok((id, latest_received_nonce))
 	}
 
-	async fn generated_messages_weights(
+	async fn generated_message_details(
 		&self,
 		id: SourceHeaderIdOf<P>,
 		nonces: RangeInclusive<MessageNonce>,
-	) -> Result<MessageWeightsMap, SubstrateError> {
+	) -> Result<MessageDetailsMap<P::SourceChainBalance>, SubstrateError> {
 		let encoded_response = self
 			.client
 			.state_call(
-				P::OUTBOUND_LANE_MESSAGES_DISPATCH_WEIGHT_METHOD.into(),
+				P::OUTBOUND_LANE_MESSAGE_DETAILS_METHOD.into(),
 				Bytes((self.lane_id, nonces.start(), nonces.end()).encode()),
 				Some(id.1),
 			)
 			.await?;
 
-		make_message_weights_map::<C>(
+		let mut message_details = make_message_details_map::<C, _>(
 			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?,
 			nonces,
-		)
+		)?;
+
+		// we need to decode every message payload to find out if it is sent by one of served senders
+		if !self.only_messages_from.is_empty() {
+			for (nonce, details) in message_details.iter_mut() {
+				let message_key = pallet_bridge_messages::storage_keys::message_key::<R, I>(&self.lane_id, *nonce);
+				let message_data: Option<MessageData<P::SourceChainBalance>> =
+					self.client.storage_value(message_key).await?;
+				let message_sender = message_data.and_then(|message_data| P::message_sender(&message_data.payload));
+				details.is_wanted = message_sender
+					.map(|message_sender| self.only_messages_from.contains(&message_sender))
+					.unwrap_or(false);
+			}
+		}
+
+		Ok(message_details)
 	}
 
 	async fn prove_messages(
@@ -212,7 +249,8 @@ where
 			.prove_storage(storage_keys, id.1)
 			.await?
 			.iter_nodes()
-			.collect();
+			.collect::<Vec<_>>();
+		self.metrics.observe_proof_size(DELIVERY_TRANSACTION, &proof);
 		let proof = FromBridgedChainMessagesProof {
 			bridged_header_hash: id.1,
 			storage_proof: proof,
@@ -228,12 +266,27 @@ where
 		generated_at_block: TargetHeaderIdOf<P>,
 		proof: P::MessagesReceivingProof,
 	) -> Result<(), SubstrateError> {
+		let mut transaction = None;
 		self.client
-			.submit_signed_extrinsic(self.lane.source_transactions_author(), move |transaction_nonce| {
-				self.lane
-					.make_messages_receiving_proof_transaction(transaction_nonce, generated_at_block, proof)
-			})
+			.submit_signed_extrinsic(
+				self.lane.source_transactions_author(),
+				|best_block_id, transaction_nonce| {
+					let signed_transaction = self.lane.make_messages_receiving_proof_transaction(
+						best_block_id,
+						transaction_nonce,
+						generated_at_block,
+						proof,
+					);
+					transaction = Some(signed_transaction.clone());
+					signed_transaction
+				},
+			)
 			.await?;
+		if let Some(transaction) = transaction {
+			self.metrics
+				.observe_transaction(&self.client, CONFIRMATION_TRANSACTION, transaction)
+				.await;
+		}
 		Ok(())
 	}
 
@@ -287,28 +340,28 @@ where
 	})
 }
 
-fn make_message_weights_map<C: Chain>(
-	weights: Vec<(MessageNonce, Weight, u32)>,
+fn make_message_details_map<C: Chain, B>(
+	source_details: Vec<bp_messages::MessageDetails<B>>,
 	nonces: RangeInclusive<MessageNonce>,
-) -> Result<MessageWeightsMap, SubstrateError> {
+) -> Result<MessageDetailsMap<B>, SubstrateError> {
 	let make_missing_nonce_error = |expected_nonce| {
 		Err(SubstrateError::Custom(format!(
-			"Missing nonce {} in messages_dispatch_weight call result. Expected all nonces from {:?}",
+			"Missing nonce {} in message_details call result. Expected all nonces from {:?}",
 			expected_nonce, nonces,
 		)))
 	};
 
-	let mut weights_map = MessageWeightsMap::new();
+	let mut details_map = MessageDetailsMap::new();
 
 	// this is actually prevented by external logic
 	if nonces.is_empty() {
-		return Ok(weights_map);
+		return Ok(details_map);
 	}
 
 	// check if last nonce is missing - loop below is not checking this
-	let last_nonce_is_missing = weights
+	let last_nonce_is_missing = source_details
 		.last()
-		.map(|(last_nonce, _, _)| last_nonce != nonces.end())
+		.map(|details| details.nonce != *nonces.end())
 		.unwrap_or(true);
 	if last_nonce_is_missing {
 		return make_missing_nonce_error(*nonces.end());
@@ -317,7 +370,8 @@ fn make_message_weights_map<C: Chain>(
 	let mut expected_nonce = *nonces.start();
 	let mut is_at_head = true;
 
-	for (nonce, weight, size) in weights {
+	for details in source_details {
+		let nonce = details.nonce;
 		match (nonce == expected_nonce, is_at_head) {
 			(true, _) => (),
 			(false, true) => {
@@ -339,33 +393,73 @@ fn make_message_weights_map<C: Chain>(
 			}
 		}
 
-		weights_map.insert(
+		details_map.insert(
 			nonce,
-			MessageWeights {
-				weight,
-				size: size as _,
+			MessageDetails {
+				dispatch_weight: details.dispatch_weight,
+				size: details.size as _,
+				reward: details.delivery_and_dispatch_fee,
+				is_wanted: true,
 			},
 		);
 		expected_nonce = nonce + 1;
 		is_at_head = false;
 	}
 
-	Ok(weights_map)
+	Ok(details_map)
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 
+	fn message_details_from_rpc(
+		nonces: RangeInclusive<MessageNonce>,
+	) -> Vec<bp_messages::MessageDetails<bp_rialto::Balance>> {
+		nonces
+			.into_iter()
+			.map(|nonce| bp_messages::MessageDetails {
+				nonce,
+				dispatch_weight: 0,
+				size: 0,
+				delivery_and_dispatch_fee: 0,
+			})
+			.collect()
+	}
+
 	#[test]
-	fn make_message_weights_map_succeeds_if_no_messages_are_missing() {
+	fn make_message_details_map_succeeds_if_no_messages_are_missing() {
 		assert_eq!(
-			make_message_weights_map::<relay_rialto_client::Rialto>(vec![(1, 0, 0), (2, 0, 0), (3, 0, 0)], 1..=3,)
+			make_message_details_map::<relay_rialto_client::Rialto, _>(message_details_from_rpc(1..=3), 1..=3,)
 				.unwrap(),
 			vec![
-				(1, MessageWeights { weight: 0, size: 0 }),
-				(2, MessageWeights { weight: 0, size: 0 }),
-				(3, MessageWeights { weight: 0, size: 0 }),
+				(
+					1,
+					MessageDetails {
+						dispatch_weight: 0,
+						size: 0,
+						reward: 0,
+						is_wanted: true,
+					}
+				),
+				(
+					2,
+					MessageDetails {
+						dispatch_weight: 0,
+						size: 0,
+						reward: 0,
+						is_wanted: true,
+					}
+				),
+				(
+					3,
+					MessageDetails {
+						dispatch_weight: 0,
+						size: 0,
+						reward: 0,
+						is_wanted: true,
+					}
+				),
 			]
 			.into_iter()
 			.collect(),
@@ -373,12 +467,29 @@ mod tests {
 	}
 
 	#[test]
-	fn make_message_weights_map_succeeds_if_head_messages_are_missing() {
+	fn make_message_details_map_succeeds_if_head_messages_are_missing() {
 		assert_eq!(
-			make_message_weights_map::<relay_rialto_client::Rialto>(vec![(2, 0, 0), (3, 0, 0)], 1..=3,).unwrap(),
+			make_message_details_map::<relay_rialto_client::Rialto, _>(message_details_from_rpc(2..=3), 1..=3,)
+				.unwrap(),
 			vec![
-				(2, MessageWeights { weight: 0, size: 0 }),
-				(3, MessageWeights { weight: 0, size: 0 }),
+				(
+					2,
+					MessageDetails {
+						dispatch_weight: 0,
+						size: 0,
+						reward: 0,
+						is_wanted: true,
+					}
+				),
+				(
+					3,
+					MessageDetails {
+						dispatch_weight: 0,
+						size: 0,
+						reward: 0,
+						is_wanted: true,
+					}
+				),
 			]
 			.into_iter()
 			.collect(),
@@ -386,25 +497,27 @@ mod tests {
 	}
 
 	#[test]
-	fn make_message_weights_map_fails_if_mid_messages_are_missing() {
+	fn make_message_details_map_fails_if_mid_messages_are_missing() {
+		let mut message_details_from_rpc = message_details_from_rpc(1..=3);
+		message_details_from_rpc.remove(1);
 		assert!(matches!(
-			make_message_weights_map::<relay_rialto_client::Rialto>(vec![(1, 0, 0), (3, 0, 0)], 1..=3,),
+			make_message_details_map::<relay_rialto_client::Rialto, _>(message_details_from_rpc, 1..=3,),
 			Err(SubstrateError::Custom(_))
 		));
 	}
 
 	#[test]
-	fn make_message_weights_map_fails_if_tail_messages_are_missing() {
+	fn make_message_details_map_fails_if_tail_messages_are_missing() {
 		assert!(matches!(
-			make_message_weights_map::<relay_rialto_client::Rialto>(vec![(1, 0, 0), (2, 0, 0)], 1..=3,),
+			make_message_details_map::<relay_rialto_client::Rialto, _>(message_details_from_rpc(1..=2), 1..=3,),
 			Err(SubstrateError::Custom(_))
 		));
 	}
 
 	#[test]
-	fn make_message_weights_map_fails_if_all_messages_are_missing() {
+	fn make_message_details_map_fails_if_all_messages_are_missing() {
 		assert!(matches!(
-			make_message_weights_map::<relay_rialto_client::Rialto>(vec![], 1..=3),
+			make_message_details_map::<relay_rialto_client::Rialto, bp_rialto::Balance>(vec![], 1..=3),
 			Err(SubstrateError::Custom(_))
 		));
 	}
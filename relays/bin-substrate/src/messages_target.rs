@@ -19,6 +19,7 @@
 //! <BridgedName> chain.
 
 use crate::messages_lane::SubstrateMessageLane;
+use crate::messages_metrics::{MessagesTransactionsMetrics, CONFIRMATION_TRANSACTION, DELIVERY_TRANSACTION};
 use crate::messages_source::read_client_state;
 use crate::on_demand_headers::OnDemandHeadersRelay;
 
@@ -33,9 +34,9 @@ use messages_relay::{
 	message_lane_loop::{TargetClient, TargetClientState},
 };
 use pallet_bridge_messages::Config as MessagesConfig;
-use relay_substrate_client::{Chain, Client, Error as SubstrateError, HashOf};
+use relay_substrate_client::{Chain, ChainWithTips, Client, Error as SubstrateError, HashOf};
 use relay_utils::{relay_loop::Client as RelayClient, BlockNumberBase};
-use sp_core::Bytes;
+use sp_core::{storage::StorageKey, Bytes};
 use sp_runtime::{traits::Header as HeaderT, DeserializeOwned};
 use std::{marker::PhantomData, ops::RangeInclusive};
 
@@ -45,6 +46,102 @@ pub type SubstrateMessagesReceivingProof<C> = (
 	FromBridgedChainMessagesDeliveryProof<HashOf<C>>,
 );
 
+/// Confirmations of messages that are delivered over the reverse lane (the lane with the same id,
+/// that delivers messages from the target chain to the source chain).
+///
+/// If configured, these confirmations are piggybacked to messages delivery transactions (using
+/// `utility::batch_all`). Delivery of reverse lane messages is proved by the inbound lane state of
+/// the source chain, read at the same source header that is used to prove delivered messages. So
+/// the target chain is guaranteed to know this header.
+pub struct ReverseLaneConfirmations<SC: Chain> {
+	/// Source chain client.
+	pub source_client: Client<SC>,
+	/// Storage key of the reverse lane inbound data at the source chain.
+	pub inbound_lane_data_key: StorageKey,
+	/// Name of the runtime method that returns latest received nonce of the reverse lane at the source chain.
+	pub inbound_lane_latest_received_nonce_method: &'static str,
+	/// Name of the runtime method that returns unrewarded relayers state of the reverse lane at the source chain.
+	pub inbound_lane_unrewarded_relayers_state_method: &'static str,
+	/// Name of the runtime method that returns latest received nonce of the reverse lane at the target chain.
+	pub outbound_lane_latest_received_nonce_method: &'static str,
+}
+
+impl<SC: Chain> Clone for ReverseLaneConfirmations<SC> {
+	fn clone(&self) -> Self {
+		Self {
+			source_client: self.source_client.clone(),
+			inbound_lane_data_key: self.inbound_lane_data_key.clone(),
+			inbound_lane_latest_received_nonce_method: self.inbound_lane_latest_received_nonce_method,
+			inbound_lane_unrewarded_relayers_state_method: self.inbound_lane_unrewarded_relayers_state_method,
+			outbound_lane_latest_received_nonce_method: self.outbound_lane_latest_received_nonce_method,
+		}
+	}
+}
+
+impl<SC: Chain> ReverseLaneConfirmations<SC> {
+	/// Prove delivery of reverse lane messages at given source chain header.
+	///
+	/// Returns `None` if the target chain already knows about all messages that are delivered
+	/// to the source chain at this header.
+	async fn prove<TC: Chain>(
+		&self,
+		target_client: &Client<TC>,
+		lane_id: LaneId,
+		at_source_header_hash: SC::Hash,
+	) -> Result<Option<SubstrateMessagesReceivingProof<SC>>, SubstrateError> {
+		let encoded_response = self
+			.source_client
+			.state_call(
+				self.inbound_lane_latest_received_nonce_method.into(),
+				Bytes(lane_id.encode()),
+				Some(at_source_header_hash),
+			)
+			.await?;
+		let received_at_source: MessageNonce =
+			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
+
+		let encoded_response = target_client
+			.state_call(
+				self.outbound_lane_latest_received_nonce_method.into(),
+				Bytes(lane_id.encode()),
+				None,
+			)
+			.await?;
+		let confirmed_at_target: MessageNonce =
+			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
+
+		if received_at_source <= confirmed_at_target {
+			return Ok(None);
+		}
+
+		let encoded_response = self
+			.source_client
+			.state_call(
+				self.inbound_lane_unrewarded_relayers_state_method.into(),
+				Bytes(lane_id.encode()),
+				Some(at_source_header_hash),
+			)
+			.await?;
+		let relayers_state: UnrewardedRelayersState =
+			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
+		let proof = self
+			.source_client
+			.prove_storage(vec![self.inbound_lane_data_key.clone()], at_source_header_hash)
+			.await?
+			.iter_nodes()
+			.collect();
+
+		Ok(Some((
+			relayers_state,
+			FromBridgedChainMessagesDeliveryProof {
+				bridged_header_hash: at_source_header_hash,
+				storage_proof: proof,
+				lane: lane_id,
+			},
+		)))
+	}
+}
+
 /// Substrate client as Substrate messages target.
 pub struct SubstrateMessagesTarget<C: Chain, P: SubstrateMessageLane, R, I> {
 	client: Client<C>,
@@ -52,6 +149,8 @@ pub struct SubstrateMessagesTarget<C: Chain, P: SubstrateMessageLane, R, I> {
 	lane_id: LaneId,
 	instance: ChainId,
 	source_to_target_headers_relay: Option<OnDemandHeadersRelay<P::SourceChain>>,
+	reverse_lane_confirmations: Option<ReverseLaneConfirmations<P::SourceChain>>,
+	metrics: MessagesTransactionsMetrics,
 	_phantom: PhantomData<(R, I)>,
 }
 
@@ -63,6 +162,8 @@ impl<C: Chain, P: SubstrateMessageLane, R, I> SubstrateMessagesTarget<C, P, R, I
 		lane_id: LaneId,
 		instance: ChainId,
 		source_to_target_headers_relay: Option<OnDemandHeadersRelay<P::SourceChain>>,
+		reverse_lane_confirmations: Option<ReverseLaneConfirmations<P::SourceChain>>,
+		metrics: MessagesTransactionsMetrics,
 	) -> Self {
 		SubstrateMessagesTarget {
 			client,
@@ -70,6 +171,8 @@ impl<C: Chain, P: SubstrateMessageLane, R, I> SubstrateMessagesTarget<C, P, R, I
 			lane_id,
 			instance,
 			source_to_target_headers_relay,
+			reverse_lane_confirmations,
+			metrics,
 			_phantom: Default::default(),
 		}
 	}
@@ -83,6 +186,8 @@ impl<C: Chain, P: SubstrateMessageLane, R, I> Clone for SubstrateMessagesTarget<
 			lane_id: self.lane_id,
 			instance: self.instance,
 			source_to_target_headers_relay: self.source_to_target_headers_relay.clone(),
+			reverse_lane_confirmations: self.reverse_lane_confirmations.clone(),
+			metrics: self.metrics.clone(),
 			_phantom: Default::default(),
 		}
 	}
@@ -101,12 +206,21 @@ where
 	async fn reconnect(&mut self) -> Result<(), SubstrateError> {
 		self.client.reconnect().await
 	}
+
+	async fn resync(&mut self) -> Result<(), SubstrateError> {
+		self.client.resync().await
+	}
+
+	async fn failover(&mut self) -> Result<bool, SubstrateError> {
+		self.client.failover().await
+	}
 }
 
 #[async_trait]
 impl<C, P, R, I> TargetClient<P> for SubstrateMessagesTarget<C, P, R, I>
 where
-	C: Chain,
+	C: ChainWithTips,
+	C::NativeBalance: Into<u128>,
 	C::Header: DeserializeOwned,
 	C::Index: DeserializeOwned,
 	<C::Header as HeaderT>::Number: BlockNumberBase,
@@ -196,7 +310,8 @@ where
 			.prove_storage(vec![inbound_data_key], id.1)
 			.await?
 			.iter_nodes()
-			.collect();
+			.collect::<Vec<_>>();
+		self.metrics.observe_proof_size(CONFIRMATION_TRANSACTION, &proof);
 		let proof = FromBridgedChainMessagesDeliveryProof {
 			bridged_header_hash: id.1,
 			storage_proof: proof,
@@ -211,16 +326,41 @@ where
 		nonces: RangeInclusive<MessageNonce>,
 		proof: P::MessagesProof,
 	) -> Result<RangeInclusive<MessageNonce>, SubstrateError> {
+		let reverse_lane_confirmation = match self.reverse_lane_confirmations {
+			Some(ref reverse_lane_confirmations) => {
+				reverse_lane_confirmations
+					.prove(&self.client, self.lane_id, generated_at_header.1)
+					.await?
+			}
+			None => None,
+		};
+
+		let transactions_author = self.lane.target_transactions_author();
+		let tip = self
+			.client
+			.select_transaction_tip(&transactions_author, self.lane.target_max_transaction_tip())
+			.await?;
+		let mut transaction = None;
 		self.client
-			.submit_signed_extrinsic(self.lane.target_transactions_author(), |transaction_nonce| {
-				self.lane.make_messages_delivery_transaction(
+			.submit_signed_extrinsic(transactions_author, |best_block_id, transaction_nonce| {
+				let signed_transaction = self.lane.make_messages_delivery_transaction(
+					best_block_id,
 					transaction_nonce,
+					tip,
 					generated_at_header,
 					nonces.clone(),
 					proof,
-				)
+					reverse_lane_confirmation,
+				);
+				transaction = Some(signed_transaction.clone());
+				signed_transaction
 			})
 			.await?;
+		if let Some(transaction) = transaction {
+			self.metrics
+				.observe_transaction(&self.client, DELIVERY_TRANSACTION, transaction)
+				.await;
+		}
 		Ok(nonces)
 	}
 
@@ -29,20 +29,25 @@ use futures::{
 };
 use num_traits::{CheckedSub, Zero};
 use relay_substrate_client::{
-	finality_source::FinalitySource as SubstrateFinalitySource, BlockNumberOf, Chain, Client, HashOf, HeaderIdOf,
-	SyncHeader,
+	finality_source::FinalitySource as SubstrateFinalitySource, BlockNumberOf, Chain, ChainWithBalances, Client,
+	HashOf, HeaderIdOf, SyncHeader,
 };
 use relay_utils::{
-	metrics::MetricsParams, relay_loop::Client as RelayClient, BlockNumberBase, FailedClient, HeaderId,
-	MaybeConnectionError,
+	metrics::MetricsParams,
+	relay_loop::{Client as RelayClient, ReconnectBackoff, RECONNECT_DELAY},
+	BlockNumberBase, FailedClient, HeaderId, MaybeConnectionError,
 };
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Instant};
 
 /// On-demand Substrate <-> Substrate headers relay.
 ///
 /// This relay may be started by messages whenever some other relay (e.g. messages relay) needs more
 /// headers to be relayed to continue its regular work. When enough headers are relayed, on-demand
 /// relay may be deactivated.
+///
+/// If `only_mandatory_headers` is `true`, the relay that only submits mandatory headers is running
+/// while there are no required headers. Otherwise, the relay is also started when there are too
+/// many headers missing at the target chain.
 #[derive(Clone)]
 pub struct OnDemandHeadersRelay<SourceChain: Chain> {
 	/// Background task name.
@@ -58,11 +63,12 @@ impl<SourceChain: Chain> OnDemandHeadersRelay<SourceChain> {
 		target_client: Client<TargetChain>,
 		pipeline: SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign>,
 		maximal_headers_difference: SourceChain::BlockNumber,
+		only_mandatory_headers: bool,
 	) -> Self
 	where
 		SourceChain: Chain + Debug,
 		SourceChain::BlockNumber: BlockNumberBase,
-		TargetChain: Chain + Debug,
+		TargetChain: ChainWithBalances + Debug,
 		TargetChain::BlockNumber: BlockNumberBase,
 		TargetSign: Clone + Send + Sync + 'static,
 		SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign>: SubstrateFinalitySyncPipeline<
@@ -82,6 +88,7 @@ impl<SourceChain: Chain> OnDemandHeadersRelay<SourceChain> {
 				target_client,
 				pipeline,
 				maximal_headers_difference,
+				only_mandatory_headers,
 				required_header_rx,
 			)
 			.await;
@@ -117,11 +124,12 @@ async fn background_task<SourceChain, TargetChain, TargetSign>(
 	target_client: Client<TargetChain>,
 	pipeline: SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign>,
 	maximal_headers_difference: SourceChain::BlockNumber,
+	only_mandatory_headers: bool,
 	mut required_header_rx: mpsc::Receiver<HeaderIdOf<SourceChain>>,
 ) where
 	SourceChain: Chain + Debug,
 	SourceChain::BlockNumber: BlockNumberBase,
-	TargetChain: Chain + Debug,
+	TargetChain: ChainWithBalances + Debug,
 	TargetChain::BlockNumber: BlockNumberBase,
 	TargetSign: Clone + Send + Sync + 'static,
 	SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign>: SubstrateFinalitySyncPipeline<
@@ -142,8 +150,11 @@ async fn background_task<SourceChain, TargetChain, TargetSign>(
 	let mut finality_target = SubstrateFinalityTarget::new(target_client.clone(), pipeline.clone());
 
 	let mut active_headers_relay = None;
+	let mut active_headers_relay_mode = None;
 	let mut required_header_number = Zero::zero();
 	let mut relay_exited_rx = futures::future::pending().left_future();
+	let mut reconnect_backoff = ReconnectBackoff::new(RECONNECT_DELAY);
+	let mut clients_connected_at = Instant::now();
 
 	loop {
 		// wait for next target block or for new required header
@@ -168,6 +179,7 @@ async fn background_task<SourceChain, TargetChain, TargetSign>(
 				// but it isn't critical, because even if we'll accidentally stop new relay
 				// we'll restart it almost immediately
 				stop_on_demand_headers_relay(active_headers_relay.take()).await;
+				active_headers_relay_mode = None;
 			},
 		}
 
@@ -175,13 +187,15 @@ async fn background_task<SourceChain, TargetChain, TargetSign>(
 		let best_finalized_source_header_at_source =
 			best_finalized_source_header_at_source(&finality_source, &relay_task_name).await;
 		if matches!(best_finalized_source_header_at_source, Err(ref e) if e.is_connection_error()) {
+			reconnect_backoff.clients_failed(clients_connected_at.elapsed());
 			relay_utils::relay_loop::reconnect_failed_client(
 				FailedClient::Source,
-				relay_utils::relay_loop::RECONNECT_DELAY,
+				&mut reconnect_backoff,
 				&mut finality_source,
 				&mut finality_target,
 			)
 			.await;
+			clients_connected_at = Instant::now();
 			continue;
 		}
 
@@ -189,13 +203,15 @@ async fn background_task<SourceChain, TargetChain, TargetSign>(
 		let best_finalized_source_header_at_target =
 			best_finalized_source_header_at_target::<SourceChain, _, _>(&finality_target, &relay_task_name).await;
 		if matches!(best_finalized_source_header_at_target, Err(ref e) if e.is_connection_error()) {
+			reconnect_backoff.clients_failed(clients_connected_at.elapsed());
 			relay_utils::relay_loop::reconnect_failed_client(
 				FailedClient::Target,
-				relay_utils::relay_loop::RECONNECT_DELAY,
+				&mut reconnect_backoff,
 				&mut finality_source,
 				&mut finality_target,
 			)
 			.await;
+			clients_connected_at = Instant::now();
 			continue;
 		}
 
@@ -205,11 +221,16 @@ async fn background_task<SourceChain, TargetChain, TargetSign>(
 			best_finalized_source_header_at_target.ok(),
 			required_header_number,
 			maximal_headers_difference,
+			only_mandatory_headers,
 			&relay_task_name,
-			active_headers_relay.is_some(),
+			active_headers_relay_mode,
 		);
 		match action {
-			OnDemandRelayAction::Start => {
+			OnDemandRelayAction::Start(mode) => {
+				// relay in other mode may be active => stop it first
+				stop_on_demand_headers_relay(active_headers_relay.take()).await;
+				active_headers_relay_mode = None;
+
 				let (relay_exited_tx, new_relay_exited_rx) = oneshot::channel();
 				active_headers_relay = start_on_demand_headers_relay(
 					relay_task_name.clone(),
@@ -217,13 +238,16 @@ async fn background_task<SourceChain, TargetChain, TargetSign>(
 					source_client.clone(),
 					target_client.clone(),
 					pipeline.clone(),
+					mode,
 				);
 				if active_headers_relay.is_some() {
+					active_headers_relay_mode = Some(mode);
 					relay_exited_rx = new_relay_exited_rx.right_future();
 				}
 			}
 			OnDemandRelayAction::Stop => {
 				stop_on_demand_headers_relay(active_headers_relay.take()).await;
+				active_headers_relay_mode = None;
 			}
 			OnDemandRelayAction::None => (),
 		}
@@ -279,10 +303,19 @@ where
 		})
 }
 
+/// Headers that are submitted by the on-demand relay task.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum OnDemandRelayMode {
+	/// All finalized headers are submitted.
+	AllHeaders,
+	/// Only mandatory headers are submitted.
+	MandatoryHeaders,
+}
+
 /// What to do with the on-demand relay task?
 #[derive(Debug, PartialEq)]
 enum OnDemandRelayAction {
-	Start,
+	Start(OnDemandRelayMode),
 	Stop,
 	None,
 }
@@ -292,9 +325,12 @@ fn select_on_demand_relay_action<C: Chain>(
 	best_finalized_source_header_at_target: Option<C::BlockNumber>,
 	mut required_source_header_at_target: C::BlockNumber,
 	maximal_headers_difference: C::BlockNumber,
+	only_mandatory_headers: bool,
 	relay_task_name: &str,
-	is_active: bool,
+	active_mode: Option<OnDemandRelayMode>,
 ) -> OnDemandRelayAction {
+	let is_active = active_mode == Some(OnDemandRelayMode::AllHeaders);
+
 	// if we have been unable to read header number from the target, then let's assume
 	// that it is the same as required header number. Otherwise we risk submitting
 	// unneeded transactions
@@ -314,10 +350,13 @@ fn select_on_demand_relay_action<C: Chain>(
 	// `pallet-bridge-grandpa` module relies on the fact that headers are synced in real-time and
 	// that it'll see authorities-change header before unbonding period will end for previous
 	// authorities set.
+	//
+	// if we're running the mandatory headers relay, then authorities-change headers are synced
+	// anyway, so we don't need to sync all headers.
 	let current_headers_difference = best_finalized_source_header_at_source
 		.checked_sub(&best_finalized_source_header_at_target)
 		.unwrap_or_else(Zero::zero);
-	if current_headers_difference > maximal_headers_difference {
+	if !only_mandatory_headers && current_headers_difference > maximal_headers_difference {
 		required_source_header_at_target = best_finalized_source_header_at_source;
 
 		// don't log if relay is already running
@@ -335,11 +374,16 @@ fn select_on_demand_relay_action<C: Chain>(
 	}
 
 	// now let's select what to do with relay
-	let needs_to_be_active = required_source_header_at_target > best_finalized_source_header_at_target;
-	match (needs_to_be_active, is_active) {
-		(true, false) => OnDemandRelayAction::Start,
-		(false, true) => OnDemandRelayAction::Stop,
-		_ => OnDemandRelayAction::None,
+	let needs_all_headers = required_source_header_at_target > best_finalized_source_header_at_target;
+	let required_mode = match (needs_all_headers, only_mandatory_headers) {
+		(true, _) => Some(OnDemandRelayMode::AllHeaders),
+		(false, true) => Some(OnDemandRelayMode::MandatoryHeaders),
+		(false, false) => None,
+	};
+	match (required_mode, active_mode) {
+		(required_mode, active_mode) if required_mode == active_mode => OnDemandRelayAction::None,
+		(Some(required_mode), _) => OnDemandRelayAction::Start(required_mode),
+		(None, _) => OnDemandRelayAction::Stop,
 	}
 }
 
@@ -355,6 +399,7 @@ fn start_on_demand_headers_relay<SourceChain: Chain, TargetChain: Chain, TargetS
 	source_client: Client<SourceChain>,
 	target_client: Client<TargetChain>,
 	pipeline: SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign>,
+	mode: OnDemandRelayMode,
 ) -> Option<async_std::task::JoinHandle<()>>
 where
 	SourceChain::BlockNumber: BlockNumberBase,
@@ -367,13 +412,25 @@ where
 	>,
 	TargetSign: 'static,
 {
-	let headers_relay_future =
-		crate::finality_pipeline::run(pipeline, source_client, target_client, true, MetricsParams::disabled());
+	let transactions_mortality = pipeline.transactions_mortality;
+	let headers_relay_future = crate::finality_pipeline::run(
+		pipeline,
+		source_client,
+		target_client,
+		true,
+		mode == OnDemandRelayMode::MandatoryHeaders,
+		None,
+		1,
+		transactions_mortality,
+		Default::default(),
+		Default::default(),
+		MetricsParams::disabled(),
+	);
 	let closure_task_name = task_name.clone();
 	async_std::task::Builder::new()
 		.name(task_name.clone())
 		.spawn(async move {
-			log::info!(target: "bridge", "Starting {} headers relay", closure_task_name);
+			log::info!(target: "bridge", "Starting {} headers relay ({:?})", closure_task_name, mode);
 			let result = headers_relay_future.await;
 			log::trace!(target: "bridge", "{} headers relay has exited. Result: {:?}", closure_task_name, result);
 			let _ = relay_exited_tx.send(());
@@ -415,12 +472,20 @@ mod tests {
 	#[test]
 	fn starts_relay_when_headers_are_required() {
 		assert_eq!(
-			select_on_demand_relay_action::<TestChain>(AT_SOURCE, AT_TARGET, 5, 100, "test", false),
-			OnDemandRelayAction::Start,
+			select_on_demand_relay_action::<TestChain>(AT_SOURCE, AT_TARGET, 5, 100, false, "test", None),
+			OnDemandRelayAction::Start(OnDemandRelayMode::AllHeaders),
 		);
 
 		assert_eq!(
-			select_on_demand_relay_action::<TestChain>(AT_SOURCE, AT_TARGET, 5, 100, "test", true),
+			select_on_demand_relay_action::<TestChain>(
+				AT_SOURCE,
+				AT_TARGET,
+				5,
+				100,
+				false,
+				"test",
+				Some(OnDemandRelayMode::AllHeaders)
+			),
 			OnDemandRelayAction::None,
 		);
 	}
@@ -428,12 +493,20 @@ mod tests {
 	#[test]
 	fn starts_relay_when_too_many_headers_missing() {
 		assert_eq!(
-			select_on_demand_relay_action::<TestChain>(AT_SOURCE, AT_TARGET, 0, 5, "test", false),
-			OnDemandRelayAction::Start,
+			select_on_demand_relay_action::<TestChain>(AT_SOURCE, AT_TARGET, 0, 5, false, "test", None),
+			OnDemandRelayAction::Start(OnDemandRelayMode::AllHeaders),
 		);
 
 		assert_eq!(
-			select_on_demand_relay_action::<TestChain>(AT_SOURCE, AT_TARGET, 0, 5, "test", true),
+			select_on_demand_relay_action::<TestChain>(
+				AT_SOURCE,
+				AT_TARGET,
+				0,
+				5,
+				false,
+				"test",
+				Some(OnDemandRelayMode::AllHeaders)
+			),
 			OnDemandRelayAction::None,
 		);
 	}
@@ -441,13 +514,79 @@ mod tests {
 	#[test]
 	fn stops_relay_if_required_header_is_synced() {
 		assert_eq!(
-			select_on_demand_relay_action::<TestChain>(AT_SOURCE, AT_TARGET, AT_TARGET.unwrap(), 100, "test", true),
+			select_on_demand_relay_action::<TestChain>(
+				AT_SOURCE,
+				AT_TARGET,
+				AT_TARGET.unwrap(),
+				100,
+				false,
+				"test",
+				Some(OnDemandRelayMode::AllHeaders)
+			),
 			OnDemandRelayAction::Stop,
 		);
 
 		assert_eq!(
-			select_on_demand_relay_action::<TestChain>(AT_SOURCE, AT_TARGET, AT_TARGET.unwrap(), 100, "test", false),
+			select_on_demand_relay_action::<TestChain>(
+				AT_SOURCE,
+				AT_TARGET,
+				AT_TARGET.unwrap(),
+				100,
+				false,
+				"test",
+				None
+			),
+			OnDemandRelayAction::None,
+		);
+	}
+
+	#[test]
+	fn starts_mandatory_headers_relay_when_headers_are_not_required() {
+		assert_eq!(
+			select_on_demand_relay_action::<TestChain>(AT_SOURCE, AT_TARGET, 0, 5, true, "test", None),
+			OnDemandRelayAction::Start(OnDemandRelayMode::MandatoryHeaders),
+		);
+
+		assert_eq!(
+			select_on_demand_relay_action::<TestChain>(
+				AT_SOURCE,
+				AT_TARGET,
+				0,
+				5,
+				true,
+				"test",
+				Some(OnDemandRelayMode::MandatoryHeaders)
+			),
 			OnDemandRelayAction::None,
 		);
 	}
+
+	#[test]
+	fn switches_to_all_headers_relay_when_headers_are_required() {
+		assert_eq!(
+			select_on_demand_relay_action::<TestChain>(
+				AT_SOURCE,
+				AT_TARGET,
+				5,
+				100,
+				true,
+				"test",
+				Some(OnDemandRelayMode::MandatoryHeaders)
+			),
+			OnDemandRelayAction::Start(OnDemandRelayMode::AllHeaders),
+		);
+
+		assert_eq!(
+			select_on_demand_relay_action::<TestChain>(
+				AT_SOURCE,
+				AT_TARGET,
+				AT_TARGET.unwrap(),
+				100,
+				true,
+				"test",
+				Some(OnDemandRelayMode::AllHeaders)
+			),
+			OnDemandRelayAction::Start(OnDemandRelayMode::MandatoryHeaders),
+		);
+	}
 }
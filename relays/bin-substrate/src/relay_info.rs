@@ -0,0 +1,110 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Relay build and configuration information metric.
+//!
+//! The `bridge_relay_info` gauge always has value `1`. Everything useful is in its labels: relay
+//! version, git commit it has been built from, bridged chains, served lanes and relay mode. This
+//! allows correlating changes of relay behavior with relay deployments.
+//!
+//! The metric is registered in the same registry as other relay metrics, so if the registry is
+//! prefixed (i.e. when standalone headers or messages relay is started), the metric name is also
+//! prefixed.
+
+use bp_messages::LaneId;
+use messages_relay::message_lane_loop::RelayerMode;
+use relay_utils::metrics::{metric_name, register, GaugeVec, MetricsParams, Opts, PrometheusError, U64};
+
+/// Relay version.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Git commit the relay has been built from.
+const GIT_COMMIT: &str = env!("SUBSTRATE_RELAY_GIT_COMMIT");
+
+/// Mode of the relay, that is exposed by the `bridge_relay_info` metric.
+#[derive(Debug, Clone, Copy)]
+pub enum RelayInfoMode {
+	/// Finality headers relay.
+	Headers,
+	/// Messages relay, working in given mode.
+	Messages(RelayerMode),
+	/// Complex headers+messages relay, whose messages relays are working in given mode.
+	HeadersAndMessages(RelayerMode),
+}
+
+impl RelayInfoMode {
+	/// Returns value of the `mode` label.
+	fn label(&self) -> String {
+		match *self {
+			RelayInfoMode::Headers => "headers".into(),
+			RelayInfoMode::Messages(relayer_mode) => format!("messages-{}", relayer_mode_label(relayer_mode)),
+			RelayInfoMode::HeadersAndMessages(relayer_mode) => {
+				format!("headers-and-messages-{}", relayer_mode_label(relayer_mode))
+			}
+		}
+	}
+}
+
+/// Register `bridge_relay_info` metric in the registry of given metrics params.
+///
+/// The registry must be created (e.g. by `relay_utils::relay_metrics(...).into_params()`)
+/// before calling this function.
+pub fn register_relay_info(
+	params: &MetricsParams,
+	source_chain: &str,
+	target_chain: &str,
+	lanes: &[LaneId],
+	mode: RelayInfoMode,
+) -> Result<(), String> {
+	let registry = params
+		.registry
+		.as_ref()
+		.ok_or_else(|| "Metrics registry is required to register relay info metric".to_string())?;
+	let relay_info = match register(
+		GaugeVec::<U64>::new(
+			Opts::new(
+				metric_name(params.metrics_prefix.as_deref(), "bridge_relay_info"),
+				"Relay version and configuration. Value is always 1",
+			),
+			&["version", "git_commit", "chains", "lanes", "mode"],
+		)
+		.map_err(|e| e.to_string())?,
+		registry,
+	) {
+		Ok(relay_info) => relay_info,
+		Err(PrometheusError::AlreadyReg) => return Ok(()),
+		Err(e) => return Err(e.to_string()),
+	};
+
+	relay_info
+		.with_label_values(&[
+			VERSION,
+			GIT_COMMIT,
+			&format!("{}-{}", source_chain, target_chain),
+			&lanes.iter().map(ToString::to_string).collect::<Vec<_>>().join(","),
+			&mode.label(),
+		])
+		.set(1);
+
+	Ok(())
+}
+
+/// Returns label of given relayer mode.
+fn relayer_mode_label(relayer_mode: RelayerMode) -> &'static str {
+	match relayer_mode {
+		RelayerMode::Altruistic => "altruistic",
+		RelayerMode::Rational => "rational",
+	}
+}
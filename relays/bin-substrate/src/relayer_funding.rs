@@ -0,0 +1,127 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Automatic funding of relayer accounts at test networks.
+
+use codec::Encode;
+use relay_substrate_client::{
+	ChainWithBalances, Client, Error as SubstrateError, HashOf, TransactionEra, TransactionSignScheme,
+};
+use sp_core::{Bytes, Pair};
+
+/// Number of blocks between checks of relayer balance.
+///
+/// The funding transaction is not tracked, so this interval must be large enough for it to be mined.
+const BALANCE_CHECK_INTERVAL_IN_BLOCKS: u32 = 50;
+
+/// Submit funding transaction, signed by the funding account.
+pub async fn submit_funding_call<C: ChainWithBalances + TransactionSignScheme<Chain = C>>(
+	client: &Client<C>,
+	funding_signer: C::AccountKeyPair,
+	funding_call: C::Call,
+	transactions_mortality: Option<u32>,
+) -> Result<HashOf<C>, SubstrateError>
+where
+	C::AccountId: From<<C::AccountKeyPair as Pair>::Public>,
+	C::SignedTransaction: Encode,
+{
+	let genesis_hash = *client.genesis_hash();
+	client
+		.submit_signed_extrinsic(
+			funding_signer.public().into(),
+			move |best_block_id, transaction_nonce| {
+				Bytes(
+					C::sign_transaction(
+						genesis_hash,
+						&funding_signer,
+						TransactionEra::new(best_block_id.0, best_block_id.1, transactions_mortality),
+						transaction_nonce,
+						num_traits::Zero::zero(),
+						funding_call,
+					)
+					.encode(),
+				)
+			},
+		)
+		.await
+}
+
+/// Start background task that submits the funding call once free balance of the relayer account
+/// drops below the `threshold`.
+///
+/// The funding call is signed by the `funding_signer`. It is expected to increase the relayer balance,
+/// e.g. it could be a transfer from the funding account, or a call to the faucet pallet.
+pub fn start_auto_funding<C: ChainWithBalances + TransactionSignScheme<Chain = C>>(
+	client: Client<C>,
+	relayer: C::AccountId,
+	funding_signer: C::AccountKeyPair,
+	funding_call: impl Fn() -> anyhow::Result<C::Call> + Send + 'static,
+	transactions_mortality: Option<u32>,
+	threshold: C::NativeBalance,
+) where
+	C::AccountId: From<<C::AccountKeyPair as Pair>::Public>,
+	C::Call: Send,
+	C::SignedTransaction: Encode,
+{
+	async_std::task::spawn(async move {
+		loop {
+			async_std::task::sleep(C::AVERAGE_BLOCK_INTERVAL * BALANCE_CHECK_INTERVAL_IN_BLOCKS).await;
+
+			let balance = match client.free_native_balance(relayer.clone()).await {
+				Ok(balance) => balance,
+				Err(error) => {
+					log::warn!(
+						target: "bridge",
+						"Failed to read balance of {} relayer {:?}: {:?}",
+						C::NAME,
+						relayer,
+						error,
+					);
+					continue;
+				}
+			};
+			if balance >= threshold {
+				continue;
+			}
+
+			let result = match funding_call() {
+				Ok(funding_call) => {
+					submit_funding_call(&client, funding_signer.clone(), funding_call, transactions_mortality)
+						.await
+						.map_err(|e| anyhow::format_err!("{:?}", e))
+				}
+				Err(error) => Err(error),
+			};
+			match result {
+				Ok(transaction_hash) => log::info!(
+					target: "bridge",
+					"Funding {} relayer {:?} with balance {:?}: {:?}",
+					C::NAME,
+					relayer,
+					balance,
+					transaction_hash,
+				),
+				Err(error) => log::warn!(
+					target: "bridge",
+					"Failed to fund {} relayer {:?}: {:?}",
+					C::NAME,
+					relayer,
+					error,
+				),
+			}
+		}
+	});
+}
@@ -0,0 +1,134 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Claiming rewards that relayers are earning at the bridge relayers pallet.
+
+use codec::Encode;
+use relay_substrate_client::{
+	Chain, ChainWithBalances, ChainWithProxy, Client, Error as SubstrateError, HashOf, TransactionEra,
+	TransactionSignScheme,
+};
+use sp_core::{storage::StorageKey, Bytes, Pair};
+
+/// Number of blocks between checks of relayer rewards.
+///
+/// The claim transaction is not tracked, so this interval must be large enough for it to be mined.
+const REWARDS_CHECK_INTERVAL_IN_BLOCKS: u32 = 100;
+
+/// Chain with the bridge relayers pallet, where relayers are earning rewards for delivering messages.
+pub trait ChainWithRelayersRewards: ChainWithBalances + ChainWithProxy + TransactionSignScheme<Chain = Self> {
+	/// Returns storage key of rewards, earned by the relayer.
+	fn relayer_rewards_key(relayer: &Self::AccountId) -> StorageKey;
+
+	/// Returns call that transfers all rewards, earned by the call origin.
+	fn claim_rewards_call() -> Self::Call;
+}
+
+/// Returns rewards that have been earned by the relayer and have not been claimed yet.
+pub async fn relayer_rewards<C: ChainWithRelayersRewards>(
+	client: &Client<C>,
+	relayer: &C::AccountId,
+) -> Result<C::NativeBalance, SubstrateError> {
+	Ok(client
+		.storage_value(C::relayer_rewards_key(relayer))
+		.await?
+		.unwrap_or_else(num_traits::Zero::zero))
+}
+
+/// Submit transaction that claims all rewards of the relayer.
+///
+/// If `proxied_account` is specified, rewards of this account are claimed using the `proxy` call.
+pub async fn claim_rewards<C: ChainWithRelayersRewards>(
+	client: &Client<C>,
+	signer: C::AccountKeyPair,
+	proxied_account: Option<C::AccountId>,
+	transactions_mortality: Option<u32>,
+) -> Result<HashOf<C>, SubstrateError>
+where
+	C::AccountId: From<<C::AccountKeyPair as Pair>::Public>,
+	C::SignedTransaction: Encode,
+{
+	let genesis_hash = *client.genesis_hash();
+	client
+		.submit_signed_extrinsic(signer.public().into(), move |best_block_id, transaction_nonce| {
+			Bytes(
+				C::sign_transaction(
+					genesis_hash,
+					&signer,
+					TransactionEra::new(best_block_id.0, best_block_id.1, transactions_mortality),
+					transaction_nonce,
+					num_traits::Zero::zero(),
+					C::maybe_proxy_call(proxied_account, C::claim_rewards_call()),
+				)
+				.encode(),
+			)
+		})
+		.await
+}
+
+/// Start background task that claims rewards of the relayer once they reach the `threshold`.
+pub fn start_rewards_auto_claim<C: ChainWithRelayersRewards>(
+	client: Client<C>,
+	signer: C::AccountKeyPair,
+	proxied_account: Option<C::AccountId>,
+	transactions_mortality: Option<u32>,
+	threshold: C::NativeBalance,
+) where
+	C::AccountId: From<<C::AccountKeyPair as Pair>::Public>,
+	C::SignedTransaction: Encode,
+{
+	let relayer = proxied_account.clone().unwrap_or_else(|| signer.public().into());
+	async_std::task::spawn(async move {
+		loop {
+			async_std::task::sleep(C::AVERAGE_BLOCK_INTERVAL * REWARDS_CHECK_INTERVAL_IN_BLOCKS).await;
+
+			let rewards = match relayer_rewards(&client, &relayer).await {
+				Ok(rewards) => rewards,
+				Err(error) => {
+					log::warn!(
+						target: "bridge",
+						"Failed to read rewards of {} relayer {:?}: {:?}",
+						C::NAME,
+						relayer,
+						error,
+					);
+					continue;
+				}
+			};
+			if rewards < threshold {
+				continue;
+			}
+
+			match claim_rewards(&client, signer.clone(), proxied_account.clone(), transactions_mortality).await {
+				Ok(transaction_hash) => log::info!(
+					target: "bridge",
+					"Claiming {:?} rewards of {} relayer {:?}: {:?}",
+					rewards,
+					C::NAME,
+					relayer,
+					transaction_hash,
+				),
+				Err(error) => log::warn!(
+					target: "bridge",
+					"Failed to claim rewards of {} relayer {:?}: {:?}",
+					C::NAME,
+					relayer,
+					error,
+				),
+			}
+		}
+	});
+}
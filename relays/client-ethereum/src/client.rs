@@ -22,7 +22,7 @@ use crate::types::{
 use crate::{ConnectionParams, Error, Result};
 
 use jsonrpsee_ws_client::{WsClient as RpcClient, WsClientBuilder as RpcClientBuilder};
-use relay_utils::relay_loop::RECONNECT_DELAY;
+use relay_utils::{relay_loop::RECONNECT_DELAY, transport::connection_uri};
 use std::sync::Arc;
 
 /// Number of headers missing from the Ethereum node for us to consider node not synced.
@@ -67,7 +67,9 @@ impl Client {
 
 	/// Build client to use in connection.
 	async fn build_client(params: &ConnectionParams) -> Result<Arc<RpcClient>> {
-		let uri = format!("ws://{}:{}", params.host, params.port);
+		let uri = connection_uri(&params.transport, &params.host, params.port, params.secure)
+			.await
+			.map_err(Error::Transport)?;
 		let client = RpcClientBuilder::default().build(&uri).await?;
 		Ok(Arc::new(client))
 	}
@@ -0,0 +1,242 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! ABI-driven interaction with Ethereum contracts.
+//!
+//! Contract ABI is loaded at runtime, so the relay may be pointed to any contract version
+//! as long as it exposes functions and events with the expected signatures.
+
+use crate::types::{Log, H256, U256};
+use crate::{Error, Result};
+
+pub use ethabi::{Log as ContractEvent, Token};
+
+/// Ethereum contract with known ABI.
+#[derive(Debug, Clone)]
+pub struct Contract {
+	abi: ethabi::Contract,
+}
+
+impl Contract {
+	/// Load contract from its JSON ABI.
+	pub fn from_json_abi(abi: &[u8]) -> Result<Self> {
+		Ok(Contract {
+			abi: ethabi::Contract::load(abi)?,
+		})
+	}
+
+	/// Encode contract deployment transaction payload.
+	pub fn encode_constructor(&self, code: Vec<u8>, params: &[Token]) -> Result<Vec<u8>> {
+		match self.abi.constructor {
+			Some(ref constructor) => Ok(constructor.encode_input(code, params)?),
+			None if params.is_empty() => Ok(code),
+			None => Err(Error::ContractAbi("contract has no constructor".into())),
+		}
+	}
+
+	/// Encode call of given contract function.
+	pub fn encode_call(&self, function: &str, params: &[Token]) -> Result<Vec<u8>> {
+		Ok(self.abi.function(function)?.encode_input(params)?)
+	}
+
+	/// Decode output of given contract function.
+	pub fn decode_output(&self, function: &str, output: &[u8]) -> Result<Vec<Token>> {
+		Ok(self.abi.function(function)?.decode_output(output)?)
+	}
+
+	/// Decode given log entry as contract event.
+	///
+	/// Returns `None` if log entry is not the `event`.
+	pub fn decode_event(&self, event: &str, log: &Log) -> Result<Option<ContractEvent>> {
+		let event = self.abi.event(event)?;
+		if log.topics.first() != Some(&event.signature()) {
+			return Ok(None);
+		}
+
+		Ok(Some(event.parse_log(ethabi::RawLog {
+			topics: log.topics.clone(),
+			data: log.data.0.clone(),
+		})?))
+	}
+}
+
+/// Substrate bridge contract that is deployed to the Ethereum chain.
+#[derive(Debug, Clone)]
+pub struct SubstrateBridgeContract {
+	contract: Contract,
+}
+
+impl SubstrateBridgeContract {
+	/// Load bridge contract from its JSON ABI.
+	pub fn from_json_abi(abi: &[u8]) -> Result<Self> {
+		Ok(SubstrateBridgeContract {
+			contract: Contract::from_json_abi(abi)?,
+		})
+	}
+
+	/// Return reference to the underlying contract.
+	pub fn contract(&self) -> &Contract {
+		&self.contract
+	}
+
+	/// Encode contract deployment transaction payload.
+	pub fn encode_constructor(
+		&self,
+		code: Vec<u8>,
+		initial_header: Vec<u8>,
+		initial_set_id: u64,
+		initial_authorities: Vec<u8>,
+	) -> Result<Vec<u8>> {
+		self.contract.encode_constructor(
+			code,
+			&[
+				Token::Bytes(initial_header),
+				Token::Uint(initial_set_id.into()),
+				Token::Bytes(initial_authorities),
+			],
+		)
+	}
+
+	/// Encode `bestKnownHeader` call.
+	pub fn encode_best_known_header(&self) -> Result<Vec<u8>> {
+		self.contract.encode_call("bestKnownHeader", &[])
+	}
+
+	/// Decode `bestKnownHeader` output into header number and hash.
+	pub fn decode_best_known_header(&self, output: &[u8]) -> Result<(U256, H256)> {
+		let mut tokens = self.contract.decode_output("bestKnownHeader", output)?.into_iter();
+		let number = next_uint(&mut tokens)?;
+		let hash = next_hash(&mut tokens)?;
+		Ok((number, hash))
+	}
+
+	/// Encode `isKnownHeader` call.
+	pub fn encode_is_known_header(&self, hash: H256) -> Result<Vec<u8>> {
+		self.contract
+			.encode_call("isKnownHeader", &[Token::FixedBytes(hash.as_bytes().to_vec())])
+	}
+
+	/// Decode `isKnownHeader` output.
+	pub fn decode_is_known_header(&self, output: &[u8]) -> Result<bool> {
+		let mut tokens = self.contract.decode_output("isKnownHeader", output)?.into_iter();
+		next_token(&mut tokens)?
+			.into_bool()
+			.ok_or_else(|| unexpected_token("bool"))
+	}
+
+	/// Encode `incompleteHeaders` call.
+	pub fn encode_incomplete_headers(&self) -> Result<Vec<u8>> {
+		self.contract.encode_call("incompleteHeaders", &[])
+	}
+
+	/// Decode `incompleteHeaders` output into numbers and hashes of incomplete headers.
+	pub fn decode_incomplete_headers(&self, output: &[u8]) -> Result<Vec<(U256, H256)>> {
+		let mut tokens = self.contract.decode_output("incompleteHeaders", output)?.into_iter();
+		let numbers = next_token(&mut tokens)?
+			.into_array()
+			.ok_or_else(|| unexpected_token("uint256[]"))?;
+		let hashes = next_token(&mut tokens)?
+			.into_array()
+			.ok_or_else(|| unexpected_token("bytes32[]"))?;
+
+		let mut numbers = numbers.into_iter();
+		let mut hashes = hashes.into_iter();
+		let mut incomplete_headers = Vec::new();
+		while let (Some(number), Some(hash)) = (numbers.next(), hashes.next()) {
+			incomplete_headers.push((token_into_uint(number)?, token_into_hash(hash)?));
+		}
+
+		Ok(incomplete_headers)
+	}
+
+	/// Encode `isIncompleteHeaders` call.
+	pub fn encode_is_incomplete_headers(&self, headers: [Vec<u8>; 4]) -> Result<Vec<u8>> {
+		self.contract
+			.encode_call("isIncompleteHeaders", &headers_tokens(headers))
+	}
+
+	/// Decode `isIncompleteHeaders` output.
+	pub fn decode_is_incomplete_headers(&self, output: &[u8]) -> Result<U256> {
+		let mut tokens = self.contract.decode_output("isIncompleteHeaders", output)?.into_iter();
+		next_uint(&mut tokens)
+	}
+
+	/// Encode `importHeaders` call.
+	pub fn encode_import_headers(&self, headers: [Vec<u8>; 4]) -> Result<Vec<u8>> {
+		self.contract.encode_call("importHeaders", &headers_tokens(headers))
+	}
+
+	/// Encode `importFinalityProof` call.
+	pub fn encode_import_finality_proof(
+		&self,
+		target_number: U256,
+		target_hash: H256,
+		finality_proof: Vec<u8>,
+	) -> Result<Vec<u8>> {
+		self.contract.encode_call(
+			"importFinalityProof",
+			&[
+				Token::Uint(target_number),
+				Token::FixedBytes(target_hash.as_bytes().to_vec()),
+				Token::Bytes(finality_proof),
+			],
+		)
+	}
+}
+
+/// Convert encoded headers into function params.
+fn headers_tokens(headers: [Vec<u8>; 4]) -> Vec<Token> {
+	let [h1, h2, h3, h4] = headers;
+	vec![Token::Bytes(h1), Token::Bytes(h2), Token::Bytes(h3), Token::Bytes(h4)]
+}
+
+/// Read next token of the decoded function output.
+fn next_token(tokens: &mut impl Iterator<Item = Token>) -> Result<Token> {
+	tokens
+		.next()
+		.ok_or_else(|| Error::ContractAbi("function has returned less values than expected".into()))
+}
+
+/// Read next `uint256` token of the decoded function output.
+fn next_uint(tokens: &mut impl Iterator<Item = Token>) -> Result<U256> {
+	token_into_uint(next_token(tokens)?)
+}
+
+/// Read next `bytes32` token of the decoded function output.
+fn next_hash(tokens: &mut impl Iterator<Item = Token>) -> Result<H256> {
+	token_into_hash(next_token(tokens)?)
+}
+
+/// Convert `uint256` token into number.
+fn token_into_uint(token: Token) -> Result<U256> {
+	token.into_uint().ok_or_else(|| unexpected_token("uint256"))
+}
+
+/// Convert `bytes32` token into hash.
+fn token_into_hash(token: Token) -> Result<H256> {
+	match token.into_fixed_bytes() {
+		Some(ref bytes) if bytes.len() == H256::len_bytes() => Ok(H256::from_slice(bytes)),
+		_ => Err(unexpected_token("bytes32")),
+	}
+}
+
+/// Error that is returned when function output has unexpected type.
+fn unexpected_token(expected: &str) -> Error {
+	Error::ContractAbi(format!(
+		"function has returned value of unexpected type. Expected {}",
+		expected
+	))
+}
@@ -45,6 +45,10 @@ pub enum Error {
 	/// The client we're connected to is not synced, so we can't rely on its state. Contains
 	/// number of unsynced headers.
 	ClientNotSynced(U256),
+	/// Failed to encode contract call or to decode its output.
+	ContractAbi(String),
+	/// Failed to prepare transport of the RPC connection.
+	Transport(String),
 }
 
 impl From<RpcError> for Error {
@@ -53,6 +57,12 @@ impl From<RpcError> for Error {
 	}
 }
 
+impl From<ethabi::Error> for Error {
+	fn from(error: ethabi::Error) -> Self {
+		Error::ContractAbi(error.to_string())
+	}
+}
+
 impl MaybeConnectionError for Error {
 	fn is_connection_error(&self) -> bool {
 		matches!(
@@ -61,7 +71,8 @@ impl MaybeConnectionError for Error {
 				// right now if connection to the ws server is dropped (after it is already established),
 				// we're getting this error
 				| Error::RpcError(RpcError::Internal(_))
-				| Error::ClientNotSynced(_),
+				| Error::ClientNotSynced(_)
+				| Error::Transport(_),
 		)
 	}
 }
@@ -81,6 +92,8 @@ impl ToString for Error {
 			Self::ClientNotSynced(missing_headers) => {
 				format!("Ethereum client is not synced: syncing {} headers", missing_headers)
 			}
+			Self::ContractAbi(e) => format!("Contract ABI error: {}", e),
+			Self::Transport(e) => format!("RPC connection transport error: {}", e),
 		}
 	}
 }
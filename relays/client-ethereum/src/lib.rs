@@ -26,7 +26,9 @@ mod sign;
 pub use crate::client::Client;
 pub use crate::error::{Error, Result};
 pub use crate::sign::{sign_and_submit_transaction, SigningParams};
+pub use relay_utils::transport::{ProxyParams, TlsParams, TransportParams};
 
+pub mod contract;
 pub mod types;
 
 /// Ethereum-over-websocket connection params.
@@ -36,6 +38,10 @@ pub struct ConnectionParams {
 	pub host: String,
 	/// Websocket server TCP port.
 	pub port: u16,
+	/// Use secure websocket connection.
+	pub secure: bool,
+	/// Transport of the RPC connection.
+	pub transport: TransportParams,
 }
 
 impl Default for ConnectionParams {
@@ -43,6 +49,8 @@ impl Default for ConnectionParams {
 		ConnectionParams {
 			host: "localhost".into(),
 			port: 8546,
+			secure: false,
+			transport: Default::default(),
 		}
 	}
 }
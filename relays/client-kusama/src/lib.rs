@@ -35,6 +35,7 @@ impl ChainBase for Kusama {
 
 impl Chain for Kusama {
 	const NAME: &'static str = "Kusama";
+	const SPEC_NAME: &'static str = "kusama";
 	const AVERAGE_BLOCK_INTERVAL: Duration = Duration::from_secs(6);
 
 	type AccountId = bp_kusama::AccountId;
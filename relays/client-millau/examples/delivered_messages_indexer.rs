@@ -0,0 +1,78 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Minimal indexer of messages, delivered from Rialto to Millau.
+//!
+//! The messages pallet records every delivered message in the offchain indexing storage. This
+//! example reads these records for the given lane, starting from the given nonce, and prints
+//! them. The Millau node must be started with `--enable-offchain-indexing true` and
+//! `--rpc-methods Unsafe` flags.
+//!
+//! Usage: `cargo run --example delivered_messages_indexer -- <host> <port> <lane> [<first nonce>]`,
+//! where lane is the hex-encoded 4-bytes lane identifier (e.g. `00000000`) or the name of well-known lane.
+
+use bp_messages::{DeliveredMessageRecord, LaneId, MessageNonce};
+use pallet_bridge_messages::offchain_keys::delivered_message_key;
+use relay_millau_client::Millau;
+use relay_substrate_client::{Client, ConnectionParams};
+
+/// Delivered message record, written by the Millau runtime.
+type Record = DeliveredMessageRecord<bp_rialto::AccountId, bp_millau::BlockNumber>;
+
+#[async_std::main]
+async fn main() -> Result<(), String> {
+	let mut args = std::env::args().skip(1);
+	let mut next_arg = |name: &str| args.next().ok_or_else(|| format!("Missing argument: {}", name));
+	let host = next_arg("host")?;
+	let port = next_arg("port")?.parse().map_err(|e| format!("Invalid port: {}", e))?;
+	let lane: LaneId = next_arg("lane")?.parse().map_err(|e| format!("Invalid lane: {}", e))?;
+	let first_nonce: MessageNonce = next_arg("first nonce")
+		.ok()
+		.map(|nonce| nonce.parse())
+		.transpose()
+		.map_err(|e| format!("Invalid nonce: {}", e))?
+		.unwrap_or(1);
+
+	let client = Client::<Millau>::try_connect(ConnectionParams {
+		host,
+		port,
+		secure: false,
+		failover_endpoints: Vec::new(),
+		transport: Default::default(),
+	})
+	.await
+	.map_err(|e| format!("Failed to connect to Millau node: {:?}", e))?;
+
+	for nonce in first_nonce.. {
+		let key = delivered_message_key::<millau_runtime::WithRialtoMessagesInstance>(&lane, nonce);
+		let record: Option<Record> = client
+			.offchain_storage_value(key)
+			.await
+			.map_err(|e| format!("Failed to read record of message {}: {:?}", nonce, e))?;
+		match record {
+			Some(record) => println!(
+				"lane={} nonce={} delivered_at={} relayer={} dispatched={}",
+				record.lane_id, record.nonce, record.block_number, record.relayer, record.dispatch_result,
+			),
+			None => {
+				println!("Message {} is not yet delivered", nonce);
+				break;
+			}
+		}
+	}
+
+	Ok(())
+}
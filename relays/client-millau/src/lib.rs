@@ -16,8 +16,15 @@
 
 //! Types used to connect to the Millau-Substrate chain.
 
-use codec::Encode;
-use relay_substrate_client::{Chain, ChainBase, ChainWithBalances, TransactionSignScheme};
+use codec::{Compact, Decode, Encode};
+use frame_support::{
+	traits::Get,
+	weights::{DispatchClass, Weight},
+};
+use relay_substrate_client::{
+	Chain, ChainBase, ChainWithBalances, ChainWithProxy, ChainWithTips, ParsedTransaction, TransactionEraOf,
+	TransactionSignScheme,
+};
 use sp_core::{storage::StorageKey, Pair};
 use sp_runtime::{generic::SignedPayload, traits::IdentifyAccount};
 use std::time::Duration;
@@ -38,6 +45,7 @@ impl ChainBase for Millau {
 
 impl Chain for Millau {
 	const NAME: &'static str = "Millau";
+	const SPEC_NAME: &'static str = "millau-runtime";
 	const AVERAGE_BLOCK_INTERVAL: Duration = Duration::from_secs(5);
 
 	type AccountId = millau_runtime::AccountId;
@@ -57,6 +65,34 @@ impl ChainWithBalances for Millau {
 	}
 }
 
+impl ChainWithProxy for Millau {
+	fn proxy_call(real: Self::AccountId, call: Self::Call) -> Self::Call {
+		millau_runtime::Call::Proxy(millau_runtime::ProxyCall::proxy(real, None, Box::new(call)))
+	}
+}
+
+impl ChainWithTips for Millau {
+	fn max_normal_block_weight() -> Weight {
+		let block_weights = <millau_runtime::Runtime as frame_system::Config>::BlockWeights::get();
+		block_weights
+			.get(DispatchClass::Normal)
+			.max_total
+			.unwrap_or(block_weights.max_block)
+	}
+
+	fn bridge_transaction_signer(encoded_transaction: &[u8]) -> Option<Self::AccountId> {
+		let transaction = millau_runtime::UncheckedExtrinsic::decode(&mut &encoded_transaction[..]).ok()?;
+		match transaction.function {
+			millau_runtime::Call::BridgeRialtoGrandpa(_)
+			| millau_runtime::Call::BridgeWestendGrandpa(_)
+			| millau_runtime::Call::BridgeRialtoParachains(_)
+			| millau_runtime::Call::BridgeRialtoMessages(_)
+			| millau_runtime::Call::BridgeRialtoParachainMessages(_) => transaction.signature.map(|(signer, _, _)| signer),
+			_ => None,
+		}
+	}
+}
+
 impl TransactionSignScheme for Millau {
 	type Chain = Millau;
 	type AccountKeyPair = sp_core::sr25519::Pair;
@@ -65,7 +101,9 @@ impl TransactionSignScheme for Millau {
 	fn sign_transaction(
 		genesis_hash: <Self::Chain as ChainBase>::Hash,
 		signer: &Self::AccountKeyPair,
+		era: TransactionEraOf<Self::Chain>,
 		signer_nonce: <Self::Chain as Chain>::Index,
+		tip: <Self::Chain as ChainWithBalances>::NativeBalance,
 		call: <Self::Chain as Chain>::Call,
 	) -> Self::SignedTransaction {
 		let raw_payload = SignedPayload::from_raw(
@@ -74,16 +112,18 @@ impl TransactionSignScheme for Millau {
 				frame_system::CheckSpecVersion::<millau_runtime::Runtime>::new(),
 				frame_system::CheckTxVersion::<millau_runtime::Runtime>::new(),
 				frame_system::CheckGenesis::<millau_runtime::Runtime>::new(),
-				frame_system::CheckEra::<millau_runtime::Runtime>::from(sp_runtime::generic::Era::Immortal),
+				frame_system::CheckEra::<millau_runtime::Runtime>::from(era.frame_era()),
 				frame_system::CheckNonce::<millau_runtime::Runtime>::from(signer_nonce),
 				frame_system::CheckWeight::<millau_runtime::Runtime>::new(),
-				pallet_transaction_payment::ChargeTransactionPayment::<millau_runtime::Runtime>::from(0),
+				pallet_transaction_payment::ChargeTransactionPayment::<millau_runtime::Runtime>::from(tip),
+				pallet_bridge_relayers::SlashInvalidBridgeTransactions::<millau_runtime::Runtime>::new(),
 			),
 			(
 				millau_runtime::VERSION.spec_version,
 				millau_runtime::VERSION.transaction_version,
 				genesis_hash,
-				genesis_hash,
+				era.signed_payload(genesis_hash),
+				(),
 				(),
 				(),
 				(),
@@ -95,6 +135,19 @@ impl TransactionSignScheme for Millau {
 
 		millau_runtime::UncheckedExtrinsic::new_signed(call, signer.into_account(), signature.into(), extra)
 	}
+
+	fn parse_transaction(encoded_transaction: &[u8]) -> Option<ParsedTransaction<Self::Chain>> {
+		let transaction = millau_runtime::UncheckedExtrinsic::decode(&mut &encoded_transaction[..]).ok()?;
+		let (signer, _, extra) = transaction.signature?;
+		// tip is not exposed by the `ChargeTransactionPayment`, but it is its only encoded field
+		let tip: Compact<millau_runtime::Balance> = Decode::decode(&mut &extra.6.encode()[..]).ok()?;
+		Some(ParsedTransaction {
+			signer,
+			nonce: extra.4 .0,
+			tip: tip.0,
+			call: transaction.function,
+		})
+	}
 }
 
 /// Millau signing params.
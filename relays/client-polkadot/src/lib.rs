@@ -35,6 +35,7 @@ impl ChainBase for Polkadot {
 
 impl Chain for Polkadot {
 	const NAME: &'static str = "Polkadot";
+	const SPEC_NAME: &'static str = "polkadot";
 	const AVERAGE_BLOCK_INTERVAL: Duration = Duration::from_secs(6);
 
 	type AccountId = bp_polkadot::AccountId;
@@ -0,0 +1,143 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types used to connect to the Rialto parachain.
+
+use codec::{Compact, Decode, Encode};
+use frame_support::{
+	traits::Get,
+	weights::{DispatchClass, Weight},
+};
+use relay_substrate_client::{
+	Chain, ChainBase, ChainWithBalances, ChainWithTips, ParsedTransaction, TransactionEraOf, TransactionSignScheme,
+};
+use sp_core::{storage::StorageKey, Pair};
+use sp_runtime::{generic::SignedPayload, traits::IdentifyAccount};
+use std::time::Duration;
+
+/// Rialto parachain header id.
+pub type HeaderId = relay_utils::HeaderId<rialto_parachain_runtime::Hash, rialto_parachain_runtime::BlockNumber>;
+
+/// Rialto parachain definition.
+#[derive(Debug, Clone, Copy)]
+pub struct RialtoParachain;
+
+impl ChainBase for RialtoParachain {
+	type BlockNumber = rialto_parachain_runtime::BlockNumber;
+	type Hash = rialto_parachain_runtime::Hash;
+	type Hasher = rialto_parachain_runtime::Hashing;
+	type Header = rialto_parachain_runtime::Header;
+}
+
+impl Chain for RialtoParachain {
+	const NAME: &'static str = "RialtoParachain";
+	const SPEC_NAME: &'static str = "rialto-parachain-runtime";
+	const AVERAGE_BLOCK_INTERVAL: Duration = Duration::from_secs(12);
+
+	type AccountId = rialto_parachain_runtime::AccountId;
+	type Index = rialto_parachain_runtime::Index;
+	type SignedBlock = rialto_parachain_runtime::SignedBlock;
+	type Call = rialto_parachain_runtime::Call;
+}
+
+impl ChainWithBalances for RialtoParachain {
+	type NativeBalance = rialto_parachain_runtime::Balance;
+
+	fn account_info_storage_key(account_id: &Self::AccountId) -> StorageKey {
+		use frame_support::storage::generator::StorageMap;
+		StorageKey(frame_system::Account::<rialto_parachain_runtime::Runtime>::storage_map_final_key(account_id))
+	}
+}
+
+impl ChainWithTips for RialtoParachain {
+	fn max_normal_block_weight() -> Weight {
+		let block_weights = <rialto_parachain_runtime::Runtime as frame_system::Config>::BlockWeights::get();
+		block_weights
+			.get(DispatchClass::Normal)
+			.max_total
+			.unwrap_or(block_weights.max_block)
+	}
+
+	fn bridge_transaction_signer(encoded_transaction: &[u8]) -> Option<Self::AccountId> {
+		let transaction = rialto_parachain_runtime::UncheckedExtrinsic::decode(&mut &encoded_transaction[..]).ok()?;
+		match transaction.function {
+			rialto_parachain_runtime::Call::BridgeMillauGrandpa(_)
+			| rialto_parachain_runtime::Call::BridgeMillauMessages(_) => transaction.signature.map(|(signer, _, _)| signer),
+			_ => None,
+		}
+	}
+}
+
+impl TransactionSignScheme for RialtoParachain {
+	type Chain = RialtoParachain;
+	type AccountKeyPair = sp_core::sr25519::Pair;
+	type SignedTransaction = rialto_parachain_runtime::UncheckedExtrinsic;
+
+	fn sign_transaction(
+		genesis_hash: <Self::Chain as ChainBase>::Hash,
+		signer: &Self::AccountKeyPair,
+		era: TransactionEraOf<Self::Chain>,
+		signer_nonce: <Self::Chain as Chain>::Index,
+		tip: <Self::Chain as ChainWithBalances>::NativeBalance,
+		call: <Self::Chain as Chain>::Call,
+	) -> Self::SignedTransaction {
+		let raw_payload = SignedPayload::from_raw(
+			call,
+			(
+				frame_system::CheckSpecVersion::<rialto_parachain_runtime::Runtime>::new(),
+				frame_system::CheckTxVersion::<rialto_parachain_runtime::Runtime>::new(),
+				frame_system::CheckGenesis::<rialto_parachain_runtime::Runtime>::new(),
+				frame_system::CheckEra::<rialto_parachain_runtime::Runtime>::from(era.frame_era()),
+				frame_system::CheckNonce::<rialto_parachain_runtime::Runtime>::from(signer_nonce),
+				frame_system::CheckWeight::<rialto_parachain_runtime::Runtime>::new(),
+				pallet_transaction_payment::ChargeTransactionPayment::<rialto_parachain_runtime::Runtime>::from(tip),
+			),
+			(
+				rialto_parachain_runtime::VERSION.spec_version,
+				rialto_parachain_runtime::VERSION.transaction_version,
+				genesis_hash,
+				era.signed_payload(genesis_hash),
+				(),
+				(),
+				(),
+			),
+		);
+		let signature = raw_payload.using_encoded(|payload| signer.sign(payload));
+		let signer: sp_runtime::MultiSigner = signer.public().into();
+		let (call, extra, _) = raw_payload.deconstruct();
+
+		rialto_parachain_runtime::UncheckedExtrinsic::new_signed(call, signer.into_account(), signature.into(), extra)
+	}
+
+	fn parse_transaction(encoded_transaction: &[u8]) -> Option<ParsedTransaction<Self::Chain>> {
+		let transaction = rialto_parachain_runtime::UncheckedExtrinsic::decode(&mut &encoded_transaction[..]).ok()?;
+		let (signer, _, extra) = transaction.signature?;
+		// tip is not exposed by the `ChargeTransactionPayment`, but it is its only encoded field
+		let tip: Compact<rialto_parachain_runtime::Balance> = Decode::decode(&mut &extra.6.encode()[..]).ok()?;
+		Some(ParsedTransaction {
+			signer,
+			nonce: extra.4 .0,
+			tip: tip.0,
+			call: transaction.function,
+		})
+	}
+}
+
+/// Rialto parachain signing params.
+pub type SigningParams = sp_core::sr25519::Pair;
+
+/// Rialto parachain header type used in headers sync.
+pub type SyncHeader = relay_substrate_client::SyncHeader<rialto_parachain_runtime::Header>;
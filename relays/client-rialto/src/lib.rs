@@ -16,8 +16,15 @@
 
 //! Types used to connect to the Rialto-Substrate chain.
 
-use codec::Encode;
-use relay_substrate_client::{Chain, ChainBase, ChainWithBalances, TransactionSignScheme};
+use codec::{Compact, Decode, Encode};
+use frame_support::{
+	traits::Get,
+	weights::{DispatchClass, Weight},
+};
+use relay_substrate_client::{
+	Chain, ChainBase, ChainWithBalances, ChainWithProxy, ChainWithTips, ParsedTransaction, TransactionEraOf,
+	TransactionSignScheme,
+};
 use sp_core::{storage::StorageKey, Pair};
 use sp_runtime::{generic::SignedPayload, traits::IdentifyAccount};
 use std::time::Duration;
@@ -38,6 +45,7 @@ impl ChainBase for Rialto {
 
 impl Chain for Rialto {
 	const NAME: &'static str = "Rialto";
+	const SPEC_NAME: &'static str = "rialto-runtime";
 	const AVERAGE_BLOCK_INTERVAL: Duration = Duration::from_secs(5);
 
 	type AccountId = rialto_runtime::AccountId;
@@ -57,6 +65,32 @@ impl ChainWithBalances for Rialto {
 	}
 }
 
+impl ChainWithProxy for Rialto {
+	fn proxy_call(real: Self::AccountId, call: Self::Call) -> Self::Call {
+		rialto_runtime::Call::Proxy(rialto_runtime::ProxyCall::proxy(real, None, Box::new(call)))
+	}
+}
+
+impl ChainWithTips for Rialto {
+	fn max_normal_block_weight() -> Weight {
+		let block_weights = <rialto_runtime::Runtime as frame_system::Config>::BlockWeights::get();
+		block_weights
+			.get(DispatchClass::Normal)
+			.max_total
+			.unwrap_or(block_weights.max_block)
+	}
+
+	fn bridge_transaction_signer(encoded_transaction: &[u8]) -> Option<Self::AccountId> {
+		let transaction = rialto_runtime::UncheckedExtrinsic::decode(&mut &encoded_transaction[..]).ok()?;
+		match transaction.function {
+			rialto_runtime::Call::BridgeMillauGrandpa(_) | rialto_runtime::Call::BridgeMillauMessages(_) => {
+				transaction.signature.map(|(signer, _, _)| signer)
+			}
+			_ => None,
+		}
+	}
+}
+
 impl TransactionSignScheme for Rialto {
 	type Chain = Rialto;
 	type AccountKeyPair = sp_core::sr25519::Pair;
@@ -65,7 +99,9 @@ impl TransactionSignScheme for Rialto {
 	fn sign_transaction(
 		genesis_hash: <Self::Chain as ChainBase>::Hash,
 		signer: &Self::AccountKeyPair,
+		era: TransactionEraOf<Self::Chain>,
 		signer_nonce: <Self::Chain as Chain>::Index,
+		tip: <Self::Chain as ChainWithBalances>::NativeBalance,
 		call: <Self::Chain as Chain>::Call,
 	) -> Self::SignedTransaction {
 		let raw_payload = SignedPayload::from_raw(
@@ -74,16 +110,18 @@ impl TransactionSignScheme for Rialto {
 				frame_system::CheckSpecVersion::<rialto_runtime::Runtime>::new(),
 				frame_system::CheckTxVersion::<rialto_runtime::Runtime>::new(),
 				frame_system::CheckGenesis::<rialto_runtime::Runtime>::new(),
-				frame_system::CheckEra::<rialto_runtime::Runtime>::from(sp_runtime::generic::Era::Immortal),
+				frame_system::CheckEra::<rialto_runtime::Runtime>::from(era.frame_era()),
 				frame_system::CheckNonce::<rialto_runtime::Runtime>::from(signer_nonce),
 				frame_system::CheckWeight::<rialto_runtime::Runtime>::new(),
-				pallet_transaction_payment::ChargeTransactionPayment::<rialto_runtime::Runtime>::from(0),
+				pallet_transaction_payment::ChargeTransactionPayment::<rialto_runtime::Runtime>::from(tip),
+				pallet_bridge_relayers::SlashInvalidBridgeTransactions::<rialto_runtime::Runtime>::new(),
 			),
 			(
 				rialto_runtime::VERSION.spec_version,
 				rialto_runtime::VERSION.transaction_version,
 				genesis_hash,
-				genesis_hash,
+				era.signed_payload(genesis_hash),
+				(),
 				(),
 				(),
 				(),
@@ -95,6 +133,19 @@ impl TransactionSignScheme for Rialto {
 
 		rialto_runtime::UncheckedExtrinsic::new_signed(call, signer.into_account(), signature.into(), extra)
 	}
+
+	fn parse_transaction(encoded_transaction: &[u8]) -> Option<ParsedTransaction<Self::Chain>> {
+		let transaction = rialto_runtime::UncheckedExtrinsic::decode(&mut &encoded_transaction[..]).ok()?;
+		let (signer, _, extra) = transaction.signature?;
+		// tip is not exposed by the `ChargeTransactionPayment`, but it is its only encoded field
+		let tip: Compact<rialto_runtime::Balance> = Decode::decode(&mut &extra.6.encode()[..]).ok()?;
+		Some(ParsedTransaction {
+			signer,
+			nonce: extra.4 .0,
+			tip: tip.0,
+			call: transaction.function,
+		})
+	}
 }
 
 /// Rialto signing params.
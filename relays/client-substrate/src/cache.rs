@@ -0,0 +1,128 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cache of data that is read from the Substrate node.
+//!
+//! Only the data that never changes once it is known is cached here. So every entry is keyed
+//! by the block hash: headers, justified blocks and runtime calls, made at given block. The
+//! cache is shared by all clones of the same `Client`, so loops (finality relay, messages relays
+//! of different lanes) that are running in the same process, don't repeat the same requests.
+
+use crate::chain::{BlockWithJustification, Chain};
+
+use lru::LruCache;
+use sp_core::Bytes;
+use std::sync::Mutex;
+
+/// Maximal number of headers in the cache.
+const HEADERS_CACHE_CAPACITY: usize = 1024;
+/// Maximal number of justified blocks in the cache.
+///
+/// Blocks are relatively large, so we're only keeping few of them.
+const BLOCKS_CACHE_CAPACITY: usize = 128;
+/// Maximal number of runtime calls results in the cache.
+const STATE_CALLS_CACHE_CAPACITY: usize = 1024;
+
+/// Key of the runtime call result in the cache.
+type StateCallKey<Hash> = (Hash, String, Bytes);
+
+/// Cache of data that is read from the Substrate node.
+pub struct ClientCache<C: Chain> {
+	/// Headers by their hashes.
+	headers: Mutex<LruCache<C::Hash, C::Header>>,
+	/// Justified blocks by their hashes.
+	blocks: Mutex<LruCache<C::Hash, C::SignedBlock>>,
+	/// Results of runtime calls, made at given block.
+	state_calls: Mutex<LruCache<StateCallKey<C::Hash>, Bytes>>,
+}
+
+impl<C: Chain> Default for ClientCache<C> {
+	fn default() -> Self {
+		ClientCache {
+			headers: Mutex::new(LruCache::new(HEADERS_CACHE_CAPACITY)),
+			blocks: Mutex::new(LruCache::new(BLOCKS_CACHE_CAPACITY)),
+			state_calls: Mutex::new(LruCache::new(STATE_CALLS_CACHE_CAPACITY)),
+		}
+	}
+}
+
+impl<C: Chain> ClientCache<C> {
+	/// Return cached header.
+	pub fn header(&self, hash: &C::Hash) -> Option<C::Header> {
+		self.headers
+			.lock()
+			.expect("cache mutex is never poisoned; qed")
+			.get(hash)
+			.cloned()
+	}
+
+	/// Remember header.
+	pub fn insert_header(&self, hash: C::Hash, header: C::Header) {
+		self.headers
+			.lock()
+			.expect("cache mutex is never poisoned; qed")
+			.put(hash, header);
+	}
+
+	/// Return cached block.
+	pub fn block(&self, hash: &C::Hash) -> Option<C::SignedBlock> {
+		self.blocks
+			.lock()
+			.expect("cache mutex is never poisoned; qed")
+			.get(hash)
+			.cloned()
+	}
+
+	/// Remember block if it is justified.
+	///
+	/// Justification may be generated after we have read the block, so unjustified blocks are
+	/// not cached.
+	pub fn insert_block(&self, hash: C::Hash, block: C::SignedBlock) {
+		if block.justification().is_some() {
+			self.blocks
+				.lock()
+				.expect("cache mutex is never poisoned; qed")
+				.put(hash, block);
+		}
+	}
+
+	/// Return cached result of the runtime call.
+	pub fn state_call(&self, at_block: C::Hash, method: &str, data: &Bytes) -> Option<Bytes> {
+		self.state_calls
+			.lock()
+			.expect("cache mutex is never poisoned; qed")
+			.get(&(at_block, method.into(), data.clone()))
+			.cloned()
+	}
+
+	/// Remember result of the runtime call.
+	pub fn insert_state_call(&self, at_block: C::Hash, method: String, data: Bytes, result: Bytes) {
+		self.state_calls
+			.lock()
+			.expect("cache mutex is never poisoned; qed")
+			.put((at_block, method, data), result);
+	}
+
+	/// Forget all cached data.
+	pub fn clear(&self) {
+		self.headers.lock().expect("cache mutex is never poisoned; qed").clear();
+		self.blocks.lock().expect("cache mutex is never poisoned; qed").clear();
+		self.state_calls
+			.lock()
+			.expect("cache mutex is never poisoned; qed")
+			.clear();
+	}
+}
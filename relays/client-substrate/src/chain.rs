@@ -14,15 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
+use crate::TransactionEraOf;
+
 use bp_runtime::Chain as ChainBase;
-use frame_support::Parameter;
+use frame_support::{weights::Weight, Parameter};
 use jsonrpsee_ws_client::{DeserializeOwned, Serialize};
-use num_traits::{CheckedSub, Zero};
 use sp_core::{storage::StorageKey, Pair};
 use sp_runtime::{
 	generic::SignedBlock,
 	traits::{
-		AtLeast32Bit, Block as BlockT, Dispatchable, MaybeDisplay, MaybeSerialize, MaybeSerializeDeserialize, Member,
+		AtLeast32Bit, AtLeast32BitUnsigned, Block as BlockT, Dispatchable, MaybeDisplay, MaybeSerialize,
+		MaybeSerializeDeserialize, Member,
 	},
 	EncodedJustification,
 };
@@ -32,6 +34,8 @@ use std::{fmt::Debug, time::Duration};
 pub trait Chain: ChainBase + Clone {
 	/// Chain name.
 	const NAME: &'static str;
+	/// Name of the runtime (`RuntimeVersion::spec_name`) that the chain node is expected to run.
+	const SPEC_NAME: &'static str;
 	/// Average block interval.
 	///
 	/// How often blocks are produced on that chain. It's suggested to set this value
@@ -60,12 +64,38 @@ pub trait Chain: ChainBase + Clone {
 /// the `pallet_balances::AccountData<NativeBalance>`.
 pub trait ChainWithBalances: Chain {
 	/// Balance of an account in native tokens.
-	type NativeBalance: Parameter + Member + DeserializeOwned + Clone + Copy + CheckedSub + PartialOrd + Zero;
+	type NativeBalance: Parameter + Member + DeserializeOwned + Clone + Copy + AtLeast32BitUnsigned;
 
 	/// Return runtime storage key for getting `frame_system::AccountInfo` of given account.
 	fn account_info_storage_key(account_id: &Self::AccountId) -> StorageKey;
 }
 
+/// Substrate-based chain with the `pallet_proxy` deployed.
+pub trait ChainWithProxy: Chain {
+	/// Wrap given call into `pallet_proxy::Call::proxy`, dispatched on behalf of the `real` account.
+	fn proxy_call(real: Self::AccountId, call: Self::Call) -> Self::Call;
+
+	/// Wrap given call into `pallet_proxy::Call::proxy` if the proxied account is specified.
+	fn maybe_proxy_call(real: Option<Self::AccountId>, call: Self::Call) -> Self::Call {
+		match real {
+			Some(real) => Self::proxy_call(real, call),
+			None => call,
+		}
+	}
+}
+
+/// Substrate-based chain that supports automatic tip adjustment of the relay transactions.
+pub trait ChainWithTips: ChainWithBalances {
+	/// Maximal total weight of normal-class transactions in the single block.
+	fn max_normal_block_weight() -> Weight;
+
+	/// Returns signer of given encoded transaction, if it is a bridge transaction.
+	///
+	/// Bridge transactions of other relayers are competing with our transactions for the block space
+	/// when the chain is congested.
+	fn bridge_transaction_signer(encoded_transaction: &[u8]) -> Option<Self::AccountId>;
+}
+
 /// Block with justification.
 pub trait BlockWithJustification<Header> {
 	/// Return block header.
@@ -77,19 +107,41 @@ pub trait BlockWithJustification<Header> {
 /// Substrate-based chain transactions signing scheme.
 pub trait TransactionSignScheme {
 	/// Chain that this scheme is to be used.
-	type Chain: Chain;
+	type Chain: ChainWithBalances;
 	/// Type of key pairs used to sign transactions.
 	type AccountKeyPair: Pair;
 	/// Signed transaction.
 	type SignedTransaction;
 
 	/// Create transaction for given runtime call, signed by given account.
+	///
+	/// The `tip` is paid by the signer to increase priority of the transaction.
 	fn sign_transaction(
 		genesis_hash: <Self::Chain as ChainBase>::Hash,
 		signer: &Self::AccountKeyPair,
+		era: TransactionEraOf<Self::Chain>,
 		signer_nonce: <Self::Chain as Chain>::Index,
+		tip: <Self::Chain as ChainWithBalances>::NativeBalance,
 		call: <Self::Chain as Chain>::Call,
 	) -> Self::SignedTransaction;
+
+	/// Parse given encoded signed transaction.
+	///
+	/// Returns `None` if transaction is unsigned or if its format is not supported.
+	fn parse_transaction(encoded_transaction: &[u8]) -> Option<ParsedTransaction<Self::Chain>>;
+}
+
+/// Signed transaction, parsed into parts that are required to sign it again.
+#[derive(Debug)]
+pub struct ParsedTransaction<C: ChainWithBalances> {
+	/// Transaction signer.
+	pub signer: C::AccountId,
+	/// Nonce of the signer, used by the transaction.
+	pub nonce: C::Index,
+	/// Tip that is paid by the signer.
+	pub tip: C::NativeBalance,
+	/// Runtime call of the transaction.
+	pub call: C::Call,
 }
 
 impl<Block: BlockT> BlockWithJustification<Block::Header> for SignedBlock<Block> {
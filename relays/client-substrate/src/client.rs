@@ -16,37 +16,100 @@
 
 //! Substrate node client.
 
-use crate::chain::{Chain, ChainWithBalances};
+use crate::cache::ClientCache;
+use crate::chain::{Chain, ChainWithBalances, ChainWithTips};
 use crate::rpc::Substrate;
-use crate::{ConnectionParams, Error, Result};
+use crate::tip::{block_weight_storage_key, CongestionState, FULLNESS_BLOCKS};
+use crate::{ConnectionParams, Error, HeaderIdOf, Result};
 
 use async_std::sync::{Arc, Mutex};
-use codec::Decode;
-use frame_system::AccountInfo;
+use codec::{Decode, Encode};
+use frame_support::weights::DispatchClass;
+use frame_system::{AccountInfo, ConsumedWeight};
 use jsonrpsee_ws_client::{traits::SubscriptionClient, v2::params::JsonRpcParams, DeserializeOwned};
 use jsonrpsee_ws_client::{Subscription, WsClient as RpcClient, WsClientBuilder as RpcClientBuilder};
 use num_traits::Zero;
 use pallet_balances::AccountData;
-use relay_utils::relay_loop::RECONNECT_DELAY;
-use sp_core::{storage::StorageKey, Bytes};
+use pallet_mmr_rpc::LeafProof;
+use pallet_transaction_payment::RuntimeDispatchInfo;
+use relay_utils::{relay_loop::RECONNECT_DELAY, transport::connection_uri};
+use sp_core::{offchain::StorageKind, storage::StorageKey, Bytes};
+use sp_runtime::{traits::Header as HeaderT, Perbill};
 use sp_trie::StorageProof;
 use sp_version::RuntimeVersion;
+use std::convert::TryInto;
 
 const SUB_API_GRANDPA_AUTHORITIES: &str = "GrandpaApi_grandpa_authorities";
+const SUB_API_BEEFY_VALIDATOR_SET: &str = "BeefyApi_validator_set";
+const SUB_API_TRANSACTION_PAYMENT_QUERY_INFO: &str = "TransactionPaymentApi_query_info";
 const MAX_SUBSCRIPTION_CAPACITY: usize = 4096;
+const STORAGE_KEYS_PAGE_SIZE: u32 = 1_000;
 
 /// Opaque justifications subscription type.
-pub type JustificationsSubscription = Subscription<Bytes>;
+pub type JustificationsSubscription = futures::channel::mpsc::Receiver<Bytes>;
 
 /// Opaque GRANDPA authorities set.
 pub type OpaqueGrandpaAuthoritiesSet = Vec<u8>;
 
+/// Opaque BEEFY validator set.
+pub type OpaqueBeefyValidatorSet = Vec<u8>;
+
+/// Stream of SCALE-encoded BEEFY signed commitments.
+pub type BeefyJustificationsSubscription = Subscription<Bytes>;
+
+/// Properties of the chain native token, reported by the node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenProperties {
+	/// Number of decimals in the token amount representation.
+	pub decimals: u32,
+	/// Token symbol.
+	pub symbol: String,
+}
+
+impl Default for TokenProperties {
+	fn default() -> Self {
+		TokenProperties {
+			decimals: 0,
+			symbol: "UNIT".into(),
+		}
+	}
+}
+
+impl TokenProperties {
+	/// Read token properties from the `system_properties` RPC response.
+	///
+	/// Chains with multiple tokens are reporting arrays of decimals and symbols. The first
+	/// (native) token is selected in this case. Missing properties are replaced with defaults.
+	pub fn from_system_properties(properties: &serde_json::Map<String, serde_json::Value>) -> Self {
+		let first = |value: &serde_json::Value| match value {
+			serde_json::Value::Array(values) => values.first().cloned(),
+			value => Some(value.clone()),
+		};
+		let default = Self::default();
+		TokenProperties {
+			decimals: properties
+				.get("tokenDecimals")
+				.and_then(first)
+				.and_then(|decimals| decimals.as_u64())
+				.and_then(|decimals| decimals.try_into().ok())
+				.unwrap_or(default.decimals),
+			symbol: properties
+				.get("tokenSymbol")
+				.and_then(first)
+				.and_then(|symbol| symbol.as_str().map(Into::into))
+				.unwrap_or(default.symbol),
+		}
+	}
+}
+
 /// Substrate client type.
 ///
 /// Cloning `Client` is a cheap operation.
 pub struct Client<C: Chain> {
 	/// Client connection params.
 	params: ConnectionParams,
+	/// Index of the node endpoint (see `ConnectionParams::endpoint`) we're connected to.
+	endpoint: usize,
 	/// Substrate RPC client.
 	client: Arc<RpcClient>,
 	/// Genesis block hash.
@@ -55,15 +118,35 @@ pub struct Client<C: Chain> {
 	/// method, they may get the same transaction nonce. So one of transactions will be rejected
 	/// from the pool. This lock is here to prevent situations like that.
 	submit_signed_extrinsic_lock: Arc<Mutex<()>>,
+	/// Cache of data that has been read from the node.
+	cache: Arc<ClientCache<C>>,
+	/// Justifications subscription, shared by all clones of the client.
+	justifications: Arc<Mutex<SharedJustifications>>,
+}
+
+/// Justifications subscription that is shared by all clones of the client.
+///
+/// The node subscription is opened once, and every received justification is sent to all
+/// subscribers. So when several relay loops are started from the same source chain, they
+/// don't need to open their own node subscriptions.
+#[derive(Default)]
+struct SharedJustifications {
+	/// Channels of all active subscribers.
+	subscribers: Vec<futures::channel::mpsc::Sender<Bytes>>,
+	/// True if the node subscription is active.
+	is_subscribed: bool,
 }
 
 impl<C: Chain> Clone for Client<C> {
 	fn clone(&self) -> Self {
 		Client {
 			params: self.params.clone(),
+			endpoint: self.endpoint,
 			client: self.client.clone(),
 			genesis_hash: self.genesis_hash,
 			submit_signed_extrinsic_lock: self.submit_signed_extrinsic_lock.clone(),
+			cache: self.cache.clone(),
+			justifications: self.justifications.clone(),
 		}
 	}
 }
@@ -71,6 +154,7 @@ impl<C: Chain> Clone for Client<C> {
 impl<C: Chain> std::fmt::Debug for Client<C> {
 	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
 		fmt.debug_struct("Client")
+			.field("endpoint", &self.params.endpoint(self.endpoint))
 			.field("genesis_hash", &self.genesis_hash)
 			.finish()
 	}
@@ -101,33 +185,132 @@ impl<C: Chain> Client<C> {
 	/// Try to connect to Substrate node over websocket. Returns Substrate RPC client if connection
 	/// has been established or error otherwise.
 	pub async fn try_connect(params: ConnectionParams) -> Result<Self> {
-		let client = Self::build_client(params.clone()).await?;
+		let client = Self::build_client(&params, 0).await?;
 
 		let number: C::BlockNumber = Zero::zero();
 		let genesis_hash = Substrate::<C>::chain_get_block_hash(&*client, number).await?;
 
 		Ok(Self {
 			params,
+			endpoint: 0,
 			client,
 			genesis_hash,
 			submit_signed_extrinsic_lock: Arc::new(Mutex::new(())),
+			cache: Arc::new(ClientCache::default()),
+			justifications: Arc::new(Mutex::new(SharedJustifications::default())),
 		})
 	}
 
 	/// Reopen client connection.
 	pub async fn reconnect(&mut self) -> Result<()> {
-		self.client = Self::build_client(self.params.clone()).await?;
+		self.client = self.connect_to_endpoint(self.endpoint).await?;
 		Ok(())
 	}
 
-	/// Build client to use in connection.
-	async fn build_client(params: ConnectionParams) -> Result<Arc<RpcClient>> {
-		let uri = format!(
-			"{}://{}:{}",
-			if params.secure { "wss" } else { "ws" },
-			params.host,
-			params.port,
+	/// Reopen client connection, dropping all data that has been cached by the client.
+	///
+	/// The cache is shared by all clones of the client, so they're also losing their cached data.
+	pub async fn resync(&mut self) -> Result<()> {
+		self.cache.clear();
+		self.reconnect().await
+	}
+
+	/// Switch to the next failover node endpoint.
+	///
+	/// Returns `Ok(false)` if all failover endpoints have been tried. The next failover attempt
+	/// then starts from the first failover endpoint again. If connection to the endpoint fails,
+	/// the error is returned and the next failover attempt switches to the next endpoint.
+	pub async fn failover(&mut self) -> Result<bool> {
+		let next_endpoint = self.endpoint + 1;
+		if self.params.endpoint(next_endpoint).is_none() {
+			// reconnects are now made to the main node until next failover
+			self.endpoint = 0;
+			return Ok(false);
+		}
+
+		self.endpoint = next_endpoint;
+		self.client = self.connect_to_endpoint(next_endpoint).await?;
+		log::info!(
+			target: "bridge",
+			"{} client has switched to failover endpoint {:?}",
+			C::NAME,
+			self.params.endpoint(next_endpoint),
 		);
+		Ok(true)
+	}
+
+	/// Connect to the node endpoint and ensure that it runs the same chain.
+	async fn connect_to_endpoint(&self, endpoint: usize) -> Result<Arc<RpcClient>> {
+		let client = Self::build_client(&self.params, endpoint).await?;
+
+		// the node behind the same address may have been replaced with the node of another chain
+		// (and failover node may be misconfigured)
+		let number: C::BlockNumber = Zero::zero();
+		let genesis_hash = Substrate::<C>::chain_get_block_hash(&*client, number).await?;
+		if genesis_hash != self.genesis_hash {
+			return Err(Error::ChainMismatch(format!(
+				"{} node at {:?} has genesis hash {:?}, expected {:?}",
+				C::NAME,
+				self.params.endpoint(endpoint),
+				genesis_hash,
+				self.genesis_hash,
+			)));
+		}
+
+		Ok(client)
+	}
+
+	/// Ensure that the node we're connected to runs the chain we're expecting.
+	///
+	/// Checks that the node runtime has expected spec name, that the node reports expected
+	/// SS58 address format (if it reports it at all) and that the chain has expected genesis
+	/// hash (if it is provided).
+	pub async fn ensure_correct_chain(&self, ss58_format: u16, genesis_hash: Option<C::Hash>) -> Result<()> {
+		if let Some(genesis_hash) = genesis_hash {
+			if genesis_hash != self.genesis_hash {
+				return Err(Error::ChainMismatch(format!(
+					"expected {} genesis hash {:?}, node has {:?}",
+					C::NAME,
+					genesis_hash,
+					self.genesis_hash,
+				)));
+			}
+		}
+
+		let runtime_version = self.runtime_version().await?;
+		if runtime_version.spec_name != C::SPEC_NAME {
+			return Err(Error::ChainMismatch(format!(
+				"expected {} runtime spec name {}, node runs {}",
+				C::NAME,
+				C::SPEC_NAME,
+				runtime_version.spec_name,
+			)));
+		}
+
+		let properties = Substrate::<C>::system_properties(&*self.client).await?;
+		let node_ss58_format = properties.get("ss58Format").and_then(|format| format.as_u64());
+		if let Some(node_ss58_format) = node_ss58_format {
+			if node_ss58_format != ss58_format as u64 {
+				return Err(Error::ChainMismatch(format!(
+					"expected {} SS58 format {}, node reports {}",
+					C::NAME,
+					ss58_format,
+					node_ss58_format,
+				)));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Build client to use in connection.
+	async fn build_client(params: &ConnectionParams, endpoint: usize) -> Result<Arc<RpcClient>> {
+		let (host, port) = params
+			.endpoint(endpoint)
+			.ok_or_else(|| Error::Custom(format!("Unknown {} node endpoint: {}", C::NAME, endpoint)))?;
+		let uri = connection_uri(&params.transport, host, port, params.secure)
+			.await
+			.map_err(Error::Transport)?;
 		let client = RpcClientBuilder::default()
 			.max_notifs_per_subscription(MAX_SUBSCRIPTION_CAPACITY)
 			.build(&uri)
@@ -168,8 +351,18 @@ impl<C: Chain> Client<C> {
 	}
 
 	/// Get a Substrate block from its hash.
+	///
+	/// Justified blocks are cached, so repeated requests of the same block don't hit the node.
 	pub async fn get_block(&self, block_hash: Option<C::Hash>) -> Result<C::SignedBlock> {
-		Ok(Substrate::<C>::chain_get_block(&*self.client, block_hash).await?)
+		if let Some(block) = block_hash.and_then(|block_hash| self.cache.block(&block_hash)) {
+			return Ok(block);
+		}
+
+		let block = Substrate::<C>::chain_get_block(&*self.client, block_hash).await?;
+		if let Some(block_hash) = block_hash {
+			self.cache.insert_block(block_hash, block.clone());
+		}
+		Ok(block)
 	}
 
 	/// Get a Substrate header by its hash.
@@ -177,7 +370,13 @@ impl<C: Chain> Client<C> {
 	where
 		C::Header: DeserializeOwned,
 	{
-		Ok(Substrate::<C>::chain_get_header(&*self.client, block_hash).await?)
+		if let Some(header) = self.cache.header(&block_hash) {
+			return Ok(header);
+		}
+
+		let header: C::Header = Substrate::<C>::chain_get_header(&*self.client, block_hash).await?;
+		self.cache.insert_header(block_hash, header.clone());
+		Ok(header)
 	}
 
 	/// Get a Substrate block hash by its number.
@@ -199,9 +398,48 @@ impl<C: Chain> Client<C> {
 		Ok(Substrate::<C>::state_runtime_version(&*self.client).await?)
 	}
 
+	/// Return properties of the chain native token.
+	pub async fn token_properties(&self) -> Result<TokenProperties> {
+		let properties = Substrate::<C>::system_properties(&*self.client).await?;
+		Ok(TokenProperties::from_system_properties(&properties))
+	}
+
 	/// Read value from runtime storage.
 	pub async fn storage_value<T: Decode>(&self, storage_key: StorageKey) -> Result<Option<T>> {
-		Substrate::<C>::state_get_storage(&*self.client, storage_key)
+		Substrate::<C>::state_get_storage(&*self.client, storage_key, None)
+			.await?
+			.map(|encoded_value| T::decode(&mut &encoded_value.0[..]).map_err(Error::ResponseParseFailed))
+			.transpose()
+	}
+
+	/// Read keys of all runtime storage entries that start with given prefix.
+	///
+	/// Keys are read page by page, so the number of keys is not limited by the node.
+	pub async fn storage_keys(&self, prefix: StorageKey) -> Result<Vec<StorageKey>> {
+		let mut keys: Vec<StorageKey> = Vec::new();
+		loop {
+			let page = Substrate::<C>::state_get_keys_paged(
+				&*self.client,
+				prefix.clone(),
+				STORAGE_KEYS_PAGE_SIZE,
+				keys.last().cloned(),
+				None,
+			)
+			.await?;
+			let is_last_page = page.len() < STORAGE_KEYS_PAGE_SIZE as usize;
+			keys.extend(page);
+			if is_last_page {
+				return Ok(keys);
+			}
+		}
+	}
+
+	/// Read value from the persistent offchain storage of the node.
+	///
+	/// This storage holds values, written by the runtime using offchain indexing. The node must
+	/// be started with `--enable-offchain-indexing true` and with unsafe RPC methods enabled.
+	pub async fn offchain_storage_value<T: Decode>(&self, key: Vec<u8>) -> Result<Option<T>> {
+		Substrate::<C>::offchain_local_storage_get(&*self.client, StorageKind::PERSISTENT, Bytes(key))
 			.await?
 			.map(|encoded_value| T::decode(&mut &encoded_value.0[..]).map_err(Error::ResponseParseFailed))
 			.transpose()
@@ -213,7 +451,7 @@ impl<C: Chain> Client<C> {
 		C: ChainWithBalances,
 	{
 		let storage_key = C::account_info_storage_key(&account);
-		let encoded_account_data = Substrate::<C>::state_get_storage(&*self.client, storage_key)
+		let encoded_account_data = Substrate::<C>::state_get_storage(&*self.client, storage_key, None)
 			.await?
 			.ok_or(Error::AccountDoesNotExist)?;
 		let decoded_account_data =
@@ -229,6 +467,28 @@ impl<C: Chain> Client<C> {
 		Ok(Substrate::<C>::system_account_next_index(&*self.client, account).await?)
 	}
 
+	/// Ensure that no other process is submitting transactions on behalf of given account.
+	///
+	/// Watches the account nonce (that also counts account transactions in the node pool) for
+	/// `blocks` blocks and returns error if it changes.
+	pub async fn ensure_account_is_idle(&self, account: C::AccountId, blocks: u32) -> Result<()> {
+		let initial_nonce = self.next_account_index(account.clone()).await?;
+		async_std::task::sleep(C::AVERAGE_BLOCK_INTERVAL * blocks).await;
+		let current_nonce = self.next_account_index(account.clone()).await?;
+		if current_nonce != initial_nonce {
+			return Err(Error::Custom(format!(
+				"Nonce of {} account {:?} has changed from {} to {} in {} blocks. Is it used by another relay?",
+				C::NAME,
+				account,
+				initial_nonce,
+				current_nonce,
+				blocks,
+			)));
+		}
+
+		Ok(())
+	}
+
 	/// Submit unsigned extrinsic for inclusion in a block.
 	///
 	/// Note: The given transaction needs to be SCALE encoded beforehand.
@@ -244,38 +504,178 @@ impl<C: Chain> Client<C> {
 	/// `submit_signed_extrinsic()` call. This guarantees that no nonces collision may happen
 	/// if all client instances are clones of the same initial `Client`.
 	///
+	/// The `prepare_extrinsic` is called with id of the current best block (that may be used to
+	/// build mortal transaction) and the nonce of the signer account.
+	///
 	/// Note: The given transaction needs to be SCALE encoded beforehand.
 	pub async fn submit_signed_extrinsic(
 		&self,
 		extrinsic_signer: C::AccountId,
-		prepare_extrinsic: impl FnOnce(C::Index) -> Bytes,
+		prepare_extrinsic: impl FnOnce(HeaderIdOf<C>, C::Index) -> Bytes,
 	) -> Result<C::Hash> {
 		let _guard = self.submit_signed_extrinsic_lock.lock().await;
 		let transaction_nonce = self.next_account_index(extrinsic_signer).await?;
-		let extrinsic = prepare_extrinsic(transaction_nonce);
+		let best_header = self.best_header().await?;
+		let best_header_id = relay_utils::HeaderId(*best_header.number(), best_header.hash());
+		let extrinsic = prepare_extrinsic(best_header_id, transaction_nonce);
 		let tx_hash = Substrate::<C>::author_submit_extrinsic(&*self.client, extrinsic).await?;
 		log::trace!(target: "bridge", "Sent transaction to {} node: {:?}", C::NAME, tx_hash);
 		Ok(tx_hash)
 	}
 
+	/// Return encoded transactions that are currently in the node transaction pool.
+	pub async fn pending_extrinsics(&self) -> Result<Vec<Bytes>> {
+		Ok(Substrate::<C>::author_pending_extrinsics(&*self.client).await?)
+	}
+
+	/// Inspect recent blocks and the transaction pool to find out whether the chain is congested.
+	///
+	/// Bridge transactions that are signed by the `relay_account` are not counted as competing
+	/// transactions.
+	pub async fn congestion_state(&self, relay_account: &C::AccountId) -> Result<CongestionState>
+	where
+		C: ChainWithTips,
+		C::Header: DeserializeOwned,
+	{
+		let max_block_weight = C::max_normal_block_weight();
+		let mut header = self.best_header().await?;
+		let mut total_fullness: u64 = 0;
+		let mut inspected_blocks: u64 = 0;
+		loop {
+			let block_weight: ConsumedWeight =
+				Substrate::<C>::state_get_storage(&*self.client, block_weight_storage_key(), Some(header.hash()))
+					.await?
+					.map(|encoded_weight| {
+						ConsumedWeight::decode(&mut &encoded_weight.0[..]).map_err(Error::ResponseParseFailed)
+					})
+					.transpose()?
+					.unwrap_or_default();
+			let block_fullness = Perbill::from_rational(*block_weight.get(DispatchClass::Normal), max_block_weight);
+			total_fullness += block_fullness.deconstruct() as u64;
+			inspected_blocks += 1;
+
+			if inspected_blocks == FULLNESS_BLOCKS as u64 || header.number().is_zero() {
+				break;
+			}
+			header = self.header_by_hash(*header.parent_hash()).await?;
+		}
+
+		let competing_transactions = self
+			.pending_extrinsics()
+			.await?
+			.iter()
+			.filter_map(|transaction| C::bridge_transaction_signer(&transaction.0))
+			.filter(|signer| signer != relay_account)
+			.count();
+
+		Ok(CongestionState {
+			block_fullness: Perbill::from_parts((total_fullness / inspected_blocks) as u32),
+			competing_transactions: competing_transactions.try_into().unwrap_or(u32::MAX),
+		})
+	}
+
+	/// Select tip for the next relay transaction, signed by the `relay_account`.
+	///
+	/// The tip never exceeds `max_tip`. If `max_tip` is zero, the chain state is not inspected at all.
+	pub async fn select_transaction_tip(
+		&self,
+		relay_account: &C::AccountId,
+		max_tip: C::NativeBalance,
+	) -> Result<C::NativeBalance>
+	where
+		C: ChainWithTips,
+		C::Header: DeserializeOwned,
+	{
+		if max_tip.is_zero() {
+			return Ok(max_tip);
+		}
+
+		let congestion_state = self.congestion_state(relay_account).await?;
+		let tip = congestion_state.select_tip(max_tip);
+		log::trace!(
+			target: "bridge",
+			"Selected {} transaction tip: {:?}. Congestion state: {:?}",
+			C::NAME,
+			tip,
+			congestion_state,
+		);
+		Ok(tip)
+	}
+
+	/// Estimate fee that will be paid for given extrinsic, if it is included into the next block.
+	///
+	/// The estimation is made by the `TransactionPaymentApi` at the best block and doesn't include tip.
+	pub async fn estimate_extrinsic_fee(&self, transaction: Bytes) -> Result<C::NativeBalance>
+	where
+		C: ChainWithBalances,
+	{
+		let transaction_len = transaction.0.len() as u32;
+		let mut data = transaction.0;
+		transaction_len.encode_to(&mut data);
+
+		let encoded_response = Substrate::<C>::state_call(
+			&*self.client,
+			SUB_API_TRANSACTION_PAYMENT_QUERY_INFO.into(),
+			Bytes(data),
+			None,
+		)
+		.await?;
+		let dispatch_info = RuntimeDispatchInfo::<C::NativeBalance>::decode(&mut &encoded_response.0[..])
+			.map_err(Error::ResponseParseFailed)?;
+		Ok(dispatch_info.partial_fee)
+	}
+
 	/// Get the GRANDPA authority set at given block.
 	pub async fn grandpa_authorities_set(&self, block: C::Hash) -> Result<OpaqueGrandpaAuthoritiesSet> {
 		let call = SUB_API_GRANDPA_AUTHORITIES.to_string();
 		let data = Bytes(Vec::new());
 
-		let encoded_response = Substrate::<C>::state_call(&*self.client, call, data, Some(block)).await?;
+		let encoded_response = self.state_call(call, data, Some(block)).await?;
 		let authority_list = encoded_response.0;
 
 		Ok(authority_list)
 	}
 
-	/// Execute runtime call at given block.
-	pub async fn state_call(&self, method: String, data: Bytes, at_block: Option<C::Hash>) -> Result<Bytes> {
-		Substrate::<C>::state_call(&*self.client, method, data, at_block)
+	/// Get the BEEFY validator set at given block.
+	pub async fn beefy_validator_set(&self, block: C::Hash) -> Result<OpaqueBeefyValidatorSet> {
+		let call = SUB_API_BEEFY_VALIDATOR_SET.to_string();
+		let data = Bytes(Vec::new());
+
+		let encoded_response = self.state_call(call, data, Some(block)).await?;
+		let validator_set = encoded_response.0;
+
+		Ok(validator_set)
+	}
+
+	/// Generate proof of the MMR leaf with given index, using the MMR state at given block.
+	pub async fn mmr_generate_proof(&self, leaf_index: u64, at_block: C::Hash) -> Result<LeafProof<C::Hash>> {
+		Substrate::<C>::mmr_generate_proof(&*self.client, leaf_index, Some(at_block))
 			.await
 			.map_err(Into::into)
 	}
 
+	/// Execute runtime call at given block.
+	///
+	/// Results of calls at given (not best) block are cached, so repeated calls don't hit the node.
+	pub async fn state_call(&self, method: String, data: Bytes, at_block: Option<C::Hash>) -> Result<Bytes> {
+		let at_block = match at_block {
+			Some(at_block) => at_block,
+			None => {
+				return Substrate::<C>::state_call(&*self.client, method, data, None)
+					.await
+					.map_err(Into::into)
+			}
+		};
+
+		if let Some(result) = self.cache.state_call(at_block, &method, &data) {
+			return Ok(result);
+		}
+
+		let result = Substrate::<C>::state_call(&*self.client, method.clone(), data.clone(), Some(at_block)).await?;
+		self.cache.insert_state_call(at_block, method, data, result.clone());
+		Ok(result)
+	}
+
 	/// Returns storage proof of given storage keys.
 	pub async fn prove_storage(&self, keys: Vec<StorageKey>, at_block: C::Hash) -> Result<StorageProof> {
 		Substrate::<C>::state_prove_storage(&*self.client, keys, Some(at_block))
@@ -285,14 +685,121 @@ impl<C: Chain> Client<C> {
 	}
 
 	/// Return new justifications stream.
+	///
+	/// The node subscription is shared by all streams, opened by this client and its clones.
 	pub async fn subscribe_justifications(&self) -> Result<JustificationsSubscription> {
+		let (sender, receiver) = futures::channel::mpsc::channel(MAX_SUBSCRIPTION_CAPACITY);
+		let mut justifications = self.justifications.lock().await;
+		if !justifications.is_subscribed {
+			let subscription = self
+				.client
+				.subscribe(
+					"grandpa_subscribeJustifications",
+					JsonRpcParams::NoParams,
+					"grandpa_unsubscribeJustifications",
+				)
+				.await?;
+			justifications.is_subscribed = true;
+			async_std::task::spawn(broadcast_justifications::<C>(subscription, self.justifications.clone()));
+		}
+		justifications.subscribers.push(sender);
+
+		Ok(receiver)
+	}
+
+	/// Return new BEEFY justifications (signed commitments) stream.
+	///
+	/// Unlike GRANDPA justifications, BEEFY subscription isn't shared with other streams.
+	pub async fn subscribe_beefy_justifications(&self) -> Result<BeefyJustificationsSubscription> {
 		Ok(self
 			.client
 			.subscribe(
-				"grandpa_subscribeJustifications",
+				"beefy_subscribeJustifications",
 				JsonRpcParams::NoParams,
-				"grandpa_unsubscribeJustifications",
+				"beefy_unsubscribeJustifications",
 			)
 			.await?)
 	}
 }
+
+/// Send justifications from the node subscription to all subscribers.
+///
+/// Stops when the node subscription ends or when there are no subscribers left. Streams of
+/// all subscribers are closed when the node subscription ends, so they're able to reconnect.
+async fn broadcast_justifications<C: Chain>(
+	mut subscription: Subscription<Bytes>,
+	justifications: Arc<Mutex<SharedJustifications>>,
+) {
+	while let Some(justification) = subscription.next().await {
+		let mut justifications = justifications.lock().await;
+		justifications.subscribers = std::mem::take(&mut justifications.subscribers)
+			.into_iter()
+			.filter_map(|mut subscriber| match subscriber.try_send(justification.clone()) {
+				Ok(()) => Some(subscriber),
+				Err(e) if e.is_full() => {
+					log::warn!(
+						target: "bridge",
+						"Dropping {} justification: subscriber is not reading the stream",
+						C::NAME,
+					);
+					Some(subscriber)
+				}
+				Err(_) => None,
+			})
+			.collect();
+		if justifications.subscribers.is_empty() {
+			justifications.is_subscribed = false;
+			return;
+		}
+	}
+
+	log::debug!(target: "bridge", "{} justifications subscription has ended", C::NAME);
+	let mut justifications = justifications.lock().await;
+	justifications.is_subscribed = false;
+	justifications.subscribers.clear();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn properties(json: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+		json.as_object().cloned().unwrap()
+	}
+
+	#[test]
+	fn token_properties_are_read_from_system_properties() {
+		assert_eq!(
+			TokenProperties::from_system_properties(&properties(serde_json::json!({
+				"tokenDecimals": 9,
+				"tokenSymbol": "MLAU",
+			}))),
+			TokenProperties {
+				decimals: 9,
+				symbol: "MLAU".into()
+			},
+		);
+	}
+
+	#[test]
+	fn native_token_properties_are_selected_for_multi_token_chains() {
+		assert_eq!(
+			TokenProperties::from_system_properties(&properties(serde_json::json!({
+				"tokenDecimals": [12, 18],
+				"tokenSymbol": ["NATIVE", "OTHER"],
+			}))),
+			TokenProperties {
+				decimals: 12,
+				symbol: "NATIVE".into()
+			},
+		);
+	}
+
+	#[test]
+	fn default_token_properties_are_used_when_node_does_not_report_them() {
+		assert_eq!(
+			TokenProperties::from_system_properties(&properties(serde_json::json!({}))),
+			TokenProperties::default(),
+		);
+	}
+}
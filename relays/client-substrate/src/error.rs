@@ -17,7 +17,7 @@
 //! Substrate node RPC errors.
 
 use jsonrpsee_ws_client::Error as RpcError;
-use relay_utils::MaybeConnectionError;
+use relay_utils::{ErrorKind, MaybeConnectionError};
 use sc_rpc_api::system::Health;
 
 /// Result type used by Substrate client.
@@ -42,6 +42,10 @@ pub enum Error {
 	ClientNotSynced(Health),
 	/// An error has happened when we have tried to parse storage proof.
 	StorageProofError(bp_runtime::StorageProofError),
+	/// The node we're connected to runs chain that is different from the expected one.
+	ChainMismatch(String),
+	/// Failed to prepare transport of the RPC connection.
+	Transport(String),
 	/// Custom logic error.
 	Custom(String),
 }
@@ -56,6 +60,8 @@ impl std::error::Error for Error {
 			Self::MissingMandatoryCodeEntry => None,
 			Self::ClientNotSynced(_) => None,
 			Self::StorageProofError(_) => None,
+			Self::ChainMismatch(_) => None,
+			Self::Transport(_) => None,
 			Self::Custom(_) => None,
 		}
 	}
@@ -76,9 +82,23 @@ impl MaybeConnectionError for Error {
 				// we're getting this error
 				| Error::RpcError(RpcError::Internal(_))
 				| Error::RpcError(RpcError::RestartNeeded(_))
-				| Error::ClientNotSynced(_),
+				| Error::ClientNotSynced(_)
+				| Error::Transport(_),
 		)
 	}
+
+	fn error_kind(&self) -> ErrorKind {
+		match *self {
+			_ if self.is_connection_error() => ErrorKind::Connection,
+			Error::Transport(_) => ErrorKind::Connection,
+			Error::ResponseParseFailed(_) | Error::StorageProofError(_) => ErrorKind::Decoding,
+			Error::UninitializedBridgePallet | Error::MissingMandatoryCodeEntry | Error::ChainMismatch(_) => {
+				ErrorKind::RuntimeMismatch
+			}
+			Error::AccountDoesNotExist => ErrorKind::Account,
+			Error::RpcError(_) | Error::ClientNotSynced(_) | Error::Custom(_) => ErrorKind::Logic,
+		}
+	}
 }
 
 impl std::fmt::Display for Error {
@@ -91,6 +111,8 @@ impl std::fmt::Display for Error {
 			Self::MissingMandatoryCodeEntry => "Mandatory :code: entry is missing from runtime storage".into(),
 			Self::StorageProofError(e) => format!("Error when parsing storage proof: {:?}", e),
 			Self::ClientNotSynced(health) => format!("Substrate client is not synced: {}", health),
+			Self::ChainMismatch(e) => format!("Connected to unexpected chain: {}", e),
+			Self::Transport(e) => format!("RPC connection transport error: {}", e),
 			Self::Custom(e) => e.clone(),
 		};
 
@@ -62,17 +62,25 @@ impl<C: Chain, P: FinalitySyncPipeline> RelayClient for FinalitySource<C, P> {
 	async fn reconnect(&mut self) -> Result<(), Error> {
 		self.client.reconnect().await
 	}
+
+	async fn resync(&mut self) -> Result<(), Error> {
+		self.client.resync().await
+	}
+
+	async fn failover(&mut self) -> Result<bool, Error> {
+		self.client.failover().await
+	}
 }
 
 #[async_trait]
-impl<C, P> SourceClient<P> for FinalitySource<C, P>
+impl<C, P, D> SourceClient<P> for FinalitySource<C, P>
 where
 	C: Chain,
 	C::BlockNumber: relay_utils::BlockNumberBase,
 	P: FinalitySyncPipeline<
 		Hash = C::Hash,
 		Number = C::BlockNumber,
-		Header = SyncHeader<C::Header>,
+		Header = SyncHeader<C::Header, D>,
 		FinalityProof = GrandpaJustification<C::Header>,
 	>,
 	P::Header: SourceHeader<C::BlockNumber>,
@@ -15,12 +15,18 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Pallet provides a set of guard functions that are running in background threads
-//! and are aborting process if some condition fails.
+//! and are aborting process (or firing alerts) if some condition fails.
 
 use crate::{Chain, ChainWithBalances, Client};
 
 use async_trait::async_trait;
 use num_traits::CheckedSub;
+use relay_utils::{
+	alerts::{fire_alert, AlertKind},
+	ErrorKind,
+};
+use sp_core::storage::StorageKey;
+use sp_runtime::traits::UniqueSaturatedInto;
 use sp_version::RuntimeVersion;
 use std::{
 	collections::VecDeque,
@@ -34,6 +40,10 @@ pub trait Environment<C: ChainWithBalances>: Send + Sync + 'static {
 	async fn runtime_version(&mut self) -> Result<RuntimeVersion, String>;
 	/// Return free native balance of the account on the chain.
 	async fn free_native_balance(&mut self, account: C::AccountId) -> Result<C::NativeBalance, String>;
+	/// Return index of the next transaction of the account on the chain.
+	async fn next_account_index(&mut self, account: C::AccountId) -> Result<C::Index, String>;
+	/// Return true if pallet is halted, i.e. if the boolean value at given storage key is `true`.
+	async fn is_halted(&mut self, is_halted_key: StorageKey) -> Result<bool, String>;
 
 	/// Return current time.
 	fn now(&self) -> Instant {
@@ -44,8 +54,10 @@ pub trait Environment<C: ChainWithBalances>: Send + Sync + 'static {
 		async_std::task::sleep(duration).await
 	}
 	/// Abort current process. Called when guard condition check fails.
-	async fn abort(&mut self) {
-		std::process::abort();
+	///
+	/// Process exit code is selected using the kind of the failed condition.
+	async fn abort(&mut self, kind: ErrorKind) {
+		relay_utils::error::exit_on_error(Some(kind), "relay guard condition has failed");
 	}
 }
 
@@ -65,7 +77,7 @@ pub fn abort_on_spec_version_change<C: ChainWithBalances>(mut env: impl Environm
 						version.spec_version,
 					);
 
-					env.abort().await;
+					env.abort(ErrorKind::RuntimeMismatch).await;
 				}
 				Err(error) => log::warn!(
 					target: "bridge-guard",
@@ -125,7 +137,7 @@ pub fn abort_when_account_balance_decreased<C: ChainWithBalances>(
 							current_time.duration_since(*oldest_time).as_secs() / 60,
 						);
 
-						env.abort().await;
+						env.abort(ErrorKind::Account).await;
 					}
 				}
 				Err(error) => {
@@ -144,6 +156,157 @@ pub fn abort_when_account_balance_decreased<C: ChainWithBalances>(
 	});
 }
 
+/// Fire alert when free balance of given account is below given threshold.
+pub fn alert_when_account_balance_below<C: ChainWithBalances>(
+	mut env: impl Environment<C>,
+	account_id: C::AccountId,
+	threshold: C::NativeBalance,
+) {
+	async_std::task::spawn(async move {
+		loop {
+			match env.free_native_balance(account_id.clone()).await {
+				Ok(balance) if balance < threshold => fire_alert(
+					AlertKind::LowBalance,
+					&format!("{} account {:?}", C::NAME, account_id),
+					format!("Balance {:?} is below the threshold {:?}", balance, threshold),
+				),
+				Ok(_) => (),
+				Err(error) => log::warn!(
+					target: "bridge-guard",
+					"Failed to read {} account {:?} balance: {:?}",
+					C::NAME,
+					account_id,
+					error,
+				),
+			}
+
+			env.sleep(conditions_check_delay::<C>()).await;
+		}
+	});
+}
+
+/// Fire alert when free balance of given account is estimated to be enough for less than given number
+/// of transactions.
+///
+/// The cost of single transaction is estimated using recent changes of the account nonce and balance. If
+/// the nonce has increased and the balance has decreased since the previous check, the average cost of
+/// transactions that have been submitted during this period is remembered. The alert is never fired before
+/// the first estimation is made.
+pub fn alert_when_account_transactions_remaining_below<C: ChainWithBalances>(
+	mut env: impl Environment<C>,
+	account_id: C::AccountId,
+	min_transactions: u32,
+) {
+	async_std::task::spawn(async move {
+		let mut previous_state = None;
+		let mut transaction_cost = None;
+
+		loop {
+			match read_account_state(&mut env, account_id.clone()).await {
+				Ok(current_state) => {
+					if let Some(cost) = previous_state
+						.and_then(|previous_state| average_transaction_cost(previous_state, current_state))
+					{
+						transaction_cost = Some(cost);
+					}
+					previous_state = Some(current_state);
+
+					if let Some(transaction_cost) = transaction_cost {
+						let transactions_remaining = current_state.balance / transaction_cost;
+						if transactions_remaining < min_transactions as u128 {
+							fire_alert(
+								AlertKind::LowBalance,
+								&format!("{} account {:?}", C::NAME, account_id),
+								format!(
+									"Balance {} is enough for ~{} transactions (estimated transaction cost is {}), \
+									which is below the threshold {}",
+									current_state.balance, transactions_remaining, transaction_cost, min_transactions,
+								),
+							);
+						}
+					}
+				}
+				Err(error) => log::warn!(
+					target: "bridge-guard",
+					"Failed to read {} account {:?} state: {:?}",
+					C::NAME,
+					account_id,
+					error,
+				),
+			}
+
+			env.sleep(conditions_check_delay::<C>()).await;
+		}
+	});
+}
+
+/// Fire alert when the pallet is halted.
+pub fn alert_when_pallet_halted<C: ChainWithBalances>(
+	mut env: impl Environment<C>,
+	pallet_name: String,
+	is_halted_key: StorageKey,
+) {
+	async_std::task::spawn(async move {
+		loop {
+			match env.is_halted(is_halted_key.clone()).await {
+				Ok(true) => fire_alert(
+					AlertKind::BridgeHalted,
+					&format!("{} {}", C::NAME, pallet_name),
+					"Pallet is halted".into(),
+				),
+				Ok(false) => (),
+				Err(error) => log::warn!(
+					target: "bridge-guard",
+					"Failed to read {} {} operating mode: {:?}",
+					C::NAME,
+					pallet_name,
+					error,
+				),
+			}
+
+			env.sleep(conditions_check_delay::<C>()).await;
+		}
+	});
+}
+
+/// Account nonce and free balance, observed at some point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AccountState {
+	/// Index of the next account transaction.
+	nonce: u128,
+	/// Free native balance of the account.
+	balance: u128,
+}
+
+/// Read current nonce and free balance of the account.
+async fn read_account_state<C: ChainWithBalances>(
+	env: &mut impl Environment<C>,
+	account_id: C::AccountId,
+) -> Result<AccountState, String> {
+	let nonce = env.next_account_index(account_id.clone()).await?;
+	let balance = env.free_native_balance(account_id).await?;
+	Ok(AccountState {
+		nonce: nonce.unique_saturated_into(),
+		balance: balance.unique_saturated_into(),
+	})
+}
+
+/// Returns average cost of transactions that have been submitted by the account between two observations.
+///
+/// Returns `None` if no transactions have been submitted, or if the balance hasn't decreased (e.g. because
+/// the account has received some funds).
+fn average_transaction_cost(previous_state: AccountState, current_state: AccountState) -> Option<u128> {
+	let transactions = current_state
+		.nonce
+		.checked_sub(previous_state.nonce)
+		.filter(|transactions| *transactions != 0)?;
+	let spent = previous_state
+		.balance
+		.checked_sub(current_state.balance)
+		.filter(|spent| *spent != 0)?;
+	Some(std::cmp::max(spent / transactions, 1))
+}
+
 /// Delay between conditions check.
 fn conditions_check_delay<C: Chain>() -> Duration {
 	C::AVERAGE_BLOCK_INTERVAL * (10 + rand::random::<u32>() % 10)
@@ -160,6 +323,19 @@ impl<C: ChainWithBalances> Environment<C> for Client<C> {
 			.await
 			.map_err(|e| e.to_string())
 	}
+
+	async fn next_account_index(&mut self, account: C::AccountId) -> Result<C::Index, String> {
+		Client::<C>::next_account_index(self, account)
+			.await
+			.map_err(|e| e.to_string())
+	}
+
+	async fn is_halted(&mut self, is_halted_key: StorageKey) -> Result<bool, String> {
+		Client::<C>::storage_value::<bool>(self, is_halted_key)
+			.await
+			.map(|is_halted| is_halted.unwrap_or(false))
+			.map_err(|e| e.to_string())
+	}
 }
 
 #[cfg(test)]
@@ -184,6 +360,7 @@ mod tests {
 
 	impl Chain for TestChain {
 		const NAME: &'static str = "Test";
+		const SPEC_NAME: &'static str = "test";
 		const AVERAGE_BLOCK_INTERVAL: Duration = Duration::from_millis(1);
 
 		type AccountId = u32;
@@ -218,11 +395,19 @@ mod tests {
 			Ok(self.free_native_balance_rx.next().await.unwrap_or_default())
 		}
 
+		async fn next_account_index(&mut self, _account: u32) -> Result<u32, String> {
+			Ok(0)
+		}
+
+		async fn is_halted(&mut self, _is_halted_key: StorageKey) -> Result<bool, String> {
+			Ok(false)
+		}
+
 		async fn sleep(&mut self, _duration: Duration) {
 			let _ = self.slept_tx.send(()).await;
 		}
 
-		async fn abort(&mut self) {
+		async fn abort(&mut self, _kind: ErrorKind) {
 			let _ = self.aborted_tx.send(()).await;
 			// simulate process abort :)
 			async_std::task::sleep(Duration::from_secs(60)).await;
@@ -370,4 +555,55 @@ mod tests {
 			assert!(aborted_rx.next().now_or_never().is_none());
 		});
 	}
+
+	#[test]
+	fn average_transaction_cost_is_computed_when_transactions_are_submitted() {
+		assert_eq!(
+			average_transaction_cost(
+				AccountState {
+					nonce: 10,
+					balance: 1000
+				},
+				AccountState {
+					nonce: 14,
+					balance: 600
+				},
+			),
+			Some(100),
+		);
+	}
+
+	#[test]
+	fn average_transaction_cost_is_not_computed_without_transactions() {
+		assert_eq!(
+			average_transaction_cost(
+				AccountState {
+					nonce: 10,
+					balance: 1000
+				},
+				AccountState {
+					nonce: 10,
+					balance: 900
+				},
+			),
+			None,
+		);
+	}
+
+	#[test]
+	fn average_transaction_cost_is_not_computed_when_balance_has_increased() {
+		assert_eq!(
+			average_transaction_cost(
+				AccountState {
+					nonce: 10,
+					balance: 1000
+				},
+				AccountState {
+					nonce: 12,
+					balance: 1100
+				},
+			),
+			None,
+		);
+	}
 }
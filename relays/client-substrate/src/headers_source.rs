@@ -61,6 +61,14 @@ impl<C: Chain, P: HeadersSyncPipeline> RelayClient for HeadersSource<C, P> {
 	async fn reconnect(&mut self) -> Result<(), Error> {
 		self.client.reconnect().await
 	}
+
+	async fn resync(&mut self) -> Result<(), Error> {
+		self.client.resync().await
+	}
+
+	async fn failover(&mut self) -> Result<bool, Error> {
+		self.client.failover().await
+	}
 }
 
 #[async_trait]
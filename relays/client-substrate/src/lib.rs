@@ -18,26 +18,44 @@
 
 #![warn(missing_docs)]
 
+mod cache;
 mod chain;
 mod client;
 mod error;
 mod rpc;
 mod sync_header;
+mod tip;
 
 pub mod finality_source;
 pub mod guard;
 pub mod headers_source;
 pub mod metrics;
 
-pub use crate::chain::{BlockWithJustification, Chain, ChainWithBalances, TransactionSignScheme};
-pub use crate::client::{Client, JustificationsSubscription, OpaqueGrandpaAuthoritiesSet};
+pub use crate::chain::{
+	BlockWithJustification, Chain, ChainWithBalances, ChainWithProxy, ChainWithTips, ParsedTransaction,
+	TransactionSignScheme,
+};
+pub use crate::client::{
+	BeefyJustificationsSubscription, Client, JustificationsSubscription, OpaqueBeefyValidatorSet,
+	OpaqueGrandpaAuthoritiesSet, TokenProperties,
+};
 pub use crate::error::{Error, Result};
-pub use crate::sync_header::SyncHeader;
-pub use bp_runtime::{BlockNumberOf, Chain as ChainBase, HashOf, HeaderOf};
+pub use crate::sync_header::{GrandpaMandatoryHeaders, MandatoryHeaderDetector, SyncHeader};
+pub use crate::tip::CongestionState;
+pub use bp_runtime::{BlockNumberOf, Chain as ChainBase, HashOf, HeaderOf, TransactionEra};
+pub use relay_utils::transport::{ProxyParams, TlsParams, TransportParams};
+
+use std::time::Duration;
 
 /// Header id used by the chain.
 pub type HeaderIdOf<C> = relay_utils::HeaderId<HashOf<C>, BlockNumberOf<C>>;
 
+/// Native balance type used by the chain.
+pub type BalanceOf<C> = <C as ChainWithBalances>::NativeBalance;
+
+/// Era of the transaction that is signed for the chain.
+pub type TransactionEraOf<C> = TransactionEra<BlockNumberOf<C>, HashOf<C>>;
+
 /// Substrate-over-websocket connection params.
 #[derive(Debug, Clone)]
 pub struct ConnectionParams {
@@ -47,6 +65,10 @@ pub struct ConnectionParams {
 	pub port: u16,
 	/// Use secure websocket connection.
 	pub secure: bool,
+	/// Hostnames and ports of websocket servers of failover nodes (see `Client::failover`).
+	pub failover_endpoints: Vec<(String, u16)>,
+	/// Transport of the RPC connection.
+	pub transport: TransportParams,
 }
 
 impl Default for ConnectionParams {
@@ -55,6 +77,66 @@ impl Default for ConnectionParams {
 			host: "localhost".into(),
 			port: 9944,
 			secure: false,
+			failover_endpoints: Vec::new(),
+			transport: Default::default(),
 		}
 	}
 }
+
+impl ConnectionParams {
+	/// Returns hostname and port of the node endpoint with given index.
+	///
+	/// Endpoint `0` is the main node endpoint. Other endpoints are failover endpoints.
+	pub fn endpoint(&self, index: usize) -> Option<(&str, u16)> {
+		match index {
+			0 => Some((&self.host, self.port)),
+			_ => self
+				.failover_endpoints
+				.get(index - 1)
+				.map(|(host, port)| (host.as_str(), *port)),
+		}
+	}
+}
+
+/// Returns stall timeout for relay loop that submits transactions with given mortality.
+///
+/// If transaction is mortal, it is either mined or dropped from the pool once its mortality period
+/// ends. So after that period we may safely consider the transaction lost and resubmit it.
+/// If transaction is immortal, the `default_stall_timeout` is returned.
+pub fn transaction_stall_timeout(
+	mortality_period: Option<u32>,
+	average_block_interval: Duration,
+	default_stall_timeout: Duration,
+) -> Duration {
+	// 1 extra block for transaction to reach the pool && 1 for relayer to awake after it is mined
+	mortality_period
+		.map(|mortality_period| average_block_interval.saturating_mul(mortality_period.saturating_add(2)))
+		.unwrap_or(default_stall_timeout)
+}
+
+/// Returns stall timeout for relay loop that submits transactions to two chains.
+///
+/// Bidirectional relay may have two active transactions. Even if one of them has been spoiled, we
+/// can't just restart the loop - the other transaction may still be alive and we'll be submitting
+/// duplicate transaction, which may result in funds loss. So we'll be selecting maximal mortality
+/// for choosing loop stall timeout.
+pub fn bidirectional_transaction_stall_timeout(
+	left_mortality_period: Option<u32>,
+	right_mortality_period: Option<u32>,
+	left_average_block_interval: Duration,
+	right_average_block_interval: Duration,
+	default_stall_timeout: Duration,
+) -> Duration {
+	std::cmp::max(
+		transaction_stall_timeout(
+			left_mortality_period,
+			left_average_block_interval,
+			default_stall_timeout,
+		),
+		transaction_stall_timeout(
+			right_mortality_period,
+			right_average_block_interval,
+			default_stall_timeout,
+		),
+	)
+}
@@ -0,0 +1,81 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::chain::ChainWithBalances;
+use crate::client::Client;
+
+use async_trait::async_trait;
+use relay_utils::metrics::{metric_name, register, Gauge, PrometheusError, Registry, StandaloneMetrics, F64};
+use sp_runtime::traits::UniqueSaturatedInto;
+use std::time::Duration;
+
+/// Free balance update interval (in blocks).
+const UPDATE_INTERVAL_IN_BLOCKS: u32 = 5;
+
+/// Metric that represents free native balance of the account as float gauge.
+///
+/// The balance is exposed in the smallest units of the chain native token.
+#[derive(Debug)]
+pub struct FreeNativeBalanceMetric<C: ChainWithBalances> {
+	client: Client<C>,
+	account_id: C::AccountId,
+	metric: Gauge<F64>,
+}
+
+impl<C: ChainWithBalances> Clone for FreeNativeBalanceMetric<C> {
+	fn clone(&self) -> Self {
+		FreeNativeBalanceMetric {
+			client: self.client.clone(),
+			account_id: self.account_id.clone(),
+			metric: self.metric.clone(),
+		}
+	}
+}
+
+impl<C: ChainWithBalances> FreeNativeBalanceMetric<C> {
+	/// Create new metric instance with given name and help.
+	pub fn new(
+		registry: &Registry,
+		prefix: Option<&str>,
+		client: Client<C>,
+		account_id: C::AccountId,
+		name: String,
+		help: String,
+	) -> Result<Self, PrometheusError> {
+		Ok(FreeNativeBalanceMetric {
+			client,
+			account_id,
+			metric: register(Gauge::new(metric_name(prefix, &name), help)?, registry)?,
+		})
+	}
+}
+
+#[async_trait]
+impl<C: ChainWithBalances> StandaloneMetrics for FreeNativeBalanceMetric<C> {
+	fn update_interval(&self) -> Duration {
+		C::AVERAGE_BLOCK_INTERVAL * UPDATE_INTERVAL_IN_BLOCKS
+	}
+
+	async fn update(&self) {
+		relay_utils::metrics::set_gauge_value(
+			&self.metric,
+			self.client
+				.free_native_balance(self.account_id.clone())
+				.await
+				.map(|balance| Some(UniqueSaturatedInto::<u128>::unique_saturated_into(balance) as f64)),
+		);
+	}
+}
@@ -17,7 +17,9 @@
 //! Contains several Substrate-specific metrics that may be exposed by relay.
 
 pub use float_storage_value::FloatStorageValueMetric;
+pub use free_native_balance::FreeNativeBalanceMetric;
 pub use storage_proof_overhead::StorageProofOverheadMetric;
 
 mod float_storage_value;
+mod free_native_balance;
 mod storage_proof_overhead;
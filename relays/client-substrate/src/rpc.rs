@@ -18,8 +18,13 @@
 
 use crate::chain::Chain;
 
-use sc_rpc_api::{state::ReadProof, system::Health};
+use pallet_mmr_rpc::LeafProof;
+use sc_rpc_api::{
+	state::ReadProof,
+	system::{helpers::Properties, Health},
+};
 use sp_core::{
+	offchain::StorageKind,
 	storage::{StorageData, StorageKey},
 	Bytes,
 };
@@ -29,6 +34,8 @@ jsonrpsee_proc_macros::rpc_client_api! {
 	pub(crate) Substrate<C: Chain> {
 		#[rpc(method = "system_health", positional_params)]
 		fn system_health() -> Health;
+		#[rpc(method = "system_properties", positional_params)]
+		fn system_properties() -> Properties;
 		#[rpc(method = "chain_getHeader", positional_params)]
 		fn chain_get_header(block_hash: Option<C::Hash>) -> C::Header;
 		#[rpc(method = "chain_getFinalizedHead", positional_params)]
@@ -41,13 +48,26 @@ jsonrpsee_proc_macros::rpc_client_api! {
 		fn system_account_next_index(account_id: C::AccountId) -> C::Index;
 		#[rpc(method = "author_submitExtrinsic", positional_params)]
 		fn author_submit_extrinsic(extrinsic: Bytes) -> C::Hash;
+		#[rpc(method = "author_pendingExtrinsics", positional_params)]
+		fn author_pending_extrinsics() -> Vec<Bytes>;
 		#[rpc(method = "state_call", positional_params)]
 		fn state_call(method: String, data: Bytes, at_block: Option<C::Hash>) -> Bytes;
 		#[rpc(method = "state_getStorage", positional_params)]
-		fn state_get_storage(key: StorageKey) -> Option<StorageData>;
+		fn state_get_storage(key: StorageKey, at_block: Option<C::Hash>) -> Option<StorageData>;
+		#[rpc(method = "state_getKeysPaged", positional_params)]
+		fn state_get_keys_paged(
+			prefix: StorageKey,
+			count: u32,
+			start_key: Option<StorageKey>,
+			at_block: Option<C::Hash>,
+		) -> Vec<StorageKey>;
 		#[rpc(method = "state_getReadProof", positional_params)]
 		fn state_prove_storage(keys: Vec<StorageKey>, hash: Option<C::Hash>) -> ReadProof<C::Hash>;
 		#[rpc(method = "state_getRuntimeVersion", positional_params)]
 		fn state_runtime_version() -> RuntimeVersion;
+		#[rpc(method = "offchain_localStorageGet", positional_params)]
+		fn offchain_local_storage_get(kind: StorageKind, key: Bytes) -> Option<Bytes>;
+		#[rpc(method = "mmr_generateProof", positional_params)]
+		fn mmr_generate_proof(leaf_index: u64, at_block: Option<C::Hash>) -> LeafProof<C::Hash>;
 	}
 }
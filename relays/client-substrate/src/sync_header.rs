@@ -20,20 +20,40 @@ use headers_relay::sync_types::SourceHeader;
 use num_traits::{CheckedSub, One};
 use relay_utils::HeaderId;
 use sp_runtime::traits::Header as HeaderT;
+use std::marker::PhantomData;
+
+/// Detects headers that must be relayed by the finality relay.
+///
+/// Chains that are signalling authorities set changes using non-standard digest items
+/// may provide their own implementation.
+pub trait MandatoryHeaderDetector<Header> {
+	/// Returns true if the header is mandatory and can't be skipped by the finality relay.
+	fn is_mandatory(header: &Header) -> bool;
+}
+
+/// Mandatory headers detector that looks for GRANDPA authorities set change digests.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrandpaMandatoryHeaders;
+
+impl<Header: HeaderT> MandatoryHeaderDetector<Header> for GrandpaMandatoryHeaders {
+	fn is_mandatory(header: &Header) -> bool {
+		find_grandpa_authorities_scheduled_change(header).is_some()
+	}
+}
 
 /// Generic wrapper for `sp_runtime::traits::Header` based headers, that
 /// implements `headers_relay::sync_types::SourceHeader` and may be used in headers sync directly.
 #[derive(Clone, Debug, PartialEq)]
-pub struct SyncHeader<Header>(Header);
+pub struct SyncHeader<Header, Detector = GrandpaMandatoryHeaders>(Header, PhantomData<Detector>);
 
-impl<Header> SyncHeader<Header> {
+impl<Header, Detector> SyncHeader<Header, Detector> {
 	/// Extracts wrapped header from self.
 	pub fn into_inner(self) -> Header {
 		self.0
 	}
 }
 
-impl<Header> std::ops::Deref for SyncHeader<Header> {
+impl<Header, Detector> std::ops::Deref for SyncHeader<Header, Detector> {
 	type Target = Header;
 
 	fn deref(&self) -> &Self::Target {
@@ -41,13 +61,13 @@ impl<Header> std::ops::Deref for SyncHeader<Header> {
 	}
 }
 
-impl<Header> From<Header> for SyncHeader<Header> {
+impl<Header, Detector> From<Header> for SyncHeader<Header, Detector> {
 	fn from(header: Header) -> Self {
-		Self(header)
+		Self(header, PhantomData)
 	}
 }
 
-impl<Header: HeaderT> SourceHeader<Header::Hash, Header::Number> for SyncHeader<Header> {
+impl<Header: HeaderT, Detector> SourceHeader<Header::Hash, Header::Number> for SyncHeader<Header, Detector> {
 	fn id(&self) -> HeaderId<Header::Hash, Header::Number> {
 		relay_utils::HeaderId(*self.0.number(), self.hash())
 	}
@@ -62,12 +82,80 @@ impl<Header: HeaderT> SourceHeader<Header::Hash, Header::Number> for SyncHeader<
 	}
 }
 
-impl<Header: HeaderT> FinalitySourceHeader<Header::Number> for SyncHeader<Header> {
+impl<Header: HeaderT, Detector: MandatoryHeaderDetector<Header>> FinalitySourceHeader<Header::Number>
+	for SyncHeader<Header, Detector>
+{
 	fn number(&self) -> Header::Number {
 		*self.0.number()
 	}
 
 	fn is_mandatory(&self) -> bool {
-		find_grandpa_authorities_scheduled_change(&self.0).is_some()
+		Detector::is_mandatory(&self.0)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use sp_finality_grandpa::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+	use sp_runtime::{
+		generic::{Digest, DigestItem},
+		traits::Keccak256,
+	};
+
+	type KeccakHeader = sp_runtime::generic::Header<u64, Keccak256>;
+
+	const CUSTOM_ENGINE_ID: sp_runtime::ConsensusEngineId = *b"cstm";
+
+	#[derive(Clone, Debug, PartialEq)]
+	struct CustomMandatoryHeaders;
+
+	impl MandatoryHeaderDetector<KeccakHeader> for CustomMandatoryHeaders {
+		fn is_mandatory(header: &KeccakHeader) -> bool {
+			header
+				.digest()
+				.logs()
+				.iter()
+				.any(|item| matches!(item, DigestItem::Consensus(id, _) if *id == CUSTOM_ENGINE_ID))
+		}
+	}
+
+	fn header_with_digest(number: u64, logs: Vec<DigestItem<<KeccakHeader as HeaderT>::Hash>>) -> KeccakHeader {
+		KeccakHeader::new(
+			number,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Digest { logs },
+		)
+	}
+
+	#[test]
+	fn grandpa_mandatory_headers_are_detected_for_keccak_chain() {
+		let change = ConsensusLog::<u64>::ScheduledChange(ScheduledChange {
+			next_authorities: vec![],
+			delay: 0,
+		});
+		let mandatory: SyncHeader<KeccakHeader> = header_with_digest(
+			u64::MAX,
+			vec![DigestItem::Consensus(GRANDPA_ENGINE_ID, change.encode())],
+		)
+		.into();
+		let regular: SyncHeader<KeccakHeader> = header_with_digest(u64::MAX, vec![]).into();
+
+		assert!(mandatory.is_mandatory());
+		assert!(!regular.is_mandatory());
+		assert_eq!(FinalitySourceHeader::number(&mandatory), u64::MAX);
+	}
+
+	#[test]
+	fn custom_mandatory_headers_detector_is_used() {
+		let mandatory: SyncHeader<KeccakHeader, CustomMandatoryHeaders> =
+			header_with_digest(1, vec![DigestItem::Consensus(CUSTOM_ENGINE_ID, vec![])]).into();
+		let regular: SyncHeader<KeccakHeader, CustomMandatoryHeaders> = header_with_digest(1, vec![]).into();
+
+		assert!(mandatory.is_mandatory());
+		assert!(!regular.is_mandatory());
 	}
 }
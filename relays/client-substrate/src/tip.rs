@@ -0,0 +1,112 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Automatic tip adjustment of relay transactions.
+//!
+//! When the chain is congested, relay transactions are competing for the block space with other
+//! transactions, including transactions of other relayers that are serving the same bridge. The
+//! tip increases priority of the transaction in the pool, so it is more likely to be included
+//! into the next block. The tip is only attached when recent blocks are almost full, so we're
+//! not overpaying when there's enough space for everyone.
+
+use sp_core::storage::StorageKey;
+use sp_runtime::{traits::AtLeast32BitUnsigned, Perbill};
+
+/// Number of recent blocks that are inspected to compute average block fullness.
+pub const FULLNESS_BLOCKS: u32 = 4;
+/// Average fullness of recent blocks, starting from which the chain is considered congested.
+pub const CONGESTION_THRESHOLD: Perbill = Perbill::from_percent(75);
+/// Number of competing transactions in the pool, starting from which the maximal tip is attached.
+pub const MAX_TIP_COMPETITORS: u32 = 3;
+
+/// Congestion state of the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CongestionState {
+	/// Average fullness (by normal dispatch class weight) of recent blocks.
+	pub block_fullness: Perbill,
+	/// Number of competing bridge transactions in the transaction pool.
+	pub competing_transactions: u32,
+}
+
+impl CongestionState {
+	/// Returns true if the chain is considered congested.
+	pub fn is_congested(&self) -> bool {
+		self.block_fullness >= CONGESTION_THRESHOLD
+	}
+
+	/// Select tip for the relay transaction.
+	///
+	/// No tip is attached if the chain isn't congested. Otherwise, the tip grows linearly with
+	/// the number of competing transactions, but never exceeds `max_tip`.
+	pub fn select_tip<Balance: AtLeast32BitUnsigned + Copy>(&self, max_tip: Balance) -> Balance {
+		if !self.is_congested() {
+			return Balance::zero();
+		}
+
+		let competitors = std::cmp::min(self.competing_transactions, MAX_TIP_COMPETITORS);
+		let tip_steps = Balance::from(competitors + 1);
+		let max_tip_steps = Balance::from(MAX_TIP_COMPETITORS + 1);
+		(max_tip / max_tip_steps).saturating_mul(tip_steps)
+	}
+}
+
+/// Storage key of the `frame_system::BlockWeight` value.
+pub fn block_weight_storage_key() -> StorageKey {
+	let mut key = sp_core::twox_128(b"System").to_vec();
+	key.extend_from_slice(&sp_core::twox_128(b"BlockWeight"));
+	StorageKey(key)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn state(block_fullness: u32, competing_transactions: u32) -> CongestionState {
+		CongestionState {
+			block_fullness: Perbill::from_percent(block_fullness),
+			competing_transactions,
+		}
+	}
+
+	#[test]
+	fn no_tip_is_attached_when_blocks_are_not_full() {
+		assert_eq!(state(0, 0).select_tip(1_000u64), 0);
+		assert_eq!(state(50, 10).select_tip(1_000u64), 0);
+		assert_eq!(state(74, 10).select_tip(1_000u64), 0);
+	}
+
+	#[test]
+	fn tip_grows_with_number_of_competing_transactions() {
+		assert_eq!(state(75, 0).select_tip(1_000u64), 250);
+		assert_eq!(state(90, 1).select_tip(1_000u64), 500);
+		assert_eq!(state(100, 2).select_tip(1_000u64), 750);
+		assert_eq!(state(100, 3).select_tip(1_000u64), 1_000);
+	}
+
+	#[test]
+	fn tip_never_exceeds_maximal_tip() {
+		assert_eq!(state(100, 100).select_tip(1_000u64), 1_000);
+		assert_eq!(state(100, u32::MAX).select_tip(1_000u64), 1_000);
+	}
+
+	#[test]
+	fn block_weight_storage_key_is_correct() {
+		assert_eq!(
+			block_weight_storage_key().0,
+			hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef734abf5cb34d6244378cddbf18e849d96").to_vec(),
+		);
+	}
+}
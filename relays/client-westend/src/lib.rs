@@ -17,7 +17,9 @@
 //! Types used to connect to the Westend chain.
 
 use codec::Encode;
-use relay_substrate_client::{Chain, ChainBase, ChainWithBalances, TransactionSignScheme};
+use relay_substrate_client::{
+	Chain, ChainBase, ChainWithBalances, ParsedTransaction, TransactionEraOf, TransactionSignScheme,
+};
 use sp_core::{storage::StorageKey, Pair};
 use sp_runtime::{generic::SignedPayload, traits::IdentifyAccount};
 use std::time::Duration;
@@ -41,6 +43,7 @@ impl ChainBase for Westend {
 
 impl Chain for Westend {
 	const NAME: &'static str = "Westend";
+	const SPEC_NAME: &'static str = "westend";
 	const AVERAGE_BLOCK_INTERVAL: Duration = Duration::from_secs(6);
 
 	type AccountId = bp_westend::AccountId;
@@ -65,18 +68,14 @@ impl TransactionSignScheme for Westend {
 	fn sign_transaction(
 		genesis_hash: <Self::Chain as ChainBase>::Hash,
 		signer: &Self::AccountKeyPair,
+		era: TransactionEraOf<Self::Chain>,
 		signer_nonce: <Self::Chain as Chain>::Index,
+		tip: <Self::Chain as ChainWithBalances>::NativeBalance,
 		call: <Self::Chain as Chain>::Call,
 	) -> Self::SignedTransaction {
 		let raw_payload = SignedPayload::new(
 			call,
-			bp_westend::SignedExtensions::new(
-				bp_westend::VERSION,
-				sp_runtime::generic::Era::Immortal,
-				genesis_hash,
-				signer_nonce,
-				0,
-			),
+			bp_westend::SignedExtensions::new(bp_westend::VERSION, era, genesis_hash, signer_nonce, tip),
 		)
 		.expect("SignedExtension never fails.");
 
@@ -91,6 +90,11 @@ impl TransactionSignScheme for Westend {
 			extra,
 		)
 	}
+
+	fn parse_transaction(_encoded_transaction: &[u8]) -> Option<ParsedTransaction<Self::Chain>> {
+		// signed extensions of Polkadot-like chains can't be decoded
+		None
+	}
 }
 
 /// Westend signing params.
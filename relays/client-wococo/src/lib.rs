@@ -17,7 +17,13 @@
 //! Types used to connect to the Wococo-Substrate chain.
 
 use codec::Encode;
-use relay_substrate_client::{Chain, ChainBase, ChainWithBalances, TransactionSignScheme};
+use frame_support::{
+	traits::Get,
+	weights::{DispatchClass, Weight},
+};
+use relay_substrate_client::{
+	Chain, ChainBase, ChainWithBalances, ChainWithTips, ParsedTransaction, TransactionEraOf, TransactionSignScheme,
+};
 use sp_core::{storage::StorageKey, Pair};
 use sp_runtime::{generic::SignedPayload, traits::IdentifyAccount};
 use std::time::Duration;
@@ -41,6 +47,7 @@ impl ChainBase for Wococo {
 
 impl Chain for Wococo {
 	const NAME: &'static str = "Wococo";
+	const SPEC_NAME: &'static str = "rococo";
 	const AVERAGE_BLOCK_INTERVAL: Duration = Duration::from_secs(6);
 
 	type AccountId = bp_wococo::AccountId;
@@ -57,6 +64,22 @@ impl ChainWithBalances for Wococo {
 	}
 }
 
+impl ChainWithTips for Wococo {
+	fn max_normal_block_weight() -> Weight {
+		let block_weights = bp_wococo::BlockWeights::get();
+		block_weights
+			.get(DispatchClass::Normal)
+			.max_total
+			.unwrap_or(block_weights.max_block)
+	}
+
+	fn bridge_transaction_signer(_encoded_transaction: &[u8]) -> Option<Self::AccountId> {
+		// signed extensions of Polkadot-like chains are never decoded, so we can't recognize
+		// transactions of other relayers here
+		None
+	}
+}
+
 impl TransactionSignScheme for Wococo {
 	type Chain = Wococo;
 	type AccountKeyPair = sp_core::sr25519::Pair;
@@ -65,18 +88,14 @@ impl TransactionSignScheme for Wococo {
 	fn sign_transaction(
 		genesis_hash: <Self::Chain as ChainBase>::Hash,
 		signer: &Self::AccountKeyPair,
+		era: TransactionEraOf<Self::Chain>,
 		signer_nonce: <Self::Chain as Chain>::Index,
+		tip: <Self::Chain as ChainWithBalances>::NativeBalance,
 		call: <Self::Chain as Chain>::Call,
 	) -> Self::SignedTransaction {
 		let raw_payload = SignedPayload::new(
 			call,
-			bp_wococo::SignedExtensions::new(
-				bp_wococo::VERSION,
-				sp_runtime::generic::Era::Immortal,
-				genesis_hash,
-				signer_nonce,
-				0,
-			),
+			bp_wococo::SignedExtensions::new(bp_wococo::VERSION, era, genesis_hash, signer_nonce, tip),
 		)
 		.expect("SignedExtension never fails.");
 
@@ -91,6 +110,11 @@ impl TransactionSignScheme for Wococo {
 			extra,
 		)
 	}
+
+	fn parse_transaction(_encoded_transaction: &[u8]) -> Option<ParsedTransaction<Self::Chain>> {
+		// signed extensions of Polkadot-like chains can't be decoded
+		None
+	}
 }
 
 /// Wococo signing params.
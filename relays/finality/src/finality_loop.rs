@@ -28,6 +28,7 @@ use headers_relay::sync_loop_metrics::SyncLoopMetrics;
 use num_traits::{One, Saturating};
 use relay_utils::{
 	metrics::{GlobalMetrics, MetricsParams},
+	recovery::RecoveryPolicyParams,
 	relay_loop::Client as RelayClient,
 	retry_backoff, FailedClient, MaybeConnectionError,
 };
@@ -60,6 +61,36 @@ pub struct FinalitySyncParams {
 	pub recent_finality_proofs_limit: usize,
 	/// Timeout before we treat our transactions as lost and restart the whole sync process.
 	pub stall_timeout: Duration,
+	/// Maximal number of concurrent requests for headers (and their finality proofs) to the source node.
+	///
+	/// When the target node is far behind the source node, all missing headers are read from the
+	/// source node. Reading them concurrently hides the round-trip latency, which otherwise dominates
+	/// the catch-up time.
+	pub max_concurrent_headers_requests: usize,
+	/// If `true`, only mandatory headers (headers that are changing GRANDPA authorities set) are
+	/// submitted to the target node.
+	///
+	/// This minimizes target chain costs when bridge applications only need headers occasionally.
+	/// Other headers are then expected to be relayed on demand (e.g. by the separate on-demand
+	/// relay that is started by the messages relay).
+	pub only_mandatory_headers: bool,
+	/// Maximal number of source headers that are scanned at every loop iteration. If `None`, all
+	/// headers up to the best finalized source header are scanned.
+	///
+	/// Limiting this number decreases the load on the source node while the target node is far
+	/// behind. But the value should be large enough to include at least one header with persistent
+	/// finality proof (e.g. session length), or the sync may never progress.
+	pub max_headers_to_scan: Option<u32>,
+	/// Maximal number of finality proofs that are submitted at every loop iteration.
+	///
+	/// Normally we're waiting until the submitted transaction is mined before submitting the next
+	/// one. Increasing this number allows to submit several (e.g. mandatory) headers at once, which
+	/// speeds up the catch-up at the cost of higher target chain load.
+	pub max_headers_to_submit: u32,
+	/// Recovery policy of the source client.
+	pub source_recovery_policy: RecoveryPolicyParams,
+	/// Recovery policy of the target client.
+	pub target_recovery_policy: RecoveryPolicyParams,
 }
 
 /// Source client used in finality synchronization loop.
@@ -108,6 +139,7 @@ pub async fn run<P: FinalitySyncPipeline>(
 	let exit_signal = exit_signal.shared();
 	relay_utils::relay_loop(source_client, target_client)
 		.spawn_loop_task(!sync_params.is_on_demand_task)
+		.recovery_policies(&sync_params.source_recovery_policy, &sync_params.target_recovery_policy)
 		.with_metrics(Some(metrics_prefix::<P>()), metrics_params)
 		.loop_metric(|registry, prefix| SyncLoopMetrics::new(registry, prefix))?
 		.standalone_metric(|registry, prefix| GlobalMetrics::new(registry, prefix))?
@@ -204,6 +236,8 @@ struct FinalityLoopState<'a, P: FinalitySyncPipeline, FinalityProofsStream> {
 	recent_finality_proofs: &'a mut FinalityProofs<P>,
 	/// Last transaction that we have submitted to the target node.
 	last_transaction: Option<Transaction<P::Number>>,
+	/// Best source block number at the target node and the time when it has been updated.
+	target_progress: &'a mut Option<(Instant, P::Number)>,
 }
 
 async fn run_until_connection_lost<P: FinalitySyncPipeline>(
@@ -238,6 +272,7 @@ async fn run_until_connection_lost<P: FinalitySyncPipeline>(
 	let mut progress = (Instant::now(), None);
 	let mut retry_backoff = retry_backoff();
 	let mut last_transaction = None;
+	let mut target_progress = None;
 
 	loop {
 		// run loop iteration
@@ -249,6 +284,7 @@ async fn run_until_connection_lost<P: FinalitySyncPipeline>(
 				finality_proofs_stream: &mut finality_proofs_stream,
 				recent_finality_proofs: &mut recent_finality_proofs,
 				last_transaction: last_transaction.clone(),
+				target_progress: &mut target_progress,
 			},
 			&sync_params,
 			&metrics_sync,
@@ -260,6 +296,7 @@ async fn run_until_connection_lost<P: FinalitySyncPipeline>(
 			Ok(updated_last_transaction) => {
 				last_transaction = updated_last_transaction;
 				retry_backoff.reset();
+				relay_utils::watchdog::loop_progress();
 				sync_params.tick
 			}
 			Err(error) => {
@@ -311,6 +348,9 @@ where
 		metrics_sync.update_best_block_at_target(best_number_at_target);
 	}
 	*state.progress = print_sync_progress::<P>(*state.progress, best_number_at_source, best_number_at_target);
+	if !sync_params.is_on_demand_task {
+		check_sync_progress::<P>(state.target_progress, best_number_at_source, best_number_at_target);
+	}
 
 	// if we have already submitted header, then we just need to wait for it
 	// if we're waiting too much, then we believe our transaction has been lost and restart sync
@@ -324,6 +364,7 @@ where
 				P::SOURCE_NAME,
 				P::TARGET_NAME,
 			);
+			relay_utils::alerts::note_transaction_result(false);
 
 			return Err(Error::Stalled);
 		} else {
@@ -331,40 +372,62 @@ where
 		}
 	}
 
-	// submit new header if we have something new
-	match select_header_to_submit(
-		source_client,
-		target_client,
-		state.finality_proofs_stream,
-		state.recent_finality_proofs,
-		best_number_at_source,
-		best_number_at_target,
-		sync_params,
-	)
-	.await?
-	{
-		Some((header, justification)) => {
-			let new_transaction = Transaction {
-				time: Instant::now(),
-				submitted_header_number: header.number(),
-			};
-
-			log::debug!(
-				target: "bridge",
-				"Going to submit finality proof of {} header #{:?} to {}",
-				P::SOURCE_NAME,
-				new_transaction.submitted_header_number,
-				P::TARGET_NAME,
-			);
+	// submit new headers if we have something new
+	let mut last_transaction = None;
+	let mut best_submitted_number = best_number_at_target;
+	for _ in 0..std::cmp::max(sync_params.max_headers_to_submit, 1) {
+		let (header, justification) = match select_header_to_submit(
+			source_client,
+			target_client,
+			state.finality_proofs_stream,
+			state.recent_finality_proofs,
+			best_number_at_source,
+			best_submitted_number,
+			sync_params,
+		)
+		.await?
+		{
+			Some(header_and_justification) => header_and_justification,
+			None => break,
+		};
+
+		let new_transaction = Transaction {
+			time: Instant::now(),
+			submitted_header_number: header.number(),
+		};
 
-			target_client
-				.submit_finality_proof(header, justification)
-				.await
-				.map_err(Error::Target)?;
-			Ok(Some(new_transaction))
+		log::debug!(
+			target: "bridge",
+			"Going to submit finality proof of {} header #{:?} to {}",
+			P::SOURCE_NAME,
+			new_transaction.submitted_header_number,
+			P::TARGET_NAME,
+		);
+
+		let submit_result = target_client.submit_finality_proof(header, justification).await;
+		relay_utils::alerts::note_transaction_result(submit_result.is_ok());
+		match submit_result {
+			Ok(()) => (),
+			// we need to wait for already submitted transactions anyway
+			Err(error) if last_transaction.is_some() => {
+				log::error!(
+					target: "bridge",
+					"Failed to submit finality proof of {} header #{:?} to {}: {:?}",
+					P::SOURCE_NAME,
+					new_transaction.submitted_header_number,
+					P::TARGET_NAME,
+					error,
+				);
+				break;
+			}
+			Err(error) => return Err(Error::Target(error)),
 		}
-		None => Ok(None),
+
+		best_submitted_number = new_transaction.submitted_header_number;
+		last_transaction = Some(new_transaction);
 	}
+
+	Ok(last_transaction)
 }
 
 async fn select_header_to_submit<P, SC, TC>(
@@ -381,6 +444,15 @@ where
 	SC: SourceClient<P>,
 	TC: TargetClient<P>,
 {
+	// limit number of headers that we're going to scan
+	let best_number_at_source = match sync_params.max_headers_to_scan {
+		Some(max_headers_to_scan) => std::cmp::min(
+			best_number_at_source,
+			best_number_at_target.saturating_add(max_headers_to_scan.into()),
+		),
+		None => best_number_at_source,
+	};
+
 	// to see that the loop is progressing
 	log::trace!(
 		target: "bridge",
@@ -396,6 +468,7 @@ where
 		target_client,
 		best_number_at_source,
 		best_number_at_target,
+		sync_params.max_concurrent_headers_requests,
 	)
 	.await?;
 	let (mut unjustified_headers, mut selected_finality_proof) = match selected_finality_proof {
@@ -416,6 +489,11 @@ where
 		selected_finality_proof,
 	);
 
+	// if we're only relaying mandatory headers, then regular headers are never submitted
+	if sync_params.only_mandatory_headers {
+		selected_finality_proof = None;
+	}
+
 	// remove obsolete 'recent' finality proofs + keep its size under certain limit
 	let oldest_finality_proof_to_keep = selected_finality_proof
 		.as_ref()
@@ -447,20 +525,29 @@ pub(crate) enum SelectedFinalityProof<Header, FinalityProof> {
 /// Otherwise, `SelectedFinalityProof::None` is returned.
 ///
 /// Unless we have found mandatory header, all missing headers are collected and returned.
+///
+/// Up to `max_concurrent_requests` headers are requested from the source node at once. Headers are
+/// still processed in order, so the only cost of the concurrency is that some headers after the
+/// mandatory header may be read in vain.
 pub(crate) async fn read_missing_headers<P: FinalitySyncPipeline, SC: SourceClient<P>, TC: TargetClient<P>>(
 	source_client: &SC,
 	_target_client: &TC,
 	best_number_at_source: P::Number,
 	best_number_at_target: P::Number,
+	max_concurrent_requests: usize,
 ) -> Result<SelectedFinalityProof<P::Header, P::FinalityProof>, Error<P, SC::Error, TC::Error>> {
 	let mut unjustified_headers = Vec::new();
 	let mut selected_finality_proof = None;
-	let mut header_number = best_number_at_target + One::one();
-	while header_number <= best_number_at_source {
-		let (header, finality_proof) = source_client
-			.header_and_finality_proof(header_number)
-			.await
-			.map_err(Error::Source)?;
+	let missing_headers_numbers = std::iter::successors(Some(best_number_at_target + One::one()), |number| {
+		Some(*number + One::one())
+	})
+	.take_while(|number| *number <= best_number_at_source);
+	let mut missing_headers = futures::stream::iter(missing_headers_numbers)
+		.map(|number| source_client.header_and_finality_proof(number))
+		.buffered(std::cmp::max(max_concurrent_requests, 1));
+	while let Some(header_and_finality_proof) = missing_headers.next().await {
+		let (header, finality_proof) = header_and_finality_proof.map_err(Error::Source)?;
+		let header_number = header.number();
 		let is_mandatory = header.is_mandatory();
 
 		match (is_mandatory, finality_proof) {
@@ -478,8 +565,6 @@ pub(crate) async fn read_missing_headers<P: FinalitySyncPipeline, SC: SourceClie
 				unjustified_headers.push(header);
 			}
 		}
-
-		header_number = header_number + One::one();
 	}
 
 	Ok(match selected_finality_proof {
@@ -573,6 +658,47 @@ pub(crate) fn prune_recent_finality_proofs<P: FinalitySyncPipeline>(
 	*recent_finality_proofs = recent_finality_proofs.split_off(split_index);
 }
 
+/// Fire alert if the target node hasn't received any new headers for too long, while there are
+/// headers to sync.
+fn check_sync_progress<P: FinalitySyncPipeline>(
+	target_progress: &mut Option<(Instant, P::Number)>,
+	best_number_at_source: P::Number,
+	best_number_at_target: P::Number,
+) {
+	let now = Instant::now();
+	let last_progress_at = match *target_progress {
+		Some((last_progress_at, prev_best_number_at_target)) if prev_best_number_at_target == best_number_at_target => {
+			last_progress_at
+		}
+		_ => {
+			*target_progress = Some((now, best_number_at_target));
+			return;
+		}
+	};
+
+	let stall_timeout = match relay_utils::alerts::finality_stall_timeout() {
+		Some(stall_timeout) => stall_timeout,
+		None => return,
+	};
+	let stalled_for = now.saturating_duration_since(last_progress_at);
+	if best_number_at_source > best_number_at_target && stalled_for > stall_timeout {
+		relay_utils::alerts::fire_alert(
+			relay_utils::alerts::AlertKind::FinalityStalled,
+			&metrics_prefix::<P>(),
+			format!(
+				"{} headers have not been synced to {} for {} minutes. Best header at {}: {:?}, at {}: {:?}",
+				P::SOURCE_NAME,
+				P::TARGET_NAME,
+				stalled_for.as_secs() / 60,
+				P::SOURCE_NAME,
+				best_number_at_source,
+				P::TARGET_NAME,
+				best_number_at_target,
+			),
+		);
+	}
+}
+
 fn print_sync_progress<P: FinalitySyncPipeline>(
 	progress_context: (Instant, Option<P::Number>),
 	best_number_at_source: P::Number,
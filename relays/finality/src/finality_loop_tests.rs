@@ -19,8 +19,9 @@
 #![cfg(test)]
 
 use crate::finality_loop::{
-	prune_recent_finality_proofs, read_finality_proofs_from_stream, run, select_better_recent_finality_proof,
-	FinalityProofs, FinalitySyncParams, SourceClient, TargetClient,
+	prune_recent_finality_proofs, read_finality_proofs_from_stream, read_missing_headers, run,
+	select_better_recent_finality_proof, FinalityProofs, FinalitySyncParams, SelectedFinalityProof, SourceClient,
+	TargetClient,
 };
 use crate::{FinalityProof, FinalitySyncPipeline, SourceHeader};
 
@@ -165,7 +166,12 @@ impl TargetClient<TestFinalitySyncPipeline> for TestTargetClient {
 	}
 }
 
-fn run_sync_loop(state_function: impl Fn(&mut ClientsData) -> bool + Send + Sync + 'static) -> ClientsData {
+fn run_sync_loop(
+	only_mandatory_headers: bool,
+	max_headers_to_scan: Option<u32>,
+	max_headers_to_submit: u32,
+	state_function: impl Fn(&mut ClientsData) -> bool + Send + Sync + 'static,
+) -> ClientsData {
 	let (exit_sender, exit_receiver) = futures::channel::mpsc::unbounded();
 	let internal_state_function: Arc<dyn Fn(&mut ClientsData) + Send + Sync> = Arc::new(move |data| {
 		if state_function(data) {
@@ -201,6 +207,12 @@ fn run_sync_loop(state_function: impl Fn(&mut ClientsData) -> bool + Send + Sync
 		tick: Duration::from_secs(0),
 		recent_finality_proofs_limit: 1024,
 		stall_timeout: Duration::from_secs(1),
+		max_concurrent_headers_requests: 4,
+		only_mandatory_headers,
+		max_headers_to_scan,
+		max_headers_to_submit,
+		source_recovery_policy: Default::default(),
+		target_recovery_policy: Default::default(),
 	};
 
 	let _ = async_std::task::block_on(run(
@@ -217,7 +229,7 @@ fn run_sync_loop(state_function: impl Fn(&mut ClientsData) -> bool + Send + Sync
 
 #[test]
 fn finality_sync_loop_works() {
-	let client_data = run_sync_loop(|data| {
+	let client_data = run_sync_loop(false, None, 1, |data| {
 		// header#7 has persistent finality proof, but it isn't mandatory => it isn't submitted, because
 		// header#8 has persistent finality proof && it is mandatory => it is submitted
 		// header#9 has persistent finality proof, but it isn't mandatory => it is submitted, because
@@ -260,6 +272,117 @@ fn finality_sync_loop_works() {
 	);
 }
 
+#[test]
+fn finality_sync_loop_only_submits_mandatory_headers_if_asked() {
+	let client_data = run_sync_loop(true, None, 1, |data| {
+		// header#8 has persistent finality proof && it is mandatory => it is submitted
+		// header#9 has persistent finality proof, but it isn't mandatory => it isn't submitted
+		//
+		// once this ^^^ is done, we generate more blocks, including mandatory header#12
+		if data.target_best_block_number == 8 {
+			data.source_best_block_number = 13;
+			data.source_headers
+				.insert(11, (TestSourceHeader(false, 11), Some(TestFinalityProof(11))));
+			data.source_headers
+				.insert(12, (TestSourceHeader(true, 12), Some(TestFinalityProof(12))));
+			data.source_headers.insert(13, (TestSourceHeader(false, 13), None));
+		}
+
+		data.target_best_block_number == 12
+	});
+
+	assert_eq!(
+		client_data.target_headers,
+		vec![
+			// before adding 11..13: finality proof for mandatory header#8
+			(TestSourceHeader(true, 8), TestFinalityProof(8)),
+			// after adding 11..13: finality proof for mandatory header#12
+			(TestSourceHeader(true, 12), TestFinalityProof(12)),
+		],
+	);
+}
+
+#[test]
+fn finality_sync_loop_respects_maximal_number_of_scanned_headers() {
+	let client_data = run_sync_loop(false, Some(2), 1, |data| data.target_best_block_number == 9);
+
+	assert_eq!(
+		client_data.target_headers,
+		vec![
+			// headers 6..7 are scanned: persistent finality proof for non-mandatory header#7
+			(TestSourceHeader(false, 7), TestFinalityProof(7)),
+			// headers 8..9 are scanned: finality proof for mandatory header#8
+			(TestSourceHeader(true, 8), TestFinalityProof(8)),
+			// headers 9..10 are scanned: persistent finality proof for non-mandatory header#9
+			(TestSourceHeader(false, 9), TestFinalityProof(9)),
+		],
+	);
+}
+
+#[test]
+fn finality_sync_loop_submits_several_headers_per_iteration_if_allowed() {
+	let client_data = run_sync_loop(false, None, 2, |data| data.target_best_block_number == 9);
+
+	assert_eq!(
+		client_data.target_headers,
+		vec![
+			// finality proof for mandatory header#8
+			(TestSourceHeader(true, 8), TestFinalityProof(8)),
+			// persistent finality proof for non-mandatory header#9, submitted right after header#8
+			(TestSourceHeader(false, 9), TestFinalityProof(9)),
+		],
+	);
+}
+
+#[test]
+fn read_missing_headers_processes_concurrently_read_headers_in_order() {
+	let clients_data = Arc::new(Mutex::new(ClientsData {
+		source_best_block_number: 10,
+		source_headers: (1..=10)
+			.map(|number| {
+				let finality_proof = if number == 4 || number == 7 {
+					Some(TestFinalityProof(number))
+				} else {
+					None
+				};
+				(number, (TestSourceHeader(false, number), finality_proof))
+			})
+			.collect(),
+		..Default::default()
+	}));
+	let source_client = TestSourceClient {
+		on_method_call: Arc::new(|_| {}),
+		data: clients_data.clone(),
+	};
+	let target_client = TestTargetClient {
+		on_method_call: Arc::new(|_| {}),
+		data: clients_data,
+	};
+
+	let selected_finality_proof = async_std::task::block_on(read_missing_headers::<TestFinalitySyncPipeline, _, _>(
+		&source_client,
+		&target_client,
+		10,
+		0,
+		4,
+	));
+	match selected_finality_proof {
+		Ok(SelectedFinalityProof::Regular(unjustified_headers, header, finality_proof)) => {
+			assert_eq!(
+				unjustified_headers,
+				vec![
+					TestSourceHeader(false, 8),
+					TestSourceHeader(false, 9),
+					TestSourceHeader(false, 10)
+				],
+			);
+			assert_eq!(header, TestSourceHeader(false, 7));
+			assert_eq!(finality_proof, TestFinalityProof(7));
+		}
+		_ => panic!("Unexpected result of read_missing_headers"),
+	}
+}
+
 #[test]
 fn select_better_recent_finality_proof_works() {
 	// if there are no unjustified headers, nothing is changed
@@ -20,7 +20,13 @@ use crate::sync::HeadersSync;
 use crate::sync_types::{HeaderStatus, HeadersSyncPipeline};
 
 use num_traits::Zero;
-use relay_utils::metrics::{metric_name, register, GaugeVec, Opts, PrometheusError, Registry, U64};
+use relay_utils::metrics::{
+	metric_name, register, Gauge, GaugeVec, ObservedLag, Opts, PrometheusError, Registry, F64, U64,
+};
+use std::{
+	sync::{Arc, Mutex},
+	time::Instant,
+};
 
 /// Headers sync metrics.
 #[derive(Clone)]
@@ -29,6 +35,12 @@ pub struct SyncLoopMetrics {
 	best_block_numbers: GaugeVec<U64>,
 	/// Number of headers in given states (see `HeaderStatus`).
 	blocks_in_state: GaugeVec<U64>,
+	/// Number of source blocks that are not yet known to the target node.
+	sync_lag_blocks: Gauge<U64>,
+	/// Seconds since the oldest source block, that is not yet known to the target node, has been seen.
+	sync_lag_seconds: Gauge<F64>,
+	/// Lag between best block numbers at source and target nodes.
+	sync_lag: Arc<Mutex<ObservedLag>>,
 }
 
 impl SyncLoopMetrics {
@@ -55,6 +67,21 @@ impl SyncLoopMetrics {
 				)?,
 				registry,
 			)?,
+			sync_lag_blocks: register(
+				Gauge::new(
+					metric_name(prefix, "sync_lag_blocks"),
+					"Number of source blocks that are not yet known to the target node",
+				)?,
+				registry,
+			)?,
+			sync_lag_seconds: register(
+				Gauge::new(
+					metric_name(prefix, "sync_lag_seconds"),
+					"Seconds since the oldest source block, that is not yet known to the target node, has been seen by the relay",
+				)?,
+				registry,
+			)?,
+			sync_lag: Arc::new(Mutex::new(ObservedLag::default())),
 		})
 	}
 }
@@ -62,16 +89,20 @@ impl SyncLoopMetrics {
 impl SyncLoopMetrics {
 	/// Update best block number at source.
 	pub fn update_best_block_at_source<Number: Into<u64>>(&self, source_best_number: Number) {
+		let source_best_number = source_best_number.into();
 		self.best_block_numbers
 			.with_label_values(&["source"])
-			.set(source_best_number.into());
+			.set(source_best_number);
+		self.update_sync_lag(|sync_lag| sync_lag.update_source(source_best_number, Instant::now()));
 	}
 
 	/// Update best block number at target.
 	pub fn update_best_block_at_target<Number: Into<u64>>(&self, target_best_number: Number) {
+		let target_best_number = target_best_number.into();
 		self.best_block_numbers
 			.with_label_values(&["target"])
-			.set(target_best_number.into());
+			.set(target_best_number);
+		self.update_sync_lag(|sync_lag| sync_lag.update_target(target_best_number));
 	}
 
 	/// Update metrics.
@@ -105,4 +136,13 @@ impl SyncLoopMetrics {
 			.with_label_values(&["submitted"])
 			.set(headers.headers_in_status(HeaderStatus::Submitted) as _);
 	}
+
+	/// Update sync lag and its metrics.
+	fn update_sync_lag(&self, update: impl FnOnce(&mut ObservedLag)) {
+		let mut sync_lag = self.sync_lag.lock().expect("metrics mutex is never poisoned; qed");
+		update(&mut sync_lag);
+		self.sync_lag_blocks.set(sync_lag.lag());
+		self.sync_lag_seconds
+			.set(sync_lag.lag_duration(Instant::now()).as_secs_f64());
+	}
 }
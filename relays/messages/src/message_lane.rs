@@ -34,6 +34,9 @@ pub trait MessageLane: 'static + Clone + Send + Sync {
 	/// Messages receiving proof.
 	type MessagesReceivingProof: Clone + Debug + Send + Sync;
 
+	/// Balance of the source chain, in which message delivery and dispatch fees are paid.
+	type SourceChainBalance: Clone + Copy + Debug + Into<u128> + Send + Sync;
+
 	/// Number of the source header.
 	type SourceHeaderNumber: BlockNumberBase;
 	/// Hash of the source header.
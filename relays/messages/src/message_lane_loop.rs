@@ -33,13 +33,15 @@ use async_trait::async_trait;
 use bp_messages::{LaneId, MessageNonce, UnrewardedRelayersState, Weight};
 use futures::{channel::mpsc::unbounded, future::FutureExt, stream::StreamExt};
 use relay_utils::{
+	control::{LoopControl, Reloadable},
 	interval,
 	metrics::{GlobalMetrics, MetricsParams},
 	process_future_result,
+	recovery::RecoveryPolicyParams,
 	relay_loop::Client as RelayClient,
 	retry_backoff, FailedClient,
 };
-use std::{collections::BTreeMap, fmt::Debug, future::Future, ops::RangeInclusive, time::Duration};
+use std::{collections::BTreeMap, fmt::Debug, future::Future, ops::RangeInclusive, sync::Arc, time::Duration};
 
 /// Message lane loop configuration params.
 #[derive(Debug, Clone)]
@@ -52,15 +54,58 @@ pub struct Params {
 	pub target_tick: Duration,
 	/// Delay between moments when connection error happens and our reconnect attempt.
 	pub reconnect_delay: Duration,
+	/// Recovery policy of the source client.
+	pub source_recovery_policy: RecoveryPolicyParams,
+	/// Recovery policy of the target client.
+	pub target_recovery_policy: RecoveryPolicyParams,
 	/// The loop will auto-restart if there has been no updates during this period.
 	pub stall_timeout: Duration,
 	/// Message delivery race parameters.
 	pub delivery_params: MessageDeliveryParams,
+	/// Message receiving confirmations race parameters.
+	pub receiving_params: MessageReceivingParams,
+	/// Messages that must be delivered before the loop is started.
+	pub forced_delivery: Option<ForcedDelivery>,
+	/// Control of the loop, used by the relay control interface.
+	pub control: LoopControl,
+}
+
+/// Messages that are delivered regardless of the delivery race state.
+///
+/// This is used to recover the lane after bugs or manual interventions, when the delivery race is
+/// unable to make progress.
+#[derive(Debug, Clone)]
+pub struct ForcedDelivery {
+	/// Nonces of messages to deliver.
+	pub nonces: RangeInclusive<MessageNonce>,
+	/// If true, the loop exits right after messages are delivered.
+	pub exit_when_delivered: bool,
+}
+
+/// Relayer operating mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelayerMode {
+	/// The relayer doesn't care about rewards and delivers as many messages as it can fit into
+	/// every delivery transaction (strict FIFO).
+	Altruistic,
+	/// The relayer selects the number of messages in every delivery transaction so that the
+	/// profit (total reward minus delivery transaction cost) is maximal. Messages are not delivered
+	/// if the delivery brings no profit.
+	Rational,
+}
+
+/// Message delivery transaction cost estimator, used in the rational relayer mode.
+pub trait DeliveryCostEstimator: Debug + Send + Sync {
+	/// Returns fee of the delivery transaction with given weight and size at the target chain,
+	/// converted to the source chain tokens (in which relayer rewards are paid).
+	fn delivery_transaction_cost(&self, weight: Weight, size: usize) -> u128;
 }
 
 /// Message delivery race parameters.
 #[derive(Debug, Clone)]
 pub struct MessageDeliveryParams {
+	/// Relayer operating mode. May be changed while the loop is running.
+	pub relayer_mode: Reloadable<RelayerMode>,
 	/// Maximal number of unconfirmed relayer entries at the inbound lane. If there's that number of entries
 	/// in the `InboundLaneData::relayers` set, all new messages will be rejected until reward payment will
 	/// be proved (by including outbound lane state to the message delivery transaction).
@@ -71,23 +116,57 @@ pub struct MessageDeliveryParams {
 	pub max_unconfirmed_nonces_at_target: MessageNonce,
 	/// Maximal number of relayed messages in single delivery transaction.
 	pub max_messages_in_single_batch: MessageNonce,
-	/// Maximal cumulative dispatch weight of relayed messages in single delivery transaction.
+	/// Maximal number of delivery transactions that may be submitted, but not yet accepted by the
+	/// target node. Every transaction delivers its own range of nonces. May be changed while the loop is running.
+	pub max_transactions_in_flight: Reloadable<usize>,
+	/// Maximal cumulative weight of relayed messages in single delivery transaction. Every message is
+	/// accounted with its declared dispatch weight plus `per_message_weight_overhead`.
 	pub max_messages_weight_in_single_batch: Weight,
-	/// Maximal cumulative size of relayed messages in single delivery transaction.
+	/// Maximal cumulative size of relayed messages in single delivery transaction. Every message is
+	/// accounted with its payload size plus `per_message_size_overhead`.
 	pub max_messages_size_in_single_batch: usize,
+	/// Weight that every relayed message adds to the delivery transaction, in addition to its dispatch weight.
+	pub per_message_weight_overhead: Weight,
+	/// Size that every relayed message adds to the delivery transaction, in addition to its payload size.
+	pub per_message_size_overhead: usize,
+	/// Weight of the delivery transaction itself, excluding messages. Only used in the rational
+	/// relayer mode to estimate cost of the delivery transaction.
+	pub delivery_transaction_base_weight: Weight,
+	/// Delivery transaction cost estimator. Only used in the rational relayer mode.
+	pub delivery_cost_estimator: Arc<dyn DeliveryCostEstimator>,
 }
 
-/// Message weights.
+/// Message receiving confirmations race parameters.
+#[derive(Debug, Clone)]
+pub struct MessageReceivingParams {
+	/// Maximal number of source chain blocks for which the confirmations transaction may be delayed. Delaying
+	/// confirmations allows to confirm several delivered ranges with a single transaction, which reduces source
+	/// chain fees at the cost of confirmations latency. Zero means that confirmations are never delayed.
+	/// May be changed while the loop is running.
+	pub max_confirmations_delay: Reloadable<u32>,
+	/// Confirmations are never delayed if there are that many unconfirmed messages at the target chain,
+	/// because otherwise the delivery race would be blocked. Should be the same as the
+	/// `MessageDeliveryParams::max_unconfirmed_nonces_at_target`.
+	pub max_unconfirmed_nonces_at_target: MessageNonce,
+}
+
+/// Message details.
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct MessageWeights {
+pub struct MessageDetails<SourceChainBalance> {
 	/// Message dispatch weight.
-	pub weight: Weight,
+	pub dispatch_weight: Weight,
 	/// Message size (number of bytes in encoded payload).
 	pub size: usize,
+	/// The relayer reward paid in the source chain tokens.
+	pub reward: SourceChainBalance,
+	/// Whether the relayer wants to deliver this message. The relayer may be configured to serve
+	/// only messages of some senders. Messages are still delivered in order, though, so unwanted
+	/// messages that precede wanted messages are delivered too.
+	pub is_wanted: bool,
 }
 
-/// Messages weights map.
-pub type MessageWeightsMap = BTreeMap<MessageNonce, MessageWeights>;
+/// Messages details map.
+pub type MessageDetailsMap<SourceChainBalance> = BTreeMap<MessageNonce, MessageDetails<SourceChainBalance>>;
 
 /// Message delivery race proof parameters.
 #[derive(Debug, PartialEq)]
@@ -115,15 +194,15 @@ pub trait SourceClient<P: MessageLane>: RelayClient {
 		id: SourceHeaderIdOf<P>,
 	) -> Result<(SourceHeaderIdOf<P>, MessageNonce), Self::Error>;
 
-	/// Returns mapping of message nonces, generated on this client, to their weights.
+	/// Returns mapping of message nonces, generated on this client, to their details.
 	///
-	/// Some weights may be missing from returned map, if corresponding messages were pruned at
+	/// Some messages may be missing from returned map, if corresponding messages were pruned at
 	/// the source chain.
-	async fn generated_messages_weights(
+	async fn generated_message_details(
 		&self,
 		id: SourceHeaderIdOf<P>,
 		nonces: RangeInclusive<MessageNonce>,
-	) -> Result<MessageWeightsMap, Self::Error>;
+	) -> Result<MessageDetailsMap<P::SourceChainBalance>, Self::Error>;
 
 	/// Prove messages in inclusive range [begin; end].
 	async fn prove_messages(
@@ -213,12 +292,7 @@ pub struct ClientsState<P: MessageLane> {
 
 /// Return prefix that will be used by default to expose Prometheus metrics of the finality proofs sync loop.
 pub fn metrics_prefix<P: MessageLane>(lane: &LaneId) -> String {
-	format!(
-		"{}_to_{}_MessageLane_{}",
-		P::SOURCE_NAME,
-		P::TARGET_NAME,
-		hex::encode(lane)
-	)
+	format!("{}_to_{}_MessageLane_{}", P::SOURCE_NAME, P::TARGET_NAME, lane)
 }
 
 /// Run message lane service loop.
@@ -229,9 +303,17 @@ pub async fn run<P: MessageLane>(
 	metrics_params: MetricsParams,
 	exit_signal: impl Future<Output = ()> + Send + 'static,
 ) -> Result<(), String> {
+	if let Some(ref forced_delivery) = params.forced_delivery {
+		deliver_messages_range(&source_client, &target_client, forced_delivery.nonces.clone()).await?;
+		if forced_delivery.exit_when_delivered {
+			return Ok(());
+		}
+	}
+
 	let exit_signal = exit_signal.shared();
 	relay_utils::relay_loop(source_client, target_client)
 		.reconnect_delay(params.reconnect_delay)
+		.recovery_policies(&params.source_recovery_policy, &params.target_recovery_policy)
 		.with_metrics(Some(metrics_prefix::<P>(&params.lane)), metrics_params)
 		.loop_metric(|registry, prefix| MessageLaneLoopMetrics::new(registry, prefix))?
 		.standalone_metric(|registry, prefix| GlobalMetrics::new(registry, prefix))?
@@ -252,6 +334,66 @@ pub async fn run<P: MessageLane>(
 		.await
 }
 
+/// Deliver given range of messages to the target chain, ignoring the delivery race strategy.
+///
+/// Messages are proved at the best finalized source header that is known to the target node, so
+/// all of them must have been generated at this header.
+pub async fn deliver_messages_range<P: MessageLane>(
+	source_client: &impl SourceClient<P>,
+	target_client: &impl TargetClient<P>,
+	nonces: RangeInclusive<MessageNonce>,
+) -> Result<RangeInclusive<MessageNonce>, String> {
+	let target_state = target_client
+		.state()
+		.await
+		.map_err(|e| format!("Error retrieving state from {} node: {:?}", P::TARGET_NAME, e))?;
+	let source_header_id = target_state.best_finalized_peer_at_best_self;
+
+	let messages_details = source_client
+		.generated_message_details(source_header_id.clone(), nonces.clone())
+		.await
+		.map_err(|e| format!("Error retrieving message details from {} node: {:?}", P::SOURCE_NAME, e))?;
+	if let Some(missing_nonce) = nonces.clone().find(|nonce| !messages_details.contains_key(nonce)) {
+		return Err(format!(
+			"Message {} is missing at {} header {:?}. It has been either pruned, or the header is not yet \
+			finalized at {}",
+			missing_nonce,
+			P::SOURCE_NAME,
+			source_header_id,
+			P::TARGET_NAME,
+		));
+	}
+	let dispatch_weight = messages_details.values().fold(0, |total_weight: Weight, details| {
+		total_weight.saturating_add(details.dispatch_weight)
+	});
+
+	let (generated_at_header, nonces, proof) = source_client
+		.prove_messages(
+			source_header_id,
+			nonces,
+			MessageProofParameters {
+				outbound_state_proof_required: false,
+				dispatch_weight,
+			},
+		)
+		.await
+		.map_err(|e| format!("Error proving messages at {} node: {:?}", P::SOURCE_NAME, e))?;
+	let delivered_nonces = target_client
+		.submit_messages_proof(generated_at_header, nonces, proof)
+		.await
+		.map_err(|e| format!("Error submitting messages proof to {} node: {:?}", P::TARGET_NAME, e))?;
+
+	log::info!(
+		target: "bridge",
+		"Forcibly delivered {} -> {} messages: {:?}",
+		P::SOURCE_NAME,
+		P::TARGET_NAME,
+		delivered_nonces,
+	);
+
+	Ok(delivered_nonces)
+}
+
 /// Run one-way message delivery loop until connection with target or source node is lost, or exit signal is received.
 async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: TargetClient<P>>(
 	params: Params,
@@ -274,6 +416,8 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 	let target_go_offline_future = futures::future::Fuse::terminated();
 	let target_tick_stream = interval(params.target_tick).fuse();
 
+	let sync_requests = params.control.sync_requests();
+
 	let (
 		(delivery_source_state_sender, delivery_source_state_receiver),
 		(delivery_target_state_sender, delivery_target_state_receiver),
@@ -284,6 +428,7 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 		target_client.clone(),
 		delivery_target_state_receiver,
 		params.stall_timeout,
+		params.control.clone(),
 		metrics_msg.clone(),
 		params.delivery_params,
 	)
@@ -299,7 +444,9 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 		target_client.clone(),
 		receiving_target_state_receiver,
 		params.stall_timeout,
+		params.control.clone(),
 		metrics_msg.clone(),
+		params.receiving_params,
 	)
 	.fuse();
 
@@ -312,6 +459,7 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 		target_state,
 		target_go_offline_future,
 		target_tick_stream,
+		sync_requests,
 		delivery_race_loop,
 		receiving_race_loop,
 		exit_signal
@@ -338,6 +486,7 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 						if let Some(metrics_msg) = metrics_msg.as_ref() {
 							metrics_msg.update_source_state::<P>(new_source_state);
 						}
+						relay_utils::watchdog::loop_progress();
 					},
 					&mut source_go_offline_future,
 					async_std::task::sleep,
@@ -369,6 +518,7 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 						if let Some(metrics_msg) = metrics_msg.as_ref() {
 							metrics_msg.update_target_state::<P>(new_target_state);
 						}
+						relay_utils::watchdog::loop_progress();
 					},
 					&mut target_go_offline_future,
 					async_std::task::sleep,
@@ -381,6 +531,11 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 			_ = target_tick_stream.next() => {
 				target_state_required = true;
 			},
+			_ = sync_requests.next() => {
+				log::debug!(target: "bridge", "Sync of {} -> {} lane has been requested", P::SOURCE_NAME, P::TARGET_NAME);
+				source_state_required = true;
+				target_state_required = true;
+			},
 
 			delivery_error = delivery_race_loop => {
 				match delivery_error {
@@ -420,7 +575,6 @@ pub(crate) mod tests {
 	use futures::stream::StreamExt;
 	use parking_lot::Mutex;
 	use relay_utils::{HeaderId, MaybeConnectionError};
-	use std::sync::Arc;
 
 	pub fn header_id(number: TestSourceHeaderNumber) -> TestSourceHeaderId {
 		HeaderId(number, number)
@@ -432,6 +586,19 @@ pub(crate) mod tests {
 	pub type TestMessagesProof = (RangeInclusive<MessageNonce>, Option<MessageNonce>);
 	pub type TestMessagesReceivingProof = MessageNonce;
 
+	pub type TestSourceChainBalance = u64;
+
+	#[derive(Debug)]
+	pub struct TestDeliveryCostEstimator {
+		pub fee_per_weight_unit: u128,
+	}
+
+	impl DeliveryCostEstimator for TestDeliveryCostEstimator {
+		fn delivery_transaction_cost(&self, weight: Weight, _size: usize) -> u128 {
+			(weight as u128).saturating_mul(self.fee_per_weight_unit)
+		}
+	}
+
 	pub type TestSourceHeaderNumber = u64;
 	pub type TestSourceHeaderHash = u64;
 
@@ -457,6 +624,8 @@ pub(crate) mod tests {
 		type MessagesProof = TestMessagesProof;
 		type MessagesReceivingProof = TestMessagesReceivingProof;
 
+		type SourceChainBalance = TestSourceChainBalance;
+
 		type SourceHeaderNumber = TestSourceHeaderNumber;
 		type SourceHeaderHash = TestSourceHeaderHash;
 
@@ -536,13 +705,23 @@ pub(crate) mod tests {
 			Ok((id, data.source_latest_confirmed_received_nonce))
 		}
 
-		async fn generated_messages_weights(
+		async fn generated_message_details(
 			&self,
 			_id: SourceHeaderIdOf<TestMessageLane>,
 			nonces: RangeInclusive<MessageNonce>,
-		) -> Result<MessageWeightsMap, TestError> {
+		) -> Result<MessageDetailsMap<TestSourceChainBalance>, TestError> {
 			Ok(nonces
-				.map(|nonce| (nonce, MessageWeights { weight: 1, size: 1 }))
+				.map(|nonce| {
+					(
+						nonce,
+						MessageDetails {
+							dispatch_weight: 1,
+							size: 1,
+							reward: 1,
+							is_wanted: true,
+						},
+					)
+				})
 				.collect())
 		}
 
@@ -708,6 +887,7 @@ pub(crate) mod tests {
 		data: TestClientData,
 		source_tick: Arc<dyn Fn(&mut TestClientData) + Send + Sync>,
 		target_tick: Arc<dyn Fn(&mut TestClientData) + Send + Sync>,
+		forced_delivery: Option<ForcedDelivery>,
 		exit_signal: impl Future<Output = ()> + 'static + Send,
 	) -> TestClientData {
 		async_std::task::block_on(async {
@@ -723,18 +903,32 @@ pub(crate) mod tests {
 			};
 			let _ = run(
 				Params {
-					lane: [0, 0, 0, 0],
+					lane: LaneId([0, 0, 0, 0]),
 					source_tick: Duration::from_millis(100),
 					target_tick: Duration::from_millis(100),
 					reconnect_delay: Duration::from_millis(0),
+					source_recovery_policy: Default::default(),
+					target_recovery_policy: Default::default(),
 					stall_timeout: Duration::from_millis(60 * 1000),
 					delivery_params: MessageDeliveryParams {
+						relayer_mode: Reloadable::new(RelayerMode::Altruistic),
 						max_unrewarded_relayer_entries_at_target: 4,
 						max_unconfirmed_nonces_at_target: 4,
 						max_messages_in_single_batch: 4,
+						max_transactions_in_flight: Reloadable::new(1),
 						max_messages_weight_in_single_batch: 4,
 						max_messages_size_in_single_batch: 4,
+						per_message_weight_overhead: 0,
+						per_message_size_overhead: 0,
+						delivery_transaction_base_weight: 0,
+						delivery_cost_estimator: Arc::new(TestDeliveryCostEstimator { fee_per_weight_unit: 0 }),
+					},
+					receiving_params: MessageReceivingParams {
+						max_confirmations_delay: Reloadable::new(0),
+						max_unconfirmed_nonces_at_target: 4,
 					},
+					forced_delivery,
+					control: Default::default(),
 				},
 				source_client,
 				target_client,
@@ -790,6 +984,7 @@ pub(crate) mod tests {
 					exit_sender.unbounded_send(()).unwrap();
 				}
 			}),
+			None,
 			exit_receiver.into_future().map(|(_, _)| ()),
 		);
 
@@ -853,6 +1048,7 @@ pub(crate) mod tests {
 					exit_sender.unbounded_send(()).unwrap();
 				}
 			}),
+			None,
 			exit_receiver.into_future().map(|(_, _)| ()),
 		);
 
@@ -869,4 +1065,34 @@ pub(crate) mod tests {
 		assert!(!result.target_to_source_header_requirements.is_empty());
 		assert!(!result.source_to_target_header_requirements.is_empty());
 	}
+
+	#[test]
+	fn message_lane_loop_delivers_forced_messages_and_exits() {
+		let result = run_loop_test(
+			TestClientData {
+				source_state: ClientState {
+					best_self: HeaderId(10, 10),
+					best_finalized_self: HeaderId(10, 10),
+					best_finalized_peer_at_best_self: HeaderId(0, 0),
+				},
+				source_latest_generated_nonce: 10,
+				target_state: ClientState {
+					best_self: HeaderId(0, 0),
+					best_finalized_self: HeaderId(0, 0),
+					best_finalized_peer_at_best_self: HeaderId(10, 10),
+				},
+				target_latest_received_nonce: 4,
+				..Default::default()
+			},
+			Arc::new(|_: &mut TestClientData| {}),
+			Arc::new(|_: &mut TestClientData| {}),
+			Some(ForcedDelivery {
+				nonces: 5..=7,
+				exit_when_delivered: true,
+			}),
+			futures::future::pending(),
+		);
+
+		assert_eq!(result.submitted_messages_proofs, vec![(5..=7, None)]);
+	}
 }
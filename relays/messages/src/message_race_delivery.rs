@@ -15,8 +15,9 @@
 
 use crate::message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf};
 use crate::message_lane_loop::{
-	MessageDeliveryParams, MessageProofParameters, MessageWeightsMap, SourceClient as MessageLaneSourceClient,
-	SourceClientState, TargetClient as MessageLaneTargetClient, TargetClientState,
+	DeliveryCostEstimator, MessageDeliveryParams, MessageDetails, MessageDetailsMap, MessageProofParameters,
+	RelayerMode, SourceClient as MessageLaneSourceClient, SourceClientState, TargetClient as MessageLaneTargetClient,
+	TargetClientState,
 };
 use crate::message_race_loop::{
 	MessageRace, NoncesRange, RaceState, RaceStrategy, SourceClient, SourceClientNonces, TargetClient,
@@ -26,13 +27,17 @@ use crate::message_race_strategy::BasicStrategy;
 use crate::metrics::MessageLaneLoopMetrics;
 
 use async_trait::async_trait;
-use bp_messages::{MessageNonce, UnrewardedRelayersState, Weight};
+use bp_messages::{nonces::DeliveredMessages, MessageNonce, UnrewardedRelayersState, Weight};
 use futures::stream::FusedStream;
-use relay_utils::FailedClient;
+use relay_utils::{
+	control::{LoopControl, Reloadable},
+	FailedClient,
+};
 use std::{
 	collections::{BTreeMap, VecDeque},
 	marker::PhantomData,
 	ops::RangeInclusive,
+	sync::Arc,
 	time::Duration,
 };
 
@@ -43,6 +48,7 @@ pub async fn run<P: MessageLane>(
 	target_client: impl MessageLaneTargetClient<P>,
 	target_state_updates: impl FusedStream<Item = TargetClientState<P>>,
 	stall_timeout: Duration,
+	control: LoopControl,
 	metrics_msg: Option<MessageLaneLoopMetrics>,
 	params: MessageDeliveryParams,
 ) -> Result<(), FailedClient> {
@@ -60,15 +66,22 @@ pub async fn run<P: MessageLane>(
 		},
 		target_state_updates,
 		stall_timeout,
+		control,
 		MessageDeliveryStrategy::<P> {
+			relayer_mode: params.relayer_mode,
+			max_transactions_in_flight: params.max_transactions_in_flight.clone(),
 			max_unrewarded_relayer_entries_at_target: params.max_unrewarded_relayer_entries_at_target,
 			max_unconfirmed_nonces_at_target: params.max_unconfirmed_nonces_at_target,
 			max_messages_in_single_batch: params.max_messages_in_single_batch,
 			max_messages_weight_in_single_batch: params.max_messages_weight_in_single_batch,
 			max_messages_size_in_single_batch: params.max_messages_size_in_single_batch,
+			per_message_weight_overhead: params.per_message_weight_overhead,
+			per_message_size_overhead: params.per_message_size_overhead,
+			delivery_transaction_base_weight: params.delivery_transaction_base_weight,
+			delivery_cost_estimator: params.delivery_cost_estimator,
 			latest_confirmed_nonces_at_source: VecDeque::new(),
 			target_nonces: None,
-			strategy: BasicStrategy::new(),
+			strategy: BasicStrategy::with_max_transactions_in_flight(params.max_transactions_in_flight.get()),
 		},
 	)
 	.await
@@ -107,7 +120,7 @@ where
 	C: MessageLaneSourceClient<P>,
 {
 	type Error = C::Error;
-	type NoncesRange = MessageWeightsMap;
+	type NoncesRange = MessageDetailsMap<P::SourceChainBalance>;
 	type ProofParameters = MessageProofParameters;
 
 	async fn nonces(
@@ -125,10 +138,10 @@ where
 
 		let new_nonces = if latest_generated_nonce > prev_latest_nonce {
 			self.client
-				.generated_messages_weights(at_block.clone(), prev_latest_nonce + 1..=latest_generated_nonce)
+				.generated_message_details(at_block.clone(), prev_latest_nonce + 1..=latest_generated_nonce)
 				.await?
 		} else {
-			MessageWeightsMap::new()
+			MessageDetailsMap::new()
 		};
 
 		Ok((
@@ -223,6 +236,10 @@ struct DeliveryRaceTargetNoncesData {
 
 /// Messages delivery strategy.
 struct MessageDeliveryStrategy<P: MessageLane> {
+	/// Relayer operating mode.
+	relayer_mode: Reloadable<RelayerMode>,
+	/// Maximal number of delivery transactions in flight. Applied to the basic strategy before selecting nonces.
+	max_transactions_in_flight: Reloadable<usize>,
 	/// Maximal unrewarded relayer entries at target client.
 	max_unrewarded_relayer_entries_at_target: MessageNonce,
 	/// Maximal unconfirmed nonces at target client.
@@ -233,6 +250,14 @@ struct MessageDeliveryStrategy<P: MessageLane> {
 	max_messages_weight_in_single_batch: Weight,
 	/// Maximal messages size in the single delivery transaction.
 	max_messages_size_in_single_batch: usize,
+	/// Weight that every message adds to the delivery transaction, in addition to its dispatch weight.
+	per_message_weight_overhead: Weight,
+	/// Size that every message adds to the delivery transaction, in addition to its payload size.
+	per_message_size_overhead: usize,
+	/// Weight of the delivery transaction itself, excluding messages.
+	delivery_transaction_base_weight: Weight,
+	/// Delivery transaction cost estimator.
+	delivery_cost_estimator: Arc<dyn DeliveryCostEstimator>,
 	/// Latest confirmed nonces at the source client + the header id where we have first met this nonce.
 	latest_confirmed_nonces_at_source: VecDeque<(SourceHeaderIdOf<P>, MessageNonce)>,
 	/// Target nonces from the source client.
@@ -246,13 +271,15 @@ type MessageDeliveryStrategyBase<P> = BasicStrategy<
 	<P as MessageLane>::SourceHeaderHash,
 	<P as MessageLane>::TargetHeaderNumber,
 	<P as MessageLane>::TargetHeaderHash,
-	MessageWeightsMap,
+	MessageDetailsMap<<P as MessageLane>::SourceChainBalance>,
 	<P as MessageLane>::MessagesProof,
 >;
 
 impl<P: MessageLane> std::fmt::Debug for MessageDeliveryStrategy<P> {
 	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
 		fmt.debug_struct("MessageDeliveryStrategy")
+			.field("relayer_mode", &self.relayer_mode)
+			.field("max_transactions_in_flight", &self.max_transactions_in_flight)
 			.field(
 				"max_unrewarded_relayer_entries_at_target",
 				&self.max_unrewarded_relayer_entries_at_target,
@@ -270,6 +297,13 @@ impl<P: MessageLane> std::fmt::Debug for MessageDeliveryStrategy<P> {
 				"max_messages_size_in_single_batch",
 				&self.max_messages_size_in_single_batch,
 			)
+			.field("per_message_weight_overhead", &self.per_message_weight_overhead)
+			.field("per_message_size_overhead", &self.per_message_size_overhead)
+			.field(
+				"delivery_transaction_base_weight",
+				&self.delivery_transaction_base_weight,
+			)
+			.field("delivery_cost_estimator", &self.delivery_cost_estimator)
 			.field(
 				"latest_confirmed_nonces_at_source",
 				&self.latest_confirmed_nonces_at_source,
@@ -280,10 +314,113 @@ impl<P: MessageLane> std::fmt::Debug for MessageDeliveryStrategy<P> {
 	}
 }
 
+impl<P: MessageLane> MessageDeliveryStrategy<P> {
+	/// Returns details of queued messages (starting from the first one) that may be delivered in the
+	/// single transaction.
+	///
+	/// The same limits are applied by the `select_nonces_to_deliver` method, so the returned vector
+	/// may be used to make decisions about the number of messages we want to deliver.
+	fn select_messages_fitting_single_transaction(
+		&self,
+		best_finalized_source_header_id_at_best_target: &SourceHeaderIdOf<P>,
+		max_nonces: MessageNonce,
+	) -> Vec<&MessageDetails<P::SourceChainBalance>> {
+		let mut selected_weight: Weight = 0;
+		let mut selected_size: usize = 0;
+		let mut selected_messages = Vec::new();
+
+		let queued_messages = self
+			.strategy
+			.source_queue()
+			.iter()
+			.take_while(|(queued_at, _)| queued_at.0 <= best_finalized_source_header_id_at_best_target.0)
+			.flat_map(|(_, range)| range.values());
+		for details in queued_messages {
+			if selected_messages.len() as MessageNonce == max_nonces {
+				break;
+			}
+
+			let message_weight = details.dispatch_weight.saturating_add(self.per_message_weight_overhead);
+			let message_size = details.size.saturating_add(self.per_message_size_overhead);
+			let new_selected_weight = selected_weight.saturating_add(message_weight);
+			let new_selected_size = selected_size.saturating_add(message_size);
+			// the first message is always selected - see `select_nonces_to_deliver`
+			if !selected_messages.is_empty()
+				&& (new_selected_weight > self.max_messages_weight_in_single_batch
+					|| new_selected_size > self.max_messages_size_in_single_batch)
+			{
+				break;
+			}
+
+			selected_weight = new_selected_weight;
+			selected_size = new_selected_size;
+			selected_messages.push(details);
+		}
+
+		selected_messages
+	}
+
+	/// Returns number of given messages (starting from the first one) that we want to deliver in the
+	/// single transaction to get the maximal profit, i.e. total reward minus delivery transaction cost.
+	///
+	/// Messages are always delivered in order, so the only thing we may choose here is how many
+	/// messages to deliver. If several batches bring the same profit, we'll select the larger one.
+	/// If no batch is profitable, nothing is delivered.
+	fn select_most_profitable_nonces_count(&self, messages: &[&MessageDetails<P::SourceChainBalance>]) -> MessageNonce {
+		let mut selected_weight: Weight = self.delivery_transaction_base_weight;
+		let mut selected_size: usize = 0;
+		let mut selected_reward: u128 = 0;
+		let mut best_count: MessageNonce = 0;
+		let mut best_profit: u128 = 0;
+		for (index, details) in messages.iter().enumerate() {
+			selected_weight = selected_weight
+				.saturating_add(details.dispatch_weight)
+				.saturating_add(self.per_message_weight_overhead);
+			selected_size = selected_size
+				.saturating_add(details.size)
+				.saturating_add(self.per_message_size_overhead);
+			selected_reward = selected_reward.saturating_add(details.reward.into());
+
+			let selected_cost = self
+				.delivery_cost_estimator
+				.delivery_transaction_cost(selected_weight, selected_size);
+			let selected_profit = match selected_reward.checked_sub(selected_cost) {
+				Some(selected_profit) if selected_profit != 0 => selected_profit,
+				_ => continue,
+			};
+			if selected_profit >= best_profit {
+				best_count = index as MessageNonce + 1;
+				best_profit = selected_profit;
+			}
+		}
+
+		if best_count == 0 && !messages.is_empty() {
+			log::trace!(
+				target: "bridge",
+				"None of {} messages that fit into {} -> {} delivery transaction is profitable to deliver",
+				messages.len(),
+				MessageDeliveryRace::<P>::source_name(),
+				MessageDeliveryRace::<P>::target_name(),
+			);
+		} else if best_count != messages.len() as MessageNonce {
+			log::trace!(
+				target: "bridge",
+				"Selected {} most profitable messages out of {} messages that fit into {} -> {} delivery transaction",
+				best_count,
+				messages.len(),
+				MessageDeliveryRace::<P>::source_name(),
+				MessageDeliveryRace::<P>::target_name(),
+			);
+		}
+
+		best_count
+	}
+}
+
 impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::MessagesProof>
 	for MessageDeliveryStrategy<P>
 {
-	type SourceNoncesRange = MessageWeightsMap;
+	type SourceNoncesRange = MessageDetailsMap<P::SourceChainBalance>;
 	type ProofParameters = MessageProofParameters;
 	type TargetNoncesData = DeliveryRaceTargetNoncesData;
 
@@ -396,13 +533,25 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 			.last()
 			.map(|(_, nonce)| *nonce)?;
 		let target_nonces = self.target_nonces.as_ref()?;
+		self.strategy
+			.set_max_transactions_in_flight(self.max_transactions_in_flight.get());
+
+		// Nonces that are delivered by transactions in flight, are not selected again.
+		self.strategy.remove_submitted_nonces_from_source_queue(race_state);
 
 		// There's additional condition in the message delivery race: target would reject messages
 		// if there are too much unconfirmed messages at the inbound lane.
 
 		// The receiving race is responsible to deliver confirmations back to the source chain. So if
 		// there's a lot of unconfirmed messages, let's wait until it'll be able to do its job.
-		let latest_received_nonce_at_target = target_nonces.latest_nonce;
+		//
+		// Messages of transactions in flight will be received by the target soon, so they're also
+		// considered unconfirmed.
+		let latest_received_nonce_at_target = race_state
+			.nonces_submitted
+			.back()
+			.map(|nonces| std::cmp::max(*nonces.end(), target_nonces.latest_nonce))
+			.unwrap_or(target_nonces.latest_nonce);
 		let confirmations_missing = latest_received_nonce_at_target.checked_sub(latest_confirmed_nonce_at_source);
 		match confirmations_missing {
 			Some(confirmations_missing) if confirmations_missing >= self.max_unconfirmed_nonces_at_target => {
@@ -434,8 +583,13 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 		// The target node would also reject messages if there are too many entries in the
 		// "unrewarded relayers" set. If we are unable to prove new rewards to the target node, then
 		// we should wait for confirmations race.
+		//
+		// Transactions in flight may add (at most) one more entry to the set, because all of them
+		// are submitted by the same relayer.
+		let unrewarded_relayer_entries_in_flight = if race_state.nonces_submitted.is_empty() { 0 } else { 1 };
 		let unrewarded_relayer_entries_limit_reached =
 			target_nonces.nonces_data.unrewarded_relayers.unrewarded_relayer_entries
+				+ unrewarded_relayer_entries_in_flight
 				>= self.max_unrewarded_relayer_entries_at_target;
 		if unrewarded_relayer_entries_limit_reached {
 			// so there are already too many unrewarded relayer entries in the set
@@ -471,8 +625,34 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 			.and_then(|diff| self.max_unconfirmed_nonces_at_target.checked_sub(diff))
 			.unwrap_or_default();
 		let max_nonces = std::cmp::min(max_nonces, self.max_messages_in_single_batch);
+		// If relayer is configured to serve only some senders, it only delivers messages if there's at
+		// least one message from those senders. All preceding messages are delivered too, because
+		// messages are delivered in order.
+		let fitting_messages = self
+			.select_messages_fitting_single_transaction(&best_finalized_source_header_id_at_best_target, max_nonces);
+		let wanted_messages_count = fitting_messages
+			.iter()
+			.rposition(|details| details.is_wanted)
+			.map(|position| position + 1)
+			.unwrap_or(0);
+		if wanted_messages_count == 0 && !fitting_messages.is_empty() {
+			log::trace!(
+				target: "bridge",
+				"None of {} -> {} messages that fit into delivery transaction are sent by served senders",
+				MessageDeliveryRace::<P>::source_name(),
+				MessageDeliveryRace::<P>::target_name(),
+			);
+		}
+		let wanted_messages = &fitting_messages[..wanted_messages_count];
+		let max_nonces = match self.relayer_mode.get() {
+			RelayerMode::Altruistic => wanted_messages.len() as MessageNonce,
+			RelayerMode::Rational => self.select_most_profitable_nonces_count(wanted_messages),
+		};
 		let max_messages_weight_in_single_batch = self.max_messages_weight_in_single_batch;
 		let max_messages_size_in_single_batch = self.max_messages_size_in_single_batch;
+		let per_message_weight_overhead = self.per_message_weight_overhead;
+		let per_message_size_overhead = self.per_message_size_overhead;
+		let mut selected_dispatch_weight: Weight = 0;
 		let mut selected_weight: Weight = 0;
 		let mut selected_size: usize = 0;
 		let mut selected_count: MessageNonce = 0;
@@ -482,14 +662,16 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 			.select_nonces_to_deliver_with_selector(race_state, |range| {
 				let to_requeue = range
 					.into_iter()
-					.skip_while(|(_, weight)| {
+					.skip_while(|(_, details)| {
 						// Since we (hopefully) have some reserves in `max_messages_weight_in_single_batch`
 						// and `max_messages_size_in_single_batch`, we may still try to submit transaction
 						// with single message if message overflows these limits. The worst case would be if
 						// transaction will be rejected by the target runtime, but at least we have tried.
 
-						// limit messages in the batch by weight
-						let new_selected_weight = match selected_weight.checked_add(weight.weight) {
+						// limit messages in the batch by weight (message is also adding some overhead
+						// to the delivery transaction weight)
+						let message_weight = details.dispatch_weight.saturating_add(per_message_weight_overhead);
+						let new_selected_weight = match selected_weight.checked_add(message_weight) {
 							Some(new_selected_weight) if new_selected_weight <= max_messages_weight_in_single_batch => {
 								new_selected_weight
 							}
@@ -506,8 +688,10 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 							_ => return false,
 						};
 
-						// limit messages in the batch by size
-						let new_selected_size = match selected_size.checked_add(weight.size) {
+						// limit messages in the batch by size (message is also adding some extra nodes
+						// to the storage proof)
+						let message_size = details.size.saturating_add(per_message_size_overhead);
+						let new_selected_size = match selected_size.checked_add(message_size) {
 							Some(new_selected_size) if new_selected_size <= max_messages_size_in_single_batch => {
 								new_selected_size
 							}
@@ -530,6 +714,7 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 							return false;
 						}
 
+						selected_dispatch_weight = selected_dispatch_weight.saturating_add(details.dispatch_weight);
 						selected_weight = new_selected_weight;
 						selected_size = new_selected_size;
 						selected_count = new_selected_count;
@@ -547,13 +732,13 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 			selected_nonces,
 			MessageProofParameters {
 				outbound_state_proof_required,
-				dispatch_weight: selected_weight,
+				dispatch_weight: selected_dispatch_weight,
 			},
 		))
 	}
 }
 
-impl NoncesRange for MessageWeightsMap {
+impl<SourceChainBalance: std::fmt::Debug> NoncesRange for MessageDetailsMap<SourceChainBalance> {
 	fn begin(&self) -> MessageNonce {
 		self.keys().next().cloned().unwrap_or_default()
 	}
@@ -563,7 +748,8 @@ impl NoncesRange for MessageWeightsMap {
 	}
 
 	fn greater_than(mut self, nonce: MessageNonce) -> Option<Self> {
-		let gte = self.split_off(&(nonce + 1));
+		let greater_nonces = DeliveredMessages::new(self.begin(), self.end())?.greater_than(nonce)?;
+		let gte = self.split_off(&greater_nonces.begin());
 		if gte.is_empty() {
 			None
 		} else {
@@ -575,9 +761,9 @@ impl NoncesRange for MessageWeightsMap {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::message_lane_loop::{
-		tests::{header_id, TestMessageLane, TestMessagesProof, TestSourceHeaderId, TestTargetHeaderId},
-		MessageWeights,
+	use crate::message_lane_loop::tests::{
+		header_id, TestDeliveryCostEstimator, TestMessageLane, TestMessagesProof, TestSourceChainBalance,
+		TestSourceHeaderId, TestTargetHeaderId,
 	};
 
 	type TestRaceState = RaceState<TestSourceHeaderId, TestTargetHeaderId, TestMessagesProof>;
@@ -590,15 +776,21 @@ mod tests {
 			best_target_header_id: Some(header_id(1)),
 			best_finalized_target_header_id: Some(header_id(1)),
 			nonces_to_submit: None,
-			nonces_submitted: None,
+			nonces_submitted: Default::default(),
 		};
 
 		let mut race_strategy = TestStrategy {
+			relayer_mode: Reloadable::new(RelayerMode::Altruistic),
+			max_transactions_in_flight: Reloadable::new(2),
 			max_unrewarded_relayer_entries_at_target: 4,
 			max_unconfirmed_nonces_at_target: 4,
 			max_messages_in_single_batch: 4,
 			max_messages_weight_in_single_batch: 4,
 			max_messages_size_in_single_batch: 4,
+			per_message_weight_overhead: 0,
+			per_message_size_overhead: 0,
+			delivery_transaction_base_weight: 0,
+			delivery_cost_estimator: Arc::new(TestDeliveryCostEstimator { fee_per_weight_unit: 0 }),
 			latest_confirmed_nonces_at_source: vec![(header_id(1), 19)].into_iter().collect(),
 			target_nonces: Some(TargetClientNonces {
 				latest_nonce: 19,
@@ -611,17 +803,17 @@ mod tests {
 					},
 				},
 			}),
-			strategy: BasicStrategy::new(),
+			strategy: BasicStrategy::with_max_transactions_in_flight(2),
 		};
 
 		race_strategy.strategy.source_nonces_updated(
 			header_id(1),
 			SourceClientNonces {
 				new_nonces: vec![
-					(20, MessageWeights { weight: 1, size: 1 }),
-					(21, MessageWeights { weight: 1, size: 1 }),
-					(22, MessageWeights { weight: 1, size: 1 }),
-					(23, MessageWeights { weight: 1, size: 1 }),
+					(20, test_message_details()),
+					(21, test_message_details()),
+					(22, test_message_details()),
+					(23, test_message_details()),
 				]
 				.into_iter()
 				.collect(),
@@ -643,6 +835,15 @@ mod tests {
 		(race_state, race_strategy)
 	}
 
+	fn test_message_details() -> MessageDetails<TestSourceChainBalance> {
+		MessageDetails {
+			dispatch_weight: 1,
+			size: 1,
+			reward: 1,
+			is_wanted: true,
+		}
+	}
+
 	fn proof_parameters(state_required: bool, weight: Weight) -> MessageProofParameters {
 		MessageProofParameters {
 			outbound_state_proof_required: state_required,
@@ -652,14 +853,16 @@ mod tests {
 
 	#[test]
 	fn weights_map_works_as_nonces_range() {
-		fn build_map(range: RangeInclusive<MessageNonce>) -> MessageWeightsMap {
+		fn build_map(range: RangeInclusive<MessageNonce>) -> MessageDetailsMap<TestSourceChainBalance> {
 			range
 				.map(|idx| {
 					(
 						idx,
-						MessageWeights {
-							weight: idx,
+						MessageDetails {
+							dispatch_weight: idx,
 							size: idx as _,
+							reward: idx,
+							is_wanted: true,
 						},
 					)
 				})
@@ -689,6 +892,46 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn message_delivery_strategy_accounts_messages_in_flight() {
+		let (mut state, mut strategy) = prepare_strategy();
+
+		// first transaction delivers two messages
+		strategy.max_messages_in_single_batch = 2;
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=21), proof_parameters(false, 2)))
+		);
+		state.nonces_submitted.push_back(20..=21);
+
+		// messages of the first transaction are not yet confirmed, so the second transaction
+		// may only deliver single message
+		strategy.max_messages_in_single_batch = 4;
+		strategy.max_unconfirmed_nonces_at_target = 3;
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((22..=22), proof_parameters(false, 1)))
+		);
+	}
+
+	#[test]
+	fn message_delivery_strategy_uses_reloaded_max_transactions_in_flight() {
+		let (mut state, mut strategy) = prepare_strategy();
+		strategy.max_messages_in_single_batch = 2;
+		state.nonces_submitted.push_back(20..=21);
+
+		// single transaction is already in flight
+		strategy.max_transactions_in_flight.set(1);
+		assert_eq!(strategy.select_nonces_to_deliver(&state), None);
+
+		// the limit is increased while the race is running
+		strategy.max_transactions_in_flight.set(2);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((22..=23), proof_parameters(false, 2)))
+		);
+	}
+
 	#[test]
 	fn message_delivery_strategy_selects_nothing_if_too_many_confirmations_missing() {
 		let (state, mut strategy) = prepare_strategy();
@@ -786,7 +1029,11 @@ mod tests {
 		let (state, mut strategy) = prepare_strategy();
 
 		// first message doesn't fit in the batch, because it has weight (10) that overflows max weight (4)
-		strategy.strategy.source_queue_mut()[0].1.get_mut(&20).unwrap().weight = 10;
+		strategy.strategy.source_queue_mut()[0]
+			.1
+			.get_mut(&20)
+			.unwrap()
+			.dispatch_weight = 10;
 		assert_eq!(
 			strategy.select_nonces_to_deliver(&state),
 			Some(((20..=20), proof_parameters(false, 10)))
@@ -817,6 +1064,135 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn message_delivery_strategy_accounts_per_message_weight_overhead() {
+		let (state, mut strategy) = prepare_strategy();
+
+		// every message has dispatch weight 1 and adds 1 to the transaction weight => only 2 messages
+		// fit in the batch with max weight 4
+		strategy.per_message_weight_overhead = 1;
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=21), proof_parameters(false, 2)))
+		);
+	}
+
+	#[test]
+	fn message_delivery_strategy_accounts_per_message_size_overhead() {
+		let (state, mut strategy) = prepare_strategy();
+
+		// every message has size 1 and adds 1 to the transaction size => only 2 messages
+		// fit in the batch with max size 4
+		strategy.per_message_size_overhead = 1;
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=21), proof_parameters(false, 2)))
+		);
+	}
+
+	fn prepare_rational_strategy(rewards: [TestSourceChainBalance; 4]) -> (TestRaceState, TestStrategy) {
+		let (state, mut strategy) = prepare_strategy();
+
+		// every message has dispatch weight 1 and the delivery transaction itself has weight 1, so
+		// the cost of delivering N messages is N + 1
+		strategy.relayer_mode.set(RelayerMode::Rational);
+		strategy.delivery_transaction_base_weight = 1;
+		strategy.delivery_cost_estimator = Arc::new(TestDeliveryCostEstimator { fee_per_weight_unit: 1 });
+		for (nonce, reward) in (20..=23).zip(rewards.iter()) {
+			strategy.strategy.source_queue_mut()[0]
+				.1
+				.get_mut(&nonce)
+				.unwrap()
+				.reward = *reward;
+		}
+		(state, strategy)
+	}
+
+	#[test]
+	fn rational_message_delivery_strategy_selects_all_messages_if_they_are_equally_profitable() {
+		// the base transaction cost is spread among all messages => the more, the better
+		let (state, mut strategy) = prepare_rational_strategy([10, 10, 10, 10]);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=23), proof_parameters(false, 4)))
+		);
+	}
+
+	#[test]
+	fn rational_message_delivery_strategy_skips_unprofitable_tail_messages() {
+		// messages 22 and 23 bring no reward => delivering them only increases transaction cost
+		let (state, mut strategy) = prepare_rational_strategy([10, 10, 0, 0]);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=21), proof_parameters(false, 2)))
+		);
+	}
+
+	#[test]
+	fn rational_message_delivery_strategy_delivers_unprofitable_messages_if_followed_by_profitable() {
+		// messages 21 and 22 bring no reward, but message 23 compensates that
+		let (state, mut strategy) = prepare_rational_strategy([10, 0, 0, 100]);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=23), proof_parameters(false, 4)))
+		);
+	}
+
+	#[test]
+	fn rational_message_delivery_strategy_selects_nothing_if_delivery_is_loss_making() {
+		// every batch costs more than it brings: N + 1 > N
+		let (state, mut strategy) = prepare_rational_strategy([1, 1, 1, 1]);
+		assert_eq!(strategy.select_nonces_to_deliver(&state), None);
+
+		// while altruistic relayer delivers all messages anyway
+		strategy.relayer_mode.set(RelayerMode::Altruistic);
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=23), proof_parameters(false, 4)))
+		);
+	}
+
+	#[test]
+	fn rational_message_delivery_strategy_accounts_delivery_cost_in_source_tokens() {
+		// rewards are enough to cover the weight, but not when target chain fee is 10 source tokens
+		// per weight unit
+		let (state, mut strategy) = prepare_rational_strategy([10, 10, 10, 10]);
+		strategy.delivery_cost_estimator = Arc::new(TestDeliveryCostEstimator {
+			fee_per_weight_unit: 10,
+		});
+		assert_eq!(strategy.select_nonces_to_deliver(&state), None);
+	}
+
+	#[test]
+	fn message_delivery_strategy_selects_nothing_if_there_are_no_wanted_messages() {
+		let (state, mut strategy) = prepare_strategy();
+
+		// relayer is not interested in any of queued messages
+		for details in strategy.strategy.source_queue_mut()[0].1.values_mut() {
+			details.is_wanted = false;
+		}
+		assert_eq!(strategy.select_nonces_to_deliver(&state), None);
+	}
+
+	#[test]
+	fn message_delivery_strategy_delivers_unwanted_messages_that_precede_wanted_message() {
+		let (state, mut strategy) = prepare_strategy();
+
+		// relayer is only interested in message 22, but it must deliver messages 20 and 21 first
+		for details in strategy.strategy.source_queue_mut()[0].1.values_mut() {
+			details.is_wanted = false;
+		}
+		strategy.strategy.source_queue_mut()[0]
+			.1
+			.get_mut(&22)
+			.unwrap()
+			.is_wanted = true;
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((20..=22), proof_parameters(false, 3)))
+		);
+	}
+
 	#[test]
 	fn message_delivery_strategy_limits_batch_by_messages_count_when_there_is_upper_limit() {
 		let (state, mut strategy) = prepare_strategy();
@@ -28,8 +28,9 @@ use futures::{
 	future::FutureExt,
 	stream::{FusedStream, StreamExt},
 };
-use relay_utils::{process_future_result, retry_backoff, FailedClient, MaybeConnectionError};
+use relay_utils::{control::LoopControl, process_future_result, retry_backoff, FailedClient, MaybeConnectionError};
 use std::{
+	collections::VecDeque,
 	fmt::Debug,
 	ops::RangeInclusive,
 	time::{Duration, Instant},
@@ -202,8 +203,12 @@ pub struct RaceState<SourceHeaderId, TargetHeaderId, Proof> {
 	pub best_finalized_target_header_id: Option<TargetHeaderId>,
 	/// Range of nonces that we have selected to submit.
 	pub nonces_to_submit: Option<(SourceHeaderId, RangeInclusive<MessageNonce>, Proof)>,
-	/// Range of nonces that is currently submitted.
-	pub nonces_submitted: Option<RangeInclusive<MessageNonce>>,
+	/// Ranges of nonces that are currently submitted, but not yet accepted by the target node.
+	///
+	/// Ranges are ordered by nonce and every next range starts right after the previous one ends.
+	/// There may be several ranges (submitted by consecutive transactions of the same signer), if
+	/// the strategy allows multiple transactions in flight.
+	pub nonces_submitted: VecDeque<RangeInclusive<MessageNonce>>,
 }
 
 /// Run race loop until connection with target or source node is lost.
@@ -213,6 +218,7 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 	race_target: TC,
 	race_target_updated: impl FusedStream<Item = TargetClientState<P>>,
 	stall_timeout: Duration,
+	control: LoopControl,
 	mut strategy: impl RaceStrategy<
 		P::SourceHeaderId,
 		P::TargetHeaderId,
@@ -387,6 +393,7 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 				).fail_if_connection_error(FailedClient::Source)?;
 			},
 			proof_submit_result = target_submit_proof => {
+				relay_utils::alerts::note_transaction_result(proof_submit_result.is_ok());
 				target_client_is_online = process_future_result(
 					proof_submit_result,
 					&mut target_retry_backoff,
@@ -399,7 +406,7 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 						);
 
 						race_state.nonces_to_submit = None;
-						race_state.nonces_submitted = Some(nonces_range);
+						race_state.nonces_submitted.push_back(nonces_range);
 						stall_countdown = Instant::now();
 					},
 					&mut target_go_offline_future,
@@ -418,6 +425,16 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 		}
 
 		progress_context = print_race_progress::<P, _>(progress_context, &strategy);
+		control.set_state(
+			&format!("{} -> {}", P::source_name(), P::target_name()),
+			format!("State: {:?}. Strategy: {:?}", race_state, strategy),
+		);
+
+		// paused race doesn't make any progress, so it can't stall
+		let is_paused = control.is_paused();
+		if is_paused {
+			stall_countdown = Instant::now();
+		}
 
 		if stall_countdown.elapsed() > stall_timeout {
 			log::warn!(
@@ -430,7 +447,7 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 			);
 
 			return Err(FailedClient::Both);
-		} else if race_state.nonces_to_submit.is_none() && race_state.nonces_submitted.is_none() && strategy.is_empty()
+		} else if race_state.nonces_to_submit.is_none() && race_state.nonces_submitted.is_empty() && strategy.is_empty()
 		{
 			stall_countdown = Instant::now();
 		}
@@ -438,7 +455,11 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 		if source_client_is_online {
 			source_client_is_online = false;
 
-			let nonces_to_deliver = select_nonces_to_deliver(&race_state, &mut strategy);
+			let nonces_to_deliver = if is_paused {
+				None
+			} else {
+				select_nonces_to_deliver(&race_state, &mut strategy)
+			};
 			let best_at_source = strategy.best_at_source();
 
 			if let Some((at_block, nonces_range, proof_parameters)) = nonces_to_deliver {
@@ -477,7 +498,8 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 		if target_client_is_online {
 			target_client_is_online = false;
 
-			if let Some((at_block, nonces_range, proof)) = race_state.nonces_to_submit.as_ref() {
+			let nonces_to_submit = race_state.nonces_to_submit.as_ref().filter(|_| !is_paused);
+			if let Some((at_block, nonces_range, proof)) = nonces_to_submit {
 				log::debug!(
 					target: "bridge",
 					"Going to submit proof of messages in range {:?} to {} node",
@@ -523,7 +545,7 @@ impl<SourceHeaderId, TargetHeaderId, Proof> Default for RaceState<SourceHeaderId
 			best_target_header_id: None,
 			best_finalized_target_header_id: None,
 			nonces_to_submit: None,
-			nonces_submitted: None,
+			nonces_submitted: VecDeque::new(),
 		}
 	}
 }
@@ -598,7 +620,7 @@ mod tests {
 			best_target_header_id: Some(HeaderId(0, 0)),
 			best_finalized_target_header_id: Some(HeaderId(0, 0)),
 			nonces_to_submit: None,
-			nonces_submitted: None,
+			nonces_submitted: VecDeque::new(),
 		};
 
 		// we have some nonces to deliver and they're generated at GENERATED_AT < BEST_AT_SOURCE
@@ -15,19 +15,23 @@
 
 use crate::message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf};
 use crate::message_lane_loop::{
-	SourceClient as MessageLaneSourceClient, SourceClientState, TargetClient as MessageLaneTargetClient,
-	TargetClientState,
+	MessageReceivingParams, SourceClient as MessageLaneSourceClient, SourceClientState,
+	TargetClient as MessageLaneTargetClient, TargetClientState,
 };
 use crate::message_race_loop::{
-	MessageRace, NoncesRange, SourceClient, SourceClientNonces, TargetClient, TargetClientNonces,
+	MessageRace, NoncesRange, RaceState, RaceStrategy, SourceClient, SourceClientNonces, TargetClient,
+	TargetClientNonces,
 };
 use crate::message_race_strategy::BasicStrategy;
 use crate::metrics::MessageLaneLoopMetrics;
 
 use async_trait::async_trait;
-use bp_messages::MessageNonce;
+use bp_messages::{nonces::DeliveredMessages, MessageNonce};
 use futures::stream::FusedStream;
-use relay_utils::FailedClient;
+use relay_utils::{
+	control::{LoopControl, Reloadable},
+	FailedClient,
+};
 use std::{marker::PhantomData, ops::RangeInclusive, time::Duration};
 
 /// Message receiving confirmations delivery strategy.
@@ -47,7 +51,9 @@ pub async fn run<P: MessageLane>(
 	target_client: impl MessageLaneTargetClient<P>,
 	target_state_updates: impl FusedStream<Item = TargetClientState<P>>,
 	stall_timeout: Duration,
+	control: LoopControl,
 	metrics_msg: Option<MessageLaneLoopMetrics>,
+	params: MessageReceivingParams,
 ) -> Result<(), FailedClient> {
 	crate::message_race_loop::run(
 		ReceivingConfirmationsRaceSource {
@@ -63,7 +69,13 @@ pub async fn run<P: MessageLane>(
 		},
 		source_state_updates,
 		stall_timeout,
-		ReceivingConfirmationsBasicStrategy::<P>::new(),
+		control,
+		ReceivingConfirmationsStrategy::<P> {
+			max_confirmations_delay: params.max_confirmations_delay,
+			max_unconfirmed_nonces_at_target: params.max_unconfirmed_nonces_at_target,
+			confirmations_pending_since: None,
+			strategy: ReceivingConfirmationsBasicStrategy::<P>::new(),
+		},
 	)
 	.await
 }
@@ -196,6 +208,150 @@ where
 	}
 }
 
+/// Message receiving confirmations delivery strategy.
+///
+/// Confirmations may be delayed for up to `max_confirmations_delay` source chain blocks, so that
+/// several delivered ranges are confirmed by a single transaction.
+struct ReceivingConfirmationsStrategy<P: MessageLane> {
+	/// Maximal number of source chain blocks for which the confirmations delivery may be delayed.
+	max_confirmations_delay: Reloadable<u32>,
+	/// Confirmations are never delayed if there are that many unconfirmed messages at the target chain.
+	max_unconfirmed_nonces_at_target: MessageNonce,
+	/// Number of the best source header at the moment when we have seen undelivered confirmations.
+	confirmations_pending_since: Option<P::SourceHeaderNumber>,
+	/// Basic confirmations delivery strategy.
+	strategy: ReceivingConfirmationsBasicStrategy<P>,
+}
+
+impl<P: MessageLane> std::fmt::Debug for ReceivingConfirmationsStrategy<P> {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		fmt.debug_struct("ReceivingConfirmationsStrategy")
+			.field("max_confirmations_delay", &self.max_confirmations_delay)
+			.field(
+				"max_unconfirmed_nonces_at_target",
+				&self.max_unconfirmed_nonces_at_target,
+			)
+			.field("confirmations_pending_since", &self.confirmations_pending_since)
+			.field("strategy", &self.strategy)
+			.finish()
+	}
+}
+
+impl<P: MessageLane> ReceivingConfirmationsStrategy<P> {
+	/// Returns true if we may deliver confirmations right now.
+	fn is_confirmations_delay_elapsed(
+		&mut self,
+		race_state: &RaceState<TargetHeaderIdOf<P>, SourceHeaderIdOf<P>, P::MessagesReceivingProof>,
+	) -> bool {
+		let max_confirmations_delay: P::SourceHeaderNumber = self.max_confirmations_delay.get().into();
+		if max_confirmations_delay == 0u32.into() {
+			return true;
+		}
+
+		// if there's nothing to deliver, the basic strategy won't select anything anyway
+		if self.strategy.is_empty() || race_state.nonces_to_submit.is_some() || !race_state.nonces_submitted.is_empty()
+		{
+			return true;
+		}
+
+		// if the delivery race is blocked by unconfirmed messages, we shall not delay confirmations
+		let unconfirmed_nonces = match (self.strategy.best_at_source(), self.strategy.best_at_target()) {
+			(Some(best_at_source), Some(best_at_target)) => best_at_source.saturating_sub(best_at_target),
+			_ => return true,
+		};
+		if unconfirmed_nonces >= self.max_unconfirmed_nonces_at_target {
+			return true;
+		}
+
+		let best_source_header_number = match race_state.best_target_header_id.as_ref() {
+			Some(best_source_header_id) => best_source_header_id.0,
+			None => return true,
+		};
+		let confirmations_pending_since = *self
+			.confirmations_pending_since
+			.get_or_insert(best_source_header_number);
+		let is_delay_elapsed = best_source_header_number >= confirmations_pending_since
+			&& best_source_header_number - confirmations_pending_since >= max_confirmations_delay;
+		if !is_delay_elapsed {
+			log::trace!(
+				target: "bridge",
+				"Delaying {} -> {} confirmations: pending since {} header {}, best {} header is {}",
+				P::TARGET_NAME,
+				P::SOURCE_NAME,
+				P::SOURCE_NAME,
+				confirmations_pending_since,
+				P::SOURCE_NAME,
+				best_source_header_number,
+			);
+		}
+
+		is_delay_elapsed
+	}
+}
+
+impl<P: MessageLane> RaceStrategy<TargetHeaderIdOf<P>, SourceHeaderIdOf<P>, P::MessagesReceivingProof>
+	for ReceivingConfirmationsStrategy<P>
+{
+	type SourceNoncesRange = RangeInclusive<MessageNonce>;
+	type ProofParameters = ();
+	type TargetNoncesData = ();
+
+	fn is_empty(&self) -> bool {
+		self.strategy.is_empty()
+	}
+
+	fn required_source_header_at_target(&self, current_best: &TargetHeaderIdOf<P>) -> Option<TargetHeaderIdOf<P>> {
+		self.strategy.required_source_header_at_target(current_best)
+	}
+
+	fn best_at_source(&self) -> Option<MessageNonce> {
+		self.strategy.best_at_source()
+	}
+
+	fn best_at_target(&self) -> Option<MessageNonce> {
+		self.strategy.best_at_target()
+	}
+
+	fn source_nonces_updated(
+		&mut self,
+		at_block: TargetHeaderIdOf<P>,
+		nonces: SourceClientNonces<Self::SourceNoncesRange>,
+	) {
+		self.strategy.source_nonces_updated(at_block, nonces)
+	}
+
+	fn best_target_nonces_updated(
+		&mut self,
+		nonces: TargetClientNonces<()>,
+		race_state: &mut RaceState<TargetHeaderIdOf<P>, SourceHeaderIdOf<P>, P::MessagesReceivingProof>,
+	) {
+		self.strategy.best_target_nonces_updated(nonces, race_state)
+	}
+
+	fn finalized_target_nonces_updated(
+		&mut self,
+		nonces: TargetClientNonces<()>,
+		race_state: &mut RaceState<TargetHeaderIdOf<P>, SourceHeaderIdOf<P>, P::MessagesReceivingProof>,
+	) {
+		self.strategy.finalized_target_nonces_updated(nonces, race_state)
+	}
+
+	fn select_nonces_to_deliver(
+		&mut self,
+		race_state: &RaceState<TargetHeaderIdOf<P>, SourceHeaderIdOf<P>, P::MessagesReceivingProof>,
+	) -> Option<(RangeInclusive<MessageNonce>, Self::ProofParameters)> {
+		if !self.is_confirmations_delay_elapsed(race_state) {
+			return None;
+		}
+
+		let selected_nonces = self.strategy.select_nonces_to_deliver(race_state);
+		if selected_nonces.is_some() {
+			self.confirmations_pending_since = None;
+		}
+		selected_nonces
+	}
+}
+
 impl NoncesRange for RangeInclusive<MessageNonce> {
 	fn begin(&self) -> MessageNonce {
 		*RangeInclusive::<MessageNonce>::start(self)
@@ -206,19 +362,85 @@ impl NoncesRange for RangeInclusive<MessageNonce> {
 	}
 
 	fn greater_than(self, nonce: MessageNonce) -> Option<Self> {
-		let next_nonce = nonce + 1;
-		let end = *self.end();
-		if next_nonce > end {
-			None
-		} else {
-			Some(std::cmp::max(self.begin(), next_nonce)..=end)
-		}
+		DeliveredMessages::from_range(&self)
+			.and_then(|nonces| nonces.greater_than(nonce))
+			.map(Into::into)
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::message_lane_loop::tests::{header_id, TestMessageLane};
+	use relay_utils::HeaderId;
+
+	fn prepare_strategy(
+		max_confirmations_delay: u32,
+	) -> (
+		RaceState<TargetHeaderIdOf<TestMessageLane>, SourceHeaderIdOf<TestMessageLane>, MessageNonce>,
+		ReceivingConfirmationsStrategy<TestMessageLane>,
+	) {
+		let mut race_state = RaceState {
+			best_finalized_source_header_id_at_source: Some(HeaderId(1, 1)),
+			best_finalized_source_header_id_at_best_target: Some(HeaderId(1, 1)),
+			best_target_header_id: Some(header_id(1)),
+			best_finalized_target_header_id: Some(header_id(1)),
+			nonces_to_submit: None,
+			nonces_submitted: Default::default(),
+		};
+
+		let mut strategy = ReceivingConfirmationsStrategy::<TestMessageLane> {
+			max_confirmations_delay: Reloadable::new(max_confirmations_delay),
+			max_unconfirmed_nonces_at_target: 10,
+			confirmations_pending_since: None,
+			strategy: ReceivingConfirmationsBasicStrategy::<TestMessageLane>::new(),
+		};
+		strategy.strategy.source_queue_mut().push_back((HeaderId(1, 1), 1..=5));
+		strategy.best_target_nonces_updated(
+			TargetClientNonces {
+				latest_nonce: 0,
+				nonces_data: (),
+			},
+			&mut race_state,
+		);
+
+		(race_state, strategy)
+	}
+
+	#[test]
+	fn receiving_confirmations_strategy_does_not_delay_confirmations_by_default() {
+		let (race_state, mut strategy) = prepare_strategy(0);
+		assert_eq!(strategy.select_nonces_to_deliver(&race_state), Some((1..=5, ())));
+	}
+
+	#[test]
+	fn receiving_confirmations_strategy_delays_confirmations() {
+		let (mut race_state, mut strategy) = prepare_strategy(3);
+		assert_eq!(strategy.select_nonces_to_deliver(&race_state), None);
+
+		race_state.best_target_header_id = Some(header_id(3));
+		assert_eq!(strategy.select_nonces_to_deliver(&race_state), None);
+
+		race_state.best_target_header_id = Some(header_id(4));
+		assert_eq!(strategy.select_nonces_to_deliver(&race_state), Some((1..=5, ())));
+		assert_eq!(strategy.confirmations_pending_since, None);
+	}
+
+	#[test]
+	fn receiving_confirmations_strategy_uses_reloaded_max_confirmations_delay() {
+		let (race_state, mut strategy) = prepare_strategy(3);
+		assert_eq!(strategy.select_nonces_to_deliver(&race_state), None);
+
+		strategy.max_confirmations_delay.set(0);
+		assert_eq!(strategy.select_nonces_to_deliver(&race_state), Some((1..=5, ())));
+	}
+
+	#[test]
+	fn receiving_confirmations_strategy_does_not_delay_confirmations_when_delivery_is_blocked() {
+		let (race_state, mut strategy) = prepare_strategy(3);
+		strategy.max_unconfirmed_nonces_at_target = 5;
+		assert_eq!(strategy.select_nonces_to_deliver(&race_state), Some((1..=5, ())));
+	}
 
 	#[test]
 	fn range_inclusive_works_as_nonces_range() {
@@ -232,5 +454,6 @@ mod tests {
 		assert_eq!(range.clone().greater_than(25), Some(26..=30));
 		assert_eq!(range.clone().greater_than(29), Some(30..=30));
 		assert_eq!(range.greater_than(30), None);
+		assert_eq!((0..=MessageNonce::MAX).greater_than(MessageNonce::MAX), None);
 	}
 }
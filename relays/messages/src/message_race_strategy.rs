@@ -19,7 +19,10 @@
 
 use crate::message_race_loop::{NoncesRange, RaceState, RaceStrategy, SourceClientNonces, TargetClientNonces};
 
-use bp_messages::MessageNonce;
+use bp_messages::{
+	nonces::{find_gaps, DeliveredMessages},
+	MessageNonce,
+};
 use relay_utils::HeaderId;
 use std::{collections::VecDeque, fmt::Debug, marker::PhantomData, ops::RangeInclusive};
 
@@ -37,6 +40,9 @@ pub struct BasicStrategy<
 	source_queue: VecDeque<(HeaderId<SourceHeaderHash, SourceHeaderNumber>, SourceNoncesRange)>,
 	/// Best nonce known to target node (at its best block). `None` if it has not been received yet.
 	best_target_nonce: Option<MessageNonce>,
+	/// Maximal number of submitted transactions that may be in flight (not yet accepted by the target
+	/// node) at the same time.
+	max_transactions_in_flight: usize,
 	/// Unused generic types dump.
 	_phantom: PhantomData<(TargetHeaderNumber, TargetHeaderHash, Proof)>,
 }
@@ -48,15 +54,38 @@ where
 	SourceHeaderNumber: Clone + Ord,
 	SourceNoncesRange: NoncesRange,
 {
-	/// Create new delivery strategy.
+	/// Create new delivery strategy that only submits next transaction when the previous one is accepted
+	/// by the target node.
 	pub fn new() -> Self {
+		Self::with_max_transactions_in_flight(1)
+	}
+
+	/// Create new delivery strategy that may have up to `max_transactions_in_flight` submitted
+	/// transactions, that are not yet accepted by the target node.
+	///
+	/// Every transaction delivers its own range of nonces, starting right after the range of the
+	/// previous transaction. Transactions are signed by the same signer, so they're mined in order.
+	pub fn with_max_transactions_in_flight(max_transactions_in_flight: usize) -> Self {
 		BasicStrategy {
 			source_queue: VecDeque::new(),
 			best_target_nonce: None,
+			max_transactions_in_flight: std::cmp::max(max_transactions_in_flight, 1),
 			_phantom: Default::default(),
 		}
 	}
 
+	/// Change maximal number of submitted transactions that may be in flight at the same time.
+	pub(crate) fn set_max_transactions_in_flight(&mut self, max_transactions_in_flight: usize) {
+		self.max_transactions_in_flight = std::cmp::max(max_transactions_in_flight, 1);
+	}
+
+	/// Reference to source queue.
+	pub(crate) fn source_queue(
+		&self,
+	) -> &VecDeque<(HeaderId<SourceHeaderHash, SourceHeaderNumber>, SourceNoncesRange)> {
+		&self.source_queue
+	}
+
 	/// Mutable reference to source queue to use in tests.
 	#[cfg(test)]
 	pub(crate) fn source_queue_mut(
@@ -65,6 +94,37 @@ where
 		&mut self.source_queue
 	}
 
+	/// Remove nonces that are already submitted to the target node from the source queue.
+	///
+	/// Submitted nonces may be (re)queued by the `source_nonces_updated`, because the target node
+	/// is not yet aware of them.
+	pub(crate) fn remove_submitted_nonces_from_source_queue(
+		&mut self,
+		race_state: &RaceState<
+			HeaderId<SourceHeaderHash, SourceHeaderNumber>,
+			HeaderId<TargetHeaderHash, TargetHeaderNumber>,
+			Proof,
+		>,
+	) {
+		if let Some(best_submitted_nonce) = race_state.nonces_submitted.back().map(|nonces| *nonces.end()) {
+			self.remove_le_nonces_from_source_queue(best_submitted_nonce);
+		}
+	}
+
+	/// Remove all nonces that are less than or equal to given nonce from the source queue.
+	fn remove_le_nonces_from_source_queue(&mut self, nonce: MessageNonce) {
+		while let Some(true) = self.source_queue.front().map(|(_, range)| range.begin() <= nonce) {
+			let maybe_subrange = self
+				.source_queue
+				.pop_front()
+				.and_then(|(at_block, range)| range.greater_than(nonce).map(|subrange| (at_block, subrange)));
+			if let Some((at_block, subrange)) = maybe_subrange {
+				self.source_queue.push_front((at_block, subrange));
+				break;
+			}
+		}
+	}
+
 	/// Should return `Some(nonces)` if we need to deliver proof of `nonces` (and associated
 	/// data) from source to target node.
 	///
@@ -90,12 +150,20 @@ where
 			return None;
 		}
 
-		// if we already submitted some nonces, do nothing
-		if race_state.nonces_submitted.is_some() {
+		// if we already have maximal number of transactions in flight, do nothing
+		if race_state.nonces_submitted.len() >= self.max_transactions_in_flight {
 			return None;
 		}
 
-		// 1) we want to deliver all nonces, starting from `target_nonce + 1`
+		// nonces that are already submitted, are delivered by transactions in flight
+		self.remove_submitted_nonces_from_source_queue(race_state);
+		let best_submitted_nonce = race_state
+			.nonces_submitted
+			.back()
+			.map(|nonces| std::cmp::max(*nonces.end(), target_nonce))
+			.unwrap_or(target_nonce);
+
+		// 1) we want to deliver all nonces, starting from `best_submitted_nonce + 1`
 		// 2) we can't deliver new nonce until header, that has emitted this nonce, is finalized
 		// by target client
 		// 3) selector is used for more complicated logic
@@ -117,10 +185,12 @@ where
 			// requeue (sub) range and update range to deliver
 			match range_to_requeue {
 				Some(range_to_requeue) => {
+					let is_queued_range_suffix = DeliveredMessages::new(queued_range_begin, queued_range_end)
+						.zip(DeliveredMessages::new(range_to_requeue.begin(), range_to_requeue.end()))
+						.map(|(queued, to_requeue)| queued.contains(&to_requeue) && queued.end() == to_requeue.end())
+						.unwrap_or(false);
 					assert!(
-						range_to_requeue.begin() <= range_to_requeue.end()
-							&& range_to_requeue.begin() >= queued_range_begin
-							&& range_to_requeue.end() == queued_range_end,
+						is_queued_range_suffix,
 						"Incorrect implementation of internal `selector` function. Expected original\
 						range {:?} to end with returned range {:?}",
 						queued_range_begin..=queued_range_end,
@@ -139,7 +209,7 @@ where
 			}
 		}
 
-		nonces_end.map(|nonces_end| RangeInclusive::new(target_nonce + 1, nonces_end))
+		nonces_end.map(|nonces_end| RangeInclusive::new(best_submitted_nonce + 1, nonces_end))
 	}
 }
 
@@ -195,13 +265,26 @@ where
 			.map(|(_, range)| range.end())
 			.or(self.best_target_nonce)
 			.unwrap_or_default();
-		self.source_queue.extend(
-			nonces
-				.new_nonces
-				.greater_than(best_in_queue)
-				.into_iter()
-				.map(move |range| (at_block.clone(), range)),
-		)
+		let new_nonces = nonces.new_nonces.greater_than(best_in_queue);
+
+		// if there's a gap between queued and new nonces, we won't be able to deliver new nonces
+		// until the gap is filled
+		if let Some(new_nonces) = new_nonces.as_ref() {
+			let known_nonces = DeliveredMessages::new(0, best_in_queue);
+			let new_nonces = DeliveredMessages::new(new_nonces.begin(), new_nonces.end());
+			let gaps = find_gaps(known_nonces.iter().chain(new_nonces.iter()));
+			if !gaps.is_empty() {
+				log::warn!(
+					target: "bridge",
+					"Source client has reported nonces with gaps: {:?}. Best queued nonce: {}",
+					gaps,
+					best_in_queue,
+				);
+			}
+		}
+
+		self.source_queue
+			.extend(new_nonces.into_iter().map(move |range| (at_block.clone(), range)))
 	}
 
 	fn best_target_nonces_updated(
@@ -221,16 +304,7 @@ where
 			}
 		}
 
-		while let Some(true) = self.source_queue.front().map(|(_, range)| range.begin() <= nonce) {
-			let maybe_subrange = self
-				.source_queue
-				.pop_front()
-				.and_then(|(at_block, range)| range.greater_than(nonce).map(|subrange| (at_block, subrange)));
-			if let Some((at_block, subrange)) = maybe_subrange {
-				self.source_queue.push_front((at_block, subrange));
-				break;
-			}
-		}
+		self.remove_le_nonces_from_source_queue(nonce);
 
 		let need_to_select_new_nonces = race_state
 			.nonces_to_submit
@@ -241,13 +315,8 @@ where
 			race_state.nonces_to_submit = None;
 		}
 
-		let need_new_nonces_to_submit = race_state
-			.nonces_submitted
-			.as_ref()
-			.map(|nonces| *nonces.end() <= nonce)
-			.unwrap_or(false);
-		if need_new_nonces_to_submit {
-			race_state.nonces_submitted = None;
+		while let Some(true) = race_state.nonces_submitted.front().map(|nonces| *nonces.end() <= nonce) {
+			race_state.nonces_submitted.pop_front();
 		}
 
 		self.best_target_nonce = Some(std::cmp::max(
@@ -389,11 +458,22 @@ mod tests {
 	fn submitted_nonces_are_dropped_on_target_nonce_update() {
 		let mut state = RaceState::default();
 		let mut strategy = BasicStrategy::<TestMessageLane>::new();
-		state.nonces_submitted = Some(5..=10);
+		state.nonces_submitted.push_back(5..=10);
+		strategy.best_target_nonces_updated(target_nonces(7), &mut state);
+		assert!(!state.nonces_submitted.is_empty());
+		strategy.best_target_nonces_updated(target_nonces(10), &mut state);
+		assert!(state.nonces_submitted.is_empty());
+	}
+
+	#[test]
+	fn submitted_nonces_are_dropped_one_by_one_on_target_nonce_update() {
+		let mut state = RaceState::default();
+		let mut strategy = BasicStrategy::<TestMessageLane>::with_max_transactions_in_flight(3);
+		state.nonces_submitted.extend(vec![1..=4, 5..=7, 8..=10]);
 		strategy.best_target_nonces_updated(target_nonces(7), &mut state);
-		assert!(state.nonces_submitted.is_some());
+		assert_eq!(state.nonces_submitted, vec![8..=10]);
 		strategy.best_target_nonces_updated(target_nonces(10), &mut state);
-		assert!(state.nonces_submitted.is_none());
+		assert!(state.nonces_submitted.is_empty());
 	}
 
 	#[test]
@@ -410,7 +490,7 @@ mod tests {
 	fn nothing_is_selected_if_something_is_already_submitted() {
 		let mut state = RaceState::default();
 		let mut strategy = BasicStrategy::<TestMessageLane>::new();
-		state.nonces_submitted = Some(1..=10);
+		state.nonces_submitted.push_back(1..=10);
 		strategy.best_target_nonces_updated(target_nonces(0), &mut state);
 		strategy.source_nonces_updated(header_id(1), source_nonces(1..=10));
 		assert_eq!(strategy.select_nonces_to_deliver(&state), None);
@@ -437,6 +517,47 @@ mod tests {
 		assert_eq!(strategy.select_nonces_to_deliver(&state), None);
 	}
 
+	#[test]
+	fn select_nonces_to_deliver_selects_nonces_after_submitted_nonces() {
+		let mut state = RaceState::<_, _, TestMessagesProof>::default();
+		let mut strategy = BasicStrategy::<TestMessageLane>::with_max_transactions_in_flight(2);
+		strategy.best_target_nonces_updated(target_nonces(0), &mut state);
+		strategy.source_nonces_updated(header_id(1), source_nonces(1..=6));
+		strategy.source_nonces_updated(header_id(2), source_nonces(7..=8));
+		strategy.source_nonces_updated(header_id(3), source_nonces(9..=10));
+
+		state.best_finalized_source_header_id_at_best_target = Some(header_id(1));
+		assert_eq!(strategy.select_nonces_to_deliver(&state), Some((1..=6, ())));
+		state.nonces_submitted.push_back(1..=6);
+
+		// second transaction may be submitted while the first one is in flight
+		state.best_finalized_source_header_id_at_best_target = Some(header_id(2));
+		assert_eq!(strategy.select_nonces_to_deliver(&state), Some((7..=8, ())));
+		state.nonces_submitted.push_back(7..=8);
+
+		// but the third one needs to wait until the first transaction is accepted
+		state.best_finalized_source_header_id_at_best_target = Some(header_id(3));
+		assert_eq!(strategy.select_nonces_to_deliver(&state), None);
+		strategy.best_target_nonces_updated(target_nonces(6), &mut state);
+		assert_eq!(strategy.select_nonces_to_deliver(&state), Some((9..=10, ())));
+	}
+
+	#[test]
+	fn select_nonces_to_deliver_ignores_requeued_submitted_nonces() {
+		let mut state = RaceState::<_, _, TestMessagesProof>::default();
+		let mut strategy = BasicStrategy::<TestMessageLane>::with_max_transactions_in_flight(2);
+		strategy.best_target_nonces_updated(target_nonces(0), &mut state);
+		strategy.source_nonces_updated(header_id(1), source_nonces(1..=6));
+
+		state.best_finalized_source_header_id_at_best_target = Some(header_id(2));
+		assert_eq!(strategy.select_nonces_to_deliver(&state), Some((1..=6, ())));
+		state.nonces_submitted.push_back(1..=6);
+
+		// target node is not yet aware of submitted nonces, so they're queued again
+		strategy.source_nonces_updated(header_id(2), source_nonces(1..=8));
+		assert_eq!(strategy.select_nonces_to_deliver(&state), Some((7..=8, ())));
+	}
+
 	#[test]
 	fn select_nonces_to_deliver_able_to_split_ranges_with_selector() {
 		let mut state = RaceState::<_, _, TestMessagesProof>::default();
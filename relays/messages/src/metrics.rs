@@ -20,7 +20,13 @@ use crate::message_lane::MessageLane;
 use crate::message_lane_loop::{SourceClientState, TargetClientState};
 
 use bp_messages::MessageNonce;
-use relay_utils::metrics::{metric_name, register, GaugeVec, Opts, PrometheusError, Registry, U64};
+use relay_utils::metrics::{
+	metric_name, register, Gauge, GaugeVec, ObservedLag, Opts, PrometheusError, Registry, F64, U64,
+};
+use std::{
+	sync::{Arc, Mutex},
+	time::Instant,
+};
 
 /// Message lane relay metrics.
 ///
@@ -32,6 +38,19 @@ pub struct MessageLaneLoopMetrics {
 	/// Lane state nonces: "source_latest_generated", "source_latest_confirmed",
 	/// "target_latest_received", "target_latest_confirmed".
 	lane_state_nonces: GaugeVec<U64>,
+	/// Number of finalized blocks that are not yet known to the peer chain - "source_to_target", "target_to_source".
+	finality_lag_blocks: GaugeVec<U64>,
+	/// Seconds since the oldest finalized block, that is not yet known to the peer chain, has been seen
+	/// - "source_to_target", "target_to_source".
+	finality_lag_seconds: GaugeVec<F64>,
+	/// Seconds since the oldest undelivered message has been seen at the source chain.
+	oldest_undelivered_message_age_seconds: Gauge<F64>,
+	/// Source -> Target finality lag.
+	source_to_target_finality_lag: Arc<Mutex<ObservedLag>>,
+	/// Target -> Source finality lag.
+	target_to_source_finality_lag: Arc<Mutex<ObservedLag>>,
+	/// Lag between latest generated and latest received nonces.
+	delivery_lag: Arc<Mutex<ObservedLag>>,
 }
 
 impl MessageLaneLoopMetrics {
@@ -55,6 +74,36 @@ impl MessageLaneLoopMetrics {
 				)?,
 				registry,
 			)?,
+			finality_lag_blocks: register(
+				GaugeVec::new(
+					Opts::new(
+						metric_name(prefix, "finality_lag_blocks"),
+						"Number of finalized blocks that are not yet known to the peer chain",
+					),
+					&["type"],
+				)?,
+				registry,
+			)?,
+			finality_lag_seconds: register(
+				GaugeVec::new(
+					Opts::new(
+						metric_name(prefix, "finality_lag_seconds"),
+						"Seconds since the oldest finalized block, that is not yet known to the peer chain, has been seen",
+					),
+					&["type"],
+				)?,
+				registry,
+			)?,
+			oldest_undelivered_message_age_seconds: register(
+				Gauge::new(
+					metric_name(prefix, "oldest_undelivered_message_age_seconds"),
+					"Seconds since the oldest undelivered message has been seen at the source chain",
+				)?,
+				registry,
+			)?,
+			source_to_target_finality_lag: Arc::new(Mutex::new(ObservedLag::default())),
+			target_to_source_finality_lag: Arc::new(Mutex::new(ObservedLag::default())),
+			delivery_lag: Arc::new(Mutex::new(ObservedLag::default())),
 		})
 	}
 }
@@ -68,6 +117,15 @@ impl MessageLaneLoopMetrics {
 		self.best_block_numbers
 			.with_label_values(&["target_at_source"])
 			.set(source_client_state.best_finalized_peer_at_best_self.0.into());
+
+		let now = Instant::now();
+		update_lag(&self.source_to_target_finality_lag, |lag| {
+			lag.update_source(source_client_state.best_finalized_self.0.into(), now)
+		});
+		update_lag(&self.target_to_source_finality_lag, |lag| {
+			lag.update_target(source_client_state.best_finalized_peer_at_best_self.0.into())
+		});
+		self.update_finality_lag_metrics(now);
 	}
 
 	/// Update target client state metrics.
@@ -78,6 +136,15 @@ impl MessageLaneLoopMetrics {
 		self.best_block_numbers
 			.with_label_values(&["source_at_target"])
 			.set(target_client_state.best_finalized_peer_at_best_self.0.into());
+
+		let now = Instant::now();
+		update_lag(&self.target_to_source_finality_lag, |lag| {
+			lag.update_source(target_client_state.best_finalized_self.0.into(), now)
+		});
+		update_lag(&self.source_to_target_finality_lag, |lag| {
+			lag.update_target(target_client_state.best_finalized_peer_at_best_self.0.into())
+		});
+		self.update_finality_lag_metrics(now);
 	}
 
 	/// Update latest generated nonce at source.
@@ -85,6 +152,12 @@ impl MessageLaneLoopMetrics {
 		self.lane_state_nonces
 			.with_label_values(&["source_latest_generated"])
 			.set(source_latest_generated_nonce);
+
+		let now = Instant::now();
+		update_lag(&self.delivery_lag, |lag| {
+			lag.update_source(source_latest_generated_nonce, now)
+		});
+		self.update_delivery_lag_metrics(now);
 	}
 
 	/// Update latest confirmed nonce at source.
@@ -99,6 +172,11 @@ impl MessageLaneLoopMetrics {
 		self.lane_state_nonces
 			.with_label_values(&["target_latest_received"])
 			.set(target_latest_generated_nonce);
+
+		update_lag(&self.delivery_lag, |lag| {
+			lag.update_target(target_latest_generated_nonce)
+		});
+		self.update_delivery_lag_metrics(Instant::now());
 	}
 
 	/// Update latest confirmed nonce at target.
@@ -107,4 +185,32 @@ impl MessageLaneLoopMetrics {
 			.with_label_values(&["target_latest_confirmed"])
 			.set(target_latest_confirmed_nonce);
 	}
+
+	/// Update finality lag metrics of both directions.
+	fn update_finality_lag_metrics(&self, now: Instant) {
+		for (label, lag) in &[
+			("source_to_target", &self.source_to_target_finality_lag),
+			("target_to_source", &self.target_to_source_finality_lag),
+		] {
+			update_lag(lag, |lag| {
+				self.finality_lag_blocks.with_label_values(&[*label]).set(lag.lag());
+				self.finality_lag_seconds
+					.with_label_values(&[*label])
+					.set(lag.lag_duration(now).as_secs_f64());
+			});
+		}
+	}
+
+	/// Update undelivered messages age metric.
+	fn update_delivery_lag_metrics(&self, now: Instant) {
+		update_lag(&self.delivery_lag, |lag| {
+			self.oldest_undelivered_message_age_seconds
+				.set(lag.lag_duration(now).as_secs_f64())
+		});
+	}
+}
+
+/// Update observed lag.
+fn update_lag(lag: &Mutex<ObservedLag>, update: impl FnOnce(&mut ObservedLag)) {
+	update(&mut lag.lock().expect("metrics mutex is never poisoned; qed"))
 }
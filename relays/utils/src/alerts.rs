@@ -0,0 +1,297 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Alerts on critical relay conditions.
+//!
+//! Metrics alone require external alerting infrastructure. So the relay may also notify its
+//! operator directly, by sending alerts to the configured webhook. Every alert is also logged, even
+//! if webhook is not configured.
+//!
+//! Alerts are process-wide: they're configured once (using `initialize_alerts`) and then may be
+//! fired from any relay component.
+
+use crate::initialize::LOOP_NAME;
+
+use once_cell::sync::{Lazy, OnceCell};
+use std::{
+	collections::HashMap,
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+/// Default interval between two alerts with the same key.
+pub const DEFAULT_REPEAT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// URL of the PagerDuty Events API v2.
+pub const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Format of alerts, sent to the webhook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookFormat {
+	/// JSON object with `kind`, `subject` and `message` fields.
+	Generic,
+	/// Slack incoming webhook message.
+	Slack,
+	/// PagerDuty Events API v2 event.
+	PagerDuty,
+}
+
+/// Alerts configuration.
+#[derive(Debug, Clone)]
+pub struct AlertsParams {
+	/// URL of the webhook. If `None`, alerts are only logged.
+	pub webhook: Option<String>,
+	/// Format of alerts, sent to the webhook.
+	pub format: WebhookFormat,
+	/// PagerDuty integration (routing) key. Required if `format` is `WebhookFormat::PagerDuty`.
+	pub pagerduty_routing_key: Option<String>,
+	/// Minimal interval between two alerts with the same key.
+	pub repeat_interval: Duration,
+	/// Fire alert if the finality relay hasn't synced any headers for this period, while there
+	/// are headers to sync. `None` disables the alert.
+	pub finality_stall_timeout: Option<Duration>,
+	/// Fire alert if given number of consecutive transactions, submitted by the same relay loop, have failed.
+	/// Zero disables the alert.
+	pub max_transaction_failures: u32,
+}
+
+impl Default for AlertsParams {
+	fn default() -> Self {
+		AlertsParams {
+			webhook: None,
+			format: WebhookFormat::Generic,
+			pagerduty_routing_key: None,
+			repeat_interval: DEFAULT_REPEAT_INTERVAL,
+			finality_stall_timeout: None,
+			max_transaction_failures: 0,
+		}
+	}
+}
+
+/// Kind of critical relay condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertKind {
+	/// Relayer account balance is below the threshold.
+	LowBalance,
+	/// Bridge pallet is halted on-chain.
+	BridgeHalted,
+	/// Finality relay has made no progress for too long.
+	FinalityStalled,
+	/// Several consecutive transactions have failed.
+	TransactionFailures,
+}
+
+impl AlertKind {
+	/// Returns name of the alert kind.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			AlertKind::LowBalance => "low_balance",
+			AlertKind::BridgeHalted => "bridge_halted",
+			AlertKind::FinalityStalled => "finality_stalled",
+			AlertKind::TransactionFailures => "transaction_failures",
+		}
+	}
+}
+
+/// Alerts configuration.
+static ALERTS_PARAMS: OnceCell<AlertsParams> = OnceCell::new();
+/// Time when alert with given key has been fired last time.
+static FIRED_ALERTS: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(Default::default);
+/// Number of consecutive failed transactions, submitted by every relay loop.
+static TRANSACTION_FAILURES: Lazy<Mutex<HashMap<String, u32>>> = Lazy::new(Default::default);
+
+/// Configure alerts. Must be called once, before any alert is fired.
+pub fn initialize_alerts(params: AlertsParams) -> Result<(), String> {
+	if params.webhook.is_some() && params.format == WebhookFormat::PagerDuty && params.pagerduty_routing_key.is_none() {
+		return Err("PagerDuty routing key is required to send alerts to PagerDuty".into());
+	}
+
+	ALERTS_PARAMS
+		.set(params)
+		.map_err(|_| "Alerts are already initialized".to_string())
+}
+
+/// Returns period without finality relay progress, after which the alert is fired.
+pub fn finality_stall_timeout() -> Option<Duration> {
+	alerts_params().finality_stall_timeout
+}
+
+/// Remember result of transaction submission by the current relay loop.
+///
+/// Fires alert if too many consecutive transactions have failed.
+pub fn note_transaction_result(is_ok: bool) {
+	let max_transaction_failures = alerts_params().max_transaction_failures;
+	let loop_name = LOOP_NAME
+		.try_with(|loop_name| loop_name.borrow().clone())
+		.unwrap_or_default();
+	let mut transaction_failures = TRANSACTION_FAILURES
+		.lock()
+		.expect("alerts mutex is never poisoned; qed");
+	if is_ok {
+		transaction_failures.remove(&loop_name);
+		return;
+	}
+
+	let failures = transaction_failures.entry(loop_name.clone()).or_insert(0);
+	*failures += 1;
+	if max_transaction_failures != 0 && *failures >= max_transaction_failures {
+		let failures = *failures;
+		drop(transaction_failures);
+
+		fire_alert(
+			AlertKind::TransactionFailures,
+			&loop_name,
+			format!("{} consecutive transactions have failed", failures),
+		);
+	}
+}
+
+/// Fire alert.
+///
+/// Alert is always logged. It is sent to the webhook if it has not been fired (with the same kind
+/// and subject) during the repeat interval.
+pub fn fire_alert(kind: AlertKind, subject: &str, message: String) {
+	log::error!(target: "bridge", "Alert ({}) {}: {}", kind.name(), subject, message);
+
+	let params = alerts_params();
+	let webhook = match params.webhook {
+		Some(ref webhook) => webhook.clone(),
+		None => return,
+	};
+
+	let key = format!("{}:{}", kind.name(), subject);
+	if !should_fire(
+		&mut FIRED_ALERTS.lock().expect("alerts mutex is never poisoned; qed"),
+		&key,
+		Instant::now(),
+		params.repeat_interval,
+	) {
+		return;
+	}
+
+	let body = webhook_body(params, kind, &key, subject, &message);
+	async_std::task::spawn(async move {
+		if let Err(error) = send_webhook(&webhook, body).await {
+			log::warn!(target: "bridge", "Failed to send alert to webhook: {}", error);
+		}
+	});
+}
+
+/// Returns alerts configuration.
+fn alerts_params() -> &'static AlertsParams {
+	static DEFAULT_PARAMS: Lazy<AlertsParams> = Lazy::new(Default::default);
+	ALERTS_PARAMS.get().unwrap_or(&*DEFAULT_PARAMS)
+}
+
+/// Returns true if alert with given key should be sent to the webhook now. If so, remembers the time.
+fn should_fire(
+	fired_alerts: &mut HashMap<String, Instant>,
+	key: &str,
+	now: Instant,
+	repeat_interval: Duration,
+) -> bool {
+	match fired_alerts.get(key) {
+		Some(fired_at) if now.saturating_duration_since(*fired_at) < repeat_interval => false,
+		_ => {
+			fired_alerts.insert(key.into(), now);
+			true
+		}
+	}
+}
+
+/// Prepare webhook request body.
+fn webhook_body(params: &AlertsParams, kind: AlertKind, key: &str, subject: &str, message: &str) -> String {
+	let body = match params.format {
+		WebhookFormat::Generic => serde_json::json!({
+			"kind": kind.name(),
+			"subject": subject,
+			"message": message,
+		}),
+		WebhookFormat::Slack => serde_json::json!({
+			"text": format!(":rotating_light: *{}* {}: {}", kind.name(), subject, message),
+		}),
+		WebhookFormat::PagerDuty => serde_json::json!({
+			"routing_key": params.pagerduty_routing_key,
+			"event_action": "trigger",
+			"dedup_key": key,
+			"payload": {
+				"summary": format!("{}: {}", subject, message),
+				"source": subject,
+				"severity": "critical",
+				"class": kind.name(),
+			},
+		}),
+	};
+	body.to_string()
+}
+
+/// Send alert to the webhook.
+async fn send_webhook(webhook: &str, body: String) -> Result<(), String> {
+	use isahc::{HttpClient, Request};
+
+	let request = Request::post(webhook)
+		.header("Content-Type", "application/json")
+		.body(body)
+		.map_err(|e| e.to_string())?;
+	let response = HttpClient::new()
+		.map_err(|e| e.to_string())?
+		.send_async(request)
+		.await
+		.map_err(|e| e.to_string())?;
+	if !response.status().is_success() {
+		return Err(format!("webhook has responded with {}", response.status()));
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn alerts_with_same_key_are_not_repeated_within_interval() {
+		let mut fired_alerts = HashMap::new();
+		let now = Instant::now();
+		let interval = Duration::from_secs(60);
+
+		assert!(should_fire(&mut fired_alerts, "a", now, interval));
+		assert!(should_fire(&mut fired_alerts, "b", now, interval));
+		assert!(!should_fire(&mut fired_alerts, "a", now + interval / 2, interval));
+		assert!(should_fire(&mut fired_alerts, "a", now + interval, interval));
+	}
+
+	#[test]
+	fn pagerduty_alert_has_routing_and_dedup_keys() {
+		let params = AlertsParams {
+			webhook: Some(PAGERDUTY_EVENTS_URL.into()),
+			format: WebhookFormat::PagerDuty,
+			pagerduty_routing_key: Some("routing_key".into()),
+			..Default::default()
+		};
+		let body: serde_json::Value = serde_json::from_str(&webhook_body(
+			&params,
+			AlertKind::LowBalance,
+			"low_balance:Millau",
+			"Millau",
+			"balance is low",
+		))
+		.unwrap();
+
+		assert_eq!(body["routing_key"], "routing_key");
+		assert_eq!(body["dedup_key"], "low_balance:Millau");
+		assert_eq!(body["payload"]["summary"], "Millau: balance is low");
+	}
+}
@@ -0,0 +1,546 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Relay control interface.
+//!
+//! The interface is a minimal JSON-RPC 2.0 server over HTTP, that may be exposed by the relay
+//! process. It allows to pause and resume individual loops (message lanes), to trigger immediate
+//! sync iteration, to change log filters and to read internal state of loops without restarting
+//! the relay.
+//!
+//! Supported methods:
+//!
+//! - `relay_loops()` - names of all registered loops, with their pause flags;
+//! - `relay_pause(name)` and `relay_resume(name)` - pause and resume the loop;
+//! - `relay_sync(name)` - ask the loop to refresh clients state right now, without waiting for a tick;
+//! - `relay_state(name)` - internal state of the loop;
+//! - `relay_setLogFilters(filters)` - change log filters (using `RUST_LOG` syntax);
+//! - `relay_reloadConfig()` - ask the relay to reload its configuration file.
+
+use async_std::{
+	io::{BufReadExt, BufReader, ReadExt, WriteExt},
+	net::{SocketAddr, TcpListener, TcpStream},
+};
+use futures::{
+	stream::FusedStream,
+	task::{AtomicWaker, Context, Poll},
+	Stream, StreamExt,
+};
+use serde_json::{json, Value};
+use std::{
+	collections::BTreeMap,
+	fmt::Debug,
+	pin::Pin,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex,
+	},
+};
+
+/// Maximal size of control request body.
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+
+/// JSON-RPC error code of the invalid request.
+const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code of the unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code of the invalid method parameters.
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code of the request that can't be parsed.
+const PARSE_ERROR: i64 = -32700;
+
+/// Address of the relay control interface.
+#[derive(Debug, Clone)]
+pub struct ControlAddress {
+	/// Serve HTTP requests at given host.
+	pub host: String,
+	/// Serve HTTP requests at given port.
+	pub port: u16,
+}
+
+/// Registry of loops that are controlled by the control interface.
+///
+/// Cloning `RelayControl` is a cheap operation.
+#[derive(Debug, Clone, Default)]
+pub struct RelayControl {
+	loops: Arc<Mutex<BTreeMap<String, LoopControl>>>,
+	reload: Arc<Notification>,
+}
+
+impl RelayControl {
+	/// Return control of the loop with given name. The loop is registered if it is not yet known.
+	pub fn loop_control(&self, name: impl Into<String>) -> LoopControl {
+		self.loops
+			.lock()
+			.expect("control mutex is never poisoned; qed")
+			.entry(name.into())
+			.or_default()
+			.clone()
+	}
+
+	/// Returns stream of configuration reload requests.
+	///
+	/// There must be at most one consumer of the stream at any time.
+	pub fn reload_requests(&self) -> Notifications {
+		Notifications {
+			notification: self.reload.clone(),
+		}
+	}
+
+	/// Start serving control requests at given address, if it is specified.
+	pub async fn expose(self, address: Option<ControlAddress>) -> Result<(), String> {
+		let address = match address {
+			Some(address) => address,
+			None => return Ok(()),
+		};
+
+		let socket_addr = SocketAddr::new(
+			address.host.parse().map_err(|err| {
+				format!(
+					"Invalid host {} is used to expose relay control interface: {}",
+					address.host, err,
+				)
+			})?,
+			address.port,
+		);
+		let listener = TcpListener::bind(socket_addr)
+			.await
+			.map_err(|err| format!("Failed to bind relay control interface to {}: {}", socket_addr, err))?;
+
+		log::info!(target: "bridge", "Relay control interface is listening at {}", socket_addr);
+
+		async_std::task::spawn(async move {
+			let mut incoming = listener.incoming();
+			while let Some(stream) = incoming.next().await {
+				let stream = match stream {
+					Ok(stream) => stream,
+					Err(err) => {
+						log::trace!(target: "bridge", "Failed to accept control connection: {}", err);
+						continue;
+					}
+				};
+
+				let control = self.clone();
+				async_std::task::spawn(async move {
+					if let Err(err) = control.serve_connection(stream).await {
+						log::trace!(target: "bridge", "Failed to serve control connection: {}", err);
+					}
+				});
+			}
+		});
+
+		Ok(())
+	}
+
+	/// Read single HTTP request from the stream and write response to it.
+	async fn serve_connection(&self, stream: TcpStream) -> Result<(), String> {
+		let mut reader = BufReader::new(stream.clone());
+
+		let mut content_length = 0;
+		let mut line = String::new();
+		loop {
+			line.clear();
+			reader.read_line(&mut line).await.map_err(|err| err.to_string())?;
+			let header = line.trim();
+			if header.is_empty() {
+				break;
+			}
+
+			let mut header_parts = header.splitn(2, ':');
+			let header_name = header_parts.next().unwrap_or_default();
+			if header_name.eq_ignore_ascii_case("content-length") {
+				content_length = header_parts
+					.next()
+					.unwrap_or_default()
+					.trim()
+					.parse()
+					.map_err(|err| format!("Invalid Content-Length header: {}", err))?;
+			}
+		}
+		if content_length > MAX_REQUEST_SIZE {
+			return Err(format!("Too large control request: {} bytes", content_length));
+		}
+
+		let mut body = vec![0u8; content_length];
+		reader.read_exact(&mut body).await.map_err(|err| err.to_string())?;
+
+		let response = self.handle_request(&body).to_string();
+		let mut stream = stream;
+		stream
+			.write_all(
+				format!(
+					"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+					response.len(),
+					response,
+				)
+				.as_bytes(),
+			)
+			.await
+			.map_err(|err| err.to_string())
+	}
+
+	/// Handle JSON-RPC request and return the response.
+	fn handle_request(&self, request: &[u8]) -> Value {
+		let request: Value = match serde_json::from_slice(request) {
+			Ok(request) => request,
+			Err(err) => return error_response(Value::Null, PARSE_ERROR, err.to_string()),
+		};
+		let id = request.get("id").cloned().unwrap_or(Value::Null);
+		let method = match request.get("method").and_then(Value::as_str) {
+			Some(method) => method,
+			None => return error_response(id, INVALID_REQUEST, "Missing method".into()),
+		};
+		let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+		match self.call(method, &params) {
+			Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+			Err((code, message)) => error_response(id, code, message),
+		}
+	}
+
+	/// Call control method.
+	fn call(&self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+		match method {
+			"relay_loops" => {
+				let loops = self.loops.lock().expect("control mutex is never poisoned; qed");
+				Ok(Value::Array(
+					loops
+						.iter()
+						.map(|(name, control)| json!({ "name": name, "paused": control.is_paused() }))
+						.collect(),
+				))
+			}
+			"relay_pause" => {
+				self.find_loop(params)?.set_paused(true);
+				Ok(Value::Bool(true))
+			}
+			"relay_resume" => {
+				self.find_loop(params)?.set_paused(false);
+				Ok(Value::Bool(true))
+			}
+			"relay_sync" => {
+				self.find_loop(params)?.request_sync();
+				Ok(Value::Bool(true))
+			}
+			"relay_state" => {
+				let control = self.find_loop(params)?;
+				Ok(json!({ "paused": control.is_paused(), "state": control.state() }))
+			}
+			"relay_setLogFilters" => {
+				let filters = string_param(params)?;
+				crate::initialize::set_log_filters(filters).map_err(|err| (INVALID_PARAMS, err))?;
+				Ok(Value::Bool(true))
+			}
+			"relay_reloadConfig" => {
+				self.reload.notify();
+				Ok(Value::Bool(true))
+			}
+			_ => Err((METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+		}
+	}
+
+	/// Find loop, referenced by the request parameters.
+	fn find_loop(&self, params: &Value) -> Result<LoopControl, (i64, String)> {
+		let name = string_param(params)?;
+		self.loops
+			.lock()
+			.expect("control mutex is never poisoned; qed")
+			.get(name)
+			.cloned()
+			.ok_or_else(|| (INVALID_PARAMS, format!("Unknown loop: {}", name)))
+	}
+}
+
+/// Control of the single relay loop.
+///
+/// Cloning `LoopControl` is a cheap operation. Default value isn't registered anywhere, so the
+/// loop that is using it, may only be controlled by the relay code.
+#[derive(Clone, Default)]
+pub struct LoopControl {
+	inner: Arc<LoopControlInner>,
+}
+
+#[derive(Default)]
+struct LoopControlInner {
+	is_paused: AtomicBool,
+	sync: Arc<Notification>,
+	state: Mutex<BTreeMap<String, String>>,
+}
+
+impl std::fmt::Debug for LoopControl {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		fmt.debug_struct("LoopControl")
+			.field("is_paused", &self.is_paused())
+			.finish()
+	}
+}
+
+impl LoopControl {
+	/// Returns true if the loop is paused.
+	pub fn is_paused(&self) -> bool {
+		self.inner.is_paused.load(Ordering::SeqCst)
+	}
+
+	/// Pause or resume the loop.
+	pub fn set_paused(&self, is_paused: bool) {
+		self.inner.is_paused.store(is_paused, Ordering::SeqCst);
+		// resumed loop shall not wait for the next tick
+		self.request_sync();
+	}
+
+	/// Ask the loop to start the sync iteration right now.
+	pub fn request_sync(&self) {
+		self.inner.sync.notify();
+	}
+
+	/// Returns stream of sync requests.
+	///
+	/// There must be at most one consumer of the stream at any time.
+	pub fn sync_requests(&self) -> Notifications {
+		Notifications {
+			notification: self.inner.sync.clone(),
+		}
+	}
+
+	/// Update the reported state of the loop component.
+	pub fn set_state(&self, component: &str, state: String) {
+		self.inner
+			.state
+			.lock()
+			.expect("control mutex is never poisoned; qed")
+			.insert(component.into(), state);
+	}
+
+	/// Returns reported state of all loop components.
+	pub fn state(&self) -> BTreeMap<String, String> {
+		self.inner
+			.state
+			.lock()
+			.expect("control mutex is never poisoned; qed")
+			.clone()
+	}
+}
+
+/// Relay parameter that may be changed while the relay is running (e.g. when the relay
+/// configuration is reloaded).
+///
+/// Cloning `Reloadable` is a cheap operation. All clones are sharing the same value, so the
+/// code that is reading the value, sees updates without being restarted.
+#[derive(Clone, Default)]
+pub struct Reloadable<T> {
+	value: Arc<Mutex<T>>,
+	updated: Arc<Notification>,
+}
+
+impl<T: Clone> Reloadable<T> {
+	/// Create new reloadable parameter with given initial value.
+	pub fn new(value: T) -> Self {
+		Reloadable {
+			value: Arc::new(Mutex::new(value)),
+			updated: Default::default(),
+		}
+	}
+
+	/// Returns current value of the parameter.
+	pub fn get(&self) -> T {
+		self.value
+			.lock()
+			.expect("reloadable mutex is never poisoned; qed")
+			.clone()
+	}
+
+	/// Update value of the parameter.
+	pub fn set(&self, value: T) {
+		*self.value.lock().expect("reloadable mutex is never poisoned; qed") = value;
+		self.updated.notify();
+	}
+
+	/// Returns stream of parameter updates.
+	///
+	/// There must be at most one consumer of the stream at any time.
+	pub fn updates(&self) -> Notifications {
+		Notifications {
+			notification: self.updated.clone(),
+		}
+	}
+}
+
+impl<T: Clone + Debug> Debug for Reloadable<T> {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		self.get().fmt(fmt)
+	}
+}
+
+/// Notification flag with the waker of its (single) consumer.
+#[derive(Debug, Default)]
+struct Notification {
+	is_notified: AtomicBool,
+	waker: AtomicWaker,
+}
+
+impl Notification {
+	/// Raise the flag and wake the consumer.
+	fn notify(&self) {
+		self.is_notified.store(true, Ordering::SeqCst);
+		self.waker.wake();
+	}
+}
+
+/// Stream of notifications (sync requests of the single loop, configuration reload requests, ...).
+///
+/// Multiple notifications that have been raised before the stream is polled, are merged into
+/// single stream item.
+pub struct Notifications {
+	notification: Arc<Notification>,
+}
+
+impl Stream for Notifications {
+	type Item = ();
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<()>> {
+		let notification = &self.notification;
+		if notification.is_notified.swap(false, Ordering::SeqCst) {
+			return Poll::Ready(Some(()));
+		}
+
+		notification.waker.register(cx.waker());
+		if notification.is_notified.swap(false, Ordering::SeqCst) {
+			return Poll::Ready(Some(()));
+		}
+
+		Poll::Pending
+	}
+}
+
+impl FusedStream for Notifications {
+	fn is_terminated(&self) -> bool {
+		false
+	}
+}
+
+/// Returns the only string parameter of the request.
+fn string_param(params: &Value) -> Result<&str, (i64, String)> {
+	params
+		.get(0)
+		.and_then(Value::as_str)
+		.ok_or_else(|| (INVALID_PARAMS, "Expected single string parameter".into()))
+}
+
+/// Build JSON-RPC error response.
+fn error_response(id: Value, code: i64, message: String) -> Value {
+	json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::FutureExt;
+
+	fn call(control: &RelayControl, method: &str, params: Value) -> Value {
+		control.handle_request(
+			json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })
+				.to_string()
+				.as_bytes(),
+		)
+	}
+
+	#[test]
+	fn loop_is_paused_and_resumed() {
+		let control = RelayControl::default();
+		let loop_control = control.loop_control("lane");
+		assert!(!loop_control.is_paused());
+
+		assert_eq!(call(&control, "relay_pause", json!(["lane"]))["result"], json!(true));
+		assert!(loop_control.is_paused());
+		assert_eq!(
+			call(&control, "relay_loops", Value::Null)["result"],
+			json!([{ "name": "lane", "paused": true }]),
+		);
+
+		assert_eq!(call(&control, "relay_resume", json!(["lane"]))["result"], json!(true));
+		assert!(!loop_control.is_paused());
+	}
+
+	#[test]
+	fn sync_is_requested() {
+		let control = RelayControl::default();
+		let loop_control = control.loop_control("lane");
+		let mut sync_requests = loop_control.sync_requests();
+		assert_eq!(sync_requests.next().now_or_never(), None);
+
+		assert_eq!(call(&control, "relay_sync", json!(["lane"]))["result"], json!(true));
+		assert_eq!(sync_requests.next().now_or_never(), Some(Some(())));
+		assert_eq!(sync_requests.next().now_or_never(), None);
+	}
+
+	#[test]
+	fn config_reload_is_requested() {
+		let control = RelayControl::default();
+		let mut reload_requests = control.reload_requests();
+		assert_eq!(reload_requests.next().now_or_never(), None);
+
+		assert_eq!(call(&control, "relay_reloadConfig", Value::Null)["result"], json!(true));
+		assert_eq!(call(&control, "relay_reloadConfig", Value::Null)["result"], json!(true));
+		assert_eq!(reload_requests.next().now_or_never(), Some(Some(())));
+		assert_eq!(reload_requests.next().now_or_never(), None);
+	}
+
+	#[test]
+	fn reloadable_value_is_shared_by_clones() {
+		let value = Reloadable::new(1u32);
+		let value_clone = value.clone();
+		let mut updates = value_clone.updates();
+		assert_eq!(updates.next().now_or_never(), None);
+
+		value.set(2);
+		assert_eq!(value_clone.get(), 2);
+		assert_eq!(format!("{:?}", value_clone), "2");
+		assert_eq!(updates.next().now_or_never(), Some(Some(())));
+		assert_eq!(updates.next().now_or_never(), None);
+	}
+
+	#[test]
+	fn loop_state_is_reported() {
+		let control = RelayControl::default();
+		control.loop_control("lane").set_state("race", "state".into());
+
+		assert_eq!(
+			call(&control, "relay_state", json!(["lane"]))["result"],
+			json!({ "paused": false, "state": { "race": "state" } }),
+		);
+	}
+
+	#[test]
+	fn invalid_requests_are_rejected() {
+		let control = RelayControl::default();
+		assert_eq!(
+			call(&control, "relay_pause", json!(["lane"]))["error"]["code"],
+			json!(INVALID_PARAMS)
+		);
+		assert_eq!(
+			call(&control, "relay_pause", Value::Null)["error"]["code"],
+			json!(INVALID_PARAMS)
+		);
+		assert_eq!(
+			call(&control, "relay_unknown", Value::Null)["error"]["code"],
+			json!(METHOD_NOT_FOUND)
+		);
+		assert_eq!(
+			control.handle_request(b"not a json")["error"]["code"],
+			json!(PARSE_ERROR)
+		);
+	}
+}
@@ -0,0 +1,196 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Relay errors taxonomy.
+//!
+//! Every error that may stop the relay belongs to one of few kinds. The kind defines process exit
+//! code, so that orchestration tools may distinguish failures that are fixed by simple restart
+//! (connection errors) from failures that require human intervention (everything else). Errors
+//! that are handled by relay loops are also counted by kind and exposed by `GlobalMetrics`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Process exit code that is used when the error kind is unknown.
+pub const UNKNOWN_ERROR_EXIT_CODE: i32 = 1;
+
+/// Kind of relay error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+	/// Node connection has failed or the node is unable to serve our requests (e.g. it is not synced).
+	Connection,
+	/// We have failed to decode data that we have received from the node.
+	Decoding,
+	/// Node runs runtime that is different from the runtime that the relay has been built for.
+	RuntimeMismatch,
+	/// Something is wrong with the relay account: it is missing, or its balance is too low.
+	Account,
+	/// All other errors: misconfiguration, relay bugs, ...
+	Logic,
+}
+
+impl ErrorKind {
+	/// All known error kinds.
+	pub const ALL: [ErrorKind; 5] = [
+		ErrorKind::Connection,
+		ErrorKind::Decoding,
+		ErrorKind::RuntimeMismatch,
+		ErrorKind::Account,
+		ErrorKind::Logic,
+	];
+
+	/// Returns name of the error kind that is used in logs and metrics.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			ErrorKind::Connection => "connection",
+			ErrorKind::Decoding => "decoding",
+			ErrorKind::RuntimeMismatch => "runtime_mismatch",
+			ErrorKind::Account => "account",
+			ErrorKind::Logic => "logic",
+		}
+	}
+
+	/// Returns process exit code that is used when the relay is stopped because of error of this kind.
+	pub fn exit_code(&self) -> i32 {
+		match *self {
+			ErrorKind::Connection => 10,
+			ErrorKind::Decoding => 11,
+			ErrorKind::RuntimeMismatch => 12,
+			ErrorKind::Account => 13,
+			ErrorKind::Logic => 14,
+		}
+	}
+
+	/// Returns true if the relay may be simply restarted after failing with error of this kind.
+	///
+	/// Errors of other kinds require human intervention.
+	pub fn is_restartable(&self) -> bool {
+		matches!(*self, ErrorKind::Connection)
+	}
+}
+
+impl std::fmt::Display for ErrorKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+/// Relay error of known kind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+	kind: ErrorKind,
+	message: String,
+}
+
+impl Error {
+	/// Create new error of given kind.
+	pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+		Error {
+			kind,
+			message: message.into(),
+		}
+	}
+
+	/// Returns error kind.
+	pub fn kind(&self) -> ErrorKind {
+		self.kind
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{} error: {}", self.kind, self.message)
+	}
+}
+
+/// Number of errors of every kind that have been reported by relay loops.
+static ERRORS_COUNT: [AtomicU64; 5] = [
+	AtomicU64::new(0),
+	AtomicU64::new(0),
+	AtomicU64::new(0),
+	AtomicU64::new(0),
+	AtomicU64::new(0),
+];
+
+/// Remember that the error of given kind has happened.
+pub fn note_error(kind: ErrorKind) {
+	errors_counter(kind).fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns total number of errors of given kind, that have happened since process start.
+pub fn errors_count(kind: ErrorKind) -> u64 {
+	errors_counter(kind).load(Ordering::Relaxed)
+}
+
+/// Log error and stop the process with exit code, matching the error kind.
+///
+/// If `kind` is `None`, the `UNKNOWN_ERROR_EXIT_CODE` is used.
+pub fn exit_on_error(kind: Option<ErrorKind>, error: impl std::fmt::Display) -> ! {
+	let exit_code = kind.map(|kind| kind.exit_code()).unwrap_or(UNKNOWN_ERROR_EXIT_CODE);
+	log::error!(
+		target: "bridge",
+		"Stopping relay because of {} error (restartable: {}, exit code: {}): {}",
+		kind.map(|kind| kind.name()).unwrap_or("unknown"),
+		kind.map(|kind| kind.is_restartable()).unwrap_or(false),
+		exit_code,
+		error,
+	);
+
+	std::process::exit(exit_code)
+}
+
+fn errors_counter(kind: ErrorKind) -> &'static AtomicU64 {
+	// error kinds are declared in the same order as in `ErrorKind::ALL`
+	&ERRORS_COUNT[kind as usize]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn exit_codes_are_distinct() {
+		let mut exit_codes = ErrorKind::ALL.iter().map(ErrorKind::exit_code).collect::<Vec<_>>();
+		exit_codes.push(UNKNOWN_ERROR_EXIT_CODE);
+		exit_codes.sort_unstable();
+		exit_codes.dedup();
+		assert_eq!(exit_codes.len(), ErrorKind::ALL.len() + 1);
+	}
+
+	#[test]
+	fn only_connection_errors_are_restartable() {
+		assert_eq!(
+			ErrorKind::ALL
+				.iter()
+				.filter(|kind| kind.is_restartable())
+				.collect::<Vec<_>>(),
+			vec![&ErrorKind::Connection],
+		);
+	}
+
+	#[test]
+	fn errors_are_counted_by_kind() {
+		let decoding_errors = errors_count(ErrorKind::Decoding);
+		let account_errors = errors_count(ErrorKind::Account);
+
+		note_error(ErrorKind::Decoding);
+		note_error(ErrorKind::Decoding);
+
+		assert_eq!(errors_count(ErrorKind::Decoding), decoding_errors + 2);
+		assert_eq!(errors_count(ErrorKind::Account), account_errors);
+	}
+}
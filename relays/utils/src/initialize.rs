@@ -16,7 +16,9 @@
 
 //! Relayer initialization functions.
 
-use std::{cell::RefCell, fmt::Display, io::Write};
+use log::Log;
+use once_cell::sync::OnceCell;
+use std::{cell::RefCell, fmt::Display, io::Write, sync::RwLock};
 
 async_std::task_local! {
 	pub(crate) static LOOP_NAME: RefCell<String> = RefCell::new(String::default());
@@ -27,12 +29,67 @@ pub fn initialize_relay() {
 	initialize_logger(true);
 }
 
+/// Relay logger, that supports changing log filters at runtime.
+struct RelayLogger {
+	with_timestamp: bool,
+	logger: RwLock<env_logger::Logger>,
+}
+
+static LOGGER: OnceCell<RelayLogger> = OnceCell::new();
+
+impl Log for RelayLogger {
+	fn enabled(&self, metadata: &log::Metadata) -> bool {
+		self.logger
+			.read()
+			.expect("logger lock is never poisoned; qed")
+			.enabled(metadata)
+	}
+
+	fn log(&self, record: &log::Record) {
+		self.logger
+			.read()
+			.expect("logger lock is never poisoned; qed")
+			.log(record)
+	}
+
+	fn flush(&self) {
+		self.logger.read().expect("logger lock is never poisoned; qed").flush()
+	}
+}
+
 /// Initialize Relay logger instance.
 pub fn initialize_logger(with_timestamp: bool) {
+	let logger = build_logger(with_timestamp, None);
+	let max_level = logger.filter();
+	let relay_logger = LOGGER.get_or_init(|| RelayLogger {
+		with_timestamp,
+		logger: RwLock::new(logger),
+	});
+	log::set_logger(relay_logger).expect("logger is only initialized once; qed");
+	log::set_max_level(max_level);
+}
+
+/// Change filters of the relay logger. Filters are using the `RUST_LOG` syntax and are applied on top
+/// of default filters.
+pub fn set_log_filters(filters: &str) -> Result<(), String> {
+	let relay_logger = LOGGER
+		.get()
+		.ok_or_else(|| "Relay logger is not initialized".to_string())?;
+	let logger = build_logger(relay_logger.with_timestamp, Some(filters));
+	log::set_max_level(logger.filter());
+	*relay_logger.logger.write().expect("logger lock is never poisoned; qed") = logger;
+	Ok(())
+}
+
+/// Build `env_logger` logger. If filters are not specified, they are read from the `RUST_LOG` env variable.
+fn build_logger(with_timestamp: bool, filters: Option<&str>) -> env_logger::Logger {
 	let mut builder = env_logger::Builder::new();
 	builder.filter_level(log::LevelFilter::Warn);
 	builder.filter_module("bridge", log::LevelFilter::Info);
-	builder.parse_default_env();
+	match filters {
+		Some(filters) => builder.parse_filters(filters),
+		None => builder.parse_default_env(),
+	};
 	if with_timestamp {
 		builder.format(move |buf, record| {
 			let timestamp = time::OffsetDateTime::try_now_local()
@@ -73,7 +130,7 @@ pub fn initialize_logger(with_timestamp: bool) {
 		});
 	}
 
-	builder.init();
+	builder.build()
 }
 
 /// Initialize relay loop. Must only be called once per every loop task.
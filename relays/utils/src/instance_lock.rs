@@ -0,0 +1,113 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Lock that prevents running several relay instances that are sharing the same resource
+//! (e.g. the same transactions signer).
+
+use std::{
+	fs::OpenOptions,
+	io::{ErrorKind, Write},
+	path::PathBuf,
+};
+use sysinfo::{RefreshKind, System, SystemExt};
+
+/// Lock that is held by the running relay instance.
+///
+/// The lock is a file in the temporary directory, containing the id of the owner process. It is
+/// removed when the lock is dropped. If the file is left by the process that is not running
+/// anymore, the lock is considered stale and is taken over.
+#[derive(Debug)]
+pub struct InstanceLock {
+	path: PathBuf,
+}
+
+impl InstanceLock {
+	/// Acquire lock with given name.
+	///
+	/// Fails if the lock is held by another running process.
+	pub fn acquire(name: &str) -> Result<Self, String> {
+		let path = std::env::temp_dir().join(format!("substrate-relay-{}.lock", name));
+		let mut removed_stale_lock = false;
+		loop {
+			match OpenOptions::new().write(true).create_new(true).open(&path) {
+				Ok(mut file) => {
+					file.write_all(std::process::id().to_string().as_bytes())
+						.map_err(|e| format!("Failed to write lock file {}: {}", path.display(), e))?;
+					return Ok(InstanceLock { path });
+				}
+				Err(ref e) if e.kind() == ErrorKind::AlreadyExists && !removed_stale_lock => {
+					let owner = std::fs::read_to_string(&path)
+						.ok()
+						.and_then(|owner| owner.trim().parse::<sysinfo::Pid>().ok());
+					if let Some(owner) = owner {
+						if System::new_with_specifics(RefreshKind::new()).refresh_process(owner) {
+							return Err(format!(
+								"Another relay instance (process {}) is already running with {}. \
+								If it is not true, remove the lock file {}",
+								owner,
+								name,
+								path.display(),
+							));
+						}
+					}
+
+					log::warn!(target: "bridge", "Removing stale lock file {}", path.display());
+					std::fs::remove_file(&path)
+						.map_err(|e| format!("Failed to remove stale lock file {}: {}", path.display(), e))?;
+					removed_stale_lock = true;
+				}
+				Err(e) => return Err(format!("Failed to create lock file {}: {}", path.display(), e)),
+			}
+		}
+	}
+}
+
+impl Drop for InstanceLock {
+	fn drop(&mut self) {
+		if let Err(e) = std::fs::remove_file(&self.path) {
+			log::warn!(target: "bridge", "Failed to remove lock file {}: {}", self.path.display(), e);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lock_is_not_acquired_twice() {
+		let name = format!("test-{}", std::process::id());
+
+		let lock = InstanceLock::acquire(&name).unwrap();
+		assert!(InstanceLock::acquire(&name).is_err());
+
+		drop(lock);
+		assert!(InstanceLock::acquire(&name).is_ok());
+	}
+
+	#[test]
+	fn stale_lock_is_taken_over() {
+		let name = format!("stale-test-{}", std::process::id());
+		let path = std::env::temp_dir().join(format!("substrate-relay-{}.lock", name));
+		std::fs::write(&path, "not-a-pid").unwrap();
+
+		let lock = InstanceLock::acquire(&name).unwrap();
+		assert_eq!(std::fs::read_to_string(&path).unwrap(), std::process::id().to_string());
+
+		drop(lock);
+		assert!(!path.exists());
+	}
+}
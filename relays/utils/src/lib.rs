@@ -16,6 +16,7 @@
 
 //! Utilities used by different relays.
 
+pub use error::ErrorKind;
 pub use relay_loop::{relay_loop, relay_metrics};
 
 use backoff::{backoff::Backoff, ExponentialBackoff};
@@ -29,9 +30,16 @@ pub const MAX_BACKOFF_INTERVAL: Duration = Duration::from_secs(60);
 /// reconnection again.
 pub const CONNECTION_ERROR_DELAY: Duration = Duration::from_secs(10);
 
+pub mod alerts;
+pub mod control;
+pub mod error;
 pub mod initialize;
+pub mod instance_lock;
 pub mod metrics;
+pub mod recovery;
 pub mod relay_loop;
+pub mod transport;
+pub mod watchdog;
 
 /// Block number traits shared by all chains that relay is able to serve.
 pub trait BlockNumberBase:
@@ -108,6 +116,17 @@ pub struct HeaderId<Hash, Number>(pub Number, pub Hash);
 pub trait MaybeConnectionError {
 	/// Returns true if error (maybe) represents connection error.
 	fn is_connection_error(&self) -> bool;
+
+	/// Returns kind of the error.
+	///
+	/// By default all connection-unrelated errors are treated as logic errors.
+	fn error_kind(&self) -> ErrorKind {
+		if self.is_connection_error() {
+			ErrorKind::Connection
+		} else {
+			ErrorKind::Logic
+		}
+	}
 }
 
 /// Stringified error that may be either connection-related or not.
@@ -249,11 +268,14 @@ where
 			ProcessFutureResult::Success
 		}
 		Err(error) if error.is_connection_error() => {
+			let error_kind = error.error_kind();
+			error::note_error(error_kind);
 			log::error!(
 				target: "bridge",
-				"{}: {:?}. Going to restart",
+				"{}: {:?} ({} error). Going to restart",
 				error_pattern(),
 				error,
+				error_kind,
 			);
 
 			retry_backoff.reset();
@@ -261,12 +283,15 @@ where
 			ProcessFutureResult::ConnectionFailed
 		}
 		Err(error) => {
+			let error_kind = error.error_kind();
+			error::note_error(error_kind);
 			let retry_delay = retry_backoff.next_backoff().unwrap_or(CONNECTION_ERROR_DELAY);
 			log::error!(
 				target: "bridge",
-				"{}: {:?}. Retrying in {}",
+				"{}: {:?} ({} error). Retrying in {}",
 				error_pattern(),
 				error,
+				error_kind,
 				retry_delay.as_secs_f64(),
 			);
 
@@ -14,11 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-pub use float_json_value::FloatJsonValueMetric;
+pub use float_json_value::{read_json_value, FloatJsonValueMetric};
 pub use global::GlobalMetrics;
+pub use observed_lag::ObservedLag;
 pub use substrate_prometheus_endpoint::{
+	exponential_buckets,
 	prometheus::core::{Atomic, Collector},
-	register, Counter, CounterVec, Gauge, GaugeVec, Opts, PrometheusError, Registry, F64, U64,
+	register, Counter, CounterVec, Gauge, GaugeVec, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry, F64,
+	U64,
 };
 
 use async_trait::async_trait;
@@ -26,6 +29,7 @@ use std::{fmt::Debug, time::Duration};
 
 mod float_json_value;
 mod global;
+mod observed_lag;
 
 /// Unparsed address that needs to be used to expose Prometheus metrics.
 #[derive(Debug, Clone)]
@@ -49,29 +49,34 @@ impl FloatJsonValueMetric {
 
 	/// Read value from HTTP service.
 	async fn read_value(&self) -> Result<f64, String> {
-		use isahc::{AsyncReadResponseExt, HttpClient, Request};
-
-		fn map_isahc_err(err: impl std::fmt::Display) -> String {
-			format!("Failed to fetch token price from remote server: {}", err)
-		}
-
-		let request = Request::get(&self.url)
-			.header("Accept", "application/json")
-			.body(())
-			.map_err(map_isahc_err)?;
-		let raw_response = HttpClient::new()
-			.map_err(map_isahc_err)?
-			.send_async(request)
-			.await
-			.map_err(map_isahc_err)?
-			.text()
-			.await
-			.map_err(map_isahc_err)?;
-
-		parse_service_response(&self.json_path, &raw_response)
+		read_json_value(&self.url, &self.json_path).await
 	}
 }
 
+/// Read float value (e.g. token price) from HTTP service, using given JSON path.
+pub async fn read_json_value(url: &str, json_path: &str) -> Result<f64, String> {
+	use isahc::{AsyncReadResponseExt, HttpClient, Request};
+
+	fn map_isahc_err(err: impl std::fmt::Display) -> String {
+		format!("Failed to fetch token price from remote server: {}", err)
+	}
+
+	let request = Request::get(url)
+		.header("Accept", "application/json")
+		.body(())
+		.map_err(map_isahc_err)?;
+	let raw_response = HttpClient::new()
+		.map_err(map_isahc_err)?
+		.send_async(request)
+		.await
+		.map_err(map_isahc_err)?
+		.text()
+		.await
+		.map_err(map_isahc_err)?;
+
+	parse_service_response(json_path, &raw_response)
+}
+
 #[async_trait]
 impl StandaloneMetrics for FloatJsonValueMetric {
 	fn update_interval(&self) -> Duration {
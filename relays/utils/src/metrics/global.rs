@@ -16,8 +16,12 @@
 
 //! Global system-wide Prometheus metrics exposed by relays.
 
-use crate::metrics::{
-	metric_name, register, Gauge, GaugeVec, Opts, PrometheusError, Registry, StandaloneMetrics, F64, U64,
+use crate::{
+	error::{errors_count, ErrorKind},
+	metrics::{
+		metric_name, register, CounterVec, Gauge, GaugeVec, Opts, PrometheusError, Registry, StandaloneMetrics, F64,
+		U64,
+	},
 };
 
 use async_std::sync::{Arc, Mutex};
@@ -35,6 +39,7 @@ pub struct GlobalMetrics {
 	system_average_load: GaugeVec<F64>,
 	process_cpu_usage_percentage: Gauge<F64>,
 	process_memory_usage_bytes: Gauge<U64>,
+	errors: CounterVec<U64>,
 }
 
 impl GlobalMetrics {
@@ -60,6 +65,16 @@ impl GlobalMetrics {
 				)?,
 				registry,
 			)?,
+			errors: register(
+				CounterVec::new(
+					Opts::new(
+						metric_name(prefix, "errors"),
+						"Total number of relay errors of every kind",
+					),
+					&["kind"],
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
@@ -67,6 +82,12 @@ impl GlobalMetrics {
 #[async_trait]
 impl StandaloneMetrics for GlobalMetrics {
 	async fn update(&self) {
+		// update errors counters
+		for kind in ErrorKind::ALL.iter() {
+			let counter = self.errors.with_label_values(&[kind.name()]);
+			counter.inc_by(errors_count(*kind).saturating_sub(counter.get()));
+		}
+
 		// update system-wide metrics
 		let mut system = self.system.lock().await;
 		let load = system.get_load_average();
@@ -0,0 +1,134 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Lag between monotonically increasing values at two sides of the bridge.
+
+use std::{
+	collections::VecDeque,
+	time::{Duration, Instant},
+};
+
+/// Maximal number of remembered observations.
+///
+/// If the lag is large, we only keep oldest observations, because only they're required to compute
+/// the lag duration.
+const MAX_OBSERVATIONS: usize = 4096;
+
+/// Lag between monotonically increasing value at the "source" side (e.g. best finalized block
+/// number or latest generated message nonce) and the same value, known to the "target" side
+/// (e.g. best finalized source block number, known to the target chain or latest received message
+/// nonce).
+///
+/// The wall-clock lag is computed using the time when source values have been observed by the
+/// relay. So it is the lower bound of the actual lag.
+#[derive(Debug, Default)]
+pub struct ObservedLag {
+	/// Source values that are not yet known to the target side, along with the time when they
+	/// have been observed for the first time.
+	observations: VecDeque<(u64, Instant)>,
+	/// Best known value at the source side.
+	best_at_source: u64,
+	/// Best known value at the target side.
+	best_at_target: u64,
+}
+
+impl ObservedLag {
+	/// Update value at the source side.
+	pub fn update_source(&mut self, value: u64, now: Instant) {
+		if value <= self.best_at_source {
+			return;
+		}
+
+		self.best_at_source = value;
+		if value > self.best_at_target && self.observations.len() < MAX_OBSERVATIONS {
+			self.observations.push_back((value, now));
+		}
+	}
+
+	/// Update value at the target side.
+	pub fn update_target(&mut self, value: u64) {
+		self.best_at_target = value;
+		while self
+			.observations
+			.front()
+			.map(|(observed_value, _)| *observed_value <= value)
+			.unwrap_or(false)
+		{
+			self.observations.pop_front();
+		}
+	}
+
+	/// Returns difference between source and target values.
+	pub fn lag(&self) -> u64 {
+		self.best_at_source.saturating_sub(self.best_at_target)
+	}
+
+	/// Returns time since the oldest source value, that is not yet known to the target side, has been observed.
+	pub fn lag_duration(&self, now: Instant) -> Duration {
+		self.observations
+			.front()
+			.map(|(_, observed_at)| now.saturating_duration_since(*observed_at))
+			.unwrap_or_default()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lag_is_computed_using_oldest_unknown_value() {
+		let start = Instant::now();
+		let mut lag = ObservedLag::default();
+
+		lag.update_source(10, start);
+		lag.update_source(20, start + Duration::from_secs(10));
+		lag.update_source(30, start + Duration::from_secs(20));
+		assert_eq!(lag.lag(), 30);
+		assert_eq!(
+			lag.lag_duration(start + Duration::from_secs(30)),
+			Duration::from_secs(30)
+		);
+
+		lag.update_target(15);
+		assert_eq!(lag.lag(), 15);
+		assert_eq!(
+			lag.lag_duration(start + Duration::from_secs(30)),
+			Duration::from_secs(20)
+		);
+
+		lag.update_target(30);
+		assert_eq!(lag.lag(), 0);
+		assert_eq!(
+			lag.lag_duration(start + Duration::from_secs(30)),
+			Duration::from_secs(0)
+		);
+	}
+
+	#[test]
+	fn source_values_known_to_target_are_not_remembered() {
+		let start = Instant::now();
+		let mut lag = ObservedLag::default();
+
+		lag.update_target(20);
+		lag.update_source(20, start);
+		assert_eq!(lag.lag(), 0);
+		assert_eq!(
+			lag.lag_duration(start + Duration::from_secs(10)),
+			Duration::from_secs(0)
+		);
+	}
+}
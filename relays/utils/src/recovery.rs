@@ -0,0 +1,548 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Recovery policies of relay loop clients.
+//!
+//! When relay loop client fails with connection error, the relay loop asks the recovery policy
+//! of this client to bring it back. Every recovery attempt is made after the reconnect backoff
+//! delay (see `ReconnectBackoff`). Policies may be composed, e.g. the
+//! `Reconnect::with_max_attempts(3).or_else(Failover).or_else(Abort)` policy makes three
+//! reconnect attempts, then switches to failover endpoints and stops the relay if there are no
+//! endpoints left.
+//!
+//! The same policy may be described by the `RecoveryPolicyParams`, which are parsed from the
+//! comma-separated list of policy names (e.g. `reconnect(3),failover,abort`). This is how the
+//! recovery policy is selected by the relay user.
+
+use crate::relay_loop::Client;
+
+use async_trait::async_trait;
+use std::str::FromStr;
+
+/// Result of single recovery attempt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecoveryResult {
+	/// Client has been recovered.
+	Recovered,
+	/// Recovery attempt has failed. It'll be retried after the backoff delay.
+	Failed,
+	/// Policy has no more recovery options. If the policy is the part of `Fallback` policy, the next
+	/// policy is used. Otherwise, the policy is reset and recovery is retried after the backoff delay.
+	Exhausted,
+	/// Relay must be stopped.
+	Abort,
+}
+
+impl RecoveryResult {
+	/// Returns name of the result, used in metrics.
+	pub fn as_str(&self) -> &'static str {
+		match *self {
+			RecoveryResult::Recovered => "recovered",
+			RecoveryResult::Failed => "failed",
+			RecoveryResult::Exhausted => "exhausted",
+			RecoveryResult::Abort => "abort",
+		}
+	}
+}
+
+/// Recovery policy of relay loop client.
+#[async_trait]
+pub trait RecoveryPolicy<C>: Send + Sync {
+	/// Name of the policy, exposed in metrics.
+	fn name(&self) -> String;
+
+	/// Make single attempt to recover failed client.
+	async fn recover(&mut self, client: &mut C) -> RecoveryResult;
+
+	/// Reset policy state. Called when the client has been recovered or when the policy has
+	/// exhausted its recovery options.
+	fn reset(&mut self) {}
+}
+
+/// Composition of recovery policies.
+///
+/// It isn't a part of `RecoveryPolicy`, because policies are usually generic over the client
+/// type and it couldn't be inferred from the composed policy.
+pub trait ComposeRecoveryPolicy: Sized {
+	/// Use `next` policy when this policy has exhausted its recovery options.
+	fn or_else<P>(self, next: P) -> Fallback<Self, P> {
+		Fallback {
+			first: self,
+			second: next,
+			first_exhausted: false,
+		}
+	}
+}
+
+impl ComposeRecoveryPolicy for Reconnect {}
+impl ComposeRecoveryPolicy for Resync {}
+impl ComposeRecoveryPolicy for Failover {}
+impl ComposeRecoveryPolicy for Abort {}
+impl<A, B> ComposeRecoveryPolicy for Fallback<A, B> {}
+impl<P: ?Sized> ComposeRecoveryPolicy for Box<P> {}
+
+#[async_trait]
+impl<C: Send, P: ?Sized + RecoveryPolicy<C>> RecoveryPolicy<C> for Box<P> {
+	fn name(&self) -> String {
+		(**self).name()
+	}
+
+	async fn recover(&mut self, client: &mut C) -> RecoveryResult {
+		(**self).recover(client).await
+	}
+
+	fn reset(&mut self) {
+		(**self).reset()
+	}
+}
+
+/// Reconnect to the same node (`Client::reconnect`).
+///
+/// Unlimited reconnect is the default recovery policy of all relay loops.
+#[derive(Debug, Clone, Default)]
+pub struct Reconnect {
+	max_attempts: Option<u32>,
+	attempts: u32,
+}
+
+impl Reconnect {
+	/// Reconnect until the client is recovered.
+	pub fn unlimited() -> Self {
+		Reconnect {
+			max_attempts: None,
+			attempts: 0,
+		}
+	}
+
+	/// Make at most `max_attempts` reconnect attempts.
+	pub fn with_max_attempts(max_attempts: u32) -> Self {
+		Reconnect {
+			max_attempts: Some(max_attempts),
+			attempts: 0,
+		}
+	}
+}
+
+#[async_trait]
+impl<C: Client> RecoveryPolicy<C> for Reconnect {
+	fn name(&self) -> String {
+		attempts_policy_name("reconnect", self.max_attempts)
+	}
+
+	async fn recover(&mut self, client: &mut C) -> RecoveryResult {
+		if !start_attempt(&mut self.attempts, self.max_attempts) {
+			return RecoveryResult::Exhausted;
+		}
+
+		match client.reconnect().await {
+			Ok(()) => RecoveryResult::Recovered,
+			Err(error) => {
+				log::warn!(target: "bridge", "Failed to reconnect client. Going to retry: {:?}", error);
+				RecoveryResult::Failed
+			}
+		}
+	}
+
+	fn reset(&mut self) {
+		self.attempts = 0;
+	}
+}
+
+/// Reconnect to the same node, dropping all cached client state (`Client::resync`).
+#[derive(Debug, Clone, Default)]
+pub struct Resync {
+	max_attempts: Option<u32>,
+	attempts: u32,
+}
+
+impl Resync {
+	/// Resync until the client is recovered.
+	pub fn unlimited() -> Self {
+		Resync {
+			max_attempts: None,
+			attempts: 0,
+		}
+	}
+
+	/// Make at most `max_attempts` resync attempts.
+	pub fn with_max_attempts(max_attempts: u32) -> Self {
+		Resync {
+			max_attempts: Some(max_attempts),
+			attempts: 0,
+		}
+	}
+}
+
+#[async_trait]
+impl<C: Client> RecoveryPolicy<C> for Resync {
+	fn name(&self) -> String {
+		attempts_policy_name("resync", self.max_attempts)
+	}
+
+	async fn recover(&mut self, client: &mut C) -> RecoveryResult {
+		if !start_attempt(&mut self.attempts, self.max_attempts) {
+			return RecoveryResult::Exhausted;
+		}
+
+		match client.resync().await {
+			Ok(()) => RecoveryResult::Recovered,
+			Err(error) => {
+				log::warn!(target: "bridge", "Failed to resync client. Going to retry: {:?}", error);
+				RecoveryResult::Failed
+			}
+		}
+	}
+
+	fn reset(&mut self) {
+		self.attempts = 0;
+	}
+}
+
+/// Switch to the next node endpoint (`Client::failover`).
+///
+/// The policy is exhausted when the client has no more failover endpoints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Failover;
+
+#[async_trait]
+impl<C: Client> RecoveryPolicy<C> for Failover {
+	fn name(&self) -> String {
+		"failover".into()
+	}
+
+	async fn recover(&mut self, client: &mut C) -> RecoveryResult {
+		match client.failover().await {
+			Ok(true) => RecoveryResult::Recovered,
+			Ok(false) => RecoveryResult::Exhausted,
+			Err(error) => {
+				log::warn!(target: "bridge", "Failed to switch client to failover endpoint: {:?}", error);
+				RecoveryResult::Failed
+			}
+		}
+	}
+}
+
+/// Stop the relay.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Abort;
+
+#[async_trait]
+impl<C: Send> RecoveryPolicy<C> for Abort {
+	fn name(&self) -> String {
+		"abort".into()
+	}
+
+	async fn recover(&mut self, _client: &mut C) -> RecoveryResult {
+		RecoveryResult::Abort
+	}
+}
+
+/// Composite policy that uses `second` policy after `first` policy has exhausted its options.
+#[derive(Debug, Clone)]
+pub struct Fallback<A, B> {
+	first: A,
+	second: B,
+	first_exhausted: bool,
+}
+
+#[async_trait]
+impl<C, A, B> RecoveryPolicy<C> for Fallback<A, B>
+where
+	C: Send,
+	A: RecoveryPolicy<C>,
+	B: RecoveryPolicy<C>,
+{
+	fn name(&self) -> String {
+		format!("{},{}", self.first.name(), self.second.name())
+	}
+
+	async fn recover(&mut self, client: &mut C) -> RecoveryResult {
+		if !self.first_exhausted {
+			match self.first.recover(client).await {
+				RecoveryResult::Exhausted => {
+					log::warn!(
+						target: "bridge",
+						"Client recovery policy {} is exhausted. Switching to {}",
+						self.first.name(),
+						self.second.name(),
+					);
+					self.first_exhausted = true;
+				}
+				result => return result,
+			}
+		}
+
+		self.second.recover(client).await
+	}
+
+	fn reset(&mut self) {
+		self.first.reset();
+		self.second.reset();
+		self.first_exhausted = false;
+	}
+}
+
+/// Recovery policy of relay loop client, selected by the relay user.
+///
+/// Parsed from the comma-separated list of policies, where every next policy is used when the
+/// previous one is exhausted. Names are the same that are exposed in metrics: `reconnect`,
+/// `reconnect(<max-attempts>)`, `resync`, `resync(<max-attempts>)`, `failover` and `abort`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryPolicyParams(Vec<RecoveryPolicyKind>);
+
+/// Single recovery policy in the `RecoveryPolicyParams`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryPolicyKind {
+	/// `Reconnect` policy with optional limit of attempts.
+	Reconnect(Option<u32>),
+	/// `Resync` policy with optional limit of attempts.
+	Resync(Option<u32>),
+	/// `Failover` policy.
+	Failover,
+	/// `Abort` policy.
+	Abort,
+}
+
+impl Default for RecoveryPolicyParams {
+	fn default() -> Self {
+		RecoveryPolicyParams(vec![RecoveryPolicyKind::Reconnect(None)])
+	}
+}
+
+impl RecoveryPolicyParams {
+	/// Build recovery policy of the relay loop client.
+	pub fn build<C: Client>(&self) -> Box<dyn RecoveryPolicy<C>> {
+		let mut policies = self.0.iter().rev().map(|kind| kind.build::<C>());
+		let last = policies.next().unwrap_or_else(|| Box::new(Reconnect::unlimited()));
+		policies.fold(last, |next, policy| Box::new(policy.or_else(next)))
+	}
+}
+
+impl RecoveryPolicyKind {
+	/// Build recovery policy of this kind.
+	fn build<C: Client>(&self) -> Box<dyn RecoveryPolicy<C>> {
+		match *self {
+			RecoveryPolicyKind::Reconnect(None) => Box::new(Reconnect::unlimited()),
+			RecoveryPolicyKind::Reconnect(Some(max_attempts)) => Box::new(Reconnect::with_max_attempts(max_attempts)),
+			RecoveryPolicyKind::Resync(None) => Box::new(Resync::unlimited()),
+			RecoveryPolicyKind::Resync(Some(max_attempts)) => Box::new(Resync::with_max_attempts(max_attempts)),
+			RecoveryPolicyKind::Failover => Box::new(Failover),
+			RecoveryPolicyKind::Abort => Box::new(Abort),
+		}
+	}
+}
+
+impl FromStr for RecoveryPolicyParams {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		s.split(',')
+			.map(|policy| policy.trim().parse())
+			.collect::<Result<Vec<_>, _>>()
+			.map(RecoveryPolicyParams)
+	}
+}
+
+impl FromStr for RecoveryPolicyKind {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.splitn(2, '(');
+		let (name, max_attempts) = match (parts.next(), parts.next()) {
+			(Some(name), Some(max_attempts)) => {
+				let max_attempts = max_attempts
+					.strip_suffix(')')
+					.ok_or_else(|| format!("Expected <POLICY>(<MAX-ATTEMPTS>), got: {}", s))?
+					.parse()
+					.map_err(|err| format!("Unable to decode max attempts of {}: {:?}", name, err))?;
+				(name, Some(max_attempts))
+			}
+			_ => (s, None),
+		};
+
+		match (name, max_attempts) {
+			("reconnect", max_attempts) => Ok(RecoveryPolicyKind::Reconnect(max_attempts)),
+			("resync", max_attempts) => Ok(RecoveryPolicyKind::Resync(max_attempts)),
+			("failover", None) => Ok(RecoveryPolicyKind::Failover),
+			("abort", None) => Ok(RecoveryPolicyKind::Abort),
+			_ => Err(format!("Unknown recovery policy: {}", s)),
+		}
+	}
+}
+
+/// Returns name of the policy with (optionally) limited number of attempts.
+fn attempts_policy_name(name: &str, max_attempts: Option<u32>) -> String {
+	match max_attempts {
+		Some(max_attempts) => format!("{}({})", name, max_attempts),
+		None => name.into(),
+	}
+}
+
+/// Register new recovery attempt. Returns false if no more attempts are allowed.
+fn start_attempt(attempts: &mut u32, max_attempts: Option<u32>) -> bool {
+	if matches!(max_attempts, Some(max_attempts) if *attempts >= max_attempts) {
+		return false;
+	}
+
+	*attempts = attempts.saturating_add(1);
+	true
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::MaybeConnectionError;
+
+	#[derive(Debug)]
+	struct TestError;
+
+	impl MaybeConnectionError for TestError {
+		fn is_connection_error(&self) -> bool {
+			true
+		}
+	}
+
+	#[derive(Debug, Clone, Default)]
+	struct TestClient {
+		reconnect_fails: bool,
+		reconnects: u32,
+		resyncs: u32,
+		failover_endpoints: u32,
+	}
+
+	#[async_trait]
+	impl Client for TestClient {
+		type Error = TestError;
+
+		async fn reconnect(&mut self) -> Result<(), TestError> {
+			self.reconnects += 1;
+			if self.reconnect_fails {
+				Err(TestError)
+			} else {
+				Ok(())
+			}
+		}
+
+		async fn resync(&mut self) -> Result<(), TestError> {
+			self.resyncs += 1;
+			Ok(())
+		}
+
+		async fn failover(&mut self) -> Result<bool, TestError> {
+			if self.failover_endpoints == 0 {
+				return Ok(false);
+			}
+
+			self.failover_endpoints -= 1;
+			Ok(true)
+		}
+	}
+
+	#[test]
+	fn limited_reconnect_is_exhausted() {
+		let mut client = TestClient {
+			reconnect_fails: true,
+			..Default::default()
+		};
+		let mut policy = Reconnect::with_max_attempts(2);
+		async_std::task::block_on(async {
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Failed);
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Failed);
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Exhausted);
+
+			RecoveryPolicy::<TestClient>::reset(&mut policy);
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Failed);
+		});
+		assert_eq!(client.reconnects, 3);
+	}
+
+	#[test]
+	fn fallback_policy_switches_to_next_policy_when_exhausted() {
+		let mut client = TestClient {
+			reconnect_fails: true,
+			failover_endpoints: 1,
+			..Default::default()
+		};
+		let mut policy = Reconnect::with_max_attempts(1).or_else(Failover).or_else(Abort);
+		assert_eq!(
+			RecoveryPolicy::<TestClient>::name(&policy),
+			"reconnect(1),failover,abort"
+		);
+		async_std::task::block_on(async {
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Failed);
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Recovered);
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Abort);
+
+			// after reset, policy starts from the first policy again
+			RecoveryPolicy::<TestClient>::reset(&mut policy);
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Failed);
+		});
+		assert_eq!(client.reconnects, 2);
+	}
+
+	#[test]
+	fn resync_policy_resyncs_client() {
+		let mut client = TestClient::default();
+		async_std::task::block_on(async {
+			assert_eq!(
+				Resync::unlimited().recover(&mut client).await,
+				RecoveryResult::Recovered
+			);
+		});
+		assert_eq!(client.resyncs, 1);
+		assert_eq!(client.reconnects, 0);
+	}
+
+	#[test]
+	fn recovery_policy_params_are_parsed() {
+		assert_eq!(
+			RecoveryPolicyParams::from_str("reconnect"),
+			Ok(RecoveryPolicyParams::default())
+		);
+		assert_eq!(
+			RecoveryPolicyParams::from_str("reconnect(3), resync(1),failover,abort"),
+			Ok(RecoveryPolicyParams(vec![
+				RecoveryPolicyKind::Reconnect(Some(3)),
+				RecoveryPolicyKind::Resync(Some(1)),
+				RecoveryPolicyKind::Failover,
+				RecoveryPolicyKind::Abort,
+			])),
+		);
+
+		assert!(RecoveryPolicyParams::from_str("").is_err());
+		assert!(RecoveryPolicyParams::from_str("reconnect(x)").is_err());
+		assert!(RecoveryPolicyParams::from_str("failover(3)").is_err());
+		assert!(RecoveryPolicyParams::from_str("reconnect,retry").is_err());
+	}
+
+	#[test]
+	fn recovery_policy_is_built_from_params() {
+		let params = RecoveryPolicyParams::from_str("reconnect(1),failover,abort").unwrap();
+		let mut policy = params.build::<TestClient>();
+		assert_eq!(policy.name(), "reconnect(1),failover,abort");
+
+		let mut client = TestClient {
+			reconnect_fails: true,
+			failover_endpoints: 1,
+			..Default::default()
+		};
+		async_std::task::block_on(async {
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Failed);
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Recovered);
+			assert_eq!(policy.recover(&mut client).await, RecoveryResult::Abort);
+		});
+	}
+}
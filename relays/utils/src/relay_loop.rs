@@ -14,15 +14,32 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::metrics::{Metrics, MetricsAddress, MetricsParams, PrometheusError, StandaloneMetrics};
+use crate::metrics::{
+	metric_name, register, Counter, CounterVec, Gauge, GaugeVec, Metrics, MetricsAddress, MetricsParams, Opts,
+	PrometheusError, StandaloneMetrics, U64,
+};
+use crate::recovery::{Reconnect, RecoveryPolicy, RecoveryPolicyParams, RecoveryResult};
 use crate::{FailedClient, MaybeConnectionError};
 
 use async_trait::async_trait;
-use std::{fmt::Debug, future::Future, net::SocketAddr, time::Duration};
+use backoff::{backoff::Backoff, ExponentialBackoff};
+use std::{
+	fmt::Debug,
+	future::Future,
+	net::SocketAddr,
+	time::{Duration, Instant},
+};
 use substrate_prometheus_endpoint::{init_prometheus, Registry};
 
 /// Default pause between reconnect attempts.
 pub const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+/// Maximal pause between reconnect attempts is the initial pause, multiplied by this value.
+pub const MAX_RECONNECT_DELAY_MULTIPLIER: u32 = 30;
+/// Number of consecutive client failures after which the reconnect circuit breaker is opened.
+pub const CIRCUIT_BREAKER_FAILURES_THRESHOLD: u32 = 8;
+/// If clients have been working for this time before failure, they're considered healthy and
+/// the reconnect backoff is reset.
+pub const HEALTHY_CLIENTS_DURATION: Duration = Duration::from_secs(5 * 60);
 
 /// Basic blockchain client from relay perspective.
 #[async_trait]
@@ -32,6 +49,21 @@ pub trait Client: 'static + Clone + Send + Sync {
 
 	/// Try to reconnect to source node.
 	async fn reconnect(&mut self) -> Result<(), Self::Error>;
+
+	/// Try to reconnect to source node, dropping all cached client state.
+	///
+	/// By default it is the same as `reconnect`.
+	async fn resync(&mut self) -> Result<(), Self::Error> {
+		self.reconnect().await
+	}
+
+	/// Try to switch to the next (failover) node endpoint.
+	///
+	/// Returns `Ok(false)` if there are no failover endpoints left. By default, failover
+	/// endpoints are not supported.
+	async fn failover(&mut self) -> Result<bool, Self::Error> {
+		Ok(false)
+	}
 }
 
 /// Returns generic loop that may be customized and started.
@@ -42,6 +74,9 @@ pub fn relay_loop<SC, TC>(source_client: SC, target_client: TC) -> Loop<SC, TC,
 		source_client,
 		target_client,
 		loop_metric: None,
+		reconnect_metrics: None,
+		source_recovery: None,
+		target_recovery: None,
 	}
 }
 
@@ -54,6 +89,9 @@ pub fn relay_metrics(prefix: Option<String>, params: MetricsParams) -> LoopMetri
 			source_client: (),
 			target_client: (),
 			loop_metric: None,
+			reconnect_metrics: None,
+			source_recovery: None,
+			target_recovery: None,
 		},
 		address: params.address,
 		registry: params.registry.unwrap_or_else(|| create_metrics_registry(prefix)),
@@ -69,6 +107,9 @@ pub struct Loop<SC, TC, LM> {
 	source_client: SC,
 	target_client: TC,
 	loop_metric: Option<LM>,
+	reconnect_metrics: Option<ReconnectMetrics>,
+	source_recovery: Option<Box<dyn RecoveryPolicy<SC>>>,
+	target_recovery: Option<Box<dyn RecoveryPolicy<TC>>>,
 }
 
 /// Relay loop metrics builder.
@@ -87,6 +128,33 @@ impl<SC, TC, LM> Loop<SC, TC, LM> {
 		self
 	}
 
+	/// Customize recovery policy of the source client.
+	///
+	/// By default, the relay loop reconnects to the failed client until it is recovered.
+	pub fn source_recovery_policy(mut self, policy: impl 'static + RecoveryPolicy<SC>) -> Self {
+		self.source_recovery = Some(Box::new(policy));
+		self
+	}
+
+	/// Customize recovery policy of the target client.
+	///
+	/// By default, the relay loop reconnects to the failed client until it is recovered.
+	pub fn target_recovery_policy(mut self, policy: impl 'static + RecoveryPolicy<TC>) -> Self {
+		self.target_recovery = Some(Box::new(policy));
+		self
+	}
+
+	/// Customize recovery policies of both clients, using policies selected by the relay user.
+	pub fn recovery_policies(mut self, source: &RecoveryPolicyParams, target: &RecoveryPolicyParams) -> Self
+	where
+		SC: Client,
+		TC: Client,
+	{
+		self.source_recovery = Some(source.build());
+		self.target_recovery = Some(target.build());
+		self
+	}
+
 	/// Set spawn-dedicated-loop-task flag.
 	///
 	/// If `true` (default), separate async task is spawned to run relay loop. This is the default
@@ -100,6 +168,15 @@ impl<SC, TC, LM> Loop<SC, TC, LM> {
 
 	/// Start building loop metrics using given prefix.
 	pub fn with_metrics(self, prefix: Option<String>, params: MetricsParams) -> LoopMetrics<SC, TC, ()> {
+		let registry = params.registry.unwrap_or_else(|| create_metrics_registry(prefix));
+		let reconnect_metrics = match ReconnectMetrics::new(&registry, params.metrics_prefix.as_deref()) {
+			Ok(reconnect_metrics) => Some(reconnect_metrics),
+			Err(error) => {
+				log::warn!(target: "bridge-metrics", "Failed to register reconnect metrics: {:?}", error);
+				None
+			}
+		};
+
 		LoopMetrics {
 			relay_loop: Loop {
 				reconnect_delay: self.reconnect_delay,
@@ -107,9 +184,12 @@ impl<SC, TC, LM> Loop<SC, TC, LM> {
 				source_client: self.source_client,
 				target_client: self.target_client,
 				loop_metric: None,
+				reconnect_metrics,
+				source_recovery: self.source_recovery,
+				target_recovery: self.target_recovery,
 			},
 			address: params.address,
-			registry: params.registry.unwrap_or_else(|| create_metrics_registry(prefix)),
+			registry,
 			metrics_prefix: params.metrics_prefix,
 			loop_metric: None,
 		}
@@ -118,8 +198,10 @@ impl<SC, TC, LM> Loop<SC, TC, LM> {
 	/// Run relay loop.
 	///
 	/// This function represents an outer loop, which in turn calls provided `run_loop` function to do
-	/// actual job. When `run_loop` returns, this outer loop reconnects to failed client (source,
-	/// target or both) and calls `run_loop` again.
+	/// actual job. When `run_loop` returns, this outer loop recovers failed client (source,
+	/// target or both) using its recovery policy and calls `run_loop` again. Recovery attempts are
+	/// made using exponential backoff (see `ReconnectBackoff`). If recovery policy decides to abort,
+	/// the error is returned.
 	pub async fn run<R, F>(mut self, loop_name: String, run_loop: R) -> Result<(), String>
 	where
 		R: 'static + Send + Fn(SC, TC, Option<LM>) -> F,
@@ -131,28 +213,47 @@ impl<SC, TC, LM> Loop<SC, TC, LM> {
 		let spawn_loop_task = self.spawn_loop_task;
 		let run_loop_task = async move {
 			crate::initialize::initialize_loop(loop_name);
+			crate::watchdog::loop_started();
+
+			let mut source_recovery = self.source_recovery.take().unwrap_or_else(default_recovery_policy);
+			let mut target_recovery = self.target_recovery.take().unwrap_or_else(default_recovery_policy);
+			if let Some(ref metrics) = self.reconnect_metrics {
+				metrics.note_recovery_policy("source", &source_recovery.name());
+				metrics.note_recovery_policy("target", &target_recovery.name());
+			}
 
+			let mut reconnect_backoff = ReconnectBackoff::new(self.reconnect_delay);
+			reconnect_backoff.metrics = self.reconnect_metrics.clone();
 			loop {
 				let loop_metric = self.loop_metric.clone();
+				let loop_started_at = Instant::now();
 				let future_result = run_loop(self.source_client.clone(), self.target_client.clone(), loop_metric);
 				let result = future_result.await;
 
 				match result {
 					Ok(()) => break,
 					Err(failed_client) => {
-						reconnect_failed_client(
+						reconnect_backoff.clients_failed(loop_started_at.elapsed());
+						let recovery_result = recover_failed_client(
 							failed_client,
-							self.reconnect_delay,
+							&mut reconnect_backoff,
 							&mut self.source_client,
+							&mut *source_recovery,
 							&mut self.target_client,
+							&mut *target_recovery,
 						)
-						.await
+						.await;
+						if let Err(error) = recovery_result {
+							crate::watchdog::loop_finished();
+							return Err(error);
+						}
 					}
 				}
 
 				log::debug!(target: "bridge", "Restarting relay loop");
 			}
 
+			crate::watchdog::loop_finished();
 			Ok(())
 		};
 
@@ -240,49 +341,280 @@ impl<SC, TC, LM> LoopMetrics<SC, TC, LM> {
 			source_client: self.relay_loop.source_client,
 			target_client: self.relay_loop.target_client,
 			loop_metric: self.loop_metric,
+			reconnect_metrics: self.relay_loop.reconnect_metrics,
+			source_recovery: self.relay_loop.source_recovery,
+			target_recovery: self.relay_loop.target_recovery,
 		})
 	}
 }
 
 /// Deal with the client who has returned connection error.
-pub async fn reconnect_failed_client(
+///
+/// The caller is responsible for registering the failure in the `reconnect_backoff` before
+/// calling this function. Clients are reconnecting until they're recovered.
+pub async fn reconnect_failed_client<SC: Client, TC: Client>(
 	failed_client: FailedClient,
-	reconnect_delay: Duration,
-	source_client: &mut impl Client,
-	target_client: &mut impl Client,
+	reconnect_backoff: &mut ReconnectBackoff,
+	source_client: &mut SC,
+	target_client: &mut TC,
 ) {
+	recover_failed_client(
+		failed_client,
+		reconnect_backoff,
+		source_client,
+		&mut Reconnect::unlimited(),
+		target_client,
+		&mut Reconnect::unlimited(),
+	)
+	.await
+	.expect("unlimited reconnect policy never aborts; qed")
+}
+
+/// Recover the client who has returned connection error, using its recovery policy.
+///
+/// The caller is responsible for registering the failure in the `reconnect_backoff` before
+/// calling this function. Returns error if recovery policy of the failed client has decided to
+/// abort the relay.
+pub async fn recover_failed_client<SC: Client, TC: Client>(
+	failed_client: FailedClient,
+	reconnect_backoff: &mut ReconnectBackoff,
+	source_client: &mut SC,
+	source_recovery: &mut dyn RecoveryPolicy<SC>,
+	target_client: &mut TC,
+	target_recovery: &mut dyn RecoveryPolicy<TC>,
+) -> Result<(), String> {
 	loop {
+		let reconnect_delay = reconnect_backoff.next_reconnect_delay();
 		async_std::task::sleep(reconnect_delay).await;
-		if failed_client == FailedClient::Both || failed_client == FailedClient::Source {
-			match source_client.reconnect().await {
-				Ok(()) => (),
-				Err(error) => {
-					log::warn!(
-						target: "bridge",
-						"Failed to reconnect to source client. Going to retry in {}s: {:?}",
-						reconnect_delay.as_secs(),
-						error,
-					);
-					continue;
-				}
-			}
+		if (failed_client == FailedClient::Both || failed_client == FailedClient::Source)
+			&& !recover_client("source", reconnect_backoff, source_client, source_recovery).await?
+		{
+			continue;
 		}
-		if failed_client == FailedClient::Both || failed_client == FailedClient::Target {
-			match target_client.reconnect().await {
-				Ok(()) => (),
-				Err(error) => {
-					log::warn!(
-						target: "bridge",
-						"Failed to reconnect to target client. Going to retry in {}s: {:?}",
-						reconnect_delay.as_secs(),
-						error,
-					);
-					continue;
-				}
+		if (failed_client == FailedClient::Both || failed_client == FailedClient::Target)
+			&& !recover_client("target", reconnect_backoff, target_client, target_recovery).await?
+		{
+			continue;
+		}
+
+		return Ok(());
+	}
+}
+
+/// Make single recovery attempt. Returns true if client has been recovered.
+async fn recover_client<C: Client>(
+	client_name: &str,
+	reconnect_backoff: &mut ReconnectBackoff,
+	client: &mut C,
+	recovery: &mut dyn RecoveryPolicy<C>,
+) -> Result<bool, String> {
+	let result = recovery.recover(client).await;
+	if let Some(ref metrics) = reconnect_backoff.metrics {
+		metrics.note_recovery(client_name, &recovery.name(), result);
+	}
+
+	match result {
+		RecoveryResult::Recovered => {
+			recovery.reset();
+			Ok(true)
+		}
+		RecoveryResult::Failed => {
+			reconnect_backoff.clients_failed(Duration::from_secs(0));
+			Ok(false)
+		}
+		RecoveryResult::Exhausted => {
+			log::warn!(
+				target: "bridge",
+				"Recovery policy {} of {} client is exhausted. Restarting recovery",
+				recovery.name(),
+				client_name,
+			);
+			recovery.reset();
+			reconnect_backoff.clients_failed(Duration::from_secs(0));
+			Ok(false)
+		}
+		RecoveryResult::Abort => Err(format!(
+			"Recovery policy {} of {} client has aborted the relay",
+			recovery.name(),
+			client_name,
+		)),
+	}
+}
+
+/// Returns default recovery policy of relay loop clients.
+fn default_recovery_policy<C: Client>() -> Box<dyn RecoveryPolicy<C>> {
+	Box::new(Reconnect::unlimited())
+}
+
+/// State of the reconnect circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircuitBreakerState {
+	/// Clients are failing rarely. Reconnects are made after exponentially growing delays.
+	Closed,
+	/// Clients are failing repeatedly. Reconnects are made after maximal delay.
+	Open,
+	/// Reconnect is made after the circuit breaker has been opened. If clients keep working for
+	/// `HEALTHY_CLIENTS_DURATION`, the circuit breaker is closed. Otherwise it is opened again.
+	HalfOpen,
+}
+
+/// Exponential reconnect backoff (with jitter) and reconnect circuit breaker.
+///
+/// Prevents relay from reconnecting to flapping nodes too often.
+pub struct ReconnectBackoff {
+	backoff: ExponentialBackoff,
+	consecutive_failures: u32,
+	state: CircuitBreakerState,
+	metrics: Option<ReconnectMetrics>,
+}
+
+impl Debug for ReconnectBackoff {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		fmt.debug_struct("ReconnectBackoff")
+			.field("current_interval", &self.backoff.current_interval)
+			.field("consecutive_failures", &self.consecutive_failures)
+			.field("state", &self.state)
+			.finish()
+	}
+}
+
+impl ReconnectBackoff {
+	/// Create new reconnect backoff with given initial reconnect delay.
+	pub fn new(reconnect_delay: Duration) -> Self {
+		ReconnectBackoff {
+			backoff: ExponentialBackoff {
+				initial_interval: reconnect_delay,
+				current_interval: reconnect_delay,
+				max_interval: reconnect_delay * MAX_RECONNECT_DELAY_MULTIPLIER,
+				// we do not want relayer to stop
+				max_elapsed_time: None,
+				..Default::default()
+			},
+			consecutive_failures: 0,
+			state: CircuitBreakerState::Closed,
+			metrics: None,
+		}
+	}
+
+	/// Returns current state of the circuit breaker.
+	pub fn state(&self) -> CircuitBreakerState {
+		self.state
+	}
+
+	/// Register clients failure. `working_time` is the time clients have been working since previous
+	/// reconnect.
+	pub fn clients_failed(&mut self, working_time: Duration) {
+		if working_time >= HEALTHY_CLIENTS_DURATION {
+			self.backoff.reset();
+			self.consecutive_failures = 0;
+			self.state = CircuitBreakerState::Closed;
+		}
+
+		self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+		if self.state == CircuitBreakerState::HalfOpen
+			|| self.consecutive_failures >= CIRCUIT_BREAKER_FAILURES_THRESHOLD
+		{
+			if self.state == CircuitBreakerState::Closed {
+				log::warn!(
+					target: "bridge",
+					"Clients have failed {} times in a row. Opening reconnect circuit breaker",
+					self.consecutive_failures,
+				);
 			}
+			self.state = CircuitBreakerState::Open;
 		}
 
-		break;
+		if let Some(ref metrics) = self.metrics {
+			metrics.update(self.state);
+		}
+	}
+
+	/// Returns delay before next reconnect attempt.
+	pub fn next_reconnect_delay(&mut self) -> Duration {
+		if self.state == CircuitBreakerState::Open {
+			self.backoff.current_interval = self.backoff.max_interval;
+			self.state = CircuitBreakerState::HalfOpen;
+		}
+
+		if let Some(ref metrics) = self.metrics {
+			metrics.reconnects.inc();
+			metrics.update(self.state);
+		}
+
+		self.backoff.next_backoff().unwrap_or(self.backoff.max_interval)
+	}
+}
+
+/// Reconnect metrics.
+#[derive(Debug, Clone)]
+pub struct ReconnectMetrics {
+	/// State of the reconnect circuit breaker: 0 if closed, 1 if half-open and 2 if open.
+	circuit_breaker_state: Gauge<U64>,
+	/// Total number of reconnect attempts.
+	reconnects: Counter<U64>,
+	/// Recovery policies of relay loop clients.
+	recovery_policies: GaugeVec<U64>,
+	/// Total number of client recovery attempts.
+	recoveries: CounterVec<U64>,
+}
+
+impl ReconnectMetrics {
+	/// Create and register reconnect metrics.
+	pub fn new(registry: &Registry, prefix: Option<&str>) -> Result<Self, PrometheusError> {
+		Ok(ReconnectMetrics {
+			circuit_breaker_state: register(
+				Gauge::new(
+					metric_name(prefix, "reconnect_circuit_breaker_state"),
+					"State of the reconnect circuit breaker: 0 if closed, 1 if half-open and 2 if open",
+				)?,
+				registry,
+			)?,
+			reconnects: register(
+				Counter::new(metric_name(prefix, "reconnects"), "Total number of reconnect attempts")?,
+				registry,
+			)?,
+			recovery_policies: register(
+				GaugeVec::new(
+					Opts::new(
+						metric_name(prefix, "client_recovery_policy"),
+						"Recovery policies of relay loop clients (value is always 1)",
+					),
+					&["client", "policy"],
+				)?,
+				registry,
+			)?,
+			recoveries: register(
+				CounterVec::new(
+					Opts::new(
+						metric_name(prefix, "client_recoveries"),
+						"Total number of client recovery attempts",
+					),
+					&["client", "policy", "result"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Update recovery policy metric.
+	fn note_recovery_policy(&self, client: &str, policy: &str) {
+		self.recovery_policies.with_label_values(&[client, policy]).set(1);
+	}
+
+	/// Update recovery attempts metric.
+	fn note_recovery(&self, client: &str, policy: &str, result: RecoveryResult) {
+		self.recoveries
+			.with_label_values(&[client, policy, result.as_str()])
+			.inc();
+	}
+
+	/// Update circuit breaker state metric.
+	fn update(&self, state: CircuitBreakerState) {
+		self.circuit_breaker_state.set(match state {
+			CircuitBreakerState::Closed => 0,
+			CircuitBreakerState::HalfOpen => 1,
+			CircuitBreakerState::Open => 2,
+		});
 	}
 }
 
@@ -296,3 +628,87 @@ fn create_metrics_registry(prefix: Option<String>) -> Registry {
 		None => Registry::new(),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::recovery::{Abort, ComposeRecoveryPolicy};
+	use crate::StringifiedMaybeConnectionError;
+
+	#[derive(Debug, Clone)]
+	struct TestClient;
+
+	#[async_trait]
+	impl Client for TestClient {
+		type Error = StringifiedMaybeConnectionError;
+
+		async fn reconnect(&mut self) -> Result<(), Self::Error> {
+			Err(StringifiedMaybeConnectionError::new(true, "failed".into()))
+		}
+	}
+
+	#[test]
+	fn reconnect_delay_grows_exponentially() {
+		let mut backoff = ReconnectBackoff::new(Duration::from_secs(10));
+		backoff.clients_failed(Duration::from_secs(0));
+		let first_delay = backoff.next_reconnect_delay();
+		backoff.clients_failed(Duration::from_secs(0));
+		backoff.next_reconnect_delay();
+		backoff.clients_failed(Duration::from_secs(0));
+		let third_delay = backoff.next_reconnect_delay();
+
+		// with default multiplier (1.5) and randomization factor (0.5), third delay is in [11.25s; 33.75s],
+		// while first delay is in [5s; 15s]
+		assert!(first_delay <= Duration::from_secs(15));
+		assert!(third_delay >= Duration::from_millis(11_250));
+		assert!(third_delay <= Duration::from_millis(33_750));
+	}
+
+	#[test]
+	fn circuit_breaker_opens_after_repeated_failures() {
+		let mut backoff = ReconnectBackoff::new(Duration::from_secs(10));
+		for _ in 0..CIRCUIT_BREAKER_FAILURES_THRESHOLD - 1 {
+			backoff.clients_failed(Duration::from_secs(0));
+			assert_eq!(backoff.state(), CircuitBreakerState::Closed);
+			backoff.next_reconnect_delay();
+		}
+
+		backoff.clients_failed(Duration::from_secs(0));
+		assert_eq!(backoff.state(), CircuitBreakerState::Open);
+
+		// when circuit breaker is open, we're using (randomized) maximal delay
+		assert!(backoff.next_reconnect_delay() >= Duration::from_secs(10) * MAX_RECONNECT_DELAY_MULTIPLIER / 2);
+		assert_eq!(backoff.state(), CircuitBreakerState::HalfOpen);
+
+		// if clients fail right after reconnect, the circuit breaker is opened again
+		backoff.clients_failed(Duration::from_secs(1));
+		assert_eq!(backoff.state(), CircuitBreakerState::Open);
+		backoff.next_reconnect_delay();
+
+		// if clients have been working for a long time, the circuit breaker is closed
+		backoff.clients_failed(HEALTHY_CLIENTS_DURATION);
+		assert_eq!(backoff.state(), CircuitBreakerState::Closed);
+		assert!(backoff.next_reconnect_delay() <= Duration::from_secs(15));
+	}
+
+	#[test]
+	fn recovery_is_aborted_by_recovery_policy() {
+		let mut backoff = ReconnectBackoff::new(Duration::from_millis(1));
+		backoff.clients_failed(Duration::from_secs(0));
+		let result = async_std::task::block_on(recover_failed_client(
+			FailedClient::Target,
+			&mut backoff,
+			&mut TestClient,
+			&mut Reconnect::unlimited(),
+			&mut TestClient,
+			&mut Reconnect::with_max_attempts(2).or_else(Abort),
+		));
+
+		assert_eq!(
+			result,
+			Err("Recovery policy reconnect(2),abort of target client has aborted the relay".into()),
+		);
+		// initial failure + two failed reconnects
+		assert_eq!(backoff.consecutive_failures, 3);
+	}
+}
@@ -0,0 +1,271 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! systemd service notifications.
+//!
+//! If the relay is started by systemd as a `Type=notify` service, it sends `READY=1` notification
+//! once any relay loop has made some progress. If the watchdog is enabled for the service
+//! (`WatchdogSec=`), the relay then periodically sends `WATCHDOG=1` notifications, but only while
+//! every running relay loop keeps making progress. So if some loop has silently deadlocked, systemd
+//! will restart the relay, even though the process itself is alive.
+//!
+//! Loops are reporting progress using `loop_progress` function. The watchdog interval must be
+//! larger than the maximal period without progress that may be considered normal (e.g. reconnect
+//! delays).
+
+use crate::initialize::LOOP_NAME;
+
+use once_cell::sync::Lazy;
+use std::{
+	collections::HashMap,
+	ffi::OsString,
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+/// Environment variable with path of the systemd notifications socket.
+const NOTIFY_SOCKET_ENV: &str = "NOTIFY_SOCKET";
+/// Environment variable with the watchdog interval (in microseconds).
+const WATCHDOG_USEC_ENV: &str = "WATCHDOG_USEC";
+/// Environment variable with the pid of process that is supervised by the watchdog.
+const WATCHDOG_PID_ENV: &str = "WATCHDOG_PID";
+/// Interval between checks of loops progress before the `READY=1` notification is sent.
+const READY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time of the latest progress of every running relay loop.
+static LOOPS_PROGRESS: Lazy<Mutex<HashMap<String, LoopProgress>>> = Lazy::new(Default::default);
+
+/// Progress of the relay loop.
+#[derive(Debug, Clone, Copy)]
+struct LoopProgress {
+	/// Time when loop has been started.
+	started_at: Instant,
+	/// Time of the latest loop progress. `None` if loop has not made any progress yet.
+	progress_at: Option<Instant>,
+}
+
+/// Report progress of the current relay loop.
+///
+/// Must be called from the relay loop task.
+pub fn loop_progress() {
+	let now = Instant::now();
+	update_loops_progress(|loops_progress, loop_name| {
+		loops_progress
+			.entry(loop_name)
+			.or_insert(LoopProgress {
+				started_at: now,
+				progress_at: None,
+			})
+			.progress_at = Some(now);
+	});
+}
+
+/// Start watching progress of the current relay loop.
+pub(crate) fn loop_started() {
+	let now = Instant::now();
+	update_loops_progress(|loops_progress, loop_name| {
+		loops_progress.insert(
+			loop_name,
+			LoopProgress {
+				started_at: now,
+				progress_at: None,
+			},
+		);
+	});
+}
+
+/// Stop watching progress of the current relay loop.
+pub(crate) fn loop_finished() {
+	update_loops_progress(|loops_progress, loop_name| {
+		loops_progress.remove(&loop_name);
+	});
+}
+
+/// Start sending systemd notifications, if the relay is started as systemd service.
+///
+/// Does nothing if the `NOTIFY_SOCKET` environment variable is not set.
+pub fn start() {
+	let socket_path = match std::env::var_os(NOTIFY_SOCKET_ENV) {
+		Some(socket_path) => socket_path,
+		None => return,
+	};
+	let watchdog_interval = watchdog_interval();
+
+	log::info!(
+		target: "bridge",
+		"Going to send systemd notifications. Watchdog interval: {:?}",
+		watchdog_interval,
+	);
+
+	async_std::task::spawn(async move {
+		// relay is ready when any loop has made progress
+		while !any_loop_progressed() {
+			async_std::task::sleep(READY_CHECK_INTERVAL).await;
+		}
+		notify(&socket_path, "READY=1");
+
+		let watchdog_interval = match watchdog_interval {
+			Some(watchdog_interval) => watchdog_interval,
+			None => return,
+		};
+		loop {
+			// systemd recommends to send notifications twice per watchdog interval
+			async_std::task::sleep(watchdog_interval / 2).await;
+
+			let stalled_loops = stalled_loops(Instant::now(), watchdog_interval);
+			if stalled_loops.is_empty() {
+				notify(&socket_path, "WATCHDOG=1");
+			} else {
+				log::error!(
+					target: "bridge",
+					"Relay loops {:?} have made no progress for {}s. Watchdog notification is not sent",
+					stalled_loops,
+					watchdog_interval.as_secs(),
+				);
+			}
+		}
+	});
+}
+
+/// Read watchdog interval from the environment.
+fn watchdog_interval() -> Option<Duration> {
+	// if pid is specified, watchdog is enabled only for given process
+	if let Ok(watchdog_pid) = std::env::var(WATCHDOG_PID_ENV) {
+		if watchdog_pid != std::process::id().to_string() {
+			return None;
+		}
+	}
+
+	std::env::var(WATCHDOG_USEC_ENV)
+		.ok()
+		.and_then(|watchdog_usec| watchdog_usec.parse().ok())
+		.filter(|watchdog_usec| *watchdog_usec != 0)
+		.map(Duration::from_micros)
+}
+
+/// Returns true if any loop has made progress since relay start.
+fn any_loop_progressed() -> bool {
+	LOOPS_PROGRESS
+		.lock()
+		.expect("watchdog mutex is never poisoned; qed")
+		.values()
+		.any(|loop_progress| loop_progress.progress_at.is_some())
+}
+
+/// Returns names of loops that have made no progress for given interval.
+fn stalled_loops(now: Instant, interval: Duration) -> Vec<String> {
+	let mut stalled_loops = LOOPS_PROGRESS
+		.lock()
+		.expect("watchdog mutex is never poisoned; qed")
+		.iter()
+		.filter(|(_, loop_progress)| is_loop_stalled(loop_progress, now, interval))
+		.map(|(loop_name, _)| loop_name.clone())
+		.collect::<Vec<_>>();
+	stalled_loops.sort();
+	stalled_loops
+}
+
+/// Returns true if loop has made no progress for given interval.
+fn is_loop_stalled(loop_progress: &LoopProgress, now: Instant, interval: Duration) -> bool {
+	let last_progress_at = loop_progress.progress_at.unwrap_or(loop_progress.started_at);
+	now.saturating_duration_since(last_progress_at) > interval
+}
+
+/// Update progress of the current relay loop.
+fn update_loops_progress(update: impl FnOnce(&mut HashMap<String, LoopProgress>, String)) {
+	// try_with to avoid panic outside of async-std task context
+	let loop_name = LOOP_NAME
+		.try_with(|loop_name| loop_name.borrow().clone())
+		.unwrap_or_default();
+	update(
+		&mut LOOPS_PROGRESS.lock().expect("watchdog mutex is never poisoned; qed"),
+		loop_name,
+	);
+}
+
+/// Send notification to systemd.
+#[cfg(unix)]
+fn notify(socket_path: &OsString, state: &str) {
+	use std::os::unix::net::UnixDatagram;
+
+	if socket_path.to_string_lossy().starts_with('@') {
+		log::warn!(
+			target: "bridge",
+			"Abstract systemd notification sockets are not supported: {:?}",
+			socket_path,
+		);
+		return;
+	}
+
+	let result = UnixDatagram::unbound().and_then(|socket| socket.send_to(state.as_bytes(), socket_path));
+	if let Err(error) = result {
+		log::warn!(
+			target: "bridge",
+			"Failed to send {} notification to systemd: {:?}",
+			state,
+			error,
+		);
+	}
+}
+
+/// Send notification to systemd.
+#[cfg(not(unix))]
+fn notify(_socket_path: &OsString, state: &str) {
+	log::warn!(
+		target: "bridge",
+		"Systemd notifications are only supported on unix. Not sending {}",
+		state,
+	);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const INTERVAL: Duration = Duration::from_secs(60);
+
+	#[test]
+	fn loop_without_progress_is_stalled_after_interval() {
+		let started_at = Instant::now();
+		let loop_progress = LoopProgress {
+			started_at,
+			progress_at: None,
+		};
+
+		assert!(!is_loop_stalled(&loop_progress, started_at + INTERVAL, INTERVAL));
+		assert!(is_loop_stalled(
+			&loop_progress,
+			started_at + INTERVAL + Duration::from_secs(1),
+			INTERVAL
+		));
+	}
+
+	#[test]
+	fn loop_progress_resets_stall_timer() {
+		let started_at = Instant::now();
+		let loop_progress = LoopProgress {
+			started_at,
+			progress_at: Some(started_at + INTERVAL),
+		};
+
+		assert!(!is_loop_stalled(&loop_progress, started_at + INTERVAL * 2, INTERVAL));
+		assert!(is_loop_stalled(
+			&loop_progress,
+			started_at + INTERVAL * 2 + Duration::from_secs(1),
+			INTERVAL
+		));
+	}
+}